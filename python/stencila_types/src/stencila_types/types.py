@@ -992,6 +992,15 @@ class SuggestionBlock(Suggestion):
     content: list[Block]
     """The content that is suggested to be inserted, modified, replaced, or deleted."""
 
+    prompt_tokens: UnsignedInteger | None = None
+    """The number of tokens in the prompt used to generate the suggestion."""
+
+    completion_tokens: UnsignedInteger | None = None
+    """The number of tokens generated for the suggestion."""
+
+    cost: float | None = None
+    """The estimated cost, in US dollars, of generating the suggestion."""
+
 
 @dataclass(kw_only=True, repr=False)
 class SuggestionInline(Suggestion):
@@ -1167,6 +1176,18 @@ class AuthorRole(Role):
     """Timestamp of most recent modification, by the author, in the role."""
 
 
+@dataclass(kw_only=True, repr=False)
+class Book(CreativeWork):
+    """
+    A book.
+    """
+
+    type: Literal["Book"] = "Book"
+
+    extra: Object | None = None
+    """Additional metadata for the book."""
+
+
 @dataclass(kw_only=True, repr=False)
 class BooleanValidator(Entity):
     """
@@ -1239,6 +1260,27 @@ class CallBlock(IncludeBlock):
     """The value of the source document's parameters to call it with"""
 
 
+@dataclass(kw_only=True, repr=False)
+class Chapter(CreativeWork):
+    """
+    A chapter, or other section, of a book.
+    """
+
+    type: Literal["Chapter"] = "Chapter"
+
+    page_start: int | str | None = None
+    """The page on which the chapter starts; for example "135" or "xiii"."""
+
+    page_end: int | str | None = None
+    """The page on which the chapter ends; for example "138" or "xvi"."""
+
+    pagination: str | None = None
+    """Any description of pages that is not separated into pageStart and pageEnd; for example, "1-6, 9, 55"."""
+
+    extra: Object | None = None
+    """Additional metadata for the chapter."""
+
+
 @dataclass(kw_only=True, repr=False)
 class Chat(CreativeWork, Executable):
     """
@@ -1406,6 +1448,9 @@ class CodeChunk(CodeExecutable):
     is_invisible: bool | None = None
     """Whether the outputs of the code chunk should be invisible to the reader."""
 
+    is_code_hidden: bool | None = None
+    """Whether the code of the code chunk should be hidden from the reader."""
+
     execution_pure: bool | None = None
     """Whether the code should be treated as side-effect free when executed."""
 
@@ -1544,6 +1589,18 @@ class Config(Entity):
     theme: str | None = None
     """The styling theme to use for the document"""
 
+    kernels: list[str] | None = None
+    """The names of the kernels to use for executing code, in order of preference."""
+
+    models: list[str] | None = None
+    """The ids of the models to use for instructions, in order of preference."""
+
+    execution_bounds: ExecutionBounds | None = None
+    """The default bounds on the execution of suggestions and other child nodes."""
+
+    budget: float | None = None
+    """The maximum cost, in US dollars, of model tasks when executing the document."""
+
 
 @dataclass(kw_only=True, repr=False)
 class ConstantValidator(Entity):
@@ -2994,6 +3051,18 @@ class ThematicBreak(Entity):
     type: Literal["ThematicBreak"] = "ThematicBreak"
 
 
+@dataclass(kw_only=True, repr=False)
+class Thesis(CreativeWork):
+    """
+    A thesis or dissertation submitted in support of an academic degree.
+    """
+
+    type: Literal["Thesis"] = "Thesis"
+
+    extra: Object | None = None
+    """Additional metadata for the thesis."""
+
+
 @dataclass(kw_only=True, repr=False)
 class Time(Entity):
     """
@@ -3228,6 +3297,8 @@ Union type in block content node types.
 CreativeWorkType = Union[
     Article,
     AudioObject,
+    Book,
+    Chapter,
     Chat,
     Claim,
     Collection,
@@ -3244,6 +3315,7 @@ CreativeWorkType = Union[
     SoftwareApplication,
     SoftwareSourceCode,
     Table,
+    Thesis,
     VideoObject,
 ]
 """
@@ -3370,11 +3442,13 @@ Node = Union[
     Article,
     AudioObject,
     AuthorRole,
+    Book,
     BooleanValidator,
     Brand,
     Button,
     CallArgument,
     CallBlock,
+    Chapter,
     Chat,
     ChatMessage,
     ChatMessageGroup,
@@ -3485,6 +3559,7 @@ Node = Union[
     TableRow,
     Text,
     ThematicBreak,
+    Thesis,
     Thing,
     Time,
     TimeValidator,
@@ -3507,7 +3582,9 @@ Union type for all types in this schema, including primitives and entities
 ThingType = Union[
     Article,
     AudioObject,
+    Book,
     Brand,
+    Chapter,
     Chat,
     Claim,
     Collection,
@@ -3536,6 +3613,7 @@ ThingType = Union[
     SoftwareApplication,
     SoftwareSourceCode,
     Table,
+    Thesis,
     VideoObject,
 ]
 """
@@ -3591,11 +3669,13 @@ TYPES = [
     Article,
     AudioObject,
     AuthorRole,
+    Book,
     BooleanValidator,
     Brand,
     Button,
     CallArgument,
     CallBlock,
+    Chapter,
     Chat,
     ChatMessage,
     ChatMessageGroup,
@@ -3697,6 +3777,7 @@ TYPES = [
     TableRow,
     Text,
     ThematicBreak,
+    Thesis,
     Time,
     TimeValidator,
     Timestamp,
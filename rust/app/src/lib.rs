@@ -24,6 +24,7 @@ pub enum DirType {
     Prompts,
     Plugins,
     Kernels,
+    Models,
 }
 
 /// Get an application directory
@@ -44,6 +45,14 @@ pub fn get_app_dir(dir_type: DirType, mut ensure: bool) -> Result<PathBuf> {
             }
             DirType::Plugins => dirs.config_dir().join("plugins"),
             DirType::Kernels => dirs.config_dir().join("kernels"),
+            DirType::Models => {
+                if let Ok(dir) = env::var("STENCILA_MODELS_DIR") {
+                    ensure = false;
+                    PathBuf::from(dir)
+                } else {
+                    dirs.config_dir().join("models")
+                }
+            }
         }
     };
 
@@ -0,0 +1,436 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use cli_utils::{
+    table::{self, Attribute, Cell, Color},
+    AsFormat, Code, ToStdout,
+};
+use codecs::{DecodeOptions, EncodeOptions, LossesResponse, MessageLevel};
+use common::{
+    clap::{self, Parser},
+    eyre::{bail, Result},
+    futures::{stream, StreamExt},
+    serde::{Deserialize, Serialize},
+    serde_yaml,
+    strum::Display,
+    tokio, toml, tracing,
+};
+use document::{CommandWait, Document, SaveDocumentSidecar, SaveDocumentSource};
+use format::Format;
+use node_execute::{execution_required_digests, ExecuteOptions};
+use schema::{Block, CompilationDigest, ExecutionRequired, Inline, Visitor, WalkControl, WalkNode};
+
+/// Build many documents using a manifest
+///
+/// Converts, executes and renders each of the documents listed in the
+/// manifest, in parallel. Outputs are only rebuilt if they are missing or
+/// older than their input, or if any of the executable nodes in the
+/// document are stale (i.e. their compilation digest differs from that
+/// when they were last executed).
+///
+/// The manifest is a YAML or TOML file listing each document's `input`,
+/// `outputs` and, optionally, execution `options`. For example,
+///
+/// ```yaml
+/// options:
+///   force_all: false
+/// documents:
+///   - input: report.smd
+///     outputs:
+///       - report.html
+///       - path: report.docx
+///         render: true
+///   - input: notes.md
+///     execute: false
+///     outputs: [notes.ipynb]
+/// ```
+#[derive(Debug, Parser)]
+pub struct Cli {
+    /// The path of the build manifest
+    ///
+    /// Paths within the manifest are relative to its directory.
+    #[arg(default_value = "stencila-build.yaml")]
+    manifest: PathBuf,
+
+    /// Rebuild all documents, and re-execute all nodes within them,
+    /// even if they are not stale
+    #[arg(long, short)]
+    force: bool,
+
+    /// The maximum number of documents to build concurrently
+    #[arg(long, short, default_value_t = 4)]
+    jobs: usize,
+
+    /// Output the build summary as JSON or YAML
+    #[arg(long, short)]
+    r#as: Option<AsFormat>,
+}
+
+/// A build manifest
+#[derive(Debug, Deserialize)]
+#[serde(crate = "common::serde")]
+struct Manifest {
+    /// Execution options for all documents
+    #[serde(default)]
+    options: ExecuteOptions,
+
+    /// The documents to build
+    documents: Vec<Entry>,
+}
+
+/// A document within a build manifest
+#[derive(Debug, Deserialize)]
+#[serde(crate = "common::serde")]
+struct Entry {
+    /// The path of the input document
+    input: PathBuf,
+
+    /// The format of the input document, if it can not be inferred from its extension
+    from: Option<String>,
+
+    /// The outputs to render the document to
+    #[serde(default)]
+    outputs: Vec<Output>,
+
+    /// Whether to execute the document before rendering it
+    #[serde(default = "default_true")]
+    execute: bool,
+
+    /// Execution options which override those for the whole manifest
+    options: Option<ExecuteOptions>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// An output of a document within a build manifest
+#[derive(Debug, Deserialize)]
+#[serde(untagged, crate = "common::serde")]
+enum Output {
+    /// Just the path, with format inferred from its extension
+    Path(PathBuf),
+
+    /// The path and encoding options
+    Spec {
+        path: PathBuf,
+        to: Option<String>,
+        render: Option<bool>,
+        standalone: Option<bool>,
+    },
+}
+
+impl Output {
+    fn path(&self) -> &Path {
+        match self {
+            Output::Path(path) | Output::Spec { path, .. } => path,
+        }
+    }
+}
+
+/// The status of the build of a document
+#[derive(Debug, Display, Serialize)]
+#[serde(rename_all = "kebab-case", crate = "common::serde")]
+#[strum(serialize_all = "kebab-case", crate = "common::strum")]
+enum Status {
+    Succeeded,
+    Warnings,
+    Failed,
+    UpToDate,
+}
+
+/// A summary of the build of a document
+#[derive(Debug, Serialize)]
+#[serde(crate = "common::serde")]
+struct Summary {
+    input: PathBuf,
+    status: Status,
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<()> {
+        let content = tokio::fs::read_to_string(&self.manifest).await?;
+        let manifest: Manifest = match Format::from_path(&self.manifest) {
+            Format::Toml => toml::from_str(&content)?,
+            _ => serde_yaml::from_str(&content)?,
+        };
+
+        let dir = self
+            .manifest
+            .canonicalize()?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        let options = &manifest.options;
+        let force = self.force;
+        let summaries: Vec<Summary> = stream::iter(manifest.documents)
+            .map(|entry| {
+                let dir = dir.clone();
+                async move {
+                    let input = entry.input.clone();
+                    match build(&dir, entry, options, force).await {
+                        Ok(summary) => summary,
+                        Err(error) => Summary {
+                            input,
+                            status: Status::Failed,
+                            warnings: Vec::new(),
+                            error: Some(error.to_string()),
+                        },
+                    }
+                }
+            })
+            .buffered(self.jobs.max(1))
+            .collect()
+            .await;
+
+        let failed = summaries
+            .iter()
+            .filter(|summary| matches!(summary.status, Status::Failed))
+            .count();
+
+        if let Some(format) = self.r#as {
+            Code::new_from(format.into(), &summaries)?.to_stdout();
+        } else {
+            let mut table = table::new();
+            table.set_header(["Input", "Status", "Warnings", "Details"]);
+            for summary in summaries {
+                let color = match summary.status {
+                    Status::Succeeded => Color::Green,
+                    Status::Warnings => Color::Yellow,
+                    Status::Failed => Color::Red,
+                    Status::UpToDate => Color::Blue,
+                };
+                let details = match summary.error {
+                    Some(error) => error,
+                    None => summary.warnings.join("\n"),
+                };
+                table.add_row([
+                    Cell::new(summary.input.display()).add_attribute(Attribute::Bold),
+                    Cell::new(summary.status).fg(color),
+                    Cell::new(summary.warnings.len()),
+                    Cell::new(details),
+                ]);
+            }
+            table.to_stdout();
+        }
+
+        if failed > 0 {
+            bail!("{failed} document/s failed to build")
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a single document in the manifest
+async fn build(dir: &Path, entry: Entry, options: &ExecuteOptions, force: bool) -> Result<Summary> {
+    let input = dir.join(&entry.input);
+    if !input.exists() {
+        bail!("File does not exist: {}", input.display())
+    }
+    let input_modified = modification_time(&input);
+
+    // Outputs are out of date if missing, or older than the input
+    let mut stale = force
+        || entry.outputs.iter().any(|output| {
+            let path = dir.join(output.path());
+            !path.exists() || modification_time(&path) < input_modified
+        });
+
+    let mut warnings = Vec::new();
+
+    // Initialize the document from its sidecar (if any) and merge in the
+    // input if it has been modified since the sidecar was last written
+    let home = input.parent().map(Path::to_path_buf).unwrap_or_default();
+    let doc = Document::init(home, Some(input.clone()), None)?;
+    let sidecar = Document::sidecar_path(&input);
+    if !sidecar.exists() || input_modified > modification_time(&sidecar) {
+        let format = entry.from.as_deref().map(Format::from_name);
+        let decode_options = DecodeOptions {
+            format: format.clone(),
+            losses: LossesResponse::Debug,
+            ..Default::default()
+        };
+        let (node, info) = codecs::from_path_with_info(&input, Some(decode_options)).await?;
+        for message in info.messages.iter() {
+            if matches!(message.level, MessageLevel::Warning | MessageLevel::Error) {
+                warnings.push(format!("Decoding: {}", message.message));
+            }
+        }
+        for (loss, count) in info.losses.iter() {
+            warnings.push(format!("Decoding loss: {loss} ({count})"));
+        }
+        doc.merge(
+            node,
+            format.or_else(|| Some(Format::from_path(&input))),
+            None,
+        )
+        .await?;
+    }
+
+    // Compile the document to update the compilation digests of executable
+    // nodes and so determine whether any need to be re-executed
+    doc.compile(CommandWait::Yes).await?;
+    if entry.execute && !stale {
+        stale = doc.inspect(count_stale).await > 0;
+    }
+
+    if !stale {
+        return Ok(Summary {
+            input: entry.input,
+            status: Status::UpToDate,
+            warnings,
+            error: None,
+        });
+    }
+
+    if entry.execute {
+        let options = ExecuteOptions {
+            force_all: force || options.force_all,
+            ..entry.options.unwrap_or_else(|| options.clone())
+        };
+        doc.execute(options, CommandWait::Yes).await?;
+    }
+
+    let root = doc.root().await;
+    for output in &entry.outputs {
+        let path = dir.join(output.path());
+        let (format, render, standalone) = match output {
+            Output::Path(..) => (None, None, None),
+            Output::Spec {
+                to,
+                render,
+                standalone,
+                ..
+            } => (to.as_deref().map(Format::from_name), *render, *standalone),
+        };
+        let encode_options = EncodeOptions {
+            format,
+            render,
+            standalone,
+            from_path: Some(input.clone()),
+            losses: LossesResponse::Debug,
+            ..Default::default()
+        };
+
+        let info = codecs::to_path_with_info(&root, &path, Some(encode_options)).await?;
+        for message in info.messages.iter() {
+            if matches!(message.level, MessageLevel::Warning | MessageLevel::Error) {
+                warnings.push(format!(
+                    "Encoding `{}`: {}",
+                    output.path().display(),
+                    message.message
+                ));
+            }
+        }
+        for (loss, count) in info.losses.iter() {
+            warnings.push(format!(
+                "Encoding `{}` loss: {loss} ({count})",
+                output.path().display()
+            ));
+        }
+    }
+
+    // Save the sidecar so that execution digests are available for the next build
+    doc.save_with(
+        CommandWait::Yes,
+        SaveDocumentSource::No,
+        SaveDocumentSidecar::Yes,
+    )
+    .await?;
+
+    tracing::debug!("Built `{}`", input.display());
+
+    Ok(Summary {
+        input: entry.input,
+        status: if warnings.is_empty() {
+            Status::Succeeded
+        } else {
+            Status::Warnings
+        },
+        warnings,
+        error: None,
+    })
+}
+
+/// Get the last modification time of a file, if available
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Count the number of executable nodes which require execution
+///
+/// Compares the compilation digest of each code chunk, code expression, and
+/// `for` and `if` block (updated when the document is compiled) with its execution
+/// digest (the compilation digest when it was last executed). Nodes that have not
+/// been compiled are counted as stale.
+fn count_stale<T: WalkNode>(node: &T) -> usize {
+    let mut counter = StaleCounter::default();
+    counter.visit(node);
+    counter.count
+}
+
+/// A visitor which counts executable nodes which require execution
+#[derive(Default)]
+struct StaleCounter {
+    count: usize,
+}
+
+impl StaleCounter {
+    /// Count a node as stale if its digests require it to be executed
+    fn digests(
+        &mut self,
+        compilation_digest: &Option<CompilationDigest>,
+        execution_digest: &Option<CompilationDigest>,
+    ) {
+        let stale = match compilation_digest {
+            Some(compilation_digest) => !matches!(
+                execution_required_digests(execution_digest, compilation_digest),
+                ExecutionRequired::No
+            ),
+            None => true,
+        };
+        self.count += stale as usize;
+    }
+}
+
+impl Visitor for StaleCounter {
+    fn visit_block(&mut self, block: &Block) -> WalkControl {
+        macro_rules! digests {
+            ($node:expr) => {
+                self.digests(
+                    &$node.options.compilation_digest,
+                    &$node.options.execution_digest,
+                )
+            };
+        }
+
+        match block {
+            Block::CodeChunk(node) => digests!(node),
+            Block::ForBlock(node) => digests!(node),
+            Block::IfBlock(node) => digests!(node),
+            _ => {}
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &Inline) -> WalkControl {
+        if let Inline::CodeExpression(node) = inline {
+            self.digests(
+                &node.options.compilation_digest,
+                &node.options.execution_digest,
+            )
+        }
+
+        WalkControl::Continue
+    }
+}
@@ -1,6 +1,5 @@
-use app::DirType;
 use common::{
-    clap::{self, Args, Parser, Subcommand},
+    clap::{self, Parser, Subcommand},
     eyre::{bail, Result},
     tracing,
 };
@@ -8,9 +7,9 @@ use server::{self, ServeOptions};
 use version::STENCILA_VERSION;
 
 use crate::{
-    compile, convert, execute,
+    build, compile, config, convert, execute,
     logging::{LoggingFormat, LoggingLevel},
-    new, preview, render, sync, uninstall, upgrade,
+    new, preview, render, sync, uninstall, upgrade, watch,
 };
 
 /// CLI subcommands and global options
@@ -104,6 +103,8 @@ pub enum Command {
     Compile(compile::Cli),
     Execute(execute::Cli),
     Render(render::Cli),
+    Watch(watch::Cli),
+    Build(build::Cli),
 
     Preview(preview::Cli),
     Publish(publish::Cli),
@@ -112,7 +113,7 @@ pub enum Command {
     /// Run the Language Server Protocol server
     Lsp,
 
-    Prompts(prompts::cli::Cli),
+    Prompts(prompts_eval::cli::Cli),
     Models(models::cli::Cli),
     Kernels(kernels::cli::Cli),
     Codecs(codecs::cli::Cli),
@@ -122,16 +123,7 @@ pub enum Command {
     Upgrade(upgrade::Cli),
     Uninstall(uninstall::Cli),
 
-    Config(ConfigOptions),
-}
-
-#[derive(Debug, Args)]
-pub struct ConfigOptions {
-    #[arg(long, default_value = "config")]
-    dir: DirType,
-
-    #[arg(long)]
-    ensure: bool,
+    Config(config::Cli),
 }
 
 impl Cli {
@@ -153,6 +145,8 @@ impl Cli {
             Command::Compile(compile) => compile.run().await?,
             Command::Execute(execute) => execute.run().await?,
             Command::Render(render) => render.run().await?,
+            Command::Watch(watch) => watch.run().await?,
+            Command::Build(build) => build.run().await?,
 
             Command::Preview(preview) => preview.run().await?,
             Command::Publish(publish) => publish.run().await?,
@@ -169,11 +163,7 @@ impl Cli {
             Command::Upgrade(upgrade) => upgrade.run().await?,
             Command::Uninstall(uninstall) => uninstall.run()?,
 
-            Command::Config(options) => {
-                // TODO: Make options.dir an option, and if it not there, show all folders.
-                let dir = app::get_app_dir(options.dir, options.ensure)?;
-                println!("{}", dir.display());
-            }
+            Command::Config(config) => config.run().await?,
 
             // Handled before this function
             Command::Lsp => bail!("The LSP command should already been run"),
@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use app::DirType;
+use cli_utils::{
+    table::{self, Attribute, Cell, Color},
+    AsFormat, Code, ToStdout,
+};
+use common::{
+    clap::{self, Args, Parser, Subcommand},
+    eyre::Result,
+    indexmap::IndexMap,
+    serde_json::{self, json, Value},
+};
+use document::{resolve_config, ConfigSource, Document};
+use format::Format;
+
+/// Manage configuration
+///
+/// With no subcommand, prints the path of the Stencila config
+/// directory (or other application directory, see `--dir`).
+#[derive(Debug, Parser)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// The application directory to print the path of
+    #[arg(long, default_value = "config")]
+    dir: DirType,
+
+    /// Create the application directory if it does not yet exist
+    #[arg(long)]
+    ensure: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    Show(Show),
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<()> {
+        let Some(command) = self.command else {
+            let dir = app::get_app_dir(self.dir, self.ensure)?;
+            println!("{}", dir.display());
+            return Ok(());
+        };
+
+        match command {
+            Command::Show(show) => show.run().await,
+        }
+    }
+}
+
+/// Show the configuration for a document or directory
+///
+/// By default, only shows the configuration defined in the document
+/// itself (e.g. in its YAML front-matter), or in the config file in the
+/// directory. Use `--resolved` to show the configuration resolved from
+/// user, workspace and document sources, and where each value came from.
+#[derive(Debug, Args)]
+struct Show {
+    /// The path of the document or directory
+    ///
+    /// Defaults to the current directory.
+    path: Option<PathBuf>,
+
+    /// Show the resolved configuration and the source of each value
+    #[arg(long)]
+    resolved: bool,
+
+    /// Output the configuration as JSON or YAML
+    #[arg(long, short)]
+    r#as: Option<AsFormat>,
+}
+
+impl Show {
+    async fn run(self) -> Result<()> {
+        let path = match self.path {
+            Some(path) => path,
+            None => std::env::current_dir()?,
+        };
+
+        let (config, sources) = if path.is_dir() {
+            resolve_config(&path, None).await?
+        } else {
+            Document::open(&path).await?.config_resolved().await?
+        };
+
+        let Value::Object(mut values) = serde_json::to_value(config)? else {
+            return Ok(());
+        };
+
+        let mut entries: IndexMap<String, (Value, ConfigSource)> = IndexMap::new();
+        for (property, source) in sources {
+            if !(self.resolved || is_local(&path, &source)) {
+                continue;
+            }
+            if let Some(value) = values.remove(&property) {
+                entries.insert(property, (value, source));
+            }
+        }
+
+        if !self.resolved {
+            let values = entries
+                .into_iter()
+                .map(|(property, (value, ..))| (property, value))
+                .collect::<serde_json::Map<_, _>>();
+
+            let format = self.r#as.map_or(Format::Yaml, Format::from);
+            Code::new_from(format, &values)?.to_stdout();
+
+            return Ok(());
+        }
+
+        if let Some(format) = self.r#as {
+            let values = entries
+                .into_iter()
+                .map(|(property, (value, source))| {
+                    (property, json!({ "value": value, "source": source }))
+                })
+                .collect::<serde_json::Map<_, _>>();
+
+            Code::new_from(format.into(), &values)?.to_stdout();
+
+            return Ok(());
+        }
+
+        let mut table = table::new();
+        table.set_header(["Property", "Value", "Source"]);
+        for (property, (value, source)) in entries {
+            let value = match value {
+                Value::String(string) => string,
+                value => value.to_string(),
+            };
+            let color = match source {
+                ConfigSource::User(..) => Color::Blue,
+                ConfigSource::Workspace(..) => Color::Green,
+                ConfigSource::Document => Color::Cyan,
+            };
+            table.add_row([
+                Cell::new(property).add_attribute(Attribute::Bold),
+                Cell::new(value),
+                Cell::new(source).fg(color),
+            ]);
+        }
+        table.to_stdout();
+
+        Ok(())
+    }
+}
+
+/// Whether a config source is local to a document or directory path
+///
+/// For a document, only configuration in the document itself is local.
+/// For a directory, only configuration in its config file is local.
+fn is_local(path: &Path, source: &ConfigSource) -> bool {
+    match source {
+        ConfigSource::Document => true,
+        ConfigSource::Workspace(file) => {
+            path.is_dir() && file.parent() == path.canonicalize().ok().as_deref()
+        }
+        ConfigSource::User(..) => false,
+    }
+}
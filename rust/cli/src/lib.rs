@@ -3,7 +3,9 @@
 mod cli;
 pub use crate::cli::{Cli, Command};
 
+mod build;
 mod compile;
+mod config;
 mod convert;
 pub mod errors;
 mod execute;
@@ -15,3 +17,4 @@ mod render;
 mod sync;
 mod uninstall;
 pub mod upgrade;
+mod watch;
@@ -84,6 +84,14 @@ pub struct EncodeOptions {
     /// which are supported by some formats (e.g. JSON, HTML).
     #[arg(long, short, conflicts_with = "compact")]
     pretty: bool,
+
+    /// The engine to use when encoding to PDF
+    ///
+    /// Defaults to using Pandoc with LaTeX. Use `typst` to use the built-in
+    /// Typst engine which does not require any external tools. Other values
+    /// (e.g. `xelatex`) are passed on to Pandoc as its PDF engine.
+    #[arg(long)]
+    pdf_engine: Option<String>,
 }
 
 impl EncodeOptions {
@@ -136,6 +144,7 @@ impl EncodeOptions {
             strip_props: strip_options.strip_props,
             losses,
             passthrough_args,
+            pdf_engine: self.pdf_engine.clone(),
             ..Default::default()
         }
     }
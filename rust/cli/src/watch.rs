@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use codecs::LossesResponse;
+use common::{
+    clap::{self, Parser},
+    eyre::Result,
+    tokio, tracing,
+};
+use document::{CommandWait, Document, ScheduleOptions};
+use format::Format;
+use node_execute::ExecuteOptions;
+
+use crate::options::{EncodeOptions, StripOptions};
+
+/// Re-execute a document on a schedule or when files change
+///
+/// Executes the document each time the `--cron` schedule is due, or
+/// one of the files it depends on changes, and renders it to each
+/// of the `outputs`. Runs until interrupted (e.g. with `Ctrl+C`).
+#[derive(Debug, Parser)]
+pub struct Cli {
+    /// The path of the document to execute
+    input: PathBuf,
+
+    /// The paths to render the document to after each execution
+    outputs: Vec<PathBuf>,
+
+    /// A cron expression for when to re-execute the document
+    ///
+    /// Standard five field expressions are supported, as are six field
+    /// expressions with a leading seconds field. For example, `0 * * * *`
+    /// re-executes at the start of every hour.
+    #[arg(long)]
+    cron: Option<String>,
+
+    /// Do not re-execute when the files that the document depends on change
+    ///
+    /// By default, the document is re-executed when any of the files in
+    /// its execution dependency graph change (e.g. the source of an include
+    /// block, or a CSV file declared using a `@reads` tag in a code chunk).
+    /// Use this flag to only re-execute on the `--cron` schedule or when
+    /// one of the `--watch` paths change.
+    #[arg(long)]
+    no_dependencies: bool,
+
+    /// Additional files or directories to watch for changes
+    #[arg(long = "watch", short)]
+    paths: Vec<PathBuf>,
+
+    /// Execute and render the document before starting to watch
+    #[arg(long)]
+    now: bool,
+
+    #[clap(flatten)]
+    execute_options: ExecuteOptions,
+
+    #[command(flatten)]
+    encode_options: EncodeOptions,
+
+    #[command(flatten)]
+    strip_options: StripOptions,
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<()> {
+        let Self {
+            input,
+            outputs,
+            cron,
+            no_dependencies,
+            paths,
+            now,
+            execute_options,
+            encode_options,
+            strip_options,
+        } = self;
+
+        let outputs = outputs
+            .into_iter()
+            .map(|output| {
+                let mut options = encode_options.build(
+                    Some(input.as_ref()),
+                    Some(output.as_ref()),
+                    None,
+                    Format::Html,
+                    strip_options.clone(),
+                    LossesResponse::Debug,
+                    Vec::new(),
+                );
+                options.render = Some(true);
+                (output, Some(options))
+            })
+            .collect::<Vec<_>>();
+
+        let doc = Document::open(&input).await?;
+        doc.compile(CommandWait::Yes).await?;
+
+        if now {
+            doc.execute(execute_options.clone(), CommandWait::Yes)
+                .await?;
+            for (path, options) in &outputs {
+                doc.export(path, options.clone()).await?;
+            }
+        }
+
+        let options = ScheduleOptions {
+            cron,
+            dependencies: !no_dependencies,
+            paths,
+            execute_options,
+            outputs,
+        };
+
+        tokio::select! {
+            result = doc.schedule(options) => result,
+            _ = tokio::signal::ctrl_c() => {
+                tracing::info!("Stopped watching `{}`", input.display());
+                Ok(())
+            }
+        }
+    }
+}
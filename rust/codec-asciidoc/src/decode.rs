@@ -0,0 +1,1361 @@
+use std::str::FromStr;
+
+use codec::{
+    common::{eyre::Result, indexmap::IndexMap, itertools::Itertools},
+    schema::{
+        shortcuts::{cb, cc, em, img, lnk, mb, mi, p, qb, stg, sub, sup, t, tb},
+        Admonition, AdmonitionType, Article, Author, Block, CodeInline, Date, Figure, Heading,
+        IncludeBlock, Inline, List, ListItem, ListOrder, Node, Object, Person, Primitive,
+        StringOrNumber, Table, TableCell, TableCellType, TableRow, TableRowType,
+    },
+    DecodeInfo, DecodeOptions, Losses,
+};
+
+/// Decode AsciiDoc to a Stencila [`Node`]
+///
+/// A line-based parser for the parts of AsciiDoc most used in technical
+/// documentation: the document header, section titles, paragraphs (including
+/// admonition paragraphs), lists, delimited blocks, tables, images and
+/// includes. Source blocks with the `exec` option (e.g. `[source%exec,python]`)
+/// are decoded as executable code chunks. Document attributes are substituted
+/// into text, and those which do not correspond to an article property are
+/// kept in the article's `extra` so that they can be encoded again.
+pub(super) fn decode(adoc: &str, _options: Option<DecodeOptions>) -> Result<(Node, DecodeInfo)> {
+    let lines = adoc
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect_vec();
+
+    let mut context = Context::default();
+    let (mut article, start) = context.header(&lines);
+    article.content = context.blocks(&lines[start..]);
+    context.article_attributes(&mut article);
+
+    let info = DecodeInfo {
+        losses: context.losses,
+        ..Default::default()
+    };
+
+    Ok((Node::Article(article), info))
+}
+
+#[derive(Default)]
+struct Context {
+    /// Document attributes, in the order that they were defined
+    attributes: IndexMap<String, String>,
+
+    /// Losses when decoding
+    losses: Losses,
+}
+
+/// The characters which can be escaped with a backslash
+const ESCAPABLE: &str = "\\*_`#^~{[<+|";
+
+/// The AsciiDoc admonition styles and their corresponding admonition type
+fn admonition_type(style: &str) -> Option<AdmonitionType> {
+    Some(match style {
+        "NOTE" => AdmonitionType::Note,
+        "TIP" => AdmonitionType::Tip,
+        "IMPORTANT" => AdmonitionType::Important,
+        "WARNING" => AdmonitionType::Warning,
+        "CAUTION" => AdmonitionType::Danger,
+        _ => return None,
+    })
+}
+
+/// Get the value of a built-in character replacement attribute
+fn builtin_attribute(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "empty" => "",
+        "sp" => " ",
+        "nbsp" => "\u{a0}",
+        "zwsp" => "\u{200b}",
+        "amp" => "&",
+        "lt" => "<",
+        "gt" => ">",
+        "apos" => "'",
+        "quot" => "\"",
+        "plus" => "+",
+        "vbar" => "|",
+        "startsb" => "[",
+        "endsb" => "]",
+        "caret" => "^",
+        "tilde" => "~",
+        "asterisk" => "*",
+        "backslash" => "\\",
+        "backtick" => "`",
+        _ => return None,
+    })
+}
+
+/// Parse an attribute entry e.g. `:toc: left`
+///
+/// Returns the name (which may have a leading or trailing `!` if the
+/// attribute is being unset) and the value.
+fn attribute_entry(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.strip_prefix(':')?.split_once(':')?;
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|char| char.is_alphanumeric() || matches!(char, '-' | '_' | '!'))
+    {
+        return None;
+    }
+    if !value.is_empty() && !value.starts_with(' ') {
+        return None;
+    }
+    Some((name, value.trim()))
+}
+
+/// Is a line a single line comment?
+fn is_line_comment(line: &str) -> bool {
+    line.starts_with("//") && !line.starts_with("///")
+}
+
+/// Is a line a block title e.g. `.Example`?
+fn is_block_title(line: &str) -> bool {
+    line.strip_prefix('.').map_or(false, |title| {
+        title
+            .chars()
+            .next()
+            .map_or(false, |char| !char.is_whitespace() && char != '.')
+    })
+}
+
+/// Is a line a block attribute list e.g. `[source,python]` or an anchor e.g. `[[intro]]`?
+fn is_block_attributes(line: &str) -> bool {
+    line.len() > 2 && line.starts_with('[') && line.ends_with(']')
+}
+
+/// If a line is the delimiter of a delimited block, return it
+fn delimiter(line: &str) -> Option<&str> {
+    if line == "--" {
+        return Some(line);
+    }
+    if let Some(rest) = line.strip_prefix('|') {
+        return (rest.len() >= 3 && rest.chars().all(|char| char == '=')).then_some(line);
+    }
+    let first = line.chars().next()?;
+    (matches!(first, '-' | '.' | '_' | '=' | '*' | '+' | '/')
+        && line.len() >= 4
+        && line.chars().all(|char| char == first))
+    .then_some(line)
+}
+
+/// Get the level of a section title and its text e.g. `== Introduction`
+fn section_title(line: &str) -> Option<(i64, &str)> {
+    let level = line.chars().take_while(|char| *char == '=').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let title = line[level..].strip_prefix(' ')?.trim();
+    (!title.is_empty()).then_some(((level as i64 - 1).max(1), title))
+}
+
+/// Get the order and depth of a list item marker, and the text after it
+fn list_marker(line: &str) -> Option<(ListOrder, usize, &str)> {
+    let line = line.trim_start();
+    let count = |marker: char| line.chars().take_while(|char| *char == marker).count();
+
+    let (order, depth, rest) = if line.starts_with('*') {
+        let depth = count('*');
+        (ListOrder::Unordered, depth, &line[depth..])
+    } else if line.starts_with("- ") {
+        (ListOrder::Unordered, 1, &line[1..])
+    } else if line.starts_with('.') {
+        let depth = count('.');
+        (ListOrder::Ascending, depth, &line[depth..])
+    } else {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        let rest = line[digits..].strip_prefix('.').filter(|_| digits > 0)?;
+        (ListOrder::Ascending, 1, rest)
+    };
+
+    let text = rest.strip_prefix(' ')?.trim();
+    (depth <= 5 && !text.is_empty()).then_some((order, depth, text))
+}
+
+/// Split a list of attributes on commas which are not within double quotes
+fn split_attributes(list: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for char in list.chars() {
+        match char {
+            '"' => {
+                quoted = !quoted;
+                current.push(char);
+            }
+            ',' if !quoted => items.push(std::mem::take(&mut current)),
+            _ => current.push(char),
+        }
+    }
+    items.push(current);
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .collect()
+}
+
+/// The attributes of a block e.g. `[source%exec,python]`, `[#intro.lead]`
+#[derive(Default)]
+struct Attributes {
+    /// The block style (the first positional attribute) e.g. `source`, `NOTE`
+    style: Option<String>,
+
+    /// Other positional attributes e.g. the language of a source block
+    positional: Vec<String>,
+
+    /// Named attributes e.g. `cols="1,2"`
+    named: IndexMap<String, String>,
+
+    /// The id of the block
+    id: Option<String>,
+
+    /// The options of the block e.g. `header`, `exec`
+    options: Vec<String>,
+}
+
+impl Attributes {
+    /// Parse a block attribute line and merge it into these attributes
+    fn merge(&mut self, line: &str) {
+        // Block anchor e.g. `[[intro]]` or `[[intro,Introduction]]`
+        if let Some(anchor) = line
+            .strip_prefix("[[")
+            .and_then(|line| line.strip_suffix("]]"))
+        {
+            let id = anchor.split(',').next().unwrap_or_default().trim();
+            self.id = Some(id.to_string());
+            return;
+        }
+
+        let list = &line[1..line.len() - 1];
+        for (index, item) in split_attributes(list).into_iter().enumerate() {
+            if let Some((name, value)) = item.split_once('=') {
+                let value = value.trim().trim_matches('"').to_string();
+                match name.trim() {
+                    "id" => self.id = Some(value),
+                    "opts" | "options" => self
+                        .options
+                        .extend(value.split(',').map(|option| option.trim().to_string())),
+                    name => {
+                        self.named.insert(name.to_string(), value);
+                    }
+                }
+            } else if index == 0 {
+                self.shorthands(&item);
+            } else {
+                self.positional.push(item.trim_matches('"').to_string());
+            }
+        }
+    }
+
+    /// Parse the first positional attribute which may have shorthands
+    /// for the id, roles and options e.g. `source#example.lead%exec`
+    fn shorthands(&mut self, item: &str) {
+        let mut kind = ' ';
+        let mut current = String::new();
+        for char in item.chars().chain(['\0']) {
+            if matches!(char, '#' | '.' | '%' | '\0') {
+                let value = std::mem::take(&mut current);
+                match kind {
+                    ' ' if !value.is_empty() => self.style = Some(value),
+                    '#' => self.id = Some(value),
+                    '%' => self.options.push(value),
+                    _ => {}
+                }
+                kind = char;
+            } else {
+                current.push(char);
+            }
+        }
+    }
+
+    /// Get the style of the block
+    fn style(&self) -> &str {
+        self.style.as_deref().unwrap_or_default()
+    }
+
+    /// Does the block have an option?
+    fn has_option(&self, option: &str) -> bool {
+        self.options.iter().any(|name| name == option)
+    }
+}
+
+impl Context {
+    /// Decode the document header
+    ///
+    /// Returns an article with the title and authors from the header, and
+    /// the index of the first line after the header.
+    fn header(&mut self, lines: &[String]) -> (Article, usize) {
+        let mut article = Article::default();
+
+        let is_header_line =
+            |line: &&String| !line.is_empty() && !line.starts_with(':') && !is_line_comment(line);
+
+        let mut started = false;
+        let mut index = 0;
+        while let Some(line) = lines.get(index) {
+            if line.is_empty() {
+                if started {
+                    break;
+                }
+            } else if let Some((name, value)) = attribute_entry(line) {
+                self.set_attribute(name, value);
+            } else if is_line_comment(line) {
+                // Ignore comments
+            } else if let Some(title) = line.strip_prefix("= ").filter(|_| article.title.is_none())
+            {
+                article.title = Some(self.inlines(title.trim()));
+
+                // The title may be followed by an author line and a revision line
+                if let Some(line) = lines.get(index + 1).filter(is_header_line) {
+                    article.authors = Some(authors(line));
+                    index += 1;
+                    if let Some(line) = lines.get(index + 1).filter(is_header_line) {
+                        self.revision(line);
+                        index += 1;
+                    }
+                }
+            } else {
+                break;
+            }
+
+            started = true;
+            index += 1;
+        }
+
+        (article, index)
+    }
+
+    /// Decode a revision line e.g. `v1.0, 2024-03-01: Initial release`
+    fn revision(&mut self, line: &str) {
+        let (line, remark) = match line.split_once(": ") {
+            Some((line, remark)) => (line, Some(remark)),
+            None => (line, None),
+        };
+
+        let parts = line.split(',').map(str::trim).collect_vec();
+        match parts.as_slice() {
+            [number, date, ..] => {
+                self.set_attribute("revnumber", number.trim_start_matches('v'));
+                self.set_attribute("revdate", date);
+            }
+            [number] if number.starts_with('v') => {
+                self.set_attribute("revnumber", number.trim_start_matches('v'))
+            }
+            [date] => self.set_attribute("revdate", date),
+            [] => {}
+        }
+
+        if let Some(remark) = remark {
+            self.set_attribute("revremark", remark);
+        }
+    }
+
+    /// Set (e.g. `:name: value`) or unset (e.g. `:name!:`) a document attribute
+    fn set_attribute(&mut self, name: &str, value: &str) {
+        if let Some(name) = name.strip_prefix('!').or_else(|| name.strip_suffix('!')) {
+            self.attributes.shift_remove(name);
+        } else {
+            let value = self.substitute(value);
+            self.attributes.insert(name.to_lowercase(), value);
+        }
+    }
+
+    /// Get the value of an attribute
+    fn attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .get(&name.to_lowercase())
+            .cloned()
+            .or_else(|| builtin_attribute(name).map(String::from))
+    }
+
+    /// Substitute references to attributes (e.g. `{product}`) in text
+    fn substitute(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match rest
+                .find('}')
+                .and_then(|end| Some((end, self.attribute(&rest[1..end])?)))
+            {
+                Some((end, value)) => {
+                    result.push_str(&value);
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    result.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    /// Set article properties from document attributes
+    ///
+    /// Attributes which do not correspond to an article property are
+    /// kept in `extra` so that they can be encoded in the document header.
+    fn article_attributes(&mut self, article: &mut Article) {
+        let mut extra = Object::new();
+
+        if article.authors.is_none() {
+            if let Some(author) = self.attributes.shift_remove("author") {
+                let author = match self.attributes.shift_remove("email") {
+                    Some(email) => format!("{author} <{email}>"),
+                    None => author,
+                };
+                article.authors = Some(authors(&author));
+            }
+        }
+
+        for (name, value) in self.attributes.drain(..) {
+            match name.as_str() {
+                "description" => article.description = Some(value),
+                "keywords" => {
+                    article.keywords = Some(
+                        value
+                            .split(',')
+                            .map(|keyword| keyword.trim().to_string())
+                            .filter(|keyword| !keyword.is_empty())
+                            .collect(),
+                    )
+                }
+                "revnumber" => article.options.version = Some(StringOrNumber::String(value)),
+                "revdate" => match Date::from_str(&value) {
+                    Ok(date) => article.date_modified = Some(date),
+                    Err(..) => {
+                        extra.insert(name, Primitive::String(value));
+                    }
+                },
+                _ => {
+                    extra.insert(name, Primitive::String(value));
+                }
+            }
+        }
+
+        if !extra.is_empty() {
+            article.options.extra = Some(extra);
+        }
+    }
+
+    /// Decode lines into blocks
+    fn blocks(&mut self, lines: &[String]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+
+        // Block attributes and title which apply to the next block
+        let mut attrs = Attributes::default();
+        let mut title: Option<String> = None;
+
+        let mut index = 0;
+        while let Some(line) = lines.get(index) {
+            if line.is_empty() || is_line_comment(line) {
+                index += 1;
+                continue;
+            }
+
+            if let Some((name, value)) = attribute_entry(line) {
+                self.set_attribute(name, value);
+                index += 1;
+                continue;
+            }
+
+            if is_block_attributes(line) && !line.contains("::") {
+                attrs.merge(line);
+                index += 1;
+                continue;
+            }
+
+            if is_block_title(line) {
+                title = Some(line[1..].to_string());
+                index += 1;
+                continue;
+            }
+
+            let block_attrs = std::mem::take(&mut attrs);
+            let block_title = title.take();
+
+            if let Some(delimiter) = delimiter(line) {
+                let close = lines[index + 1..]
+                    .iter()
+                    .position(|line| line == delimiter)
+                    .map_or(lines.len(), |offset| index + 1 + offset);
+                let body = &lines[index + 1..close];
+                let delimited = self.delimited(delimiter, body, &block_attrs);
+                for block in delimited {
+                    blocks.push(self.identify(block, &block_attrs, block_title.clone()));
+                }
+                index = (close + 1).min(lines.len());
+                continue;
+            }
+
+            if let Some((level, text)) = section_title(line) {
+                let content = self.inlines(text);
+                let block = self.identify(
+                    Block::Heading(Heading::new(level, content)),
+                    &block_attrs,
+                    block_title,
+                );
+                blocks.push(block);
+                index += 1;
+                continue;
+            }
+
+            if let Some(target) = line.strip_prefix("include::") {
+                let (source, attrs) = target.split_once('[').unwrap_or((target, "]"));
+                if attrs != "]" {
+                    self.losses.add("AsciiDoc include attributes");
+                }
+                blocks.push(Block::IncludeBlock(IncludeBlock::new(
+                    self.substitute(source),
+                )));
+                index += 1;
+                continue;
+            }
+
+            if let Some(target) = line.strip_prefix("image::") {
+                let image = self.image(target);
+                let block = self.identify(p([image]), &block_attrs, block_title);
+                blocks.push(block);
+                index += 1;
+                continue;
+            }
+
+            if matches!(line.as_str(), "'''" | "---" | "***" | "- - -" | "* * *") {
+                blocks.push(tb());
+                index += 1;
+                continue;
+            }
+
+            if line == "<<<" {
+                self.losses.add("AsciiDoc page break");
+                index += 1;
+                continue;
+            }
+
+            if let Some((order, depth, ..)) = list_marker(line) {
+                let (list, end) = self.list(lines, index, order, depth);
+                blocks.push(list);
+                index = end;
+                continue;
+            }
+
+            // Paragraph, continuing until a blank line
+            let end = lines[index..]
+                .iter()
+                .position(|line| line.is_empty())
+                .map_or(lines.len(), |offset| index + offset);
+            let paragraph = &lines[index..end];
+            index = end;
+
+            let block = self.paragraph(paragraph, &block_attrs);
+            if let Some(block) = block {
+                blocks.push(self.identify(block, &block_attrs, block_title));
+            }
+        }
+
+        blocks
+    }
+
+    /// Decode a paragraph
+    ///
+    /// Depending upon its style, or first line, a paragraph may be decoded
+    /// as an admonition, code block, literal block or quote.
+    fn paragraph(&mut self, lines: &[String], attrs: &Attributes) -> Option<Block> {
+        let style = attrs.style();
+
+        if let Some(admonition_type) = admonition_type(style) {
+            let content = self.inlines(&lines.join("\n"));
+            return Some(admonition(admonition_type, vec![p(content)]));
+        }
+
+        if let Some((admonition_type, rest)) = lines.first().and_then(|line| {
+            let (style, rest) = line.split_once(": ")?;
+            Some((admonition_type(style)?, rest))
+        }) {
+            let text = [vec![rest.to_string()], lines[1..].to_vec()]
+                .concat()
+                .join("\n");
+            let content = self.inlines(&text);
+            return Some(admonition(admonition_type, vec![p(content)]));
+        }
+
+        if style == "source" || style == "listing" {
+            return Some(self.listing(lines.join("\n"), attrs));
+        }
+
+        if style == "literal" || lines.first().map_or(false, |line| line.starts_with(' ')) {
+            let indent = lines
+                .iter()
+                .map(|line| line.len() - line.trim_start().len())
+                .min()
+                .unwrap_or_default();
+            let code = lines.iter().map(|line| &line[indent..]).join("\n");
+            return Some(cb(code, None::<String>));
+        }
+
+        if matches!(style, "quote" | "verse") {
+            return Some(qb([p(self.inlines(&lines.join("\n")))]));
+        }
+
+        let content = self.inlines(&lines.join("\n"));
+        (!content.is_empty()).then(|| p(content))
+    }
+
+    /// Decode a delimited block
+    fn delimited(&mut self, delimiter: &str, lines: &[String], attrs: &Attributes) -> Vec<Block> {
+        let style = attrs.style();
+        let code = || lines.join("\n");
+
+        let block = match delimiter.chars().next().unwrap_or_default() {
+            '/' => return Vec::new(),
+            '-' if delimiter == "--" => match admonition_type(style) {
+                Some(admonition_type) => admonition(admonition_type, self.blocks(lines)),
+                None => return self.blocks(lines),
+            },
+            '-' => self.listing(code(), attrs),
+            '.' => cb(code(), None::<String>),
+            '_' => {
+                if !attrs.positional.is_empty() {
+                    self.losses.add("AsciiDoc quote attribution");
+                }
+                qb(self.blocks(lines))
+            }
+            '=' => match admonition_type(style) {
+                Some(admonition_type) => admonition(admonition_type, self.blocks(lines)),
+                None => return self.blocks(lines),
+            },
+            '*' => {
+                self.losses.add("AsciiDoc sidebar");
+                return self.blocks(lines);
+            }
+            '+' => match style {
+                "stem" | "asciimath" => mb(code(), Some(self.stem_language(style))),
+                "latexmath" => mb(code(), Some("tex")),
+                _ => {
+                    self.losses.add("AsciiDoc passthrough block");
+                    return Vec::new();
+                }
+            },
+            '|' => Block::Table(self.table(lines, attrs)),
+            _ => return Vec::new(),
+        };
+
+        vec![block]
+    }
+
+    /// Decode a listing block as a code block, or a code chunk if it has the `exec` option
+    fn listing(&mut self, code: String, attrs: &Attributes) -> Block {
+        let lang = attrs
+            .positional
+            .first()
+            .or_else(|| attrs.named.get("language"))
+            .cloned()
+            .or_else(|| {
+                (attrs.style() == "source")
+                    .then(|| self.attribute("source-language"))
+                    .flatten()
+            })
+            .filter(|lang| !lang.is_empty());
+
+        if attrs.has_option("exec") {
+            cc(code, lang)
+        } else {
+            cb(code, lang)
+        }
+    }
+
+    /// Get the math language of `stem` content from the document's `stem` attribute
+    fn stem_language(&self, style: &str) -> &'static str {
+        if style == "stem"
+            && self
+                .attribute("stem")
+                .map_or(false, |stem| stem == "latexmath" || stem == "tex")
+        {
+            "tex"
+        } else {
+            "asciimath"
+        }
+    }
+
+    /// Apply the id and title of a block to it
+    fn identify(&mut self, block: Block, attrs: &Attributes, title: Option<String>) -> Block {
+        let id = attrs.id.clone();
+        let caption = |context: &mut Self, title: String| vec![p(context.inlines(&title))];
+
+        match block {
+            Block::Heading(mut heading) => {
+                heading.id = id.or(heading.id);
+                Block::Heading(heading)
+            }
+            Block::Admonition(mut admonition) => {
+                admonition.id = id;
+                admonition.title = title.map(|title| self.inlines(&title));
+                Block::Admonition(admonition)
+            }
+            Block::CodeChunk(mut chunk) => {
+                chunk.id = id;
+                chunk.caption = title.map(|title| caption(self, title));
+                Block::CodeChunk(chunk)
+            }
+            Block::Table(mut table) => {
+                table.id = id;
+                table.caption = title.map(|title| caption(self, title));
+                Block::Table(table)
+            }
+            Block::Paragraph(paragraph)
+                if matches!(paragraph.content.as_slice(), [Inline::ImageObject(..)])
+                    && (id.is_some() || title.is_some()) =>
+            {
+                Block::Figure(Figure {
+                    id,
+                    caption: title.map(|title| caption(self, title)),
+                    ..Figure::new(vec![Block::Paragraph(paragraph)])
+                })
+            }
+            block => {
+                if title.is_some() {
+                    self.losses.add("AsciiDoc block title");
+                }
+                block
+            }
+        }
+    }
+
+    /// Decode a list starting at `start`
+    ///
+    /// Items are continued by indented or unindented text, by blocks attached
+    /// using a `+` line, and by nested lists (those with a different marker).
+    fn list(
+        &mut self,
+        lines: &[String],
+        start: usize,
+        order: ListOrder,
+        depth: usize,
+    ) -> (Block, usize) {
+        let is_sibling = |line: &str| {
+            list_marker(line).map_or(false, |(item_order, item_depth, ..)| {
+                item_order == order && item_depth == depth
+            })
+        };
+
+        let mut items = Vec::new();
+        let mut index = start;
+        'items: while let Some((.., text)) = lines
+            .get(index)
+            .filter(|line| is_sibling(line))
+            .and_then(|line| list_marker(line))
+        {
+            let mut body = vec![text.to_string()];
+            index += 1;
+
+            while let Some(line) = lines.get(index) {
+                if line == "+" {
+                    let end = attached_end(lines, index + 1);
+                    body.extend_from_slice(&lines[index..end]);
+                    index = end;
+                    continue;
+                }
+
+                if line.is_empty() {
+                    // A blank line continues the list only if followed by an item
+                    // of the list, or a nested list with a deeper marker of the same kind
+                    let next = lines[index..]
+                        .iter()
+                        .position(|line| !line.is_empty())
+                        .map(|offset| index + offset);
+                    match next.and_then(|next| Some((next, list_marker(&lines[next])?))) {
+                        Some((next, (item_order, item_depth, ..))) if item_order == order => {
+                            if item_depth > depth {
+                                body.extend_from_slice(&lines[index..next]);
+                            }
+                            index = next;
+                            if item_depth == depth {
+                                break;
+                            }
+                            continue;
+                        }
+                        _ => {
+                            items.push(self.list_item(body));
+                            break 'items;
+                        }
+                    }
+                }
+
+                if is_sibling(line) {
+                    break;
+                }
+
+                body.push(line.clone());
+                index += 1;
+            }
+
+            items.push(self.list_item(body));
+        }
+
+        (Block::List(List::new(items, order)), index)
+    }
+
+    /// Decode the lines of a list item
+    fn list_item(&mut self, lines: Vec<String>) -> ListItem {
+        let mut lines = lines;
+
+        // Checklist items e.g. `* [x] Done`
+        let is_checked = lines.first_mut().and_then(|first| {
+            let checked = match first.get(..4) {
+                Some("[x] " | "[X] " | "[*] ") => true,
+                Some("[ ] ") => false,
+                _ => return None,
+            };
+            *first = first[4..].to_string();
+            Some(checked)
+        });
+
+        // Lines up to any nested list belong to the item, with `+` continuations
+        // separating blocks. Nested lists (and anything attached to their items)
+        // are decoded separately.
+        let mut delimiter_: Option<String> = None;
+        let nested = lines
+            .iter()
+            .enumerate()
+            .skip(1)
+            .find(|(.., line)| {
+                if let Some(open) = &delimiter_ {
+                    if *line == open {
+                        delimiter_ = None;
+                    }
+                    return false;
+                }
+                if let Some(open) = delimiter(line) {
+                    delimiter_ = Some(open.to_string());
+                    return false;
+                }
+                list_marker(line).is_some()
+            })
+            .map_or(lines.len(), |(index, ..)| index);
+
+        let own = lines[..nested]
+            .iter()
+            .map(|line| {
+                if line == "+" {
+                    String::new()
+                } else {
+                    line.clone()
+                }
+            })
+            .collect_vec();
+
+        let mut content = self.blocks(&own);
+        content.append(&mut self.blocks(&lines[nested..]));
+
+        ListItem {
+            is_checked,
+            ..ListItem::new(content)
+        }
+    }
+
+    /// Decode a table
+    fn table(&mut self, lines: &[String], attrs: &Attributes) -> Table {
+        struct Cell {
+            spec: String,
+            text: String,
+            line: usize,
+        }
+
+        let mut cells: Vec<Cell> = Vec::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            let segments = split_cells(line);
+            let Some((first, rest)) = segments.split_first().filter(|(.., rest)| !rest.is_empty())
+            else {
+                // Continuation of the previous cell (including blank lines between paragraphs)
+                if let Some(cell) = cells.last_mut() {
+                    cell.text.push('\n');
+                    cell.text.push_str(line);
+                }
+                continue;
+            };
+
+            let (before, mut spec) = split_spec(first);
+            if let (false, Some(cell)) = (before.trim().is_empty(), cells.last_mut()) {
+                cell.text.push('\n');
+                cell.text.push_str(before);
+            }
+
+            for (index, segment) in rest.iter().enumerate() {
+                let (text, next_spec) = if index + 1 < rest.len() {
+                    split_spec(segment)
+                } else {
+                    (segment.as_str(), "")
+                };
+                cells.push(Cell {
+                    spec: spec.to_string(),
+                    text: text.trim().to_string(),
+                    line: line_index,
+                });
+                spec = next_spec;
+            }
+        }
+
+        // The number of columns is from the `cols` attribute or the number of cells on the first line
+        let columns = attrs
+            .named
+            .get("cols")
+            .map(|cols| {
+                if cols.contains([',', ';']) {
+                    cols.split([',', ';']).count()
+                } else {
+                    cols.trim_end_matches('*').parse().unwrap_or(1)
+                }
+            })
+            .unwrap_or_else(|| {
+                let first = cells.first().map(|cell| cell.line);
+                cells
+                    .iter()
+                    .take_while(|cell| Some(cell.line) == first)
+                    .count()
+            })
+            .max(1);
+
+        // The first row is a header row if the `header` option is set, or
+        // if it is on a single line that is followed by a blank line
+        let header = attrs.has_option("header")
+            || (!attrs.has_option("noheader")
+                && cells.len() > columns
+                && cells[..columns]
+                    .iter()
+                    .all(|cell| Some(cell.line) == cells.first().map(|cell| cell.line))
+                && cells
+                    .first()
+                    .and_then(|cell| lines.get(cell.line + 1))
+                    .map_or(false, |line| line.is_empty()));
+
+        let mut rows = Vec::new();
+        for (row_index, row) in cells.into_iter().chunks(columns).into_iter().enumerate() {
+            let is_header = header && row_index == 0;
+            let cells = row
+                .map(|Cell { spec, text, .. }| {
+                    if spec.contains(['+', '*']) {
+                        self.losses.add("AsciiDoc table cell span");
+                    }
+                    let content = if spec.ends_with('a') {
+                        let lines = text.lines().map(String::from).collect_vec();
+                        self.blocks(&lines)
+                    } else {
+                        text.split("\n\n")
+                            .filter(|para| !para.trim().is_empty())
+                            .map(|para| p(self.inlines(para.trim())))
+                            .collect()
+                    };
+                    TableCell {
+                        cell_type: is_header.then_some(TableCellType::HeaderCell),
+                        ..TableCell::new(content)
+                    }
+                })
+                .collect_vec();
+            rows.push(TableRow {
+                row_type: is_header.then_some(TableRowType::HeaderRow),
+                ..TableRow::new(cells)
+            });
+        }
+
+        Table::new(rows)
+    }
+
+    /// Decode the target and attributes of an image macro e.g. `diagram.png[Alt text]`
+    fn image(&mut self, target: &str) -> Inline {
+        let (url, attrs) = target.split_once('[').unwrap_or((target, "]"));
+        let alt = split_attributes(attrs.trim_end_matches(']'))
+            .into_iter()
+            .next()
+            .filter(|alt| !alt.is_empty() && !alt.contains('='));
+
+        match img(self.substitute(url)) {
+            Inline::ImageObject(mut image) => {
+                image.caption = alt.map(|alt| vec![t(alt.trim_matches('"'))]);
+                Inline::ImageObject(image)
+            }
+            inline => inline,
+        }
+    }
+
+    /// Decode text into inlines
+    fn inlines(&mut self, text: &str) -> Vec<Inline> {
+        let chars = text.chars().collect_vec();
+
+        let mut inlines = Vec::new();
+        let mut current = String::new();
+
+        let flush = |current: &mut String, inlines: &mut Vec<Inline>| {
+            if !current.is_empty() {
+                inlines.push(t(std::mem::take(current)));
+            }
+        };
+
+        let mut index = 0;
+        while index < chars.len() {
+            let char = chars[index];
+
+            if char == '\\' {
+                if let Some(next) = chars
+                    .get(index + 1)
+                    .filter(|next| ESCAPABLE.contains(**next))
+                {
+                    current.push(*next);
+                    index += 2;
+                    continue;
+                }
+            }
+
+            if char == '\n' {
+                // Hard line breaks (a trailing ` +`) are not supported so become spaces
+                if current.ends_with(" +") {
+                    current.truncate(current.len() - 2);
+                }
+                current.push(' ');
+                index += 1;
+                continue;
+            }
+
+            if let Some((inline, end)) = self.inline_markup(&chars, index) {
+                flush(&mut current, &mut inlines);
+                inlines.extend(inline);
+                index = end;
+                continue;
+            }
+
+            // Attribute references e.g. `{product}`
+            if char == '{' {
+                if let Some(end) = chars[index..].iter().position(|char| *char == '}') {
+                    let name = chars[index + 1..index + end].iter().collect::<String>();
+                    if let Some(value) = self.attribute(&name) {
+                        current.push_str(&value);
+                        index += end + 1;
+                        continue;
+                    }
+                }
+            }
+
+            current.push(char);
+            index += 1;
+        }
+        flush(&mut current, &mut inlines);
+
+        inlines
+    }
+
+    /// Attempt to decode inline markup starting at `start`
+    ///
+    /// Returns the inlines (if any) and the index after the markup.
+    fn inline_markup(&mut self, chars: &[char], start: usize) -> Option<(Vec<Inline>, usize)> {
+        let rest = &chars[start..];
+        let starts_with = |prefix: &str| {
+            let prefix = prefix.chars().collect_vec();
+            rest.starts_with(&prefix)
+        };
+        let text = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+
+        // Constrained formatting must be at word boundaries
+        let boundary = start == 0 || !chars[start - 1].is_alphanumeric();
+
+        // Find the end of unconstrained formatting (which can be anywhere)
+        let unconstrained = |mark: &str| -> Option<usize> {
+            let mark = mark.chars().collect_vec();
+            let content = start + mark.len();
+            (content + 1..=chars.len().saturating_sub(mark.len()))
+                .find(|&index| chars[index..].starts_with(&mark))
+        };
+
+        // Find the end of constrained formatting
+        let constrained = |mark: char| -> Option<usize> {
+            let content = start + 1;
+            if !boundary
+                || chars
+                    .get(content)
+                    .map_or(true, |char| char.is_whitespace() || *char == mark)
+            {
+                return None;
+            }
+            (content..chars.len()).find(|&index| {
+                chars[index] == mark
+                    && index > content
+                    && !chars[index - 1].is_whitespace()
+                    && chars
+                        .get(index + 1)
+                        .map_or(true, |next| !next.is_alphanumeric())
+            })
+        };
+
+        // Find the closing bracket of a macro's attributes
+        let bracket = |from: usize| -> Option<usize> {
+            (chars.get(from) == Some(&'[')).then_some(())?;
+            (from + 1..chars.len()).find(|&index| chars[index] == ']' && chars[index - 1] != '\\')
+        };
+
+        match chars[start] {
+            '`' => {
+                // Literal monospace e.g. `+{not-an-attribute}+`
+                if starts_with("`+") {
+                    if let Some(end) = (start + 2..chars.len().saturating_sub(1))
+                        .find(|&index| chars[index] == '+' && chars[index + 1] == '`')
+                    {
+                        let code = text(start + 2, end);
+                        return Some((vec![code_inline(code)], end + 2));
+                    }
+                }
+                let (end, width) = if starts_with("``") {
+                    (unconstrained("``")?, 2)
+                } else {
+                    (constrained('`')?, 1)
+                };
+                let code = self.substitute(&text(start + width, end));
+                Some((vec![code_inline(code)], end + width))
+            }
+            '*' | '_' | '#' => {
+                let mark = chars[start];
+                let double = [mark, mark].iter().collect::<String>();
+                let (end, width) = if starts_with(&double) {
+                    (unconstrained(&double)?, 2)
+                } else {
+                    (constrained(mark)?, 1)
+                };
+                let content = self.inlines(&text(start + width, end));
+                let inline = match mark {
+                    '*' => stg(content),
+                    '_' => em(content),
+                    _ => {
+                        // Highlighted (marked) text has no equivalent so only its content is kept
+                        self.losses.add("AsciiDoc highlight");
+                        return Some((content, end + width));
+                    }
+                };
+                Some((vec![inline], end + width))
+            }
+            '^' | '~' => {
+                // Superscript and subscript can not contain spaces
+                let mark = chars[start];
+                let end = (start + 2..chars.len()).find(|&index| chars[index] == mark)?;
+                if chars[start + 1..end]
+                    .iter()
+                    .any(|char| char.is_whitespace())
+                {
+                    return None;
+                }
+                let content = self.inlines(&text(start + 1, end));
+                let inline = if mark == '^' {
+                    sup(content)
+                } else {
+                    sub(content)
+                };
+                Some((vec![inline], end + 1))
+            }
+            '+' if boundary => {
+                // Inline passthrough
+                let end = constrained('+')?;
+                Some((vec![t(text(start + 1, end))], end + 1))
+            }
+            '<' if starts_with("<<") => {
+                // Cross reference e.g. `<<intro>>` or `<<intro,Introduction>>`
+                let end = (start + 2..chars.len().saturating_sub(1))
+                    .find(|&index| chars[index] == '>' && chars[index + 1] == '>')?;
+                let xref = text(start + 2, end);
+                let (id, label) = match xref.split_once(',') {
+                    Some((id, label)) => (id.trim().to_string(), label.trim().to_string()),
+                    None => (xref.trim().to_string(), xref.trim().to_string()),
+                };
+                Some((
+                    vec![lnk(self.inlines(&label), ["#", &id].concat())],
+                    end + 2,
+                ))
+            }
+            '{' => {
+                // An attribute reference which is the URL of a link e.g. `{api-url}[API]`
+                let close = (start + 1..chars.len()).find(|&index| chars[index] == '}')?;
+                let url = self
+                    .attribute(&text(start + 1, close))
+                    .filter(|url| url.contains("://"))?;
+                let end = bracket(close + 1)?;
+                let label = text(close + 2, end);
+                let content = if label.is_empty() {
+                    vec![t(url.clone())]
+                } else {
+                    self.inlines(&label)
+                };
+                Some((vec![lnk(content, url)], end + 1))
+            }
+            _ if boundary => {
+                // Macros e.g. `stem:[x^2]`, `link:file.pdf[File]`, `image:icon.png[]`
+                let name_end = (start..chars.len())
+                    .find(|&index| !chars[index].is_ascii_alphanumeric())
+                    .unwrap_or(chars.len());
+                let name = text(start, name_end);
+
+                // URLs e.g. `https://example.org` or `https://example.org[Example]`
+                if matches!(name.as_str(), "http" | "https" | "ftp" | "mailto")
+                    && (starts_with(&[name.as_str(), "://"].concat()) || name == "mailto")
+                    && chars.get(name_end) == Some(&':')
+                {
+                    let url_end = (name_end..chars.len())
+                        .find(|&index| chars[index].is_whitespace() || chars[index] == '[')
+                        .unwrap_or(chars.len());
+                    if chars.get(url_end) == Some(&'[') {
+                        let end = bracket(url_end)?;
+                        let url = text(start, url_end);
+                        let label = text(url_end + 1, end);
+                        let content = if label.is_empty() {
+                            vec![t(url.clone())]
+                        } else {
+                            self.inlines(&label)
+                        };
+                        return Some((vec![lnk(content, url)], end + 1));
+                    }
+
+                    // Bare URLs exclude trailing punctuation
+                    let mut url_end = url_end;
+                    while url_end > name_end && ".,;:!?)".contains(chars[url_end - 1]) {
+                        url_end -= 1;
+                    }
+                    let url = text(start, url_end);
+                    return Some((vec![lnk([t(url.clone())], url)], url_end));
+                }
+
+                if chars.get(name_end) != Some(&':') {
+                    return None;
+                }
+                let target_end = (name_end + 1..chars.len())
+                    .find(|&index| chars[index] == '[' || chars[index].is_whitespace())?;
+                let end = bracket(target_end)?;
+                let target = text(name_end + 1, target_end);
+                let content = text(target_end + 1, end);
+
+                let inline = match name.as_str() {
+                    "stem" | "asciimath" | "latexmath" => {
+                        let lang = match name.as_str() {
+                            "latexmath" => "tex",
+                            style => self.stem_language(style),
+                        };
+                        mi(content.replace("\\]", "]"), Some(lang))
+                    }
+                    "link" => {
+                        let target = self.substitute(&target);
+                        let content = if content.is_empty() {
+                            vec![t(target.clone())]
+                        } else {
+                            self.inlines(&content)
+                        };
+                        lnk(content, target)
+                    }
+                    "xref" => {
+                        let id = target.trim_start_matches('#');
+                        let content = if content.is_empty() {
+                            vec![t(id)]
+                        } else {
+                            self.inlines(&content)
+                        };
+                        lnk(content, ["#", id].concat())
+                    }
+                    "image" => self.image(&text(name_end + 1, end + 1)),
+                    "pass" => t(content),
+                    "kbd" | "btn" | "menu" | "footnote" | "indexterm" | "indexterm2" => {
+                        self.losses.add(format!("AsciiDoc `{name}` macro"));
+                        return Some((Vec::new(), end + 1));
+                    }
+                    _ => return None,
+                };
+                Some((vec![inline], end + 1))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Create an admonition
+fn admonition(admonition_type: AdmonitionType, content: Vec<Block>) -> Block {
+    Block::Admonition(Admonition {
+        admonition_type,
+        content,
+        ..Default::default()
+    })
+}
+
+/// Create an inline code node
+fn code_inline(code: String) -> Inline {
+    Inline::CodeInline(CodeInline::new(code.into()))
+}
+
+/// Parse an author line e.g. `Jane Doe <jane@example.org>; John Smith`
+fn authors(line: &str) -> Vec<Author> {
+    line.split(';')
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .filter_map(|author| Person::from_str(author).ok())
+        .map(Author::Person)
+        .collect()
+}
+
+/// Get the end of a block attached to a list item with a `+` line
+fn attached_end(lines: &[String], start: usize) -> usize {
+    let mut index = start;
+    while lines.get(index).map_or(false, |line| {
+        is_block_attributes(line) || is_block_title(line)
+    }) {
+        index += 1;
+    }
+
+    if let Some(open) = lines.get(index).and_then(|line| delimiter(line)) {
+        return lines[index + 1..]
+            .iter()
+            .position(|line| line == open)
+            .map_or(lines.len(), |offset| index + offset + 2);
+    }
+
+    lines[index..]
+        .iter()
+        .position(|line| line.is_empty() || line == "+" || list_marker(line).is_some())
+        .map_or(lines.len(), |offset| index + offset)
+}
+
+/// Split a table line into segments on unescaped `|` characters
+fn split_cells(line: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push('|');
+                chars.next();
+            }
+            '|' => segments.push(std::mem::take(&mut current)),
+            _ => current.push(char),
+        }
+    }
+    segments.push(current);
+    segments
+}
+
+/// Split a cell specifier (e.g. `a`, `2+`, `^.^h`) from the end of a segment
+fn split_spec(segment: &str) -> (&str, &str) {
+    let start = segment
+        .rfind(char::is_whitespace)
+        .map_or(0, |index| index + 1);
+    let token = &segment[start..];
+
+    let prefix = token
+        .strip_suffix(|char: char| "adehlmsv".contains(char))
+        .unwrap_or(token);
+    let is_spec = !token.is_empty()
+        && prefix
+            .chars()
+            .all(|char| char.is_ascii_digit() || "+*.<^>".contains(char))
+        && (prefix.len() < token.len() || prefix.contains(['+', '*', '<', '^', '>']));
+
+    if is_spec {
+        (&segment[..start], token)
+    } else {
+        (segment, "")
+    }
+}
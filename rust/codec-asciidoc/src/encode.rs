@@ -0,0 +1,691 @@
+use codec::{
+    common::itertools::Itertools,
+    schema::{
+        Admonition, AdmonitionType, Article, Author, AuthorRoleAuthor, Block, CodeChunk, Figure,
+        ImageObject, Inline, List, ListOrder, Node, NodeId, NodeType, Paragraph, Primitive,
+        StringOrNumber, Table, TableRowType,
+    },
+    EncodeInfo, Losses, Mapping,
+};
+use codec_text_trait::to_text;
+
+/// Encode a node to AsciiDoc
+///
+/// Only articles and blocks can be encoded. Node types that can not be
+/// represented in AsciiDoc are recorded as losses. A mapping between nodes
+/// and their location in the AsciiDoc is created for use by language servers.
+pub(super) fn encode(node: &Node) -> (String, EncodeInfo) {
+    let mut context = Context::default();
+
+    match node {
+        Node::Article(article) => context.article(article),
+        node => match Block::try_from(node.clone()) {
+            Ok(block) => context.block(&block),
+            Err(..) => context.losses.add(node.to_string()),
+        },
+    }
+
+    let info = EncodeInfo {
+        losses: context.losses,
+        mapping: context.mapping,
+        ..Default::default()
+    };
+
+    (context.content, info)
+}
+
+#[derive(Default)]
+struct Context {
+    /// The encoded AsciiDoc
+    content: String,
+
+    /// The number of characters in `content`
+    ///
+    /// Maintained separately to avoid counting characters each time a node is mapped.
+    chars: usize,
+
+    /// The nodes currently being encoded and the character index at which each started
+    node_stack: Vec<(NodeType, NodeId, usize)>,
+
+    /// The mapping between nodes and their location in `content`
+    mapping: Mapping,
+
+    /// Losses when encoding
+    losses: Losses,
+
+    /// The names of document attributes which, if referenced in text, need to be escaped
+    attributes: Vec<String>,
+
+    /// The nesting depth of delimited blocks, used to lengthen the delimiters of nested blocks
+    depth: usize,
+
+    /// The nesting depths of unordered and ordered lists
+    list_depths: (usize, usize),
+
+    /// Whether currently encoding a table cell (in which `|` needs to be escaped)
+    in_table: bool,
+}
+
+/// Get the name of an author for an author line
+fn author_name(author: &Author) -> Option<String> {
+    match author {
+        Author::Person(person) => Some(person.as_string()),
+        Author::Organization(organization) => organization.name.clone(),
+        Author::SoftwareApplication(software) => Some(software.name.clone()),
+        Author::AuthorRole(role) => match &role.author {
+            AuthorRoleAuthor::Person(person) => Some(person.as_string()),
+            AuthorRoleAuthor::Organization(organization) => organization.name.clone(),
+            AuthorRoleAuthor::SoftwareApplication(software) => Some(software.name.clone()),
+            AuthorRoleAuthor::Thing(..) => None,
+        },
+    }
+}
+
+impl Context {
+    /// Push a string onto the content
+    fn str(&mut self, value: &str) -> &mut Self {
+        self.content.push_str(value);
+        self.chars += value.chars().count();
+        self
+    }
+
+    /// Enter a node, recording where it starts
+    fn enter(&mut self, node_type: NodeType, node_id: NodeId) -> &mut Self {
+        self.node_stack.push((node_type, node_id, self.chars));
+        self
+    }
+
+    /// Exit a node, adding a mapping entry from where it started to the current position
+    fn exit(&mut self) -> &mut Self {
+        if let Some((node_type, node_id, start)) = self.node_stack.pop() {
+            self.mapping
+                .add(start, self.chars, node_type, node_id, None, None);
+        }
+        self
+    }
+
+    /// Encode an article, including a document header for its metadata
+    fn article(&mut self, article: &Article) {
+        self.enter(NodeType::Article, article.node_id());
+
+        if let Some(title) = &article.title {
+            self.str("= ");
+            self.inlines(title);
+            self.str("\n");
+
+            let authors = article
+                .authors
+                .iter()
+                .flatten()
+                .filter_map(author_name)
+                .join("; ");
+            if !authors.is_empty() {
+                self.str(&authors).str("\n");
+            }
+        } else if article.authors.is_some() {
+            // Authors can only be in the document header after a title
+            self.losses.add("Article.authors");
+        }
+
+        let mut attributes = Vec::new();
+        if let Some(description) = &article.description {
+            attributes.push(("description".to_string(), description.to_string()));
+        }
+        if let Some(keywords) = &article.keywords {
+            attributes.push(("keywords".to_string(), keywords.join(", ")));
+        }
+        if let Some(version) = &article.options.version {
+            let version = match version {
+                StringOrNumber::String(version) => version.clone(),
+                StringOrNumber::Number(version) => version.to_string(),
+            };
+            attributes.push(("revnumber".to_string(), version));
+        }
+        if let Some(date) = &article.date_modified {
+            attributes.push(("revdate".to_string(), date.value.clone()));
+        }
+        for (name, value) in article.options.extra.iter().flat_map(|extra| extra.iter()) {
+            let value = match value {
+                Primitive::String(value) => value.clone(),
+                Primitive::Boolean(value) => value.to_string(),
+                Primitive::Integer(value) => value.to_string(),
+                Primitive::UnsignedInteger(value) => value.to_string(),
+                Primitive::Number(value) => value.to_string(),
+                _ => {
+                    self.losses.add("Article.extra");
+                    continue;
+                }
+            };
+            attributes.push((name.clone(), value));
+        }
+
+        for (name, value) in &attributes {
+            self.str(&format!(":{name}: {value}\n"));
+        }
+        self.attributes = attributes.into_iter().map(|(name, ..)| name).collect();
+
+        if !self.content.is_empty() && !article.content.is_empty() {
+            self.str("\n");
+        }
+
+        self.blocks(&article.content);
+
+        self.exit();
+    }
+
+    /// Encode blocks, separated by blank lines
+    fn blocks(&mut self, blocks: &[Block]) {
+        let mut previous: Option<&Block> = None;
+        for block in blocks {
+            let start = (self.content.len(), self.chars);
+
+            if let Some(previous) = previous {
+                self.str("\n\n");
+
+                // Adjacent lists need to be separated by a comment so they are not joined
+                if matches!((previous, block), (Block::List(..), Block::List(..))) {
+                    self.str("//\n\n");
+                }
+            }
+
+            let before = self.content.len();
+            self.block(block);
+
+            if self.content.len() == before {
+                // Nothing encoded for the block so remove any separator
+                self.content.truncate(start.0);
+                self.chars = start.1;
+            } else {
+                previous = Some(block);
+            }
+        }
+    }
+
+    /// Encode a block
+    fn block(&mut self, block: &Block) {
+        let Some(node_id) = block.node_id() else {
+            return;
+        };
+        self.enter(block.node_type(), node_id);
+
+        match block {
+            Block::Paragraph(Paragraph { content, .. }) => match content.as_slice() {
+                [Inline::ImageObject(image)] => self.image(image, "::"),
+                _ => self.inlines(content),
+            },
+            Block::Heading(heading) => {
+                if let Some(id) = &heading.id {
+                    self.str(&format!("[#{id}]\n"));
+                }
+                let level = heading.level.clamp(1, 5) as usize;
+                self.str(&"=".repeat(level + 1)).str(" ");
+                self.inlines(&heading.content);
+            }
+            Block::CodeBlock(code_block) => {
+                if let Some(lang) = &code_block.programming_language {
+                    self.str(&format!("[source,{lang}]\n"));
+                }
+                self.listing(&code_block.code);
+            }
+            Block::CodeChunk(code_chunk) => self.code_chunk(code_chunk),
+            Block::MathBlock(math_block) => {
+                let style = self.math_style(math_block.math_language.as_deref(), "MathBlock");
+                self.str(&format!("[{style}]\n++++\n"))
+                    .str(&math_block.code)
+                    .str("\n++++");
+            }
+            Block::Admonition(admonition) => self.admonition(admonition),
+            Block::Figure(figure) => self.figure(figure),
+            Block::ImageObject(image) => self.image(image, "::"),
+            Block::IncludeBlock(include) => {
+                self.str(&format!("include::{}[]", include.source));
+            }
+            Block::List(list) => self.list(list),
+            Block::QuoteBlock(quote) => self.delimited('_', &quote.content),
+            Block::Section(section) => self.blocks(&section.content),
+            Block::Table(table) => self.table(table),
+            Block::ThematicBreak(..) => {
+                self.str("'''");
+            }
+            _ => {
+                self.node_stack.pop();
+                self.losses.add(block.to_string());
+                return;
+            }
+        }
+
+        self.exit();
+    }
+
+    /// Encode code in a listing block
+    fn listing(&mut self, code: &str) {
+        self.str("----\n").str(code).str("\n----");
+    }
+
+    /// Encode blocks within a delimited block e.g. a quote
+    ///
+    /// The delimiter is lengthened for nested blocks so that it is not
+    /// confused with the delimiter of an enclosing block of the same type.
+    fn delimited(&mut self, char: char, blocks: &[Block]) {
+        let delimiter = char.to_string().repeat(4 + self.depth);
+
+        self.str(&delimiter).str("\n");
+        self.depth += 1;
+        self.blocks(blocks);
+        self.depth -= 1;
+        self.str("\n").str(&delimiter);
+    }
+
+    /// Encode a block title from a caption e.g. `.A caption`
+    ///
+    /// Only the first paragraph of the caption can be used as the title.
+    fn caption(&mut self, caption: &[Block]) {
+        let inlines = match caption {
+            [Block::Paragraph(paragraph), rest @ ..] => {
+                if !rest.is_empty() {
+                    self.losses.add("Caption.content");
+                }
+                paragraph.content.clone()
+            }
+            [] => return,
+            _ => {
+                self.losses.add("Caption.content");
+                return;
+            }
+        };
+
+        self.str(".");
+        self.inlines(&inlines);
+        self.str("\n");
+    }
+
+    /// Get the style for math with a language
+    fn math_style(&mut self, lang: Option<&str>, node_type: &str) -> &'static str {
+        match lang {
+            None | Some("tex" | "latex") => "latexmath",
+            Some("asciimath") => "asciimath",
+            _ => {
+                self.losses.add(format!("{node_type}.mathLanguage"));
+                "latexmath"
+            }
+        }
+    }
+
+    /// Encode a code chunk as a source block with the `exec` option
+    fn code_chunk(&mut self, code_chunk: &CodeChunk) {
+        if let Some(caption) = &code_chunk.caption {
+            self.caption(caption);
+        }
+
+        let id = code_chunk
+            .id
+            .as_ref()
+            .map(|id| ["#", id].concat())
+            .unwrap_or_default();
+        let lang = code_chunk
+            .programming_language
+            .as_ref()
+            .map(|lang| [",", lang].concat())
+            .unwrap_or_default();
+        self.str(&format!("[source{id}%exec{lang}]\n"));
+
+        self.listing(&code_chunk.code);
+
+        if code_chunk
+            .outputs
+            .as_ref()
+            .map_or(false, |outputs| !outputs.is_empty())
+        {
+            self.losses.add("CodeChunk.outputs");
+        }
+    }
+
+    /// Encode an admonition
+    ///
+    /// Admonitions with a single paragraph and no title use the shorter
+    /// paragraph form e.g. `NOTE: Some text`. Admonition types that do not
+    /// have a corresponding AsciiDoc style are encoded using the closest style.
+    fn admonition(&mut self, admonition: &Admonition) {
+        let style = match admonition.admonition_type {
+            AdmonitionType::Note => "NOTE",
+            AdmonitionType::Tip => "TIP",
+            AdmonitionType::Important => "IMPORTANT",
+            AdmonitionType::Warning => "WARNING",
+            AdmonitionType::Danger => "CAUTION",
+            AdmonitionType::Info => {
+                self.losses.add("Admonition.admonitionType");
+                "NOTE"
+            }
+            AdmonitionType::Success => {
+                self.losses.add("Admonition.admonitionType");
+                "TIP"
+            }
+            AdmonitionType::Failure | AdmonitionType::Error => {
+                self.losses.add("Admonition.admonitionType");
+                "WARNING"
+            }
+        };
+
+        if let (None, None, [Block::Paragraph(paragraph)]) = (
+            &admonition.title,
+            &admonition.id,
+            admonition.content.as_slice(),
+        ) {
+            self.str(style).str(": ");
+            self.enter(paragraph.node_type(), paragraph.node_id());
+            self.inlines(&paragraph.content);
+            self.exit();
+            return;
+        }
+
+        if let Some(title) = &admonition.title {
+            self.str(".");
+            self.inlines(title);
+            self.str("\n");
+        }
+
+        let id = admonition
+            .id
+            .as_ref()
+            .map(|id| ["#", id].concat())
+            .unwrap_or_default();
+        self.str(&format!("[{style}{id}]\n"));
+
+        self.delimited('=', &admonition.content);
+    }
+
+    /// Encode a figure
+    ///
+    /// Only figures containing a single image can be represented.
+    fn figure(&mut self, figure: &Figure) {
+        let image = match figure.content.as_slice() {
+            [Block::ImageObject(image)] => Some(image),
+            [Block::Paragraph(Paragraph { content, .. })] => match content.as_slice() {
+                [Inline::ImageObject(image)] => Some(image),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let Some(image) = image else {
+            self.losses.add("Figure.content");
+            self.blocks(&figure.content);
+            return;
+        };
+
+        if let Some(caption) = &figure.caption {
+            self.caption(caption);
+        }
+        if let Some(id) = &figure.id {
+            self.str(&format!("[#{id}]\n"));
+        }
+        self.image(image, "::");
+    }
+
+    /// Encode an image as a block (`image::`) or inline (`image:`) macro
+    fn image(&mut self, image: &ImageObject, colons: &str) {
+        let alt = image
+            .caption
+            .as_ref()
+            .map(|caption| to_text(caption).replace(']', "\\]"))
+            .unwrap_or_default();
+        self.str(&format!("image{colons}{}[{alt}]", image.content_url));
+    }
+
+    /// Encode a list
+    ///
+    /// The first paragraph of each item is on the same line as the marker.
+    /// Nested lists follow directly and other blocks are attached using `+` lines.
+    fn list(&mut self, list: &List) {
+        let ordered = !matches!(list.order, ListOrder::Unordered);
+        let marker = if ordered {
+            self.list_depths.1 += 1;
+            ".".repeat(self.list_depths.1)
+        } else {
+            self.list_depths.0 += 1;
+            "*".repeat(self.list_depths.0)
+        };
+
+        for (index, item) in list.items.iter().enumerate() {
+            if index > 0 {
+                self.str("\n");
+            }
+
+            self.enter(item.node_type(), item.node_id());
+            self.str(&marker).str(" ");
+
+            if let Some(is_checked) = item.is_checked {
+                self.str(if is_checked { "[x] " } else { "[ ] " });
+            }
+
+            let rest = match item.content.split_first() {
+                Some((first @ Block::Paragraph(..), rest)) => {
+                    self.block(first);
+                    rest
+                }
+                _ => {
+                    self.str("{empty}");
+                    item.content.as_slice()
+                }
+            };
+
+            for block in rest {
+                self.str(if matches!(block, Block::List(..)) {
+                    "\n"
+                } else {
+                    "\n+\n"
+                });
+                self.block(block);
+            }
+
+            self.exit();
+        }
+
+        if ordered {
+            self.list_depths.1 -= 1;
+        } else {
+            self.list_depths.0 -= 1;
+        }
+    }
+
+    /// Encode a table
+    ///
+    /// Cells containing a single paragraph are encoded on the same line as
+    /// the other cells in the row. Rows with any other cells have each cell
+    /// on its own line with other cells using the AsciiDoc (`a`) style.
+    fn table(&mut self, table: &Table) {
+        if let Some(caption) = &table.caption {
+            self.caption(caption);
+        }
+
+        let header = table.rows.first().map_or(false, |row| {
+            matches!(row.row_type, Some(TableRowType::HeaderRow))
+        });
+
+        let mut attrs = String::new();
+        if let Some(id) = &table.id {
+            attrs.push('#');
+            attrs.push_str(id);
+        }
+        if header {
+            attrs.push_str("%header");
+        }
+        if !attrs.is_empty() {
+            self.str(&format!("[{attrs}]\n"));
+        }
+
+        self.str("|===\n");
+        for (index, row) in table.rows.iter().enumerate() {
+            if index > 0 {
+                self.str(if header && index == 1 { "\n\n" } else { "\n" });
+            }
+
+            self.enter(row.node_type(), row.node_id());
+
+            let simple = row
+                .cells
+                .iter()
+                .all(|cell| matches!(cell.content.as_slice(), [] | [Block::Paragraph(..)]));
+            for (index, cell) in row.cells.iter().enumerate() {
+                if index > 0 {
+                    self.str(if simple { " " } else { "\n" });
+                }
+
+                self.enter(cell.node_type(), cell.node_id());
+                match cell.content.as_slice() {
+                    [] => {
+                        self.str("|");
+                    }
+                    [Block::Paragraph(paragraph)] => {
+                        self.str("| ");
+                        self.in_table = true;
+                        self.inlines(&paragraph.content);
+                        self.in_table = false;
+                    }
+                    blocks => {
+                        self.str("a|\n");
+                        self.blocks(blocks);
+                    }
+                }
+                self.exit();
+            }
+
+            self.exit();
+        }
+        self.str("\n|===");
+    }
+
+    /// Encode inlines
+    fn inlines(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(inline);
+        }
+    }
+
+    /// Encode an inline
+    fn inline(&mut self, inline: &Inline) {
+        let Some(node_id) = inline.node_id() else {
+            return;
+        };
+        self.enter(inline.node_type(), node_id);
+
+        match inline {
+            Inline::Text(text) => self.text(&text.value),
+            Inline::Emphasis(emphasis) => self.formatted("_", &emphasis.content),
+            Inline::Strong(strong) => self.formatted("*", &strong.content),
+            Inline::Subscript(subscript) => {
+                self.str("~");
+                self.inlines(&subscript.content);
+                self.str("~");
+            }
+            Inline::Superscript(superscript) => {
+                self.str("^");
+                self.inlines(&superscript.content);
+                self.str("^");
+            }
+            Inline::CodeInline(code) => {
+                // Use literal monospace if the code contains characters that
+                // would otherwise be interpreted as markup or attribute references
+                if code
+                    .code
+                    .contains(['`', '*', '_', '#', '^', '~', '{', '\\', '+'])
+                {
+                    self.str("`+").str(&code.code).str("+`");
+                } else {
+                    self.str("`").str(&code.code).str("`");
+                }
+            }
+            Inline::MathInline(math) => {
+                let style = self.math_style(math.math_language.as_deref(), "MathInline");
+                let code = math.code.replace(']', "\\]");
+                self.str(&format!("{style}:[{code}]"));
+            }
+            Inline::Link(link) => {
+                let text = to_text(&link.content);
+                let target = &link.target;
+                if let Some(id) = target.strip_prefix('#') {
+                    if text == id {
+                        self.str(&format!("<<{id}>>"));
+                    } else {
+                        self.str(&format!("<<{id},")).inlines(&link.content);
+                        self.str(">>");
+                    }
+                } else if text == *target && target.contains("://") {
+                    self.str(target);
+                } else {
+                    if !target.contains("://") && !target.starts_with("mailto:") {
+                        self.str("link:");
+                    }
+                    self.str(target).str("[");
+                    self.inlines(&link.content);
+                    self.str("]");
+                }
+            }
+            Inline::ImageObject(image) => self.image(image, ":"),
+            _ => {
+                self.losses.add(inline.to_string());
+                self.text(&to_text(inline));
+            }
+        }
+
+        self.exit();
+    }
+
+    /// Encode formatted inline content e.g. emphasis
+    ///
+    /// Uses unconstrained formatting (e.g. `__`) if immediately preceded by a
+    /// word character, otherwise constrained formatting (e.g. `_`).
+    fn formatted(&mut self, mark: &str, content: &[Inline]) {
+        let mark = if self
+            .content
+            .chars()
+            .last()
+            .map_or(false, |char| char.is_alphanumeric())
+        {
+            mark.repeat(2)
+        } else {
+            mark.to_string()
+        };
+
+        self.str(&mark);
+        self.inlines(content);
+        self.str(&mark);
+    }
+
+    /// Encode text, escaping characters which would otherwise be interpreted as markup
+    fn text(&mut self, text: &str) {
+        let chars = text.chars().collect_vec();
+        let last = self.content.chars().last();
+
+        let mut escaped = String::with_capacity(text.len());
+        for (index, &char) in chars.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|index| chars[index]).or(last);
+            let next = chars.get(index + 1);
+            let boundary = previous.map_or(true, |previous| !previous.is_alphanumeric());
+            let closes = chars[index + 1..].contains(&char);
+
+            let escape = match char {
+                '\\' => next.map_or(false, |next| "\\*_`#^~{[<+|".contains(*next)),
+                '*' | '_' | '`' | '#' | '+' => (boundary && closes) || next == Some(&char),
+                '^' | '~' => closes,
+                '<' => next == Some(&'<'),
+                '|' => self.in_table,
+                '{' => {
+                    let name = chars[index + 1..]
+                        .iter()
+                        .take_while(|char| **char != '}')
+                        .collect::<String>();
+                    self.attributes.contains(&name)
+                }
+                _ => false,
+            };
+
+            if escape {
+                escaped.push('\\');
+            }
+            escaped.push(char);
+        }
+
+        self.str(&escaped);
+    }
+}
@@ -0,0 +1,261 @@
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, NodeType,
+};
+
+mod decode;
+mod encode;
+
+/// A codec for AsciiDoc
+pub struct AsciiDocCodec;
+
+#[async_trait]
+impl Codec for AsciiDocCodec {
+    fn name(&self) -> &str {
+        "asciidoc"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::AsciiDoc => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::AsciiDoc => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        decode::decode(str, options)
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        _options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        Ok(encode::encode(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::tokio,
+        schema::{
+            shortcuts::{adm, cb, cc, em, h1, inb, lnk, mi, p, stg, t},
+            AdmonitionType, Article, Block, CodeInline, Inline, Primitive,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn decode_document() -> Result<()> {
+        let (node, info) = AsciiDocCodec
+            .from_str(
+                r#"= API Guide
+Jane Doe <jane@example.org>
+:description: How to use the API
+:product: Widget
+
+== Getting started
+
+The *{product}* API is _simple_ to use with `curl` and stem:[x^2].
+See https://example.org[the docs] or <<usage>>.
+
+NOTE: Requires a token.
+
+[source,bash]
+----
+curl https://example.org
+----
+
+[source%exec,python]
+----
+print(1)
+----
+
+include::usage.adoc[]
+
+++++
+<div>raw</div>
+++++
+"#,
+                None,
+            )
+            .await?;
+
+        let Node::Article(Article {
+            title,
+            description,
+            authors,
+            content,
+            options,
+            ..
+        }) = node
+        else {
+            panic!("expected article")
+        };
+
+        assert_eq!(title, Some(vec![t("API Guide")]));
+        assert_eq!(description.as_deref(), Some("How to use the API"));
+        assert_eq!(authors.map(|authors| authors.len()), Some(1));
+        assert_eq!(
+            options
+                .extra
+                .and_then(|extra| extra.0.get("product").cloned()),
+            Some(Primitive::String("Widget".into()))
+        );
+
+        assert_eq!(
+            content,
+            vec![
+                h1([t("Getting started")]),
+                p([
+                    t("The "),
+                    stg([t("Widget")]),
+                    t(" API is "),
+                    em([t("simple")]),
+                    t(" to use with "),
+                    Inline::CodeInline(CodeInline::new("curl".into())),
+                    t(" and "),
+                    mi("x^2", Some("asciimath")),
+                    t(". See "),
+                    lnk([t("the docs")], "https://example.org"),
+                    t(" or "),
+                    lnk([t("usage")], "#usage"),
+                    t(".")
+                ]),
+                adm(
+                    AdmonitionType::Note,
+                    None::<String>,
+                    [p([t("Requires a token.")])]
+                ),
+                cb("curl https://example.org", Some("bash")),
+                cc("print(1)", Some("python")),
+                inb("usage.adoc"),
+            ]
+        );
+
+        assert_eq!(
+            info.losses
+                .iter()
+                .map(|(label, ..)| label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["AsciiDoc passthrough block"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let adoc = r#"= Title
+:keywords: one, two
+
+== Section
+
+A paragraph with *strong*, _emphasis_ and a https://example.org[link].
+
+* One
+* Two
+** Nested
+
+//
+
+. First
++
+With a second paragraph
+. Second
+
+[source,r]
+----
+x <- 1
+----
+
+[source%exec,python]
+----
+print(1)
+----
+
+.Heads up
+[TIP]
+====
+Some advice.
+====
+
+WARNING: Careful!
+
+[%header]
+|===
+| A | B
+
+| 1 | 2
+|===
+
+include::part.adoc[]
+
+'''
+
+____
+A quote
+____"#;
+
+        let (node, info) = AsciiDocCodec.from_str(adoc, None).await?;
+        assert!(info.losses.is_empty());
+
+        let Node::Article(article) = &node else {
+            panic!("expected article")
+        };
+        assert!(matches!(article.content[5], Block::CodeChunk(..)));
+
+        let (encoded, info) = AsciiDocCodec.to_string(&node, None).await?;
+        assert_eq!(encoded, adoc);
+        assert!(info.losses.is_empty());
+
+        // The mapping includes an entry for the heading spanning its source
+        let heading = article.content[0].node_id();
+        let range = heading
+            .and_then(|node_id| info.mapping.range_of_node(&node_id))
+            .map(|range| {
+                encoded
+                    .chars()
+                    .take(range.end)
+                    .skip(range.start)
+                    .collect::<String>()
+            });
+        assert_eq!(range.as_deref(), Some("== Section"));
+
+        Ok(())
+    }
+}
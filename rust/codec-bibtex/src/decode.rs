@@ -0,0 +1,158 @@
+use codec::{common::serde_json::Value, Losses};
+use codec_csl::{DateVariable, Item, Name};
+
+use crate::parse::{clean, split_names, Entry};
+
+/// Convert a BibTeX or BibLaTeX entry into a CSL item
+///
+/// Fields from both dialects are accepted (e.g. `journal` and `journaltitle`,
+/// `year` and `date`). Fields that have no equivalent CSL variable are recorded as losses.
+pub fn entry_to_item(entry: Entry, losses: &mut Losses) -> Item {
+    let Entry {
+        entry_type,
+        key,
+        mut fields,
+    } = entry;
+
+    let mut item = Item {
+        id: (!key.is_empty()).then_some(key),
+        r#type: csl_type(&entry_type).to_string(),
+        ..Default::default()
+    };
+
+    // Names are split before cleaning so that braced literal names are retained
+    for (field, names) in [("author", &mut item.author), ("editor", &mut item.editor)] {
+        if let Some(value) = fields.shift_remove(field) {
+            *names = split_names(&value)
+                .into_iter()
+                .map(|(name, literal)| match literal {
+                    true => Name {
+                        literal: Some(name),
+                        ..Default::default()
+                    },
+                    false => Name::parse(&name),
+                })
+                .collect();
+        }
+    }
+
+    let mut fields = fields
+        .into_iter()
+        .map(|(name, value)| {
+            // DOIs and URLs are verbatim fields so are not cleaned
+            let value = match name.as_str() {
+                "doi" | "url" => value.trim().to_string(),
+                _ => clean(&value),
+            };
+            (name, value)
+        })
+        .filter(|(.., value)| !value.is_empty())
+        .collect::<Vec<_>>();
+
+    // The year and month are combined into a date if there is no BibLaTeX `date`
+    let year = take(&mut fields, "year");
+    let month = take(&mut fields, "month");
+    item.issued = match (take(&mut fields, "date"), year) {
+        (Some(date), ..) => Some(DateVariable::from_iso(&date)),
+        (None, Some(year)) => {
+            let mut parts = vec![year];
+            if let Some(month) = month.as_deref().and_then(month_number) {
+                parts.push(month.to_string());
+            }
+            Some(DateVariable::from_iso(&parts.join("-")))
+        }
+        (None, None) => None,
+    };
+
+    let is_article = entry_type == "article";
+    let eprint_type = take(&mut fields, "eprinttype");
+
+    for (name, value) in fields {
+        match name.as_str() {
+            "title" => item.title = Some(value),
+            "journal" | "journaltitle" | "booktitle" => item.container_title = Some(value),
+            "volume" => item.volume = Some(value),
+            "number" if is_article => item.issue = Some(value),
+            "issue" => item.issue = Some(value),
+            "number" => item.number = Some(value),
+            "pages" => item.page = Some(value.replace("--", "-").replace('–', "-")),
+            "edition" => item.edition = Some(value),
+            "publisher" => item.publisher = Some(value),
+            "school" | "institution" | "organization" => {
+                item.publisher.get_or_insert(value);
+            }
+            "address" | "location" => item.publisher_place = Some(value),
+            "abstract" => item.r#abstract = Some(value),
+            "keywords" => item.keyword = Some(value),
+            "doi" => item.doi = Some(value),
+            "url" => item.url = Some(value),
+            "isbn" => item.isbn = Some(value),
+            "issn" => item.issn = Some(value),
+            "pmid" => item.pmid = Some(value),
+            "eprint" if eprint_type.as_deref() == Some("pubmed") => item.pmid = Some(value),
+            "note" | "annote" => {
+                item.extra.insert("note".into(), Value::String(value));
+            }
+            "series" => {
+                item.extra
+                    .insert("collection-title".into(), Value::String(value));
+            }
+            "chapter" => {
+                item.extra
+                    .insert("chapter-number".into(), Value::String(value));
+            }
+            "language" | "langid" => {
+                item.extra.insert("language".into(), Value::String(value));
+            }
+            _ => losses.add(format!("BibTeX.{name}")),
+        }
+    }
+
+    item
+}
+
+/// Remove a field from a list of fields
+fn take(fields: &mut Vec<(String, String)>, name: &str) -> Option<String> {
+    let index = fields.iter().position(|(field, ..)| field == name)?;
+    Some(fields.remove(index).1)
+}
+
+/// Get the number of a month from a `month` field
+///
+/// Month macros (e.g. `mar`) are already expanded by the parser but
+/// month names (e.g. `March`) may also be used.
+fn month_number(month: &str) -> Option<u32> {
+    if let Ok(number) = month.trim().parse::<u32>() {
+        return (1..=12).contains(&number).then_some(number);
+    }
+
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix = month.trim().get(..3)?.to_lowercase();
+    MONTHS
+        .iter()
+        .position(|name| *name == prefix)
+        .map(|index| index as u32 + 1)
+}
+
+/// Get the CSL type corresponding to a BibTeX or BibLaTeX entry type
+fn csl_type(entry_type: &str) -> &'static str {
+    match entry_type {
+        "article" => "article-journal",
+        "book" | "mvbook" | "proceedings" | "mvproceedings" | "collection" | "mvcollection" => {
+            "book"
+        }
+        "booklet" => "pamphlet",
+        "inbook" | "incollection" | "bookinbook" | "suppbook" => "chapter",
+        "inproceedings" | "conference" => "paper-conference",
+        "mastersthesis" | "phdthesis" | "thesis" => "thesis",
+        "techreport" | "report" | "manual" => "report",
+        "online" | "electronic" | "www" => "webpage",
+        "unpublished" => "manuscript",
+        "patent" => "patent",
+        "dataset" => "dataset",
+        "software" => "software",
+        _ => "document",
+    }
+}
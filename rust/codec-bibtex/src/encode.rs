@@ -0,0 +1,225 @@
+use codec::{common::serde_json::Value, Losses};
+use codec_csl::{Item, Name};
+
+use crate::parse::Entry;
+
+/// Convert a CSL item into a BibTeX or BibLaTeX entry
+///
+/// Field values are escaped LaTeX. The differences between the dialects are
+/// in the names of entry types and fields (e.g. BibLaTeX uses `date`, `journaltitle`
+/// and `location` rather than `year`/`month`, `journal` and `address`).
+pub fn item_to_entry(item: &Item, biblatex: bool, losses: &mut Losses) -> Entry {
+    let entry_type = entry_type(&item.r#type, biblatex);
+
+    let mut entry = Entry {
+        entry_type: entry_type.to_string(),
+        key: item.id.clone().unwrap_or_default(),
+        ..Default::default()
+    };
+
+    let mut field = |name: &str, value: String| {
+        entry.fields.insert(name.to_string(), value);
+    };
+
+    if !item.author.is_empty() {
+        field("author", names(&item.author));
+    }
+    if !item.editor.is_empty() {
+        field("editor", names(&item.editor));
+    }
+    if let Some(title) = &item.title {
+        field("title", escape(title));
+    }
+
+    if let Some(container) = &item.container_title {
+        let name = match entry_type {
+            "incollection" | "inproceedings" | "inbook" => "booktitle",
+            _ if biblatex => "journaltitle",
+            _ => "journal",
+        };
+        field(name, escape(container));
+    }
+
+    if let Some(issued) = &item.issued {
+        if biblatex {
+            if let Some(date) = issued.to_iso() {
+                field("date", escape(&date));
+            }
+        } else {
+            if let Some(year) = issued.year() {
+                field("year", year);
+            }
+            if let Some(month) = issued.month() {
+                field("month", month.to_string());
+            }
+        }
+    }
+
+    for (name, value) in [("volume", &item.volume), ("edition", &item.edition)] {
+        if let Some(value) = value {
+            field(name, escape(value));
+        }
+    }
+
+    // BibTeX (and conventionally BibLaTeX) uses `number` for the issue of a journal
+    match (&item.issue, &item.number) {
+        (Some(issue), Some(number)) => {
+            field("number", escape(number));
+            if biblatex {
+                field("issue", escape(issue));
+            } else {
+                losses.add("CSL.issue");
+            }
+        }
+        (Some(value), None) | (None, Some(value)) => field("number", escape(value)),
+        (None, None) => {}
+    }
+
+    if let Some(page) = &item.page {
+        field("pages", escape(&page.replace("--", "-").replace('-', "--")));
+    }
+
+    if let Some(publisher) = &item.publisher {
+        let name = match entry_type {
+            "phdthesis" | "mastersthesis" | "thesis" => "school",
+            "techreport" | "report" => "institution",
+            _ => "publisher",
+        };
+        field(name, escape(publisher));
+    }
+    if let Some(place) = &item.publisher_place {
+        field(if biblatex { "location" } else { "address" }, escape(place));
+    }
+
+    for (name, value) in [
+        ("abstract", &item.r#abstract),
+        ("keywords", &item.keyword),
+        ("doi", &item.doi),
+        ("url", &item.url),
+        ("isbn", &item.isbn),
+        ("issn", &item.issn),
+        ("pmid", &item.pmid),
+    ] {
+        if let Some(value) = value {
+            // DOIs and URLs are verbatim fields so are not escaped
+            let value = match name {
+                "doi" | "url" => value.clone(),
+                _ => escape(value),
+            };
+            field(name, value);
+        }
+    }
+
+    for (key, value) in &item.extra {
+        let name = match key.as_str() {
+            "note" => "note",
+            "collection-title" => "series",
+            "chapter-number" => "chapter",
+            "language" => {
+                if biblatex {
+                    "langid"
+                } else {
+                    "language"
+                }
+            }
+            _ => {
+                losses.add(format!("CSL.{key}"));
+                continue;
+            }
+        };
+        let value = match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+        field(name, escape(&value));
+    }
+
+    entry
+}
+
+/// Serialize a list of entries
+pub fn entries_to_string(entries: &[Entry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let fields = entry
+                .fields
+                .iter()
+                .map(|(name, value)| {
+                    if name == "month" {
+                        if let Some(month) = month_macro(value) {
+                            return format!("  {name} = {month}");
+                        }
+                    }
+                    format!("  {name} = {{{value}}}")
+                })
+                .collect::<Vec<_>>()
+                .join(",\n");
+
+            format!("@{}{{{},\n{fields}\n}}\n", entry.entry_type, entry.key)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Join a list of names using ` and `
+///
+/// Literal names (e.g. organizations) are wrapped in braces so that they are not parsed.
+fn names(names: &[Name]) -> String {
+    names
+        .iter()
+        .map(|name| match &name.literal {
+            Some(literal) => ["{", &escape(literal), "}"].concat(),
+            None => escape(&name.to_inverted()),
+        })
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Escape LaTeX special characters in a value
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '&' | '%' | '$' | '#' | '_' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            '{' => escaped.push_str("\\{"),
+            '}' => escaped.push_str("\\}"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// Get the standard BibTeX month macro for a month number
+fn month_macro(month: &str) -> Option<&'static str> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let index = month.parse::<usize>().ok()?.checked_sub(1)?;
+    MONTHS.get(index).copied()
+}
+
+/// Get the BibTeX or BibLaTeX entry type for a CSL type
+fn entry_type(csl_type: &str, biblatex: bool) -> &'static str {
+    match csl_type {
+        "article" | "article-journal" | "article-magazine" | "article-newspaper" | "review"
+        | "review-book" => "article",
+        "book" => "book",
+        "chapter" | "entry" | "entry-dictionary" | "entry-encyclopedia" => "incollection",
+        "paper-conference" => "inproceedings",
+        "pamphlet" => "booklet",
+        "thesis" if biblatex => "thesis",
+        "thesis" => "phdthesis",
+        "report" if biblatex => "report",
+        "report" => "techreport",
+        "manuscript" => "unpublished",
+        "webpage" | "post" | "post-weblog" if biblatex => "online",
+        "dataset" if biblatex => "dataset",
+        "software" if biblatex => "software",
+        "patent" if biblatex => "patent",
+        _ => "misc",
+    }
+}
@@ -0,0 +1,263 @@
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, Losses, NodeType,
+};
+use codec_csl::{items_to_node, node_to_items};
+
+mod decode;
+mod encode;
+mod parse;
+
+pub use decode::entry_to_item;
+pub use encode::{entries_to_string, item_to_entry};
+pub use parse::{parse, Entry};
+
+/// A codec for BibTeX and BibLaTeX bibliographies
+///
+/// Entries are converted to and from CSL items (see [`codec_csl`]) which are in turn
+/// mapped to creative works (e.g. `Article`s, `Book`s, `Chapter`s and `Thesis`s)
+/// in the `references` of an article.
+pub struct BibtexCodec;
+
+#[async_trait]
+impl Codec for BibtexCodec {
+    fn name(&self) -> &str {
+        "bibtex"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Bibtex | Format::Biblatex => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Bibtex | Format::Biblatex => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        _options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        let mut losses = Losses::none();
+
+        let items = parse(str)?
+            .into_iter()
+            .map(|entry| entry_to_item(entry, &mut losses))
+            .collect();
+
+        Ok((
+            items_to_node(items),
+            DecodeInfo {
+                losses,
+                ..DecodeInfo::none()
+            },
+        ))
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        let options = options.unwrap_or_default();
+        let biblatex = matches!(options.format, Some(Format::Biblatex));
+
+        let (items, mut losses) = node_to_items(node)?;
+
+        let entries = items
+            .iter()
+            .map(|item| item_to_entry(item, biblatex, &mut losses))
+            .collect::<Vec<_>>();
+
+        Ok((
+            entries_to_string(&entries),
+            EncodeInfo {
+                losses,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::{eyre::bail, tokio},
+        schema::{Article, CreativeWorkType, CreativeWorkTypeOrText},
+    };
+    use codec_csl::Item;
+
+    use super::*;
+
+    #[test]
+    fn parse_entries() -> Result<()> {
+        let entries = parse(
+            r#"
+            @string{jot = "Journal of Things"}
+            @comment{Ignored}
+            @Article{smith2020,
+              author = {Smith, Jane A. and van der Berg, Jan and {The Things Consortium}},
+              title = {A {Study} of Th{\"o}ngs \& Stuff},
+              journal = jot,
+              year = 2020,
+              month = mar,
+              volume = {12},
+              number = "4",
+              pages = {100--110},
+              doi = {10.1234/things_5678},
+            }
+            "#,
+        )?;
+        assert_eq!(entries.len(), 1);
+
+        let mut losses = Losses::none();
+        let item = entry_to_item(entries[0].clone(), &mut losses);
+        assert!(losses.is_empty());
+
+        assert_eq!(item.id.as_deref(), Some("smith2020"));
+        assert_eq!(item.r#type, "article-journal");
+        assert_eq!(item.title.as_deref(), Some("A Study of Thöngs & Stuff"));
+        assert_eq!(item.container_title.as_deref(), Some("Journal of Things"));
+        assert_eq!(
+            item.issued
+                .as_ref()
+                .and_then(|date| date.to_iso())
+                .as_deref(),
+            Some("2020-03")
+        );
+        assert_eq!(item.issue.as_deref(), Some("4"));
+        assert_eq!(item.page.as_deref(), Some("100-110"));
+        assert_eq!(item.doi.as_deref(), Some("10.1234/things_5678"));
+
+        assert_eq!(item.author.len(), 3);
+        assert_eq!(item.author[0].family.as_deref(), Some("Smith"));
+        assert_eq!(item.author[0].given.as_deref(), Some("Jane A."));
+        assert_eq!(
+            item.author[1].non_dropping_particle.as_deref(),
+            Some("van der")
+        );
+        assert_eq!(
+            item.author[2].literal.as_deref(),
+            Some("The Things Consortium")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let bib = r#"@inproceedings{doe2019,
+  author = {Doe, John},
+  title = {Proceeding with caution},
+  booktitle = {Proceedings of Things},
+  year = {2019},
+  publisher = {Things Press}
+}
+"#;
+
+        let (node, ..) = BibtexCodec.from_str(bib, None).await?;
+
+        let Node::Article(Article {
+            references: Some(references),
+            ..
+        }) = &node
+        else {
+            bail!("Expected an article with references")
+        };
+        let CreativeWorkTypeOrText::CreativeWorkType(CreativeWorkType::Article(article)) =
+            &references[0]
+        else {
+            bail!("Expected an article")
+        };
+        assert_eq!(article.genre, Some(vec!["paper-conference".to_string()]));
+
+        let (bibtex, ..) = BibtexCodec.to_string(&node, None).await?;
+        assert_eq!(bibtex, bib);
+
+        let (biblatex, ..) = BibtexCodec
+            .to_string(
+                &node,
+                Some(EncodeOptions {
+                    format: Some(Format::Biblatex),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        assert!(biblatex.contains("date = {2019}"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn thesis() -> Result<()> {
+        let bib = r#"@phdthesis{doe2021,
+  author = {Doe, Jane},
+  title = {A thesis on things},
+  year = {2021},
+  school = {University of Things}
+}
+"#;
+
+        let (node, ..) = BibtexCodec.from_str(bib, None).await?;
+
+        let Node::Article(Article {
+            references: Some(references),
+            ..
+        }) = &node
+        else {
+            bail!("Expected an article with references")
+        };
+        assert!(matches!(
+            &references[0],
+            CreativeWorkTypeOrText::CreativeWorkType(CreativeWorkType::Thesis(..))
+        ));
+
+        let (bibtex, ..) = BibtexCodec.to_string(&node, None).await?;
+        assert_eq!(bibtex, bib);
+
+        Ok(())
+    }
+
+    #[test]
+    fn escaping() {
+        let item = Item {
+            id: Some("ref".into()),
+            r#type: "book".into(),
+            title: Some("Profits & Losses: 100% {guaranteed}".into()),
+            ..Default::default()
+        };
+
+        let entry = item_to_entry(&item, false, &mut Losses::none());
+        assert_eq!(
+            entry.fields.get("title").map(String::as_str),
+            Some(r"Profits \& Losses: 100\% \{guaranteed\}")
+        );
+    }
+}
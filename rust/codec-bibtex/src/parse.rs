@@ -0,0 +1,485 @@
+use codec::common::{
+    eyre::{bail, Result},
+    indexmap::IndexMap,
+};
+
+/// A BibTeX or BibLaTeX entry
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Entry {
+    /// The type of entry, lowercased e.g. `article`, `inproceedings`
+    pub entry_type: String,
+
+    /// The citation key of the entry
+    pub key: String,
+
+    /// The fields of the entry with lowercased names and raw (LaTeX) values
+    ///
+    /// Use [`clean`] to convert values to plain text.
+    pub fields: IndexMap<String, String>,
+}
+
+/// Parse BibTeX or BibLaTeX into a list of entries
+///
+/// `@string` macros (and the standard month abbreviations) are expanded,
+/// `#` concatenations are resolved, and `@comment` and `@preamble` entries
+/// are ignored. Field values are kept as LaTeX, minus their outer delimiters.
+pub fn parse(bib: &str) -> Result<Vec<Entry>> {
+    let mut parser = Parser {
+        chars: bib.chars().collect(),
+        pos: 0,
+        strings: IndexMap::new(),
+    };
+
+    let mut entries = Vec::new();
+    while parser.skip_to_entry() {
+        if let Some(entry) = parser.entry()? {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    strings: IndexMap<String, String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek();
+        self.pos += 1;
+        char
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skip any text until the start of the next entry, returning false if there is none
+    fn skip_to_entry(&mut self) -> bool {
+        while let Some(char) = self.next() {
+            if char == '@' {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Expect a character, after any whitespace
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        match self.next() {
+            Some(char) if char == expected => Ok(()),
+            Some(char) => bail!(
+                "Expected `{expected}` but found `{char}` at character {}",
+                self.pos
+            ),
+            None => bail!("Expected `{expected}` but found end of input"),
+        }
+    }
+
+    /// Parse an identifier (entry type, field name, or macro name)
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        let mut ident = String::new();
+        while let Some(char) = self.peek() {
+            if char.is_whitespace() || matches!(char, '{' | '}' | '(' | ')' | ',' | '=' | '#' | '"')
+            {
+                break;
+            }
+            ident.push(char);
+            self.pos += 1;
+        }
+        ident
+    }
+
+    /// Parse an entry, after the `@`
+    fn entry(&mut self) -> Result<Option<Entry>> {
+        let entry_type = self.identifier().to_lowercase();
+
+        self.skip_whitespace();
+        let close = match self.next() {
+            Some('{') => '}',
+            Some('(') => ')',
+            _ => return Ok(None),
+        };
+
+        match entry_type.as_str() {
+            "comment" | "preamble" => {
+                self.pos -= 1;
+                self.braced_or_parenthesized(close);
+                return Ok(None);
+            }
+            "string" => {
+                let name = self.identifier().to_lowercase();
+                self.expect('=')?;
+                let value = self.value()?;
+                self.strings.insert(name, value);
+                self.expect(close)?;
+                return Ok(None);
+            }
+            _ => {}
+        }
+
+        // The key can contain characters such as `:` and `/` so read up to the comma
+        self.skip_whitespace();
+        let mut key = String::new();
+        while let Some(char) = self.peek() {
+            if char == ',' || char == close {
+                break;
+            }
+            key.push(char);
+            self.pos += 1;
+        }
+        let key = key.trim().to_string();
+
+        let mut fields = IndexMap::new();
+        loop {
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => {}
+                Some(char) if char == close => break,
+                Some(char) => bail!("Unexpected `{char}` in entry `{key}`"),
+                None => bail!("Unexpected end of input in entry `{key}`"),
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                break;
+            }
+
+            let name = self.identifier().to_lowercase();
+            if name.is_empty() {
+                bail!("Expected a field name in entry `{key}`");
+            }
+            self.expect('=')?;
+            let value = self.value()?;
+            fields.insert(name, value);
+        }
+
+        Ok(Some(Entry {
+            entry_type,
+            key,
+            fields,
+        }))
+    }
+
+    /// Parse a field value, which may be a concatenation of parts using `#`
+    fn value(&mut self) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => value.push_str(&self.braced_or_parenthesized('}')),
+                Some('"') => value.push_str(&self.quoted()),
+                Some(char) if char.is_ascii_digit() => {
+                    while let Some(char) = self.peek().filter(char::is_ascii_digit) {
+                        value.push(char);
+                        self.pos += 1;
+                    }
+                }
+                Some(..) => {
+                    let name = self.identifier().to_lowercase();
+                    if name.is_empty() {
+                        bail!("Expected a field value at character {}", self.pos);
+                    }
+                    match self.strings.get(&name) {
+                        Some(string) => value.push_str(string),
+                        None => value.push_str(month(&name).unwrap_or(&name)),
+                    }
+                }
+                None => bail!("Unexpected end of input in field value"),
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    /// Parse a delimited value, starting at the opening delimiter, retaining any nested braces
+    fn braced_or_parenthesized(&mut self, close: char) -> String {
+        let open = self.next().unwrap_or_default();
+
+        let mut depth = 0;
+        let mut value = String::new();
+        while let Some(char) = self.next() {
+            if char == '{' || (char == open && open != '{') {
+                depth += 1;
+            } else if char == close && depth == 0 {
+                break;
+            } else if char == '}' || char == close {
+                depth -= 1;
+            }
+            value.push(char);
+        }
+        value
+    }
+
+    /// Parse a double quoted value, starting at the opening quote
+    fn quoted(&mut self) -> String {
+        self.pos += 1;
+
+        let mut depth = 0;
+        let mut value = String::new();
+        while let Some(char) = self.next() {
+            match char {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '"' if depth == 0 => break,
+                _ => {}
+            }
+            value.push(char);
+        }
+        value
+    }
+}
+
+/// Get the number of a month from its standard BibTeX macro name
+fn month(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "jan" => "1",
+        "feb" => "2",
+        "mar" => "3",
+        "apr" => "4",
+        "may" => "5",
+        "jun" => "6",
+        "jul" => "7",
+        "aug" => "8",
+        "sep" => "9",
+        "oct" => "10",
+        "nov" => "11",
+        "dec" => "12",
+        _ => return None,
+    })
+}
+
+/// Convert a LaTeX field value to plain text
+///
+/// Removes grouping braces, converts common escapes (e.g. `\&`) and accent
+/// commands (e.g. `{\"o}`) to Unicode, and normalizes whitespace.
+/// Braces directly wrapping a whole name (e.g. `{World Health Organization}`)
+/// are significant in author lists so those are handled separately in [`split_names`].
+pub fn clean(value: &str) -> String {
+    let chars = value.chars().collect::<Vec<_>>();
+
+    let mut text = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        index += 1;
+        match char {
+            '{' | '}' => {}
+            '~' => text.push(' '),
+            '\\' => {
+                let Some(&next) = chars.get(index) else {
+                    break;
+                };
+                index += 1;
+
+                if let Some(mark) = accent(next) {
+                    // Accent command e.g. \"o, \"{o}, \'{e}
+                    while chars.get(index) == Some(&'{') {
+                        index += 1;
+                    }
+                    if let Some(&base) = chars.get(index) {
+                        index += 1;
+                        text.push(base);
+                        text.push(mark);
+                    }
+                } else if next.is_ascii_alphabetic() {
+                    // Command e.g. \textit, \ss, \o: drop the command name
+                    // but keep any argument
+                    let mut name = next.to_string();
+                    while let Some(&char) = chars.get(index).filter(|c| c.is_ascii_alphabetic()) {
+                        name.push(char);
+                        index += 1;
+                    }
+                    match name.as_str() {
+                        "ss" => text.push('ß'),
+                        "o" => text.push('ø'),
+                        "O" => text.push('Ø'),
+                        "ae" => text.push('æ'),
+                        "AE" => text.push('Æ'),
+                        "aa" => text.push('å'),
+                        "AA" => text.push('Å'),
+                        "l" => text.push('ł'),
+                        "L" => text.push('Ł'),
+                        "i" => text.push('ı'),
+                        _ => {}
+                    }
+                    // Skip the space that terminates a command name
+                    if chars.get(index) == Some(&' ') {
+                        index += 1;
+                    }
+                } else {
+                    // Escaped character e.g. \&, \%, \_
+                    text.push(next);
+                }
+            }
+            _ => text.push(char),
+        }
+    }
+
+    // Compose combining accents and normalize whitespace
+    let text = compose(&text);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Get the Unicode combining character for a LaTeX accent command
+fn accent(command: char) -> Option<char> {
+    Some(match command {
+        '\'' => '\u{301}',
+        '`' => '\u{300}',
+        '^' => '\u{302}',
+        '"' => '\u{308}',
+        '~' => '\u{303}',
+        '=' => '\u{304}',
+        '.' => '\u{307}',
+        'c' => '\u{327}',
+        'v' => '\u{30C}',
+        'u' => '\u{306}',
+        'H' => '\u{30B}',
+        _ => return None,
+    })
+}
+
+/// Compose common base characters and combining accents into precomposed characters
+fn compose(text: &str) -> String {
+    const TABLE: &[(char, char, char)] = &[
+        ('a', '\u{301}', 'á'),
+        ('e', '\u{301}', 'é'),
+        ('i', '\u{301}', 'í'),
+        ('ı', '\u{301}', 'í'),
+        ('o', '\u{301}', 'ó'),
+        ('u', '\u{301}', 'ú'),
+        ('y', '\u{301}', 'ý'),
+        ('c', '\u{301}', 'ć'),
+        ('n', '\u{301}', 'ń'),
+        ('s', '\u{301}', 'ś'),
+        ('z', '\u{301}', 'ź'),
+        ('E', '\u{301}', 'É'),
+        ('A', '\u{301}', 'Á'),
+        ('O', '\u{301}', 'Ó'),
+        ('a', '\u{300}', 'à'),
+        ('e', '\u{300}', 'è'),
+        ('i', '\u{300}', 'ì'),
+        ('o', '\u{300}', 'ò'),
+        ('u', '\u{300}', 'ù'),
+        ('a', '\u{302}', 'â'),
+        ('e', '\u{302}', 'ê'),
+        ('i', '\u{302}', 'î'),
+        ('o', '\u{302}', 'ô'),
+        ('u', '\u{302}', 'û'),
+        ('a', '\u{308}', 'ä'),
+        ('e', '\u{308}', 'ë'),
+        ('i', '\u{308}', 'ï'),
+        ('o', '\u{308}', 'ö'),
+        ('u', '\u{308}', 'ü'),
+        ('A', '\u{308}', 'Ä'),
+        ('O', '\u{308}', 'Ö'),
+        ('U', '\u{308}', 'Ü'),
+        ('a', '\u{303}', 'ã'),
+        ('n', '\u{303}', 'ñ'),
+        ('o', '\u{303}', 'õ'),
+        ('c', '\u{327}', 'ç'),
+        ('C', '\u{327}', 'Ç'),
+        ('c', '\u{30C}', 'č'),
+        ('s', '\u{30C}', 'š'),
+        ('z', '\u{30C}', 'ž'),
+        ('r', '\u{30C}', 'ř'),
+        ('C', '\u{30C}', 'Č'),
+        ('S', '\u{30C}', 'Š'),
+        ('Z', '\u{30C}', 'Ž'),
+        ('o', '\u{30B}', 'ő'),
+        ('u', '\u{30B}', 'ű'),
+    ];
+
+    let mut composed = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        if let Some(&mark) = chars.peek() {
+            if let Some((.., precomposed)) = TABLE
+                .iter()
+                .find(|(base, combining, ..)| *base == char && *combining == mark)
+            {
+                composed.push(*precomposed);
+                chars.next();
+                continue;
+            }
+        }
+        composed.push(char);
+    }
+    composed
+}
+
+/// Split a raw (uncleaned) name list on ` and ` at the top brace level
+///
+/// Names wholly wrapped in braces (e.g. `{World Health Organization}`) are
+/// returned with a flag indicating that they are literal names which should not be parsed.
+pub fn split_names(names: &str) -> Vec<(String, bool)> {
+    let mut parts = Vec::new();
+
+    let mut depth = 0;
+    let mut current = String::new();
+    for word in names.split_inclusive(char::is_whitespace) {
+        if depth == 0 && word.trim() == "and" {
+            parts.push(std::mem::take(&mut current));
+            continue;
+        }
+        for char in word.chars() {
+            match char {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        current.push_str(word);
+    }
+    parts.push(current);
+
+    parts
+        .into_iter()
+        .map(|name| {
+            let name = name.trim();
+            let literal = is_wholly_braced(name);
+            (clean(name), literal)
+        })
+        .filter(|(name, ..)| !name.is_empty())
+        .collect()
+}
+
+/// Whether a string is wholly wrapped in a single pair of braces
+fn is_wholly_braced(value: &str) -> bool {
+    if !(value.starts_with('{') && value.ends_with('}')) {
+        return false;
+    }
+
+    let mut depth = 0;
+    for (index, char) in value.char_indices() {
+        match char {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 && index != value.len() - 1 {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+    true
+}
@@ -0,0 +1,398 @@
+use codec::{
+    common::{itertools::Itertools, serde_json},
+    schema::{
+        shortcuts::t, Article, ArticleOptions, Author, Book, BookOptions, Chapter, ChapterOptions,
+        CreativeWorkType, Date, IntegerOrString, Object, Organization, OrganizationOptions,
+        Periodical, PeriodicalOptions, Person, PersonOptions, PersonOrOrganization,
+        PostalAddressOrString, Primitive, PropertyValue, PropertyValueOrString, PublicationIssue,
+        PublicationVolume, StringOrNumber, Thesis, ThesisOptions,
+    },
+};
+
+use crate::{DateVariable, Item, Name};
+
+/// Registry URLs used as the `propertyId` of identifiers
+pub(crate) const DOI: &str = "https://registry.identifiers.org/registry/doi";
+pub(crate) const PMID: &str = "https://registry.identifiers.org/registry/pubmed";
+pub(crate) const ISBN: &str = "https://registry.identifiers.org/registry/isbn";
+
+/// Convert a CSL item into a Stencila [`CreativeWorkType`]
+///
+/// Books, chapters and theses are converted to a [`Book`], [`Chapter`] and [`Thesis`]
+/// respectively. All other types of items are converted to an [`Article`].
+pub fn item_to_work(item: Item) -> CreativeWorkType {
+    let r#type = item.r#type.clone();
+    let article = item_to_article(item);
+    match r#type.as_str() {
+        "book" => CreativeWorkType::Book(article_to_book(article)),
+        "chapter" => CreativeWorkType::Chapter(article_to_chapter(article)),
+        "thesis" => CreativeWorkType::Thesis(article_to_thesis(article)),
+        _ => CreativeWorkType::Article(article),
+    }
+}
+
+/// Convert a CSL item into a Stencila [`Article`]
+///
+/// The CSL type of the item is stored as the first `genre` of the article so
+/// that it can be restored when encoding. Any container (e.g. journal), volume
+/// and issue are represented as a chain of `isPartOf` properties. CSL variables that
+/// are not mapped to schema properties are stored in the article's `extra` metadata.
+pub fn item_to_article(item: Item) -> Article {
+    let Item {
+        id,
+        r#type,
+        title,
+        container_title,
+        author,
+        editor,
+        issued,
+        volume,
+        issue,
+        page,
+        number,
+        edition,
+        publisher,
+        publisher_place,
+        r#abstract,
+        keyword,
+        doi,
+        url,
+        isbn,
+        issn,
+        pmid,
+        extra,
+    } = item;
+
+    let authors = author.into_iter().map(name_to_author).collect_vec();
+    let editors = editor.into_iter().map(name_to_person).collect_vec();
+
+    let keywords = keyword.map(|keywords| {
+        keywords
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|keyword| !keyword.is_empty())
+            .map(String::from)
+            .collect_vec()
+    });
+
+    let mut identifiers = Vec::new();
+    for (property_id, value) in [(DOI, doi), (PMID, pmid), (ISBN, isbn)] {
+        if let Some(value) = value {
+            identifiers.push(PropertyValueOrString::PropertyValue(PropertyValue {
+                property_id: Some(property_id.into()),
+                value: Primitive::String(value),
+                ..Default::default()
+            }));
+        }
+    }
+
+    // The container of a chapter is a book, which may itself be part of a volume
+    let is_part_of = match (r#type.as_str(), container_title) {
+        ("chapter", Some(title)) => Some(CreativeWorkType::Book(Book {
+            title: Some(vec![t(title)]),
+            options: Box::new(BookOptions {
+                is_part_of: container(None, issn, volume, issue),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })),
+        (.., container_title) => container(container_title, issn, volume, issue),
+    };
+
+    let publisher = (publisher.is_some() || publisher_place.is_some()).then(|| {
+        PersonOrOrganization::Organization(Organization {
+            name: publisher,
+            options: Box::new(OrganizationOptions {
+                address: publisher_place.map(PostalAddressOrString::String),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    });
+
+    let (page_start, page_end) = match &page {
+        Some(page) => match page.split_once(['-', '–']) {
+            Some((start, end)) => (
+                Some(integer_or_string(start.trim())),
+                Some(integer_or_string(end.trim())),
+            ),
+            None => (Some(integer_or_string(page.trim())), None),
+        },
+        None => (None, None),
+    };
+
+    let mut extra = Object(
+        extra
+            .into_iter()
+            .filter_map(|(key, value)| {
+                serde_json::from_value::<Primitive>(value)
+                    .ok()
+                    .map(|value| (key, value))
+            })
+            .collect(),
+    );
+    for (key, value) in [("number", number), ("edition", edition)] {
+        if let Some(value) = value {
+            extra.insert(key.into(), Primitive::String(value));
+        }
+    }
+
+    Article {
+        id,
+        title: title.map(|title| vec![t(title)]),
+        authors: (!authors.is_empty()).then_some(authors),
+        date_published: issued
+            .as_ref()
+            .and_then(DateVariable::to_iso)
+            .map(Date::new),
+        description: r#abstract,
+        genre: (!r#type.is_empty()).then(|| vec![r#type]),
+        keywords,
+        options: Box::new(ArticleOptions {
+            editors: (!editors.is_empty()).then_some(editors),
+            identifiers: (!identifiers.is_empty()).then_some(identifiers),
+            is_part_of,
+            publisher,
+            url,
+            page_start,
+            page_end,
+            pagination: page,
+            extra: (!extra.is_empty()).then_some(extra),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert an [`Article`] for a CSL `book` item into a [`Book`]
+///
+/// The CSL `edition` variable is used for the `version` of the book.
+fn article_to_book(article: Article) -> Book {
+    let Article {
+        id,
+        title,
+        authors,
+        date_published,
+        description,
+        keywords,
+        options,
+        ..
+    } = article;
+    let ArticleOptions {
+        editors,
+        identifiers,
+        is_part_of,
+        publisher,
+        url,
+        mut extra,
+        ..
+    } = *options;
+
+    let version = take_string(&mut extra, "edition").map(StringOrNumber::String);
+
+    Book {
+        id,
+        title,
+        authors,
+        date_published,
+        options: Box::new(BookOptions {
+            description,
+            keywords,
+            editors,
+            identifiers,
+            is_part_of,
+            publisher,
+            url,
+            version,
+            extra,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert an [`Article`] for a CSL `chapter` item into a [`Chapter`]
+fn article_to_chapter(article: Article) -> Chapter {
+    let Article {
+        id,
+        title,
+        authors,
+        date_published,
+        description,
+        keywords,
+        options,
+        ..
+    } = article;
+    let ArticleOptions {
+        editors,
+        identifiers,
+        is_part_of,
+        publisher,
+        url,
+        page_start,
+        page_end,
+        pagination,
+        extra,
+        ..
+    } = *options;
+
+    Chapter {
+        id,
+        title,
+        authors,
+        date_published,
+        options: Box::new(ChapterOptions {
+            description,
+            keywords,
+            editors,
+            identifiers,
+            is_part_of,
+            publisher,
+            url,
+            page_start,
+            page_end,
+            pagination,
+            extra,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert an [`Article`] for a CSL `thesis` item into a [`Thesis`]
+fn article_to_thesis(article: Article) -> Thesis {
+    let Article {
+        id,
+        title,
+        authors,
+        date_published,
+        description,
+        keywords,
+        options,
+        ..
+    } = article;
+    let ArticleOptions {
+        editors,
+        identifiers,
+        is_part_of,
+        publisher,
+        url,
+        extra,
+        ..
+    } = *options;
+
+    Thesis {
+        id,
+        title,
+        authors,
+        date_published,
+        options: Box::new(ThesisOptions {
+            description,
+            keywords,
+            editors,
+            identifiers,
+            is_part_of,
+            publisher,
+            url,
+            extra,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Remove a string from `extra` metadata, setting it to `None` if it becomes empty
+fn take_string(extra: &mut Option<Object>, key: &str) -> Option<String> {
+    let object = extra.as_mut()?;
+    let value = match object.shift_remove(key)? {
+        Primitive::String(value) => Some(value),
+        value => {
+            object.insert(key.to_string(), value);
+            None
+        }
+    };
+    if object.is_empty() {
+        *extra = None;
+    }
+    value
+}
+
+/// Create the chain of periodical, volume and issue that a work is part of
+fn container(
+    title: Option<String>,
+    issn: Option<String>,
+    volume: Option<String>,
+    issue: Option<String>,
+) -> Option<CreativeWorkType> {
+    let periodical = (title.is_some() || issn.is_some()).then(|| {
+        CreativeWorkType::Periodical(Periodical {
+            options: Box::new(PeriodicalOptions {
+                name: title,
+                issns: issn.map(|issn| vec![issn]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    });
+
+    let volume = match volume {
+        Some(volume) => Some(CreativeWorkType::PublicationVolume(PublicationVolume {
+            volume_number: Some(integer_or_string(&volume)),
+            is_part_of: periodical.map(Box::new),
+            ..Default::default()
+        })),
+        None => periodical,
+    };
+
+    match issue {
+        Some(issue) => Some(CreativeWorkType::PublicationIssue(PublicationIssue {
+            issue_number: Some(integer_or_string(&issue)),
+            is_part_of: volume.map(Box::new),
+            ..Default::default()
+        })),
+        None => volume,
+    }
+}
+
+/// Convert a CSL name into an [`Author`]
+///
+/// Literal names are assumed to be organizations.
+fn name_to_author(name: Name) -> Author {
+    match (&name.family, &name.literal) {
+        (None, Some(literal)) => Author::Organization(Organization {
+            name: Some(literal.clone()),
+            ..Default::default()
+        }),
+        _ => Author::Person(name_to_person(name)),
+    }
+}
+
+/// Convert a CSL name into a [`Person`]
+fn name_to_person(name: Name) -> Person {
+    let family = match (name.non_dropping_particle, name.family) {
+        (Some(particle), Some(family)) => Some(format!("{particle} {family}")),
+        (None, family) => family,
+        (Some(particle), None) => Some(particle),
+    };
+
+    Person {
+        family_names: family.map(|family| vec![family]),
+        given_names: name
+            .given
+            .map(|given| given.split_whitespace().map(String::from).collect()),
+        options: Box::new(PersonOptions {
+            name: name.literal,
+            honorific_suffix: name.suffix,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert a string to an integer if possible
+fn integer_or_string(value: &str) -> IntegerOrString {
+    match value.parse::<i64>() {
+        Ok(integer) => IntegerOrString::Integer(integer),
+        Err(..) => IntegerOrString::String(value.to_string()),
+    }
+}
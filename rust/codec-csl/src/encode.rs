@@ -0,0 +1,413 @@
+use codec::{
+    common::{itertools::Itertools, serde_json},
+    schema::{
+        Article, ArticleOptions, Author, AuthorRoleAuthor, Book, Chapter, CreativeWorkType,
+        IntegerOrString, Object, Organization, Person, PersonOrOrganization, PostalAddressOrString,
+        Primitive, PropertyValueOrString, StringOrNumber, Thesis,
+    },
+    Losses,
+};
+use codec_text_trait::to_text;
+
+use crate::{
+    decode::{DOI, ISBN, PMID},
+    DateVariable, Item, Name,
+};
+
+/// Convert a Stencila [`CreativeWorkType`] into a CSL item
+///
+/// The inverse of [`item_to_work`](crate::item_to_work). Returns `None` if the
+/// type of creative work can not be represented as a CSL item.
+pub fn work_to_item(work: &CreativeWorkType, losses: &mut Losses) -> Option<Item> {
+    let (r#type, article) = match work {
+        CreativeWorkType::Article(article) => return Some(article_to_item(article, losses)),
+        CreativeWorkType::Book(book) => ("book", book_to_article(book)),
+        CreativeWorkType::Chapter(chapter) => ("chapter", chapter_to_article(chapter)),
+        CreativeWorkType::Thesis(thesis) => ("thesis", thesis_to_article(thesis)),
+        _ => return None,
+    };
+
+    let mut item = article_to_item(&article, losses);
+    item.r#type = r#type.to_string();
+
+    Some(item)
+}
+
+/// Convert a [`Book`] into an [`Article`] so that it can be converted to a CSL item
+///
+/// The `version` of the book is used for the CSL `edition` variable.
+fn book_to_article(book: &Book) -> Article {
+    let mut extra = book.options.extra.clone();
+    if let Some(version) = &book.options.version {
+        let version = match version {
+            StringOrNumber::String(version) => version.clone(),
+            StringOrNumber::Number(version) => version.to_string(),
+        };
+        extra
+            .get_or_insert_with(Object::default)
+            .insert("edition".into(), Primitive::String(version));
+    }
+
+    Article {
+        id: book.id.clone(),
+        title: book.title.clone(),
+        authors: book.authors.clone(),
+        date_published: book.date_published.clone(),
+        description: book.options.description.clone(),
+        keywords: book.options.keywords.clone(),
+        options: Box::new(ArticleOptions {
+            editors: book.options.editors.clone(),
+            identifiers: book.options.identifiers.clone(),
+            is_part_of: book.options.is_part_of.clone(),
+            publisher: book.options.publisher.clone(),
+            url: book.options.url.clone(),
+            extra,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert a [`Chapter`] into an [`Article`] so that it can be converted to a CSL item
+fn chapter_to_article(chapter: &Chapter) -> Article {
+    Article {
+        id: chapter.id.clone(),
+        title: chapter.title.clone(),
+        authors: chapter.authors.clone(),
+        date_published: chapter.date_published.clone(),
+        description: chapter.options.description.clone(),
+        keywords: chapter.options.keywords.clone(),
+        options: Box::new(ArticleOptions {
+            editors: chapter.options.editors.clone(),
+            identifiers: chapter.options.identifiers.clone(),
+            is_part_of: chapter.options.is_part_of.clone(),
+            publisher: chapter.options.publisher.clone(),
+            url: chapter.options.url.clone(),
+            page_start: chapter.options.page_start.clone(),
+            page_end: chapter.options.page_end.clone(),
+            pagination: chapter.options.pagination.clone(),
+            extra: chapter.options.extra.clone(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert a [`Thesis`] into an [`Article`] so that it can be converted to a CSL item
+fn thesis_to_article(thesis: &Thesis) -> Article {
+    Article {
+        id: thesis.id.clone(),
+        title: thesis.title.clone(),
+        authors: thesis.authors.clone(),
+        date_published: thesis.date_published.clone(),
+        description: thesis.options.description.clone(),
+        keywords: thesis.options.keywords.clone(),
+        options: Box::new(ArticleOptions {
+            editors: thesis.options.editors.clone(),
+            identifiers: thesis.options.identifiers.clone(),
+            is_part_of: thesis.options.is_part_of.clone(),
+            publisher: thesis.options.publisher.clone(),
+            url: thesis.options.url.clone(),
+            extra: thesis.options.extra.clone(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Convert a Stencila [`Article`] into a CSL item
+///
+/// The inverse of [`item_to_article`](crate::item_to_article). Properties of the
+/// article that have no equivalent CSL variable (e.g. its content) are recorded as losses.
+pub fn article_to_item(article: &Article, losses: &mut Losses) -> Item {
+    let mut item = Item {
+        id: article.id.clone(),
+        r#type: article
+            .genre
+            .iter()
+            .flatten()
+            .next()
+            .cloned()
+            .unwrap_or_else(|| {
+                match article.options.is_part_of {
+                    Some(..) => "article-journal",
+                    None => "article",
+                }
+                .to_string()
+            }),
+        title: article.title.as_ref().map(to_text),
+        issued: article
+            .date_published
+            .as_ref()
+            .map(|date| DateVariable::from_iso(&date.value)),
+        r#abstract: article.description.clone(),
+        keyword: article
+            .keywords
+            .as_ref()
+            .map(|keywords| keywords.join(", ")),
+        url: article.options.url.clone(),
+        ..Default::default()
+    };
+
+    for author in article.authors.iter().flatten() {
+        match author_to_name(author) {
+            Some(name) => item.author.push(name),
+            None => losses.add("Article.authors"),
+        }
+    }
+
+    for editor in article.options.editors.iter().flatten() {
+        item.editor.push(person_to_name(editor));
+    }
+
+    for identifier in article.options.identifiers.iter().flatten() {
+        let PropertyValueOrString::PropertyValue(identifier) = identifier else {
+            losses.add("Article.identifiers");
+            continue;
+        };
+
+        let value = match &identifier.value {
+            Primitive::String(value) => value.clone(),
+            value => serde_json::to_string(value).unwrap_or_default(),
+        };
+
+        match identifier.property_id.as_deref() {
+            Some(DOI) => item.doi = Some(value),
+            Some(PMID) => item.pmid = Some(value),
+            Some(ISBN) => item.isbn = Some(value),
+            _ => losses.add("Article.identifiers"),
+        }
+    }
+
+    if let Some(is_part_of) = &article.options.is_part_of {
+        container(is_part_of, &mut item, losses);
+    }
+
+    match &article.options.publisher {
+        Some(PersonOrOrganization::Organization(Organization { name, options, .. })) => {
+            item.publisher = name.clone();
+            item.publisher_place = match &options.address {
+                Some(PostalAddressOrString::String(address)) => Some(address.clone()),
+                Some(..) => {
+                    losses.add("Organization.address");
+                    None
+                }
+                None => None,
+            };
+        }
+        Some(PersonOrOrganization::Person(person)) => {
+            item.publisher = Some(person.as_string());
+        }
+        None => {}
+    }
+
+    item.page = article.options.pagination.clone().or_else(|| {
+        match (&article.options.page_start, &article.options.page_end) {
+            (Some(start), Some(end)) => Some(format!(
+                "{}-{}",
+                integer_or_string(start),
+                integer_or_string(end)
+            )),
+            (Some(start), None) => Some(integer_or_string(start)),
+            _ => None,
+        }
+    });
+
+    if let Some(extra) = &article.options.extra {
+        for (key, value) in extra.iter() {
+            let value = match value {
+                Primitive::String(value) => match key.as_str() {
+                    "number" => {
+                        item.number = Some(value.clone());
+                        continue;
+                    }
+                    "edition" => {
+                        item.edition = Some(value.clone());
+                        continue;
+                    }
+                    _ => serde_json::Value::String(value.clone()),
+                },
+                value => match serde_json::to_value(value) {
+                    Ok(value) => value,
+                    Err(..) => continue,
+                },
+            };
+            item.extra.insert(key.clone(), value);
+        }
+    }
+
+    if !article.content.is_empty() {
+        losses.add("Article.content");
+    }
+    if article.references.is_some() {
+        losses.add("Article.references");
+    }
+
+    item
+}
+
+/// Set the container, volume and issue of an item from the chain of `isPartOf` properties
+fn container(work: &CreativeWorkType, item: &mut Item, losses: &mut Losses) {
+    match work {
+        CreativeWorkType::PublicationIssue(issue) => {
+            item.issue = issue.issue_number.as_ref().map(integer_or_string);
+            if let Some(is_part_of) = &issue.is_part_of {
+                container(is_part_of, item, losses)
+            }
+        }
+        CreativeWorkType::PublicationVolume(volume) => {
+            item.volume = volume.volume_number.as_ref().map(integer_or_string);
+            if let Some(is_part_of) = &volume.is_part_of {
+                container(is_part_of, item, losses)
+            }
+        }
+        CreativeWorkType::Periodical(periodical) => {
+            item.container_title = periodical
+                .options
+                .name
+                .clone()
+                .or_else(|| periodical.options.title.as_ref().map(to_text));
+            item.issn = periodical
+                .options
+                .issns
+                .as_ref()
+                .and_then(|issns| issns.first().cloned());
+        }
+        CreativeWorkType::Book(book) => {
+            item.container_title = book
+                .title
+                .as_ref()
+                .map(to_text)
+                .or_else(|| book.options.name.clone());
+            if let Some(is_part_of) = &book.options.is_part_of {
+                container(is_part_of, item, losses)
+            }
+        }
+        CreativeWorkType::Article(article) => {
+            item.container_title = article
+                .title
+                .as_ref()
+                .map(to_text)
+                .or_else(|| article.options.name.clone());
+        }
+        _ => losses.add("Article.isPartOf"),
+    }
+}
+
+/// Convert an [`Author`] to a CSL name
+fn author_to_name(author: &Author) -> Option<Name> {
+    match author {
+        Author::Person(person) => Some(person_to_name(person)),
+        Author::Organization(organization) => Some(organization_to_name(organization)),
+        Author::AuthorRole(role) => match &role.author {
+            AuthorRoleAuthor::Person(person) => Some(person_to_name(person)),
+            AuthorRoleAuthor::Organization(organization) => {
+                Some(organization_to_name(organization))
+            }
+            _ => None,
+        },
+        Author::SoftwareApplication(..) => None,
+    }
+}
+
+/// Convert a [`Person`] to a CSL name
+fn person_to_name(person: &Person) -> Name {
+    let family = person.family_names.as_ref().map(|names| names.join(" "));
+    let given = person.given_names.as_ref().map(|names| names.join(" "));
+
+    let literal = if family.is_none() && given.is_none() {
+        person
+            .options
+            .name
+            .clone()
+            .or_else(|| Some(person.as_string()))
+            .filter(|name| !name.is_empty())
+    } else {
+        None
+    };
+
+    Name {
+        family,
+        given,
+        suffix: person.options.honorific_suffix.clone(),
+        literal,
+        ..Default::default()
+    }
+}
+
+/// Convert an [`Organization`] to a CSL name
+fn organization_to_name(organization: &Organization) -> Name {
+    Name {
+        literal: organization
+            .name
+            .clone()
+            .or_else(|| organization.options.legal_name.clone()),
+        ..Default::default()
+    }
+}
+
+/// Convert an [`IntegerOrString`] to a string
+fn integer_or_string(value: &IntegerOrString) -> String {
+    match value {
+        IntegerOrString::Integer(integer) => integer.to_string(),
+        IntegerOrString::String(string) => string.clone(),
+    }
+}
+
+/// Generate a citation key for an item from its first author and year
+///
+/// e.g. `smith2024`. Used when encoding to formats that require a key but the
+/// item has no `id`.
+pub(crate) fn citation_key(item: &Item) -> String {
+    let name = item
+        .author
+        .first()
+        .and_then(|name| name.family.clone().or_else(|| name.literal.clone()))
+        .or_else(|| item.title.clone())
+        .unwrap_or_else(|| "ref".to_string());
+
+    let name = name
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|char| char.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+
+    let year = item
+        .issued
+        .as_ref()
+        .and_then(DateVariable::year)
+        .unwrap_or_default();
+
+    [name, year].concat()
+}
+
+/// Ensure that all items have unique, non-empty ids
+///
+/// Items without an id are assigned a generated citation key, with a letter suffix
+/// (e.g. `smith2024b`) added when necessary to make it unique.
+pub fn ensure_ids(items: &mut [Item]) {
+    let mut used = items
+        .iter()
+        .filter_map(|item| item.id.clone())
+        .collect_vec();
+
+    for item in items.iter_mut() {
+        if item.id.as_ref().map_or(false, |id| !id.is_empty()) {
+            continue;
+        }
+
+        let base = citation_key(item);
+        let mut key = base.clone();
+        let mut suffix = b'a';
+        while used.contains(&key) {
+            suffix = suffix.saturating_add(1);
+            key = format!("{base}{}", suffix as char);
+        }
+
+        used.push(key.clone());
+        item.id = Some(key);
+    }
+}
@@ -0,0 +1,273 @@
+use codec::common::{
+    indexmap::IndexMap,
+    serde::{de, Deserialize, Deserializer, Serialize},
+    serde_json::Value,
+    serde_with::skip_serializing_none,
+};
+
+/// A CSL-JSON bibliographic item
+///
+/// Only the variables that are mapped to and from Stencila Schema types
+/// have fields. All other variables are collected into `extra`.
+/// See https://citeproc-js.readthedocs.io/en/latest/csl-json/markup.html
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", crate = "codec::common::serde")]
+pub struct Item {
+    /// The identifier (citation key) of the item
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub id: Option<String>,
+
+    /// The CSL type of the item e.g. `article-journal`, `book`
+    #[serde(rename = "type", default)]
+    pub r#type: String,
+
+    pub title: Option<String>,
+
+    pub container_title: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub author: Vec<Name>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub editor: Vec<Name>,
+
+    pub issued: Option<DateVariable>,
+
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub volume: Option<String>,
+
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub issue: Option<String>,
+
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub page: Option<String>,
+
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub number: Option<String>,
+
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub edition: Option<String>,
+
+    pub publisher: Option<String>,
+
+    pub publisher_place: Option<String>,
+
+    #[serde(rename = "abstract")]
+    pub r#abstract: Option<String>,
+
+    pub keyword: Option<String>,
+
+    #[serde(rename = "DOI")]
+    pub doi: Option<String>,
+
+    #[serde(rename = "URL")]
+    pub url: Option<String>,
+
+    #[serde(rename = "ISBN")]
+    pub isbn: Option<String>,
+
+    #[serde(rename = "ISSN")]
+    pub issn: Option<String>,
+
+    #[serde(rename = "PMID")]
+    pub pmid: Option<String>,
+
+    /// Other CSL variables which are not mapped to schema properties
+    #[serde(flatten)]
+    pub extra: IndexMap<String, Value>,
+}
+
+/// A CSL-JSON name variable
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", crate = "codec::common::serde")]
+pub struct Name {
+    pub family: Option<String>,
+
+    pub given: Option<String>,
+
+    pub non_dropping_particle: Option<String>,
+
+    pub suffix: Option<String>,
+
+    /// The name of an organization, or a name that should not be parsed
+    pub literal: Option<String>,
+}
+
+impl Name {
+    /// Parse a name from a string
+    ///
+    /// Handles names in both the "Family, Given" (optionally with a suffix
+    /// e.g. "Family, Jr., Given") and "Given von Family" forms used by
+    /// BibTeX and RIS. Lowercase words preceding the family name
+    /// (e.g. "van", "de") are treated as a non-dropping particle.
+    pub fn parse(name: &str) -> Self {
+        let name = name.trim();
+
+        let parts = name.split(',').map(str::trim).collect::<Vec<_>>();
+        let (family, given, suffix) = match parts.as_slice() {
+            [family, suffix, given, ..] => (*family, Some(*given), Some(*suffix)),
+            [family, given] => (*family, Some(*given), None),
+            _ => {
+                // "Given von Family" form: the family name is the last word
+                // along with any lowercase words (particles) preceding it
+                let words = name.split_whitespace().collect::<Vec<_>>();
+                let Some((family, rest)) = words.split_last() else {
+                    return Self::default();
+                };
+
+                let particle_start = rest
+                    .iter()
+                    .position(|word| word.starts_with(char::is_lowercase))
+                    .unwrap_or(rest.len());
+
+                let given = rest[..particle_start].join(" ");
+                let particle = rest[particle_start..].join(" ");
+
+                return Self {
+                    family: Some(family.to_string()),
+                    given: (!given.is_empty()).then_some(given),
+                    non_dropping_particle: (!particle.is_empty()).then_some(particle),
+                    ..Default::default()
+                };
+            }
+        };
+
+        // In the "Family, Given" form any lowercase words at the start of
+        // the family part are a particle e.g. "van der Berg, Jan"
+        let words = family.split_whitespace().collect::<Vec<_>>();
+        let particle_end = words
+            .iter()
+            .position(|word| !word.starts_with(char::is_lowercase))
+            .unwrap_or(words.len().saturating_sub(1));
+        let particle = words[..particle_end].join(" ");
+        let family = words[particle_end..].join(" ");
+
+        Self {
+            family: (!family.is_empty()).then_some(family),
+            given: given.filter(|given| !given.is_empty()).map(String::from),
+            non_dropping_particle: (!particle.is_empty()).then_some(particle),
+            suffix: suffix.filter(|suffix| !suffix.is_empty()).map(String::from),
+            ..Default::default()
+        }
+    }
+
+    /// Get the name in "Family, Given" form (or "Family, Suffix, Given" if there is a suffix)
+    ///
+    /// Literal names are returned as is.
+    pub fn to_inverted(&self) -> String {
+        if let Some(literal) = &self.literal {
+            return literal.clone();
+        }
+
+        let family = [
+            self.non_dropping_particle.as_deref(),
+            self.family.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+        [
+            Some(family.as_str()),
+            self.suffix.as_deref(),
+            self.given.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// A CSL-JSON date variable
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", crate = "codec::common::serde")]
+pub struct DateVariable {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub date_parts: Vec<Vec<Value>>,
+
+    pub literal: Option<String>,
+
+    pub raw: Option<String>,
+}
+
+impl DateVariable {
+    /// Create a date variable from an ISO 8601 date string (e.g. `2024`, `2024-03`, `2024-03-01`)
+    pub fn from_iso(date: &str) -> Self {
+        let parts = date
+            .split('-')
+            .take(3)
+            .map_while(|part| part.trim().parse::<u32>().ok())
+            .map(Value::from)
+            .collect::<Vec<_>>();
+
+        if parts.is_empty() {
+            Self {
+                literal: Some(date.to_string()),
+                ..Default::default()
+            }
+        } else {
+            Self {
+                date_parts: vec![parts],
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Get the date as an ISO 8601 date string
+    ///
+    /// Only the first of any date parts (i.e. the start of a range) is used.
+    pub fn to_iso(&self) -> Option<String> {
+        if let Some(parts) = self.date_parts.first() {
+            let parts = parts
+                .iter()
+                .filter_map(|part| match part {
+                    Value::Number(number) => number.as_u64(),
+                    Value::String(string) => string.trim().parse().ok(),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            return match parts.as_slice() {
+                [year] => Some(format!("{year:04}")),
+                [year, month] => Some(format!("{year:04}-{month:02}")),
+                [year, month, day, ..] => Some(format!("{year:04}-{month:02}-{day:02}")),
+                [] => None,
+            };
+        }
+
+        self.raw.clone().or_else(|| self.literal.clone())
+    }
+
+    /// Get the year of the date
+    pub fn year(&self) -> Option<String> {
+        self.to_iso()
+            .and_then(|date| date.split('-').next().map(String::from))
+    }
+
+    /// Get the month of the date
+    pub fn month(&self) -> Option<u32> {
+        self.to_iso()
+            .and_then(|date| date.split('-').nth(1).and_then(|month| month.parse().ok()))
+    }
+}
+
+/// Deserialize an optional variable which may be either a string or a number
+fn string_or_number<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(string)) => Ok(Some(string)),
+        Some(Value::Number(number)) => Ok(Some(number.to_string())),
+        Some(value) => Err(de::Error::custom(format!(
+            "expected a string or number, got {value}"
+        ))),
+    }
+}
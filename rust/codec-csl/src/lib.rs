@@ -0,0 +1,284 @@
+use codec::{
+    common::{
+        async_trait::async_trait,
+        eyre::{bail, Result},
+        serde_json,
+    },
+    format::Format,
+    schema::{Article, CreativeWorkTypeOrText, Node},
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, Losses, NodeType,
+};
+
+mod decode;
+mod encode;
+mod item;
+
+pub use decode::{item_to_article, item_to_work};
+pub use encode::{article_to_item, ensure_ids, work_to_item};
+pub use item::{DateVariable, Item, Name};
+
+/// A codec for CSL-JSON bibliographies
+///
+/// CSL-JSON is used as the intermediate representation for the other
+/// bibliographic codecs (e.g. BibTeX and RIS) which convert to and from [`Item`]s.
+pub struct CslCodec;
+
+#[async_trait]
+impl Codec for CslCodec {
+    fn name(&self) -> &str {
+        "csl"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::CslJson => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::CslJson => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        _options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        // Accept either an array of items or a single item
+        let items = match str.trim_start().starts_with('[') {
+            true => serde_json::from_str::<Vec<Item>>(str)?,
+            false => vec![serde_json::from_str::<Item>(str)?],
+        };
+
+        Ok((items_to_node(items), DecodeInfo::none()))
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        let options = options.unwrap_or_default();
+
+        let (items, losses) = node_to_items(node)?;
+
+        let json = match options.compact {
+            Some(true) => serde_json::to_string(&items)?,
+            _ => serde_json::to_string_pretty(&items)?,
+        };
+
+        Ok((
+            json,
+            EncodeInfo {
+                losses,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// Create a node from a list of bibliographic items
+///
+/// Returns an [`Article`] with the items as its `references` (and no content)
+/// so that a bibliography can be converted, validated, or embedded in another article.
+pub fn items_to_node(items: Vec<Item>) -> Node {
+    let references = items
+        .into_iter()
+        .map(|item| CreativeWorkTypeOrText::CreativeWorkType(item_to_work(item)))
+        .collect();
+
+    Node::Article(Article {
+        references: Some(references),
+        ..Default::default()
+    })
+}
+
+/// Convert a node into a list of bibliographic items
+///
+/// If the node is an article with `references` then each reference is converted
+/// to an item. Otherwise, the node itself is converted to a single item (e.g. to
+/// generate a citation for an article from its metadata). Items are given
+/// unique ids if they do not already have them.
+pub fn node_to_items(node: &Node) -> Result<(Vec<Item>, Losses)> {
+    let mut losses = Losses::none();
+
+    let mut items = match node {
+        Node::Article(Article {
+            references: Some(references),
+            ..
+        }) => {
+            let mut items = Vec::new();
+            for reference in references {
+                match reference {
+                    CreativeWorkTypeOrText::CreativeWorkType(work) => {
+                        match work_to_item(work, &mut losses) {
+                            Some(item) => items.push(item),
+                            None => losses.add(work.to_string()),
+                        }
+                    }
+                    CreativeWorkTypeOrText::Text(..) => losses.add("Text"),
+                }
+            }
+            items
+        }
+        Node::Article(article) => vec![article_to_item(article, &mut losses)],
+        _ => bail!("Unable to encode a `{node}` as a bibliography"),
+    };
+
+    ensure_ids(&mut items);
+
+    Ok((items, losses))
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::tokio,
+        schema::{Author, CreativeWorkType, PublicationIssue},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let csl = r#"[{
+            "id": "smith2020",
+            "type": "article-journal",
+            "title": "A study of things",
+            "container-title": "Journal of Things",
+            "author": [{"family": "Smith", "given": "Jane A."}, {"literal": "The Things Consortium"}],
+            "issued": {"date-parts": [[2020, 3]]},
+            "volume": 12,
+            "issue": "4",
+            "page": "100-110",
+            "DOI": "10.1234/things.5678",
+            "note": "An unmapped variable"
+        }]"#;
+
+        let (node, ..) = CslCodec.from_str(csl, None).await?;
+
+        let Node::Article(Article {
+            references: Some(references),
+            ..
+        }) = &node
+        else {
+            bail!("Expected an article with references")
+        };
+        let CreativeWorkTypeOrText::CreativeWorkType(CreativeWorkType::Article(article)) =
+            &references[0]
+        else {
+            bail!("Expected an article")
+        };
+
+        assert!(matches!(
+            article.authors.as_deref(),
+            Some([Author::Person(..), Author::Organization(..)])
+        ));
+        assert_eq!(
+            article
+                .date_published
+                .as_ref()
+                .map(|date| date.value.as_str()),
+            Some("2020-03")
+        );
+        assert!(matches!(
+            &article.options.is_part_of,
+            Some(CreativeWorkType::PublicationIssue(PublicationIssue {
+                is_part_of: Some(..),
+                ..
+            }))
+        ));
+
+        let (items, losses) = node_to_items(&node)?;
+        assert!(losses.is_empty());
+
+        let item = &items[0];
+        assert_eq!(item.id.as_deref(), Some("smith2020"));
+        assert_eq!(item.container_title.as_deref(), Some("Journal of Things"));
+        assert_eq!(item.volume.as_deref(), Some("12"));
+        assert_eq!(item.issue.as_deref(), Some("4"));
+        assert_eq!(item.page.as_deref(), Some("100-110"));
+        assert_eq!(item.doi.as_deref(), Some("10.1234/things.5678"));
+        assert_eq!(item.author[0].given.as_deref(), Some("Jane A."));
+        assert_eq!(
+            item.author[1].literal.as_deref(),
+            Some("The Things Consortium")
+        );
+        assert!(item.extra.contains_key("note"));
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn books_chapters_theses() -> Result<()> {
+        let csl = r#"[
+            {"id": "a", "type": "book", "title": "A book", "edition": "2", "publisher": "Press"},
+            {"id": "b", "type": "chapter", "title": "A chapter", "container-title": "A book", "page": "5-10"},
+            {"id": "c", "type": "thesis", "title": "A thesis", "publisher": "University"}
+        ]"#;
+
+        let (node, ..) = CslCodec.from_str(csl, None).await?;
+
+        let Node::Article(Article {
+            references: Some(references),
+            ..
+        }) = &node
+        else {
+            bail!("Expected an article with references")
+        };
+        assert!(matches!(
+            &references[..],
+            [
+                CreativeWorkTypeOrText::CreativeWorkType(CreativeWorkType::Book(..)),
+                CreativeWorkTypeOrText::CreativeWorkType(CreativeWorkType::Chapter(..)),
+                CreativeWorkTypeOrText::CreativeWorkType(CreativeWorkType::Thesis(..))
+            ]
+        ));
+
+        let (items, losses) = node_to_items(&node)?;
+        assert!(losses.is_empty());
+
+        assert_eq!(items[0].r#type, "book");
+        assert_eq!(items[0].edition.as_deref(), Some("2"));
+        assert_eq!(items[1].r#type, "chapter");
+        assert_eq!(items[1].container_title.as_deref(), Some("A book"));
+        assert_eq!(items[1].page.as_deref(), Some("5-10"));
+        assert_eq!(items[2].r#type, "thesis");
+        assert_eq!(items[2].publisher.as_deref(), Some("University"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn invalid_array() {
+        let Err(error) = CslCodec
+            .from_str(r#"[{"id": "a", "author": "not a list"}]"#, None)
+            .await
+        else {
+            panic!("should fail")
+        };
+        assert!(error.to_string().contains("sequence"));
+    }
+}
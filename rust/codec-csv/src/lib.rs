@@ -0,0 +1,387 @@
+use codec::{
+    common::{
+        async_trait::async_trait,
+        eyre::{bail, Result},
+        itertools::Itertools,
+        serde_json,
+    },
+    format::Format,
+    schema::{Block, Datatable, DatatableColumn, Node, Null, Primitive, Table},
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, Losses, NodeType,
+};
+use codec_text_trait::TextCodec;
+
+mod select;
+
+pub use select::{CellRange, CellRef, Selection};
+
+/// A codec for comma and tab separated values
+pub struct CsvCodec;
+
+#[async_trait]
+impl Codec for CsvCodec {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Csv | Format::Tsv => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Csv | Format::Tsv => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Datatable => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Datatable => CodecSupport::LowLoss,
+            NodeType::Table => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        let options = options.unwrap_or_default();
+
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter(options.format.as_ref()))
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(str.as_bytes());
+
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            rows.push(record?.iter().map(parse_value).collect_vec());
+        }
+
+        if let Some(select) = &options.select {
+            let selection = Selection::parse(select, &[])?;
+            if let Some(range) = selection.range {
+                rows = range.apply(rows, (0, 0));
+            }
+        }
+
+        let datatable = rows_to_datatable(rows);
+
+        Ok((Node::Datatable(datatable), DecodeInfo::none()))
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        let options = options.unwrap_or_default();
+
+        let (rows, losses) = node_to_rows(node)?;
+
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(delimiter(options.format.as_ref()))
+            .flexible(true)
+            .from_writer(Vec::new());
+        for row in rows {
+            writer.write_record(row.iter().map(value_to_string))?;
+        }
+        let csv = String::from_utf8(writer.into_inner()?)?;
+
+        Ok((
+            csv,
+            EncodeInfo {
+                losses,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// Get the delimiter for a format
+fn delimiter(format: Option<&Format>) -> u8 {
+    match format {
+        Some(Format::Tsv) => b'\t',
+        _ => b',',
+    }
+}
+
+/// Parse a value in a cell into a [`Primitive`]
+///
+/// Empty cells are parsed as `Null` and booleans, integers and numbers
+/// are parsed as such. All other values are strings.
+pub fn parse_value(value: &str) -> Primitive {
+    let trimmed = value.trim();
+
+    if trimmed.is_empty() {
+        return Primitive::Null(Null);
+    }
+
+    match trimmed.to_lowercase().as_str() {
+        "true" => return Primitive::Boolean(true),
+        "false" => return Primitive::Boolean(false),
+        _ => {}
+    }
+
+    if let Ok(integer) = trimmed.parse::<i64>() {
+        return Primitive::Integer(integer);
+    }
+
+    if let Ok(number) = trimmed.parse::<f64>() {
+        if number.is_finite() {
+            return Primitive::Number(number);
+        }
+    }
+
+    Primitive::String(value.to_string())
+}
+
+/// Convert a [`Primitive`] to a string for a cell
+pub fn value_to_string(value: &Primitive) -> String {
+    match value {
+        Primitive::Null(..) => String::new(),
+        Primitive::Boolean(value) => value.to_string(),
+        Primitive::Integer(value) => value.to_string(),
+        Primitive::UnsignedInteger(value) => value.to_string(),
+        Primitive::Number(value) => value.to_string(),
+        Primitive::String(value) => value.clone(),
+        Primitive::Array(..) | Primitive::Object(..) => {
+            serde_json::to_string(value).unwrap_or_default()
+        }
+    }
+}
+
+/// Create a [`Datatable`] from rows of values
+///
+/// The first row is used for the names of the columns. A validator is inferred
+/// for each column from its values.
+pub fn rows_to_datatable(rows: Vec<Vec<Primitive>>) -> Datatable {
+    let mut rows = rows.into_iter();
+
+    let names = rows
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(value_to_string)
+        .collect_vec();
+    let rows = rows.collect_vec();
+
+    let columns = rows
+        .iter()
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0)
+        .max(names.len());
+
+    let columns = (0..columns)
+        .map(|index| {
+            let name = names
+                .get(index)
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .unwrap_or_else(|| format!("column{}", index + 1));
+
+            let values = rows
+                .iter()
+                .map(|row| row.get(index).cloned().unwrap_or(Primitive::Null(Null)))
+                .collect();
+
+            DatatableColumn::from_values(name, values)
+        })
+        .collect();
+
+    Datatable::new(columns)
+}
+
+/// Convert a node into rows of values
+///
+/// For `Datatable`s, the first row contains the names of the columns. `Table`s
+/// are converted to rows of values parsed from the text of each cell. For `Article`s,
+/// the first table in the content is used.
+pub fn node_to_rows(node: &Node) -> Result<(Vec<Vec<Primitive>>, Losses)> {
+    match node {
+        Node::Datatable(datatable) => Ok((datatable_to_rows(datatable), Losses::none())),
+        Node::Table(table) => Ok(table_to_rows(table)),
+        Node::Article(article) => {
+            let table = article.content.iter().find_map(|block| match block {
+                Block::Table(table) => Some(table),
+                _ => None,
+            });
+            let Some(table) = table else {
+                bail!("Article does not contain a table")
+            };
+
+            let (rows, mut losses) = table_to_rows(table);
+            if article.content.len() > 1 {
+                losses.add("Article.content");
+            }
+
+            Ok((rows, losses))
+        }
+        _ => bail!("Unable to encode a `{node}` as rows of values"),
+    }
+}
+
+/// Convert a [`Datatable`] into rows of values
+fn datatable_to_rows(datatable: &Datatable) -> Vec<Vec<Primitive>> {
+    let header = datatable
+        .columns
+        .iter()
+        .map(|column| Primitive::String(column.name.clone()))
+        .collect_vec();
+
+    let rows = (0..datatable.rows()).map(|row| {
+        datatable
+            .columns
+            .iter()
+            .map(|column| {
+                column
+                    .values
+                    .get(row)
+                    .cloned()
+                    .unwrap_or(Primitive::Null(Null))
+            })
+            .collect_vec()
+    });
+
+    std::iter::once(header).chain(rows).collect()
+}
+
+/// Convert a [`Table`] into rows of values
+fn table_to_rows(table: &Table) -> (Vec<Vec<Primitive>>, Losses) {
+    let mut losses = Losses::none();
+
+    if table.caption.is_some() {
+        losses.add("Table.caption");
+    }
+    if table.notes.is_some() {
+        losses.add("Table.notes");
+    }
+
+    let rows = table
+        .rows
+        .iter()
+        .map(|row| {
+            row.cells
+                .iter()
+                .map(|cell| {
+                    let (text, cell_losses) = cell.content.to_text();
+                    losses.merge(cell_losses);
+                    parse_value(text.trim())
+                })
+                .collect_vec()
+        })
+        .collect();
+
+    (rows, losses)
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::tokio,
+        schema::{ArrayValidator, Validator},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn decode() -> Result<()> {
+        let (node, ..) = CsvCodec
+            .from_str(
+                "name,height,born,kind\na,1.5,2001-02-03,x\nb,2,2002-03-04,x\nc,,2000-01-01,y\nd,3,1999-12-31,x\n",
+                None,
+            )
+            .await?;
+
+        let Node::Datatable(datatable) = node else {
+            bail!("Expected a datatable")
+        };
+        assert_eq!(datatable.rows(), 4);
+
+        let validators = datatable
+            .columns
+            .iter()
+            .map(|column| match &column.validator {
+                Some(ArrayValidator {
+                    items_validator: Some(validator),
+                    ..
+                }) => validator.to_string(),
+                _ => String::new(),
+            })
+            .collect_vec();
+        assert_eq!(
+            validators,
+            vec![
+                "StringValidator",
+                "NumberValidator",
+                "DateValidator",
+                "EnumValidator"
+            ]
+        );
+        assert!(matches!(
+            datatable.columns[1].validator.as_ref().and_then(|validator| validator.items_validator.as_deref()),
+            Some(Validator::NumberValidator(validator)) if validator.minimum == Some(1.5)
+        ));
+
+        let hint = datatable.hint();
+        assert_eq!(hint.rows, 4);
+        let columns = hint
+            .columns
+            .iter()
+            .map(|column| {
+                (
+                    column.item_type.as_str(),
+                    column.minimum.clone(),
+                    column.maximum.clone(),
+                    column.nulls,
+                )
+            })
+            .collect_vec();
+        assert_eq!(
+            columns,
+            vec![
+                ("String", None, None, Some(0)),
+                (
+                    "Number",
+                    Some(Primitive::Number(1.5)),
+                    Some(Primitive::Number(3.0)),
+                    Some(1)
+                ),
+                (
+                    "Date",
+                    Some(Primitive::String("1999-12-31".into())),
+                    Some(Primitive::String("2002-03-04".into())),
+                    Some(0)
+                ),
+                ("String", None, None, Some(0)),
+            ]
+        );
+
+        let (csv, ..) = CsvCodec
+            .to_string(&Node::Datatable(datatable), None)
+            .await?;
+        assert!(csv.starts_with("name,height,born,kind\na,1.5,2001-02-03,x\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use codec::common::eyre::{bail, Result};
+
+/// A selection of a sheet and/or a range of cells within a spreadsheet
+///
+/// Parsed from selectors in the form used by most spreadsheet applications
+/// e.g. `Sheet1`, `A1:D20`, `Sheet1!A1:D20`, `'My sheet'!B:D`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// The name of the sheet
+    pub sheet: Option<String>,
+
+    /// The range of cells
+    pub range: Option<CellRange>,
+}
+
+/// A range of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRange {
+    /// The first cell of the range
+    pub start: CellRef,
+
+    /// The last cell of the range (inclusive)
+    ///
+    /// If `None` then the range extends to the last row and column.
+    pub end: Option<CellRef>,
+}
+
+/// A reference to a cell, row or column
+///
+/// Rows and columns are 0-based. Either may be `None` e.g. for the
+/// column only reference `B`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRef {
+    pub row: Option<usize>,
+    pub col: Option<usize>,
+}
+
+impl Selection {
+    /// Parse a selector into a [`Selection`]
+    ///
+    /// The `sheets` are the names of the sheets in the spreadsheet (if any).
+    /// A selector which is the name of a sheet is always treated as a sheet,
+    /// even if it could also be a cell reference (e.g. `AB1`). Otherwise, a
+    /// selector without a `!` is only treated as a range if it contains a `:`
+    /// or is a reference to a single cell, so that sheet names such as `Data`
+    /// (which could be a column reference) are not mistaken for ranges.
+    pub fn parse(selector: &str, sheets: &[String]) -> Result<Self> {
+        let selector = selector.trim();
+
+        let sheet = unquote(selector);
+        if sheets.contains(&sheet) {
+            return Ok(Self {
+                sheet: Some(sheet),
+                range: None,
+            });
+        }
+
+        if let Some((sheet, range)) = selector.rsplit_once('!') {
+            return Ok(Self {
+                sheet: Some(unquote(sheet)),
+                range: Some(CellRange::parse(range)?),
+            });
+        }
+
+        match CellRange::parse(selector) {
+            Ok(range)
+                if range.end.is_some()
+                    || (range.start.row.is_some() && range.start.col.is_some()) =>
+            {
+                Ok(Self {
+                    sheet: None,
+                    range: Some(range),
+                })
+            }
+            _ => Ok(Self {
+                sheet: Some(sheet),
+                range: None,
+            }),
+        }
+    }
+}
+
+/// Remove any quotes around a sheet name
+fn unquote(name: &str) -> String {
+    name.trim()
+        .trim_matches('\'')
+        .trim_matches('"')
+        .replace("''", "'")
+}
+
+impl CellRange {
+    /// Parse a range e.g. `A1:D20`, `B2`, `B:D` or `2:10`
+    pub fn parse(range: &str) -> Result<Self> {
+        let (start, end) = match range.split_once(':') {
+            Some((start, end)) => (start, Some(end)),
+            None => (range, None),
+        };
+
+        Ok(Self {
+            start: CellRef::parse(start)?,
+            end: end.map(CellRef::parse).transpose()?,
+        })
+    }
+
+    /// Apply the range to rows of values
+    ///
+    /// The `offset` is the row and column of the first value in `rows`
+    /// (some spreadsheet readers omit leading empty rows and columns).
+    pub fn apply<T: Clone>(&self, rows: Vec<Vec<T>>, offset: (usize, usize)) -> Vec<Vec<T>> {
+        let (row_offset, col_offset) = offset;
+
+        let row_start = self.start.row.unwrap_or(0);
+        let col_start = self.start.col.unwrap_or(0);
+        let row_end = self.end.as_ref().and_then(|end| end.row);
+        let col_end = self.end.as_ref().and_then(|end| end.col);
+
+        rows.into_iter()
+            .enumerate()
+            .filter(|(index, ..)| {
+                let row = index + row_offset;
+                row >= row_start && row_end.map_or(true, |end| row <= end)
+            })
+            .map(|(.., values)| {
+                values
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, ..)| {
+                        let col = index + col_offset;
+                        col >= col_start && col_end.map_or(true, |end| col <= end)
+                    })
+                    .map(|(.., value)| value)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl CellRef {
+    /// Parse a cell reference e.g. `A1`, `$B$2`, `C` or `3`
+    pub fn parse(cell: &str) -> Result<Self> {
+        let cell = cell.trim().replace('$', "");
+        if cell.is_empty() {
+            bail!("Empty cell reference")
+        }
+
+        let letters = cell
+            .chars()
+            .take_while(|char| char.is_ascii_alphabetic())
+            .collect::<String>();
+        let digits = &cell[letters.len()..];
+
+        // The last column in most spreadsheet applications is `XFD`
+        if letters.len() > 3 {
+            bail!("Invalid column in cell reference: {cell}")
+        }
+
+        if !digits.chars().all(|char| char.is_ascii_digit()) {
+            bail!("Invalid cell reference: {cell}")
+        }
+
+        let col = if letters.is_empty() {
+            None
+        } else {
+            let col = letters
+                .to_ascii_uppercase()
+                .chars()
+                .fold(0usize, |col, char| {
+                    col * 26 + (char as usize - 'A' as usize + 1)
+                });
+            Some(col - 1)
+        };
+
+        let row = if digits.is_empty() {
+            None
+        } else {
+            match digits.parse::<usize>()? {
+                0 => bail!("Row numbers start at 1: {cell}"),
+                row => Some(row - 1),
+            }
+        };
+
+        Ok(Self { row, col })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> Result<()> {
+        assert_eq!(
+            Selection::parse("Sheet1!A1:D20", &[])?,
+            Selection {
+                sheet: Some("Sheet1".into()),
+                range: Some(CellRange {
+                    start: CellRef {
+                        row: Some(0),
+                        col: Some(0)
+                    },
+                    end: Some(CellRef {
+                        row: Some(19),
+                        col: Some(3)
+                    })
+                })
+            }
+        );
+
+        assert_eq!(
+            Selection::parse("'My sheet'", &[])?,
+            Selection {
+                sheet: Some("My sheet".into()),
+                range: None
+            }
+        );
+
+        // Sheet names which could be mistaken for cell references
+        for name in ["Sheet1", "Data", "ABC"] {
+            assert_eq!(
+                Selection::parse(name, &[])?,
+                Selection {
+                    sheet: Some(name.into()),
+                    range: None
+                }
+            );
+        }
+
+        // Sheet names take precedence over cell references
+        assert_eq!(
+            Selection::parse("AB1", &["AB1".to_string()])?,
+            Selection {
+                sheet: Some("AB1".into()),
+                range: None
+            }
+        );
+        assert_eq!(
+            Selection::parse("AB1", &[])?,
+            Selection {
+                sheet: None,
+                range: Some(CellRange {
+                    start: CellRef {
+                        row: Some(0),
+                        col: Some(27)
+                    },
+                    end: None
+                })
+            }
+        );
+        assert_eq!(Selection::parse("B:D", &[])?.sheet, None);
+
+        assert_eq!(
+            CellRef::parse("AA10")?,
+            CellRef {
+                row: Some(9),
+                col: Some(26)
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn apply() -> Result<()> {
+        let rows = vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]];
+
+        assert_eq!(
+            CellRange::parse("B2:C3")?.apply(rows.clone(), (0, 0)),
+            vec![vec![5, 6], vec![8, 9]]
+        );
+        assert_eq!(
+            CellRange::parse("B:B")?.apply(rows.clone(), (0, 0)),
+            vec![vec![2], vec![5], vec![8]]
+        );
+        assert_eq!(
+            CellRange::parse("B2")?.apply(rows, (1, 1)),
+            vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]
+        );
+
+        Ok(())
+    }
+}
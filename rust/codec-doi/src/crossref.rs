@@ -0,0 +1,386 @@
+use codec::{
+    common::{chrono::Utc, itertools::Itertools},
+    MessageLevel, Messages,
+};
+
+use crate::{
+    escape,
+    metadata::{is_valid_doi, is_valid_orcid, message, Contributor, Metadata, PartialDate},
+};
+
+/// The version of the Crossref deposit schema
+const VERSION: &str = "5.3.1";
+
+/// Encode metadata as a Crossref deposit
+///
+/// Articles that are part of a journal are deposited as a `journal_article`,
+/// all others as `posted_content` of type `preprint`. The publisher is used as
+/// both the depositor and the registrant.
+pub(super) fn encode(metadata: &Metadata) -> (String, Messages) {
+    let mut messages = Messages::none();
+    let mut error = |text: &str| messages.push(message(MessageLevel::Error, text.to_string()));
+
+    if metadata.publisher.is_none() {
+        error("Crossref deposits require a depositor name: add a publisher to the article");
+    }
+    if metadata.publisher_email.is_none() {
+        error("Crossref deposits require a depositor email: add an email to the publisher");
+    }
+    if metadata.title.is_none() {
+        error("Crossref deposits require a title");
+    }
+    if metadata.published.is_none() {
+        error("Crossref deposits require a publication date with a year");
+    }
+    match &metadata.doi {
+        Some(doi) if !is_valid_doi(doi) => error(&format!("Invalid DOI `{doi}`")),
+        None => error("Crossref deposits require a DOI"),
+        _ => {}
+    }
+    if metadata.url.is_none() {
+        error("Crossref deposits require a resource URL for the DOI: add a URL to the article");
+    }
+
+    let timestamp = Utc::now().format("%Y%m%d%H%M%S").to_string();
+
+    let publisher = escape(metadata.publisher.as_deref().unwrap_or_default());
+    let email = escape(metadata.publisher_email.as_deref().unwrap_or_default());
+    let head = format!(
+        r#"  <head>
+    <doi_batch_id>stencila-{timestamp}</doi_batch_id>
+    <timestamp>{timestamp}</timestamp>
+    <depositor>
+      <depositor_name>{publisher}</depositor_name>
+      <email_address>{email}</email_address>
+    </depositor>
+    <registrant>{publisher}</registrant>
+  </head>"#
+    );
+
+    let mut work = Vec::new();
+    let titles = format!(
+        "<titles><title>{}</title></titles>",
+        escape(metadata.title.as_deref().unwrap_or_default())
+    );
+    let contributors = contributors(&metadata.contributors, &mut messages);
+    let date = |tag: &str| {
+        metadata
+            .published
+            .as_ref()
+            .map(|date| date_element(tag, date))
+            .unwrap_or_default()
+    };
+
+    let body = if let Some(journal) = &metadata.journal {
+        let mut journal_metadata = vec![format!(
+            "<full_title>{}</full_title>",
+            escape(&journal.title)
+        )];
+        if let Some(issn) = &journal.issn {
+            journal_metadata.push(format!("<issn>{}</issn>", escape(issn)));
+        }
+
+        let issue = if journal.volume.is_some() || journal.issue.is_some() {
+            let mut issue = Vec::new();
+            if let Some(published) = &metadata.published {
+                issue.push(date_element(
+                    "publication_date media_type=\"online\"",
+                    published,
+                ));
+            }
+            if let Some(volume) = &journal.volume {
+                issue.push(format!(
+                    "<journal_volume><volume>{}</volume></journal_volume>",
+                    escape(volume)
+                ));
+            }
+            if let Some(number) = &journal.issue {
+                issue.push(format!("<issue>{}</issue>", escape(number)));
+            }
+            format!("\n      <journal_issue>{}</journal_issue>", issue.concat())
+        } else {
+            String::new()
+        };
+
+        work.push(titles);
+        work.extend(contributors);
+        work.extend(r#abstract(metadata));
+        work.push(date("publication_date media_type=\"online\""));
+        if let Some((first, last)) = &metadata.pages {
+            work.push(format!(
+                "<pages><first_page>{}</first_page>{}</pages>",
+                escape(first),
+                last.as_ref()
+                    .map(|last| format!("<last_page>{}</last_page>", escape(last)))
+                    .unwrap_or_default()
+            ));
+        }
+        work.extend(programs(metadata));
+        work.push(doi_data(metadata));
+        work.extend(citations(metadata));
+
+        format!(
+            r#"    <journal>
+      <journal_metadata>{}</journal_metadata>{issue}
+      <journal_article publication_type="full_text">
+        {}
+      </journal_article>
+    </journal>"#,
+            journal_metadata.concat(),
+            work.iter()
+                .filter(|item| !item.is_empty())
+                .join("\n        ")
+        )
+    } else {
+        work.extend(contributors);
+        work.push(titles);
+        work.push(date("posted_date"));
+        work.extend(r#abstract(metadata));
+        work.extend(programs(metadata));
+        work.push(doi_data(metadata));
+        work.extend(citations(metadata));
+
+        format!(
+            r#"    <posted_content type="preprint">
+      {}
+    </posted_content>"#,
+            work.iter().filter(|item| !item.is_empty()).join("\n      ")
+        )
+    };
+
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<doi_batch xmlns="http://www.crossref.org/schema/{VERSION}" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:jats="http://www.ncbi.nlm.nih.gov/JATS1" xmlns:fr="http://www.crossref.org/fundref.xsd" xmlns:ai="http://www.crossref.org/AccessIndicators.xsd" version="{VERSION}" xsi:schemaLocation="http://www.crossref.org/schema/{VERSION} https://www.crossref.org/schemas/crossref{VERSION}.xsd">
+{head}
+  <body>
+{body}
+  </body>
+</doi_batch>
+"#
+    );
+
+    (xml, messages)
+}
+
+/// Generate a date element (e.g. `<publication_date>`) with the parts in the order
+/// required by Crossref
+fn date_element(tag: &str, date: &PartialDate) -> String {
+    let name = tag.split_whitespace().next().unwrap_or(tag);
+    format!(
+        "<{tag}>{}{}<year>{}</year></{name}>",
+        date.month
+            .as_ref()
+            .map(|month| format!("<month>{month}</month>"))
+            .unwrap_or_default(),
+        date.day
+            .as_ref()
+            .map(|day| format!("<day>{day}</day>"))
+            .unwrap_or_default(),
+        date.year
+    )
+}
+
+/// Generate the `<contributors>` element
+fn contributors(contributors: &[Contributor], messages: &mut Messages) -> Option<String> {
+    if contributors.is_empty() {
+        return None;
+    }
+
+    let contributors = contributors
+        .iter()
+        .enumerate()
+        .map(|(index, contributor)| {
+            let sequence = if index == 0 { "first" } else { "additional" };
+
+            if !contributor.is_person {
+                return format!(
+                    r#"<organization sequence="{sequence}" contributor_role="author">{}</organization>"#,
+                    escape(&contributor.name)
+                );
+            }
+
+            let mut parts = Vec::new();
+            if let Some(given) = &contributor.given_names {
+                parts.push(format!("<given_name>{}</given_name>", escape(given)));
+            }
+            match &contributor.family_names {
+                Some(family) => parts.push(format!("<surname>{}</surname>", escape(family))),
+                None => {
+                    messages.push(message(
+                        MessageLevel::Error,
+                        format!(
+                            "Crossref requires a surname for each person but `{}` has no family names",
+                            contributor.name
+                        ),
+                    ));
+                    parts.push(format!("<surname>{}</surname>", escape(&contributor.name)));
+                }
+            }
+            if !contributor.affiliations.is_empty() {
+                parts.push(format!(
+                    "<affiliations>{}</affiliations>",
+                    contributor
+                        .affiliations
+                        .iter()
+                        .map(|affiliation| format!(
+                            "<institution><institution_name>{}</institution_name>{}</institution>",
+                            escape(&affiliation.name),
+                            affiliation
+                                .ror
+                                .as_ref()
+                                .map(|ror| format!(
+                                    r#"<institution_id type="ror">{}</institution_id>"#,
+                                    escape(ror)
+                                ))
+                                .unwrap_or_default()
+                        ))
+                        .join("")
+                ));
+            }
+            if let Some(orcid) = &contributor.orcid {
+                if is_valid_orcid(orcid) {
+                    parts.push(format!("<ORCID>{}</ORCID>", escape(orcid)));
+                } else {
+                    messages.push(message(
+                        MessageLevel::Warning,
+                        format!(
+                            "Invalid ORCID `{orcid}` for `{}` was not deposited",
+                            contributor.name
+                        ),
+                    ));
+                }
+            }
+
+            format!(
+                r#"<person_name sequence="{sequence}" contributor_role="author">{}</person_name>"#,
+                parts.concat()
+            )
+        })
+        .join("");
+
+    Some(format!("<contributors>{contributors}</contributors>"))
+}
+
+/// Generate the `<jats:abstract>` element
+fn r#abstract(metadata: &Metadata) -> Option<String> {
+    if metadata.r#abstract.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "<jats:abstract>{}</jats:abstract>",
+        metadata
+            .r#abstract
+            .iter()
+            .map(|para| format!("<jats:p>{}</jats:p>", escape(para)))
+            .join("")
+    ))
+}
+
+/// Generate the FundRef and AccessIndicators `<program>` elements for the funding
+/// and licenses of the article
+fn programs(metadata: &Metadata) -> Vec<String> {
+    let mut programs = Vec::new();
+
+    if !metadata.funding.is_empty() {
+        let groups = metadata
+            .funding
+            .iter()
+            .map(|funding| {
+                format!(
+                    r#"<fr:assertion name="fundgroup"><fr:assertion name="funder_name">{}{}</fr:assertion>{}</fr:assertion>"#,
+                    escape(&funding.funder),
+                    funding
+                        .funder_doi
+                        .as_ref()
+                        .map(|doi| format!(
+                            r#"<fr:assertion name="funder_identifier">{}</fr:assertion>"#,
+                            escape(doi)
+                        ))
+                        .unwrap_or_default(),
+                    funding
+                        .award_number
+                        .as_ref()
+                        .map(|number| format!(
+                            r#"<fr:assertion name="award_number">{}</fr:assertion>"#,
+                            escape(number)
+                        ))
+                        .unwrap_or_default()
+                )
+            })
+            .join("");
+        programs.push(format!(
+            r#"<fr:program name="fundref">{groups}</fr:program>"#
+        ));
+    }
+
+    let licenses = metadata
+        .licenses
+        .iter()
+        .filter(|license| license.starts_with("http"))
+        .map(|url| format!("<ai:license_ref>{}</ai:license_ref>", escape(url)))
+        .collect_vec();
+    if !licenses.is_empty() {
+        programs.push(format!(
+            r#"<ai:program name="AccessIndicators">{}</ai:program>"#,
+            licenses.concat()
+        ));
+    }
+
+    programs
+}
+
+/// Generate the `<doi_data>` element
+fn doi_data(metadata: &Metadata) -> String {
+    format!(
+        "<doi_data><doi>{}</doi><resource>{}</resource></doi_data>",
+        escape(metadata.doi.as_deref().unwrap_or_default()),
+        escape(metadata.url.as_deref().unwrap_or_default())
+    )
+}
+
+/// Generate the `<citation_list>` element
+///
+/// References with a DOI are deposited with it, along with any other structured
+/// metadata. References without a DOI are deposited as an unstructured citation.
+fn citations(metadata: &Metadata) -> Option<String> {
+    if metadata.references.is_empty() {
+        return None;
+    }
+
+    let citations = metadata
+        .references
+        .iter()
+        .enumerate()
+        .map(|(index, reference)| {
+            let mut parts = Vec::new();
+            for (tag, value) in [
+                ("journal_title", &reference.journal),
+                ("author", &reference.author),
+                ("volume", &reference.volume),
+                ("first_page", &reference.first_page),
+                ("cYear", &reference.year),
+                ("doi", &reference.doi),
+                ("article_title", &reference.title),
+            ] {
+                if let Some(value) = value {
+                    parts.push(format!("<{tag}>{}</{tag}>", escape(value)));
+                }
+            }
+            if reference.doi.is_none() && !reference.text.is_empty() {
+                parts.push(format!(
+                    "<unstructured_citation>{}</unstructured_citation>",
+                    escape(&reference.text)
+                ));
+            }
+
+            format!(
+                r#"<citation key="ref{}">{}</citation>"#,
+                index + 1,
+                parts.concat()
+            )
+        })
+        .join("");
+
+    Some(format!("<citation_list>{citations}</citation_list>"))
+}
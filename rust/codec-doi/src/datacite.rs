@@ -0,0 +1,253 @@
+use codec::{common::itertools::Itertools, MessageLevel, Messages};
+
+use crate::{
+    escape,
+    metadata::{is_valid_doi, is_valid_orcid, message, Metadata},
+};
+
+/// The version of the DataCite metadata schema
+const VERSION: &str = "4.5";
+
+/// Encode metadata as a DataCite metadata record
+///
+/// Articles that are part of a journal have a `resourceTypeGeneral` of
+/// `JournalArticle`, all others `Preprint`. References which have a DOI or URL are
+/// included as related identifiers; DataCite has no equivalent of Crossref's
+/// unstructured citations so others are not included.
+pub(super) fn encode(metadata: &Metadata) -> (String, Messages) {
+    let mut messages = Messages::none();
+    let mut error = |text: &str| messages.push(message(MessageLevel::Error, text.to_string()));
+
+    match &metadata.doi {
+        Some(doi) if !is_valid_doi(doi) => error(&format!("Invalid DOI `{doi}`")),
+        None => error("DataCite metadata requires a DOI"),
+        _ => {}
+    }
+    if metadata.contributors.is_empty() {
+        error("DataCite metadata requires at least one creator: add an author to the article");
+    }
+    if metadata.title.is_none() {
+        error("DataCite metadata requires a title");
+    }
+    if metadata.publisher.is_none() {
+        error("DataCite metadata requires a publisher");
+    }
+    if metadata.published.is_none() {
+        error("DataCite metadata requires a publication year");
+    }
+
+    let mut elements = vec![format!(
+        r#"<identifier identifierType="DOI">{}</identifier>"#,
+        escape(metadata.doi.as_deref().unwrap_or_default())
+    )];
+
+    let creators = metadata
+        .contributors
+        .iter()
+        .map(|contributor| {
+            let mut parts = Vec::new();
+            if contributor.is_person {
+                let name = match (&contributor.family_names, &contributor.given_names) {
+                    (Some(family), Some(given)) => format!("{family}, {given}"),
+                    _ => contributor.name.clone(),
+                };
+                parts.push(format!(
+                    r#"<creatorName nameType="Personal">{}</creatorName>"#,
+                    escape(&name)
+                ));
+                if let Some(given) = &contributor.given_names {
+                    parts.push(format!("<givenName>{}</givenName>", escape(given)));
+                }
+                if let Some(family) = &contributor.family_names {
+                    parts.push(format!("<familyName>{}</familyName>", escape(family)));
+                }
+            } else {
+                parts.push(format!(
+                    r#"<creatorName nameType="Organizational">{}</creatorName>"#,
+                    escape(&contributor.name)
+                ));
+            }
+
+            if let Some(orcid) = &contributor.orcid {
+                if is_valid_orcid(orcid) {
+                    parts.push(format!(
+                        r#"<nameIdentifier nameIdentifierScheme="ORCID" schemeURI="https://orcid.org">{}</nameIdentifier>"#,
+                        escape(orcid)
+                    ));
+                } else {
+                    messages.push(message(
+                        MessageLevel::Warning,
+                        format!(
+                            "Invalid ORCID `{orcid}` for `{}` was not included",
+                            contributor.name
+                        ),
+                    ));
+                }
+            }
+
+            for affiliation in &contributor.affiliations {
+                let identifier = affiliation
+                    .ror
+                    .as_ref()
+                    .map(|ror| {
+                        format!(
+                            r#" affiliationIdentifier="{}" affiliationIdentifierScheme="ROR" schemeURI="https://ror.org""#,
+                            escape(ror)
+                        )
+                    })
+                    .unwrap_or_default();
+                parts.push(format!(
+                    "<affiliation{identifier}>{}</affiliation>",
+                    escape(&affiliation.name)
+                ));
+            }
+
+            format!("<creator>{}</creator>", parts.concat())
+        })
+        .collect_vec();
+    if !creators.is_empty() {
+        elements.push(format!("<creators>{}</creators>", creators.concat()));
+    }
+
+    elements.push(format!(
+        "<titles><title>{}</title></titles>",
+        escape(metadata.title.as_deref().unwrap_or_default())
+    ));
+    elements.push(format!(
+        "<publisher>{}</publisher>",
+        escape(metadata.publisher.as_deref().unwrap_or_default())
+    ));
+    elements.push(format!(
+        "<publicationYear>{}</publicationYear>",
+        metadata
+            .published
+            .as_ref()
+            .map(|date| date.year.as_str())
+            .unwrap_or_default()
+    ));
+    elements.push(format!(
+        r#"<resourceType resourceTypeGeneral="{}"/>"#,
+        if metadata.journal.is_some() {
+            "JournalArticle"
+        } else {
+            "Preprint"
+        }
+    ));
+
+    if !metadata.keywords.is_empty() {
+        elements.push(format!(
+            "<subjects>{}</subjects>",
+            metadata
+                .keywords
+                .iter()
+                .map(|keyword| format!("<subject>{}</subject>", escape(keyword)))
+                .join("")
+        ));
+    }
+
+    if let Some(date) = &metadata.published {
+        elements.push(format!(
+            r#"<dates><date dateType="Issued">{}</date></dates>"#,
+            escape(&date.iso())
+        ));
+    }
+
+    let mut related = Vec::new();
+    if let Some(issn) = metadata
+        .journal
+        .as_ref()
+        .and_then(|journal| journal.issn.as_ref())
+    {
+        related.push(format!(
+            r#"<relatedIdentifier relatedIdentifierType="ISSN" relationType="IsPublishedIn">{}</relatedIdentifier>"#,
+            escape(issn)
+        ));
+    }
+    for reference in &metadata.references {
+        let (identifier_type, identifier) = match (&reference.doi, &reference.url) {
+            (Some(doi), ..) => ("DOI", doi),
+            (None, Some(url)) => ("URL", url),
+            (None, None) => continue,
+        };
+        related.push(format!(
+            r#"<relatedIdentifier relatedIdentifierType="{identifier_type}" relationType="References">{}</relatedIdentifier>"#,
+            escape(identifier)
+        ));
+    }
+    if !related.is_empty() {
+        elements.push(format!(
+            "<relatedIdentifiers>{}</relatedIdentifiers>",
+            related.concat()
+        ));
+    }
+
+    if !metadata.licenses.is_empty() {
+        elements.push(format!(
+            "<rightsList>{}</rightsList>",
+            metadata
+                .licenses
+                .iter()
+                .map(|license| if license.starts_with("http") {
+                    format!(r#"<rights rightsURI="{}"/>"#, escape(license))
+                } else {
+                    format!("<rights>{}</rights>", escape(license))
+                })
+                .join("")
+        ));
+    }
+
+    if !metadata.r#abstract.is_empty() {
+        elements.push(format!(
+            r#"<descriptions><description descriptionType="Abstract">{}</description></descriptions>"#,
+            escape(&metadata.r#abstract.join("\n\n"))
+        ));
+    }
+
+    if !metadata.funding.is_empty() {
+        elements.push(format!(
+            "<fundingReferences>{}</fundingReferences>",
+            metadata
+                .funding
+                .iter()
+                .map(|funding| {
+                    let identifier = match (&funding.funder_doi, &funding.funder_ror) {
+                        (Some(doi), ..) => format!(
+                            r#"<funderIdentifier funderIdentifierType="Crossref Funder ID">{}</funderIdentifier>"#,
+                            escape(doi)
+                        ),
+                        (None, Some(ror)) => format!(
+                            r#"<funderIdentifier funderIdentifierType="ROR" schemeURI="https://ror.org">{}</funderIdentifier>"#,
+                            escape(ror)
+                        ),
+                        (None, None) => String::new(),
+                    };
+                    format!(
+                        "<fundingReference><funderName>{}</funderName>{identifier}{}{}</fundingReference>",
+                        escape(&funding.funder),
+                        funding
+                            .award_number
+                            .as_ref()
+                            .map(|number| format!("<awardNumber>{}</awardNumber>", escape(number)))
+                            .unwrap_or_default(),
+                        funding
+                            .award_title
+                            .as_ref()
+                            .map(|title| format!("<awardTitle>{}</awardTitle>", escape(title)))
+                            .unwrap_or_default()
+                    )
+                })
+                .join("")
+        ));
+    }
+
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<resource xmlns="http://datacite.org/schema/kernel-4" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://datacite.org/schema/kernel-4 https://schema.datacite.org/meta/kernel-{VERSION}/metadata.xsd">
+  {}
+</resource>
+"#,
+        elements.join("\n  ")
+    );
+
+    (xml, messages)
+}
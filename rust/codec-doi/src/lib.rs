@@ -0,0 +1,312 @@
+use codec::{
+    common::{
+        async_trait::async_trait,
+        eyre::{bail, Result},
+    },
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, EncodeInfo, EncodeOptions, NodeType,
+};
+
+mod crossref;
+mod datacite;
+mod metadata;
+
+use metadata::Metadata;
+
+/// A codec for the metadata used to register DOIs
+///
+/// Encodes an `Article` to a Crossref deposit (`doi_batch`) or to a DataCite
+/// metadata record (`resource`).
+///
+/// Validation against the Crossref and DataCite XSDs is out of scope: the schemas
+/// are not bundled and no XSD validator is available to the codec. Instead, there
+/// are checks for the fields that the schemas require, and for the format of DOIs
+/// and ORCIDs, with any problems reported as messages in the returned [`EncodeInfo`].
+/// An absence of messages does not guarantee that a deposit is schema valid, so
+/// deposits should still be checked by the registration agency (e.g. using its
+/// test system) before they are submitted.
+pub struct DoiCodec;
+
+#[async_trait]
+impl Codec for DoiCodec {
+    fn name(&self) -> &str {
+        "doi"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_string(&self) -> bool {
+        false
+    }
+
+    fn supports_from_path(&self) -> bool {
+        false
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Crossref | Format::DataCite => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        let Node::Article(article) = node else {
+            bail!("Only articles can be encoded to DOI metadata")
+        };
+
+        let metadata = Metadata::from_article(article);
+
+        let (xml, messages) = match options.and_then(|options| options.format) {
+            Some(Format::DataCite) => datacite::encode(&metadata),
+            _ => crossref::encode(&metadata),
+        };
+
+        Ok((
+            xml,
+            EncodeInfo {
+                messages,
+                losses: metadata.losses,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+/// Escape a string for use in XML
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::{itertools::Itertools, tokio},
+        schema::{
+            shortcuts::{p, t},
+            Article, ArticleOptions, Author, ContactPoint, CreativeWorkTypeOrText, Date, Grant,
+            GrantOptions, GrantOrMonetaryGrant, Organization, OrganizationOptions, Person,
+            PersonOptions, PersonOrOrganization, Primitive, PropertyValue, PropertyValueOrString,
+            Text,
+        },
+        MessageLevel,
+    };
+
+    use super::*;
+
+    fn article() -> Node {
+        let university = Organization {
+            name: Some("University of Somewhere".into()),
+            options: Box::new(OrganizationOptions {
+                identifiers: Some(vec![PropertyValueOrString::String(
+                    "https://ror.org/02mhbdp94".into(),
+                )]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let funder = Organization {
+            name: Some("Science Foundation".into()),
+            options: Box::new(OrganizationOptions {
+                identifiers: Some(vec![PropertyValueOrString::PropertyValue(PropertyValue {
+                    property_id: Some("https://registry.identifiers.org/registry/doi".into()),
+                    value: Primitive::String("10.13039/100000001".into()),
+                    ..Default::default()
+                })]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        Node::Article(Article {
+            title: Some(vec![t("Fish & chips")]),
+            authors: Some(vec![Author::Person(Person {
+                given_names: Some(vec!["Jane".into()]),
+                family_names: Some(vec!["Doe".into()]),
+                affiliations: Some(vec![university]),
+                options: Box::new(PersonOptions {
+                    identifiers: Some(vec![PropertyValueOrString::PropertyValue(PropertyValue {
+                        property_id: Some("https://registry.identifiers.org/registry/orcid".into()),
+                        value: Primitive::String("0000-0002-1825-0097".into()),
+                        ..Default::default()
+                    })]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })]),
+            date_published: Some(Date::new("2024-03-05".into())),
+            r#abstract: Some(vec![p([t("An abstract.")])]),
+            references: Some(vec![
+                CreativeWorkTypeOrText::Text(Text::from("https://doi.org/10.1234/ref.1")),
+                CreativeWorkTypeOrText::Text(Text::from("Smith (2020) A book")),
+            ]),
+            options: Box::new(ArticleOptions {
+                identifiers: Some(vec![PropertyValueOrString::String(
+                    "https://doi.org/10.5555/fish.123".into(),
+                )]),
+                url: Some("https://example.org/fish".into()),
+                publisher: Some(PersonOrOrganization::Organization(Organization {
+                    name: Some("Example Press".into()),
+                    options: Box::new(OrganizationOptions {
+                        contact_points: Some(vec![ContactPoint {
+                            emails: Some(vec!["doi@example.org".into()]),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })),
+                funded_by: Some(vec![GrantOrMonetaryGrant::Grant(Grant {
+                    options: Box::new(GrantOptions {
+                        identifiers: Some(vec![PropertyValueOrString::String("ABC-123".into())]),
+                        sponsors: Some(vec![PersonOrOrganization::Organization(funder)]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })]),
+                licenses: Some(vec![CreativeWorkTypeOrText::Text(Text::from(
+                    "https://creativecommons.org/licenses/by/4.0/",
+                ))]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn crossref() -> Result<()> {
+        let (xml, info) = DoiCodec
+            .to_string(
+                &article(),
+                Some(EncodeOptions {
+                    format: Some(Format::Crossref),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        assert!(info.messages.is_empty());
+        for expected in [
+            "<depositor_name>Example Press</depositor_name>",
+            "<email_address>doi@example.org</email_address>",
+            r#"<posted_content type="preprint">"#,
+            "<titles><title>Fish &amp; chips</title></titles>",
+            "<given_name>Jane</given_name><surname>Doe</surname>",
+            r#"<institution_id type="ror">https://ror.org/02mhbdp94</institution_id>"#,
+            "<ORCID>https://orcid.org/0000-0002-1825-0097</ORCID>",
+            "<posted_date><month>03</month><day>05</day><year>2024</year></posted_date>",
+            "<jats:abstract><jats:p>An abstract.</jats:p></jats:abstract>",
+            r#"<fr:assertion name="funder_identifier">https://doi.org/10.13039/100000001</fr:assertion>"#,
+            r#"<fr:assertion name="award_number">ABC-123</fr:assertion>"#,
+            "<ai:license_ref>https://creativecommons.org/licenses/by/4.0/</ai:license_ref>",
+            "<doi>10.5555/fish.123</doi><resource>https://example.org/fish</resource>",
+            r#"<citation key="ref1"><doi>10.1234/ref.1</doi></citation>"#,
+            "<unstructured_citation>Smith (2020) A book</unstructured_citation>",
+        ] {
+            assert!(xml.contains(expected), "missing `{expected}` in:\n{xml}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn datacite() -> Result<()> {
+        let (xml, info) = DoiCodec
+            .to_string(
+                &article(),
+                Some(EncodeOptions {
+                    format: Some(Format::DataCite),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        assert!(info.messages.is_empty());
+        for expected in [
+            r#"<identifier identifierType="DOI">10.5555/fish.123</identifier>"#,
+            r#"<creatorName nameType="Personal">Doe, Jane</creatorName>"#,
+            r#"<affiliation affiliationIdentifier="https://ror.org/02mhbdp94" affiliationIdentifierScheme="ROR" schemeURI="https://ror.org">University of Somewhere</affiliation>"#,
+            "<publisher>Example Press</publisher>",
+            "<publicationYear>2024</publicationYear>",
+            r#"<resourceType resourceTypeGeneral="Preprint"/>"#,
+            r#"<relatedIdentifier relatedIdentifierType="DOI" relationType="References">10.1234/ref.1</relatedIdentifier>"#,
+            r#"<rights rightsURI="https://creativecommons.org/licenses/by/4.0/"/>"#,
+            "<funderName>Science Foundation</funderName>",
+            "<awardNumber>ABC-123</awardNumber>",
+        ] {
+            assert!(xml.contains(expected), "missing `{expected}` in:\n{xml}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn validation() -> Result<()> {
+        let article = Node::Article(Article {
+            authors: Some(vec![Author::Person(Person {
+                options: Box::new(PersonOptions {
+                    name: Some("Prince".into()),
+                    url: Some("https://orcid.org/1234".into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })]),
+            options: Box::new(ArticleOptions {
+                identifiers: Some(vec![PropertyValueOrString::String("doi:11.1/x".into())]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        let (.., info) = DoiCodec
+            .to_string(
+                &article,
+                Some(EncodeOptions {
+                    format: Some(Format::Crossref),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let messages = info
+            .messages
+            .iter()
+            .map(|message| (&message.level, message.message.as_str()))
+            .collect_vec();
+        assert_eq!(
+            messages,
+            vec![
+                (&MessageLevel::Error, "Crossref deposits require a depositor name: add a publisher to the article"),
+                (&MessageLevel::Error, "Crossref deposits require a depositor email: add an email to the publisher"),
+                (&MessageLevel::Error, "Crossref deposits require a title"),
+                (&MessageLevel::Error, "Crossref deposits require a publication date with a year"),
+                (&MessageLevel::Error, "Invalid DOI `11.1/x`"),
+                (&MessageLevel::Error, "Crossref deposits require a resource URL for the DOI: add a URL to the article"),
+                (&MessageLevel::Error, "Crossref requires a surname for each person but `Prince` has no family names"),
+                (&MessageLevel::Warning, "Invalid ORCID `https://orcid.org/1234` for `Prince` was not deposited"),
+            ]
+        );
+
+        Ok(())
+    }
+}
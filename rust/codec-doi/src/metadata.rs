@@ -0,0 +1,614 @@
+use codec::{
+    common::{itertools::Itertools, once_cell::sync::Lazy, regex::Regex},
+    schema::{
+        Article, Author, AuthorRoleAuthor, CreativeWorkTypeOrText, Date, GrantOrMonetaryGrant,
+        Organization, Person, PersonOrOrganization, Primitive, PropertyValueOrString,
+    },
+    Losses, Message, MessageLevel,
+};
+use codec_csl::{article_to_item, work_to_item, Item};
+use codec_text_trait::to_text;
+
+/// Metadata about an article needed to register its DOI
+///
+/// Extracted from an [`Article`] once so that the Crossref and DataCite
+/// encoders share the same interpretation of identifiers, names and funding.
+pub(crate) struct Metadata {
+    /// The DOI of the article, without any `https://doi.org/` prefix
+    pub doi: Option<String>,
+
+    /// The URL of the landing page of the article
+    pub url: Option<String>,
+
+    /// The title of the article as plain text
+    pub title: Option<String>,
+
+    /// The paragraphs of the abstract as plain text
+    pub r#abstract: Vec<String>,
+
+    /// The authors of the article
+    pub contributors: Vec<Contributor>,
+
+    /// The date that the article was published
+    pub published: Option<PartialDate>,
+
+    /// The name of the publisher
+    pub publisher: Option<String>,
+
+    /// An email address for the publisher
+    pub publisher_email: Option<String>,
+
+    /// The journal that the article is part of (if any)
+    pub journal: Option<Journal>,
+
+    /// The first and last pages of the article within the journal
+    pub pages: Option<(String, Option<String>)>,
+
+    /// The keywords of the article
+    pub keywords: Vec<String>,
+
+    /// The licenses of the article (URLs or names)
+    pub licenses: Vec<String>,
+
+    /// The funding of the work described in the article
+    pub funding: Vec<Funding>,
+
+    /// The works referenced by the article
+    pub references: Vec<Reference>,
+
+    /// Properties of the article that are not represented in the metadata
+    pub losses: Losses,
+}
+
+/// A contributor to an article
+pub(crate) struct Contributor {
+    /// The given names of a person
+    pub given_names: Option<String>,
+
+    /// The family names of a person
+    pub family_names: Option<String>,
+
+    /// The full name of the person, or the name of the organization
+    pub name: String,
+
+    /// Whether the contributor is a person (rather than an organization)
+    pub is_person: bool,
+
+    /// The ORCID of a person, as a URL
+    pub orcid: Option<String>,
+
+    /// The organizations the person is affiliated with
+    pub affiliations: Vec<Affiliation>,
+}
+
+/// An organization that a contributor is affiliated with
+pub(crate) struct Affiliation {
+    /// The name of the organization
+    pub name: String,
+
+    /// The ROR identifier of the organization, as a URL
+    pub ror: Option<String>,
+}
+
+/// A date which may only have a year, or a year and month
+pub(crate) struct PartialDate {
+    pub year: String,
+    pub month: Option<String>,
+    pub day: Option<String>,
+}
+
+impl PartialDate {
+    /// Parse a schema [`Date`]
+    ///
+    /// Returns `None` if the date does not start with a four digit year.
+    fn parse(date: &Date) -> Option<Self> {
+        let value = date.value.split('T').next().unwrap_or_default();
+        let mut parts = value.split('-');
+
+        let year = parts
+            .next()
+            .filter(|year| year.len() == 4 && year.chars().all(|char| char.is_ascii_digit()))?
+            .to_string();
+        let month = parts.next().map(String::from);
+        let day = parts.next().map(String::from);
+
+        Some(Self { year, month, day })
+    }
+
+    /// The date in ISO 8601 format, to whatever precision is available
+    pub fn iso(&self) -> String {
+        [Some(&self.year), self.month.as_ref(), self.day.as_ref()]
+            .into_iter()
+            .flatten()
+            .join("-")
+    }
+}
+
+/// A journal that an article is part of
+pub(crate) struct Journal {
+    pub title: String,
+    pub issn: Option<String>,
+    pub volume: Option<String>,
+    pub issue: Option<String>,
+}
+
+/// A funder, and optionally the award, supporting the work
+pub(crate) struct Funding {
+    /// The name of the funder
+    pub funder: String,
+
+    /// The Crossref Funder Registry DOI of the funder, as a URL
+    pub funder_doi: Option<String>,
+
+    /// The ROR identifier of the funder, as a URL
+    pub funder_ror: Option<String>,
+
+    /// The number of the award, if any
+    pub award_number: Option<String>,
+
+    /// The title of the award, if any
+    pub award_title: Option<String>,
+}
+
+/// A work referenced by an article
+pub(crate) struct Reference {
+    pub doi: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub journal: Option<String>,
+    pub volume: Option<String>,
+    pub first_page: Option<String>,
+    pub year: Option<String>,
+
+    /// A plain text rendering of the reference
+    pub text: String,
+}
+
+impl Metadata {
+    /// Extract metadata from an [`Article`]
+    pub fn from_article(article: &Article) -> Self {
+        let mut losses = Losses::none();
+
+        let doi = article
+            .options
+            .identifiers
+            .iter()
+            .flatten()
+            .find_map(identifier_doi)
+            .or_else(|| article.options.url.as_deref().and_then(strip_doi));
+
+        let url = article
+            .options
+            .url
+            .clone()
+            .filter(|url| strip_doi(url).is_none());
+
+        let title = article
+            .title
+            .as_ref()
+            .map(to_text)
+            .filter(|title| !title.trim().is_empty());
+
+        let r#abstract = match &article.r#abstract {
+            Some(blocks) => blocks
+                .iter()
+                .map(to_text)
+                .map(|para| para.trim().to_string())
+                .filter(|para| !para.is_empty())
+                .collect(),
+            None => article
+                .description
+                .iter()
+                .map(|description| description.trim().to_string())
+                .collect(),
+        };
+
+        let mut contributors = Vec::new();
+        for author in article.authors.iter().flatten() {
+            match author_to_contributor(author) {
+                Some(contributor) => contributors.push(contributor),
+                None => losses.add("Article.authors"),
+            }
+        }
+
+        let published = article.date_published.as_ref().and_then(PartialDate::parse);
+
+        let (publisher, publisher_email) = match &article.options.publisher {
+            Some(PersonOrOrganization::Organization(org)) => (
+                org.name.clone().or_else(|| org.options.legal_name.clone()),
+                org.options
+                    .contact_points
+                    .iter()
+                    .flatten()
+                    .flat_map(|point| point.emails.iter().flatten())
+                    .next()
+                    .cloned(),
+            ),
+            Some(PersonOrOrganization::Person(person)) => (
+                Some(person.as_string()),
+                person.options.emails.iter().flatten().next().cloned(),
+            ),
+            None => (None, None),
+        };
+
+        // Use the CSL representation to resolve the journal, volume, issue and pages
+        // from the chain of `isPartOf` properties
+        let item = article_to_item(article, &mut Losses::none());
+        let journal = item.container_title.clone().map(|title| Journal {
+            title,
+            issn: item.issn.clone(),
+            volume: item.volume.clone(),
+            issue: item.issue.clone(),
+        });
+        let pages = item
+            .page
+            .as_ref()
+            .map(|page| match page.split_once(['-', '–']) {
+                Some((first, last)) => (first.trim().to_string(), Some(last.trim().to_string())),
+                None => (page.trim().to_string(), None),
+            });
+
+        let keywords = article.keywords.clone().unwrap_or_default();
+
+        let licenses = article
+            .options
+            .licenses
+            .iter()
+            .flatten()
+            .map(|license| to_text(license).trim().to_string())
+            .filter(|license| !license.is_empty())
+            .collect();
+
+        let funding = funding(article, &mut losses);
+
+        let mut references = Vec::new();
+        for reference in article.references.iter().flatten() {
+            match reference_to_reference(reference) {
+                Some(reference) => references.push(reference),
+                None => losses.add("Article.references"),
+            }
+        }
+
+        if !article.content.is_empty() {
+            losses.add("Article.content");
+        }
+
+        Self {
+            doi,
+            url,
+            title,
+            r#abstract,
+            contributors,
+            published,
+            publisher,
+            publisher_email,
+            journal,
+            pages,
+            keywords,
+            licenses,
+            funding,
+            references,
+            losses,
+        }
+    }
+}
+
+/// A regex for validating DOIs
+///
+/// This is the pattern recommended by Crossref which matches the vast majority of DOIs.
+static DOI_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^10\.\d{4,9}/[-._;()/:A-Za-z0-9<>\[\]]+$").expect("invalid regex"));
+
+/// A regex for validating ORCID URLs
+static ORCID_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^https://orcid\.org/\d{4}-\d{4}-\d{4}-\d{3}[\dX]$").expect("invalid regex")
+});
+
+/// Whether a DOI is valid
+pub(crate) fn is_valid_doi(doi: &str) -> bool {
+    DOI_REGEX.is_match(doi)
+}
+
+/// Whether an ORCID URL is valid
+pub(crate) fn is_valid_orcid(orcid: &str) -> bool {
+    ORCID_REGEX.is_match(orcid)
+}
+
+/// Create a message with the given level
+pub(crate) fn message(level: MessageLevel, message: String) -> Message {
+    Message {
+        level,
+        message,
+        ..Default::default()
+    }
+}
+
+/// Strip the prefix from a DOI URL or `doi:` string
+///
+/// Returns `None` if the string is not a DOI.
+fn strip_doi(value: &str) -> Option<String> {
+    let value = value.trim();
+    for prefix in [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ] {
+        if let Some(doi) = value.strip_prefix(prefix) {
+            return Some(doi.to_string());
+        }
+    }
+    value.starts_with("10.").then(|| value.to_string())
+}
+
+/// Get the value of an identifier having a `propertyID` containing `scheme`, or
+/// a string (usually a URL) containing `scheme`
+fn identifier_value(identifier: &PropertyValueOrString, scheme: &str) -> Option<String> {
+    match identifier {
+        PropertyValueOrString::PropertyValue(identifier) => {
+            let property_id = identifier.property_id.as_deref()?.to_lowercase();
+            if !(property_id == scheme || property_id.ends_with(&["/", scheme].concat())) {
+                return None;
+            }
+            match &identifier.value {
+                Primitive::String(value) => Some(value.trim().to_string()),
+                _ => None,
+            }
+        }
+        PropertyValueOrString::String(value) => value
+            .contains(&[scheme, ".org/"].concat())
+            .then(|| value.trim().to_string()),
+    }
+}
+
+/// Get a DOI from an identifier
+fn identifier_doi(identifier: &PropertyValueOrString) -> Option<String> {
+    match identifier {
+        PropertyValueOrString::String(value) => strip_doi(value),
+        _ => {
+            let value = identifier_value(identifier, "doi")?;
+            Some(strip_doi(&value).unwrap_or(value))
+        }
+    }
+}
+
+/// Get an identifier as a URL, adding the `https://<scheme>.org/` prefix if necessary
+fn identifier_url(
+    identifiers: &Option<Vec<PropertyValueOrString>>,
+    scheme: &str,
+) -> Option<String> {
+    identifiers
+        .iter()
+        .flatten()
+        .find_map(|identifier| identifier_value(identifier, scheme))
+        .map(|value| {
+            if value.starts_with("http") {
+                value
+            } else {
+                format!("https://{scheme}.org/{value}")
+            }
+        })
+}
+
+/// Convert an [`Author`] to a [`Contributor`]
+fn author_to_contributor(author: &Author) -> Option<Contributor> {
+    match author {
+        Author::Person(person) => Some(person_to_contributor(person)),
+        Author::Organization(org) => org_to_contributor(org),
+        Author::AuthorRole(role) => match &role.author {
+            AuthorRoleAuthor::Person(person) => Some(person_to_contributor(person)),
+            AuthorRoleAuthor::Organization(org) => org_to_contributor(org),
+            _ => None,
+        },
+        Author::SoftwareApplication(..) => None,
+    }
+}
+
+/// Convert a [`Person`] to a [`Contributor`]
+fn person_to_contributor(person: &Person) -> Contributor {
+    let orcid = identifier_url(&person.options.identifiers, "orcid").or_else(|| {
+        person
+            .options
+            .url
+            .clone()
+            .filter(|url| url.contains("orcid.org/"))
+    });
+
+    let affiliations = person
+        .affiliations
+        .iter()
+        .flatten()
+        .filter_map(|org| {
+            Some(Affiliation {
+                name: org_name(org)?,
+                ror: identifier_url(&org.options.identifiers, "ror"),
+            })
+        })
+        .collect();
+
+    Contributor {
+        given_names: person.given_names.as_ref().map(|names| names.join(" ")),
+        family_names: person.family_names.as_ref().map(|names| names.join(" ")),
+        name: person
+            .options
+            .name
+            .clone()
+            .unwrap_or_else(|| person.as_string()),
+        is_person: true,
+        orcid,
+        affiliations,
+    }
+}
+
+/// Convert an [`Organization`] to a [`Contributor`]
+fn org_to_contributor(org: &Organization) -> Option<Contributor> {
+    Some(Contributor {
+        given_names: None,
+        family_names: None,
+        name: org_name(org)?,
+        is_person: false,
+        orcid: None,
+        affiliations: Vec::new(),
+    })
+}
+
+/// Get the name of an [`Organization`]
+fn org_name(org: &Organization) -> Option<String> {
+    org.name
+        .clone()
+        .or_else(|| org.options.legal_name.clone())
+        .filter(|name| !name.trim().is_empty())
+}
+
+/// Get the funding of an article from its grants and funders
+///
+/// Each funder of each grant becomes a separate [`Funding`] with the award
+/// number of the grant. Funders of the article that are not associated with a
+/// grant are added without an award number.
+fn funding(article: &Article, losses: &mut Losses) -> Vec<Funding> {
+    let mut funding = Vec::new();
+
+    for grant in article.options.funded_by.iter().flatten() {
+        let (funders, identifiers, name) = match grant {
+            GrantOrMonetaryGrant::Grant(grant) => (
+                grant.options.sponsors.as_ref(),
+                &grant.options.identifiers,
+                &grant.options.name,
+            ),
+            GrantOrMonetaryGrant::MonetaryGrant(grant) => (
+                grant
+                    .options
+                    .funders
+                    .as_ref()
+                    .or(grant.options.sponsors.as_ref()),
+                &grant.options.identifiers,
+                &grant.options.name,
+            ),
+        };
+
+        let award_number = identifiers
+            .iter()
+            .flatten()
+            .find_map(|identifier| match identifier {
+                PropertyValueOrString::PropertyValue(identifier) => match &identifier.value {
+                    Primitive::String(value) => Some(value.clone()),
+                    Primitive::Integer(value) => Some(value.to_string()),
+                    _ => None,
+                },
+                PropertyValueOrString::String(value) => Some(value.clone()),
+            });
+
+        let funders = funders.map(|funders| funders.iter().filter_map(funder).collect_vec());
+        match funders {
+            Some(funders) if !funders.is_empty() => {
+                for (funder, funder_doi, funder_ror) in funders {
+                    funding.push(Funding {
+                        funder,
+                        funder_doi,
+                        funder_ror,
+                        award_number: award_number.clone(),
+                        award_title: name.clone(),
+                    })
+                }
+            }
+            _ => losses.add("Grant.funders"),
+        }
+    }
+
+    for (funder, funder_doi, funder_ror) in
+        article.options.funders.iter().flatten().filter_map(funder)
+    {
+        if !funding.iter().any(|existing| existing.funder == funder) {
+            funding.push(Funding {
+                funder,
+                funder_doi,
+                funder_ror,
+                award_number: None,
+                award_title: None,
+            })
+        }
+    }
+
+    funding
+}
+
+/// Get the name, Funder Registry DOI, and ROR of a funder
+fn funder(funder: &PersonOrOrganization) -> Option<(String, Option<String>, Option<String>)> {
+    match funder {
+        PersonOrOrganization::Organization(org) => Some((
+            org_name(org)?,
+            org.options
+                .identifiers
+                .iter()
+                .flatten()
+                .find_map(identifier_doi)
+                .map(|doi| ["https://doi.org/", &doi].concat()),
+            identifier_url(&org.options.identifiers, "ror"),
+        )),
+        PersonOrOrganization::Person(person) => Some((person.as_string(), None, None)),
+    }
+}
+
+/// Convert a reference of an article to a [`Reference`]
+fn reference_to_reference(reference: &CreativeWorkTypeOrText) -> Option<Reference> {
+    if let CreativeWorkTypeOrText::CreativeWorkType(work) = reference {
+        if let Some(item) = work_to_item(work, &mut Losses::none()) {
+            return Some(item_to_reference(item));
+        }
+    }
+
+    let text = to_text(reference).trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some(Reference {
+        doi: strip_doi(&text).filter(|doi| is_valid_doi(doi)),
+        url: text.starts_with("http").then(|| text.clone()),
+        title: None,
+        author: None,
+        journal: None,
+        volume: None,
+        first_page: None,
+        year: None,
+        text,
+    })
+}
+
+/// Convert a CSL [`Item`] to a [`Reference`]
+fn item_to_reference(item: Item) -> Reference {
+    let author = item
+        .author
+        .first()
+        .and_then(|name| name.family.clone().or_else(|| name.literal.clone()));
+    let year = item.issued.as_ref().and_then(|date| date.year());
+    let first_page = item
+        .page
+        .as_ref()
+        .and_then(|page| page.split(['-', '–']).next())
+        .map(|page| page.trim().to_string());
+
+    let authors = item.author.iter().map(|name| name.to_inverted()).join(", ");
+    let text = [
+        (!authors.is_empty()).then_some(authors),
+        year.as_ref().map(|year| format!("({year})")),
+        item.title.clone(),
+        item.container_title.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .join(". ");
+
+    Reference {
+        doi: item.doi.as_deref().and_then(strip_doi).or(item.doi),
+        url: item.url,
+        title: item.title,
+        author,
+        journal: item.container_title,
+        volume: item.volume,
+        first_page,
+        year,
+        text,
+    }
+}
@@ -0,0 +1,640 @@
+use std::{
+    fs::{read_dir, File},
+    io::Write,
+    path::Path,
+};
+
+use latex2mathml::{latex_to_mathml, DisplayStyle};
+
+use codec::{
+    common::{
+        chrono::Utc,
+        eyre::{bail, Result},
+        itertools::Itertools,
+        once_cell::sync::Lazy,
+        regex::{Captures, Regex},
+        tempfile, tokio,
+        uuid::Uuid,
+        zip::{write::FileOptions, CompressionMethod, ZipWriter},
+    },
+    format::Format,
+    schema::{
+        shortcuts::{p, t},
+        Article, Author, AuthorRoleAuthor, Block, Inline, Node, Person, PropertyValueOrString,
+        VisitorMut, WalkControl,
+    },
+    Codec, EncodeInfo, EncodeOptions, Losses,
+};
+use codec_text_trait::to_text;
+
+/// Encode an `Article` to an EPUB file
+///
+/// The article is split into XHTML chapters at top-level `Section`s or, if
+/// there are none, at the highest level headings. The navigation document
+/// is generated from the hierarchy of headings across all chapters.
+pub(super) async fn encode(
+    node: &Node,
+    path: &Path,
+    options: Option<EncodeOptions>,
+) -> Result<EncodeInfo> {
+    let Node::Article(article) = node else {
+        bail!("Only articles can be encoded to EPUB")
+    };
+    let options = options.unwrap_or_default();
+
+    let mut article = article.clone();
+    let mut losses = Losses::none();
+
+    // Extract images into a temporary directory so that they can be packaged
+    let media_dir = tempfile::tempdir()?;
+    let src_dir = options
+        .from_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    node_media::extract_media(&mut article, &src_dir, media_dir.path(), |_, file_name| {
+        format!("media/{file_name}")
+    });
+
+    // Replace math with placeholders for MathML
+    let mut math = Math::default();
+    math.visit(&mut article);
+    losses.merge(math.losses);
+
+    let title = article
+        .title
+        .as_ref()
+        .map(to_text)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    // Split into chapters and encode each to XHTML
+    let mut headings = Headings::default();
+    let mut chapters = Vec::new();
+    for (index, mut blocks) in split(std::mem::take(&mut article.content))
+        .into_iter()
+        .enumerate()
+    {
+        let file = format!("chapter-{}.xhtml", index + 1);
+
+        let first = headings.entries.len();
+        headings.file.clone_from(&file);
+        headings.visit(&mut blocks);
+        let chapter_title = headings
+            .entries
+            .get(first)
+            .map(|entry| entry.text.clone())
+            .unwrap_or_else(|| title.clone());
+
+        let (html, info) = codec_html::HtmlCodec
+            .to_string(
+                &Node::Article(Article::new(blocks)),
+                Some(EncodeOptions {
+                    standalone: Some(false),
+                    compact: Some(true),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        losses.merge(info.losses);
+
+        let has_math = html.contains(PLACEHOLDER);
+        let html = PLACEHOLDER_REGEX
+            .replace_all(&html, |captures: &Captures| {
+                captures[1]
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| math.mathml.get(index))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .to_string();
+
+        chapters.push(Chapter {
+            file,
+            xhtml: xhtml(&chapter_title, &html),
+            has_math,
+        });
+    }
+
+    // Collect the media files
+    let mut media = Vec::new();
+    for entry in read_dir(media_dir.path())?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        media.push((name, entry.path()));
+    }
+
+    let nav = nav(&title, &headings.entries, &chapters);
+    let opf = opf(&article, &title, &chapters, &media, &mut losses);
+
+    // Write the package, ensuring that the `mimetype` file is first and uncompressed
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let file = File::create(path)?;
+    let mut zip = ZipWriter::new(file);
+
+    zip.start_file(
+        "mimetype",
+        FileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(Format::Epub.media_type().as_bytes())?;
+
+    let options = FileOptions::default();
+    for (name, content) in [
+        ("META-INF/container.xml", CONTAINER.to_string()),
+        ("OEBPS/content.opf", opf),
+        ("OEBPS/nav.xhtml", nav),
+    ] {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+    for chapter in &chapters {
+        zip.start_file(format!("OEBPS/{}", chapter.file), options)?;
+        zip.write_all(chapter.xhtml.as_bytes())?;
+    }
+    for (name, path) in &media {
+        zip.start_file(format!("OEBPS/media/{name}"), options)?;
+        zip.write_all(&std::fs::read(path)?)?;
+    }
+    zip.finish()?;
+
+    Ok(EncodeInfo {
+        losses,
+        ..Default::default()
+    })
+}
+
+/// A chapter of the EPUB
+struct Chapter {
+    /// The file name of the chapter within the package
+    file: String,
+
+    /// The XHTML content of the chapter
+    xhtml: String,
+
+    /// Whether the chapter contains MathML
+    has_math: bool,
+}
+
+/// The container file pointing to the package document
+const CONTAINER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// Split the content of an article into chapters
+///
+/// If there are any top-level sections then each is a chapter. Otherwise, the
+/// content is split at headings of the highest level (i.e. the lowest `level`).
+/// In both cases, any content before the first boundary becomes a chapter.
+fn split(content: Vec<Block>) -> Vec<Vec<Block>> {
+    let sectioned = content
+        .iter()
+        .any(|block| matches!(block, Block::Section(..)));
+    let level = content
+        .iter()
+        .filter_map(|block| match block {
+            Block::Heading(heading) => Some(heading.level),
+            _ => None,
+        })
+        .min();
+
+    let mut chapters = Vec::new();
+    let mut current = Vec::new();
+    for block in content {
+        let boundary = match &block {
+            Block::Section(..) => sectioned,
+            Block::Heading(heading) => !sectioned && Some(heading.level) == level,
+            _ => false,
+        };
+
+        if boundary && !current.is_empty() {
+            chapters.push(std::mem::take(&mut current));
+        }
+
+        current.push(block);
+
+        if boundary && sectioned {
+            chapters.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() || chapters.is_empty() {
+        chapters.push(current);
+    }
+
+    chapters
+}
+
+/// The character used to delimit placeholders for MathML
+const PLACEHOLDER: char = '\u{E000}';
+
+/// A regex for matching MathML placeholders in generated HTML
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new("\u{E000}(\\d+)\u{E000}").expect("invalid regex"));
+
+/// A visitor which replaces math nodes with placeholders for their MathML
+///
+/// This is necessary because the HTML encoder escapes MathML.
+#[derive(Default)]
+struct Math {
+    /// The MathML for each placeholder
+    mathml: Vec<String>,
+
+    /// Losses for math which could not be converted to MathML
+    losses: Losses,
+}
+
+impl Math {
+    /// Get the MathML for math code and return a placeholder for it
+    fn placeholder(
+        &mut self,
+        code: &str,
+        lang: Option<&str>,
+        mathml: Option<&String>,
+        style: DisplayStyle,
+    ) -> Option<String> {
+        let mathml = match (mathml, lang.map(str::to_lowercase).as_deref()) {
+            (Some(mathml), ..) => mathml.clone(),
+            (None, Some("mathml")) => code.to_string(),
+            (None, None | Some("tex") | Some("latex")) => latex_to_mathml(code, style).ok()?,
+            _ => return None,
+        };
+
+        // Ensure that the MathML namespace is declared
+        let mathml = if mathml.starts_with("<math") && !mathml.contains("xmlns=") {
+            mathml.replacen(
+                "<math",
+                r#"<math xmlns="http://www.w3.org/1998/Math/MathML""#,
+                1,
+            )
+        } else {
+            mathml
+        };
+
+        let index = self.mathml.len();
+        self.mathml.push(mathml);
+
+        Some(format!("{PLACEHOLDER}{index}{PLACEHOLDER}"))
+    }
+}
+
+impl VisitorMut for Math {
+    fn visit_block(&mut self, block: &mut Block) -> WalkControl {
+        if let Block::MathBlock(math) = block {
+            match self.placeholder(
+                &math.code,
+                math.math_language.as_deref(),
+                math.options.mathml.as_ref(),
+                DisplayStyle::Block,
+            ) {
+                Some(placeholder) => *block = p([t(placeholder)]),
+                None => self.losses.add("MathBlock.mathml"),
+            }
+        }
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &mut Inline) -> WalkControl {
+        if let Inline::MathInline(math) = inline {
+            match self.placeholder(
+                &math.code,
+                math.math_language.as_deref(),
+                math.options.mathml.as_ref(),
+                DisplayStyle::Inline,
+            ) {
+                Some(placeholder) => *inline = t(placeholder),
+                None => self.losses.add("MathInline.mathml"),
+            }
+        }
+        WalkControl::Continue
+    }
+}
+
+/// An entry in the navigation document
+struct NavEntry {
+    /// The chapter file that the heading is in
+    file: String,
+
+    /// The `id` of the heading
+    id: String,
+
+    /// The level of the heading
+    level: i64,
+
+    /// The text of the heading
+    text: String,
+}
+
+/// A visitor which ensures that all headings have an `id` and collects them
+/// for the navigation document
+#[derive(Default)]
+struct Headings {
+    /// The file of the chapter currently being visited
+    file: String,
+
+    /// The headings collected so far
+    entries: Vec<NavEntry>,
+}
+
+impl VisitorMut for Headings {
+    fn visit_block(&mut self, block: &mut Block) -> WalkControl {
+        if let Block::Heading(heading) = block {
+            let count = self.entries.len() + 1;
+            let id = heading
+                .id
+                .get_or_insert_with(|| format!("heading-{count}"))
+                .clone();
+            self.entries.push(NavEntry {
+                file: self.file.clone(),
+                id,
+                level: heading.level,
+                text: to_text(&heading.content),
+            });
+        }
+        WalkControl::Continue
+    }
+}
+
+/// Escape a string for use in XML text or attribute values
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Wrap HTML content in a XHTML document
+fn xhtml(title: &str, body: &str) -> String {
+    let title = escape(title);
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="en" xml:lang="en">
+<head>
+<meta charset="UTF-8"/>
+<title>{title}</title>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#
+    )
+}
+
+/// Generate the navigation document from the hierarchy of headings
+///
+/// Falls back to a flat list of chapters if there are no headings.
+fn nav(title: &str, entries: &[NavEntry], chapters: &[Chapter]) -> String {
+    let mut list = String::new();
+
+    if entries.is_empty() {
+        list.push_str("<ol>");
+        for (index, chapter) in chapters.iter().enumerate() {
+            list.push_str(&format!(
+                r#"<li><a href="{}">Chapter {}</a></li>"#,
+                chapter.file,
+                index + 1
+            ));
+        }
+        list.push_str("</ol>");
+    } else {
+        let mut levels: Vec<i64> = Vec::new();
+        for entry in entries {
+            match levels.last() {
+                Some(&last) if entry.level <= last => {
+                    list.push_str("</li>");
+                    while levels.len() > 1 && levels.last().is_some_and(|&last| entry.level < last)
+                    {
+                        list.push_str("</ol></li>");
+                        levels.pop();
+                    }
+                }
+                _ => {
+                    list.push_str("<ol>");
+                    levels.push(entry.level);
+                }
+            }
+            list.push_str(&format!(
+                r#"<li><a href="{}#{}">{}</a>"#,
+                entry.file,
+                escape(&entry.id),
+                escape(&entry.text)
+            ));
+        }
+        for _ in levels {
+            list.push_str("</li></ol>");
+        }
+    }
+
+    xhtml(
+        title,
+        &format!(r#"<nav epub:type="toc" id="toc"><h1>Contents</h1>{list}</nav>"#),
+    )
+}
+
+/// Get the name of an author
+fn author_name(author: &Author) -> Option<String> {
+    fn person_name(person: &Person) -> Option<String> {
+        let name = person
+            .given_names
+            .iter()
+            .flatten()
+            .chain(person.family_names.iter().flatten())
+            .join(" ");
+        if name.is_empty() {
+            person.options.name.clone()
+        } else {
+            Some(name)
+        }
+    }
+
+    match author {
+        Author::Person(person) => person_name(person),
+        Author::Organization(org) => org.name.clone(),
+        Author::SoftwareApplication(app) => Some(app.name.clone()),
+        Author::AuthorRole(role) => match &role.author {
+            AuthorRoleAuthor::Person(person) => person_name(person),
+            AuthorRoleAuthor::Organization(org) => org.name.clone(),
+            AuthorRoleAuthor::SoftwareApplication(app) => Some(app.name.clone()),
+            AuthorRoleAuthor::Thing(thing) => thing.options.name.clone(),
+        },
+    }
+}
+
+/// Get the media type of a file based on its extension
+fn media_type(name: &str) -> String {
+    match Format::from_path(Path::new(name)) {
+        Format::Svg => "image/svg+xml".to_string(),
+        Format::Jpeg => "image/jpeg".to_string(),
+        Format::Other(ext) if ext == "jpg" => "image/jpeg".to_string(),
+        format => format.media_type(),
+    }
+}
+
+/// Generate the package document with metadata, manifest and spine
+fn opf(
+    article: &Article,
+    title: &str,
+    chapters: &[Chapter],
+    media: &[(String, std::path::PathBuf)],
+    losses: &mut Losses,
+) -> String {
+    let mut metadata = Vec::new();
+
+    let mut identifiers = article
+        .options
+        .identifiers
+        .iter()
+        .flatten()
+        .map(|identifier| match identifier {
+            PropertyValueOrString::String(string) => string.clone(),
+            PropertyValueOrString::PropertyValue(value) => to_text(&value.value),
+        })
+        .filter(|identifier| !identifier.is_empty());
+    let uid = identifiers
+        .next()
+        .unwrap_or_else(|| format!("urn:uuid:{}", Uuid::new_v4()));
+    metadata.push(format!(
+        r#"<dc:identifier id="uid">{}</dc:identifier>"#,
+        escape(&uid)
+    ));
+    for identifier in identifiers {
+        metadata.push(format!(
+            "<dc:identifier>{}</dc:identifier>",
+            escape(&identifier)
+        ));
+    }
+
+    metadata.push(format!("<dc:title>{}</dc:title>", escape(title)));
+    metadata.push("<dc:language>en</dc:language>".to_string());
+
+    for author in article.authors.iter().flatten() {
+        match author_name(author) {
+            Some(name) => metadata.push(format!("<dc:creator>{}</dc:creator>", escape(&name))),
+            None => losses.add("Author"),
+        }
+    }
+
+    for license in article.options.licenses.iter().flatten() {
+        let license = to_text(license);
+        if license.is_empty() {
+            losses.add("Article.licenses");
+        } else {
+            metadata.push(format!("<dc:rights>{}</dc:rights>", escape(&license)));
+        }
+    }
+
+    if let Some(date) = &article.date_published {
+        metadata.push(format!("<dc:date>{}</dc:date>", escape(&date.value)));
+    }
+
+    if let Some(description) = &article.description {
+        metadata.push(format!(
+            "<dc:description>{}</dc:description>",
+            escape(description)
+        ));
+    }
+
+    metadata.push(format!(
+        r#"<meta property="dcterms:modified">{}</meta>"#,
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    ));
+
+    let mut manifest = vec![
+        r#"<item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#
+            .to_string(),
+    ];
+    let mut spine = Vec::new();
+    for (index, chapter) in chapters.iter().enumerate() {
+        let id = format!("chapter-{}", index + 1);
+        manifest.push(format!(
+            r#"<item id="{id}" href="{}" media-type="application/xhtml+xml"{}/>"#,
+            chapter.file,
+            if chapter.has_math {
+                r#" properties="mathml""#
+            } else {
+                ""
+            }
+        ));
+        spine.push(format!(r#"<itemref idref="{id}"/>"#));
+    }
+    for (index, (name, ..)) in media.iter().enumerate() {
+        manifest.push(format!(
+            r#"<item id="media-{}" href="media/{}" media-type="{}"/>"#,
+            index + 1,
+            escape(name),
+            media_type(name)
+        ));
+    }
+
+    let indent = "\n    ";
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="uid" xml:lang="en">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    {}
+  </metadata>
+  <manifest>
+    {}
+  </manifest>
+  <spine>
+    {}
+  </spine>
+</package>
+"#,
+        metadata.join(indent),
+        manifest.join(indent),
+        spine.join(indent)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::schema::shortcuts::{h1, h2, p, sec, t};
+
+    use super::*;
+
+    #[test]
+    fn splitting() {
+        let chapters = split(vec![
+            p([t("Preface")]),
+            h1([t("One")]),
+            h2([t("One.One")]),
+            h1([t("Two")]),
+        ]);
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[1].len(), 2);
+
+        let chapters = split(vec![
+            sec([h1([t("One")])]),
+            sec([h1([t("Two")])]),
+            p([t("Postscript")]),
+        ]);
+        assert_eq!(chapters.len(), 3);
+    }
+
+    #[test]
+    fn navigation() {
+        let entry = |level, text: &str| NavEntry {
+            file: "chapter-1.xhtml".into(),
+            id: text.into(),
+            level,
+            text: text.into(),
+        };
+        let nav = nav(
+            "Title",
+            &[entry(1, "a"), entry(2, "b"), entry(2, "c"), entry(1, "d")],
+            &[],
+        );
+        assert!(nav.contains(
+            r##"<ol><li><a href="chapter-1.xhtml#a">a</a><ol><li><a href="chapter-1.xhtml#b">b</a></li><li><a href="chapter-1.xhtml#c">c</a></li></ol></li><li><a href="chapter-1.xhtml#d">d</a></li></ol>"##
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, EncodeInfo, EncodeOptions, NodeType,
+};
+
+mod encode;
+
+/// A codec for EPUB 3 e-books
+pub struct EpubCodec;
+
+#[async_trait]
+impl Codec for EpubCodec {
+    fn name(&self) -> &str {
+        "epub"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, _format: &Format) -> CodecSupport {
+        CodecSupport::None
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Epub => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, _node_type: NodeType) -> CodecSupport {
+        CodecSupport::None
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_string(&self) -> bool {
+        false
+    }
+
+    fn supports_to_string(&self) -> bool {
+        false
+    }
+
+    async fn to_path(
+        &self,
+        node: &Node,
+        path: &Path,
+        options: Option<EncodeOptions>,
+    ) -> Result<EncodeInfo> {
+        encode::encode(node, path, options).await
+    }
+}
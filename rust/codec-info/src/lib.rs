@@ -40,6 +40,9 @@ impl DecodeInfo {
 #[derive(Default)]
 
 pub struct EncodeInfo {
+    /// Any messages generated while encoding
+    pub messages: Messages,
+
     /// The losses when encoding the node to content
     pub losses: Losses,
 
@@ -51,6 +54,7 @@ impl EncodeInfo {
     /// Create an empty set on encodings information
     pub fn none() -> Self {
         Self {
+            messages: Messages::none(),
             losses: Losses::none(),
             mapping: Mapping::none(),
         }
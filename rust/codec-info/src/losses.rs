@@ -147,6 +147,11 @@ impl Losses {
         self.inner.is_empty()
     }
 
+    /// Iterate over the labels and counts of losses
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &usize)> {
+        self.inner.iter()
+    }
+
     /// Respond to losses according to the `LossesResponse` variant
     pub fn respond<D>(&self, what: D, response: LossesResponse) -> Result<()>
     where
@@ -81,6 +81,7 @@ impl Codec for LatexCodec {
             let info = EncodeInfo {
                 losses: context.losses,
                 mapping: context.mapping,
+                ..Default::default()
             };
 
             Ok((output, info))
@@ -1416,6 +1416,9 @@ fn md_to_block(md: mdast::Node, context: &mut Context) -> Option<(Block, Option<
                 Format::Myst => {
                     myst_to_block(&code, context).unwrap_or_else(|| code_to_block(code, context))
                 }
+                Format::Rmd => {
+                    rmd_to_block(&code, context).unwrap_or_else(|| code_to_block(code, context))
+                }
                 _ => code_to_block(code, context),
             };
 
@@ -1708,6 +1711,148 @@ fn myst_to_block(code: &mdast::Code, context: &mut Context) -> Option<Block> {
     })
 }
 
+/// Transform an R Markdown code chunk to a Stencila [`Block`]
+///
+/// Knitr chunk headers have the form ```` ```{r label, echo=FALSE, fig.cap="..."} ````.
+/// Options with an equivalent in Stencila are mapped to properties of the `CodeChunk`
+/// and the remainder are recorded as losses. Chunks with `eval=FALSE` are never executed
+/// so are decoded as `CodeBlock`s. Returns `None` if the code is not a knitr chunk.
+fn rmd_to_block(code: &mdast::Code, context: &mut Context) -> Option<Block> {
+    let lang = code.lang.as_deref()?.strip_prefix('{')?;
+    let header = match &code.meta {
+        Some(meta) => [lang, " ", meta].concat(),
+        None => lang.to_string(),
+    };
+    let header = header.trim_end().strip_suffix('}')?;
+
+    let (lang, options) = header
+        .split_once(|c: char| c.is_whitespace() || c == ',')
+        .unwrap_or((header, ""));
+
+    let mut chunk = CodeChunk {
+        code: code.value.clone().into(),
+        programming_language: (!lang.is_empty()).then(|| lang.to_string()),
+        ..Default::default()
+    };
+
+    let mut label = None;
+    let mut eval = true;
+    let mut include = true;
+    for (name, value) in knitr_options(options) {
+        let Some(value) = value else {
+            // An unnamed option following the language is the label
+            if label.is_none() {
+                label = Some(name);
+            } else {
+                context.lost("R Markdown chunk option");
+            }
+            continue;
+        };
+
+        let flag = match value.as_str() {
+            "TRUE" | "T" => Some(true),
+            "FALSE" | "F" => Some(false),
+            _ => None,
+        };
+
+        match (name.as_str(), flag) {
+            ("label", ..) => label = Some(knitr_string(&value)),
+            ("eval", Some(flag)) => eval = flag,
+            ("include", Some(flag)) => include = flag,
+            ("results", ..) if knitr_string(&value) == "hide" => chunk.is_invisible = Some(true),
+            ("echo", Some(flag)) => chunk.is_code_hidden = (!flag).then_some(true),
+            ("cache", Some(flag)) => {
+                chunk.execution_mode = flag.then_some(ExecutionMode::Need);
+            }
+            ("fig.cap" | "tab.cap", ..) => {
+                chunk.label_type = Some(if name == "fig.cap" {
+                    LabelType::FigureLabel
+                } else {
+                    LabelType::TableLabel
+                });
+                chunk.caption = Some(decode_blocks(&knitr_string(&value), context));
+            }
+            _ => context.lost(&format!("R Markdown `{name}` chunk option")),
+        }
+    }
+
+    // `include=FALSE` hides both the code and its outputs
+    if !include {
+        chunk.is_invisible = Some(true);
+        chunk.is_code_hidden = Some(true);
+    }
+
+    if label.is_some() {
+        chunk.label_automatically = Some(false);
+        chunk.label = label;
+    }
+
+    Some(if eval {
+        Block::CodeChunk(chunk)
+    } else {
+        Block::CodeBlock(CodeBlock {
+            code: chunk.code,
+            programming_language: chunk.programming_language,
+            ..Default::default()
+        })
+    })
+}
+
+/// Split the options in a knitr chunk header into names and optional values
+///
+/// Options are separated by commas, which may also occur within quoted strings
+/// or parenthesized R expressions e.g. `fig.dim=c(6, 4)`.
+fn knitr_options(header: &str) -> Vec<(String, Option<String>)> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut quote = None;
+    let mut depth = 0;
+    for char in header.chars() {
+        match (char, quote) {
+            ('"' | '\'', None) => quote = Some(char),
+            (_, Some(open)) if char == open && !item.ends_with('\\') => quote = None,
+            ('(' | '[', None) => depth += 1,
+            (')' | ']', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                items.push(std::mem::take(&mut item));
+                continue;
+            }
+            _ => {}
+        }
+        item.push(char);
+    }
+    items.push(item);
+
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let item = item.trim();
+            if item.is_empty() {
+                return None;
+            }
+            Some(match item.split_once('=') {
+                Some((name, value)) if !name.contains(['"', '\'', '(']) => {
+                    (name.trim().to_string(), Some(value.trim().to_string()))
+                }
+                _ => (item.to_string(), None),
+            })
+        })
+        .collect()
+}
+
+/// Unquote an R string literal in a knitr chunk option
+fn knitr_string(value: &str) -> String {
+    for quote in ['"', '\'', '`'] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return inner.replace(&format!("\\{quote}"), &quote.to_string());
+        }
+    }
+    value.to_string()
+}
+
 /// Transform a [`mdast::Code`] node to a Stencila [`Block`]
 fn code_to_block(code: mdast::Code, context: &mut Context) -> Block {
     let mdast::Code {
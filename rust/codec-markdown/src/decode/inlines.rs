@@ -47,6 +47,10 @@ pub(super) fn mds_to_inlines(mds: Vec<mdast::Node>, context: &mut Context) -> Ve
             // inline code, subscripts, superscripts etc and sentinel text like EDIT_END
             let mut inlines = inlines(&value)
                 .into_iter()
+                .map(|(inline, span)| match context.format {
+                    Format::Rmd => (rmd_inline_code(inline), span),
+                    _ => (inline, span),
+                })
                 .map(|(inline, span)| {
                     let span = position
                         .as_ref()
@@ -382,6 +386,26 @@ fn qmd_inline_code(input: &mut Located<&str>) -> PResult<Inline> {
         .parse_next(input)
 }
 
+/// Transform R Markdown inline code (e.g. `` `r nrow(data)` ``) into a `CodeExpression`
+fn rmd_inline_code(inline: Inline) -> Inline {
+    if let Inline::CodeInline(CodeInline {
+        code,
+        programming_language: None,
+        ..
+    }) = &inline
+    {
+        if let Some(expr) = code.strip_prefix("r ") {
+            return Inline::CodeExpression(CodeExpression {
+                code: expr.trim_start().into(),
+                programming_language: Some("r".into()),
+                ..Default::default()
+            });
+        }
+    }
+
+    inline
+}
+
 /// Parse inline code with optional attributes in curly braces e.g. `\`code\`{attr1 attr2}`
 /// into a `CodeFragment`, `CodeExpression` or `MathFragment` node.
 ///
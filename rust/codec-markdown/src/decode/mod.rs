@@ -17,8 +17,9 @@ use codec::{
     },
     format::Format,
     schema::{
-        shortcuts::t, Article, Block, Chat, Inline, Node, NodeId, NodeType, Null, Prompt,
-        VisitorMut, WalkControl,
+        shortcuts::{p, t},
+        Article, Block, Chat, Inline, Node, NodeId, NodeType, Null, Parameter, ParameterOptions,
+        Prompt, VisitorMut, WalkControl,
     },
     DecodeInfo, DecodeOptions, Losses, Mapping,
 };
@@ -69,10 +70,16 @@ pub fn decode(content: &str, options: Option<DecodeOptions>) -> Result<(Node, De
 
     // Transform MDAST to blocks
     let mut context = Context::new(format);
-    let content = blocks::mds_to_blocks(children, &mut context);
+    let mut content = blocks::mds_to_blocks(children, &mut context);
 
     // Decode frontmatter (which may have a `type`, but defaults to `Article`)
     let frontmatter = context.frontmatter();
+
+    // Prepend any parameters declared in the frontmatter
+    if !context.parameters.is_empty() {
+        content.insert(0, p(std::mem::take(&mut context.parameters)));
+    }
+
     let mut node = if let Some(Node::Article(rest)) = frontmatter {
         Node::Article(Article { content, ..rest })
     } else if let Some(Node::Prompt(rest)) = frontmatter {
@@ -300,6 +307,9 @@ struct Context {
     /// Footnote content
     footnotes: HashMap<String, Vec<Block>>,
 
+    /// Parameters declared in the frontmatter
+    parameters: Vec<Inline>,
+
     /// Losses during decoding
     losses: Losses,
 
@@ -425,6 +435,17 @@ impl Context {
             }
         };
 
+        // Decode R Markdown `params` (and remove from value so it is not treated as an
+        // unknown property when deserializing)
+        if matches!(self.format, Format::Rmd) {
+            if let Some(params) = value
+                .as_object_mut()
+                .and_then(|object| object.remove("params"))
+            {
+                self.rmd_params(params);
+            }
+        }
+
         // Parse title and abstract as Markdown (need to do here before deserializing to node
         // and remove from value so does not cause an error when deserializing)
         let (title, abs) = if let Some(object) = value.as_object_mut() {
@@ -472,6 +493,43 @@ impl Context {
 
         Some(node)
     }
+
+    /// Decode R Markdown `params` into `Parameter` nodes
+    ///
+    /// Each parameter is either a value, or an object with a `value` and optional
+    /// `label` and Shiny input options (e.g. `input`, `min`, `max`), which are lost.
+    fn rmd_params(&mut self, params: serde_json::Value) {
+        let serde_json::Value::Object(params) = params else {
+            self.lost("R Markdown params");
+            return;
+        };
+
+        for (name, value) in params {
+            let (label, value) = match value {
+                serde_json::Value::Object(mut object) if object.contains_key("value") => {
+                    let label = object
+                        .remove("label")
+                        .and_then(|label| label.as_str().map(String::from));
+                    let value = object.remove("value").unwrap_or_default();
+                    if !object.is_empty() {
+                        self.lost("R Markdown parameter options");
+                    }
+                    (label, value)
+                }
+                value => (None, value),
+            };
+
+            self.parameters.push(Inline::Parameter(Parameter {
+                name,
+                options: Box::new(ParameterOptions {
+                    label,
+                    default: serde_json::from_value(value).ok().map(Box::new),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }));
+        }
+    }
 }
 
 impl VisitorMut for Context {
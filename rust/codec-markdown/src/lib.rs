@@ -31,7 +31,7 @@ impl Codec for MarkdownCodec {
     fn supports_from_format(&self, format: &Format) -> CodecSupport {
         use CodecSupport::*;
         match format {
-            Format::Markdown | Format::Smd | Format::Myst | Format::Qmd => LowLoss,
+            Format::Markdown | Format::Smd | Format::Myst | Format::Qmd | Format::Rmd => LowLoss,
             _ => None,
         }
     }
@@ -39,7 +39,12 @@ impl Codec for MarkdownCodec {
     fn supports_to_format(&self, format: &Format) -> CodecSupport {
         use CodecSupport::*;
         match format {
-            Format::Markdown | Format::Smd | Format::Myst | Format::Qmd | Format::Llmd => LowLoss,
+            Format::Markdown
+            | Format::Smd
+            | Format::Myst
+            | Format::Qmd
+            | Format::Rmd
+            | Format::Llmd => LowLoss,
             _ => None,
         }
     }
@@ -0,0 +1,166 @@
+use codec::{
+    common::{eyre::Result, tokio},
+    format::Format,
+    schema::{
+        shortcuts::{cb, p, t},
+        Article, Block, CodeChunk, CodeExpression, Inline, LabelType, Node, Parameter,
+        ParameterOptions,
+    },
+    Codec, DecodeOptions, EncodeOptions,
+};
+use codec_markdown::MarkdownCodec;
+
+/// Test decoding of knitr chunk options, inline R code and YAML `params`
+#[tokio::test]
+async fn decode() -> Result<()> {
+    let (node, info) = MarkdownCodec
+        .from_str(
+            r#"---
+params:
+  year: 2020
+  region:
+    label: Region
+    value: Europe
+---
+
+```{r setup, include=FALSE}
+library(dplyr)
+```
+
+There were `r nrow(data)` rows in `r params$year`.
+
+```{r plot, echo=FALSE, fig.cap="A \"nice\" plot"}
+plot(data)
+```
+
+```{r eval=FALSE}
+install.packages("dplyr")
+```
+"#,
+            Some(DecodeOptions {
+                format: Some(Format::Rmd),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+    let Node::Article(Article { content, .. }) = node else {
+        panic!("expected article")
+    };
+
+    assert_eq!(
+        content,
+        vec![
+            p([
+                Inline::Parameter(Parameter {
+                    name: "year".into(),
+                    options: Box::new(ParameterOptions {
+                        default: Some(Box::new(Node::Integer(2020))),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                Inline::Parameter(Parameter {
+                    name: "region".into(),
+                    options: Box::new(ParameterOptions {
+                        label: Some("Region".into()),
+                        default: Some(Box::new(Node::String("Europe".into()))),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            ]),
+            Block::CodeChunk(CodeChunk {
+                code: "library(dplyr)".into(),
+                programming_language: Some("r".into()),
+                label: Some("setup".into()),
+                label_automatically: Some(false),
+                is_invisible: Some(true),
+                is_code_hidden: Some(true),
+                ..Default::default()
+            }),
+            p([
+                t("There were "),
+                Inline::CodeExpression(CodeExpression {
+                    code: "nrow(data)".into(),
+                    programming_language: Some("r".into()),
+                    ..Default::default()
+                }),
+                t(" rows in "),
+                Inline::CodeExpression(CodeExpression {
+                    code: "params$year".into(),
+                    programming_language: Some("r".into()),
+                    ..Default::default()
+                }),
+                t(".")
+            ]),
+            Block::CodeChunk(CodeChunk {
+                code: "plot(data)".into(),
+                programming_language: Some("r".into()),
+                label: Some("plot".into()),
+                label_automatically: Some(false),
+                label_type: Some(LabelType::FigureLabel),
+                caption: Some(vec![p([t("A \"nice\" plot")])]),
+                is_code_hidden: Some(true),
+                ..Default::default()
+            }),
+            cb("install.packages(\"dplyr\")", Some("r"))
+        ]
+    );
+
+    assert!(info.losses.is_empty());
+
+    Ok(())
+}
+
+/// Test that R Markdown round-trips through decoding and encoding
+#[tokio::test]
+async fn roundtrip() -> Result<()> {
+    let rmd = r#"---
+params:
+  year: 2020
+  region:
+    label: Region
+    value: Europe
+---
+
+```{r setup, include=FALSE}
+library(dplyr)
+```
+
+There were `r nrow(data)` rows.
+
+```{r summary, results='hide'}
+summary(data)
+```
+
+```{r plot, echo=FALSE, cache=TRUE, fig.cap="A \"nice\" plot"}
+plot(data)
+```
+"#;
+
+    let (node, ..) = MarkdownCodec
+        .from_str(
+            rmd,
+            Some(DecodeOptions {
+                format: Some(Format::Rmd),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+    let (encoded, info) = MarkdownCodec
+        .to_string(
+            &node,
+            Some(EncodeOptions {
+                format: Some(Format::Rmd),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+    assert_eq!(encoded, rmd);
+    assert!(info.losses.is_empty());
+
+    Ok(())
+}
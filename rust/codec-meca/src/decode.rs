@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, File},
+    io::Read,
+    path::Path,
+};
+
+use roxmltree::{Document, ParsingOptions};
+
+use codec::{
+    common::{
+        eyre::{eyre, Result},
+        itertools::Itertools,
+        zip::ZipArchive,
+    },
+    schema::{
+        AudioObject, Block, ImageObject, Inline, Link, Node, VideoObject, VisitorMut, WalkControl,
+    },
+    DecodeInfo, DecodeOptions,
+};
+
+const XLINK: &str = "http://www.w3.org/1999/xlink";
+
+/// Decode a MECA package to an `Article`
+///
+/// The JATS of the article is located using the `manifest.xml` of the package,
+/// falling back to the first other XML file if there is no manifest. All other files
+/// in the package (e.g. figures and supplementary files) are extracted into a
+/// `<name>.media` folder next to the package and the URLs of the images, audio,
+/// video and links that refer to them are rewritten to point to that folder.
+pub(super) fn decode(path: &Path, options: Option<DecodeOptions>) -> Result<(Node, DecodeInfo)> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+
+    let article_path = read(&mut zip, "manifest.xml")
+        .ok()
+        .and_then(|manifest| manifest_article(&manifest))
+        .or_else(|| {
+            zip.file_names()
+                .filter(|name| {
+                    name.ends_with(".xml") && !matches!(*name, "manifest.xml" | "transfer.xml")
+                })
+                .sorted()
+                .next()
+                .map(String::from)
+        })
+        .ok_or_else(|| eyre!("Unable to find the article XML in the MECA package"))?;
+
+    let jats = read(&mut zip, &article_path)?;
+    let (mut node, info) = codec_jats::decode(&jats, options)?;
+
+    // Extract all other files, recording their path relative to the article, and their
+    // path relative to the package after extraction
+    let article_dir = article_path
+        .rsplit_once('/')
+        .map(|(dir, ..)| [dir, "/"].concat())
+        .unwrap_or_default();
+    let media_name = format!(
+        "{}.media",
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "meca".to_string())
+    );
+    let media_dir = path.with_file_name(&media_name);
+
+    let mut files = HashMap::new();
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        let name = file.name().to_string();
+        if file.is_dir()
+            || file.enclosed_name().is_none()
+            || matches!(name.as_str(), "manifest.xml" | "transfer.xml")
+            || name == article_path
+        {
+            continue;
+        }
+
+        let relative = name.strip_prefix(&article_dir).unwrap_or(&name).to_string();
+
+        let dest = media_dir.join(&relative);
+        if let Some(parent) = dest.parent() {
+            create_dir_all(parent)?;
+        }
+        std::io::copy(&mut file, &mut File::create(dest)?)?;
+
+        files.insert(relative.clone(), format!("{media_name}/{relative}"));
+    }
+
+    if !files.is_empty() {
+        Resolver { files }.visit(&mut node);
+    }
+
+    Ok((node, info))
+}
+
+/// Read a file in the package to a string
+fn read(zip: &mut ZipArchive<File>, name: &str) -> Result<String> {
+    let mut file = zip.by_name(name)?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Get the path of the article XML from a MECA manifest
+fn manifest_article(manifest: &str) -> Option<String> {
+    let dom = Document::parse_with_options(
+        manifest,
+        ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        },
+    )
+    .ok()?;
+
+    dom.descendants()
+        .filter(|node| node.has_tag_name("item") && node.attribute("item-type") == Some("article"))
+        .flat_map(|item| item.children())
+        .filter(|node| node.has_tag_name("instance"))
+        .filter_map(|instance| instance.attribute((XLINK, "href")))
+        .find(|href| href.ends_with(".xml"))
+        .map(String::from)
+}
+
+/// A visitor that rewrites URLs to files extracted from the package
+struct Resolver {
+    /// The extracted files, keyed by their path relative to the article
+    files: HashMap<String, String>,
+}
+
+impl Resolver {
+    /// Rewrite a URL if it refers to an extracted file
+    fn resolve(&self, url: &mut String) {
+        if let Some(path) = self.files.get(url.trim_start_matches("./")) {
+            url.clone_from(path);
+        }
+    }
+}
+
+impl VisitorMut for Resolver {
+    fn visit_block(&mut self, block: &mut Block) -> WalkControl {
+        if let Block::AudioObject(AudioObject { content_url, .. })
+        | Block::ImageObject(ImageObject { content_url, .. })
+        | Block::VideoObject(VideoObject { content_url, .. }) = block
+        {
+            self.resolve(content_url);
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &mut Inline) -> WalkControl {
+        match inline {
+            Inline::AudioObject(AudioObject { content_url, .. })
+            | Inline::ImageObject(ImageObject { content_url, .. })
+            | Inline::VideoObject(VideoObject { content_url, .. }) => self.resolve(content_url),
+            Inline::Link(Link { target, .. }) => self.resolve(target),
+            _ => {}
+        }
+
+        WalkControl::Continue
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    fs::{read_dir, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use codec::{
+    common::{
+        eyre::{bail, Result},
+        itertools::Itertools,
+        tempfile, tokio,
+        zip::{write::FileOptions, ZipWriter},
+    },
+    format::Format,
+    schema::{
+        Article, AudioObject, Block, Inline, Link, Node, PersonOrOrganization, VideoObject,
+        VisitorMut, WalkControl,
+    },
+    EncodeInfo, EncodeOptions,
+};
+
+/// Encode an `Article` to a MECA package
+///
+/// The article is encoded as JATS and placed in the `content` folder of the package
+/// along with any images (gathered using `node-media`) and supplementary files
+/// (local files that are linked to, or are the source of audio or video). A
+/// `manifest.xml` listing all items, and a `transfer.xml`, are written at the root.
+pub(super) async fn encode(
+    node: &Node,
+    path: &Path,
+    options: Option<EncodeOptions>,
+) -> Result<EncodeInfo> {
+    let Node::Article(article) = node else {
+        bail!("Only articles can be encoded to MECA")
+    };
+    let options = options.unwrap_or_default();
+
+    let mut article = article.clone();
+
+    // Extract images into a temporary directory so that they can be packaged
+    // alongside the JATS (which refers to them by file name)
+    let media_dir = tempfile::tempdir()?;
+    let src_dir = options
+        .from_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+    node_media::extract_media(&mut article, &src_dir, media_dir.path(), |_, file_name| {
+        file_name.to_string()
+    });
+
+    let mut figures = Vec::new();
+    for entry in read_dir(media_dir.path())?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        figures.push((name, entry.path()));
+    }
+    figures.sort();
+
+    // Collect supplementary files and rewrite references to them
+    let mut supplementary = Supplementary {
+        src_dir,
+        files: Vec::new(),
+    };
+    supplementary.visit(&mut article);
+
+    let (jats, info) = codec_jats::encode(
+        &Node::Article(article.clone()),
+        Some(EncodeOptions {
+            standalone: Some(true),
+            ..Default::default()
+        }),
+    )?;
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "article".to_string());
+
+    let mut items = vec![Item {
+        item_type: "article",
+        href: format!("content/{name}.xml"),
+        media_type: "application/xml".to_string(),
+    }];
+    for (item_type, files) in [
+        ("figure", &figures),
+        ("supplementary-material", &supplementary.files),
+    ] {
+        for (name, ..) in files {
+            items.push(Item {
+                item_type,
+                href: format!("content/{name}"),
+                media_type: media_type(name),
+            });
+        }
+    }
+
+    // Write the package
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let file = File::create(path)?;
+    let mut zip = ZipWriter::new(file);
+
+    let zip_options = FileOptions::default();
+    for (name, content) in [
+        ("manifest.xml".to_string(), manifest(&items)),
+        ("transfer.xml".to_string(), transfer(&article)),
+        (format!("content/{name}.xml"), jats),
+    ] {
+        zip.start_file(name, zip_options)?;
+        zip.write_all(content.as_bytes())?;
+    }
+    for (name, path) in figures.iter().chain(supplementary.files.iter()) {
+        zip.start_file(format!("content/{name}"), zip_options)?;
+        zip.write_all(&std::fs::read(path)?)?;
+    }
+    zip.finish()?;
+
+    Ok(info)
+}
+
+/// An item in the MECA manifest
+struct Item {
+    /// The MECA item type e.g. `article`, `figure`
+    item_type: &'static str,
+
+    /// The path of the item within the package
+    href: String,
+
+    /// The media type of the item
+    media_type: String,
+}
+
+/// Escape a string for use in XML
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Get the media type of a file in the package
+///
+/// Only formats with a registered media type are used, with others falling
+/// back to `application/octet-stream`.
+fn media_type(name: &str) -> String {
+    use Format::*;
+    let format = Format::from_path(Path::new(name));
+    match format {
+        Pdf => "application/pdf".to_string(),
+        Jats => "application/xml".to_string(),
+        Csv | Tsv | Xlsx | Ods | Json | JsonLd | Yaml | Epub | Markdown | Text => {
+            format.media_type()
+        }
+        _ if format.is_image() || format.is_audio() || format.is_video() => format.media_type(),
+        _ => "application/octet-stream".to_string(),
+    }
+}
+
+/// Generate the `manifest.xml` for the package
+fn manifest(items: &[Item]) -> String {
+    let items = items
+        .iter()
+        .map(|item| {
+            format!(
+                r#"  <item item-type="{}">
+    <instance media-type="{}" xlink:href="{}"/>
+  </item>"#,
+                item.item_type,
+                escape(&item.media_type),
+                escape(&item.href)
+            )
+        })
+        .join("\n");
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE manifest PUBLIC "-//MECA//DTD Manifest v1.0//en" "https://meca.zip/manifest-1.0.dtd">
+<manifest xmlns="https://manuscriptexchange.org/schema/manifest" xmlns:xlink="http://www.w3.org/1999/xlink" manifest-version="1">
+{items}
+</manifest>
+"#
+    )
+}
+
+/// Generate the `transfer.xml` for the package
+///
+/// The destination of the transfer is the publisher of the article, if any.
+fn transfer(article: &Article) -> String {
+    let destination = article
+        .options
+        .publisher
+        .as_ref()
+        .and_then(|publisher| match publisher {
+            PersonOrOrganization::Person(person) => Some(person.as_string()),
+            PersonOrOrganization::Organization(org) => org.name.clone(),
+        })
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            format!(
+                r#"
+  <transfer-destination>
+    <service-provider>
+      <provider-name>{}</provider-name>
+    </service-provider>
+  </transfer-destination>"#,
+                escape(&name)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE transfer PUBLIC "-//MECA//DTD Transfer v1.0//en" "https://meca.zip/transfer-1.0.dtd">
+<transfer xmlns="https://manuscriptexchange.org/schema/transfer" transfer-version="1.0">
+  <transfer-source>
+    <service-provider>
+      <provider-name>Stencila</provider-name>
+    </service-provider>
+  </transfer-source>{destination}
+</transfer>
+"#
+    )
+}
+
+/// A visitor that collects supplementary files
+///
+/// Local files that are the target of links, or the source of audio and video, are
+/// collected so that they can be added to the package and their URLs rewritten
+/// to their file name within it.
+struct Supplementary {
+    /// The directory that relative paths are resolved against
+    src_dir: PathBuf,
+
+    /// The name within the package, and path on disk, of each file
+    files: Vec<(String, PathBuf)>,
+}
+
+impl Supplementary {
+    /// Collect a file and rewrite the URL to it
+    fn collect(&mut self, url: &mut String) {
+        if url.starts_with('#') || (url.contains(':') && !url.starts_with("file://")) {
+            return;
+        }
+
+        let path = self
+            .src_dir
+            .join(url.strip_prefix("file://").unwrap_or(url.as_str()));
+        if !path.is_file() {
+            return;
+        }
+
+        if let Some((name, ..)) = self.files.iter().find(|(.., existing)| existing == &path) {
+            *url = name.clone();
+            return;
+        }
+
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            return;
+        };
+        let name = if self.files.iter().any(|(existing, ..)| existing == &name) {
+            format!("{}-{name}", self.files.len())
+        } else {
+            name
+        };
+
+        *url = name.clone();
+        self.files.push((name, path));
+    }
+}
+
+impl VisitorMut for Supplementary {
+    fn visit_block(&mut self, block: &mut Block) -> WalkControl {
+        if let Block::AudioObject(AudioObject { content_url, .. })
+        | Block::VideoObject(VideoObject { content_url, .. }) = block
+        {
+            self.collect(content_url);
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &mut Inline) -> WalkControl {
+        match inline {
+            Inline::AudioObject(AudioObject { content_url, .. })
+            | Inline::VideoObject(VideoObject { content_url, .. }) => self.collect(content_url),
+            Inline::Link(Link { target, .. }) => self.collect(target),
+            _ => {}
+        }
+
+        WalkControl::Continue
+    }
+}
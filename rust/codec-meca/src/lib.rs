@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, NodeType,
+};
+
+mod decode;
+mod encode;
+
+/// A codec for MECA (Manuscript Exchange Common Approach) packages
+///
+/// A MECA package is a Zip archive containing the JATS XML of an article, a
+/// `manifest.xml` listing all the items in the package (e.g. figures and
+/// supplementary files), and a `transfer.xml` describing the transfer.
+pub struct MecaCodec;
+
+#[async_trait]
+impl Codec for MecaCodec {
+    fn name(&self) -> &str {
+        "meca"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Meca => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Meca => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_string(&self) -> bool {
+        false
+    }
+
+    fn supports_to_string(&self) -> bool {
+        false
+    }
+
+    async fn from_path(
+        &self,
+        path: &Path,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        decode::decode(path, options)
+    }
+
+    async fn to_path(
+        &self,
+        node: &Node,
+        path: &Path,
+        options: Option<EncodeOptions>,
+    ) -> Result<EncodeInfo> {
+        encode::encode(node, path, options).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Read};
+
+    use codec::{
+        common::{tempfile, tokio, zip::ZipArchive},
+        schema::{
+            shortcuts::{img, lnk, p, t},
+            Article, Block, Inline,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("plot.png"), b"png")?;
+        std::fs::write(dir.path().join("data.csv"), b"a,b\n1,2\n")?;
+
+        let article = Node::Article(Article::new(vec![
+            p([img("plot.png")]),
+            p([t("See the "), lnk([t("data")], "data.csv"), t(".")]),
+        ]));
+
+        let meca = dir.path().join("out").join("paper.meca");
+        MecaCodec
+            .to_path(
+                &article,
+                &meca,
+                Some(EncodeOptions {
+                    from_path: Some(dir.path().join("paper.smd")),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        // The package has a manifest listing the article, figure and supplementary file
+        let mut zip = ZipArchive::new(File::open(&meca)?)?;
+        let mut manifest = String::new();
+        zip.by_name("manifest.xml")?.read_to_string(&mut manifest)?;
+        assert!(manifest.contains(r#"<item item-type="article">"#));
+        assert!(manifest.contains(r#"xlink:href="content/paper.xml""#));
+        assert!(manifest.contains(r#"<item item-type="figure">"#));
+        assert!(manifest.contains(r#"xlink:href="content/data.csv""#));
+        assert!(zip.by_name("transfer.xml").is_ok());
+        assert!(zip.by_name("content/data.csv").is_ok());
+
+        // Decoding resolves the image and link to extracted files
+        let (node, ..) = MecaCodec.from_path(&meca, None).await?;
+        let Node::Article(Article { content, .. }) = node else {
+            panic!("expected article")
+        };
+
+        let Some(Block::Paragraph(para)) = content.first() else {
+            panic!("expected paragraph")
+        };
+        let Some(Inline::ImageObject(image)) = para.content.first() else {
+            panic!("expected image")
+        };
+        assert!(image.content_url.starts_with("paper.media/"));
+        assert!(meca.with_file_name(&image.content_url).exists());
+
+        let Some(Block::Paragraph(para)) = content.get(1) else {
+            panic!("expected paragraph")
+        };
+        let Some(Inline::Link(link)) = para.content.get(1) else {
+            panic!("expected link")
+        };
+        assert_eq!(link.target, "paper.media/data.csv");
+        assert_eq!(
+            std::fs::read_to_string(meca.with_file_name(&link.target))?,
+            "a,b\n1,2\n"
+        );
+
+        Ok(())
+    }
+}
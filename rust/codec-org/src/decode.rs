@@ -0,0 +1,1222 @@
+use std::str::FromStr;
+
+use codec::{
+    common::{eyre::Result, indexmap::IndexMap, itertools::Itertools},
+    schema::{
+        shortcuts::{cb, ci, em, img, lnk, mb, mi, p, qb, stg, stk, sub, sup, t, tb, u},
+        Admonition, AdmonitionType, Article, Author, Block, CodeChunk, CodeExpression, Date,
+        ExecutionMode, Figure, Heading, IncludeBlock, Inline, List, ListItem, ListOrder, Node,
+        Object, Person, Primitive, Section, Table, TableCell, TableCellType, TableRow,
+        TableRowType,
+    },
+    DecodeInfo, DecodeOptions, Losses,
+};
+
+/// Decode Org to a Stencila [`Node`]
+///
+/// A line-based parser for the parts of Org most used in literate programming:
+/// document keywords, headings (decoded as sections, with their property
+/// drawers), paragraphs, lists, tables, and greater blocks. Babel source blocks
+/// (`#+begin_src`) are decoded as executable code chunks, with any following
+/// `#+RESULTS:` as their outputs, and inline `src_` calls as code expressions.
+pub(super) fn decode(org: &str, _options: Option<DecodeOptions>) -> Result<(Node, DecodeInfo)> {
+    let lines = org
+        .lines()
+        .map(|line| line.trim_end().to_string())
+        .collect_vec();
+
+    let mut context = Context::default();
+    let mut article = Article::new(context.sections(&lines));
+    context.article_keywords(&mut article);
+
+    let info = DecodeInfo {
+        losses: context.losses,
+        ..Default::default()
+    };
+
+    Ok((Node::Article(article), info))
+}
+
+#[derive(Default)]
+struct Context {
+    /// Document keywords (e.g. `#+TITLE:`), in the order that they were defined
+    keywords: IndexMap<String, String>,
+
+    /// Losses when decoding
+    losses: Losses,
+}
+
+/// Affiliated keywords (e.g. `#+NAME:`, `#+CAPTION:`) which apply to the next element
+#[derive(Default)]
+struct Affiliated {
+    /// The name of the element, used as its id
+    name: Option<String>,
+
+    /// The caption of the element
+    caption: Option<String>,
+
+    /// Header arguments for a source block from `#+HEADER:` lines
+    header: Vec<String>,
+}
+
+/// Get the indentation of a line
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Get the level of a heading and its text e.g. `** Methods`
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|char| *char == '*').count();
+    if level == 0 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if rest.is_empty() {
+        return Some((level, rest));
+    }
+    rest.strip_prefix(' ').map(|text| (level, text.trim()))
+}
+
+/// Parse a keyword line e.g. `#+TITLE: A title`
+///
+/// Returns the uppercased key, without any optional value (e.g. the hash in
+/// `#+RESULTS[a1b2]:`), and the value.
+fn keyword(line: &str) -> Option<(String, &str)> {
+    let (key, value) = line.trim_start().strip_prefix("#+")?.split_once(':')?;
+    let key = key.split('[').next().unwrap_or_default();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((key.to_uppercase(), value.trim()))
+}
+
+/// Parse the start of a greater block e.g. `#+begin_src python :exports both`
+///
+/// Returns the lowercased name of the block and its parameters.
+fn block_start(line: &str) -> Option<(String, &str)> {
+    let line = line.trim_start();
+    let rest = line
+        .get(..8)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("#+begin_"))
+        .map(|_| &line[8..])?;
+    let (name, params) = rest.split_once(' ').unwrap_or((rest, ""));
+    (!name.is_empty()).then(|| (name.to_lowercase(), params.trim()))
+}
+
+/// If a line is the start of a drawer (e.g. `:PROPERTIES:`), return its name
+fn drawer(line: &str) -> Option<&str> {
+    let name = line.trim().strip_prefix(':')?.strip_suffix(':')?;
+    (!name.is_empty()
+        && name
+            .chars()
+            .all(|char| char.is_alphanumeric() || matches!(char, '_' | '-')))
+    .then_some(name)
+}
+
+/// Is a line the end of a drawer?
+fn is_drawer_end(line: &str) -> bool {
+    line.trim().eq_ignore_ascii_case(":end:")
+}
+
+/// Parse a property line within a property drawer e.g. `:CUSTOM_ID: intro`
+fn property(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.trim().strip_prefix(':')?.split_once(':')?;
+    (!name.is_empty()).then(|| (name, value.trim()))
+}
+
+/// Is a line a comment?
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line == "#" || line.starts_with("# ")
+}
+
+/// Is a line a fixed width line e.g. `: output`?
+fn is_fixed_width(line: &str) -> bool {
+    let line = line.trim_start();
+    line == ":" || line.starts_with(": ")
+}
+
+/// Is a line a table line?
+fn is_table_line(line: &str) -> bool {
+    line.trim_start().starts_with('|')
+}
+
+/// Is a line a horizontal rule (five or more dashes)?
+fn is_rule(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 5 && line.chars().all(|char| char == '-')
+}
+
+/// Is a line a planning line e.g. `SCHEDULED: <2024-03-01 Fri>`
+fn is_planning(line: &str) -> bool {
+    let line = line.trim_start();
+    ["SCHEDULED:", "DEADLINE:", "CLOSED:"]
+        .iter()
+        .any(|keyword| line.starts_with(keyword))
+}
+
+/// Parse a list item line
+///
+/// Returns the indentation of the item, the order of the list, the width
+/// of the bullet (including the following space), and the text after it.
+fn list_item(line: &str) -> Option<(usize, ListOrder, usize, &str)> {
+    let indent = indentation(line);
+    let rest = &line[indent..];
+
+    // Items may have no text on the same line as the bullet (e.g. when the
+    // first block of the item is a source block) in which case lines have
+    // had their trailing space trimmed
+    let (order, width) =
+        if matches!(rest.get(..2), Some("- " | "+ " | "* ")) || matches!(rest, "-" | "+") {
+            (ListOrder::Unordered, 2)
+        } else {
+            let digits = rest.chars().take_while(char::is_ascii_digit).count();
+            if digits == 0 || !matches!(rest[digits..].chars().next(), Some('.' | ')')) {
+                return None;
+            }
+            match rest[digits + 1..].chars().next() {
+                Some(' ') | None => (ListOrder::Ascending, digits + 2),
+                _ => return None,
+            }
+        };
+
+    Some((indent, order, width, rest.get(width..).unwrap_or_default()))
+}
+
+/// Does a line interrupt a paragraph?
+fn interrupts(line: &str) -> bool {
+    line.trim().is_empty()
+        || keyword(line).is_some()
+        || block_start(line).is_some()
+        || is_table_line(line)
+        || is_fixed_width(line)
+        || list_item(line).is_some()
+        || drawer(line).is_some()
+}
+
+/// Get the admonition type for the name of a special block e.g. `#+begin_note`
+fn admonition_type(name: &str) -> Option<AdmonitionType> {
+    Some(match name {
+        "note" => AdmonitionType::Note,
+        "info" => AdmonitionType::Info,
+        "tip" => AdmonitionType::Tip,
+        "important" => AdmonitionType::Important,
+        "success" => AdmonitionType::Success,
+        "failure" => AdmonitionType::Failure,
+        "warning" => AdmonitionType::Warning,
+        "caution" | "danger" => AdmonitionType::Danger,
+        "error" => AdmonitionType::Error,
+        _ => return None,
+    })
+}
+
+/// Parse Babel header arguments e.g. `:exports results :cache yes`
+///
+/// Returns the lowercased name and value of each argument.
+fn header_args<'a>(params: impl Iterator<Item = &'a str>) -> Vec<(String, String)> {
+    let mut args: Vec<(String, String)> = Vec::new();
+    for token in params.flat_map(str::split_whitespace) {
+        if let Some(name) = token.strip_prefix(':') {
+            args.push((name.to_lowercase(), String::new()));
+        } else if let Some((.., value)) = args.last_mut() {
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(token);
+        }
+    }
+    args
+}
+
+/// Get the code within a block
+///
+/// Removes the comma used to escape lines which would otherwise be interpreted
+/// as Org syntax (e.g. `,* not a heading`) and any common indentation.
+fn block_code(lines: &[String]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indentation(line))
+        .min()
+        .unwrap_or_default();
+
+    lines
+        .iter()
+        .map(|line| {
+            let line = line.get(indent..).unwrap_or_default();
+            let content = line.trim_start();
+            match content.strip_prefix(',') {
+                Some(rest) if rest.starts_with(['*', ',']) || rest.starts_with("#+") => {
+                    [&line[..line.len() - content.len()], rest].concat()
+                }
+                _ => line.to_string(),
+            }
+        })
+        .join("\n")
+}
+
+/// Is a link target an image?
+fn is_image(target: &str) -> bool {
+    let target = target.to_lowercase();
+    [".png", ".jpg", ".jpeg", ".gif", ".svg", ".webp"]
+        .iter()
+        .any(|ext| target.ends_with(ext))
+}
+
+/// Parse an author line e.g. `Jane Doe <jane@example.org>, John Smith`
+fn authors(line: &str) -> Vec<Author> {
+    line.split(',')
+        .flat_map(|author| author.split(" and "))
+        .map(str::trim)
+        .filter(|author| !author.is_empty())
+        .filter_map(|author| Person::from_str(author).ok())
+        .map(Author::Person)
+        .collect()
+}
+
+/// Convert a block decoded from `#+RESULTS:` into an output
+fn output(block: Block) -> Node {
+    match block {
+        Block::CodeBlock(code_block) if code_block.programming_language.is_none() => {
+            Node::String(code_block.code.to_string())
+        }
+        Block::Paragraph(mut paragraph)
+            if matches!(paragraph.content.as_slice(), [Inline::ImageObject(..)]) =>
+        {
+            match paragraph.content.pop() {
+                Some(Inline::ImageObject(image)) => Node::ImageObject(image),
+                _ => Node::from(Block::Paragraph(paragraph)),
+            }
+        }
+        block => Node::from(block),
+    }
+}
+
+impl Context {
+    /// Record a document keyword
+    ///
+    /// Keywords which are repeated (e.g. `#+PROPERTY:`) have their values
+    /// joined by newlines so that they are not lost.
+    fn keyword(&mut self, name: String, value: &str) {
+        match self.keywords.get_mut(&name) {
+            Some(existing) => {
+                existing.push('\n');
+                existing.push_str(value);
+            }
+            None => {
+                self.keywords.insert(name, value.to_string());
+            }
+        }
+    }
+
+    /// Set article properties from document keywords
+    ///
+    /// Keywords which do not correspond to an article property are
+    /// kept in `extra` so that they can be encoded again.
+    fn article_keywords(&mut self, article: &mut Article) {
+        let mut extra = Object::new();
+
+        if let Some(mut author) = self.keywords.shift_remove("author") {
+            if let Some(email) = self.keywords.shift_remove("email") {
+                author = format!("{author} <{email}>");
+            }
+            article.authors = Some(authors(&author));
+        }
+
+        let keywords = std::mem::take(&mut self.keywords);
+        for (name, value) in keywords {
+            match name.as_str() {
+                "title" => article.title = Some(self.inlines(&value)),
+                "description" => article.description = Some(value),
+                "keywords" => {
+                    let keywords = if value.contains(',') {
+                        value.split(',').map(str::trim).collect_vec()
+                    } else {
+                        value.split_whitespace().collect_vec()
+                    };
+                    article.keywords = Some(
+                        keywords
+                            .into_iter()
+                            .filter(|keyword| !keyword.is_empty())
+                            .map(String::from)
+                            .collect(),
+                    );
+                }
+                "date" => {
+                    // Dates may be timestamps e.g. `<2024-03-01 Fri>`
+                    let date = value
+                        .trim_matches(['<', '>', '[', ']'])
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default();
+                    match Date::from_str(date) {
+                        Ok(date) => article.date_published = Some(date),
+                        Err(..) => {
+                            extra.insert(name, Primitive::String(value));
+                        }
+                    }
+                }
+                _ => {
+                    extra.insert(name, Primitive::String(value));
+                }
+            }
+        }
+
+        if !extra.is_empty() {
+            article.options.extra = Some(extra);
+        }
+    }
+
+    /// Decode lines into blocks, grouping headings and the content under them into sections
+    fn sections(&mut self, lines: &[String]) -> Vec<Block> {
+        let first = lines
+            .iter()
+            .position(|line| heading(line).is_some())
+            .unwrap_or(lines.len());
+        let mut blocks = self.blocks(&lines[..first]);
+
+        let mut index = first;
+        while let Some((level, text)) = lines.get(index).and_then(|line| heading(line)) {
+            let end = lines[index + 1..]
+                .iter()
+                .position(|line| heading(line).map_or(false, |(other, ..)| other <= level))
+                .map_or(lines.len(), |offset| index + 1 + offset);
+            blocks.push(self.section(level, text, &lines[index + 1..end]));
+            index = end;
+        }
+
+        blocks
+    }
+
+    /// Decode a heading, and the lines under it, into a section
+    ///
+    /// The `CUSTOM_ID` (or `ID`) property of the heading is used as the id of the section.
+    fn section(&mut self, level: usize, text: &str, lines: &[String]) -> Block {
+        let mut id: Option<String> = None;
+        let mut index = 0;
+
+        if lines.first().map_or(false, |line| is_planning(line)) {
+            self.losses.add("Org planning");
+            index += 1;
+        }
+
+        if lines
+            .get(index)
+            .and_then(|line| drawer(line))
+            .map_or(false, |name| name.eq_ignore_ascii_case("properties"))
+        {
+            let end = lines[index + 1..]
+                .iter()
+                .position(|line| is_drawer_end(line))
+                .map_or(lines.len(), |offset| index + 1 + offset);
+            for line in &lines[index + 1..end] {
+                match property(line) {
+                    Some((name, value)) if name.eq_ignore_ascii_case("custom_id") => {
+                        id = Some(value.to_string())
+                    }
+                    Some((name, value)) if name.eq_ignore_ascii_case("id") => {
+                        id = id.or_else(|| Some(value.to_string()))
+                    }
+                    _ => self.losses.add("Org heading property"),
+                }
+            }
+            index = (end + 1).min(lines.len());
+        }
+
+        let text = self.heading_text(text);
+        let heading = Heading::new(level as i64, self.inlines(text));
+
+        let mut content = vec![Block::Heading(heading)];
+        content.append(&mut self.sections(&lines[index..]));
+
+        Block::Section(Section {
+            id,
+            ..Section::new(content)
+        })
+    }
+
+    /// Remove any TODO keyword, priority and tags from the text of a heading
+    fn heading_text<'t>(&mut self, text: &'t str) -> &'t str {
+        let mut text = text;
+
+        if let Some(rest) = ["TODO ", "DONE "]
+            .iter()
+            .find_map(|keyword| text.strip_prefix(keyword))
+        {
+            self.losses.add("Org TODO keyword");
+            text = rest;
+        }
+
+        if text.starts_with("[#") && text.get(3..5) == Some("] ") {
+            self.losses.add("Org heading priority");
+            text = &text[5..];
+        }
+
+        if let Some((rest, tags)) = text.rsplit_once(' ') {
+            if tags.len() > 1 && tags.starts_with(':') && tags.ends_with(':') {
+                self.losses.add("Org heading tags");
+                text = rest.trim_end();
+            }
+        }
+
+        text
+    }
+
+    /// Decode lines into blocks
+    fn blocks(&mut self, lines: &[String]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+
+        // Affiliated keywords which apply to the next element
+        let mut affiliated = Affiliated::default();
+
+        let mut index = 0;
+        while let Some(line) = lines.get(index) {
+            if line.trim().is_empty() || is_comment(line) {
+                index += 1;
+                continue;
+            }
+
+            if let Some((key, value)) = keyword(line) {
+                index += 1;
+                match key.as_str() {
+                    "NAME" => affiliated.name = Some(value.to_string()),
+                    "CAPTION" => affiliated.caption = Some(value.to_string()),
+                    "HEADER" => affiliated.header.push(value.to_string()),
+                    "RESULTS" => {
+                        let (outputs, end) = self.results(lines, index);
+                        index = end;
+                        match blocks.last_mut() {
+                            Some(Block::CodeChunk(chunk)) => chunk.outputs = outputs,
+                            _ => self.losses.add("Org results"),
+                        }
+                    }
+                    "INCLUDE" => {
+                        let (source, rest) = match value.strip_prefix('"') {
+                            Some(value) => value.split_once('"').unwrap_or((value, "")),
+                            None => value.split_once(' ').unwrap_or((value, "")),
+                        };
+                        if !rest.trim().is_empty() {
+                            self.losses.add("Org include arguments");
+                        }
+                        blocks.push(Block::IncludeBlock(IncludeBlock::new(source.to_string())));
+                    }
+                    "CALL" => self.losses.add("Org babel call"),
+                    "TBLFM" => self.losses.add("Org table formula"),
+                    key if key.starts_with("ATTR_") => self.losses.add("Org export attributes"),
+                    key => self.keyword(key.to_lowercase(), value),
+                }
+                continue;
+            }
+
+            let element_affiliated = std::mem::take(&mut affiliated);
+            let (elements, end) = self.element(lines, index, &element_affiliated);
+            index = end;
+
+            for block in elements {
+                blocks.push(self.identify(block, &element_affiliated));
+            }
+        }
+
+        blocks
+    }
+
+    /// Decode the element starting at `start`
+    ///
+    /// Returns the blocks decoded (most elements are a single block) and the
+    /// index of the line after the element.
+    fn element(
+        &mut self,
+        lines: &[String],
+        start: usize,
+        affiliated: &Affiliated,
+    ) -> (Vec<Block>, usize) {
+        let line = &lines[start];
+
+        if let Some((name, params)) = block_start(line) {
+            let close = ["#+end_", &name].concat();
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| line.trim().eq_ignore_ascii_case(&close))
+                .map_or(lines.len(), |offset| start + 1 + offset);
+            let blocks = self.greater_block(&name, params, &lines[start + 1..end], affiliated);
+            return (blocks, (end + 1).min(lines.len()));
+        }
+
+        if let Some(name) = drawer(line) {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| is_drawer_end(line))
+                .map_or(lines.len(), |offset| start + 1 + offset);
+            if name.eq_ignore_ascii_case("properties") {
+                // File level properties are kept as `#+PROPERTY:` keywords
+                for (name, value) in lines[start + 1..end]
+                    .iter()
+                    .filter_map(|line| property(line))
+                {
+                    self.keyword("property".to_string(), &[name, " ", value].concat());
+                }
+            } else {
+                self.losses.add("Org drawer");
+            }
+            return (Vec::new(), (end + 1).min(lines.len()));
+        }
+
+        let end_of = |predicate: fn(&str) -> bool| {
+            lines[start..]
+                .iter()
+                .position(|line| !predicate(line))
+                .map_or(lines.len(), |offset| start + offset)
+        };
+
+        if is_table_line(line) {
+            let end = end_of(is_table_line);
+            let table = self.table(&lines[start..end]);
+            return (vec![Block::Table(table)], end);
+        }
+
+        if is_fixed_width(line) {
+            let end = end_of(is_fixed_width);
+            let code = lines[start..end]
+                .iter()
+                .map(|line| {
+                    let line = line.trim_start();
+                    line.strip_prefix(": ").unwrap_or(&line[1..])
+                })
+                .join("\n");
+            return (vec![cb(code, None::<String>)], end);
+        }
+
+        if is_rule(line) {
+            return (vec![tb()], start + 1);
+        }
+
+        // Display math e.g. `\[ x^2 \]` or a LaTeX environment
+        let trimmed = line.trim();
+        let math = if trimmed.starts_with("\\[") {
+            Some(("\\[", "\\]"))
+        } else if trimmed.starts_with("$$") {
+            Some(("$$", "$$"))
+        } else if trimmed.starts_with("\\begin{") {
+            Some(("", ""))
+        } else {
+            None
+        };
+        if let Some((open, close)) = math {
+            let end = (start..lines.len())
+                .find(|&index| {
+                    let line = lines[index].trim();
+                    if open.is_empty() {
+                        line.starts_with("\\end{")
+                    } else {
+                        line.ends_with(close) && (index > start || line.len() >= 2 * close.len())
+                    }
+                })
+                .unwrap_or(lines.len() - 1);
+            let code = lines[start..=end].iter().map(|line| line.trim()).join("\n");
+            let code = code
+                .strip_prefix(open)
+                .and_then(|code| code.strip_suffix(close))
+                .unwrap_or(&code)
+                .trim()
+                .to_string();
+            return (vec![mb(code, Some("tex"))], end + 1);
+        }
+
+        if let Some((indent, order, ..)) = list_item(line) {
+            let (list, end) = self.list(lines, start, indent, order);
+            return (vec![list], end);
+        }
+
+        // Paragraph, continuing until a blank line or the start of another element
+        let end = (start + 1..lines.len())
+            .find(|&index| interrupts(&lines[index]))
+            .unwrap_or(lines.len());
+        let text = lines[start..end].iter().map(|line| line.trim()).join("\n");
+        let content = self.inlines(&text);
+
+        let blocks = if content.is_empty() {
+            Vec::new()
+        } else {
+            vec![p(content)]
+        };
+        (blocks, end)
+    }
+
+    /// Decode a greater block e.g. `#+begin_quote`
+    fn greater_block(
+        &mut self,
+        name: &str,
+        params: &str,
+        lines: &[String],
+        affiliated: &Affiliated,
+    ) -> Vec<Block> {
+        let block = match name {
+            "src" => self.src_block(params, &affiliated.header, lines),
+            "example" => cb(block_code(lines), None::<String>),
+            "quote" => qb(self.blocks(lines)),
+            "comment" => return Vec::new(),
+            "export" => {
+                self.losses.add("Org export block");
+                return Vec::new();
+            }
+            _ => match admonition_type(name) {
+                Some(admonition_type) => Block::Admonition(Admonition {
+                    admonition_type,
+                    content: self.blocks(lines),
+                    ..Default::default()
+                }),
+                None => {
+                    self.losses.add(format!("Org {name} block"));
+                    return self.blocks(lines);
+                }
+            },
+        };
+
+        vec![block]
+    }
+
+    /// Decode a source block
+    ///
+    /// Source blocks are decoded as executable code chunks unless evaluation
+    /// is disabled (i.e. `:eval no` or `:eval never`), in which case they are
+    /// decoded as code blocks. `:exports results` hides the code, `:exports code`
+    /// hides the outputs, `:exports none` hides both, and `:cache yes` is equivalent
+    /// to only executing when needed.
+    fn src_block(&mut self, params: &str, header: &[String], lines: &[String]) -> Block {
+        let (lang, args) = params.split_once(' ').unwrap_or((params, ""));
+        let lang = (!lang.is_empty()).then(|| lang.to_string());
+        let code = block_code(lines);
+
+        let mut chunk = CodeChunk {
+            code: code.into(),
+            programming_language: lang,
+            ..Default::default()
+        };
+
+        let args = header_args([args].into_iter().chain(header.iter().map(String::as_str)));
+        for (name, value) in args {
+            match (name.as_str(), value.as_str()) {
+                ("eval", "no" | "never") => {
+                    return cb(chunk.code, chunk.programming_language);
+                }
+                ("eval", "yes") | ("exports", "both") | ("cache", "no") => {}
+                ("exports", "code") => chunk.is_invisible = Some(true),
+                ("exports", "results") => chunk.is_code_hidden = Some(true),
+                ("exports", "none") => {
+                    chunk.is_invisible = Some(true);
+                    chunk.is_code_hidden = Some(true);
+                }
+                ("cache", "yes") => chunk.execution_mode = Some(ExecutionMode::Need),
+                (name, ..) => self.losses.add(format!("Org `:{name}` header argument")),
+            }
+        }
+
+        Block::CodeChunk(chunk)
+    }
+
+    /// Decode the results of a source block following a `#+RESULTS:` line
+    ///
+    /// Results are the element immediately after the line (e.g. fixed width
+    /// lines, a table, or a link to an image), or the blocks within a
+    /// `:results:` drawer.
+    fn results(&mut self, lines: &[String], start: usize) -> (Option<Vec<Node>>, usize) {
+        let Some(line) = lines.get(start).filter(|line| !line.trim().is_empty()) else {
+            return (None, start);
+        };
+
+        if drawer(line).map_or(false, |name| name.eq_ignore_ascii_case("results")) {
+            let end = lines[start + 1..]
+                .iter()
+                .position(|line| is_drawer_end(line))
+                .map_or(lines.len(), |offset| start + 1 + offset);
+            let outputs = self
+                .blocks(&lines[start + 1..end])
+                .into_iter()
+                .map(output)
+                .collect();
+            return (Some(outputs), (end + 1).min(lines.len()));
+        }
+
+        let (blocks, end) = self.element(lines, start, &Affiliated::default());
+        (Some(blocks.into_iter().map(output).collect()), end)
+    }
+
+    /// Apply affiliated keywords to a block
+    fn identify(&mut self, block: Block, affiliated: &Affiliated) -> Block {
+        let id = affiliated.name.clone();
+        let caption = |context: &mut Self| {
+            affiliated
+                .caption
+                .as_ref()
+                .map(|caption| vec![p(context.inlines(caption))])
+        };
+
+        match block {
+            Block::CodeChunk(mut chunk) => {
+                chunk.id = id;
+                chunk.caption = caption(self);
+                Block::CodeChunk(chunk)
+            }
+            Block::Table(mut table) => {
+                table.id = id;
+                table.caption = caption(self);
+                Block::Table(table)
+            }
+            Block::Admonition(mut admonition) => {
+                admonition.id = id;
+                admonition.title = affiliated
+                    .caption
+                    .as_ref()
+                    .map(|caption| self.inlines(caption));
+                Block::Admonition(admonition)
+            }
+            Block::Paragraph(paragraph)
+                if matches!(paragraph.content.as_slice(), [Inline::ImageObject(..)])
+                    && (id.is_some() || affiliated.caption.is_some()) =>
+            {
+                Block::Figure(Figure {
+                    id,
+                    caption: caption(self),
+                    ..Figure::new(vec![Block::Paragraph(paragraph)])
+                })
+            }
+            Block::CodeBlock(mut code_block) => {
+                code_block.id = id;
+                if affiliated.caption.is_some() {
+                    self.losses.add("Org caption");
+                }
+                Block::CodeBlock(code_block)
+            }
+            Block::MathBlock(mut math_block) => {
+                math_block.id = id;
+                if affiliated.caption.is_some() {
+                    self.losses.add("Org caption");
+                }
+                Block::MathBlock(math_block)
+            }
+            block => {
+                if id.is_some() {
+                    self.losses.add("Org name");
+                }
+                if affiliated.caption.is_some() {
+                    self.losses.add("Org caption");
+                }
+                block
+            }
+        }
+    }
+
+    /// Decode a list starting at `start`
+    ///
+    /// Items continue while lines are indented more than the bullet, and the
+    /// list ends at a less indented line or two consecutive blank lines.
+    fn list(
+        &mut self,
+        lines: &[String],
+        start: usize,
+        indent: usize,
+        order: ListOrder,
+    ) -> (Block, usize) {
+        let mut items = Vec::new();
+        let mut index = start;
+        while let Some((.., width, text)) = lines
+            .get(index)
+            .and_then(|line| list_item(line))
+            .filter(|(item_indent, item_order, ..)| *item_indent == indent && *item_order == order)
+        {
+            let content_indent = indent + width;
+            let mut body = vec![text.to_string()];
+            index += 1;
+
+            let mut blank = false;
+            let mut ended = false;
+            while let Some(line) = lines.get(index) {
+                if line.trim().is_empty() {
+                    if blank {
+                        ended = true;
+                        break;
+                    }
+                    blank = true;
+                    body.push(String::new());
+                    index += 1;
+                    continue;
+                }
+
+                if indentation(line) <= indent {
+                    break;
+                }
+
+                blank = false;
+                let line = if indentation(line) >= content_indent {
+                    &line[content_indent..]
+                } else {
+                    line.trim_start()
+                };
+                body.push(line.to_string());
+                index += 1;
+            }
+
+            while body.last().map_or(false, |line| line.is_empty()) {
+                body.pop();
+            }
+
+            items.push(self.list_item(body));
+
+            if ended {
+                // Two consecutive blank lines end the list
+                break;
+            }
+        }
+
+        (Block::List(List::new(items, order)), index)
+    }
+
+    /// Decode the lines of a list item
+    fn list_item(&mut self, mut lines: Vec<String>) -> ListItem {
+        let mut is_checked = None;
+        if let Some(first) = lines.first_mut() {
+            if first.starts_with("[@") {
+                if let Some(end) = first.find("] ") {
+                    self.losses.add("Org list counter");
+                    *first = first[end + 2..].to_string();
+                }
+            }
+
+            let checked = match first.get(..4) {
+                Some("[X] " | "[x] ") => Some(true),
+                Some("[ ] ") => Some(false),
+                Some("[-] ") => {
+                    self.losses.add("Org partial checkbox");
+                    Some(false)
+                }
+                _ => None,
+            };
+            if checked.is_some() {
+                is_checked = checked;
+                *first = first[4..].to_string();
+            }
+
+            if first.contains(" :: ") {
+                self.losses.add("Org description list");
+            }
+        }
+
+        ListItem {
+            is_checked,
+            ..ListItem::new(self.blocks(&lines))
+        }
+    }
+
+    /// Decode a table
+    ///
+    /// Rows before the first horizontal separator (if any) are header rows.
+    fn table(&mut self, lines: &[String]) -> Table {
+        let mut rows = Vec::new();
+        let mut header_rows = None;
+        for line in lines {
+            let line = line.trim();
+            if line.starts_with("|-") {
+                if header_rows.is_none() {
+                    header_rows = Some(rows.len());
+                }
+                continue;
+            }
+
+            let inner = line.strip_prefix('|').unwrap_or(line);
+            let inner = inner.strip_suffix('|').unwrap_or(inner);
+            let cells = inner
+                .split('|')
+                .map(|cell| {
+                    let content = self.inlines(cell.trim());
+                    let content = if content.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![p(content)]
+                    };
+                    TableCell::new(content)
+                })
+                .collect_vec();
+            rows.push(TableRow::new(cells));
+        }
+
+        if let Some(count) = header_rows.filter(|count| *count < rows.len()) {
+            for row in rows.iter_mut().take(count) {
+                row.row_type = Some(TableRowType::HeaderRow);
+                for cell in row.cells.iter_mut() {
+                    cell.cell_type = Some(TableCellType::HeaderCell);
+                }
+            }
+        }
+
+        Table::new(rows)
+    }
+
+    /// Decode text into inlines
+    fn inlines(&mut self, text: &str) -> Vec<Inline> {
+        let chars = text.chars().collect_vec();
+
+        let mut inlines = Vec::new();
+        let mut current = String::new();
+
+        let mut index = 0;
+        while index < chars.len() {
+            let char = chars[index];
+
+            // Zero width spaces are used to escape markup
+            if char == '\u{200b}' {
+                index += 1;
+                continue;
+            }
+
+            if char == '\n' {
+                // Explicit line breaks are not supported so become spaces
+                if current.ends_with("\\\\") {
+                    current.truncate(current.len() - 2);
+                }
+                current.push(' ');
+                index += 1;
+                continue;
+            }
+
+            if let Some((markup, end)) = self.inline_markup(&chars, index) {
+                if !current.is_empty() {
+                    inlines.push(t(std::mem::take(&mut current)));
+                }
+                inlines.extend(markup);
+                index = end;
+                continue;
+            }
+
+            current.push(char);
+            index += 1;
+        }
+
+        if current.ends_with("\\\\") {
+            current.truncate(current.len() - 2);
+        }
+        if !current.is_empty() {
+            inlines.push(t(current));
+        }
+
+        inlines
+    }
+
+    /// Attempt to decode inline markup starting at `start`
+    ///
+    /// Returns the inlines (if any) and the index after the markup.
+    fn inline_markup(&mut self, chars: &[char], start: usize) -> Option<(Vec<Inline>, usize)> {
+        let rest = &chars[start..];
+        let starts_with = |prefix: &str| {
+            let prefix = prefix.chars().collect_vec();
+            rest.starts_with(&prefix)
+        };
+        let text = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+        let find = |from: usize, pattern: &str| {
+            let pattern = pattern.chars().collect_vec();
+            (from..chars.len()).find(|&index| chars[index..].starts_with(&pattern))
+        };
+
+        // Markup must be preceded by the start of text, whitespace or some punctuation
+        let boundary = start == 0 || {
+            let previous = chars[start - 1];
+            previous.is_whitespace() || "-({'\"\u{200b}".contains(previous)
+        };
+
+        // Find the end of emphasis markup (e.g. `*bold*`) which must not
+        // have whitespace at the start or end of its content and must be
+        // followed by the end of text, whitespace or some punctuation
+        let emphasis = |marker: char| -> Option<usize> {
+            if !boundary
+                || chars
+                    .get(start + 1)
+                    .map_or(true, |next| next.is_whitespace() || *next == '\u{200b}')
+            {
+                return None;
+            }
+            (start + 2..chars.len()).find(|&index| {
+                chars[index] == marker
+                    && !chars[index - 1].is_whitespace()
+                    && chars.get(index + 1).map_or(true, |next| {
+                        next.is_whitespace() || "-.,;:!?')}[\"\\".contains(*next)
+                    })
+            })
+        };
+
+        match chars[start] {
+            '*' | '/' | '_' | '+' if !starts_with("_{") => {
+                let marker = chars[start];
+                let end = emphasis(marker)?;
+                let content = self.inlines(&text(start + 1, end));
+                let inline = match marker {
+                    '*' => stg(content),
+                    '/' => em(content),
+                    '_' => u(content),
+                    _ => stk(content),
+                };
+                Some((vec![inline], end + 1))
+            }
+            '=' | '~' => {
+                let end = emphasis(chars[start])?;
+                Some((vec![ci(text(start + 1, end))], end + 1))
+            }
+            '^' | '_' if chars.get(start + 1) == Some(&'{') => {
+                let end = find(start + 2, "}")?;
+                let content = self.inlines(&text(start + 2, end));
+                let inline = if chars[start] == '^' {
+                    sup(content)
+                } else {
+                    sub(content)
+                };
+                Some((vec![inline], end + 1))
+            }
+            '\\' if starts_with("\\(") => {
+                let end = find(start + 2, "\\)")?;
+                Some((vec![mi(text(start + 2, end), Some("tex"))], end + 2))
+            }
+            '$' => {
+                let end = emphasis('$')?;
+                Some((vec![mi(text(start + 1, end), Some("tex"))], end + 1))
+            }
+            '[' if starts_with("[[") => {
+                let end = find(start + 2, "]]")?;
+                let link = text(start + 2, end);
+                let (target, description) = match link.split_once("][") {
+                    Some((target, description)) => (target, Some(description)),
+                    None => (link.as_str(), None),
+                };
+                Some((vec![self.link(target, description)], end + 2))
+            }
+            '[' if starts_with("[fn:") => {
+                let end = find(start, "]")?;
+                self.losses.add("Org footnote");
+                Some((Vec::new(), end + 1))
+            }
+            '{' if starts_with("{{{") => {
+                let end = find(start + 3, "}}}")?;
+                self.losses.add("Org macro");
+                Some((Vec::new(), end + 3))
+            }
+            's' if boundary && starts_with("src_") => {
+                let expression = self.inline_src(chars, start)?;
+                Some((vec![Inline::CodeExpression(expression.0)], expression.1))
+            }
+            'c' if boundary && starts_with("call_") => {
+                let open = find(start, "(")?;
+                let end = find(open, ")")?;
+                self.losses.add("Org babel call");
+                Some((vec![t(text(start, end + 1))], end + 1))
+            }
+            'h' | 'f' | 'm' if boundary => {
+                // Bare URLs e.g. `https://example.org`, excluding trailing punctuation
+                if !["http://", "https://", "ftp://", "mailto:"]
+                    .iter()
+                    .any(|scheme| starts_with(scheme))
+                {
+                    return None;
+                }
+                let mut end = (start..chars.len())
+                    .find(|&index| chars[index].is_whitespace())
+                    .unwrap_or(chars.len());
+                while end > start && ".,;:!?)".contains(chars[end - 1]) {
+                    end -= 1;
+                }
+                let url = text(start, end);
+                Some((vec![lnk([t(url.clone())], url)], end))
+            }
+            _ => None,
+        }
+    }
+
+    /// Decode a link e.g. `[[https://example.org][Example]]`
+    fn link(&mut self, target: &str, description: Option<&str>) -> Inline {
+        let path = target.strip_prefix("file:").unwrap_or(target);
+
+        if description.is_none() && is_image(path) && !target.contains("://") {
+            return img(path);
+        }
+
+        let target = if let Some(id) = target.strip_prefix("id:") {
+            ["#", id].concat()
+        } else if target.starts_with('*') {
+            self.losses.add("Org heading link");
+            target.to_string()
+        } else {
+            path.to_string()
+        };
+
+        let content = match description {
+            Some(description) => self.inlines(description),
+            None => vec![t(target.trim_start_matches('#'))],
+        };
+
+        lnk(content, target)
+    }
+
+    /// Decode an inline source block e.g. `src_python[:exports results]{1 + 1} {{{results(=2=)}}}`
+    ///
+    /// Returns the code expression, including any output from a following
+    /// `results` macro, and the index after it.
+    fn inline_src(&mut self, chars: &[char], start: usize) -> Option<(CodeExpression, usize)> {
+        let lang_start = start + 4;
+        let lang_end = (lang_start..chars.len()).find(|&index| {
+            !(chars[index].is_alphanumeric() || matches!(chars[index], '-' | '+'))
+        })?;
+        if lang_end == lang_start {
+            return None;
+        }
+        let lang = chars[lang_start..lang_end].iter().collect::<String>();
+
+        let mut index = lang_end;
+        if chars.get(index) == Some(&'[') {
+            let end = (index..chars.len()).find(|&index| chars[index] == ']')?;
+            let args = chars[index + 1..end].iter().collect::<String>();
+            if !args.trim().is_empty() {
+                self.losses.add("Org inline header arguments");
+            }
+            index = end + 1;
+        }
+
+        if chars.get(index) != Some(&'{') {
+            return None;
+        }
+
+        // Find the matching closing brace
+        let mut depth = 0;
+        let end = (index..chars.len()).find(|&index| {
+            match chars[index] {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        })?;
+        let code = chars[index + 1..end].iter().collect::<String>();
+
+        let mut expression = CodeExpression {
+            code: code.into(),
+            programming_language: Some(lang),
+            ..Default::default()
+        };
+        let mut index = end + 1;
+
+        // Any following results macro is the output of the expression
+        let results = " {{{results(".chars().collect_vec();
+        if chars[index..].starts_with(&results) {
+            let from = index + results.len();
+            if let Some(close) =
+                (from..chars.len()).find(|&index| chars[index..].starts_with(&[')', '}', '}', '}']))
+            {
+                let value = chars[from..close].iter().collect::<String>();
+                let value = value
+                    .strip_prefix('=')
+                    .and_then(|value| value.strip_suffix('='))
+                    .unwrap_or(&value);
+                expression.output = Some(Box::new(Node::String(value.to_string())));
+                index = close + 4;
+            }
+        }
+
+        Some((expression, index))
+    }
+}
@@ -0,0 +1,833 @@
+use codec::{
+    common::itertools::Itertools,
+    schema::{
+        Admonition, AdmonitionType, Article, Author, AuthorRoleAuthor, Block, CodeChunk,
+        CodeExpression, ExecutionMode, Figure, Heading, ImageObject, Inline, List, ListOrder, Node,
+        NodeId, NodeType, Paragraph, Primitive, Section, Table, TableRowType,
+    },
+    EncodeInfo, Losses, Mapping,
+};
+use codec_text_trait::to_text;
+
+/// Encode a node to Org
+///
+/// Only articles and blocks can be encoded. Node types that can not be
+/// represented in Org are recorded as losses. A mapping between nodes
+/// and their location in the Org is created for use by language servers.
+pub(super) fn encode(node: &Node) -> (String, EncodeInfo) {
+    let mut context = Context::default();
+
+    match node {
+        Node::Article(article) => context.article(article),
+        node => match Block::try_from(node.clone()) {
+            Ok(block) => context.block(&block),
+            Err(..) => context.losses.add(node.to_string()),
+        },
+    }
+
+    let info = EncodeInfo {
+        losses: context.losses,
+        mapping: context.mapping,
+        ..Default::default()
+    };
+
+    (context.content, info)
+}
+
+#[derive(Default)]
+struct Context {
+    /// The encoded Org
+    content: String,
+
+    /// The number of characters in `content`
+    ///
+    /// Maintained separately to avoid counting characters each time a node is mapped.
+    chars: usize,
+
+    /// The nodes currently being encoded and the character index at which each started
+    node_stack: Vec<(NodeType, NodeId, usize)>,
+
+    /// The mapping between nodes and their location in `content`
+    mapping: Mapping,
+
+    /// Losses when encoding
+    losses: Losses,
+
+    /// The indentation to add at the start of each line (e.g. within list items)
+    indent: String,
+
+    /// Whether the last character added to `content` was a newline
+    line_start: bool,
+}
+
+/// The state of a [`Context`] which can be restored if nothing is encoded for a node
+struct Checkpoint {
+    len: usize,
+    chars: usize,
+    line_start: bool,
+}
+
+/// Get the name of an author for the `#+AUTHOR:` keyword
+fn author_name(author: &Author) -> Option<String> {
+    match author {
+        Author::Person(person) => Some(person.as_string()),
+        Author::Organization(organization) => organization.name.clone(),
+        Author::SoftwareApplication(software) => Some(software.name.clone()),
+        Author::AuthorRole(role) => match &role.author {
+            AuthorRoleAuthor::Person(person) => Some(person.as_string()),
+            AuthorRoleAuthor::Organization(organization) => organization.name.clone(),
+            AuthorRoleAuthor::SoftwareApplication(software) => Some(software.name.clone()),
+            AuthorRoleAuthor::Thing(..) => None,
+        },
+    }
+}
+
+/// Escape code within a block
+///
+/// Lines which would otherwise be interpreted as Org syntax (e.g. `* not a heading`)
+/// are escaped with a comma.
+fn block_code(code: &str) -> String {
+    code.lines()
+        .map(|line| {
+            let content = line.trim_start();
+            if content.starts_with(['*', ',']) || content.starts_with("#+") {
+                let indent = &line[..line.len() - content.len()];
+                [indent, ",", content].concat()
+            } else {
+                line.to_string()
+            }
+        })
+        .join("\n")
+}
+
+/// Get the target of a link to a path or URL
+///
+/// Org interprets link targets without a scheme as searches for a heading
+/// so local paths are prefixed with `file:`.
+fn link_target(target: &str) -> String {
+    if target.starts_with('#') || target.contains(':') {
+        target.to_string()
+    } else {
+        ["file:", target].concat()
+    }
+}
+
+impl Context {
+    /// Push a string onto the content, indenting any new lines
+    fn str(&mut self, value: &str) -> &mut Self {
+        for char in value.chars() {
+            if self.line_start && char != '\n' && !self.indent.is_empty() {
+                self.content.push_str(&self.indent);
+                self.chars += self.indent.chars().count();
+            }
+            self.line_start = char == '\n';
+            self.content.push(char);
+            self.chars += 1;
+        }
+        self
+    }
+
+    /// Enter a node, recording where it starts
+    fn enter(&mut self, node_type: NodeType, node_id: NodeId) -> &mut Self {
+        self.node_stack.push((node_type, node_id, self.chars));
+        self
+    }
+
+    /// Exit a node, adding a mapping entry from where it started to the current position
+    fn exit(&mut self) -> &mut Self {
+        if let Some((node_type, node_id, start)) = self.node_stack.pop() {
+            self.mapping
+                .add(start, self.chars, node_type, node_id, None, None);
+        }
+        self
+    }
+
+    /// Create a checkpoint of the content
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.content.len(),
+            chars: self.chars,
+            line_start: self.line_start,
+        }
+    }
+
+    /// Restore the content to a checkpoint
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.content.truncate(checkpoint.len);
+        self.chars = checkpoint.chars;
+        self.line_start = checkpoint.line_start;
+    }
+
+    /// Encode a keyword e.g. `#+TITLE: A title`
+    fn keyword(&mut self, key: &str, value: &str) -> &mut Self {
+        self.str("#+").str(key).str(": ").str(value).str("\n")
+    }
+
+    /// Encode the `#+NAME:` and `#+CAPTION:` affiliated keywords of an element
+    ///
+    /// Only the first paragraph of a caption can be encoded.
+    fn affiliated(&mut self, id: Option<&str>, caption: Option<&[Block]>) {
+        if let Some(id) = id {
+            self.keyword("NAME", id);
+        }
+
+        match caption {
+            Some([Block::Paragraph(paragraph), rest @ ..]) => {
+                if !rest.is_empty() {
+                    self.losses.add("Caption.content");
+                }
+                self.str("#+CAPTION: ");
+                self.inlines(&paragraph.content);
+                self.str("\n");
+            }
+            Some([]) | None => {}
+            Some(..) => self.losses.add("Caption.content"),
+        }
+    }
+
+    /// Encode an article, including keywords for its metadata
+    fn article(&mut self, article: &Article) {
+        self.enter(NodeType::Article, article.node_id());
+
+        if let Some(title) = &article.title {
+            self.str("#+TITLE: ");
+            self.inlines(title);
+            self.str("\n");
+        }
+
+        let authors = article
+            .authors
+            .iter()
+            .flatten()
+            .filter_map(author_name)
+            .join(", ");
+        if !authors.is_empty() {
+            self.keyword("AUTHOR", &authors);
+        }
+
+        if let Some(date) = &article.date_published {
+            self.keyword("DATE", &date.value);
+        }
+
+        if let Some(description) = &article.description {
+            self.keyword("DESCRIPTION", description);
+        }
+
+        if let Some(keywords) = &article.keywords {
+            self.keyword("KEYWORDS", &keywords.join(", "));
+        }
+
+        for (name, value) in article.options.extra.iter().flat_map(|extra| extra.iter()) {
+            let value = match value {
+                Primitive::String(value) => value.clone(),
+                Primitive::Boolean(value) => value.to_string(),
+                Primitive::Integer(value) => value.to_string(),
+                Primitive::UnsignedInteger(value) => value.to_string(),
+                Primitive::Number(value) => value.to_string(),
+                _ => {
+                    self.losses.add("Article.extra");
+                    continue;
+                }
+            };
+
+            // Repeated keywords (e.g. `#+PROPERTY:`) are joined by newlines when decoded
+            let key = name.to_uppercase();
+            for value in value.lines() {
+                self.keyword(&key, value);
+            }
+        }
+
+        if !self.content.is_empty() && !article.content.is_empty() {
+            self.str("\n");
+        }
+
+        self.blocks(&article.content);
+
+        self.exit();
+    }
+
+    /// Encode blocks, separated by blank lines
+    fn blocks(&mut self, blocks: &[Block]) {
+        let mut previous: Option<&Block> = None;
+        for block in blocks {
+            let checkpoint = self.checkpoint();
+
+            if let Some(previous) = previous {
+                self.str("\n\n");
+
+                // Adjacent lists of the same kind need to be separated by two blank
+                // lines so that they are not joined into one list
+                if let (Block::List(previous), Block::List(list)) = (previous, block) {
+                    if (previous.order == ListOrder::Unordered)
+                        == (list.order == ListOrder::Unordered)
+                    {
+                        self.str("\n");
+                    }
+                }
+            }
+
+            let before = self.content.len();
+            self.block(block);
+
+            if self.content.len() == before {
+                // Nothing encoded for the block so remove any separator
+                self.restore(checkpoint);
+            } else {
+                previous = Some(block);
+            }
+        }
+    }
+
+    /// Encode a block
+    fn block(&mut self, block: &Block) {
+        let Some(node_id) = block.node_id() else {
+            return;
+        };
+
+        if let Block::Section(section) = block {
+            // Sections are not mapped since they do not have a distinct location
+            self.section(section);
+            return;
+        }
+
+        self.enter(block.node_type(), node_id);
+
+        match block {
+            Block::Paragraph(Paragraph { content, .. }) => match content.as_slice() {
+                [Inline::ImageObject(image)] => self.image(image),
+                _ => self.inlines(content),
+            },
+            Block::Heading(heading) => self.heading(heading, heading.id.as_deref()),
+            Block::CodeBlock(code_block) => {
+                if let Some(id) = &code_block.id {
+                    self.keyword("NAME", id);
+                }
+                match &code_block.programming_language {
+                    Some(lang) => {
+                        self.str("#+begin_src ").str(lang).str(" :eval no\n");
+                        self.str(&block_code(&code_block.code)).str("\n#+end_src");
+                    }
+                    None => {
+                        self.str("#+begin_example\n");
+                        self.str(&block_code(&code_block.code))
+                            .str("\n#+end_example");
+                    }
+                }
+            }
+            Block::CodeChunk(code_chunk) => self.code_chunk(code_chunk),
+            Block::MathBlock(math_block) => {
+                if let Some(id) = &math_block.id {
+                    self.keyword("NAME", id);
+                }
+                if !matches!(
+                    math_block.math_language.as_deref(),
+                    None | Some("tex" | "latex")
+                ) {
+                    self.losses.add("MathBlock.mathLanguage");
+                }
+                if math_block.code.trim_start().starts_with("\\begin{") {
+                    self.str(&math_block.code);
+                } else {
+                    self.str("\\[\n").str(&math_block.code).str("\n\\]");
+                }
+            }
+            Block::Admonition(admonition) => self.admonition(admonition),
+            Block::Figure(figure) => self.figure(figure),
+            Block::ImageObject(image) => self.image(image),
+            Block::IncludeBlock(include) => {
+                self.str("#+INCLUDE: \"").str(&include.source).str("\"");
+            }
+            Block::List(list) => self.list(list),
+            Block::QuoteBlock(quote) => {
+                self.str("#+begin_quote\n");
+                self.blocks(&quote.content);
+                self.str("\n#+end_quote");
+            }
+            Block::Table(table) => self.table(table),
+            Block::ThematicBreak(..) => {
+                self.str("-----");
+            }
+            _ => {
+                self.node_stack.pop();
+                self.losses.add(block.to_string());
+                return;
+            }
+        }
+
+        self.exit();
+    }
+
+    /// Encode a section
+    ///
+    /// If the section starts with a heading, the id of the section is
+    /// encoded as the `CUSTOM_ID` property of the heading.
+    fn section(&mut self, section: &Section) {
+        let Some((Block::Heading(heading), rest)) = section.content.split_first() else {
+            if section.id.is_some() {
+                self.losses.add("Section.id");
+            }
+            self.blocks(&section.content);
+            return;
+        };
+
+        self.enter(heading.node_type(), heading.node_id());
+        self.heading(heading, section.id.as_deref().or(heading.id.as_deref()));
+        self.exit();
+
+        let checkpoint = self.checkpoint();
+        self.str("\n\n");
+        let before = self.content.len();
+        self.blocks(rest);
+        if self.content.len() == before {
+            self.restore(checkpoint);
+        }
+    }
+
+    /// Encode a heading, with an id as a `CUSTOM_ID` property
+    fn heading(&mut self, heading: &Heading, id: Option<&str>) {
+        let level = heading.level.max(1) as usize;
+        self.str(&"*".repeat(level)).str(" ");
+        self.inlines(&heading.content);
+
+        if let Some(id) = id {
+            self.str("\n:PROPERTIES:\n:CUSTOM_ID: ")
+                .str(id)
+                .str("\n:END:");
+        }
+    }
+
+    /// Encode a code chunk as a source block followed by its results
+    fn code_chunk(&mut self, code_chunk: &CodeChunk) {
+        self.affiliated(code_chunk.id.as_deref(), code_chunk.caption.as_deref());
+
+        self.str("#+begin_src");
+        if let Some(lang) = &code_chunk.programming_language {
+            self.str(" ").str(lang);
+        }
+        match (
+            code_chunk.is_invisible == Some(true),
+            code_chunk.is_code_hidden == Some(true),
+        ) {
+            (true, true) => self.str(" :exports none"),
+            (true, false) => self.str(" :exports code"),
+            (false, true) => self.str(" :exports results"),
+            (false, false) => self,
+        };
+        match code_chunk.execution_mode {
+            Some(ExecutionMode::Need) => {
+                self.str(" :cache yes");
+            }
+            None | Some(ExecutionMode::Default) => {}
+            Some(..) => self.losses.add("CodeChunk.executionMode"),
+        }
+        self.str("\n");
+
+        self.str(&block_code(&code_chunk.code)).str("\n#+end_src");
+
+        if let Some(outputs) = code_chunk
+            .outputs
+            .as_ref()
+            .filter(|outputs| !outputs.is_empty())
+        {
+            self.str("\n\n#+RESULTS:");
+            if let Some(id) = &code_chunk.id {
+                self.str(" ").str(id);
+            }
+            self.str("\n");
+            self.outputs(outputs);
+        }
+    }
+
+    /// Encode the outputs of a code chunk
+    ///
+    /// A single output which is a primitive, image or table is encoded directly.
+    /// Otherwise, outputs are encoded within a `:results:` drawer.
+    fn outputs(&mut self, outputs: &[Node]) {
+        match outputs {
+            [output @ (Node::String(..)
+            | Node::Boolean(..)
+            | Node::Integer(..)
+            | Node::UnsignedInteger(..)
+            | Node::Number(..)
+            | Node::ImageObject(..)
+            | Node::Table(..))] => self.output(output),
+            _ => {
+                self.str(":results:\n");
+                for (index, output) in outputs.iter().enumerate() {
+                    if index > 0 {
+                        self.str("\n\n");
+                    }
+                    self.output(output);
+                }
+                self.str("\n:end:");
+            }
+        }
+    }
+
+    /// Encode an output of a code chunk
+    fn output(&mut self, output: &Node) {
+        let value = match output {
+            Node::String(value) => value.clone(),
+            Node::Boolean(value) => value.to_string(),
+            Node::Integer(value) => value.to_string(),
+            Node::UnsignedInteger(value) => value.to_string(),
+            Node::Number(value) => value.to_string(),
+            Node::ImageObject(image) => return self.image(image),
+            node => {
+                match Block::try_from(node.clone()) {
+                    Ok(block) => self.block(&block),
+                    Err(..) => self.losses.add(node.to_string()),
+                }
+                return;
+            }
+        };
+
+        // Primitive outputs are encoded as fixed width lines
+        let lines = value
+            .lines()
+            .map(|line| {
+                if line.is_empty() {
+                    ":".to_string()
+                } else {
+                    [": ", line].concat()
+                }
+            })
+            .join("\n");
+        self.str(if lines.is_empty() { ":" } else { &lines });
+    }
+
+    /// Encode an admonition as a special block e.g. `#+begin_note`
+    fn admonition(&mut self, admonition: &Admonition) {
+        let name = match admonition.admonition_type {
+            AdmonitionType::Note => "note",
+            AdmonitionType::Info => "info",
+            AdmonitionType::Tip => "tip",
+            AdmonitionType::Important => "important",
+            AdmonitionType::Success => "success",
+            AdmonitionType::Failure => "failure",
+            AdmonitionType::Warning => "warning",
+            AdmonitionType::Danger => "danger",
+            AdmonitionType::Error => "error",
+        };
+
+        if let Some(id) = &admonition.id {
+            self.keyword("NAME", id);
+        }
+        if let Some(title) = &admonition.title {
+            self.str("#+CAPTION: ");
+            self.inlines(title);
+            self.str("\n");
+        }
+
+        self.str("#+begin_").str(name).str("\n");
+        self.blocks(&admonition.content);
+        self.str("\n#+end_").str(name);
+    }
+
+    /// Encode a figure
+    ///
+    /// Only figures containing a single image can be represented.
+    fn figure(&mut self, figure: &Figure) {
+        let image = match figure.content.as_slice() {
+            [Block::ImageObject(image)] => Some(image),
+            [Block::Paragraph(Paragraph { content, .. })] => match content.as_slice() {
+                [Inline::ImageObject(image)] => Some(image),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let Some(image) = image else {
+            self.losses.add("Figure.content");
+            self.blocks(&figure.content);
+            return;
+        };
+
+        self.affiliated(figure.id.as_deref(), figure.caption.as_deref());
+        self.image(image);
+    }
+
+    /// Encode an image as a link e.g. `[[file:plot.png]]`
+    fn image(&mut self, image: &ImageObject) {
+        if image.caption.is_some() {
+            self.losses.add("ImageObject.caption");
+        }
+        if image.content_url.starts_with("data:") {
+            self.losses.add("ImageObject.contentUrl");
+            return;
+        }
+        self.str("[[")
+            .str(&link_target(&image.content_url))
+            .str("]]");
+    }
+
+    /// Encode a list
+    ///
+    /// The first paragraph of each item is on the same line as the bullet, with
+    /// other blocks (including nested lists) indented to align with it.
+    fn list(&mut self, list: &List) {
+        let ordered = !matches!(list.order, ListOrder::Unordered);
+
+        for (index, item) in list.items.iter().enumerate() {
+            if index > 0 {
+                self.str("\n");
+            }
+
+            self.enter(item.node_type(), item.node_id());
+
+            let bullet = if ordered {
+                format!("{}. ", index + 1)
+            } else {
+                "- ".to_string()
+            };
+            self.str(&bullet);
+
+            if let Some(is_checked) = item.is_checked {
+                self.str(if is_checked { "[X] " } else { "[ ] " });
+            }
+
+            let outer = self.indent.clone();
+            self.indent.push_str(&" ".repeat(bullet.len()));
+
+            let rest = match item.content.split_first() {
+                Some((first @ Block::Paragraph(..), rest)) => {
+                    self.block(first);
+                    rest
+                }
+                _ => {
+                    // Remove the trailing space of the bullet
+                    self.content.pop();
+                    self.chars -= 1;
+                    item.content.as_slice()
+                }
+            };
+
+            let mut first = rest.len() == item.content.len();
+            for block in rest {
+                self.str(if first || matches!(block, Block::List(..)) {
+                    "\n"
+                } else {
+                    "\n\n"
+                });
+                self.block(block);
+                first = false;
+            }
+
+            self.indent = outer;
+
+            self.exit();
+        }
+    }
+
+    /// Encode a table
+    ///
+    /// Header rows are separated from other rows by a horizontal line.
+    /// Only cells containing a single paragraph can be represented.
+    fn table(&mut self, table: &Table) {
+        self.affiliated(table.id.as_deref(), table.caption.as_deref());
+
+        let header_rows = table
+            .rows
+            .iter()
+            .take_while(|row| matches!(row.row_type, Some(TableRowType::HeaderRow)))
+            .count();
+
+        for (index, row) in table.rows.iter().enumerate() {
+            if index > 0 {
+                self.str("\n");
+            }
+
+            self.enter(row.node_type(), row.node_id());
+            self.str("|");
+            for cell in &row.cells {
+                self.str(" ");
+                self.enter(cell.node_type(), cell.node_id());
+                match cell.content.as_slice() {
+                    [] => {}
+                    [Block::Paragraph(paragraph)] => self.inlines(&paragraph.content),
+                    _ => {
+                        self.losses.add("TableCell.content");
+                        self.text(&to_text(&cell.content));
+                    }
+                }
+                self.exit();
+                self.str(" |");
+            }
+            self.exit();
+
+            if index + 1 == header_rows && header_rows < table.rows.len() {
+                let columns = row.cells.len().max(1);
+                self.str("\n|")
+                    .str(&vec!["---"; columns].join("+"))
+                    .str("|");
+            }
+        }
+    }
+
+    /// Encode inlines
+    fn inlines(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            self.inline(inline);
+        }
+    }
+
+    /// Encode an inline
+    fn inline(&mut self, inline: &Inline) {
+        let Some(node_id) = inline.node_id() else {
+            return;
+        };
+        self.enter(inline.node_type(), node_id);
+
+        match inline {
+            Inline::Text(text) => self.text(&text.value),
+            Inline::Emphasis(emphasis) => self.emphasis('/', &emphasis.content),
+            Inline::Strong(strong) => self.emphasis('*', &strong.content),
+            Inline::Underline(underline) => self.emphasis('_', &underline.content),
+            Inline::Strikeout(strikeout) => self.emphasis('+', &strikeout.content),
+            Inline::Subscript(subscript) => {
+                self.str("_{");
+                self.inlines(&subscript.content);
+                self.str("}");
+            }
+            Inline::Superscript(superscript) => {
+                self.str("^{");
+                self.inlines(&superscript.content);
+                self.str("}");
+            }
+            Inline::CodeInline(code) => {
+                let marker = if code.code.contains('~') { "=" } else { "~" };
+                self.str(marker).str(&code.code).str(marker);
+            }
+            Inline::CodeExpression(expression) => self.code_expression(expression),
+            Inline::MathInline(math) => {
+                if !matches!(math.math_language.as_deref(), None | Some("tex" | "latex")) {
+                    self.losses.add("MathInline.mathLanguage");
+                }
+                self.str("\\(").str(&math.code).str("\\)");
+            }
+            Inline::Link(link) => {
+                let target = link_target(&link.target);
+                let text = to_text(&link.content);
+                if text == link.target || Some(text.as_str()) == link.target.strip_prefix('#') {
+                    self.str("[[").str(&target).str("]]");
+                } else {
+                    self.str("[[").str(&target).str("][");
+                    self.inlines(&link.content);
+                    self.str("]]");
+                }
+            }
+            Inline::ImageObject(image) => self.image(image),
+            _ => {
+                self.losses.add(inline.to_string());
+                self.text(&to_text(inline));
+            }
+        }
+
+        self.exit();
+    }
+
+    /// Encode emphasis markup e.g. `*bold*`
+    ///
+    /// If immediately preceded by a word character, a zero width space is
+    /// inserted before the marker so that the markup is recognized.
+    fn emphasis(&mut self, marker: char, content: &[Inline]) {
+        if self
+            .content
+            .chars()
+            .last()
+            .map_or(false, |char| char.is_alphanumeric())
+        {
+            self.str("\u{200b}");
+        }
+
+        let marker = marker.to_string();
+        self.str(&marker);
+        self.inlines(content);
+        self.str(&marker);
+    }
+
+    /// Encode a code expression as an inline source block e.g. `src_python{1 + 1}`
+    ///
+    /// Any output is encoded using the `results` macro, as Babel does.
+    fn code_expression(&mut self, expression: &CodeExpression) {
+        let Some(lang) = &expression.programming_language else {
+            self.losses.add("CodeExpression.programmingLanguage");
+            self.str("~").str(&expression.code).str("~");
+            return;
+        };
+
+        self.str("src_")
+            .str(lang)
+            .str("{")
+            .str(&expression.code)
+            .str("}");
+
+        let Some(output) = &expression.output else {
+            return;
+        };
+        let value = match output.as_ref() {
+            Node::String(value) => value.clone(),
+            Node::Boolean(value) => value.to_string(),
+            Node::Integer(value) => value.to_string(),
+            Node::UnsignedInteger(value) => value.to_string(),
+            Node::Number(value) => value.to_string(),
+            _ => {
+                self.losses.add("CodeExpression.output");
+                return;
+            }
+        };
+        self.str(" {{{results(=").str(&value).str("=)}}}");
+    }
+
+    /// Encode text, escaping characters which would otherwise be interpreted as markup
+    ///
+    /// Org does not have an escape character so a zero width space is inserted
+    /// after characters that would otherwise start markup.
+    fn text(&mut self, text: &str) {
+        let chars = text.chars().collect_vec();
+        let last = self.content.chars().last();
+
+        let mut escaped = String::with_capacity(text.len());
+
+        // Text at the start of a line which would otherwise be interpreted
+        // as the start of an element (e.g. a list item)
+        if last.map_or(true, |last| last == '\n')
+            && (text.starts_with(['#', '|', ':', '*', '-', '+'])
+                || text.split_once(['.', ')']).map_or(false, |(number, ..)| {
+                    !number.is_empty() && number.chars().all(|char| char.is_ascii_digit())
+                }))
+        {
+            escaped.push('\u{200b}');
+        }
+
+        for (index, &char) in chars.iter().enumerate() {
+            let previous = index.checked_sub(1).map(|index| chars[index]).or(last);
+            let next = chars.get(index + 1);
+            let boundary = previous.map_or(true, |previous| {
+                previous.is_whitespace() || "-({'\"".contains(previous)
+            });
+
+            escaped.push(char);
+
+            let escape = match char {
+                '*' | '/' | '_' | '+' | '=' | '~' | '$' => {
+                    (boundary
+                        && next.map_or(false, |next| !next.is_whitespace())
+                        && chars[index + 1..].contains(&char))
+                        || (char == '_' && next == Some(&'{'))
+                }
+                '^' => next == Some(&'{'),
+                '[' => next == Some(&'[') || chars[index + 1..].starts_with(&['f', 'n', ':']),
+                '\\' => next == Some(&'('),
+                '{' => chars[index + 1..].starts_with(&['{', '{']),
+                _ => false,
+            };
+
+            if escape {
+                escaped.push('\u{200b}');
+            }
+        }
+
+        self.str(&escaped);
+    }
+}
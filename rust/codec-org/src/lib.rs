@@ -0,0 +1,297 @@
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, NodeType,
+};
+
+mod decode;
+mod encode;
+
+/// A codec for Org
+pub struct OrgCodec;
+
+#[async_trait]
+impl Codec for OrgCodec {
+    fn name(&self) -> &str {
+        "org"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Org => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Org => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        decode::decode(str, options)
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        _options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        Ok(encode::encode(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::tokio,
+        schema::{
+            shortcuts::{h1, img, lnk, p, t},
+            Article, Block, CodeChunk, CodeExpression, Figure, Inline, Section,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn decode_babel() -> Result<()> {
+        let (node, info) = OrgCodec
+            .from_str(
+                r#"#+TITLE: Analysis
+#+AUTHOR: Jane Doe
+#+EMAIL: jane@example.org
+#+DATE: <2024-03-01 Fri>
+
+* TODO Results :draft:
+:PROPERTIES:
+:CUSTOM_ID: results
+:END:
+
+The mean is src_R[:exports results]{mean(x)} {{{results(=42=)}}}, see [[#fig-plot]].
+
+#+begin_src python :exports both :results output
+print("Hello")
+#+end_src
+
+#+RESULTS:
+: Hello
+
+#+NAME: fig-plot
+#+CAPTION: A plot
+[[file:plot.png]]
+"#,
+                None,
+            )
+            .await?;
+
+        let Node::Article(Article {
+            title,
+            authors,
+            date_published,
+            content,
+            ..
+        }) = node
+        else {
+            panic!("expected article")
+        };
+
+        assert_eq!(title, Some(vec![t("Analysis")]));
+        assert_eq!(authors.map(|authors| authors.len()), Some(1));
+        assert_eq!(
+            date_published.map(|date| date.value),
+            Some("2024-03-01".to_string())
+        );
+
+        assert_eq!(
+            content,
+            vec![Block::Section(Section {
+                id: Some("results".into()),
+                ..Section::new(vec![
+                    h1([t("Results")]),
+                    p([
+                        t("The mean is "),
+                        Inline::CodeExpression(CodeExpression {
+                            code: "mean(x)".into(),
+                            programming_language: Some("R".into()),
+                            output: Some(Box::new(Node::String("42".into()))),
+                            ..Default::default()
+                        }),
+                        t(", see "),
+                        lnk([t("fig-plot")], "#fig-plot"),
+                        t(".")
+                    ]),
+                    Block::CodeChunk(CodeChunk {
+                        code: "print(\"Hello\")".into(),
+                        programming_language: Some("python".into()),
+                        outputs: Some(vec![Node::String("Hello".into())]),
+                        ..Default::default()
+                    }),
+                    Block::Figure(Figure {
+                        id: Some("fig-plot".into()),
+                        caption: Some(vec![p([t("A plot")])]),
+                        ..Figure::new(vec![p([img("plot.png")])])
+                    })
+                ])
+            })]
+        );
+
+        assert_eq!(
+            info.losses
+                .iter()
+                .map(|(label, ..)| label.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Org TODO keyword",
+                "Org `:results` header argument",
+                "Org heading tags",
+                "Org inline header arguments"
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let org = r#"#+TITLE: Title
+#+AUTHOR: Jane Doe
+#+KEYWORDS: one, two
+#+PROPERTY: header-args :session
+
+A paragraph with *strong*, /emphasis/, ~code~ and a [[https://example.org][link]].
+
+* Introduction
+:PROPERTIES:
+:CUSTOM_ID: intro
+:END:
+
+Some text with src_python{1 + 1} {{{results(=2=)}}}.
+
+- One
+- [X] Two
+  - Nested
+
+1. First
+
+   With a second paragraph
+2. Second
+
+** Methods
+
+#+NAME: plot
+#+CAPTION: A plot
+#+begin_src python :exports results
+print(1)
+#+end_src
+
+#+RESULTS: plot
+: 1
+
+#+begin_src r :eval no
+x <- 1
+#+end_src
+
+#+begin_src r :exports code
+x <- 2
+#+end_src
+
+#+begin_src r :exports none
+x <- 3
+#+end_src
+
+#+begin_note
+Some advice.
+#+end_note
+
+| A | B |
+|---+---|
+| 1 | 2 |
+
+#+INCLUDE: "part.org"
+
+-----
+
+#+begin_quote
+A quote
+#+end_quote"#;
+
+        let (node, info) = OrgCodec.from_str(org, None).await?;
+        assert!(info.losses.is_empty());
+
+        let Node::Article(article) = &node else {
+            panic!("expected article")
+        };
+        let Block::Section(section) = &article.content[1] else {
+            panic!("expected section")
+        };
+        assert_eq!(section.id.as_deref(), Some("intro"));
+
+        let Some(Block::Section(methods)) = section.content.last() else {
+            panic!("expected nested section")
+        };
+        let chunks = methods
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                Block::CodeChunk(chunk) => Some((chunk.is_code_hidden, chunk.is_invisible)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chunks,
+            vec![
+                (Some(true), None),
+                (None, Some(true)),
+                (Some(true), Some(true))
+            ]
+        );
+
+        let (encoded, info) = OrgCodec.to_string(&node, None).await?;
+        assert_eq!(encoded, org);
+        assert!(info.losses.is_empty());
+
+        // The mapping includes an entry for the heading spanning its source
+        let heading = section.content[0].node_id();
+        let range = heading
+            .and_then(|node_id| info.mapping.range_of_node(&node_id))
+            .map(|range| {
+                encoded
+                    .chars()
+                    .take(range.end)
+                    .skip(range.start)
+                    .collect::<String>()
+            });
+        assert_eq!(
+            range.as_deref(),
+            Some("* Introduction\n:PROPERTIES:\n:CUSTOM_ID: intro\n:END:")
+        );
+
+        Ok(())
+    }
+}
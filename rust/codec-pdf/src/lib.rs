@@ -74,16 +74,25 @@ impl Codec for PdfCodec {
         path: &Path,
         options: Option<EncodeOptions>,
     ) -> Result<EncodeInfo> {
+        let mut passthrough_args = options
+            .as_ref()
+            .map(|options| options.passthrough_args.clone())
+            .unwrap_or_default();
+
+        // Only use Typst, which does not require any external tools, when
+        // it is explicitly requested, otherwise use Pandoc and the requested
+        // PDF engine (if any)
+        match options
+            .as_ref()
+            .and_then(|options| options.pdf_engine.as_deref())
+        {
+            Some("typst") => return codec_typst::to_pdf(node, path, options).await,
+            Some(engine) => passthrough_args.push(format!("--pdf-engine={engine}")),
+            None => {}
+        }
+
         let (pandoc, info) = root_to_pandoc(node, Format::Pdf)?;
-        pandoc_to_format(
-            &pandoc,
-            Some(path),
-            PANDOC_FORMAT,
-            options
-                .map(|options| options.passthrough_args)
-                .unwrap_or_default(),
-        )
-        .await?;
+        pandoc_to_format(&pandoc, Some(path), PANDOC_FORMAT, passthrough_args).await?;
         Ok(info)
     }
 }
@@ -0,0 +1,220 @@
+use codec::{
+    common::{
+        eyre::{bail, Result},
+        serde_json::Value,
+    },
+    Losses,
+};
+use codec_csl::{DateVariable, Item, Name};
+
+/// A RIS record: a list of tags and their values in the order they appear
+pub type Record = Vec<(String, String)>;
+
+/// Parse RIS into a list of records
+///
+/// Each line of a record has the form `TG  - value`. Records start with a `TY`
+/// tag and end with an `ER` tag. Lines that do not start with a tag are treated
+/// as continuations of the value on the previous line.
+pub fn parse(ris: &str) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    let mut record: Option<Record> = None;
+
+    for line in ris.lines() {
+        let line = line.trim_end().trim_start_matches('\u{feff}');
+        if line.is_empty() {
+            continue;
+        }
+
+        match tag(line) {
+            Some(("TY", value)) => {
+                if record.is_some() {
+                    bail!("Record starting with `{line}` started before previous record ended")
+                }
+                record = Some(vec![("TY".to_string(), value.to_string())]);
+            }
+            Some(("ER", ..)) => {
+                if let Some(record) = record.take() {
+                    records.push(record);
+                }
+            }
+            Some((tag, value)) => {
+                if let Some(record) = record.as_mut() {
+                    record.push((tag.to_string(), value.to_string()));
+                }
+            }
+            None => {
+                if let Some((.., value)) = record.as_mut().and_then(|record| record.last_mut()) {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            }
+        }
+    }
+
+    // Be lenient with a missing `ER` on the last record
+    if let Some(record) = record {
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Split a line into its tag and value, if it starts with a tag
+fn tag(line: &str) -> Option<(&str, &str)> {
+    let (tag, value) = line.split_once('-')?;
+    let tag = tag.trim_end();
+    if tag.len() != 2 || !tag.chars().all(|char| char.is_ascii_alphanumeric()) {
+        return None;
+    }
+    if !line[2..].starts_with("  -") && !line[2..].starts_with(" -") {
+        return None;
+    }
+    Some((tag, value.trim()))
+}
+
+/// Convert a RIS record into a CSL item
+///
+/// Tags that have no equivalent CSL variable are recorded as losses.
+pub fn record_to_item(record: Record, losses: &mut Losses) -> Item {
+    let mut item = Item::default();
+
+    let mut keywords = Vec::new();
+    let mut start_page = None;
+    let mut end_page = None;
+    let mut serial_number = None;
+
+    for (tag, value) in record {
+        if value.is_empty() {
+            continue;
+        }
+
+        match tag.as_str() {
+            "TY" => item.r#type = csl_type(&value).to_string(),
+            "ID" => item.id = Some(value),
+            "TI" | "T1" => item.title = Some(value),
+            "JO" | "JF" | "T2" | "BT" => item.container_title = Some(value),
+            "JA" | "J1" | "J2" => {
+                item.container_title.get_or_insert(value);
+            }
+            "AU" | "A1" => item.author.push(name(&value)),
+            "ED" | "A2" => item.editor.push(name(&value)),
+            "PY" | "Y1" | "DA" => {
+                // Prefer the more specific date if there are several
+                let date = date(&value);
+                let len = |date: &DateVariable| date.to_iso().map_or(0, |date| date.len());
+                if item
+                    .issued
+                    .as_ref()
+                    .map_or(true, |issued| len(&date) > len(issued))
+                {
+                    item.issued = Some(date);
+                }
+            }
+            "VL" => item.volume = Some(value),
+            "IS" => item.issue = Some(value),
+            "SP" => start_page = Some(value),
+            "EP" => end_page = Some(value),
+            "ET" => item.edition = Some(value),
+            "PB" => item.publisher = Some(value),
+            "CY" | "PP" => item.publisher_place = Some(value),
+            "AB" | "N2" => item.r#abstract = Some(value),
+            "KW" => keywords.push(value),
+            "DO" => item.doi = Some(value),
+            "UR" | "L2" => {
+                item.url.get_or_insert(value);
+            }
+            "SN" => serial_number = Some(value),
+            "M1" => item.number = Some(value),
+            "N1" => {
+                item.extra.insert("note".into(), Value::String(value));
+            }
+            "T3" => {
+                item.extra
+                    .insert("collection-title".into(), Value::String(value));
+            }
+            "LA" => {
+                item.extra.insert("language".into(), Value::String(value));
+            }
+            _ => losses.add(format!("RIS.{tag}")),
+        }
+    }
+
+    if !keywords.is_empty() {
+        item.keyword = Some(keywords.join(", "));
+    }
+
+    item.page = match (start_page, end_page) {
+        (Some(start), Some(end)) => Some(format!("{start}-{end}")),
+        (Some(start), None) => Some(start),
+        (None, Some(end)) => Some(end),
+        (None, None) => None,
+    };
+
+    // `SN` is an ISBN for books and chapters, and an ISSN otherwise
+    if let Some(serial_number) = serial_number {
+        match item.r#type.as_str() {
+            "book" | "chapter" => item.isbn = Some(serial_number),
+            _ => item.issn = Some(serial_number),
+        }
+    }
+
+    item
+}
+
+/// Parse a RIS name
+///
+/// RIS names are in "Family, Given, Suffix" form so the suffix is moved to
+/// where [`Name::parse`] expects it. Names without a comma are assumed to be
+/// the names of organizations.
+fn name(name: &str) -> Name {
+    let parts = name.split(',').map(str::trim).collect::<Vec<_>>();
+    match parts.as_slice() {
+        [literal] => Name {
+            literal: Some(literal.to_string()),
+            ..Default::default()
+        },
+        [family, given, suffix, ..] => Name::parse(&format!("{family}, {suffix}, {given}")),
+        _ => Name::parse(name),
+    }
+}
+
+/// Parse a RIS date
+///
+/// Dates are usually in `YYYY/MM/DD/other` form but ISO 8601 dates are also accepted.
+fn date(date: &str) -> DateVariable {
+    let parts = date
+        .split(['/', '-'])
+        .take(3)
+        .map_while(|part| part.trim().parse::<u32>().ok())
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>();
+
+    if parts.is_empty() {
+        DateVariable {
+            literal: Some(date.to_string()),
+            ..Default::default()
+        }
+    } else {
+        DateVariable::from_iso(&parts.join("-"))
+    }
+}
+
+/// Get the CSL type corresponding to a RIS reference type
+fn csl_type(ris_type: &str) -> &'static str {
+    match ris_type.trim() {
+        "JOUR" | "EJOUR" | "JFULL" | "ABST" | "INPR" => "article-journal",
+        "MGZN" => "article-magazine",
+        "NEWS" => "article-newspaper",
+        "BOOK" | "EBOOK" | "EDBOOK" | "SER" => "book",
+        "CHAP" | "ECHAP" => "chapter",
+        "CONF" | "CPAPER" => "paper-conference",
+        "THES" => "thesis",
+        "RPRT" => "report",
+        "ELEC" | "WEB" | "BLOG" => "webpage",
+        "UNPB" | "MANSCPT" => "manuscript",
+        "PAT" => "patent",
+        "DATA" => "dataset",
+        "COMP" => "software",
+        _ => "document",
+    }
+}
@@ -0,0 +1,179 @@
+use codec::{common::serde_json::Value, Losses};
+use codec_csl::{Item, Name};
+
+use crate::Record;
+
+/// Convert a CSL item into a RIS record
+pub fn item_to_record(item: &Item, losses: &mut Losses) -> Record {
+    let mut record = Record::new();
+    let mut tag = |tag: &str, value: &str| {
+        if !value.is_empty() {
+            record.push((tag.to_string(), value.to_string()));
+        }
+    };
+
+    let ris_type = ris_type(&item.r#type);
+    tag("TY", ris_type);
+
+    if let Some(id) = &item.id {
+        tag("ID", id);
+    }
+    if let Some(title) = &item.title {
+        tag("TI", title);
+    }
+    for author in &item.author {
+        tag("AU", &name(author));
+    }
+    for editor in &item.editor {
+        tag("ED", &name(editor));
+    }
+
+    if let Some(container) = &item.container_title {
+        match ris_type {
+            "JOUR" | "MGZN" | "NEWS" => tag("JO", container),
+            _ => tag("T2", container),
+        }
+    }
+
+    if let Some(date) = item.issued.as_ref().and_then(|date| date.to_iso()) {
+        tag("PY", date.split('-').next().unwrap_or_default());
+        if date.contains('-') {
+            tag("DA", &date.replace('-', "/"));
+        }
+    }
+
+    for (name, value) in [
+        ("VL", &item.volume),
+        ("IS", &item.issue),
+        ("M1", &item.number),
+        ("ET", &item.edition),
+    ] {
+        if let Some(value) = value {
+            tag(name, value);
+        }
+    }
+
+    if let Some(page) = &item.page {
+        match page.split_once('-') {
+            Some((start, end)) => {
+                tag("SP", start.trim());
+                tag("EP", end.trim_start_matches('-').trim());
+            }
+            None => tag("SP", page),
+        }
+    }
+
+    for (name, value) in [
+        ("PB", &item.publisher),
+        ("CY", &item.publisher_place),
+        ("AB", &item.r#abstract),
+        ("DO", &item.doi),
+        ("UR", &item.url),
+    ] {
+        if let Some(value) = value {
+            tag(name, value);
+        }
+    }
+
+    if let Some(keywords) = &item.keyword {
+        for keyword in keywords.split([',', ';']) {
+            tag("KW", keyword.trim());
+        }
+    }
+
+    // RIS has a single `SN` tag for both ISBNs and ISSNs
+    match (&item.isbn, &item.issn) {
+        (Some(isbn), Some(..)) => {
+            tag("SN", isbn);
+            losses.add("CSL.ISSN");
+        }
+        (Some(serial_number), None) | (None, Some(serial_number)) => tag("SN", serial_number),
+        (None, None) => {}
+    }
+
+    if item.pmid.is_some() {
+        losses.add("CSL.PMID");
+    }
+
+    for (key, value) in &item.extra {
+        let name = match key.as_str() {
+            "note" => "N1",
+            "collection-title" => "T3",
+            "language" => "LA",
+            _ => {
+                losses.add(format!("CSL.{key}"));
+                continue;
+            }
+        };
+        let value = match value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+        tag(name, &value);
+    }
+
+    record
+}
+
+/// Serialize a list of records
+pub fn records_to_string(records: &[Record]) -> String {
+    let mut ris = String::new();
+    for record in records {
+        for (tag, value) in record {
+            // Values can not span lines so newlines are replaced with spaces
+            let value = value.lines().collect::<Vec<_>>().join(" ");
+            ris.push_str(&format!("{tag}  - {value}\n"));
+        }
+        ris.push_str("ER  - \n\n");
+    }
+    ris
+}
+
+/// Format a CSL name for RIS
+///
+/// Names are in "Family, Given, Suffix" form.
+fn name(name: &Name) -> String {
+    if let Some(literal) = &name.literal {
+        return literal.clone();
+    }
+
+    let family = [
+        name.non_dropping_particle.as_deref(),
+        name.family.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    [
+        Some(family.as_str()),
+        name.given.as_deref(),
+        name.suffix.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.is_empty())
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Get the RIS reference type for a CSL type
+fn ris_type(csl_type: &str) -> &'static str {
+    match csl_type {
+        "article" | "article-journal" | "review" | "review-book" => "JOUR",
+        "article-magazine" => "MGZN",
+        "article-newspaper" => "NEWS",
+        "book" => "BOOK",
+        "chapter" | "entry" | "entry-dictionary" | "entry-encyclopedia" => "CHAP",
+        "paper-conference" => "CPAPER",
+        "thesis" => "THES",
+        "report" => "RPRT",
+        "webpage" | "post" | "post-weblog" => "ELEC",
+        "manuscript" => "UNPB",
+        "patent" => "PAT",
+        "dataset" => "DATA",
+        "software" => "COMP",
+        _ => "GEN",
+    }
+}
@@ -0,0 +1,184 @@
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, Losses, NodeType,
+};
+use codec_csl::{items_to_node, node_to_items};
+
+mod decode;
+mod encode;
+
+pub use decode::{parse, record_to_item, Record};
+pub use encode::{item_to_record, records_to_string};
+
+/// A codec for RIS (Research Information Systems) bibliographies
+///
+/// Records are converted to and from CSL items (see [`codec_csl`]) which are in turn
+/// mapped to creative works (e.g. `Article`s, `Book`s, `Chapter`s and `Thesis`s)
+/// in the `references` of an article.
+pub struct RisCodec;
+
+#[async_trait]
+impl Codec for RisCodec {
+    fn name(&self) -> &str {
+        "ris"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Ris => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Ris => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        _options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        let mut losses = Losses::none();
+
+        let items = parse(str)?
+            .into_iter()
+            .map(|record| record_to_item(record, &mut losses))
+            .collect();
+
+        Ok((
+            items_to_node(items),
+            DecodeInfo {
+                losses,
+                ..DecodeInfo::none()
+            },
+        ))
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        _options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        let (items, mut losses) = node_to_items(node)?;
+
+        let records = items
+            .iter()
+            .map(|item| item_to_record(item, &mut losses))
+            .collect::<Vec<_>>();
+
+        Ok((
+            records_to_string(&records),
+            EncodeInfo {
+                losses,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::common::tokio;
+
+    use super::*;
+
+    #[test]
+    fn parse_records() -> Result<()> {
+        let records = parse(
+            "TY  - JOUR
+AU  - Smith, Jane A.
+AU  - Jones, Bob, Jr.
+AU  - The Things Consortium
+TI  - A study of
+  things
+JO  - Journal of Things
+PY  - 2020/03/15/
+VL  - 12
+IS  - 4
+SP  - 100
+EP  - 110
+KW  - things
+KW  - stuff
+SN  - 1234-5678
+DO  - 10.1234/things.5678
+ER  - 
+",
+        )?;
+        assert_eq!(records.len(), 1);
+
+        let mut losses = Losses::none();
+        let item = record_to_item(records[0].clone(), &mut losses);
+        assert!(losses.is_empty());
+
+        assert_eq!(item.r#type, "article-journal");
+        assert_eq!(item.title.as_deref(), Some("A study of things"));
+        assert_eq!(item.container_title.as_deref(), Some("Journal of Things"));
+        assert_eq!(
+            item.issued
+                .as_ref()
+                .and_then(|date| date.to_iso())
+                .as_deref(),
+            Some("2020-03-15")
+        );
+        assert_eq!(item.page.as_deref(), Some("100-110"));
+        assert_eq!(item.keyword.as_deref(), Some("things, stuff"));
+        assert_eq!(item.issn.as_deref(), Some("1234-5678"));
+
+        assert_eq!(item.author[1].family.as_deref(), Some("Jones"));
+        assert_eq!(item.author[1].suffix.as_deref(), Some("Jr."));
+        assert_eq!(
+            item.author[2].literal.as_deref(),
+            Some("The Things Consortium")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let ris = "TY  - BOOK
+ID  - doe2019
+TI  - A book of things
+AU  - Doe, John
+PY  - 2019
+PB  - Things Press
+CY  - Auckland
+SN  - 978-3-16-148410-0
+ER  - 
+
+";
+
+        let (node, ..) = RisCodec.from_str(ris, None).await?;
+        let (encoded, info) = RisCodec.to_string(&node, None).await?;
+
+        assert_eq!(encoded, ris);
+        assert!(info.losses.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,745 @@
+use codec::{
+    common::{eyre::Result, indexmap::IndexMap, itertools::Itertools},
+    schema::{
+        shortcuts::{cb, em, h, img, lnk, mb, mi, p, qb, stg, sub, sup, t, tb},
+        Admonition, AdmonitionType, Article, Block, CodeInline, Figure, IncludeBlock, Inline, List,
+        ListItem, ListOrder, Node, Table, TableCell, TableCellType, TableRow, TableRowType,
+    },
+    DecodeInfo, DecodeOptions, Losses,
+};
+
+/// Decode reStructuredText to a Stencila [`Node`]
+///
+/// A line-based parser for the subset of reStructuredText most used in
+/// technical documentation: section titles, paragraphs, literal blocks,
+/// bullet and enumerated lists, block quotes and transitions. Common Sphinx
+/// directives (e.g. `code-block`, `math`, `figure`, `note`, `toctree`) are
+/// decoded into the corresponding node types. Other directives, and
+/// roles, are recorded as losses.
+pub(super) fn decode(rst: &str, _options: Option<DecodeOptions>) -> Result<(Node, DecodeInfo)> {
+    let lines = rst
+        .lines()
+        .map(|line| line.replace('\t', "        ").trim_end().to_string())
+        .collect_vec();
+
+    let mut context = Context {
+        targets: targets(&lines),
+        ..Default::default()
+    };
+    let content = context.blocks(&lines);
+
+    let node = Node::Article(Article::new(content));
+
+    let info = DecodeInfo {
+        losses: context.losses,
+        ..Default::default()
+    };
+
+    Ok((node, info))
+}
+
+#[derive(Default)]
+struct Context {
+    /// The section title adornment styles, in the order that they were
+    /// first encountered, used to determine the level of headings
+    heading_styles: Vec<(char, bool)>,
+
+    /// Hyperlink targets (e.g. `.. _Python: https://python.org`) used
+    /// to resolve named references (e.g. `` `Python`_ ``)
+    targets: IndexMap<String, String>,
+
+    /// Losses when decoding
+    losses: Losses,
+}
+
+/// Collect the external hyperlink targets in a document
+fn targets(lines: &[String]) -> IndexMap<String, String> {
+    lines
+        .iter()
+        .filter_map(|line| {
+            let rest = line.trim_start().strip_prefix(".. _")?;
+            let (name, url) = rest.split_once(": ")?;
+            let url = url.trim();
+            (!url.is_empty()).then(|| (name.trim_matches('`').to_lowercase(), url.to_string()))
+        })
+        .collect()
+}
+
+/// Is a line a section title adornment or transition (e.g. `=====`)?
+fn is_adornment(line: &str) -> bool {
+    let mut chars = line.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    first.is_ascii_punctuation() && line.len() >= 2 && chars.all(|char| char == first)
+}
+
+/// Get the number of leading spaces on a line
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Remove the common indentation from lines
+fn dedent(lines: &[String]) -> Vec<String> {
+    let min = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| indent(line))
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| line.get(min..).unwrap_or_default().to_string())
+        .collect()
+}
+
+/// Remove leading and trailing blank lines
+fn trim_blank(lines: &[String]) -> &[String] {
+    let start = lines
+        .iter()
+        .position(|line| !line.trim().is_empty())
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(start, |end| end + 1);
+    &lines[start..end]
+}
+
+/// Take the lines of an indented block starting at `start`
+///
+/// The block ends at the first non-blank line with less indentation than `min`.
+/// Returns the lines (with trailing blank lines removed) and the index after the block.
+fn indented(lines: &[String], start: usize, min: usize) -> (Vec<String>, usize) {
+    let mut end = start;
+    while end < lines.len() && (lines[end].trim().is_empty() || indent(&lines[end]) >= min) {
+        end += 1;
+    }
+
+    let mut block = lines[start..end].to_vec();
+    while block.last().is_some_and(|line| line.trim().is_empty()) {
+        block.pop();
+    }
+
+    (block, end)
+}
+
+/// Parse a list item marker, returning the list order and width of the marker (including spaces)
+fn list_marker(line: &str) -> Option<(ListOrder, usize)> {
+    let width = |marker: usize| {
+        let rest = &line[marker..];
+        if rest.is_empty() {
+            Some(marker)
+        } else if rest.starts_with(' ') {
+            Some(marker + indent(rest))
+        } else {
+            None
+        }
+    };
+
+    if line.starts_with(['-', '*', '+', '•']) {
+        let marker = line.chars().next().map_or(1, char::len_utf8);
+        return width(marker).map(|width| (ListOrder::Unordered, width));
+    }
+
+    let (number, parenthesized) = match line.strip_prefix('(') {
+        Some(rest) => (rest, true),
+        None => (line, false),
+    };
+    let digits = number
+        .chars()
+        .take_while(|char| char.is_ascii_digit() || *char == '#')
+        .count();
+    if digits == 0 || (digits > 1 && number.starts_with('#')) {
+        return None;
+    }
+    let after = &number[digits..];
+    let closed = if parenthesized {
+        after.starts_with(')')
+    } else {
+        after.starts_with(['.', ')'])
+    };
+    if !closed {
+        return None;
+    }
+    let marker = line.len() - after.len() + 1;
+    width(marker).map(|width| (ListOrder::Ascending, width))
+}
+
+/// A directive e.g. `.. code-block:: python`
+struct Directive {
+    name: String,
+    args: String,
+    options: IndexMap<String, String>,
+    content: Vec<String>,
+}
+
+impl Directive {
+    /// Parse a directive from its first line and its (dedented) body
+    fn parse(first: &str, body: Vec<String>) -> Option<Self> {
+        let (name, args) = first.split_once("::")?;
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let mut args = args.trim().to_string();
+        let mut options = IndexMap::new();
+        let mut lines = body.into_iter().peekable();
+
+        // Arguments may continue on lines before any options or blank line
+        if !args.is_empty() {
+            while let Some(line) = lines.next_if(|line| !line.is_empty() && !line.starts_with(':'))
+            {
+                args.push(' ');
+                args.push_str(line.trim());
+            }
+        }
+
+        while let Some(line) = lines.next_if(|line| line.starts_with(':')) {
+            if let Some((name, value)) = line[1..].split_once(':') {
+                options.insert(name.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        let content = lines.collect_vec();
+
+        Some(Self {
+            name: name.to_lowercase(),
+            args,
+            options,
+            content: trim_blank(&content).to_vec(),
+        })
+    }
+}
+
+impl Context {
+    /// Decode lines into blocks
+    fn blocks(&mut self, lines: &[String]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+
+        let mut index = 0;
+        while index < lines.len() {
+            let line = &lines[index];
+            if line.trim().is_empty() {
+                index += 1;
+                continue;
+            }
+
+            let next = lines.get(index + 1).map(String::as_str).unwrap_or_default();
+            let blank_before = index == 0 || lines[index - 1].trim().is_empty();
+
+            // Explicit markup: directives, comments, targets and substitution definitions
+            if let Some(rest) = line
+                .strip_prefix("..")
+                .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+            {
+                let (body, end) = indented(lines, index + 1, 1);
+                index = end;
+                let body = dedent(&body);
+
+                let rest = rest.trim();
+                if rest.starts_with('_') || rest.is_empty() {
+                    // Hyperlink target (already collected) or empty comment
+                    continue;
+                }
+                if rest.starts_with('|') {
+                    self.losses.add("RST substitution definition");
+                    continue;
+                }
+                if rest.starts_with('[') {
+                    self.losses.add("RST footnote or citation");
+                    continue;
+                }
+                // Explicit markup that is not a directive is a comment
+                if let Some(directive) = Directive::parse(rest, body) {
+                    blocks.append(&mut self.directive(directive));
+                }
+                continue;
+            }
+
+            // Section title with overline
+            if is_adornment(line)
+                && !next.trim().is_empty()
+                && lines.get(index + 2).map_or(false, |under| under == line)
+            {
+                let level = self.heading_level(line.chars().next().unwrap_or('='), true);
+                blocks.push(h(level, self.inlines(next.trim())));
+                index += 3;
+                continue;
+            }
+
+            // Section title with underline only
+            if indent(line) == 0
+                && !is_adornment(line)
+                && is_adornment(next)
+                && next.chars().count() >= line.chars().count().min(4)
+            {
+                let level = self.heading_level(next.chars().next().unwrap_or('='), false);
+                blocks.push(h(level, self.inlines(line)));
+                index += 2;
+                continue;
+            }
+
+            // Transition
+            if is_adornment(line) && line.len() >= 4 && blank_before && next.trim().is_empty() {
+                blocks.push(tb());
+                index += 1;
+                continue;
+            }
+
+            // Lists
+            if let Some((order, ..)) = list_marker(line) {
+                let (list, end) = self.list(lines, index, order);
+                blocks.push(list);
+                index = end;
+                continue;
+            }
+
+            // Block quote
+            if indent(line) > 0 {
+                let (body, end) = indented(lines, index, 1);
+                blocks.push(qb(self.blocks(&dedent(&body))));
+                index = end;
+                continue;
+            }
+
+            // Paragraph, possibly introducing a literal block
+            let mut end = index;
+            while end < lines.len() && !lines[end].trim().is_empty() && indent(&lines[end]) == 0 {
+                end += 1;
+            }
+            let mut text = lines[index..end].join("\n");
+            index = end;
+
+            if let Some(stripped) = text.strip_suffix("::") {
+                text = if stripped.ends_with(char::is_whitespace) || stripped.is_empty() {
+                    stripped.trim_end().to_string()
+                } else {
+                    [stripped, ":"].concat()
+                };
+
+                if !text.is_empty() {
+                    blocks.push(p(self.inlines(&text)));
+                }
+
+                let start = lines[index..]
+                    .iter()
+                    .position(|line| !line.trim().is_empty())
+                    .map_or(lines.len(), |offset| index + offset);
+                if start < lines.len() && indent(&lines[start]) > 0 {
+                    let (body, end) = indented(lines, start, 1);
+                    blocks.push(cb(dedent(&body).join("\n"), None::<String>));
+                    index = end;
+                }
+                continue;
+            }
+
+            blocks.push(p(self.inlines(&text)));
+        }
+
+        blocks
+    }
+
+    /// Get the level of a heading from its adornment style
+    fn heading_level(&mut self, char: char, overline: bool) -> i64 {
+        let style = (char, overline);
+        let position = match self.heading_styles.iter().position(|item| *item == style) {
+            Some(position) => position,
+            None => {
+                self.heading_styles.push(style);
+                self.heading_styles.len() - 1
+            }
+        };
+        position as i64 + 1
+    }
+
+    /// Decode a list starting at `start`
+    fn list(&mut self, lines: &[String], start: usize, order: ListOrder) -> (Block, usize) {
+        let mut items = Vec::new();
+
+        let mut index = start;
+        while index < lines.len() {
+            let line = &lines[index];
+            if line.trim().is_empty() {
+                index += 1;
+                continue;
+            }
+
+            let Some((item_order, width)) = list_marker(line).filter(|_| indent(line) == 0) else {
+                break;
+            };
+            if item_order != order {
+                break;
+            }
+
+            let first = line.get(width..).unwrap_or_default().to_string();
+            let (rest, end) = indented(lines, index + 1, width.max(1));
+            let body = [vec![first], dedent(&rest)].concat();
+            items.push(ListItem::new(self.blocks(&body)));
+            index = end;
+        }
+
+        (Block::List(List::new(items, order)), index)
+    }
+
+    /// Decode a directive into blocks
+    fn directive(&mut self, directive: Directive) -> Vec<Block> {
+        let Directive {
+            name,
+            args,
+            options,
+            content,
+        } = directive;
+
+        let block = match name.as_str() {
+            "code-block" | "code" | "sourcecode" => {
+                let lang = (!args.is_empty()).then_some(args);
+                cb(content.join("\n"), lang)
+            }
+            "math" => {
+                let code = if content.is_empty() {
+                    args
+                } else {
+                    content.join("\n")
+                };
+                mb(code, Some("tex"))
+            }
+            "image" => p([img(args)]),
+            "figure" => {
+                let blocks = self.blocks(&content);
+                let mut blocks = blocks.into_iter();
+                let caption = blocks.next().map(|caption| {
+                    let mut caption = vec![caption];
+                    caption.extend(blocks);
+                    caption
+                });
+                Block::Figure(Figure {
+                    id: options.get("name").cloned(),
+                    caption,
+                    ..Figure::new(vec![p([img(args)])])
+                })
+            }
+            "note" | "seealso" | "warning" | "tip" | "hint" | "important" | "danger" | "error"
+            | "caution" | "attention" | "admonition" => {
+                let admonition_type = match name.as_str() {
+                    "admonition" => options
+                        .get("class")
+                        .and_then(|class| admonition_type(class))
+                        .unwrap_or(AdmonitionType::Note),
+                    name => admonition_type(name).unwrap_or_default(),
+                };
+
+                // For generic admonitions the argument is the title, for others
+                // it is the first line of content
+                let (title, mut content) = if name == "admonition" {
+                    ((!args.is_empty()).then(|| self.inlines(&args)), content)
+                } else if args.is_empty() {
+                    (None, content)
+                } else {
+                    (None, [vec![args, String::new()], content].concat())
+                };
+                content = trim_blank(&content).to_vec();
+
+                Block::Admonition(Admonition {
+                    admonition_type,
+                    title,
+                    content: self.blocks(&content),
+                    ..Default::default()
+                })
+            }
+            "include" => Block::IncludeBlock(IncludeBlock::new(args)),
+            "toctree" => {
+                return content
+                    .iter()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|entry| {
+                        // Entries may have an explicit title e.g. `Intro <intro>`
+                        let entry = entry.trim();
+                        let path = match entry.rsplit_once('<') {
+                            Some((.., path)) if entry.ends_with('>') => path.trim_end_matches('>'),
+                            _ => entry,
+                        };
+                        let source = if path.contains('.') && !path.ends_with('.') {
+                            path.to_string()
+                        } else {
+                            [path, ".rst"].concat()
+                        };
+                        Block::IncludeBlock(IncludeBlock::new(source))
+                    })
+                    .collect();
+            }
+            "list-table" => Block::Table(self.list_table(&args, &options, &content)),
+            "rubric" => p([stg(self.inlines(&args))]),
+            "epigraph" | "pull-quote" | "highlights" => qb(self.blocks(&content)),
+            "container" | "rst-class" | "only" => return self.blocks(&content),
+            _ => {
+                self.losses.add(format!("RST `{name}` directive"));
+                return Vec::new();
+            }
+        };
+
+        vec![block]
+    }
+
+    /// Decode a `list-table` directive
+    fn list_table(
+        &mut self,
+        title: &str,
+        options: &IndexMap<String, String>,
+        content: &[String],
+    ) -> Table {
+        let header_rows = options
+            .get("header-rows")
+            .and_then(|rows| rows.parse::<usize>().ok())
+            .unwrap_or(0);
+
+        let mut rows = Vec::new();
+        if let Block::List(List { items, .. }) = self.list(content, 0, ListOrder::Unordered).0 {
+            for (index, item) in items.into_iter().enumerate() {
+                let header = index < header_rows;
+                let cells = match item.content.as_slice() {
+                    [Block::List(List { items, .. })] => items
+                        .iter()
+                        .map(|cell| TableCell {
+                            cell_type: header.then_some(TableCellType::HeaderCell),
+                            ..TableCell::new(cell.content.clone())
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                rows.push(TableRow {
+                    row_type: header.then_some(TableRowType::HeaderRow),
+                    ..TableRow::new(cells)
+                });
+            }
+        }
+
+        Table {
+            caption: (!title.is_empty()).then(|| vec![p(self.inlines(title))]),
+            ..Table::new(rows)
+        }
+    }
+
+    /// Decode text into inlines
+    fn inlines(&mut self, text: &str) -> Vec<Inline> {
+        let chars = text.chars().collect_vec();
+
+        let mut inlines = Vec::new();
+        let mut current = String::new();
+
+        let flush = |current: &mut String, inlines: &mut Vec<Inline>| {
+            if !current.is_empty() {
+                inlines.push(t(std::mem::take(current)));
+            }
+        };
+
+        let mut index = 0;
+        while index < chars.len() {
+            let char = chars[index];
+
+            // Inline markup can only start after whitespace, some punctuation, or at the start
+            let can_start = index == 0
+                || chars[index - 1].is_whitespace()
+                || "-:/'\"<([{".contains(chars[index - 1]);
+
+            if char == '\\' {
+                if let Some(next) = chars.get(index + 1) {
+                    if !next.is_whitespace() {
+                        current.push(*next);
+                    }
+                }
+                index += 2;
+                continue;
+            }
+
+            if can_start {
+                if let Some((inline, end)) = self.inline_markup(&chars, index) {
+                    flush(&mut current, &mut inlines);
+                    if let Some(inline) = inline {
+                        inlines.push(inline);
+                    }
+                    index = end;
+                    continue;
+                }
+            }
+
+            current.push(if char == '\n' { ' ' } else { char });
+            index += 1;
+        }
+        flush(&mut current, &mut inlines);
+
+        inlines
+    }
+
+    /// Attempt to decode inline markup starting at `start`
+    ///
+    /// Returns the inline (if any, unsupported roles are dropped) and the index after the markup.
+    fn inline_markup(&mut self, chars: &[char], start: usize) -> Option<(Option<Inline>, usize)> {
+        let rest = &chars[start..];
+        let starts_with = |prefix: &str| {
+            let prefix = prefix.chars().collect_vec();
+            rest.starts_with(&prefix)
+        };
+
+        // Find the end of inline markup with the given closing delimiter
+        let close = |open: usize, delimiter: &str| -> Option<usize> {
+            let delimiter = delimiter.chars().collect_vec();
+            let content_start = start + open;
+            if chars
+                .get(content_start)
+                .map_or(true, |char| char.is_whitespace())
+            {
+                return None;
+            }
+            (content_start + 1..=chars.len().saturating_sub(delimiter.len())).find(|&index| {
+                chars[index..].starts_with(&delimiter)
+                    && !chars[index - 1].is_whitespace()
+                    && chars[index - 1] != '\\'
+            })
+        };
+        let text = |from: usize, to: usize| {
+            chars[from..to]
+                .iter()
+                .collect::<String>()
+                .replace('\n', " ")
+        };
+
+        if starts_with("``") {
+            let end = close(2, "``")?;
+            let code = text(start + 2, end);
+            return Some((
+                Some(Inline::CodeInline(CodeInline::new(code.into()))),
+                end + 2,
+            ));
+        }
+
+        if starts_with("**") {
+            let end = close(2, "**")?;
+            let content = self.inlines(&text(start + 2, end));
+            return Some((Some(stg(content)), end + 2));
+        }
+
+        if starts_with("*") {
+            let end = close(1, "*")?;
+            let content = self.inlines(&text(start + 1, end));
+            return Some((Some(em(content)), end + 1));
+        }
+
+        if starts_with(":") {
+            // A role e.g. :math:`x^2`
+            let name_end = (start + 1..chars.len()).find(|&index| {
+                !(chars[index].is_alphanumeric() || matches!(chars[index], '-' | '_' | '.' | '+'))
+            })?;
+            if name_end == start + 1 || chars.get(name_end) != Some(&':') {
+                return None;
+            }
+            if chars.get(name_end + 1) != Some(&'`') {
+                return None;
+            }
+            let role = text(start + 1, name_end);
+            let open = name_end + 2 - start;
+            let end = close(open, "`")?;
+            let content = text(start + open, end);
+            return Some((self.role(&role, &content), end + 1));
+        }
+
+        if starts_with("`") {
+            let end = close(1, "`")?;
+            let content = text(start + 1, end);
+
+            // Hyperlink references e.g. `Python <https://python.org>`_
+            let mut after = end + 1;
+            let is_reference = chars.get(after) == Some(&'_');
+            if is_reference {
+                after += 1;
+                if chars.get(after) == Some(&'_') {
+                    after += 1;
+                }
+                return Some((Some(self.reference(&content)), after));
+            }
+
+            // Default role (title reference) is usually rendered as emphasis
+            return Some((Some(em([t(content)])), after));
+        }
+
+        None
+    }
+
+    /// Decode a hyperlink reference
+    fn reference(&mut self, content: &str) -> Inline {
+        match content.rsplit_once('<') {
+            Some((text, url)) if content.ends_with('>') => {
+                let url = url.trim_end_matches('>');
+                let text = text.trim();
+                let text = if text.is_empty() { url } else { text };
+                lnk([t(text)], url)
+            }
+            _ => {
+                let url = self
+                    .targets
+                    .get(&content.to_lowercase())
+                    .cloned()
+                    .unwrap_or_else(|| format!("#{}", slug(content)));
+                lnk([t(content)], url)
+            }
+        }
+    }
+
+    /// Decode an interpreted text role
+    fn role(&mut self, role: &str, content: &str) -> Option<Inline> {
+        Some(match role {
+            "math" => mi(content, Some("tex")),
+            "code" | "literal" | "samp" | "file" | "command" | "kbd" => {
+                Inline::CodeInline(CodeInline::new(content.into()))
+            }
+            "sub" | "subscript" => sub([t(content)]),
+            "sup" | "superscript" => sup([t(content)]),
+            "emphasis" | "title-reference" | "title" | "t" => em([t(content)]),
+            "strong" => stg([t(content)]),
+            "ref" | "doc" | "numref" => {
+                let (text, target) = match content.rsplit_once('<') {
+                    Some((text, target)) if content.ends_with('>') => {
+                        (text.trim(), target.trim_end_matches('>'))
+                    }
+                    _ => (content, content),
+                };
+                let target = if role == "doc" {
+                    [target, ".rst"].concat()
+                } else {
+                    ["#", target].concat()
+                };
+                lnk([t(text)], target)
+            }
+            _ => {
+                self.losses.add(format!("RST `{role}` role"));
+                t(content)
+            }
+        })
+    }
+}
+
+/// Get the admonition type corresponding to a directive name or class
+fn admonition_type(name: &str) -> Option<AdmonitionType> {
+    Some(match name {
+        "note" | "seealso" => AdmonitionType::Note,
+        "info" => AdmonitionType::Info,
+        "tip" | "hint" => AdmonitionType::Tip,
+        "important" => AdmonitionType::Important,
+        "success" => AdmonitionType::Success,
+        "failure" => AdmonitionType::Failure,
+        "warning" | "caution" | "attention" => AdmonitionType::Warning,
+        "danger" => AdmonitionType::Danger,
+        "error" => AdmonitionType::Error,
+        _ => return None,
+    })
+}
+
+/// Create a slug for a reference name, as used for implicit targets (e.g. section titles)
+fn slug(name: &str) -> String {
+    name.to_lowercase()
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .join("-")
+}
@@ -0,0 +1,377 @@
+use codec::{
+    common::itertools::Itertools,
+    schema::{
+        Admonition, AdmonitionType, Block, Figure, ImageObject, IncludeBlock, Inline, List,
+        ListOrder, Node, Paragraph, Table, TableRowType,
+    },
+    Losses,
+};
+use codec_text_trait::to_text;
+
+/// The characters used to underline headings at each level
+const HEADING_CHARS: [char; 6] = ['=', '-', '~', '^', '"', '\''];
+
+/// The indentation of the content of directives and list items
+const INDENT: &str = "   ";
+
+/// Encode a node to reStructuredText
+///
+/// Only articles and blocks can be encoded. Node types that can not be
+/// represented in reStructuredText are recorded as losses.
+pub(super) fn encode(node: &Node) -> (String, Losses) {
+    let mut context = Context::default();
+
+    let rst = match node {
+        Node::Article(article) => context.blocks(&article.content),
+        node => match Block::try_from(node.clone()) {
+            Ok(block) => context.blocks(&[block]),
+            Err(..) => {
+                context.losses.add(node.to_string());
+                String::new()
+            }
+        },
+    };
+
+    (rst, context.losses)
+}
+
+#[derive(Default)]
+struct Context {
+    /// Losses when encoding
+    losses: Losses,
+}
+
+/// Indent all non-blank lines of a string
+fn indent(text: &str, prefix: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                [prefix, line].concat()
+            }
+        })
+        .join("\n")
+}
+
+/// Create a directive with arguments, options and content
+fn directive(name: &str, args: &str, options: &[(&str, String)], content: &str) -> String {
+    let mut rst = format!(".. {name}::");
+    if !args.is_empty() {
+        rst.push(' ');
+        rst.push_str(args);
+    }
+    for (name, value) in options {
+        rst.push_str(&format!("\n{INDENT}:{name}: {value}"));
+    }
+    if !content.is_empty() {
+        rst.push_str("\n\n");
+        rst.push_str(&indent(content, INDENT));
+    }
+    rst
+}
+
+/// Encode an image as an `image` or `figure` directive
+fn image_directive(
+    name: &str,
+    image: &ImageObject,
+    options: &[(&str, String)],
+    content: &str,
+) -> String {
+    let mut options = options.to_vec();
+    if let Some(alt) = &image.caption {
+        options.push(("alt", to_text(alt)));
+    }
+    directive(name, &image.content_url, &options, content)
+}
+
+impl Context {
+    /// Encode blocks, separated by blank lines
+    fn blocks(&mut self, blocks: &[Block]) -> String {
+        let mut parts = Vec::new();
+
+        let mut index = 0;
+        while index < blocks.len() {
+            // Consecutive includes of reStructuredText files are collected into a `toctree`
+            let entries = blocks[index..]
+                .iter()
+                .map_while(|block| match block {
+                    Block::IncludeBlock(IncludeBlock { source, .. }) => source.strip_suffix(".rst"),
+                    _ => None,
+                })
+                .collect_vec();
+            if !entries.is_empty() {
+                parts.push(directive("toctree", "", &[], &entries.join("\n")));
+                index += entries.len();
+                continue;
+            }
+
+            let block = self.block(&blocks[index]);
+            if !block.is_empty() {
+                parts.push(block);
+            }
+            index += 1;
+        }
+
+        parts.join("\n\n")
+    }
+
+    /// Encode a block
+    fn block(&mut self, block: &Block) -> String {
+        match block {
+            Block::Paragraph(Paragraph { content, .. }) => match content.as_slice() {
+                [Inline::ImageObject(image)] => image_directive("image", image, &[], ""),
+                _ => self.inlines(content),
+            },
+            Block::Heading(heading) => {
+                let text = self.inlines(&heading.content);
+                let level = (heading.level.clamp(1, 6) - 1) as usize;
+                let underline = HEADING_CHARS[level]
+                    .to_string()
+                    .repeat(text.chars().count().max(1));
+                [text, underline].join("\n")
+            }
+            Block::CodeBlock(code_block) => match &code_block.programming_language {
+                Some(lang) => directive("code-block", lang, &[], &code_block.code),
+                None => ["::\n\n", &indent(&code_block.code, INDENT)].concat(),
+            },
+            Block::CodeChunk(code_chunk) => {
+                self.losses.add("CodeChunk");
+                directive(
+                    "code-block",
+                    code_chunk
+                        .programming_language
+                        .as_deref()
+                        .unwrap_or_default(),
+                    &[],
+                    &code_chunk.code,
+                )
+            }
+            Block::MathBlock(math_block) => {
+                if !matches!(
+                    math_block.math_language.as_deref(),
+                    None | Some("tex" | "latex")
+                ) {
+                    self.losses.add("MathBlock.mathLanguage");
+                }
+                directive("math", "", &[], &math_block.code)
+            }
+            Block::ImageObject(image) => image_directive("image", image, &[], ""),
+            Block::Figure(figure) => self.figure(figure),
+            Block::Admonition(admonition) => self.admonition(admonition),
+            Block::IncludeBlock(IncludeBlock { source, .. }) => {
+                directive("include", source, &[], "")
+            }
+            Block::List(list) => self.list(list),
+            Block::QuoteBlock(quote) => indent(&self.blocks(&quote.content), INDENT),
+            Block::Section(section) => self.blocks(&section.content),
+            Block::Table(table) => self.table(table),
+            Block::ThematicBreak(..) => "----".to_string(),
+            _ => {
+                self.losses.add(block.to_string());
+                String::new()
+            }
+        }
+    }
+
+    /// Encode a figure as a `figure` directive
+    ///
+    /// Only figures containing a single image can be represented. The first
+    /// block of the caption is the figure caption and any others the legend.
+    fn figure(&mut self, figure: &Figure) -> String {
+        let caption = figure
+            .caption
+            .as_ref()
+            .map(|caption| self.blocks(caption))
+            .unwrap_or_default();
+
+        let options = figure
+            .id
+            .iter()
+            .map(|id| ("name", id.clone()))
+            .collect_vec();
+
+        let image = match figure.content.as_slice() {
+            [Block::ImageObject(image)] => Some(image),
+            [Block::Paragraph(Paragraph { content, .. })] => match content.as_slice() {
+                [Inline::ImageObject(image)] => Some(image),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match image {
+            Some(image) => image_directive("figure", image, &options, &caption),
+            None => {
+                self.losses.add("Figure.content");
+                [self.blocks(&figure.content), caption]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .join("\n\n")
+            }
+        }
+    }
+
+    /// Encode an admonition
+    ///
+    /// Admonitions with a title, or with a type that does not have a
+    /// corresponding Sphinx directive, are encoded as a generic `admonition`
+    /// with the type as its class.
+    fn admonition(&mut self, admonition: &Admonition) -> String {
+        let name = match admonition.admonition_type {
+            AdmonitionType::Note => "note",
+            AdmonitionType::Info => "info",
+            AdmonitionType::Tip => "tip",
+            AdmonitionType::Important => "important",
+            AdmonitionType::Success => "success",
+            AdmonitionType::Failure => "failure",
+            AdmonitionType::Warning => "warning",
+            AdmonitionType::Danger => "danger",
+            AdmonitionType::Error => "error",
+        };
+
+        let content = self.blocks(&admonition.content);
+
+        let generic = matches!(
+            admonition.admonition_type,
+            AdmonitionType::Info | AdmonitionType::Success | AdmonitionType::Failure
+        );
+        match (&admonition.title, generic) {
+            (Some(title), ..) => {
+                let title = self.inlines(title);
+                directive(
+                    "admonition",
+                    &title,
+                    &[("class", name.to_string())],
+                    &content,
+                )
+            }
+            (None, true) => {
+                let mut title = name.to_string();
+                title[..1].make_ascii_uppercase();
+                directive(
+                    "admonition",
+                    &title,
+                    &[("class", name.to_string())],
+                    &content,
+                )
+            }
+            (None, false) => directive(name, "", &[], &content),
+        }
+    }
+
+    /// Encode a list
+    fn list(&mut self, list: &List) -> String {
+        let marker = match list.order {
+            ListOrder::Ascending => "#. ",
+            _ => "- ",
+        };
+        let spaces = " ".repeat(marker.len());
+
+        let mut multiple_blocks = false;
+        let items = list
+            .items
+            .iter()
+            .map(|item| {
+                multiple_blocks |= item.content.len() > 1;
+                let content = self.blocks(&item.content);
+                let content = indent(&content, &spaces);
+                [marker, content.trim_start()].concat()
+            })
+            .collect_vec();
+
+        // Items are separated by blank lines if any have more than one block
+        items.join(if multiple_blocks { "\n\n" } else { "\n" })
+    }
+
+    /// Encode a table as a `list-table` directive
+    fn table(&mut self, table: &Table) -> String {
+        let title = table.caption.as_ref().map(to_text).unwrap_or_default();
+
+        let header_rows = table
+            .rows
+            .iter()
+            .take_while(|row| matches!(row.row_type, Some(TableRowType::HeaderRow)))
+            .count();
+        let options = if header_rows > 0 {
+            vec![("header-rows", header_rows.to_string())]
+        } else {
+            Vec::new()
+        };
+
+        let rows = table
+            .rows
+            .iter()
+            .map(|row| {
+                let cells = row
+                    .cells
+                    .iter()
+                    .map(|cell| {
+                        let content = self.blocks(&cell.content);
+                        let content = indent(&content, "    ");
+                        ["  - ", content.trim_start()].concat()
+                    })
+                    .join("\n");
+                ["* ", cells.trim_start_matches(' ')].concat()
+            })
+            .join("\n");
+
+        directive("list-table", &title, &options, &rows)
+    }
+
+    /// Encode inlines
+    fn inlines(&mut self, inlines: &[Inline]) -> String {
+        inlines.iter().map(|inline| self.inline(inline)).join("")
+    }
+
+    /// Encode an inline
+    fn inline(&mut self, inline: &Inline) -> String {
+        match inline {
+            Inline::Text(text) => escape(&text.value),
+            Inline::Emphasis(emphasis) => ["*", &self.inlines(&emphasis.content), "*"].concat(),
+            Inline::Strong(strong) => ["**", &self.inlines(&strong.content), "**"].concat(),
+            Inline::CodeInline(code) => ["``", &code.code, "``"].concat(),
+            Inline::MathInline(math) => {
+                if !matches!(math.math_language.as_deref(), None | Some("tex" | "latex")) {
+                    self.losses.add("MathInline.mathLanguage");
+                }
+                [":math:`", &math.code, "`"].concat()
+            }
+            Inline::Subscript(subscript) => [":sub:`", &to_text(&subscript.content), "`"].concat(),
+            Inline::Superscript(superscript) => {
+                [":sup:`", &to_text(&superscript.content), "`"].concat()
+            }
+            Inline::Link(link) => {
+                let text = to_text(&link.content);
+                let target = &link.target;
+                if let Some(label) = target.strip_prefix('#') {
+                    format!(":ref:`{text} <{label}>`")
+                } else if let (Some(path), false) =
+                    (target.strip_suffix(".rst"), target.contains("://"))
+                {
+                    format!(":doc:`{text} <{path}>`")
+                } else if text == *target {
+                    format!("`<{target}>`_")
+                } else {
+                    format!("`{text} <{target}>`_")
+                }
+            }
+            _ => {
+                self.losses.add(inline.to_string());
+                escape(&to_text(inline))
+            }
+        }
+    }
+}
+
+/// Escape characters in text which would otherwise be interpreted as markup
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        if matches!(char, '\\' | '*' | '`' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
@@ -0,0 +1,229 @@
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::Node,
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, NodeType,
+};
+
+mod decode;
+mod encode;
+
+/// A codec for reStructuredText, including common Sphinx directives
+pub struct RstCodec;
+
+#[async_trait]
+impl Codec for RstCodec {
+    fn name(&self) -> &str {
+        "rst"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Rst => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Rst => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Article => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        decode::decode(str, options)
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        _options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        let (rst, losses) = encode::encode(node);
+
+        Ok((
+            rst,
+            EncodeInfo {
+                losses,
+                ..Default::default()
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::tokio,
+        schema::{
+            shortcuts::{adm, art, cb, em, h1, h2, inb, lnk, mb, mi, p, stg, t},
+            AdmonitionType, Block, CodeInline, Inline,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn decode_directives() -> Result<()> {
+        let (node, info) = RstCodec
+            .from_str(
+                r#"
+=====
+Title
+=====
+
+Some *emphasized*, **strong** and ``code`` text with :math:`x^2`
+and a `link <https://example.org>`_.
+
+Section
+-------
+
+.. code-block:: python
+   :linenos:
+
+   x = 1
+
+.. math::
+
+   E = mc^2
+
+.. note::
+
+   Remember this.
+
+.. toctree::
+   :maxdepth: 2
+
+   intro
+   Usage <usage>
+
+.. graphviz::
+
+   digraph { a -> b }
+"#,
+                None,
+            )
+            .await?;
+
+        assert_eq!(
+            node,
+            art([
+                h1([t("Title")]),
+                p([
+                    t("Some "),
+                    em([t("emphasized")]),
+                    t(", "),
+                    stg([t("strong")]),
+                    t(" and "),
+                    Inline::CodeInline(CodeInline::new("code".into())),
+                    t(" text with "),
+                    mi("x^2", Some("tex")),
+                    t(" and a "),
+                    lnk([t("link")], "https://example.org"),
+                    t(".")
+                ]),
+                h2([t("Section")]),
+                cb("x = 1", Some("python")),
+                mb("E = mc^2", Some("tex")),
+                adm(
+                    AdmonitionType::Note,
+                    None::<String>,
+                    [p([t("Remember this.")])]
+                ),
+                inb("intro.rst"),
+                inb("usage.rst"),
+            ])
+        );
+
+        assert_eq!(
+            info.losses
+                .iter()
+                .map(|(label, ..)| label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["RST `graphviz` directive"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn roundtrip() -> Result<()> {
+        let rst = r#"Title
+=====
+
+A paragraph with *emphasis* and a :ref:`reference <target>`.
+
+- One
+- Two
+
+#. First
+
+   With a second paragraph
+
+#. Second
+
+.. code-block:: r
+
+   x <- 1
+
+::
+
+   literal
+
+.. warning::
+
+   Careful!
+
+.. admonition:: Heads up
+   :class: tip
+
+   Some advice.
+
+.. toctree::
+
+   intro
+   usage
+
+----
+
+   A quote"#;
+
+        let (node, info) = RstCodec.from_str(rst, None).await?;
+        assert!(info.losses.is_empty());
+
+        let Node::Article(article) = &node else {
+            unreachable!()
+        };
+        assert!(matches!(article.content[4], Block::CodeBlock(..)));
+
+        let (encoded, info) = RstCodec.to_string(&node, None).await?;
+        assert!(info.losses.is_empty());
+        assert_eq!(encoded, rst);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+//! Provides the `TypstCodec` trait for generating Typst for Stencila Schema nodes
+
+use codec_info::{Losses, Mapping, NodeId, NodeProperty, NodeType};
+
+pub use codec_typst_derive::TypstCodec;
+
+mod math;
+pub use math::tex_to_typst;
+
+pub trait TypstCodec {
+    /// Encode a Stencila Schema node to Typst
+    fn to_typst(&self, context: &mut TypstEncodeContext);
+}
+
+/// Encode a node that implements `TypstCodec` to Typst
+///
+/// A convenience function to save the caller from having to create a context etc.
+pub fn to_typst<T>(node: &T) -> String
+where
+    T: TypstCodec,
+{
+    let mut context = TypstEncodeContext::default();
+    node.to_typst(&mut context);
+    context.content.trim().to_string()
+}
+
+/// Escape characters which have special meaning in Typst markup
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        if matches!(
+            char,
+            '\\' | '#' | '*' | '_' | '`' | '$' | '@' | '<' | '>' | '[' | ']' | '~' | '/'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// Escape a string so that it can be used as a Typst string literal
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(char),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[derive(Default)]
+pub struct TypstEncodeContext {
+    /// The encoded Typst content
+    pub content: String,
+
+    /// A stack of node types, ids and start positions
+    node_stack: Vec<(NodeType, NodeId, usize)>,
+
+    /// Node to position mapping
+    pub mapping: Mapping,
+
+    /// Encoding losses
+    pub losses: Losses,
+
+    /// The nesting depth for list items and other indented content
+    depth: usize,
+
+    /// Whether executable nodes should be encoded as their outputs only
+    pub render: bool,
+}
+
+impl TypstEncodeContext {
+    pub fn new(render: bool) -> Self {
+        Self {
+            render,
+            ..Default::default()
+        }
+    }
+
+    /// Get the current insertion position (i.e. the number of characters in the content)
+    fn char_index(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    /// Enter a node
+    ///
+    /// Pushes the node id and start position onto the stack.
+    pub fn enter_node(&mut self, node_type: NodeType, node_id: NodeId) -> &mut Self {
+        self.node_stack
+            .push((node_type, node_id, self.char_index()));
+        self
+    }
+
+    /// Exit a node
+    ///
+    /// Pops the node id and start position off the stack and creates a
+    /// new mapping entry with those and the current position as end position.
+    pub fn exit_node(&mut self) -> &mut Self {
+        if let Some((node_type, node_id, start)) = self.node_stack.pop() {
+            let mut end = self.char_index();
+            if self.content.ends_with("\n\n") {
+                end -= 1;
+            }
+            self.mapping.add(start, end, node_type, node_id, None, None)
+        }
+        self
+    }
+
+    /// Exit the final node
+    ///
+    /// Should only be used by the top level `Article`. Does not exclude any double newline
+    /// at the end from the range.
+    pub fn exit_node_final(&mut self) -> &mut Self {
+        if let Some((node_type, node_id, start)) = self.node_stack.pop() {
+            let end = self.char_index();
+            self.mapping.add(start, end, node_type, node_id, None, None)
+        }
+        self
+    }
+
+    /// Push a property represented by a string content onto the Typst
+    ///
+    /// Creates a new mapping entry for the property.
+    pub fn property_str(&mut self, prop: NodeProperty, value: &str) -> &mut Self {
+        let start = self.char_index();
+
+        self.content.push_str(value);
+
+        if let Some((node_type, node_id, ..)) = self.node_stack.last() {
+            let end = self.char_index();
+            self.mapping
+                .add(start, end, *node_type, node_id.clone(), Some(prop), None);
+        }
+        self
+    }
+
+    /// Push a property by calling a function to push content onto the Typst
+    ///
+    /// Creates a new mapping entry for the property.
+    pub fn property_fn<F>(&mut self, prop: NodeProperty, func: F) -> &mut Self
+    where
+        F: Fn(&mut Self),
+    {
+        let start = self.char_index();
+
+        func(self);
+
+        if let Some((node_type, node_id, ..)) = self.node_stack.last() {
+            let end = self.char_index();
+            self.mapping
+                .add(start, end, *node_type, node_id.clone(), Some(prop), None);
+        }
+        self
+    }
+
+    /// Increase the nesting depth
+    pub fn increase_depth(&mut self) -> &mut Self {
+        self.depth += 1;
+        self
+    }
+
+    /// Decrease the nesting depth
+    pub fn decrease_depth(&mut self) -> &mut Self {
+        self.depth = self.depth.saturating_sub(1);
+        self
+    }
+
+    /// Push a string onto the Typst content
+    pub fn str(&mut self, value: &str) -> &mut Self {
+        if self.depth > 0 && matches!(self.content.chars().last(), None | Some('\n')) {
+            self.content.push_str(&"  ".repeat(self.depth));
+        }
+        self.content.push_str(value);
+        self
+    }
+
+    /// Push a character onto the Typst content
+    pub fn char(&mut self, value: char) -> &mut Self {
+        self.content.push(value);
+        self
+    }
+
+    /// Add a single space to the end of the content
+    pub fn space(&mut self) -> &mut Self {
+        self.content.push(' ');
+        self
+    }
+
+    /// Add a single newline to the end of the content
+    pub fn newline(&mut self) -> &mut Self {
+        self.content.push('\n');
+        self
+    }
+
+    /// Enter a Typst function call with a trailing content block e.g. `#emph[`
+    pub fn function_enter(&mut self, name: &str) -> &mut Self {
+        self.content.push('#');
+        self.content.push_str(name);
+        self.content.push('[');
+        self
+    }
+
+    /// Exit a Typst function call with a trailing content block
+    pub fn function_exit(&mut self) -> &mut Self {
+        self.content.push(']');
+        self
+    }
+
+    /// Trim whitespace from the end of the content in-place
+    ///
+    /// According to [this](https://users.rust-lang.org/t/trim-string-in-place/15809/18)
+    /// this is the recommended way to trim in place.
+    pub fn trim_end(&mut self) -> &mut Self {
+        let trimmed = self.content.trim_end();
+        self.content.truncate(trimmed.len());
+        self
+    }
+
+    /// Add a single loss
+    pub fn add_loss(&mut self, label: &str) -> &mut Self {
+        self.losses.add(label);
+        self
+    }
+
+    /// Append a vector of losses
+    pub fn merge_losses(&mut self, losses: Losses) -> &mut Self {
+        self.losses.merge(losses);
+        self
+    }
+}
+
+macro_rules! to_string {
+    ($type:ty, $name:literal) => {
+        impl TypstCodec for $type {
+            fn to_typst(&self, context: &mut TypstEncodeContext) {
+                context.str(&self.to_string());
+            }
+        }
+    };
+}
+
+to_string!(bool, "Boolean");
+to_string!(i64, "Integer");
+to_string!(u64, "UnsignedInteger");
+to_string!(f64, "Number");
+
+impl TypstCodec for String {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context.str(&self.to_string());
+    }
+}
+
+impl<T> TypstCodec for Box<T>
+where
+    T: TypstCodec,
+{
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        self.as_ref().to_typst(context)
+    }
+}
+
+impl<T> TypstCodec for Option<T>
+where
+    T: TypstCodec,
+{
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        if let Some(value) = self {
+            value.to_typst(context);
+        }
+    }
+}
+
+impl<T> TypstCodec for Vec<T>
+where
+    T: TypstCodec,
+{
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        for item in self.iter() {
+            item.to_typst(context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("a #b $c$ *d*"), r"a \#b \$c\$ \*d\*");
+        assert_eq!(escape("[x](y)"), r"\[x\](y)");
+        assert_eq!(quote("a \"b\"\n"), r#""a \"b\"\n""#);
+    }
+}
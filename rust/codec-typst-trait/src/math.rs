@@ -0,0 +1,456 @@
+//! Conversion of TeX math to Typst math
+
+/// Convert TeX math to Typst math
+///
+/// Supports commonly used TeX math: Greek letters and other symbols, operators and
+/// functions, fractions, roots, accents, fonts, text, sub- and superscripts, and
+/// `\left`/`\right` delimiters. Returns `None` if the TeX uses a command or
+/// environment that is not supported so that the caller can fall back to another
+/// encoding of the math.
+pub fn tex_to_typst(tex: &str) -> Option<String> {
+    let mut converter = Converter {
+        chars: tex.chars().collect(),
+        pos: 0,
+    };
+    let atoms = converter.sequence(None)?;
+    Some(atoms.join(" "))
+}
+
+/// A converter of TeX math to Typst math
+///
+/// TeX is converted into a sequence of Typst "atoms" which are joined by spaces
+/// (which are not significant in Typst math, except to separate identifiers).
+struct Converter {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Converter {
+    /// Peek at the next character
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Consume the next character
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.pos += 1;
+        Some(char)
+    }
+
+    /// Skip any whitespace
+    fn skip_whitespace(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Convert a sequence of atoms until the end of input, or until the `close` character
+    fn sequence(&mut self, close: Option<char>) -> Option<Vec<String>> {
+        let mut atoms: Vec<String> = Vec::new();
+        loop {
+            self.skip_whitespace();
+
+            let Some(char) = self.peek() else {
+                // Unclosed groups are invalid
+                return close.is_none().then_some(atoms);
+            };
+
+            if Some(char) == close {
+                self.pos += 1;
+                return Some(atoms);
+            }
+
+            // Scripts and primes attach to the preceding atom
+            if matches!(char, '^' | '_' | '\'') {
+                self.pos += 1;
+                let base = atoms.pop().unwrap_or_else(|| "\"\"".to_string());
+                let attached = if char == '\'' {
+                    format!("{base}'")
+                } else {
+                    format!("{base}{char}{}", parenthesize(self.argument()?))
+                };
+                atoms.push(attached);
+                continue;
+            }
+
+            let atom = self.atom()?;
+            if !atom.is_empty() {
+                atoms.push(atom);
+            }
+        }
+    }
+
+    /// Convert the argument of a command or script
+    ///
+    /// Either a group, or a single token (for digits, a single digit).
+    fn argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        match self.peek()? {
+            '{' => {
+                self.pos += 1;
+                Some(self.sequence(Some('}'))?.join(" "))
+            }
+            char if char.is_ascii_digit() => {
+                self.pos += 1;
+                Some(char.to_string())
+            }
+            _ => self.atom(),
+        }
+    }
+
+    /// Get the raw (unconverted) content of a group argument e.g. for `\text{...}`
+    fn raw_argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        if self.next()? != '{' {
+            return None;
+        }
+
+        let mut content = String::new();
+        let mut depth = 0;
+        loop {
+            match self.next()? {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Some(content),
+                '}' => depth -= 1,
+                char => content.push(char),
+            }
+        }
+    }
+
+    /// Convert a single atom
+    fn atom(&mut self) -> Option<String> {
+        let char = self.next()?;
+        Some(match char {
+            '\\' => return self.command(),
+            '{' => {
+                let atoms = self.sequence(Some('}'))?;
+                if atoms.is_empty() {
+                    "\"\"".to_string()
+                } else {
+                    atoms.join(" ")
+                }
+            }
+            '}' => return None,
+            '0'..='9' | '.' => {
+                let mut number = char.to_string();
+                while let Some(char) = self
+                    .peek()
+                    .filter(|char| char.is_ascii_digit() || *char == '.')
+                {
+                    number.push(char);
+                    self.pos += 1;
+                }
+                number
+            }
+            '~' => "space".to_string(),
+            '/' | '#' | '"' | '$' | '@' => format!("\\{char}"),
+            _ => char.to_string(),
+        })
+    }
+
+    /// Convert a command (the backslash having already been consumed)
+    fn command(&mut self) -> Option<String> {
+        let first = self.next()?;
+        if !first.is_ascii_alphabetic() {
+            return Some(
+                match first {
+                    '\\' => "\\",
+                    ',' => "thin",
+                    ':' | '>' => "med",
+                    ';' => "thick",
+                    ' ' => "space",
+                    '!' => "",
+                    '|' => "bar.v.double",
+                    '{' => "\\{",
+                    '}' => "\\}",
+                    '%' | '&' | '#' | '$' | '_' => return Some(format!("\\{first}")),
+                    _ => return None,
+                }
+                .to_string(),
+            );
+        }
+
+        let mut name = first.to_string();
+        while let Some(char) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(char);
+            self.pos += 1;
+        }
+
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Some(name);
+        }
+        if let Some(symbol) = symbol(&name) {
+            return Some(symbol.to_string());
+        }
+
+        Some(match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                call("frac", &[self.argument()?, self.argument()?])?
+            }
+            "binom" => call("binom", &[self.argument()?, self.argument()?])?,
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let index = self.sequence(Some(']'))?.join(" ");
+                    call("root", &[index, self.argument()?])?
+                } else {
+                    call("sqrt", &[self.argument()?])?
+                }
+            }
+            "text" | "textrm" | "textnormal" | "mbox" => {
+                format!("\"{}\"", self.raw_argument()?.replace('"', "\\\""))
+            }
+            "operatorname" => format!("op(\"{}\")", self.raw_argument()?.replace('"', "\\\"")),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr" | "Bigl"
+            | "Bigr" | "biggl" | "biggr" | "Biggl" | "Biggr" => self.delimiter()?,
+            "limits" | "nolimits" | "displaystyle" | "textstyle" | "scriptstyle" => String::new(),
+            _ => {
+                let function = match name.as_str() {
+                    "hat" | "widehat" => "hat",
+                    "bar" | "overline" => "overline",
+                    "underline" => "underline",
+                    "vec" => "arrow",
+                    "tilde" | "widetilde" => "tilde",
+                    "dot" => "dot",
+                    "ddot" => "dot.double",
+                    "mathbf" | "boldsymbol" | "bm" => "bold",
+                    "mathrm" => "upright",
+                    "mathit" => "italic",
+                    "mathcal" => "cal",
+                    "mathbb" => "bb",
+                    "mathfrak" => "frak",
+                    "mathsf" => "sans",
+                    "mathtt" => "mono",
+                    _ => return None,
+                };
+                call(function, &[self.argument()?])?
+            }
+        })
+    }
+
+    /// Convert the delimiter following a `\left`, `\right`, or sizing command
+    ///
+    /// Typst scales matching delimiters automatically so only the delimiter is needed.
+    fn delimiter(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        match self.next()? {
+            '.' => Some(String::new()),
+            '\\' => self.command(),
+            char => Some(char.to_string()),
+        }
+    }
+}
+
+/// TeX function commands (e.g. `\sin`) which have the same names in Typst
+const FUNCTIONS: &[&str] = &[
+    "arccos", "arcsin", "arctan", "arg", "cos", "cosh", "cot", "coth", "csc", "deg", "det", "dim",
+    "exp", "gcd", "inf", "ker", "lg", "lim", "liminf", "limsup", "ln", "log", "max", "min", "mod",
+    "Pr", "sec", "sin", "sinh", "sup", "tan", "tanh",
+];
+
+/// Wrap a script in parentheses if it is not a single atom
+fn parenthesize(script: String) -> String {
+    if script
+        .chars()
+        .all(|char| char.is_alphanumeric() || char == '.')
+    {
+        script
+    } else {
+        format!("({script})")
+    }
+}
+
+/// Create a call to a Typst function
+///
+/// Returns `None` if any of the arguments contain commas or semicolons (which
+/// would be interpreted as separating arguments).
+fn call(function: &str, args: &[String]) -> Option<String> {
+    if args.iter().any(|arg| arg.contains([',', ';'])) {
+        return None;
+    }
+    Some(format!("{function}({})", args.join(", ")))
+}
+
+/// Get the Typst equivalent of a TeX symbol command
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        // Greek letters which have the same names in TeX and Typst
+        "alpha" => "alpha",
+        "beta" => "beta",
+        "gamma" => "gamma",
+        "delta" => "delta",
+        "zeta" => "zeta",
+        "eta" => "eta",
+        "theta" => "theta",
+        "iota" => "iota",
+        "kappa" => "kappa",
+        "lambda" => "lambda",
+        "mu" => "mu",
+        "nu" => "nu",
+        "xi" => "xi",
+        "pi" => "pi",
+        "rho" => "rho",
+        "sigma" => "sigma",
+        "tau" => "tau",
+        "upsilon" => "upsilon",
+        "chi" => "chi",
+        "psi" => "psi",
+        "omega" => "omega",
+        "Gamma" => "Gamma",
+        "Delta" => "Delta",
+        "Theta" => "Theta",
+        "Lambda" => "Lambda",
+        "Xi" => "Xi",
+        "Pi" => "Pi",
+        "Sigma" => "Sigma",
+        "Upsilon" => "Upsilon",
+        "Phi" => "Phi",
+        "Psi" => "Psi",
+        "Omega" => "Omega",
+
+        // Greek letters which differ in TeX and Typst
+        "epsilon" => "epsilon.alt",
+        "varepsilon" => "epsilon",
+        "phi" => "phi.alt",
+        "varphi" => "phi",
+        "vartheta" => "theta.alt",
+        "varpi" => "pi.alt",
+        "varrho" => "rho.alt",
+        "varsigma" => "sigma.alt",
+
+        // Large operators
+        "sum" => "sum",
+        "prod" => "product",
+        "coprod" => "product.co",
+        "int" => "integral",
+        "iint" => "integral.double",
+        "iiint" => "integral.triple",
+        "oint" => "integral.cont",
+        "bigcup" => "union.big",
+        "bigcap" => "sect.big",
+
+        // Binary operators and relations
+        "pm" => "plus.minus",
+        "mp" => "minus.plus",
+        "times" => "times",
+        "div" => "div",
+        "cdot" => "dot",
+        "ast" => "ast",
+        "star" => "star",
+        "circ" => "compose",
+        "bullet" => "bullet",
+        "oplus" => "plus.circle",
+        "otimes" => "times.circle",
+        "le" | "leq" => "<=",
+        "ge" | "geq" => ">=",
+        "ne" | "neq" => "!=",
+        "ll" => "<<",
+        "gg" => ">>",
+        "approx" => "approx",
+        "equiv" => "equiv",
+        "sim" => "tilde.op",
+        "simeq" => "tilde.eq",
+        "cong" => "tilde.equiv",
+        "propto" => "prop",
+        "in" => "in",
+        "notin" => "in.not",
+        "ni" => "in.rev",
+        "subset" => "subset",
+        "subseteq" => "subset.eq",
+        "supset" => "supset",
+        "supseteq" => "supset.eq",
+        "cup" => "union",
+        "cap" => "sect",
+        "setminus" => "without",
+        "land" | "wedge" => "and",
+        "lor" | "vee" => "or",
+        "lnot" | "neg" => "not",
+        "mid" => "divides",
+        "parallel" => "parallel",
+        "perp" => "perp",
+
+        // Arrows
+        "to" | "rightarrow" => "->",
+        "leftarrow" | "gets" => "<-",
+        "leftrightarrow" => "<->",
+        "Rightarrow" | "implies" => "=>",
+        "Leftarrow" => "arrow.l.double",
+        "Leftrightarrow" | "iff" => "<=>",
+        "mapsto" => "|->",
+        "uparrow" => "arrow.t",
+        "downarrow" => "arrow.b",
+
+        // Other symbols
+        "infty" => "infinity",
+        "partial" => "diff",
+        "nabla" => "nabla",
+        "forall" => "forall",
+        "exists" => "exists",
+        "emptyset" | "varnothing" => "nothing",
+        "ell" => "ell",
+        "hbar" => "planck.reduce",
+        "prime" => "prime",
+        "angle" => "angle",
+        "degree" => "degree",
+        "dots" | "ldots" => "dots",
+        "cdots" => "dots.c",
+        "vdots" => "dots.v",
+        "ddots" => "dots.down",
+        "langle" => "angle.l",
+        "rangle" => "angle.r",
+        "lfloor" => "floor.l",
+        "rfloor" => "floor.r",
+        "lceil" => "ceil.l",
+        "rceil" => "ceil.r",
+        "vert" => "bar.v",
+        "Vert" => "bar.v.double",
+        "lbrace" => "\\{",
+        "rbrace" => "\\}",
+
+        // Spacing
+        "quad" => "quad",
+        "qquad" => "wide",
+
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_tex() {
+        let convert = |tex: &str| tex_to_typst(tex).unwrap_or_else(|| "<none>".to_string());
+
+        assert_eq!(convert("x^2 + y_{i,j}"), "x^2 + y_(i , j)");
+        assert_eq!(convert("ab = 1.5x"), "a b = 1.5 x");
+        assert_eq!(convert("x^{-1}"), "x^(- 1)");
+        assert_eq!(convert("f'(x)"), "f' ( x )");
+        assert_eq!(
+            convert(r"\alpha + \beta \leq \epsilon"),
+            "alpha + beta <= epsilon.alt"
+        );
+        assert_eq!(convert(r"\frac{a}{b + 1}"), "frac(a, b + 1)");
+        assert_eq!(
+            convert(r"\sqrt[3]{x} \cdot \sqrt2"),
+            "root(3, x) dot sqrt(2)"
+        );
+        assert_eq!(convert(r"\sum_{i=1}^{n} \sin x_i"), "sum_(i = 1)^n sin x_i");
+        assert_eq!(convert(r"\left( \frac{1}{2} \right)"), "( frac(1, 2) )");
+        assert_eq!(convert(r"\text{if } x > 0"), "\"if \" x > 0");
+        assert_eq!(convert(r"\mathbf{v} = \vec{u}"), "bold(v) = arrow(u)");
+        assert_eq!(convert(r"a/b"), r"a \/ b");
+    }
+
+    #[test]
+    fn unsupported_tex() {
+        assert_eq!(tex_to_typst(r"\begin{matrix} a \end{matrix}"), None);
+        assert_eq!(tex_to_typst(r"\unknowncommand"), None);
+        assert_eq!(tex_to_typst(r"\frac{a, b}{c}"), None);
+        assert_eq!(tex_to_typst(r"{x"), None);
+        assert_eq!(tex_to_typst(r"x}"), None);
+    }
+}
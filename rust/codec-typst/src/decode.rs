@@ -0,0 +1,604 @@
+use codec::{
+    common::{eyre::Result, itertools::Itertools},
+    schema::{
+        shortcuts::{cb, em, h, img, lnk, mb, mi, p, qb, qi, stg, stk, sub, sup, t, tb, u},
+        Article, Block, CodeInline, Figure, Inline, ListItem, ListOrder, Node, Table, TableCell,
+        TableCellType, TableRow, TableRowType,
+    },
+    DecodeInfo, DecodeOptions, Losses, Message, MessageLevel, Messages,
+};
+use typst::syntax::{
+    ast::{self, AstNode, Expr, Markup},
+    parse,
+};
+
+/// Decode Typst markup to a Stencila [`Node`]
+///
+/// Parses the markup using the Typst parser and then walks over the syntax
+/// tree. Markup (e.g. headings, lists, raw blocks) and calls to common
+/// functions (e.g. `#figure`, `#table`, `#link`) are decoded into the
+/// corresponding node types. Other code (e.g. `#set` and `#show` rules) is
+/// recorded as a loss.
+pub(super) fn decode(typst: &str, _options: Option<DecodeOptions>) -> Result<(Node, DecodeInfo)> {
+    let root = parse(typst);
+
+    let messages = root
+        .errors()
+        .into_iter()
+        .map(|error| Message {
+            level: MessageLevel::Error,
+            message: error.message.to_string(),
+            ..Default::default()
+        })
+        .collect();
+
+    let mut context = Context::default();
+    let content = match root.cast::<Markup>() {
+        Some(markup) => context.blocks(markup),
+        None => Vec::new(),
+    };
+
+    let node = Node::Article(Article::new(content));
+
+    let info = DecodeInfo {
+        messages: Messages(messages),
+        losses: context.losses,
+        ..Default::default()
+    };
+
+    Ok((node, info))
+}
+
+#[derive(Default)]
+struct Context {
+    /// Losses when decoding
+    losses: Losses,
+}
+
+/// A list being accumulated from consecutive list or enum items
+struct ListBuilder {
+    order: ListOrder,
+    items: Vec<ListItem>,
+}
+
+/// Inline nodes being accumulated, with adjacent text merged
+#[derive(Default)]
+struct Inlines {
+    inlines: Vec<Inline>,
+    text: String,
+}
+
+impl Inlines {
+    fn push_text(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn push(&mut self, inline: Inline) {
+        self.flush_text();
+        self.inlines.push(inline);
+    }
+
+    fn flush_text(&mut self) {
+        if !self.text.is_empty() {
+            self.inlines.push(t(std::mem::take(&mut self.text)));
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inlines.is_empty() && self.text.trim().is_empty()
+    }
+
+    /// Take the inlines, trimming whitespace from the start and end
+    fn take(&mut self) -> Vec<Inline> {
+        self.flush_text();
+
+        let mut inlines = std::mem::take(&mut self.inlines);
+        if let Some(Inline::Text(text)) = inlines.first_mut() {
+            text.value = text.value.trim_start().into();
+        }
+        if let Some(Inline::Text(text)) = inlines.last_mut() {
+            text.value = text.value.trim_end().into();
+        }
+        inlines.retain(|inline| !matches!(inline, Inline::Text(text) if text.value.is_empty()));
+
+        inlines
+    }
+}
+
+/// The arguments of a function call
+struct Args<'a> {
+    positional: Vec<Expr<'a>>,
+    named: Vec<(String, Expr<'a>)>,
+}
+
+impl<'a> Args<'a> {
+    fn new(call: ast::FuncCall<'a>) -> Self {
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        for arg in call.args().items() {
+            match arg {
+                ast::Arg::Pos(expr) => positional.push(expr),
+                ast::Arg::Named(arg) => named.push((arg.name().get().to_string(), arg.expr())),
+                ast::Arg::Spread(..) => {}
+            }
+        }
+        Self { positional, named }
+    }
+
+    /// Get a named argument
+    fn get(&self, name: &str) -> Option<Expr<'a>> {
+        self.named
+            .iter()
+            .find_map(|(arg, expr)| (arg == name).then_some(*expr))
+    }
+
+    /// Get the markup of the first content block in the positional arguments
+    fn content(&self) -> Option<Markup<'a>> {
+        self.positional.iter().find_map(|expr| markup(*expr))
+    }
+
+    /// Get the first string in the positional arguments
+    fn string(&self) -> Option<String> {
+        self.positional.iter().find_map(|expr| match expr {
+            Expr::Str(string) => Some(string.get().to_string()),
+            _ => None,
+        })
+    }
+}
+
+/// Get the markup of a content block expression
+fn markup(expr: Expr) -> Option<Markup> {
+    match expr {
+        Expr::Content(block) => Some(block.body()),
+        _ => None,
+    }
+}
+
+/// Get the name of the function called e.g. `image` or `table.header`
+fn call_name(call: ast::FuncCall) -> Option<String> {
+    match call.callee() {
+        Expr::Ident(ident) => Some(ident.get().to_string()),
+        Expr::FieldAccess(access) => match access.target() {
+            Expr::Ident(target) => Some(format!("{}.{}", target.get(), access.field().get())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Get the source text of a syntax node e.g. the code of an equation
+fn source<'a>(node: impl AstNode<'a>) -> String {
+    node.to_untyped().clone().into_text().trim().to_string()
+}
+
+impl Context {
+    /// Decode markup into blocks
+    fn blocks(&mut self, markup: Markup) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut inlines = Inlines::default();
+        let mut list: Option<ListBuilder> = None;
+
+        fn flush_paragraph(blocks: &mut Vec<Block>, inlines: &mut Inlines) {
+            if !inlines.is_empty() {
+                blocks.push(p(inlines.take()));
+            }
+            *inlines = Inlines::default();
+        }
+
+        fn flush_list(blocks: &mut Vec<Block>, list: &mut Option<ListBuilder>) {
+            if let Some(ListBuilder { order, items }) = list.take() {
+                blocks.push(Block::List(codec::schema::List::new(items, order)));
+            }
+        }
+
+        for expr in markup.exprs() {
+            let (order, body) = match expr {
+                Expr::List(item) => (ListOrder::Unordered, Some(item.body())),
+                Expr::Enum(item) => (ListOrder::Ascending, Some(item.body())),
+                _ => (ListOrder::Unordered, None),
+            };
+            if let Some(body) = body {
+                flush_paragraph(&mut blocks, &mut inlines);
+                if list
+                    .as_ref()
+                    .map(|list| list.order != order)
+                    .unwrap_or(false)
+                {
+                    flush_list(&mut blocks, &mut list);
+                }
+                let item = ListItem::new(self.blocks(body));
+                list.get_or_insert_with(|| ListBuilder {
+                    order,
+                    items: Vec::new(),
+                })
+                .items
+                .push(item);
+                continue;
+            }
+
+            match expr {
+                Expr::Space(..) if list.is_some() && inlines.is_empty() => {}
+                Expr::Parbreak(..) => {
+                    flush_paragraph(&mut blocks, &mut inlines);
+                    flush_list(&mut blocks, &mut list);
+                }
+                Expr::Heading(heading) => {
+                    flush_paragraph(&mut blocks, &mut inlines);
+                    flush_list(&mut blocks, &mut list);
+                    blocks.push(h(
+                        heading.depth().get() as i64,
+                        self.inlines(heading.body()),
+                    ));
+                }
+                Expr::Raw(raw) if raw.block() => {
+                    flush_paragraph(&mut blocks, &mut inlines);
+                    flush_list(&mut blocks, &mut list);
+                    let code = raw.lines().map(|line| line.get().to_string()).join("\n");
+                    let lang = raw.lang().map(|lang| lang.get().to_string());
+                    blocks.push(cb(code, lang));
+                }
+                Expr::Equation(equation) if equation.block() => {
+                    flush_paragraph(&mut blocks, &mut inlines);
+                    flush_list(&mut blocks, &mut list);
+                    blocks.push(mb(source(equation.body()), Some("typst")));
+                }
+                Expr::FuncCall(call) => match self.block_call(call) {
+                    Some(mut call_blocks) => {
+                        flush_paragraph(&mut blocks, &mut inlines);
+                        flush_list(&mut blocks, &mut list);
+                        blocks.append(&mut call_blocks);
+                    }
+                    None => {
+                        flush_list(&mut blocks, &mut list);
+                        self.inline(expr, &mut inlines);
+                    }
+                },
+                _ => {
+                    if !matches!(expr, Expr::Space(..)) {
+                        flush_list(&mut blocks, &mut list);
+                    }
+                    self.inline(expr, &mut inlines);
+                }
+            }
+        }
+
+        flush_paragraph(&mut blocks, &mut inlines);
+        flush_list(&mut blocks, &mut list);
+
+        blocks
+    }
+
+    /// Decode a call to a function which produces blocks
+    ///
+    /// Returns `None` if the function is not a block function.
+    fn block_call(&mut self, call: ast::FuncCall) -> Option<Vec<Block>> {
+        let name = call_name(call)?;
+        let args = Args::new(call);
+
+        let block = match name.as_str() {
+            "figure" => {
+                // The body of a figure can be a content block, or any other
+                // expression e.g. `image("plot.png")`
+                let content = match args.content() {
+                    Some(markup) => self.blocks(markup),
+                    None => args
+                        .positional
+                        .first()
+                        .map(|expr| self.expr_blocks(*expr))
+                        .unwrap_or_default(),
+                };
+                if args.positional.len() > 1 {
+                    self.losses.add("Typst figure argument");
+                }
+                for (name, ..) in &args.named {
+                    if name != "caption" {
+                        self.losses.add(format!("Typst figure `{name}` argument"));
+                    }
+                }
+
+                let caption = args
+                    .get("caption")
+                    .and_then(markup)
+                    .map(|markup| self.blocks(markup));
+
+                // Tables with captions are wrapped in figures in Typst
+                if let (Some(..), [Block::Table(table)]) = (&caption, content.as_slice()) {
+                    return Some(vec![Block::Table(Table {
+                        caption,
+                        ..table.clone()
+                    })]);
+                }
+
+                Block::Figure(Figure {
+                    caption,
+                    ..Figure::new(content)
+                })
+            }
+            "table" => Block::Table(self.table(&args)),
+            "quote" if matches!(args.get("block"), Some(Expr::Bool(block)) if block.get()) => {
+                qb(args
+                    .content()
+                    .map(|markup| self.blocks(markup))
+                    .unwrap_or_default())
+            }
+            "heading" => {
+                let level = match args.get("level") {
+                    Some(Expr::Int(level)) => level.get(),
+                    _ => 1,
+                };
+                h(
+                    level,
+                    args.content()
+                        .map(|markup| self.inlines(markup))
+                        .unwrap_or_default(),
+                )
+            }
+            "line" => tb(),
+            "align" | "block" | "pad" => {
+                return Some(
+                    args.content()
+                        .map(|markup| self.blocks(markup))
+                        .unwrap_or_default(),
+                )
+            }
+            _ => return None,
+        };
+
+        Some(vec![block])
+    }
+
+    /// Decode an expression, other than a content block, into blocks
+    ///
+    /// Images are decoded as blocks, other inlines are wrapped in a paragraph.
+    fn expr_blocks(&mut self, expr: Expr) -> Vec<Block> {
+        if let Expr::FuncCall(call) = expr {
+            if let Some(blocks) = self.block_call(call) {
+                return blocks;
+            }
+        }
+
+        let mut inlines = Inlines::default();
+        self.inline(expr, &mut inlines);
+        let inlines = inlines.take();
+
+        match inlines.as_slice() {
+            [] => Vec::new(),
+            [Inline::ImageObject(image)] => vec![Block::ImageObject(image.clone())],
+            _ => vec![p(inlines)],
+        }
+    }
+
+    /// Decode a call to the `table` function
+    fn table(&mut self, args: &Args) -> Table {
+        let columns = match args.get("columns") {
+            Some(Expr::Int(columns)) => columns.get().max(1) as usize,
+            Some(Expr::Array(array)) => array.items().count().max(1),
+            _ => 1,
+        };
+
+        let mut rows = Vec::new();
+        let mut cells = Vec::new();
+        for expr in &args.positional {
+            match expr {
+                Expr::Content(..) => {
+                    let content = markup(*expr)
+                        .map(|markup| self.blocks(markup))
+                        .unwrap_or_default();
+                    cells.push(TableCell::new(content));
+                    if cells.len() == columns {
+                        rows.push(TableRow::new(std::mem::take(&mut cells)));
+                    }
+                }
+                Expr::FuncCall(call) => {
+                    let row_type = match call_name(*call).as_deref() {
+                        Some("table.header") => TableRowType::HeaderRow,
+                        Some("table.footer") => TableRowType::FooterRow,
+                        _ => {
+                            self.losses.add("Typst table function call");
+                            continue;
+                        }
+                    };
+                    let header = matches!(row_type, TableRowType::HeaderRow);
+                    let cells = Args::new(*call)
+                        .positional
+                        .into_iter()
+                        .filter_map(markup)
+                        .map(|markup| TableCell {
+                            cell_type: header.then_some(TableCellType::HeaderCell),
+                            ..TableCell::new(self.blocks(markup))
+                        })
+                        .collect_vec();
+                    rows.push(TableRow {
+                        row_type: Some(row_type),
+                        ..TableRow::new(cells)
+                    });
+                }
+                _ => {
+                    self.losses.add("Typst table argument");
+                }
+            }
+        }
+        if !cells.is_empty() {
+            rows.push(TableRow::new(cells));
+        }
+
+        Table::new(rows)
+    }
+
+    /// Decode markup into inlines
+    fn inlines(&mut self, markup: Markup) -> Vec<Inline> {
+        let mut inlines = Inlines::default();
+        for expr in markup.exprs() {
+            self.inline(expr, &mut inlines);
+        }
+        inlines.take()
+    }
+
+    /// Decode an expression into an inline
+    fn inline(&mut self, expr: Expr, inlines: &mut Inlines) {
+        match expr {
+            Expr::Text(text) => inlines.push_text(text.get()),
+            Expr::Space(..) | Expr::Linebreak(..) | Expr::Parbreak(..) => inlines.push_text(" "),
+            Expr::Escape(escape) => inlines.push_text(&escape.get().to_string()),
+            Expr::Shorthand(shorthand) => inlines.push_text(&shorthand.get().to_string()),
+            Expr::SmartQuote(quote) => inlines.push_text(if quote.double() { "\"" } else { "'" }),
+            Expr::Strong(strong) => inlines.push(stg(self.inlines(strong.body()))),
+            Expr::Emph(emph) => inlines.push(em(self.inlines(emph.body()))),
+            Expr::Raw(raw) => {
+                let code = raw.lines().map(|line| line.get().to_string()).join("\n");
+                inlines.push(Inline::CodeInline(CodeInline {
+                    programming_language: raw.lang().map(|lang| lang.get().to_string()),
+                    ..CodeInline::new(code.into())
+                }))
+            }
+            Expr::Link(link) => {
+                let url = link.get().to_string();
+                inlines.push(lnk([t(url.clone())], url))
+            }
+            Expr::Equation(equation) => inlines.push(mi(source(equation.body()), Some("typst"))),
+            Expr::FuncCall(call) => self.inline_call(call, inlines),
+            Expr::Str(string) => inlines.push_text(string.get().as_str()),
+            _ => {
+                self.losses.add("Typst code");
+            }
+        }
+    }
+
+    /// Decode a call to a function which produces an inline
+    fn inline_call(&mut self, call: ast::FuncCall, inlines: &mut Inlines) {
+        let Some(name) = call_name(call) else {
+            self.losses.add("Typst function call");
+            return;
+        };
+        let args = Args::new(call);
+
+        let content = |context: &mut Self| {
+            args.content()
+                .map(|markup| context.inlines(markup))
+                .unwrap_or_default()
+        };
+
+        let inline = match name.as_str() {
+            "emph" => em(content(self)),
+            "strong" => stg(content(self)),
+            "sub" => sub(content(self)),
+            "super" => sup(content(self)),
+            "underline" => u(content(self)),
+            "strike" => stk(content(self)),
+            "quote" => qi(content(self)),
+            "link" => {
+                let url = args.string().unwrap_or_default();
+                let mut content = content(self);
+                if content.is_empty() {
+                    content = vec![t(url.clone())];
+                }
+                lnk(content, url)
+            }
+            "image" => {
+                for (name, ..) in &args.named {
+                    self.losses.add(format!("Typst image `{name}` argument"));
+                }
+                img(args.string().unwrap_or_default())
+            }
+            "raw" => {
+                let lang = args.get("lang").and_then(|expr| match expr {
+                    Expr::Str(lang) => Some(lang.get().to_string()),
+                    _ => None,
+                });
+                Inline::CodeInline(CodeInline {
+                    programming_language: lang,
+                    ..CodeInline::new(args.string().unwrap_or_default().into())
+                })
+            }
+            "text" | "box" | "highlight" | "smallcaps" => {
+                for inline in content(self) {
+                    inlines.push(inline);
+                }
+                return;
+            }
+            _ => {
+                self.losses.add(format!("Typst `{name}` function call"));
+                return;
+            }
+        };
+
+        inlines.push(inline);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::schema::{
+        shortcuts::{art, cb, em, h1, h2, li, p, stg, t, ul},
+        ImageObject,
+    };
+
+    use super::*;
+
+    #[test]
+    fn markup() -> Result<()> {
+        let (node, ..) = decode(
+            r#"= Title
+
+Some _emphasized_ and *strong* text.
+
+== Section
+
+- One
+- Two
+
+```python
+x = 1
+```
+"#,
+            None,
+        )?;
+
+        assert_eq!(
+            node,
+            art([
+                h1([t("Title")]),
+                p([
+                    t("Some "),
+                    em([t("emphasized")]),
+                    t(" and "),
+                    stg([t("strong")]),
+                    t(" text.")
+                ]),
+                h2([t("Section")]),
+                ul([li([t("One")]), li([t("Two")])]),
+                cb("x = 1", Some("python")),
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn figure_with_image() -> Result<()> {
+        let (node, info) = decode(
+            r#"#figure(image("plot.png", width: 80%), caption: [A plot.], kind: "chart")"#,
+            None,
+        )?;
+
+        assert_eq!(
+            node,
+            art([Block::Figure(Figure {
+                caption: Some(vec![p([t("A plot.")])]),
+                ..Figure::new(vec![Block::ImageObject(ImageObject::new(
+                    "plot.png".into()
+                ))])
+            })])
+        );
+
+        let losses = info
+            .losses
+            .iter()
+            .map(|(label, ..)| label.as_str())
+            .collect_vec();
+        assert!(losses.contains(&"Typst image `width` argument"));
+        assert!(losses.contains(&"Typst figure `kind` argument"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use codec::{
+    common::{async_trait::async_trait, eyre::Result},
+    format::Format,
+    schema::{Article, Node},
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, NodeType,
+};
+use codec_typst_trait::{TypstCodec as _, TypstEncodeContext};
+
+mod decode;
+mod pdf;
+
+pub use pdf::to_pdf;
+
+/// A codec for Typst
+pub struct TypstCodec;
+
+#[async_trait]
+impl Codec for TypstCodec {
+    fn name(&self) -> &str {
+        "typst"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Typst => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Typst => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, _node_type: NodeType) -> CodecSupport {
+        CodecSupport::LowLoss
+    }
+
+    fn supports_to_type(&self, _node_type: NodeType) -> CodecSupport {
+        CodecSupport::LowLoss
+    }
+
+    async fn from_str(
+        &self,
+        str: &str,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        decode::decode(str, options)
+    }
+
+    async fn to_string(
+        &self,
+        node: &Node,
+        options: Option<EncodeOptions>,
+    ) -> Result<(String, EncodeInfo)> {
+        encode(node, options)
+    }
+}
+
+/// Encode a node to Typst
+///
+/// If the `standalone` option is set, and the node is an `Article`, a preamble
+/// with page settings and the title of the article is added.
+pub fn encode(node: &Node, options: Option<EncodeOptions>) -> Result<(String, EncodeInfo)> {
+    let options = options.unwrap_or_default();
+
+    let mut context = TypstEncodeContext::new(options.render.unwrap_or_default());
+
+    if let (Some(true), Node::Article(article)) = (options.standalone, node) {
+        preamble(article, &mut context);
+    }
+
+    node.to_typst(&mut context);
+
+    let mut output = context.content;
+    if output.ends_with("\n\n") {
+        output.pop();
+    }
+
+    let info = EncodeInfo {
+        losses: context.losses,
+        mapping: context.mapping,
+        ..Default::default()
+    };
+
+    Ok((output, info))
+}
+
+/// Encode the preamble of a standalone Typst document for an article
+fn preamble(article: &Article, context: &mut TypstEncodeContext) {
+    context
+        .str("#set page(numbering: \"1\")\n")
+        .str("#set par(justify: true)\n")
+        .str("#set heading(numbering: \"1.1\")\n\n");
+
+    if let Some(title) = &article.title {
+        context.str("#align(center, text(size: 1.6em, weight: \"bold\")[");
+        title.to_typst(context);
+        context.str("])\n\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::schema::shortcuts::{art, em, h1, mi, p, t};
+
+    use super::*;
+
+    #[test]
+    fn encode_article() -> Result<()> {
+        let article = art([
+            h1([t("Introduction")]),
+            p([
+                t("Some "),
+                em([t("emphasized")]),
+                t(" text with #hash and "),
+                mi("x^2", Some("typst")),
+                t(" and "),
+                mi(r"\frac{1}{\alpha}", Some("tex")),
+                t("."),
+            ]),
+        ]);
+
+        let (typst, ..) = encode(&article, None)?;
+        assert_eq!(
+            typst,
+            "= Introduction\n\nSome #emph[emphasized] text with \\#hash and $x^2$ and $frac(1, alpha)$.\n"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashMap, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use typst::{
+    diag::{FileError, FileResult},
+    foundations::{Bytes, Datetime},
+    syntax::{FileId, Source, VirtualPath},
+    text::{Font, FontBook},
+    utils::LazyHash,
+    Library, World,
+};
+
+use codec::{
+    common::{
+        chrono::{Datelike, FixedOffset, Local, Utc},
+        eyre::{bail, Result},
+        itertools::Itertools,
+        once_cell::sync::Lazy,
+        tokio, tracing,
+    },
+    schema::{
+        shortcuts::{lnk, p, t},
+        Block, ImageObject, Inline, Node, VisitorMut, WalkControl,
+    },
+    EncodeInfo, EncodeOptions, Losses,
+};
+
+use crate::encode;
+
+/// Encode a node to a PDF file using Typst
+///
+/// Encodes the node as a standalone Typst document and then compiles it
+/// to PDF in-process (i.e. without requiring Pandoc, a LaTeX distribution,
+/// or the Typst CLI to be installed). Images with `data:` URIs are made
+/// available to the compiler as virtual files. Remote images are not
+/// fetched and are instead encoded as links.
+pub async fn to_pdf(
+    node: &Node,
+    path: &Path,
+    options: Option<EncodeOptions>,
+) -> Result<EncodeInfo> {
+    let options = options.unwrap_or_default();
+
+    let mut node = node.clone();
+    let mut images = Images::default();
+    images.visit(&mut node);
+
+    let (typst, mut info) = encode(
+        &node,
+        Some(EncodeOptions {
+            standalone: Some(true),
+            render: Some(options.render.unwrap_or(true)),
+            ..options.clone()
+        }),
+    )?;
+    info.losses.merge(images.losses);
+
+    let root = options
+        .from_path
+        .as_ref()
+        .and_then(|path| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+
+    let world = Compiler {
+        main: Source::new(FileId::new(None, VirtualPath::new("/main.typ")), typst),
+        root,
+        files: images.files,
+    };
+
+    let pdf = tokio::task::spawn_blocking(move || world.pdf()).await??;
+    tokio::fs::write(path, pdf).await?;
+
+    Ok(info)
+}
+
+/// A visitor which collects images for making available to the Typst compiler
+#[derive(Default)]
+struct Images {
+    /// Image contents, keyed by the virtual path used in the Typst source
+    files: HashMap<String, Bytes>,
+
+    /// Losses for images which could not be made available
+    losses: Losses,
+}
+
+impl Images {
+    /// Decode a `data:` URI into a virtual file and return its path
+    fn data_uri(&mut self, url: &str) -> Option<String> {
+        let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+        if !header.ends_with(";base64") {
+            return None;
+        }
+
+        let bytes = STANDARD.decode(data).ok()?;
+        let ext = header
+            .trim_end_matches(";base64")
+            .split('/')
+            .last()
+            .map(|subtype| subtype.split('+').next().unwrap_or(subtype))
+            .unwrap_or("png");
+
+        let path = format!("/images/{}.{ext}", self.files.len());
+        self.files.insert(path.clone(), Bytes::from(bytes));
+
+        Some(path)
+    }
+}
+
+impl VisitorMut for Images {
+    fn visit_node(&mut self, node: &mut Node) -> WalkControl {
+        if let Node::ImageObject(image) = node {
+            self.image(image);
+        }
+        WalkControl::Continue
+    }
+
+    fn visit_block(&mut self, block: &mut Block) -> WalkControl {
+        if let Block::ImageObject(image) = block {
+            if let Some(url) = self.image(image) {
+                *block = p([lnk([t(url.clone())], url)]);
+            }
+        }
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &mut Inline) -> WalkControl {
+        if let Inline::ImageObject(image) = inline {
+            if let Some(url) = self.image(image) {
+                *inline = lnk([t(url.clone())], url);
+            }
+        }
+        WalkControl::Continue
+    }
+}
+
+impl Images {
+    /// Make an image available to the Typst compiler
+    ///
+    /// Images with `data:` URIs are replaced with a virtual file. Returns the URL
+    /// of remote images so that the caller can replace the image with a link.
+    fn image(&mut self, image: &mut ImageObject) -> Option<String> {
+        let url = &image.content_url;
+        if url.starts_with("data:") {
+            match self.data_uri(url) {
+                Some(path) => image.content_url = path,
+                None => self.losses.add("ImageObject.contentUrl"),
+            }
+            None
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            self.losses.add("ImageObject");
+            Some(url.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// Fonts bundled with Typst, loaded once
+static FONTS: Lazy<(LazyHash<FontBook>, Vec<Font>)> = Lazy::new(|| {
+    let fonts = typst_assets::fonts()
+        .flat_map(|data| Font::iter(Bytes::from_static(data)))
+        .collect_vec();
+    let book = FontBook::from_fonts(&fonts);
+    (LazyHash::new(book), fonts)
+});
+
+/// The standard library, created once
+static LIBRARY: Lazy<LazyHash<Library>> = Lazy::new(|| LazyHash::new(Library::default()));
+
+/// An implementation of a Typst [`World`] for compiling a single document
+struct Compiler {
+    /// The main source file
+    main: Source,
+
+    /// The directory that other files (e.g. images) are resolved relative to
+    root: std::path::PathBuf,
+
+    /// In-memory files e.g. decoded images
+    files: HashMap<String, Bytes>,
+}
+
+impl Compiler {
+    /// Compile the document to PDF
+    fn pdf(&self) -> Result<Vec<u8>> {
+        let result = typst::compile(self);
+
+        for warning in result.warnings {
+            tracing::debug!("Typst warning: {}", warning.message);
+        }
+
+        let document = match result.output {
+            Ok(document) => document,
+            Err(errors) => bail!(
+                "Typst compilation failed: {}",
+                errors.iter().map(|error| &error.message).join("; ")
+            ),
+        };
+
+        match typst_pdf::pdf(&document, &typst_pdf::PdfOptions::default()) {
+            Ok(pdf) => Ok(pdf),
+            Err(errors) => bail!(
+                "Typst PDF export failed: {}",
+                errors.iter().map(|error| &error.message).join("; ")
+            ),
+        }
+    }
+}
+
+impl World for Compiler {
+    fn library(&self) -> &LazyHash<Library> {
+        &LIBRARY
+    }
+
+    fn book(&self) -> &LazyHash<FontBook> {
+        &FONTS.0
+    }
+
+    fn main(&self) -> FileId {
+        self.main.id()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.main.id() {
+            return Ok(self.main.clone());
+        }
+
+        let path = id.vpath().as_rooted_path();
+        let text = std::fs::read_to_string(self.root.join(id.vpath().as_rootless_path()))
+            .map_err(|error| FileError::from_io(error, path))?;
+        Ok(Source::new(id, text))
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        let path = id.vpath().as_rooted_path();
+        if let Some(bytes) = self.files.get(&path.to_string_lossy().to_string()) {
+            return Ok(bytes.clone());
+        }
+
+        std::fs::read(self.root.join(id.vpath().as_rootless_path()))
+            .map(Bytes::from)
+            .map_err(|error| FileError::from_io(error, path))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        FONTS.1.get(index).cloned()
+    }
+
+    fn today(&self, offset: Option<i64>) -> Option<Datetime> {
+        let now = match offset {
+            Some(hours) => {
+                let offset = FixedOffset::east_opt((hours * 3600) as i32)?;
+                Utc::now().with_timezone(&offset).date_naive()
+            }
+            None => Local::now().date_naive(),
+        };
+        Datetime::from_ymd(now.year(), now.month() as u8, now.day() as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::tempfile,
+        schema::{
+            shortcuts::{art, fig, p, t},
+            Article, Figure,
+        },
+    };
+
+    use super::*;
+
+    /// A 1x1 pixel PNG
+    const PNG: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    #[test]
+    fn figure_block_images() {
+        let mut node = art([
+            fig([Block::ImageObject(ImageObject::new(PNG.into()))]),
+            fig([Block::ImageObject(ImageObject::new(
+                "https://example.org/image.png".into(),
+            ))]),
+            p([t("Text")]),
+        ]);
+
+        let mut images = Images::default();
+        images.visit(&mut node);
+
+        let Node::Article(Article { content, .. }) = node else {
+            unreachable!("should be an article")
+        };
+        let [Block::Figure(Figure { content: first, .. }), Block::Figure(Figure {
+            content: second, ..
+        }), ..] = content.as_slice()
+        else {
+            unreachable!("should have two figures")
+        };
+
+        assert!(
+            matches!(first.as_slice(), [Block::ImageObject(image)] if image.content_url == "/images/0.png")
+        );
+        assert!(images.files.contains_key("/images/0.png"));
+        assert!(matches!(second.as_slice(), [Block::Paragraph(..)]));
+        assert_eq!(
+            images.losses.iter().collect_vec(),
+            [(&"ImageObject".to_string(), &1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn figure_block_images_pdf() -> Result<()> {
+        let node = art([
+            fig([Block::ImageObject(ImageObject::new(PNG.into()))]),
+            fig([Block::ImageObject(ImageObject::new(
+                "https://example.org/image.png".into(),
+            ))]),
+        ]);
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("figures.pdf");
+        to_pdf(&node, &path, None).await?;
+        assert!(tokio::fs::read(&path).await?.starts_with(b"%PDF"));
+
+        Ok(())
+    }
+}
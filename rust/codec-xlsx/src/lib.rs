@@ -0,0 +1,321 @@
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, DataType, Reader};
+
+use codec::{
+    common::{
+        async_trait::async_trait,
+        eyre::{bail, eyre, Result},
+        itertools::Itertools,
+    },
+    format::Format,
+    schema::{Node, Null, Primitive},
+    status::Status,
+    Codec, CodecSupport, DecodeInfo, DecodeOptions, EncodeInfo, EncodeOptions, NodeType,
+};
+use codec_csv::{node_to_rows, rows_to_datatable, value_to_string, Selection};
+
+/// A codec for Microsoft Excel and OpenDocument spreadsheets
+pub struct XlsxCodec;
+
+#[async_trait]
+impl Codec for XlsxCodec {
+    fn name(&self) -> &str {
+        "xlsx"
+    }
+
+    fn status(&self) -> Status {
+        Status::UnderDevelopment
+    }
+
+    fn supports_from_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Xlsx | Format::Ods => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_format(&self, format: &Format) -> CodecSupport {
+        match format {
+            Format::Xlsx | Format::Ods => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Datatable => CodecSupport::LowLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_to_type(&self, node_type: NodeType) -> CodecSupport {
+        match node_type {
+            NodeType::Datatable => CodecSupport::LowLoss,
+            NodeType::Table => CodecSupport::HighLoss,
+            _ => CodecSupport::None,
+        }
+    }
+
+    fn supports_from_string(&self) -> bool {
+        false
+    }
+
+    fn supports_to_string(&self) -> bool {
+        false
+    }
+
+    async fn from_path(
+        &self,
+        path: &Path,
+        options: Option<DecodeOptions>,
+    ) -> Result<(Node, DecodeInfo)> {
+        let options = options.unwrap_or_default();
+
+        let mut workbook = open_workbook_auto(path)?;
+
+        let selection = options
+            .select
+            .as_deref()
+            .map(|select| Selection::parse(select, &workbook.sheet_names()))
+            .transpose()?
+            .unwrap_or_default();
+
+        let sheet = match selection.sheet {
+            Some(sheet) => sheet,
+            None => workbook
+                .sheet_names()
+                .first()
+                .cloned()
+                .ok_or_else(|| eyre!("Spreadsheet has no sheets"))?,
+        };
+        let range = workbook.worksheet_range(&sheet)?;
+
+        let offset = range
+            .start()
+            .map(|(row, col)| (row as usize, col as usize))
+            .unwrap_or_default();
+
+        let mut rows = range
+            .rows()
+            .map(|row| row.iter().map(data_to_value).collect_vec())
+            .collect_vec();
+
+        if let Some(range) = selection.range {
+            rows = range.apply(rows, offset);
+        }
+
+        let datatable = rows_to_datatable(rows);
+
+        Ok((Node::Datatable(datatable), DecodeInfo::none()))
+    }
+
+    async fn to_path(
+        &self,
+        node: &Node,
+        path: &Path,
+        options: Option<EncodeOptions>,
+    ) -> Result<EncodeInfo> {
+        let options = options.unwrap_or_default();
+
+        let (rows, losses) = node_to_rows(node)?;
+
+        let format = match options.format {
+            Some(format) => format,
+            None => Format::from_path(path),
+        };
+        match format {
+            Format::Xlsx => write_xlsx(rows, path)?,
+            Format::Ods => write_ods(rows, path)?,
+            _ => bail!("Unsupported format for spreadsheet: {format}"),
+        }
+
+        Ok(EncodeInfo {
+            losses,
+            ..Default::default()
+        })
+    }
+}
+
+/// Convert a spreadsheet cell into a [`Primitive`]
+///
+/// Floats that are whole numbers are converted to integers (spreadsheets
+/// do not distinguish between the two) and dates are converted to ISO 8601 strings.
+fn data_to_value(data: &Data) -> Primitive {
+    match data {
+        Data::Empty | Data::Error(..) => Primitive::Null(Null),
+        Data::Bool(value) => Primitive::Boolean(*value),
+        Data::Int(value) => Primitive::Integer(*value),
+        Data::Float(value) => {
+            if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+                Primitive::Integer(*value as i64)
+            } else {
+                Primitive::Number(*value)
+            }
+        }
+        Data::String(value) => codec_csv::parse_value(value),
+        Data::DateTime(..) => match data.as_datetime() {
+            Some(datetime) if datetime.time() == Default::default() => {
+                Primitive::String(datetime.date().format("%Y-%m-%d").to_string())
+            }
+            Some(datetime) => Primitive::String(datetime.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            None => Primitive::Null(Null),
+        },
+        Data::DateTimeIso(value) | Data::DurationIso(value) => Primitive::String(value.clone()),
+    }
+}
+
+/// Convert the row and column indices of a cell to those used by spreadsheet writers
+///
+/// Errors if the indices exceed those supported (rather than silently wrapping).
+fn cell_index(row: usize, col: usize) -> Result<(u32, u16)> {
+    let row = u32::try_from(row).map_err(|_| eyre!("Too many rows for spreadsheet: {row}"))?;
+    let col = u16::try_from(col).map_err(|_| eyre!("Too many columns for spreadsheet: {col}"))?;
+    Ok((row, col))
+}
+
+/// Write rows of values to an XLSX file
+fn write_xlsx(rows: Vec<Vec<Primitive>>, path: &Path) -> Result<()> {
+    let mut workbook = rust_xlsxwriter::Workbook::new();
+    let worksheet = workbook.add_worksheet();
+
+    for (row, values) in rows.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            let (row, col) = cell_index(row, col)?;
+            match value {
+                Primitive::Null(..) => continue,
+                Primitive::Boolean(value) => worksheet.write_boolean(row, col, *value)?,
+                Primitive::Integer(value) => worksheet.write_number(row, col, *value as f64)?,
+                Primitive::UnsignedInteger(value) => {
+                    worksheet.write_number(row, col, *value as f64)?
+                }
+                Primitive::Number(value) => worksheet.write_number(row, col, *value)?,
+                _ => worksheet.write_string(row, col, value_to_string(value))?,
+            };
+        }
+    }
+
+    workbook.save(path)?;
+
+    Ok(())
+}
+
+/// Write rows of values to an ODS file
+fn write_ods(rows: Vec<Vec<Primitive>>, path: &Path) -> Result<()> {
+    let mut workbook = spreadsheet_ods::WorkBook::new_empty();
+    let mut sheet = spreadsheet_ods::Sheet::new("Sheet1");
+
+    for (row, values) in rows.iter().enumerate() {
+        for (col, value) in values.iter().enumerate() {
+            let (row, col) = cell_index(row, col)?;
+            let col = u32::from(col);
+            match value {
+                Primitive::Null(..) => continue,
+                Primitive::Boolean(value) => sheet.set_value(row, col, *value),
+                Primitive::Integer(value) => sheet.set_value(row, col, *value as f64),
+                Primitive::UnsignedInteger(value) => sheet.set_value(row, col, *value as f64),
+                Primitive::Number(value) => sheet.set_value(row, col, *value),
+                _ => sheet.set_value(row, col, value_to_string(value)),
+            };
+        }
+    }
+
+    workbook.push_sheet(sheet);
+    spreadsheet_ods::write_ods(&mut workbook, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use codec::{
+        common::{tempfile::tempdir, tokio},
+        schema::Datatable,
+    };
+    use codec_csv::parse_value;
+
+    use super::*;
+
+    /// Create rows of values from CSV-like strings
+    fn rows(rows: &[&str]) -> Vec<Vec<Primitive>> {
+        rows.iter()
+            .map(|row| row.split(',').map(parse_value).collect())
+            .collect()
+    }
+
+    /// Decode a spreadsheet into a datatable
+    async fn decode(path: &Path, select: Option<&str>) -> Result<Datatable> {
+        let (node, ..) = XlsxCodec
+            .from_path(
+                path,
+                Some(DecodeOptions {
+                    select: select.map(String::from),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        match node {
+            Node::Datatable(datatable) => Ok(datatable),
+            _ => bail!("Expected a datatable"),
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trip() -> Result<()> {
+        let dir = tempdir()?;
+
+        let datatable = rows_to_datatable(rows(&[
+            "name,count,ratio,flag",
+            "a,1,1.5,true",
+            "b,,2.25,false",
+            "c,3,,true",
+        ]));
+
+        for ext in ["xlsx", "ods"] {
+            let path = dir.path().join(format!("data.{ext}"));
+            XlsxCodec
+                .to_path(&Node::Datatable(datatable.clone()), &path, None)
+                .await?;
+
+            let decoded = decode(&path, None).await?;
+            assert_eq!(decoded.columns, datatable.columns, "round trip for {ext}");
+
+            let hint = decoded.hint();
+            assert_eq!(hint.rows, 3);
+            assert_eq!(
+                hint.columns
+                    .iter()
+                    .map(|column| (column.item_type.as_str(), column.nulls))
+                    .collect_vec(),
+                vec![
+                    ("String", Some(0)),
+                    ("Integer", Some(1)),
+                    ("Number", Some(1)),
+                    ("Boolean", Some(0))
+                ],
+                "hint for {ext}"
+            );
+            assert_eq!(hint.columns[1].maximum, Some(Primitive::Integer(3)));
+
+            // Selecting the sheet by a name that looks like a cell reference
+            let decoded = decode(&path, Some("Sheet1")).await?;
+            assert_eq!(decoded.columns, datatable.columns, "sheet select for {ext}");
+
+            // Selecting a range of cells
+            let decoded = decode(&path, Some("A1:B3")).await?;
+            assert_eq!(
+                decoded.columns,
+                rows_to_datatable(rows(&["name,count", "a,1", "b,"])).columns,
+                "range select for {ext}"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn cell_indices() {
+        assert!(cell_index(1, 2).is_ok());
+        assert!(cell_index(1, u16::MAX as usize + 1).is_err());
+    }
+}
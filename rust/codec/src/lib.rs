@@ -405,6 +405,12 @@ pub struct DecodeOptions {
 
     /// Arguments to passthrough to CLI tools delegated to for decoding (e.g. Pandoc)
     pub passthrough_args: Vec<String>,
+
+    /// A selector for the part of the content to decode
+    ///
+    /// Only supported by some codecs. For example, spreadsheet codecs accept
+    /// a sheet name and/or a range of cells e.g. `Sheet1!A1:D20`.
+    pub select: Option<String>,
 }
 
 /// Encoding options
@@ -495,4 +501,10 @@ pub struct EncodeOptions {
 
     /// Arguments to passthrough to CLI tools delegated to for encoding (e.g. Pandoc)
     pub passthrough_args: Vec<String>,
+
+    /// The engine to use when encoding to PDF
+    ///
+    /// If not specified, Pandoc and its default PDF engine (LaTeX) are used.
+    /// Use `typst` for the built-in Typst engine.
+    pub pdf_engine: Option<String>,
 }
@@ -20,11 +20,17 @@ pub mod cli;
 /// Get a list of all codecs
 pub fn list() -> Vec<Box<dyn Codec>> {
     let codecs = vec![
-        Box::new(codec_cbor::CborCodec) as Box<dyn Codec>,
+        Box::new(codec_asciidoc::AsciiDocCodec) as Box<dyn Codec>,
+        Box::new(codec_bibtex::BibtexCodec),
+        Box::new(codec_cbor::CborCodec),
+        Box::new(codec_csl::CslCodec),
+        Box::new(codec_csv::CsvCodec),
         Box::new(codec_debug::DebugCodec),
         Box::new(codec_docx::DocxCodec),
+        Box::new(codec_doi::DoiCodec),
         Box::new(codec_dom::DomCodec),
         Box::new(codec_directory::DirectoryCodec),
+        Box::new(codec_epub::EpubCodec),
         Box::new(codec_html::HtmlCodec),
         Box::new(codec_ipynb::IpynbCodec),
         Box::new(codec_jats::JatsCodec),
@@ -34,11 +40,17 @@ pub fn list() -> Vec<Box<dyn Codec>> {
         Box::new(codec_latex::LatexCodec),
         Box::new(codec_lexical::LexicalCodec),
         Box::new(codec_markdown::MarkdownCodec),
+        Box::new(codec_meca::MecaCodec),
         Box::new(codec_odt::OdtCodec),
+        Box::new(codec_org::OrgCodec),
         Box::new(codec_pandoc::PandocCodec),
         Box::new(codec_pdf::PdfCodec),
+        Box::new(codec_ris::RisCodec),
+        Box::new(codec_rst::RstCodec),
         Box::<codec_swb::SwbCodec>::default(),
         Box::new(codec_text::TextCodec),
+        Box::new(codec_typst::TypstCodec),
+        Box::new(codec_xlsx::XlsxCodec),
         Box::new(codec_yaml::YamlCodec),
     ];
 
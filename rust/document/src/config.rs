@@ -1,18 +1,256 @@
-use common::eyre::Result;
-use schema::{Config, Node};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
 
-use crate::Document;
+use common::{
+    eyre::{bail, eyre, Result},
+    indexmap::IndexMap,
+    serde::Serialize,
+    serde_json::{self, Map, Value},
+    serde_yaml,
+    tokio::fs::read_to_string,
+    toml,
+};
+use node_execute::ExecuteOptions;
+use schema::{Article, Config, Node};
+
+use crate::{Document, DocumentKernels, DocumentRoot};
+
+/// The names of configuration files
+///
+/// If there is more than one of these in a directory, the first is used.
+const CONFIG_FILES: [&str; 3] = ["stencila.yaml", "stencila.yml", "stencila.toml"];
+
+/// The source of a resolved configuration value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(
+    rename_all = "lowercase",
+    tag = "type",
+    content = "path",
+    crate = "common::serde"
+)]
+pub enum ConfigSource {
+    /// A configuration file in the Stencila config directory
+    User(PathBuf),
+
+    /// A configuration file in the document's directory, or one of its ancestors
+    Workspace(PathBuf),
+
+    /// The configuration in the document itself (e.g. in front-matter)
+    Document,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::User(path) => write!(f, "user {}", path.display()),
+            ConfigSource::Workspace(path) => write!(f, "workspace {}", path.display()),
+            ConfigSource::Document => write!(f, "document"),
+        }
+    }
+}
+
+/// The source of each property of a resolved [`Config`]
+pub type ConfigSources = IndexMap<String, ConfigSource>;
 
 impl Document {
     /// Get a resolved [`Config`] for the document
     pub async fn config(&self) -> Result<Config> {
-        // TODO: walk up from the document to find any ancestor
-        // configuration files
-        // See https://github.com/stencila/stencila/issues/2387
+        Ok(self.config_resolved().await?.0)
+    }
+
+    /// Get a resolved [`Config`] for the document, and the source of each of its values
+    pub async fn config_resolved(&self) -> Result<(Config, ConfigSources)> {
         let root = &*self.root.read().await;
-        match root {
-            Node::Article(article) => Ok(article.config.clone().unwrap_or_default()),
-            _ => Ok(Config::default()),
+        resolve_config(self.directory(), Some(root)).await
+    }
+}
+
+/// Resolve the [`Config`] for a document in a directory
+///
+/// Configuration is merged from these sources, in increasing order of precedence:
+///
+/// - the user config file in the Stencila config directory (e.g. `~/.config/stencila/stencila.yaml`)
+///
+/// - workspace config files in `dir` and its ancestors, with those closest to `dir` taking precedence
+///
+/// - the `config` of the `root` node of the document (e.g. from YAML front-matter)
+///
+/// Merging is done property by property: a property in a source with higher
+/// precedence replaces the entire value of that property in sources of lower precedence.
+pub async fn resolve_config(dir: &Path, root: Option<&Node>) -> Result<(Config, ConfigSources)> {
+    let mut layers = Vec::new();
+
+    if let Some(path) = user_config_file() {
+        let value = read_config_file(&path).await?;
+        layers.push((ConfigSource::User(path), value));
+    }
+
+    for path in workspace_config_files(dir) {
+        let value = read_config_file(&path).await?;
+        layers.push((ConfigSource::Workspace(path), value));
+    }
+
+    if let Some(Node::Article(Article {
+        config: Some(config),
+        ..
+    })) = root
+    {
+        layers.push((ConfigSource::Document, serde_json::to_value(config)?));
+    }
+
+    let mut merged = Map::new();
+    let mut sources = ConfigSources::new();
+    for (source, value) in layers {
+        let Value::Object(object) = value else {
+            continue;
+        };
+        for (property, value) in object {
+            if property == "type" || value.is_null() {
+                continue;
+            }
+            merged.insert(property.clone(), value);
+            sources.insert(property, source.clone());
         }
     }
+    merged.insert("type".into(), Value::String("Config".into()));
+
+    let config = serde_json::from_value(Value::Object(merged))?;
+
+    Ok((config, sources))
+}
+
+/// Apply the resolved config for a document to execution options and kernels
+///
+/// Options that have been set explicitly (e.g. on the command line) take
+/// precedence over those in the config.
+pub(crate) async fn apply_config(
+    dir: &Path,
+    root: &DocumentRoot,
+    kernels: &DocumentKernels,
+    options: &mut ExecuteOptions,
+) -> Result<()> {
+    let (config, ..) = {
+        let root = &*root.read().await;
+        resolve_config(dir, Some(root)).await?
+    };
+
+    if options.models.is_none() {
+        options.models = config.models;
+    }
+    if options.execution_bounds.is_none() {
+        options.execution_bounds = config.execution_bounds;
+    }
+    if options.budget.is_none() {
+        options.budget = config.budget;
+    }
+
+    // Always set (or reset) the preferred kernels so that a preference removed
+    // from the config no longer applies
+    kernels
+        .write()
+        .await
+        .set_preferred(config.kernels.unwrap_or_default());
+
+    Ok(())
+}
+
+/// Get the path of the user config file, if any
+fn user_config_file() -> Option<PathBuf> {
+    let dir = app::get_app_dir(app::DirType::Config, false).ok()?;
+    config_file_in(&dir)
+}
+
+/// Get the paths of workspace config files in a directory and its ancestors
+///
+/// Returned in order of increasing precedence (i.e. furthest from `dir` first).
+fn workspace_config_files(dir: &Path) -> Vec<PathBuf> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+    let mut files: Vec<PathBuf> = dir.ancestors().filter_map(config_file_in).collect();
+    files.reverse();
+    files
+}
+
+/// Get the config file in a directory, if any
+fn config_file_in(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Read a config file and normalize it to the JSON representation of a [`Config`]
+///
+/// Deserializing into a [`Config`] and back ensures that any aliases used in the
+/// file (e.g. `execution-bounds`, `model`) are mapped to canonical property names.
+async fn read_config_file(path: &Path) -> Result<Value> {
+    let content = read_to_string(path).await?;
+
+    let value: Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&content)?,
+        _ => serde_yaml::from_str(&content)?,
+    };
+
+    let mut object = match value {
+        Value::Object(object) => object,
+        Value::Null => Map::new(),
+        _ => bail!("Config file `{}` is not a mapping", path.display()),
+    };
+    object.insert("type".into(), Value::String("Config".into()));
+
+    let config: Config = serde_json::from_value(Value::Object(object))
+        .map_err(|error| eyre!("Invalid config file `{}`: {error}", path.display()))?;
+
+    Ok(serde_json::to_value(config)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{tempfile::tempdir, tokio};
+    use common_dev::pretty_assertions::assert_eq;
+    use schema::ExecutionBounds;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn merges_workspace_and_document() -> Result<()> {
+        let temp = tempdir()?;
+        let outer = temp.path().canonicalize()?;
+        let inner = outer.join("inner");
+        std::fs::create_dir_all(&inner)?;
+
+        std::fs::write(
+            outer.join("stencila.yaml"),
+            "theme: outer\nmodels: gpt, claude\nexecution-bounds: Fork\n",
+        )?;
+        std::fs::write(inner.join("stencila.toml"), "theme = \"inner\"\n")?;
+
+        let root = Node::Article(Article {
+            config: Some(Config {
+                models: Some(vec!["mistral".into()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        let (config, sources) = resolve_config(&inner, Some(&root)).await?;
+
+        assert_eq!(config.theme.as_deref(), Some("inner"));
+        assert_eq!(config.models, Some(vec!["mistral".to_string()]));
+        assert_eq!(config.execution_bounds, Some(ExecutionBounds::Fork));
+
+        assert_eq!(
+            sources.get("theme"),
+            Some(&ConfigSource::Workspace(inner.join("stencila.toml")))
+        );
+        assert_eq!(sources.get("models"), Some(&ConfigSource::Document));
+        assert_eq!(
+            sources.get("executionBounds"),
+            Some(&ConfigSource::Workspace(outer.join("stencila.yaml")))
+        );
+
+        Ok(())
+    }
 }
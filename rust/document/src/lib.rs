@@ -1,6 +1,7 @@
 #![recursion_limit = "256"]
 
 use std::{
+    collections::BTreeMap,
     io,
     path::{Path, PathBuf},
     sync::{atomic::AtomicU64, Arc},
@@ -29,6 +30,7 @@ use schema::{
 };
 
 mod config;
+mod schedule;
 mod sync_directory;
 mod sync_dom;
 mod sync_file;
@@ -39,6 +41,8 @@ mod task_update;
 
 // Re-exports for convenience of consuming crates
 pub use codecs::{self, DecodeOptions, EncodeOptions, Format, LossesResponse};
+pub use config::{resolve_config, ConfigSource, ConfigSources};
+pub use schedule::ScheduleOptions;
 pub use schema;
 pub use sync_dom::DomPatch;
 
@@ -181,6 +185,11 @@ impl CommandNodes {
     pub fn new(node_ids: Vec<NodeId>, scope: CommandScope) -> Self {
         Self { node_ids, scope }
     }
+
+    /// Get the ids of the nodes involved in the command
+    pub fn node_ids(&self) -> &[NodeId] {
+        &self.node_ids
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
@@ -201,7 +210,7 @@ pub enum CommandScope {
 }
 
 /// The status of a command
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub enum CommandStatus {
     Ignored,
     Waiting,
@@ -290,14 +299,53 @@ type DocumentUpdateReceiver = mpsc::Receiver<Update>;
 type DocumentPatchSender = mpsc::UnboundedSender<Patch>;
 type DocumentPatchReceiver = mpsc::UnboundedReceiver<Patch>;
 
+type DocumentPatchesSender = broadcast::Sender<Patch>;
+type DocumentPatchesReceiver = broadcast::Receiver<Patch>;
+
 type DocumentCommandCounter = AtomicU64;
 
 type DocumentCommandSender = mpsc::Sender<(Command, u64)>;
 type DocumentCommandReceiver = mpsc::Receiver<(Command, u64)>;
 
-type DocumentCommandStatusSender = broadcast::Sender<(u64, CommandStatus)>;
 type DocumentCommandStatusReceiver = broadcast::Receiver<(u64, CommandStatus)>;
 
+type DocumentCommandStatuses = Arc<std::sync::Mutex<BTreeMap<u64, CommandStatus>>>;
+
+/// The maximum number of commands for which the latest status is retained
+const COMMAND_STATUSES_MAX: usize = 1024;
+
+/// A channel sender for notifying subscribers of command statuses
+///
+/// Also records the latest status of each command so that subscribers which
+/// lag behind, and so miss statuses, can get the current status of a command.
+#[derive(Clone)]
+struct DocumentCommandStatusSender {
+    sender: broadcast::Sender<(u64, CommandStatus)>,
+    statuses: DocumentCommandStatuses,
+}
+
+impl DocumentCommandStatusSender {
+    /// Record and send the status of a command
+    ///
+    /// The status of commands with id zero (those which are not waited for) is not recorded.
+    fn send(
+        &self,
+        message: (u64, CommandStatus),
+    ) -> Result<usize, broadcast::error::SendError<(u64, CommandStatus)>> {
+        let (id, status) = &message;
+        if *id != 0 {
+            if let Ok(mut statuses) = self.statuses.lock() {
+                statuses.insert(*id, status.clone());
+                while statuses.len() > COMMAND_STATUSES_MAX {
+                    statuses.pop_first();
+                }
+            }
+        }
+
+        self.sender.send(message)
+    }
+}
+
 /// A document
 #[allow(unused)]
 #[derive(Debug)]
@@ -326,6 +374,9 @@ pub struct Document {
     /// A channel sender for sending patches to the root [`Node`]
     patch_sender: DocumentPatchSender,
 
+    /// A channel sender for notifying subscribers of patches applied to the root [`Node`]
+    patches_sender: DocumentPatchesSender,
+
     /// A counter of commands used for creating unique command ids
     command_counter: DocumentCommandCounter,
 
@@ -334,6 +385,9 @@ pub struct Document {
 
     /// A channel for receiving notifications of command status
     command_status_receiver: DocumentCommandStatusReceiver,
+
+    /// The latest status of recent commands
+    command_statuses: DocumentCommandStatuses,
 }
 
 impl Document {
@@ -409,12 +463,19 @@ impl Document {
 
         let (update_sender, update_receiver) = mpsc::channel(8);
         let (patch_sender, patch_receiver) = mpsc::unbounded_channel();
+        let (patches_sender, ..) = broadcast::channel(1024);
         let (command_sender, command_receiver) = mpsc::channel(256);
         let (command_status_sender, command_status_receiver) = broadcast::channel(256);
+        let command_statuses = DocumentCommandStatuses::default();
+        let command_status_sender = DocumentCommandStatusSender {
+            sender: command_status_sender,
+            statuses: command_statuses.clone(),
+        };
 
         // Start the update task
         {
             let root = root.clone();
+            let patches_sender = patches_sender.clone();
             let command_sender = command_sender.clone();
             tokio::spawn(async move {
                 Self::update_task(
@@ -422,6 +483,7 @@ impl Document {
                     patch_receiver,
                     root,
                     watch_sender,
+                    patches_sender,
                     command_sender,
                 )
                 .await
@@ -461,9 +523,11 @@ impl Document {
             watch_receiver,
             update_sender,
             patch_sender,
+            patches_sender,
             command_counter,
             command_sender,
             command_status_receiver,
+            command_statuses,
         })
     }
 
@@ -756,6 +820,15 @@ impl Document {
         self.watch_receiver.clone()
     }
 
+    /// Subscribe to patches applied to the document's root node
+    ///
+    /// Unlike [`Document::watch`], which provides the entire root node after each
+    /// change, this provides each [`Patch`] (e.g. updates to the `executionStatus`
+    /// of nodes) as it is applied.
+    pub fn subscribe_patches(&self) -> DocumentPatchesReceiver {
+        self.patches_sender.subscribe()
+    }
+
     /// Update the root node of the document with a new node value
     pub async fn update(
         &self,
@@ -804,6 +877,18 @@ impl Document {
         Ok((command_id, status_receiver))
     }
 
+    /// Get the latest status of a command sent using [`Document::command_subscribe`]
+    ///
+    /// Intended for subscribers which lag behind on the status receiver and so may
+    /// have missed the statuses of the command, including when it finished.
+    /// Returns `None` if the command has not started or is no longer recent.
+    pub fn command_status(&self, command_id: u64) -> Option<CommandStatus> {
+        self.command_statuses
+            .lock()
+            .ok()
+            .and_then(|statuses| statuses.get(&command_id).cloned())
+    }
+
     /// Send a command to the document and wait for it to complete
     #[tracing::instrument(skip(self))]
     pub async fn command_wait(&self, command: Command) -> Result<()> {
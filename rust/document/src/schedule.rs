@@ -0,0 +1,320 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use croner::Cron;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use codecs::EncodeOptions;
+use common::{
+    chrono::Utc,
+    eyre::{bail, eyre, Result},
+    tokio::{self, sync::mpsc, time},
+    tracing,
+};
+use graph::Graph;
+use node_execute::ExecuteOptions;
+
+use crate::{CommandWait, Document};
+
+/// Options for re-executing a document on a schedule and/or when files change
+#[derive(Debug, Default, Clone)]
+pub struct ScheduleOptions {
+    /// A cron expression for when the document should be re-executed
+    ///
+    /// Supports standard five field expressions (e.g. `0 * * * *` for
+    /// hourly) and six field expressions with a leading seconds field.
+    pub cron: Option<String>,
+
+    /// Whether to re-execute the document when any of the files that it
+    /// depends on change
+    ///
+    /// The files are those that nodes in the document's execution dependency
+    /// graph depend upon: the sources of include and call blocks, files declared
+    /// using `@reads` and `@watches` tags in code, and any other `File` dependencies
+    /// recorded on nodes. Files written by the document are excluded.
+    pub dependencies: bool,
+
+    /// Additional files or directories to watch for changes
+    pub paths: Vec<PathBuf>,
+
+    /// Options for each execution of the document
+    ///
+    /// Note that `force_all` is always set so that nodes are re-executed
+    /// even when they are not stale.
+    pub execute_options: ExecuteOptions,
+
+    /// The paths to export the document to, and the options for each, after each execution
+    pub outputs: Vec<(PathBuf, Option<EncodeOptions>)>,
+}
+
+impl Document {
+    /// Re-execute the document on a schedule and/or when files change
+    ///
+    /// After each execution the document is exported to each of the `outputs`.
+    /// Changes to the outputs, and any other changes made while executing and
+    /// exporting, do not trigger another execution.
+    ///
+    /// This function does not return unless there is an error in the options,
+    /// so will usually be spawned, or selected against a shutdown signal.
+    #[tracing::instrument(skip(self))]
+    pub async fn schedule(&self, options: ScheduleOptions) -> Result<()> {
+        const DEBOUNCE_DELAY_MILLIS: u64 = 300;
+
+        let cron = options
+            .cron
+            .as_deref()
+            .map(|expr| {
+                Cron::new(expr)
+                    .with_seconds_optional()
+                    .parse()
+                    .map_err(|error| eyre!("Invalid cron expression `{expr}`: {error}"))
+            })
+            .transpose()?;
+
+        if cron.is_none() && !options.dependencies && options.paths.is_empty() {
+            bail!("Nothing to schedule on: specify a cron expression and/or files to watch")
+        }
+
+        // A watcher that forwards the paths of changed files to the async loop below
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut watcher: RecommendedWatcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) => {
+                    if matches!(
+                        event.kind,
+                        EventKind::Create(..) | EventKind::Modify(..) | EventKind::Remove(..)
+                    ) {
+                        for path in event.paths {
+                            if let Err(error) = sender.send(path) {
+                                tracing::error!("While forwarding file watching event: {error}");
+                            }
+                        }
+                    }
+                }
+                Err(error) => tracing::error!("While watching files: {error}"),
+            })?;
+        let outputs: HashSet<PathBuf> = options
+            .outputs
+            .iter()
+            .map(|(path, ..)| absolute(path))
+            .collect();
+        let mut watched: HashMap<PathBuf, RecursiveMode> = HashMap::new();
+
+        loop {
+            // Update the set of watched files since the document's dependencies
+            // may have changed with the last execution, and files that did not
+            // exist may have been created
+            let mut targets: HashSet<PathBuf> =
+                options.paths.iter().map(|path| absolute(path)).collect();
+            if options.dependencies {
+                targets.extend(
+                    self.dependency_files()
+                        .await
+                        .iter()
+                        .map(|path| absolute(path)),
+                );
+            }
+            targets.retain(|path| !outputs.contains(path));
+
+            let roots = watch_roots(&targets);
+            for (path, mode) in &watched {
+                if roots.get(path) != Some(mode) {
+                    if let Err(error) = watcher.unwatch(path) {
+                        tracing::debug!("While unwatching `{}`: {error}", path.display());
+                    }
+                }
+            }
+            for (path, mode) in &roots {
+                if watched.get(path) != Some(mode) {
+                    if let Err(error) = watcher.watch(path, *mode) {
+                        tracing::warn!("Unable to watch `{}`: {error}", path.display());
+                    }
+                }
+            }
+            watched = roots;
+
+            // Determine how long until the next scheduled execution, if any
+            let delay = cron.as_ref().and_then(|cron| {
+                let now = Utc::now();
+                let next = cron.find_next_occurrence(&now, false).ok()?;
+                (next - now).to_std().ok()
+            });
+
+            let trigger = tokio::select! {
+                () = time::sleep(delay.unwrap_or_default()), if delay.is_some() => {
+                    "scheduled time".to_string()
+                }
+                Some(path) = receiver.recv() => {
+                    // Changes to other files in a watched directory (e.g. siblings of a
+                    // file that does not exist yet) are ignored but watches are updated
+                    if !is_relevant(&path, &targets, &outputs) {
+                        continue;
+                    }
+
+                    // Debounce to avoid re-executing for each of a rapid series of changes
+                    debounce(&mut receiver, DEBOUNCE_DELAY_MILLIS).await;
+                    format!("change to `{}`", path.display())
+                }
+            };
+
+            tracing::info!("Executing document after {trigger}");
+
+            let execute_options = ExecuteOptions {
+                force_all: true,
+                ..options.execute_options.clone()
+            };
+            if let Err(error) = self.execute(execute_options, CommandWait::Yes).await {
+                tracing::error!("While executing document: {error}");
+            } else {
+                for (path, encode_options) in &options.outputs {
+                    match self.export(path, encode_options.clone()).await {
+                        Ok(..) => tracing::info!("Exported document to `{}`", path.display()),
+                        Err(error) => {
+                            tracing::error!("While exporting to `{}`: {error}", path.display())
+                        }
+                    }
+                }
+            }
+
+            // Discard changes made while executing and exporting (e.g. files written
+            // by code chunks) so that they do not cause the document to be re-executed
+            debounce(&mut receiver, DEBOUNCE_DELAY_MILLIS).await;
+        }
+    }
+
+    /// Get the paths of the files that the document depends upon
+    ///
+    /// Relative paths are resolved against the directory of the document.
+    pub async fn dependency_files(&self) -> Vec<PathBuf> {
+        let graph = {
+            let root = &*self.root.read().await;
+            Graph::from_node(root)
+        };
+
+        let dir = self.directory();
+        graph
+            .dependency_files()
+            .into_iter()
+            .map(|path| dir.join(path))
+            .collect()
+    }
+}
+
+/// Receive paths until none have been received for `millis` milliseconds
+async fn debounce(receiver: &mut mpsc::UnboundedReceiver<PathBuf>, millis: u64) {
+    while let Ok(Some(..)) = time::timeout(Duration::from_millis(millis), receiver.recv()).await {}
+}
+
+/// Make a path absolute so that it can be compared to the paths in file watching events
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Get the paths to watch, and how, for a set of target paths
+///
+/// Existing paths are watched recursively (so that changes to files in directories
+/// are detected). Paths that do not exist can not be watched, so their nearest
+/// existing ancestor is watched non-recursively instead (so that their creation
+/// is detected).
+fn watch_roots(targets: &HashSet<PathBuf>) -> HashMap<PathBuf, RecursiveMode> {
+    let mut roots = HashMap::new();
+    for path in targets {
+        let (root, mode) = if path.exists() {
+            (path.as_path(), RecursiveMode::Recursive)
+        } else if let Some(ancestor) = path.ancestors().skip(1).find(|path| path.exists()) {
+            (ancestor, RecursiveMode::NonRecursive)
+        } else {
+            continue;
+        };
+
+        let current = roots.entry(root.to_path_buf()).or_insert(mode);
+        if mode == RecursiveMode::Recursive {
+            *current = mode;
+        }
+    }
+    roots
+}
+
+/// Whether a change to a path should trigger execution
+///
+/// Changes to one of the `targets`, or a file within one of them, are
+/// relevant unless the path is one of the `outputs`.
+fn is_relevant(path: &Path, targets: &HashSet<PathBuf>, outputs: &HashSet<PathBuf>) -> bool {
+    !outputs.contains(path) && targets.iter().any(|target| path.starts_with(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{eyre::Result, tempfile::tempdir};
+
+    use super::*;
+
+    #[test]
+    fn watch_roots_of_missing_paths() -> Result<()> {
+        let dir = tempdir()?;
+        let dir = dir.path();
+        std::fs::create_dir(dir.join("data"))?;
+
+        let targets = HashSet::from([
+            dir.join("data"),
+            dir.join("data/new.csv"),
+            dir.join("missing/new.csv"),
+        ]);
+        let roots = watch_roots(&targets);
+
+        // Existing paths are watched recursively, even if also the
+        // nearest ancestor of a missing path
+        assert_eq!(
+            roots.get(&dir.join("data")),
+            Some(&RecursiveMode::Recursive)
+        );
+
+        // The nearest existing ancestor of a missing path is watched
+        assert_eq!(roots.get(dir), Some(&RecursiveMode::NonRecursive));
+        assert_eq!(roots.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn relevant_changes() {
+        let targets = HashSet::from([PathBuf::from("/docs/data"), PathBuf::from("/docs/a.csv")]);
+        let outputs = HashSet::from([PathBuf::from("/docs/data/report.html")]);
+
+        assert!(is_relevant(Path::new("/docs/a.csv"), &targets, &outputs));
+        assert!(is_relevant(
+            Path::new("/docs/data/b.csv"),
+            &targets,
+            &outputs
+        ));
+
+        // Siblings of targets, and outputs, are not relevant
+        assert!(!is_relevant(Path::new("/docs/b.csv"), &targets, &outputs));
+        assert!(!is_relevant(
+            Path::new("/docs/data/report.html"),
+            &targets,
+            &outputs
+        ));
+    }
+
+    #[tokio::test]
+    async fn dependency_files_from_tags() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("doc.md");
+        std::fs::write(
+            &path,
+            "```python exec\n# @reads data.csv\n# @writes plot.png\nprint(1)\n```\n",
+        )?;
+
+        let doc = Document::open(&path).await?;
+        doc.compile(CommandWait::Yes).await?;
+
+        let files = doc.dependency_files().await;
+        assert_eq!(files, vec![dir.path().join("data.csv")]);
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use codecs::{to_path, DecodeOptions, EncodeOptions, LossesResponse};
 use common::{
@@ -15,9 +15,9 @@ use schema::{
 };
 
 use crate::{
-    Command, CommandNodes, CommandStatus, ContentType, Document, DocumentCommandReceiver,
-    DocumentCommandStatusSender, DocumentKernels, DocumentPatchSender, DocumentRoot,
-    SaveDocumentSidecar, SaveDocumentSource,
+    config::apply_config, Command, CommandNodes, CommandStatus, ContentType, Document,
+    DocumentCommandReceiver, DocumentCommandStatusSender, DocumentKernels, DocumentPatchSender,
+    DocumentRoot, SaveDocumentSidecar, SaveDocumentSource,
 };
 
 impl Document {
@@ -210,10 +210,18 @@ impl Document {
                     });
                     current_command_details = Some((command, command_id, task));
                 }
-                ExecuteDocument(options) => {
+                ExecuteDocument(mut options) => {
+                    send_status(&status_sender, command_id, CommandStatus::Running);
+
                     let status_sender = status_sender.clone();
                     let task = tokio::spawn(async move {
+                        let dir = config_dir(&home, &path);
+                        options.document_path = path;
                         let status = if let Err(error) =
+                            apply_config(&dir, &root, &kernels, &mut options).await
+                        {
+                            CommandStatus::Failed(format!("While resolving config: {error}"))
+                        } else if let Err(error) =
                             execute(home, root, kernels, Some(patch_sender), None, Some(options))
                                 .await
                         {
@@ -228,7 +236,7 @@ impl Document {
 
                 ExecuteNodes(..) | PatchExecuteNodes(..) | PatchExecuteChat { .. } => {
                     // Extract or generate the patch if necessary
-                    let (patch, node_ids, options) = match command {
+                    let (patch, node_ids, mut options) = match command {
                         ExecuteNodes((nodes, options)) => (None, nodes.node_ids, options),
                         PatchExecuteNodes((patch, nodes, options)) => {
                             (Some(patch), nodes.node_ids, options)
@@ -273,21 +281,31 @@ impl Document {
                     }
 
                     // Execute the node/s
+                    send_status(&status_sender, command_id, CommandStatus::Running);
+
                     let status_sender = status_sender.clone();
                     let task = tokio::spawn(async move {
-                        let status = match execute(
-                            home,
-                            root,
-                            kernels,
-                            Some(patch_sender),
-                            Some(node_ids),
-                            Some(options),
-                        )
-                        .await
+                        let dir = config_dir(&home, &path);
+                        options.document_path = path;
+                        let status = if let Err(error) =
+                            apply_config(&dir, &root, &kernels, &mut options).await
                         {
-                            Ok(..) => CommandStatus::Succeeded,
-                            Err(error) => {
-                                CommandStatus::Failed(format!("While executing nodes: {error}"))
+                            CommandStatus::Failed(format!("While resolving config: {error}"))
+                        } else {
+                            match execute(
+                                home,
+                                root,
+                                kernels,
+                                Some(patch_sender),
+                                Some(node_ids),
+                                Some(options),
+                            )
+                            .await
+                            {
+                                Ok(..) => CommandStatus::Succeeded,
+                                Err(error) => {
+                                    CommandStatus::Failed(format!("While executing nodes: {error}"))
+                                }
                             }
                         };
                         send_status(&status_sender, command_id, status);
@@ -379,6 +397,14 @@ impl Document {
     }
 }
 
+/// Get the directory from which to resolve the config of a document
+fn config_dir(home: &Path, path: &Option<PathBuf>) -> PathBuf {
+    path.as_ref()
+        .and_then(|path| path.parent())
+        .unwrap_or(home)
+        .to_path_buf()
+}
+
 /// Create a patch for a chat from the fields of a [`Command::PatchExecuteChat`]
 async fn chat_patch(chat_id: &NodeId, text: String, files: Option<Vec<File>>) -> Result<Patch> {
     let Ok(Node::Article(Article { content, .. })) = codecs::from_str(
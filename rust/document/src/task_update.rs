@@ -7,7 +7,7 @@ use schema::{Node, PatchNode, PatchOp};
 
 use crate::{
     Command, CommandNodes, CommandScope, Document, DocumentCommandSender, DocumentPatchReceiver,
-    DocumentRoot, DocumentUpdateReceiver, DocumentWatchSender,
+    DocumentPatchesSender, DocumentRoot, DocumentUpdateReceiver, DocumentWatchSender,
 };
 
 impl Document {
@@ -27,6 +27,7 @@ impl Document {
         mut patch_receiver: DocumentPatchReceiver,
         root: DocumentRoot,
         watch_sender: DocumentWatchSender,
+        patches_sender: DocumentPatchesSender,
         command_sender: DocumentCommandSender,
     ) {
         tracing::debug!("Document update task started");
@@ -52,6 +53,12 @@ impl Document {
                     let compile = patch.compile;
                     let execute = patch.execute.clone();
 
+                    // Notify subscribers of the patch (before it is applied because
+                    // applying drains the patch's operations)
+                    if patches_sender.receiver_count() > 0 {
+                        patches_sender.send(patch.clone()).ok();
+                    }
+
                     let root = &mut *root.write().await;
                     if matches!(root, Node::Null(..)) && patch.node_id.is_none() && matches!(patch.ops.first().map(|(path, op)| (path.is_empty(), op)), Some((true,PatchOp::Set(..)))){
                         // If the root is null and the patch want to set it then do so
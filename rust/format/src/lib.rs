@@ -36,10 +36,16 @@ pub enum Format {
     Markdown, // Commonmark Markdown with GitHub Flavored Markdown extensions (as in the `markdown` crate)
     Smd,
     Qmd,
+    Rmd,
     Myst,
     Llmd,
+    // Other lightweight markup formats
+    Rst,
+    AsciiDoc,
+    Org,
     // Typesetting / text formats
     Latex,
+    Typst,
     Pdf,
     Text,
     // Notebook formats
@@ -47,6 +53,20 @@ pub enum Format {
     // Word processor formats
     Docx,
     Odt,
+    // E-book formats
+    Epub,
+    // Spreadsheet formats
+    Csv,
+    Tsv,
+    Xlsx,
+    Ods,
+    // Bibliographic formats
+    Bibtex,
+    Biblatex,
+    CslJson,
+    Ris,
+    Crossref,
+    DataCite,
     // Math languages
     AsciiMath,
     Tex,
@@ -97,6 +117,7 @@ pub enum Format {
     // Directories, bundles and archives
     Directory,
     Swb,
+    Meca,
     // Development focussed formats
     Debug,
     // Other arbitrary format, not listed above
@@ -112,17 +133,25 @@ impl Format {
         use Format::*;
         match self {
             Aac => "AAC",
+            AsciiDoc => "AsciiDoc",
             AsciiMath => "AsciiMath",
             Avi => "AVI",
             Bash => "Bash",
+            Biblatex => "BibLaTeX",
+            Bibtex => "BibTeX",
             Cbor => "CBOR",
             CborZst => "CBOR+Zstandard",
+            Crossref => "Crossref XML",
             Css => "CSS",
+            CslJson => "CSL-JSON",
+            Csv => "CSV",
+            DataCite => "DataCite XML",
             Debug => "Debug",
             Directory => "Directory",
             Docx => "Microsoft Word DOCX",
             Dom => "DOM HTML",
             Dot => "Graphviz DOT",
+            Epub => "EPUB",
             Flac => "FLAC",
             Gif => "GIF",
             Html => "HTML",
@@ -140,12 +169,15 @@ impl Format {
             Lexical => "Lexical JSON",
             Llmd => "LLM Markdown",
             Markdown => "Markdown",
+            Meca => "MECA",
             Mermaid => "Mermaid",
             Mkv => "Matroska",
             Mp3 => "MPEG-3",
             Mp4 => "MPEG-4",
             Myst => "MyST Markdown",
+            Ods => "OpenDocument ODS",
             Odt => "OpenDocument ODT",
+            Org => "Org",
             Ogg => "Ogg Vorbis",
             Ogv => "Ogg Vorbis Video",
             Pandoc => "Pandoc AST",
@@ -154,7 +186,10 @@ impl Format {
             Python => "Python",
             Qmd => "Quarto Markdown",
             R => "R",
+            Rmd => "R Markdown",
             Rhai => "Rhai",
+            Rst => "reStructuredText",
+            Ris => "RIS",
             Shell => "Shell",
             Smd => "Stencila Markdown",
             Swb => "Stencila Web Bundle",
@@ -163,9 +198,12 @@ impl Format {
             Tex => "TeX",
             Text => "Plain text",
             Toml => "TOML",
+            Tsv => "TSV",
+            Typst => "Typst",
             Wav => "WAV",
             WebM => "WebM",
             WebP => "WebP",
+            Xlsx => "Microsoft Excel XLSX",
             Yaml => "YAML",
             Other(name) => name,
             Unknown => "Unknown",
@@ -223,7 +261,7 @@ impl Format {
     /// Is this format a flavor or Markdown?
     pub fn is_markdown_flavor(&self) -> bool {
         use Format::*;
-        matches!(self, Markdown | Smd | Myst | Qmd | Llmd)
+        matches!(self, Markdown | Smd | Myst | Qmd | Rmd | Llmd)
     }
 
     /// Resolve a [`Format`] from a name for the format
@@ -231,17 +269,25 @@ impl Format {
         use Format::*;
         match name.to_lowercase().trim() {
             "aac" => Aac,
+            "asciidoc" | "adoc" => AsciiDoc,
             "asciimath" => AsciiMath,
             "avi" => Avi,
             "bash" => Bash,
+            "bibtex" | "bib" => Bibtex,
+            "biblatex" => Biblatex,
             "cbor" => Cbor,
             "cborzst" | "cbor.zstd" => CborZst,
+            "crossref" | "crossref.xml" => Crossref,
             "css" => Css,
+            "csljson" | "csl-json" | "csl.json" => CslJson,
+            "csv" => Csv,
+            "datacite" | "datacite.xml" => DataCite,
             "debug" => Debug,
             "directory" | "dir" => Directory,
             "docx" => Docx,
             "dom" | "dom.html" => Dom,
             "dot" => Dot,
+            "epub" => Epub,
             "flac" => Flac,
             "gif" => Gif,
             "html" => Html,
@@ -259,12 +305,15 @@ impl Format {
             "lexical" => Lexical,
             "llmd" | "llmmd" => Llmd,
             "markdown" | "md" => Markdown,
+            "meca" => Meca,
             "mermaid" => Mermaid,
             "myst" => Myst,
             "mkv" => Mkv,
             "mp3" => Mp3,
             "mp4" => Mp4,
+            "ods" => Ods,
             "odt" => Odt,
+            "org" => Org,
             "ogg" => Ogg,
             "ogv" => Ogv,
             "pandoc" => Pandoc,
@@ -273,7 +322,10 @@ impl Format {
             "python" | "py" => Python,
             "qmd" => Qmd,
             "r" => R,
+            "rmd" => Rmd,
             "rhai" => Rhai,
+            "rst" | "rest" | "restructuredtext" => Rst,
+            "ris" => Ris,
             "shell" | "sh" => Shell,
             "smd" => Smd,
             "svg" => Svg,
@@ -282,9 +334,12 @@ impl Format {
             "tex" => Tex,
             "text" | "txt" => Text,
             "toml" => Toml,
+            "tsv" | "tab" => Tsv,
+            "typst" | "typ" => Typst,
             "wav" => Wav,
             "webm" => WebM,
             "webp" => WebP,
+            "xlsx" => Xlsx,
             "yaml" | "yml" => Yaml,
             "unknown" => Unknown,
             _ => Other(name.to_string()),
@@ -303,6 +358,9 @@ impl Format {
         let path_string = path.to_string_lossy();
         for (end, format) in [
             (".cbor.zst", CborZst),
+            (".crossref.xml", Crossref),
+            (".csl.json", CslJson),
+            (".datacite.xml", DataCite),
             (".dom.html", Dom),
             (".jats.xml", Jats),
             (".json.zip", JsonZip),
@@ -340,12 +398,26 @@ impl Format {
         match media_type {
             "application/cbor" => Ok(Cbor),
             "application/cbor+zstd" => Ok(CborZst),
+            "application/epub+zip" => Ok(Epub),
             "application/json" => Ok(Json),
             "application/json+zip" => Ok(JsonZip),
             "application/ld+json" => Ok(JsonLd),
+            "application/meca+zip" => Ok(Meca),
             "application/yaml" => Ok(Yaml),
             "text/jats+xml" => Ok(Jats),
             "text/markdown" => Ok(Markdown),
+            "text/csv" => Ok(Csv),
+            "text/tab-separated-values" => Ok(Tsv),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => Ok(Xlsx),
+            "application/vnd.oasis.opendocument.spreadsheet" => Ok(Ods),
+            "application/x-bibtex" => Ok(Bibtex),
+            "application/vnd.citationstyles.csl+json" => Ok(CslJson),
+            "application/x-research-info-systems" => Ok(Ris),
+            "application/vnd.crossref.deposit+xml" => Ok(Crossref),
+            "application/vnd.datacite.datacite+xml" => Ok(DataCite),
+            "text/x-rst" => Ok(Rst),
+            "text/asciidoc" => Ok(AsciiDoc),
+            "text/org" => Ok(Org),
             "text/plain" => Ok(Text),
             _ => {
                 let name = if let Some((.., name)) = media_type.split_once('/') {
@@ -366,12 +438,26 @@ impl Format {
         match self {
             Cbor => "application/cbor".to_string(),
             CborZst => "application/cbor+zstd".to_string(),
+            Epub => "application/epub+zip".to_string(),
             Json => "application/json".to_string(),
             JsonZip => "application/json+zip".to_string(),
             JsonLd => "application/ld+json".to_string(),
+            Meca => "application/meca+zip".to_string(),
             Yaml => "application/yaml".to_string(),
             Jats => "text/jats+xml".to_string(),
             Markdown => "text/markdown".to_string(),
+            Rst => "text/x-rst".to_string(),
+            AsciiDoc => "text/asciidoc".to_string(),
+            Org => "text/org".to_string(),
+            Csv => "text/csv".to_string(),
+            Tsv => "text/tab-separated-values".to_string(),
+            Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet".to_string(),
+            Ods => "application/vnd.oasis.opendocument.spreadsheet".to_string(),
+            Bibtex | Biblatex => "application/x-bibtex".to_string(),
+            CslJson => "application/vnd.citationstyles.csl+json".to_string(),
+            Ris => "application/x-research-info-systems".to_string(),
+            Crossref => "application/vnd.crossref.deposit+xml".to_string(),
+            DataCite => "application/vnd.datacite.datacite+xml".to_string(),
             Text => "text/plain".to_string(),
             _ => {
                 if self.is_audio() {
@@ -406,17 +492,25 @@ impl Display for Format {
         use Format::*;
         f.write_str(match self {
             Aac => "aac",
+            AsciiDoc => "adoc",
             AsciiMath => "asciimath",
             Avi => "avi",
             Bash => "bash",
+            Biblatex => "biblatex",
+            Bibtex => "bib",
             Cbor => "cbor",
             CborZst => "cbor.zstd",
+            Crossref => "crossref.xml",
             Css => "css",
+            CslJson => "csl.json",
+            Csv => "csv",
+            DataCite => "datacite.xml",
             Debug => "debug",
             Directory => "directory",
             Docx => "docx",
             Dom => "dom.html",
             Dot => "dot",
+            Epub => "epub",
             Flac => "flac",
             Gif => "gif",
             Html => "html",
@@ -434,12 +528,15 @@ impl Display for Format {
             Lexical => "lexical",
             Llmd => "llmd",
             Markdown => "md",
+            Meca => "meca",
             Mermaid => "mermaid",
             Mkv => "mkv",
             Mp3 => "mp3",
             Mp4 => "mp4",
             Myst => "myst",
+            Ods => "ods",
             Odt => "odt",
+            Org => "org",
             Ogg => "ogg",
             Ogv => "ogv",
             Pandoc => "pandoc",
@@ -448,7 +545,10 @@ impl Display for Format {
             Python => "python",
             Qmd => "qmd",
             R => "r",
+            Rmd => "rmd",
             Rhai => "rhai",
+            Rst => "rst",
+            Ris => "ris",
             Shell => "shell",
             Svg => "svg",
             Smd => "smd",
@@ -457,9 +557,12 @@ impl Display for Format {
             Tex => "tex",
             Text => "text",
             Toml => "toml",
+            Tsv => "tsv",
+            Typst => "typ",
             Wav => "wav",
             WebM => "webm",
             WebP => "webp",
+            Xlsx => "xlsx",
             Yaml => "yaml",
             Other(name) => name,
             Unknown => "unknown",
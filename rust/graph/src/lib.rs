@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use petgraph::{
     stable_graph::{NodeIndex, StableGraph},
     visit::{EdgeRef, IntoEdgeReferences, IntoNodeReferences},
+    Direction,
 };
 
 use common::{
@@ -16,9 +17,11 @@ use common::{
 };
 use format::Format;
 use schema::{
-    Button, CallBlock, CodeChunk, CodeExpression, ExecutionDependantNode,
-    ExecutionDependantRelation, ExecutionDependencyNode, ExecutionDependencyRelation, File,
-    Function, Parameter, SoftwareSourceCode, StyledBlock, StyledInline, Variable,
+    Block, Button, CallBlock, CodeChunk, CodeExpression, ExecutionDependant,
+    ExecutionDependantNode, ExecutionDependantRelation, ExecutionDependency,
+    ExecutionDependencyNode, ExecutionDependencyRelation, ExecutionTag, File, Function, Inline,
+    Parameter, SoftwareSourceCode, StyledBlock, StyledInline, Variable, Visitor, WalkControl,
+    WalkNode,
 };
 
 /// The nodes in the graph
@@ -46,6 +49,9 @@ pub enum GraphNode {
     File {
         path: String,
     },
+    Include {
+        id: String,
+    },
     Function {
         name: String,
     },
@@ -190,6 +196,19 @@ pub struct Graph {
 }
 
 impl Graph {
+    /// Create a graph from the execution dependencies and dependants of nodes
+    ///
+    /// Walks over `node` and adds the `executionDependencies` and `executionDependants`
+    /// of each executable node. Nodes without an `id` are represented using their
+    /// node id. In addition, files are added for the `source` of include and call
+    /// blocks, and for the `@reads`, `@watches` and `@writes` execution tags of code
+    /// chunks and expressions (which are parsed from their code when they are compiled).
+    pub fn from_node<T: WalkNode>(node: &T) -> Self {
+        let mut collector = Collector::default();
+        collector.visit(node);
+        collector.graph
+    }
+
     /// Add a dependency relation between two nodes
     pub fn add_dependency(
         &mut self,
@@ -198,26 +217,8 @@ impl Graph {
         from: &ExecutionDependencyNode,
     ) -> Result<()> {
         let from = GraphNode::try_from(from)?;
-        let from = if let Some(index) = self.indices.get(&from) {
-            *index
-        } else {
-            let index = self.graph.add_node(from.clone());
-            self.indices.insert(from, index);
-            index
-        };
-
         let to = GraphNode::try_from(to)?;
-        let to = if let Some(index) = self.indices.get(&to) {
-            *index
-        } else {
-            let index = self.graph.add_node(to.clone());
-            self.indices.insert(to, index);
-            index
-        };
-
-        let edge = GraphEdge::from(relation);
-
-        self.graph.add_edge(from, to, edge);
+        self.add_edge(from, to, GraphEdge::from(relation));
 
         Ok(())
     }
@@ -230,28 +231,67 @@ impl Graph {
         to: &ExecutionDependantNode,
     ) -> Result<()> {
         let from = GraphNode::try_from(from)?;
-        let from = if let Some(index) = self.indices.get(&from) {
-            *index
-        } else {
-            let index = self.graph.add_node(from.clone());
-            self.indices.insert(from, index);
-            index
-        };
-
         let to = GraphNode::try_from(to)?;
-        let to = if let Some(index) = self.indices.get(&to) {
+        self.add_edge(from, to, GraphEdge::from(relation));
+
+        Ok(())
+    }
+
+    /// Add an edge between two nodes, adding the nodes if they are not yet in the graph
+    fn add_edge(&mut self, from: GraphNode, to: GraphNode, edge: GraphEdge) {
+        let from = self.index(from);
+        let to = self.index(to);
+
+        self.graph.add_edge(from, to, edge);
+    }
+
+    /// Get the index of a node, adding it to the graph if necessary
+    fn index(&mut self, node: GraphNode) -> NodeIndex {
+        if let Some(index) = self.indices.get(&node) {
             *index
         } else {
-            let index = self.graph.add_node(to.clone());
-            self.indices.insert(to, index);
+            let index = self.graph.add_node(node.clone());
+            self.indices.insert(node, index);
             index
-        };
+        }
+    }
+
+    /// Get the paths of the [`GraphNode::File`] nodes in the graph
+    pub fn files(&self) -> Vec<&str> {
+        self.graph
+            .node_weights()
+            .filter_map(|node| match node {
+                GraphNode::File { path } => Some(path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
 
-        let edge = GraphEdge::from(relation);
+    /// Get the paths of the files that nodes in the graph depend upon
+    ///
+    /// Excludes files that are written by any node in the graph since changes
+    /// to those are the result, rather than the cause, of execution.
+    pub fn dependency_files(&self) -> Vec<&str> {
+        self.graph
+            .node_references()
+            .filter_map(|(index, node)| {
+                let GraphNode::File { path } = node else {
+                    return None;
+                };
 
-        self.graph.add_edge(from, to, edge);
+                let written = self
+                    .graph
+                    .edges_directed(index, Direction::Incoming)
+                    .any(|edge| matches!(edge.weight(), GraphEdge::Writes));
+                let depended = self
+                    .graph
+                    .edges_directed(index, Direction::Outgoing)
+                    .next()
+                    .is_some();
 
-        Ok(())
+                (depended && !written).then_some(path.as_str())
+            })
+            .collect()
     }
 
     /// Convert the graph to some format
@@ -301,3 +341,210 @@ impl Serialize for Graph {
         .serialize(serializer)
     }
 }
+
+/// A visitor which collects the dependencies and dependants of executable nodes into a [`Graph`]
+#[derive(Default)]
+struct Collector {
+    graph: Graph,
+}
+
+impl Collector {
+    /// Add the dependencies and dependants of a node to the graph
+    fn add(
+        &mut self,
+        node: GraphNode,
+        dependencies: &Option<Vec<ExecutionDependency>>,
+        dependants: &Option<Vec<ExecutionDependant>>,
+    ) {
+        for dependency in dependencies.iter().flatten() {
+            if let Ok(from) = GraphNode::try_from(&dependency.dependency_node) {
+                let edge = GraphEdge::from(&dependency.dependency_relation);
+                self.graph.add_edge(from, node.clone(), edge);
+            }
+        }
+
+        for dependant in dependants.iter().flatten() {
+            if let Ok(to) = GraphNode::try_from(&dependant.dependant_node) {
+                let edge = GraphEdge::from(&dependant.dependant_relation);
+                self.graph.add_edge(node.clone(), to, edge);
+            }
+        }
+    }
+
+    /// Add the files declared in the execution tags of a node to the graph
+    fn add_tags(&mut self, node: &GraphNode, tags: &Option<Vec<ExecutionTag>>) {
+        for tag in tags.iter().flatten() {
+            let file = GraphNode::File {
+                path: tag.value.clone(),
+            };
+            match tag.name.as_str() {
+                "reads" | "watches" => self.graph.add_edge(file, node.clone(), GraphEdge::Reads),
+                "writes" => self.graph.add_edge(node.clone(), file, GraphEdge::Writes),
+                _ => {}
+            }
+        }
+    }
+
+    /// Add the file that is the source of a node to the graph
+    fn add_source(&mut self, node: GraphNode, source: &str, edge: GraphEdge) {
+        if source.is_empty() {
+            return;
+        }
+
+        let file = GraphNode::File {
+            path: source.to_string(),
+        };
+        self.graph.add_edge(file, node, edge);
+    }
+}
+
+impl Visitor for Collector {
+    fn visit_block(&mut self, block: &Block) -> WalkControl {
+        match block {
+            Block::CallBlock(call) => {
+                let node = GraphNode::Call {
+                    id: call
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| call.node_id().to_string()),
+                };
+                self.add_source(node.clone(), &call.source, GraphEdge::Calls);
+                self.add(
+                    node,
+                    &call.options.execution_dependencies,
+                    &call.options.execution_dependants,
+                )
+            }
+            Block::CodeChunk(chunk) => {
+                let node = GraphNode::CodeChunk {
+                    id: chunk
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| chunk.node_id().to_string()),
+                };
+                self.add_tags(&node, &chunk.options.execution_tags);
+                self.add(
+                    node,
+                    &chunk.options.execution_dependencies,
+                    &chunk.options.execution_dependants,
+                )
+            }
+            Block::IncludeBlock(include) => {
+                let node = GraphNode::Include {
+                    id: include
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| include.node_id().to_string()),
+                };
+                self.add_source(node, &include.source, GraphEdge::Includes);
+            }
+            _ => {}
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &Inline) -> WalkControl {
+        match inline {
+            Inline::Button(button) => self.add(
+                GraphNode::Button {
+                    id: button
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| button.node_id().to_string()),
+                },
+                &button.options.execution_dependencies,
+                &button.options.execution_dependants,
+            ),
+            Inline::CodeExpression(expr) => {
+                let node = GraphNode::CodeExpression {
+                    id: expr
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| expr.node_id().to_string()),
+                };
+                self.add_tags(&node, &expr.options.execution_tags);
+                self.add(
+                    node,
+                    &expr.options.execution_dependencies,
+                    &expr.options.execution_dependants,
+                )
+            }
+            _ => {}
+        }
+
+        WalkControl::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schema::{Article, CodeChunkOptions, IncludeBlock, Node};
+
+    use super::*;
+
+    #[test]
+    fn files_from_node() {
+        let chunk = Block::CodeChunk(CodeChunk {
+            id: Some("chunk".into()),
+            options: Box::new(CodeChunkOptions {
+                execution_dependencies: Some(vec![ExecutionDependency {
+                    dependency_relation: ExecutionDependencyRelation::Reads,
+                    dependency_node: ExecutionDependencyNode::File(File {
+                        path: "data.csv".into(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let article = Node::Article(Article::new(vec![chunk]));
+
+        let graph = Graph::from_node(&article);
+        assert_eq!(graph.files(), vec!["data.csv"]);
+        assert_eq!(graph.dependency_files(), vec!["data.csv"]);
+    }
+
+    #[test]
+    fn files_from_tags_and_sources() {
+        let tag = |name: &str, value: &str| ExecutionTag {
+            name: name.into(),
+            value: value.into(),
+            ..Default::default()
+        };
+
+        // Neither chunk has an id, so they are represented by their node ids
+        let reads = Block::CodeChunk(CodeChunk {
+            options: Box::new(CodeChunkOptions {
+                execution_tags: Some(vec![tag("reads", "data.csv"), tag("writes", "plot.png")]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let watches = Block::CodeChunk(CodeChunk {
+            options: Box::new(CodeChunkOptions {
+                execution_tags: Some(vec![tag("watches", "plot.png"), tag("pure", "")]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let include = Block::IncludeBlock(IncludeBlock {
+            source: "intro.md".into(),
+            ..Default::default()
+        });
+        let article = Node::Article(Article::new(vec![reads, watches, include]));
+
+        let graph = Graph::from_node(&article);
+
+        let mut files = graph.files();
+        files.sort();
+        assert_eq!(files, vec!["data.csv", "intro.md", "plot.png"]);
+
+        // Files written by the document are not dependencies
+        let mut files = graph.dependency_files();
+        files.sort();
+        assert_eq!(files, vec!["data.csv", "intro.md"]);
+    }
+}
@@ -14,7 +14,7 @@ use kernel::{
         tracing,
     },
     format::Format,
-    schema::{ExecutionMessage, Node},
+    schema::{ExecutionMessage, Node, Variable},
     Kernel, KernelForks, KernelInstance, KernelVariableRequest, KernelVariableRequester,
     KernelVariableResponse,
 };
@@ -111,6 +111,9 @@ pub struct Kernels {
 
     /// A sender for responses to kernels for variables
     variable_response_sender: broadcast::Sender<KernelVariableResponse>,
+
+    /// The names of kernels to prefer when creating an instance for a language
+    preferred: Vec<String>,
 }
 
 impl fmt::Debug for Kernels {
@@ -155,9 +158,17 @@ impl Kernels {
             instances,
             variable_request_sender,
             variable_response_sender,
+            preferred: Vec::new(),
         }
     }
 
+    /// Set the names of the kernels to prefer, in order, when creating an instance for a language
+    ///
+    /// Usually set from the `kernels` property of the document's resolved config.
+    pub fn set_preferred(&mut self, preferred: Vec<String>) {
+        self.preferred = preferred;
+    }
+
     /// Create a new set of kernels in the current working directory
     pub fn new_here() -> Self {
         let path = std::env::current_dir().expect("should always be a current dir");
@@ -219,8 +230,19 @@ impl Kernels {
         let kernel = match language {
             Some(language) => 'block: {
                 let format = Format::from_name(language);
+                let mut kernels = list().await;
+
+                for name in &self.preferred {
+                    if let Some(index) = kernels.iter().position(|kernel| {
+                        kernel.name() == *name
+                            && kernel.supports_language(&format)
+                            && kernel.is_available()
+                    }) {
+                        break 'block kernels.swap_remove(index);
+                    }
+                }
 
-                for kernel in list().await {
+                for kernel in kernels {
                     if kernel.name() == language {
                         break 'block kernel;
                     }
@@ -408,6 +430,17 @@ impl Kernels {
         Ok(None)
     }
 
+    /// List the variables in the kernels
+    pub async fn list(&mut self) -> Result<Vec<Variable>> {
+        let mut variables = Vec::new();
+        for entry in self.instances.read().await.iter() {
+            let mut instance = entry.instance.lock().await;
+            variables.append(&mut instance.list().await?);
+        }
+
+        Ok(variables)
+    }
+
     /// Set a variable in the first kernel instance
     pub async fn set(&mut self, name: &str, value: &Node) -> Result<()> {
         let instance = self.get_instance_programming().await?;
@@ -433,6 +466,22 @@ impl Kernels {
             .all(|entry| matches!(entry.kernel.supports_forks(), KernelForks::Yes))
     }
 
+    /// Stop all of the kernel instances
+    ///
+    /// Used to stop forks of kernels when they are no longer needed.
+    /// Attempts to stop all instances, returning the first error, if any.
+    pub async fn stop(&self) -> Result<()> {
+        let mut result = Ok(());
+        for instance in self.instances().await {
+            if let Err(error) = instance.lock().await.stop().await {
+                if result.is_ok() {
+                    result = Err(error);
+                }
+            }
+        }
+        result
+    }
+
     /// Fork the kernels
     ///
     /// Creates a new [`Kernels`] set with a fork of each current instance.
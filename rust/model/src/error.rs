@@ -0,0 +1,129 @@
+use std::{fmt, time::Duration};
+
+use common::{
+    eyre::Result,
+    reqwest::{
+        header::{HeaderMap, RETRY_AFTER},
+        Response, StatusCode,
+    },
+};
+
+/// An unsuccessful response from the API of a model provider
+///
+/// Model implementations should return this error for responses with an error
+/// status so that the task can be retried, or performed by another model, as
+/// appropriate for the status and any delay requested by the provider.
+#[derive(Debug, Clone)]
+pub struct ModelApiError {
+    /// The status of the response
+    pub status: StatusCode,
+
+    /// The time to wait before retrying the request, as requested by the provider
+    pub retry_after: Option<Duration>,
+
+    /// The body of the response, usually describing the error
+    pub message: String,
+}
+
+impl ModelApiError {
+    /// Check the status of a response
+    ///
+    /// Returns the response if it was successful, otherwise a [`ModelApiError`]
+    /// with the status, requested delay, and body of the response.
+    pub async fn check(response: Response) -> Result<Response> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let retry_after = retry_after(response.headers());
+        let message = response.text().await.unwrap_or_default();
+
+        Err(Self {
+            status,
+            retry_after,
+            message,
+        }
+        .into())
+    }
+
+    /// Whether the request may succeed if retried
+    ///
+    /// Timeouts (408), rate limiting (429) and server errors (5xx, including
+    /// the non-standard 529 used by some providers when overloaded) are transient.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.status,
+            StatusCode::REQUEST_TIMEOUT | StatusCode::TOO_MANY_REQUESTS
+        ) || self.status.is_server_error()
+    }
+}
+
+impl fmt::Display for ModelApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.status, self.message)
+    }
+}
+
+impl std::error::Error for ModelApiError {}
+
+/// Get the delay requested by the `retry-after-ms` or `Retry-After` headers of a response
+///
+/// Only delays in (possibly fractional) seconds are supported for `Retry-After`,
+/// not HTTP dates.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let seconds = |name: &str, scale: f64| {
+        headers
+            .get(name)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value >= 0.0)
+            .map(|value| Duration::from_secs_f64(value / scale))
+    };
+
+    seconds("retry-after-ms", 1000.0).or_else(|| seconds(RETRY_AFTER.as_str(), 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use common::reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn retry_after_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("2"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(2)));
+
+        headers.insert("retry-after-ms", HeaderValue::from_static("1500"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_millis(1500)));
+
+        headers.clear();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), None);
+    }
+
+    #[test]
+    fn transient_statuses() {
+        let error = |status: u16| ModelApiError {
+            status: StatusCode::from_u16(status).expect("valid status"),
+            retry_after: None,
+            message: String::new(),
+        };
+
+        assert!(error(429).is_transient());
+        assert!(error(500).is_transient());
+        assert!(error(529).is_transient());
+        assert!(!error(400).is_transient());
+        assert!(!error(401).is_transient());
+    }
+}
@@ -2,6 +2,7 @@ use common::{
     async_trait::async_trait,
     clap::{self, ValueEnum},
     eyre::Result,
+    futures::{self, StreamExt},
     inflector::Inflector,
     serde::{Deserialize, Serialize},
     strum::Display,
@@ -19,10 +20,22 @@ pub use format;
 pub use schema;
 pub use secrets;
 
+mod error;
 mod output;
+mod stream;
+mod structured;
 mod task;
+mod tool;
+mod usage;
+pub use error::ModelApiError;
 pub use output::{ModelOutput, ModelOutputKind};
+pub use stream::{sse_data, ModelStream, ModelStreamItem};
+pub use structured::{
+    block_from_json, json_schema, json_schema_inlined, json_schema_strict, validate,
+};
 pub use task::{ModelTask, ModelTaskKind};
+pub use tool::{ModelTool, ModelToolCall, ModelToolResult, ModelToolRound};
+pub use usage::{ModelPricing, ModelUsage};
 
 /// The type of provider of a model
 ///
@@ -83,6 +96,7 @@ pub struct ModelSpecification {
     version: String,
     r#type: ModelType,
     availability: ModelAvailability,
+    pricing: Option<ModelPricing>,
 }
 
 impl From<&dyn Model> for ModelSpecification {
@@ -94,6 +108,7 @@ impl From<&dyn Model> for ModelSpecification {
             version: model.version(),
             r#type: model.r#type(),
             availability: model.availability(),
+            pricing: model.pricing(),
         }
     }
 }
@@ -223,6 +238,23 @@ pub trait Model: Sync + Send {
         0
     }
 
+    /// Get the pricing of the model
+    ///
+    /// Used to calculate the cost of tasks. This default implementation treats
+    /// builtin and local models as free and looks up the pricing of other models
+    /// by their id, returning `None` if it is unknown.
+    fn pricing(&self) -> Option<ModelPricing> {
+        match self.r#type() {
+            ModelType::Builtin | ModelType::Local => Some(ModelPricing::FREE),
+            _ => ModelPricing::for_id(&self.id()),
+        }
+    }
+
+    /// Get the usage of the model for a task from counts of tokens
+    fn usage(&self, prompt_tokens: u64, completion_tokens: u64) -> ModelUsage {
+        ModelUsage::new(self.pricing(), prompt_tokens, completion_tokens)
+    }
+
     /// Does the model support a specific task
     ///
     /// This default implementation is based solely on whether the models
@@ -249,6 +281,23 @@ pub trait Model: Sync + Send {
 
     /// Perform a generation task
     async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput>;
+
+    /// Perform a generation task, streaming chunks of the generated text
+    ///
+    /// This default implementation performs the task using `perform_task` and
+    /// yields the entire content of the output as a single chunk, followed by its
+    /// usage (if any). Models which are able to stream should override this so that
+    /// text is yielded as it is generated.
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        let output = self.perform_task(task).await?;
+
+        let mut items = vec![Ok(ModelStreamItem::Text(output.content))];
+        if let Some(usage) = output.usage {
+            items.push(Ok(ModelStreamItem::Usage(usage)));
+        }
+
+        Ok(futures::stream::iter(items).boxed())
+    }
 }
 
 /// Generate a test task which has system, user and model messages
@@ -285,3 +334,19 @@ pub fn test_task_repeat_word() -> ModelTask {
         ..Default::default()
     }
 }
+
+/// Collect the text, and the last reported usage (if any), from a model stream
+///
+/// Used for tests of implementations of `Model::perform_task_stream`.
+#[allow(unused)]
+pub async fn test_collect_stream(mut stream: ModelStream) -> Result<(String, Option<ModelUsage>)> {
+    let mut text = String::new();
+    let mut usage = None;
+    while let Some(item) = stream.next().await {
+        match item? {
+            ModelStreamItem::Text(chunk) => text.push_str(&chunk),
+            ModelStreamItem::Usage(reported) => usage = Some(reported),
+        }
+    }
+    Ok((text, usage))
+}
@@ -4,12 +4,12 @@ use common::{
     serde_with::skip_serializing_none,
 };
 use format::Format;
-use schema::{AuthorRole, AuthorRoleName};
+use schema::{AuthorRole, AuthorRoleName, ExecutionMessage};
 
-use crate::Model;
+use crate::{Model, ModelToolCall, ModelUsage};
 
 /// The kind of generative model output
-#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 #[serde(crate = "common::serde")]
 pub enum ModelOutputKind {
     /// Generated text in a text format
@@ -22,7 +22,7 @@ pub enum ModelOutputKind {
 
 /// Output generated by a generative model for a task
 #[skip_serializing_none]
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
 #[serde(default, deny_unknown_fields, crate = "common::serde")]
 pub struct ModelOutput {
     /// The models that were involved in generating the output
@@ -42,6 +42,24 @@ pub struct ModelOutput {
 
     /// The content generated by the assistant
     pub content: String,
+
+    /// Any calls to tools made by the model
+    ///
+    /// If not empty, the caller should perform the calls and perform the
+    /// task again with their results.
+    pub tool_calls: Vec<ModelToolCall>,
+
+    /// The number of tokens used, and the estimated cost, of the task
+    ///
+    /// `None` if the model did not report usage (e.g. when performing a dry run).
+    pub usage: Option<ModelUsage>,
+
+    /// Messages about how the task was performed
+    ///
+    /// For example, that the task was retried because the provider's rate limit
+    /// was reached, or that it was performed by a fallback model. Usually added to
+    /// the execution messages of the node that the task was performed for.
+    pub messages: Option<Vec<ExecutionMessage>>,
 }
 
 impl ModelOutput {
@@ -54,6 +72,9 @@ impl ModelOutput {
             kind: ModelOutputKind::Text,
             format: Format::Unknown,
             content: (String::new()),
+            tool_calls: Vec::new(),
+            usage: None,
+            messages: None,
         })
     }
 
@@ -67,6 +88,9 @@ impl ModelOutput {
             kind: ModelOutputKind::Text,
             format: format.clone(),
             content: text,
+            tool_calls: Vec::new(),
+            usage: None,
+            messages: None,
         })
     }
 
@@ -79,6 +103,9 @@ impl ModelOutput {
             kind: ModelOutputKind::Url,
             format,
             content: url,
+            tool_calls: Vec::new(),
+            usage: None,
+            messages: None,
         })
     }
 }
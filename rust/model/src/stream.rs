@@ -0,0 +1,146 @@
+use common::{
+    eyre::Result,
+    futures::stream::{self, BoxStream, StreamExt},
+    itertools::Itertools,
+    reqwest::Response,
+};
+
+use crate::ModelUsage;
+
+/// An item in a stream generated by a model
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelStreamItem {
+    /// A chunk of generated text
+    Text(String),
+
+    /// The usage of the model for the task
+    ///
+    /// Yielded by models whose APIs report token counts when streaming (usually
+    /// at the end of the stream). If more than one is yielded, the last is used.
+    Usage(ModelUsage),
+}
+
+/// A stream of chunks of text, and usage, generated by a model
+pub type ModelStream = BoxStream<'static, Result<ModelStreamItem>>;
+
+/// Create a stream of the data of the server-sent events in a HTTP response
+///
+/// Used by models whose APIs stream generated content as server-sent events
+/// (SSE). Each item is the (possibly multi-line) `data` of an event. Events without
+/// data (e.g. comments used as keep-alives) are skipped and the stream ends at
+/// the end of the response, or at an OpenAI style `[DONE]` event.
+pub fn sse_data(response: Response) -> BoxStream<'static, Result<String>> {
+    stream::unfold(
+        (response, SseParser::default(), Vec::new(), false),
+        |(mut response, mut parser, mut pending, mut finished)| async move {
+            loop {
+                if !pending.is_empty() {
+                    let data = pending.remove(0);
+                    if data == "[DONE]" {
+                        return None;
+                    }
+                    return Some((Ok(data), (response, parser, pending, finished)));
+                }
+
+                if finished {
+                    return None;
+                }
+
+                match response.chunk().await {
+                    Ok(Some(bytes)) => pending = parser.push(&bytes),
+                    Ok(None) => {
+                        finished = true;
+                        pending = parser.finish();
+                    }
+                    Err(error) => {
+                        return Some((Err(error.into()), (response, parser, pending, true)))
+                    }
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+/// An incremental parser of server-sent events
+///
+/// Bytes are buffered until a complete event (terminated by a blank line) is
+/// available so that events, and multi-byte characters, split across chunks
+/// are handled.
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Push a chunk of bytes and return the data of any completed events
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut data = Vec::new();
+        while let Some((end, length)) = Self::event_end(&self.buffer) {
+            let event: Vec<u8> = self.buffer.drain(..end + length).collect();
+            if let Some(event) = Self::event_data(&event[..end]) {
+                data.push(event);
+            }
+        }
+        data
+    }
+
+    /// Finish parsing and return the data of any final, unterminated, event
+    fn finish(&mut self) -> Vec<String> {
+        let event = std::mem::take(&mut self.buffer);
+        Self::event_data(&event).into_iter().collect()
+    }
+
+    /// Find the end of the first event in the buffer and the length of its terminator
+    fn event_end(buffer: &[u8]) -> Option<(usize, usize)> {
+        (0..buffer.len()).find_map(|index| {
+            let rest = &buffer[index..];
+            if rest.starts_with(b"\n\n") {
+                Some((index, 2))
+            } else if rest.starts_with(b"\r\n\r\n") {
+                Some((index, 4))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Get the data of an event from its `data:` lines
+    fn event_data(event: &[u8]) -> Option<String> {
+        let event = String::from_utf8_lossy(event);
+        let lines = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|data| data.strip_prefix(' ').unwrap_or(data))
+            .collect_vec();
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sse_parser() {
+        let mut parser = SseParser::default();
+
+        assert_eq!(
+            parser.push(b"event: delta\ndata: {\"a\":1}\n\n: keep-alive\n\ndata: {\"b\""),
+            vec![r#"{"a":1}"#.to_string()]
+        );
+        assert_eq!(
+            parser.push(b":2}\r\n\r\ndata: line 1\ndata: line 2\n\ndata: [DONE]"),
+            vec![r#"{"b":2}"#.to_string(), "line 1\nline 2".to_string()]
+        );
+        assert_eq!(parser.finish(), vec!["[DONE]".to_string()]);
+
+        // Multi-byte characters split across chunks
+        let bytes = "data: héllo\n\n".as_bytes();
+        assert_eq!(parser.push(&bytes[..8]), Vec::<String>::new());
+        assert_eq!(parser.push(&bytes[8..]), vec!["héllo".to_string()]);
+    }
+}
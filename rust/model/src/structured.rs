@@ -0,0 +1,653 @@
+//! Structured output conforming to the JSON Schema of a node type
+//!
+//! Rather than generating Markdown which is then decoded, models can be asked
+//! to generate JSON which is deserialized directly into a node. The JSON Schema
+//! for the node type is derived from the JSON Schemas generated by `schema-gen`
+//! (in the top level `json` folder) but simplified so that it is self contained,
+//! excludes metadata and provenance properties, and limits the types of block
+//! and inline content to those which a model is likely to generate.
+
+use common::{
+    eyre::{bail, eyre, Result},
+    serde_json::{self, json, Map, Value},
+};
+use rust_embed::RustEmbed;
+use schema::{Block, Figure, NodeType, Table};
+
+#[derive(RustEmbed)]
+#[folder = "$CARGO_MANIFEST_DIR/../../json"]
+#[include = "*.schema.json"]
+struct Schemas;
+
+/// The members of union types which are used in structured output
+///
+/// Unions not listed here include all their members.
+const UNIONS: &[(&str, &[&str])] = &[
+    (
+        "Block",
+        &[
+            "Paragraph",
+            "CodeBlock",
+            "MathBlock",
+            "ImageObject",
+            "RawBlock",
+        ],
+    ),
+    (
+        "Inline",
+        &[
+            "Text",
+            "Emphasis",
+            "Strong",
+            "CodeInline",
+            "MathInline",
+            "Link",
+            "Subscript",
+            "Superscript",
+        ],
+    ),
+    ("Node", &["Null", "Boolean", "Integer", "Number", "String"]),
+];
+
+/// The JSON Schema keywords retained in structured output schemas
+///
+/// Other keywords (e.g. those used by `schema-gen` for code generation) are removed.
+const KEYWORDS: &[&str] = &["type", "description", "enum", "const", "minimum", "maximum"];
+
+/// Get the JSON Schema for structured output of a node type
+pub fn json_schema(node_type: NodeType) -> Result<Value> {
+    let mut defs = Map::new();
+    let mut schema = simplify(&node_type.to_string(), &mut defs)?;
+
+    if let Value::Object(object) = &mut schema {
+        object.insert(
+            "$schema".into(),
+            json!("https://json-schema.org/draft/2020-12/schema"),
+        );
+        object.insert("$defs".into(), Value::Object(defs));
+    }
+
+    Ok(schema)
+}
+
+/// Load the JSON Schema for a type
+fn load(title: &str) -> Result<Value> {
+    let file = Schemas::get(&[title, ".schema.json"].concat())
+        .ok_or_else(|| eyre!("No JSON Schema for type `{title}`"))?;
+    Ok(serde_json::from_slice(&file.data)?)
+}
+
+/// Add the simplified schema for a type to the definitions (if not already)
+fn define(title: &str, defs: &mut Map<String, Value>) -> Result<()> {
+    if defs.contains_key(title) {
+        return Ok(());
+    }
+
+    // Insert a placeholder to avoid infinite recursion for recursive types
+    defs.insert(title.to_string(), Value::Null);
+    let schema = simplify(title, defs)?;
+    defs.insert(title.to_string(), schema);
+
+    Ok(())
+}
+
+/// Create a simplified, self contained, schema for a type
+fn simplify(title: &str, defs: &mut Map<String, Value>) -> Result<Value> {
+    if let Some((.., members)) = UNIONS.iter().find(|(name, ..)| *name == title) {
+        let mut any_of = Vec::new();
+        for member in members.iter() {
+            define(member, defs)?;
+            let reference = ["#/$defs/", member].concat();
+            any_of.push(json!({ "$ref": reference }));
+        }
+        return Ok(json!({ "anyOf": any_of }));
+    }
+
+    let schema = load(title)?;
+
+    let Some(properties) = schema.get("properties").and_then(|value| value.as_object()) else {
+        return subschema(&schema, defs);
+    };
+
+    // Only include required and core properties which are not stripped
+    // as metadata, provenance etc.
+    let names = |key: &str| -> Vec<&str> {
+        schema
+            .get(key)
+            .and_then(|value| value.as_array())
+            .map(|array| array.iter().filter_map(|item| item.as_str()).collect())
+            .unwrap_or_default()
+    };
+    let required = names("required");
+    let core = names("core");
+
+    let mut props = Map::new();
+    for (name, property) in properties {
+        if !(required.contains(&name.as_str()) || core.contains(&name.as_str()))
+            || property.get("strip").is_some()
+        {
+            continue;
+        }
+
+        let property = if name == "type" {
+            json!({ "const": title })
+        } else {
+            subschema(property, defs)?
+        };
+        props.insert(name.clone(), property);
+    }
+
+    let required: Vec<&str> = required
+        .into_iter()
+        .filter(|name| props.contains_key(*name))
+        .collect();
+
+    let mut simplified = json!({
+        "type": "object",
+        "properties": props,
+        "required": required,
+        "additionalProperties": false
+    });
+    if let (Some(description), Value::Object(object)) = (schema.get("description"), &mut simplified)
+    {
+        object.insert("description".into(), description.clone());
+    }
+
+    Ok(simplified)
+}
+
+/// Simplify a subschema, replacing references to other schemas with local references
+fn subschema(schema: &Value, defs: &mut Map<String, Value>) -> Result<Value> {
+    let Value::Object(object) = schema else {
+        return Ok(schema.clone());
+    };
+
+    let mut simplified = Map::new();
+    for (key, value) in object {
+        match key.as_str() {
+            "$ref" => {
+                let Some(title) = value
+                    .as_str()
+                    .and_then(|value| value.strip_suffix(".schema.json"))
+                else {
+                    bail!("Unexpected JSON Schema reference: {value}")
+                };
+                define(title, defs)?;
+                simplified.insert(key.clone(), json!(["#/$defs/", title].concat()));
+            }
+            "items" => {
+                simplified.insert(key.clone(), subschema(value, defs)?);
+            }
+            "anyOf" => {
+                let mut any_of = Vec::new();
+                for item in value.as_array().into_iter().flatten() {
+                    any_of.push(subschema(item, defs)?);
+                }
+                simplified.insert(key.clone(), Value::Array(any_of));
+            }
+            _ => {
+                if KEYWORDS.contains(&key.as_str()) {
+                    simplified.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    Ok(Value::Object(simplified))
+}
+
+/// Get a version of a structured output schema for APIs with a "strict" mode
+///
+/// For use with APIs (e.g. OpenAI) which, in strict mode, require that all properties
+/// are listed as `required`. Optional properties are made nullable instead and any
+/// `null` values are removed by [`block_from_json`]. Keywords not supported in strict
+/// mode (`$schema`, `minimum` and `maximum`) are removed, leaving them to be checked
+/// by [`validate`].
+pub fn json_schema_strict(schema: &Value) -> Value {
+    let Value::Object(object) = schema else {
+        return schema.clone();
+    };
+
+    let required: Vec<&Value> = object
+        .get("required")
+        .and_then(|value| value.as_array())
+        .map(|array| array.iter().collect())
+        .unwrap_or_default();
+
+    let mut strict = Map::new();
+    for (key, value) in object {
+        match key.as_str() {
+            "properties" => {
+                let mut properties = Map::new();
+                for (name, property) in value.as_object().into_iter().flatten() {
+                    let property = json_schema_strict(property);
+                    let property = if required.contains(&&Value::String(name.clone())) {
+                        property
+                    } else {
+                        json!({ "anyOf": [property, { "type": "null" }] })
+                    };
+                    properties.insert(name.clone(), property);
+                }
+                let names = properties.keys().cloned().map(Value::String).collect();
+                strict.insert("required".into(), Value::Array(names));
+                strict.insert(key.clone(), Value::Object(properties));
+            }
+            "$defs" => {
+                let defs = value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, def)| (name.clone(), json_schema_strict(def)))
+                    .collect();
+                strict.insert(key.clone(), Value::Object(defs));
+            }
+            "items" => {
+                strict.insert(key.clone(), json_schema_strict(value));
+            }
+            "anyOf" => {
+                let any_of = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(json_schema_strict)
+                    .collect();
+                strict.insert(key.clone(), Value::Array(any_of));
+            }
+            "required" | "$schema" | "minimum" | "maximum" => {}
+            _ => {
+                strict.insert(key.clone(), value.clone());
+            }
+        }
+    }
+
+    Value::Object(strict)
+}
+
+/// Remove properties with `null` values from objects
+///
+/// Models constrained by a strict schema (see [`json_schema_strict`]) output
+/// `null` for optional properties which they do not set.
+fn remove_nulls(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.retain(|_, value| !value.is_null());
+            object.values_mut().for_each(remove_nulls);
+        }
+        Value::Array(array) => array.iter_mut().for_each(remove_nulls),
+        _ => {}
+    }
+}
+
+/// Get a version of a structured output schema without references
+///
+/// For use with APIs (e.g. Google AI) which do not support `$ref`. References are
+/// replaced by the definitions they point to. Because node types are recursive
+/// (e.g. `Emphasis` within `Emphasis`) definitions nested deeper than `depth` are
+/// replaced by a plain `object` schema. Keywords other than the basic ones supported
+/// by these APIs are removed and `const` is replaced by a single value `enum`.
+pub fn json_schema_inlined(schema: &Value, depth: usize) -> Value {
+    let defs = schema.get("$defs").cloned().unwrap_or_default();
+    inline(schema, &defs, depth)
+}
+
+/// Inline references within a schema
+fn inline(schema: &Value, defs: &Value, depth: usize) -> Value {
+    let Value::Object(object) = schema else {
+        return schema.clone();
+    };
+
+    if let Some(reference) = object.get("$ref").and_then(|value| value.as_str()) {
+        let title = reference.trim_start_matches("#/$defs/");
+        return match (depth, defs.get(title)) {
+            (1.., Some(def)) => inline(def, defs, depth - 1),
+            _ => json!({ "type": "object" }),
+        };
+    }
+
+    let mut inlined = Map::new();
+    for (key, value) in object {
+        match key.as_str() {
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), inline(property, defs, depth)))
+                    .collect();
+                inlined.insert(key.clone(), Value::Object(properties));
+            }
+            "items" => {
+                inlined.insert(key.clone(), inline(value, defs, depth));
+            }
+            "anyOf" => {
+                let any_of = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|item| inline(item, defs, depth))
+                    .collect();
+                inlined.insert(key.clone(), Value::Array(any_of));
+            }
+            "const" => {
+                inlined.insert("type".into(), json!("string"));
+                inlined.insert("enum".into(), json!([value]));
+            }
+            "required" | "type" | "description" | "enum" | "minimum" | "maximum" => {
+                inlined.insert(key.clone(), value.clone());
+            }
+            _ => {}
+        }
+    }
+
+    Value::Object(inlined)
+}
+
+/// Validate a JSON value against a structured output schema
+///
+/// Only supports the keywords used in the schemas generated by [`json_schema`]
+/// (i.e. those in `KEYWORDS`, the object and array keywords, `anyOf` and local
+/// `$ref`s). Returns the first error found with the JSON path to the invalid value.
+pub fn validate(schema: &Value, value: &Value) -> Result<()> {
+    let defs = schema.get("$defs").cloned().unwrap_or_default();
+    check(schema, &defs, value, "$")
+}
+
+/// Check a value against a subschema
+fn check(schema: &Value, defs: &Value, value: &Value, path: &str) -> Result<()> {
+    let Value::Object(schema) = schema else {
+        return Ok(());
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(|value| value.as_str()) {
+        let title = reference.trim_start_matches("#/$defs/");
+        let Some(def) = defs.get(title) else {
+            bail!("Unknown JSON Schema reference: {reference}")
+        };
+        return check(def, defs, value, path);
+    }
+
+    if let Some(any_of) = schema.get("anyOf").and_then(|value| value.as_array()) {
+        if !any_of
+            .iter()
+            .any(|item| check(item, defs, value, path).is_ok())
+        {
+            // Where possible, report the error for the alternative with the
+            // same `type` as the value, since that is the most informative
+            let alternative = value
+                .get("type")
+                .and_then(|name| name.as_str())
+                .and_then(|name| {
+                    any_of.iter().find(|item| {
+                        item.get("$ref")
+                            .and_then(|reference| reference.as_str())
+                            .map_or(false, |reference| {
+                                reference.trim_start_matches("#/$defs/") == name
+                            })
+                    })
+                });
+            if let Some(alternative) = alternative {
+                check(alternative, defs, value, path)?;
+            }
+            bail!("Value at `{path}` does not match any of the allowed schemas")
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            bail!("Value at `{path}` should be {expected} but is {value}")
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|value| value.as_array()) {
+        if !allowed.contains(value) {
+            bail!(
+                "Value at `{path}` should be one of {} but is {value}",
+                Value::Array(allowed.clone())
+            )
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(|name| name.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        let is = |name: &str| match name {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "string" => value.is_string(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            _ => true,
+        };
+        if !types.is_empty() && !types.iter().any(|name| is(name)) {
+            bail!(
+                "Value at `{path}` should be of type `{}` but is {value}",
+                types.join("` or `")
+            )
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(|value| value.as_f64()) {
+            if number < minimum {
+                bail!("Value at `{path}` should be at least {minimum} but is {number}")
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(|value| value.as_f64()) {
+            if number > maximum {
+                bail!("Value at `{path}` should be at most {maximum} but is {number}")
+            }
+        }
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema.get("properties").and_then(|value| value.as_object());
+
+        for name in schema
+            .get("required")
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.as_str())
+        {
+            if !object.contains_key(name) {
+                bail!("Object at `{path}` is missing required property `{name}`")
+            }
+        }
+
+        for (name, property) in object {
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(subschema) => check(subschema, defs, property, &[path, ".", name].concat())?,
+                None => {
+                    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                        bail!("Object at `{path}` has unexpected property `{name}`")
+                    }
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(subschema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            check(subschema, defs, item, &format!("{path}[{index}]"))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deserialize structured output generated by a model into a block of a node type
+///
+/// Any Markdown code fence around the JSON (which some models add even when asked
+/// not to) is removed. Errors if the JSON is invalid or does not conform to the
+/// schema of the node type. The error message is intended to be passed back to the
+/// model so that it can correct its output.
+pub fn block_from_json(node_type: NodeType, content: &str) -> Result<Block> {
+    let json = content.trim();
+    let json = json
+        .strip_prefix("```json")
+        .or_else(|| json.strip_prefix("```"))
+        .and_then(|json| json.strip_suffix("```"))
+        .unwrap_or(json);
+
+    let mut value: Value =
+        serde_json::from_str(json).map_err(|error| eyre!("Output is not valid JSON: {error}"))?;
+    remove_nulls(&mut value);
+    validate(&json_schema(node_type)?, &value)
+        .map_err(|error| eyre!("Output is not a valid `{node_type}`: {error}"))?;
+
+    // Deserialize into the specific type, rather than `Block`, for more
+    // informative error messages
+    let block = match node_type {
+        NodeType::Figure => serde_json::from_value::<Figure>(value).map(Block::Figure),
+        NodeType::Table => serde_json::from_value::<Table>(value).map(Block::Table),
+        _ => serde_json::from_value::<Block>(value),
+    }
+    .map_err(|error| eyre!("Output is not a valid `{node_type}`: {error}"))?;
+
+    if block.node_type() != node_type {
+        bail!(
+            "Output is a `{}` but should be a `{node_type}`",
+            block.node_type()
+        )
+    }
+
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_schema() -> Result<()> {
+        let schema = json_schema(NodeType::Table)?;
+
+        assert_eq!(schema["properties"]["type"], json!({ "const": "Table" }));
+        assert!(schema["properties"].get("rows").is_some());
+        assert!(schema["properties"].get("authors").is_none());
+        assert_eq!(schema["required"], json!(["type", "rows"]));
+
+        // All references are to local definitions
+        let defs = schema["$defs"].as_object().expect("should have defs");
+        assert!(defs.contains_key("TableRow"));
+        assert!(defs.contains_key("Paragraph"));
+        assert!(!defs.contains_key("CodeChunk"));
+        assert!(!serde_json::to_string(&schema)?.contains(".schema.json"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_from_json() -> Result<()> {
+        let json = r#"```json
+{
+  "type": "Table",
+  "rows": [
+    {
+      "type": "TableRow",
+      "cells": [
+        {
+          "type": "TableCell",
+          "content": [{ "type": "Paragraph", "content": [{ "type": "Text", "value": "A" }] }]
+        }
+      ]
+    }
+  ]
+}
+```"#;
+
+        let block = block_from_json(NodeType::Table, json)?;
+        assert_eq!(block.node_type(), NodeType::Table);
+
+        assert!(block_from_json(NodeType::Table, r#"{"type": "Table"}"#)
+            .is_err_and(|error| error.to_string().contains("rows")));
+        assert!(block_from_json(NodeType::Table, "| a | b |").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_table() -> Result<()> {
+        let schema = json_schema(NodeType::Table)?;
+
+        let row = |cell: Value| json!({ "type": "Table", "rows": [{ "type": "TableRow", "cells": [cell] }] });
+
+        validate(&schema, &row(json!({ "type": "TableCell", "content": [] })))?;
+
+        let error = validate(
+            &schema,
+            &row(json!({ "type": "TableCell", "content": [], "colour": "red" })),
+        )
+        .expect_err("should be invalid");
+        assert_eq!(
+            error.to_string(),
+            "Object at `$.rows[0].cells[0]` has unexpected property `colour`"
+        );
+
+        let error = validate(
+            &schema,
+            &row(json!({ "type": "TableCell", "content": [{ "type": "Paragraph", "content": [{ "type": "Text", "value": 1 }] }] })),
+        )
+        .expect_err("should be invalid");
+        assert!(error.to_string().contains(
+            "`$.rows[0].cells[0].content[0].content[0].value` should be of type `string`"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_schema_strict() -> Result<()> {
+        let schema = json_schema_strict(&json_schema(NodeType::Table)?);
+
+        assert!(schema.get("$schema").is_none());
+        assert!(schema["properties"].get("caption").is_some());
+        assert_eq!(
+            schema["required"],
+            json!(schema["properties"]
+                .as_object()
+                .expect("should have properties")
+                .keys()
+                .collect::<Vec<_>>())
+        );
+        assert_eq!(
+            schema["properties"]["caption"]["anyOf"][1],
+            json!({ "type": "null" })
+        );
+        assert_eq!(schema["properties"]["type"], json!({ "const": "Table" }));
+
+        // Nulls output for optional properties are removed
+        let block = block_from_json(
+            NodeType::Table,
+            r#"{"type": "Table", "caption": null, "rows": [{ "type": "TableRow", "cells": [], "rowType": null }]}"#,
+        )?;
+        assert_eq!(block.node_type(), NodeType::Table);
+
+        Ok(())
+    }
+
+    #[test]
+    fn table_schema_inlined() -> Result<()> {
+        let schema = json_schema_inlined(&json_schema(NodeType::Table)?, 4);
+        let json = serde_json::to_string(&schema)?;
+
+        assert!(!json.contains("$ref"));
+        assert!(!json.contains("$defs"));
+        assert!(!json.contains("additionalProperties"));
+        assert_eq!(
+            schema["properties"]["type"],
+            json!({ "type": "string", "enum": ["Table"] })
+        );
+        assert_eq!(
+            schema["properties"]["rows"]["items"]["properties"]["type"],
+            json!({ "type": "string", "enum": ["TableRow"] })
+        );
+
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use common::{
     serde::{Deserialize, Serialize},
+    serde_json::Value,
     serde_with::skip_serializing_none,
     smart_default::SmartDefault,
     strum::Display,
@@ -7,6 +8,8 @@ use common::{
 use format::Format;
 use schema::{InstructionMessage, InstructionType, ModelParameters};
 
+use crate::{ModelTool, ModelToolRound};
+
 /// The kind of generative model task
 #[derive(Debug, Default, Display, Clone, Copy, PartialEq, Deserialize, Serialize)]
 #[serde(crate = "common::serde")]
@@ -71,6 +74,23 @@ pub struct ModelTask {
     /// The desired format of the generated content
     pub format: Format,
 
+    /// The tools that the model may call
+    ///
+    /// Models that do not support tool calling ignore these.
+    pub tools: Vec<ModelTool>,
+
+    /// The rounds of tool calls, and their results, made so far
+    ///
+    /// Appended to the messages sent to the model.
+    pub tool_rounds: Vec<ModelToolRound>,
+
+    /// A JSON Schema that the generated content should conform to
+    ///
+    /// Models that support structured output use this to constrain generation
+    /// (or at least to generate JSON). For other models, the schema is included
+    /// in the messages of the task and the output should be validated.
+    pub json_schema: Option<Value>,
+
     /// Enable Mirostat sampling for controlling perplexity.
     ///
     /// Supported by Ollama.
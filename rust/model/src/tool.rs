@@ -0,0 +1,74 @@
+use common::{
+    serde::{Deserialize, Serialize},
+    serde_json::Value,
+};
+
+/// A tool that a model can call while performing a task
+///
+/// Tools are described to the model using a name, a description and a
+/// JSON Schema for their arguments. Model implementations map these to the
+/// equivalent in their respective APIs (e.g. OpenAI "functions" and Anthropic
+/// "tools").
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(crate = "common::serde")]
+pub struct ModelTool {
+    /// The name of the tool
+    pub name: String,
+
+    /// A description of the tool which helps the model decide when to call it
+    pub description: String,
+
+    /// A JSON Schema describing the arguments of the tool
+    pub parameters: Value,
+}
+
+/// A call to a tool made by a model
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(crate = "common::serde")]
+pub struct ModelToolCall {
+    /// The id of the call assigned by the model
+    ///
+    /// Used to associate the result of the call with the call.
+    pub id: String,
+
+    /// The name of the tool being called
+    pub name: String,
+
+    /// The arguments of the call
+    pub arguments: Value,
+}
+
+/// The result of a call to a tool
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(crate = "common::serde")]
+pub struct ModelToolResult {
+    /// The id of the call that this is the result of
+    pub call_id: String,
+
+    /// The name of the tool that was called
+    pub name: String,
+
+    /// The content of the result as text
+    pub content: String,
+
+    /// Whether the call failed
+    pub is_error: bool,
+}
+
+/// A round of tool calls made by a model, and their results
+///
+/// When a model responds with tool calls, the caller performs the calls and
+/// appends a round to the `tool_rounds` of the task before performing it again.
+/// Model implementations append these to the messages sent to the model.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(crate = "common::serde")]
+pub struct ModelToolRound {
+    /// Any text generated by the model alongside the tool calls
+    pub text: String,
+
+    /// The tool calls made by the model
+    pub calls: Vec<ModelToolCall>,
+
+    /// The results of each of the calls
+    pub results: Vec<ModelToolResult>,
+}
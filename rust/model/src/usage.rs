@@ -0,0 +1,217 @@
+use std::{
+    fmt::{self, Display},
+    ops::AddAssign,
+};
+
+use common::{
+    serde::{Deserialize, Serialize},
+    serde_with::skip_serializing_none,
+};
+
+/// The pricing of a model
+///
+/// Prices are in US dollars per million tokens.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct ModelPricing {
+    /// The price of tokens in the prompt
+    pub input: f64,
+
+    /// The price of tokens generated by the model
+    pub output: f64,
+}
+
+/// Prices of remote models
+///
+/// Each entry is a model id prefix, and the input and output prices for models
+/// whose id starts with it. More specific prefixes must come before less specific
+/// ones (e.g. `openai/gpt-4o-mini` before `openai/gpt-4o`).
+const PRICES: &[(&str, f64, f64)] = &[
+    ("anthropic/claude-3-5-haiku", 0.8, 4.0),
+    ("anthropic/claude-3-5-sonnet", 3.0, 15.0),
+    ("anthropic/claude-3-haiku", 0.25, 1.25),
+    ("anthropic/claude-3-opus", 15.0, 75.0),
+    ("anthropic/claude-3-sonnet", 3.0, 15.0),
+    ("google/gemini-1.5-flash", 0.075, 0.3),
+    ("google/gemini-1.5-pro", 1.25, 5.0),
+    ("google/gemini-1.0-pro", 0.5, 1.5),
+    ("mistral/codestral", 0.2, 0.6),
+    ("mistral/mistral-large", 2.0, 6.0),
+    ("mistral/mistral-medium", 2.7, 8.1),
+    ("mistral/mistral-small", 0.2, 0.6),
+    ("mistral/open-mistral-nemo", 0.15, 0.15),
+    ("mistral/open-mixtral-8x22b", 2.0, 6.0),
+    ("mistral/open-mixtral-8x7b", 0.7, 0.7),
+    ("openai/gpt-3.5-turbo", 0.5, 1.5),
+    ("openai/gpt-4o-mini", 0.15, 0.6),
+    ("openai/gpt-4o", 2.5, 10.0),
+    ("openai/gpt-4-turbo", 10.0, 30.0),
+    ("openai/gpt-4", 30.0, 60.0),
+    ("openai/o1-mini", 3.0, 12.0),
+    ("openai/o1", 15.0, 60.0),
+];
+
+impl ModelPricing {
+    /// Pricing for models which are free to use (e.g. local models)
+    pub const FREE: Self = Self {
+        input: 0.0,
+        output: 0.0,
+    };
+
+    /// Get the pricing of a model from its id
+    ///
+    /// Returns `None` if the model is not in the pricing table.
+    pub fn for_id(id: &str) -> Option<Self> {
+        PRICES
+            .iter()
+            .find(|(prefix, ..)| id.starts_with(prefix))
+            .map(|(.., input, output)| Self {
+                input: *input,
+                output: *output,
+            })
+    }
+
+    /// Calculate the cost of a number of prompt and completion tokens
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+        (prompt_tokens as f64 * self.input + completion_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// The usage of a model, or models, for one or more tasks
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct ModelUsage {
+    /// The number of tokens in the prompts
+    pub prompt_tokens: u64,
+
+    /// The number of tokens generated
+    pub completion_tokens: u64,
+
+    /// The estimated cost, in US dollars
+    ///
+    /// `None` if the pricing of the models used is unknown.
+    pub cost: Option<f64>,
+
+    /// The number of tokens used by models whose pricing is unknown
+    ///
+    /// These tokens are not included in the `cost` so, if this is not zero,
+    /// the `cost` is an underestimate.
+    #[serde(default)]
+    pub unpriced_tokens: u64,
+}
+
+impl ModelUsage {
+    /// Create a new usage with the cost calculated from a model's pricing
+    pub fn new(pricing: Option<ModelPricing>, prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self::with_cost(
+            prompt_tokens,
+            completion_tokens,
+            pricing.map(|pricing| pricing.cost(prompt_tokens, completion_tokens)),
+        )
+    }
+
+    /// Create a new usage from counts of tokens and their cost, if known
+    ///
+    /// Used when the usage has been recorded elsewhere (e.g. on a suggestion).
+    /// If the cost is unknown, all of the tokens are counted as unpriced.
+    pub fn with_cost(prompt_tokens: u64, completion_tokens: u64, cost: Option<f64>) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+            cost,
+            unpriced_tokens: if cost.is_none() {
+                prompt_tokens + completion_tokens
+            } else {
+                0
+            },
+        }
+    }
+
+    /// Create an estimate of usage from the length of the prompt and generated text
+    ///
+    /// Used when a model does not report token counts at the end of a stream. Uses the
+    /// common heuristic of four characters per token.
+    pub fn estimate(pricing: Option<ModelPricing>, prompt: &str, completion: &str) -> Self {
+        let tokens = |text: &str| (text.chars().count() as u64).div_ceil(4);
+        Self::new(pricing, tokens(prompt), tokens(completion))
+    }
+
+    /// The total number of tokens
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl Display for ModelUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} prompt tokens, {} completion tokens",
+            self.prompt_tokens, self.completion_tokens
+        )?;
+
+        match (self.cost, self.unpriced_tokens) {
+            (Some(cost), 0) => write!(f, ", estimated cost ${cost:.4}"),
+            (Some(cost), unpriced) => write!(
+                f,
+                ", estimated cost ${cost:.4} excluding {unpriced} tokens with unknown pricing"
+            ),
+            (None, ..) => write!(f, ", unknown cost"),
+        }
+    }
+}
+
+impl AddAssign<&ModelUsage> for ModelUsage {
+    fn add_assign(&mut self, other: &ModelUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.unpriced_tokens += other.unpriced_tokens;
+
+        // Unknown costs are ignored so that the cost is that of the usages with known
+        // pricing (the tokens of the others are counted in `unpriced_tokens`)
+        self.cost = match (self.cost, other.cost) {
+            (Some(cost), Some(other)) => Some(cost + other),
+            (cost, other) => cost.or(other),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pricing_for_id() {
+        assert_eq!(
+            ModelPricing::for_id("openai/gpt-4o-mini-2024-07-18").map(|pricing| pricing.input),
+            Some(0.15)
+        );
+        assert_eq!(
+            ModelPricing::for_id("openai/gpt-4o-2024-08-06").map(|pricing| pricing.input),
+            Some(2.5)
+        );
+        assert_eq!(ModelPricing::for_id("unknown/model"), None);
+    }
+
+    #[test]
+    fn add_usage() {
+        let pricing = ModelPricing {
+            input: 1.0,
+            output: 2.0,
+        };
+
+        let mut usage = ModelUsage::default();
+        usage += &ModelUsage::new(Some(pricing), 1_000_000, 500_000);
+        usage += &ModelUsage::new(None, 10, 10);
+
+        assert_eq!(usage.prompt_tokens, 1_000_010);
+        assert_eq!(usage.completion_tokens, 500_010);
+        assert_eq!(usage.cost, Some(2.0));
+        assert_eq!(usage.unpriced_tokens, 20);
+        assert_eq!(
+            usage.to_string(),
+            "1000010 prompt tokens, 500010 completion tokens, estimated cost $2.0000 excluding 20 tokens with unknown pricing"
+        );
+    }
+}
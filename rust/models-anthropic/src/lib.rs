@@ -5,15 +5,18 @@ use cached::proc_macro::cached;
 use model::{
     common::{
         async_trait::async_trait,
-        eyre::{bail, Result},
+        eyre::{bail, eyre, Result},
+        futures::{stream, StreamExt},
         itertools::Itertools,
-        reqwest::Client,
+        reqwest::{Client, Response},
         serde::{Deserialize, Serialize},
+        serde_json::{self, Value},
         serde_with::skip_serializing_none,
         tracing,
     },
     schema::{ImageObject, MessagePart, MessageRole},
-    secrets, Model, ModelIO, ModelOutput, ModelTask, ModelType,
+    secrets, sse_data, Model, ModelApiError, ModelIO, ModelOutput, ModelStream, ModelStreamItem,
+    ModelTask, ModelToolCall, ModelType, ModelUsage,
 };
 
 /// The base URL for the Anthropic API
@@ -49,33 +52,11 @@ impl AnthropicModel {
             client: Client::new(),
         }
     }
-}
 
-#[async_trait]
-impl Model for AnthropicModel {
-    fn id(&self) -> String {
-        format!("anthropic/{}", self.model)
-    }
-
-    fn r#type(&self) -> ModelType {
-        ModelType::Remote
-    }
-
-    fn context_length(&self) -> usize {
-        self.context_length
-    }
-
-    fn supported_inputs(&self) -> &[ModelIO] {
-        &[ModelIO::Text]
-    }
-
-    fn supported_outputs(&self) -> &[ModelIO] {
-        &[ModelIO::Text]
-    }
-
-    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
+    /// Create a Messages API request for a task
+    fn request(&self, task: &ModelTask, stream: bool) -> MessagesRequest {
         let mut system = None;
-        let messages = task
+        let mut messages = task
             .messages
             .iter()
             .filter_map(|message| {
@@ -146,7 +127,51 @@ impl Model for AnthropicModel {
             })
             .collect_vec();
 
-        let request = MessagesRequest {
+        // Add an assistant message with the tool calls, and a user message with the
+        // results of those calls, for each round of tool calls
+        for round in &task.tool_rounds {
+            let mut content = Vec::new();
+            if !round.text.is_empty() {
+                content.push(ContentPart::Text {
+                    text: round.text.clone(),
+                });
+            }
+            content.extend(round.calls.iter().map(|call| ContentPart::ToolUse {
+                id: call.id.clone(),
+                name: call.name.clone(),
+                input: call.arguments.clone(),
+            }));
+            messages.push(Message {
+                role: "assistant".into(),
+                content,
+            });
+
+            let content = round
+                .results
+                .iter()
+                .map(|result| ContentPart::ToolResult {
+                    tool_use_id: result.call_id.clone(),
+                    content: result.content.clone(),
+                    is_error: result.is_error,
+                })
+                .collect();
+            messages.push(Message {
+                role: "user".into(),
+                content,
+            });
+        }
+
+        let tools = task
+            .tools
+            .iter()
+            .map(|tool| Tool {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+                input_schema: tool.parameters.clone(),
+            })
+            .collect_vec();
+
+        MessagesRequest {
             model: self.model.clone(),
             messages,
             system,
@@ -156,38 +181,115 @@ impl Model for AnthropicModel {
             temperature: task.temperature,
             top_k: task.top_k,
             top_p: task.top_p,
-        };
-
-        if task.dry_run {
-            return ModelOutput::empty(self);
+            stream: stream.then_some(true),
+            tools: (!tools.is_empty()).then_some(tools),
         }
+    }
 
+    /// Send a Messages API request
+    async fn send(&self, request: &MessagesRequest) -> Result<Response> {
         let response = self
             .client
             .post(format!("{BASE_URL}/messages/"))
             .header("x-api-key", secrets::env_or_get(API_KEY)?)
             .header("anthropic-version", API_VERSION)
-            .json(&request)
+            .json(request)
             .send()
             .await?;
 
-        if let Err(error) = response.error_for_status_ref() {
-            let message = response.text().await?;
-            bail!("{error}: {message}");
+        ModelApiError::check(response).await
+    }
+}
+
+#[async_trait]
+impl Model for AnthropicModel {
+    fn id(&self) -> String {
+        format!("anthropic/{}", self.model)
+    }
+
+    fn r#type(&self) -> ModelType {
+        ModelType::Remote
+    }
+
+    fn context_length(&self) -> usize {
+        self.context_length
+    }
+
+    fn supported_inputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    fn supported_outputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
+        let request = self.request(task, false);
+
+        if task.dry_run {
+            return ModelOutput::empty(self);
         }
 
-        let response: MessagesResponse = response.json().await?;
+        let response: MessagesResponse = self.send(&request).await?.json().await?;
+
+        let mut texts = Vec::new();
+        let mut tool_calls = Vec::new();
+        for part in response.content {
+            match part {
+                ContentPart::Text { text } => texts.push(text),
+                ContentPart::ToolUse { id, name, input } => tool_calls.push(ModelToolCall {
+                    id,
+                    name,
+                    arguments: input,
+                }),
+                _ => {}
+            }
+        }
 
-        let text = response
-            .content
-            .into_iter()
-            .filter_map(|part| match part {
-                ContentPart::Text { text } => Some(text),
-                _ => None,
-            })
-            .join("\n\n");
+        let mut output = ModelOutput::from_text(self, &task.format, texts.join("\n\n")).await?;
+        output.tool_calls = tool_calls;
+        output.usage = response
+            .usage
+            .map(|usage| self.usage(usage.input_tokens, usage.output_tokens));
 
-        ModelOutput::from_text(self, &task.format, text).await
+        Ok(output)
+    }
+
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        let request = self.request(task, true);
+
+        if task.dry_run {
+            return Ok(stream::empty().boxed());
+        }
+
+        let response = self.send(&request).await?;
+
+        // The number of input tokens is reported at the start of the stream and
+        // the (cumulative) number of output tokens in each message delta
+        let pricing = self.pricing();
+        let mut input_tokens = 0;
+        let stream = sse_data(response).filter_map(move |data| {
+            let item = match data.and_then(|data| Ok(serde_json::from_str::<StreamEvent>(&data)?)) {
+                Ok(StreamEvent::MessageStart { message }) => {
+                    input_tokens = message.usage.input_tokens;
+                    None
+                }
+                Ok(StreamEvent::ContentBlockDelta { delta }) => {
+                    delta.text.map(|text| Ok(ModelStreamItem::Text(text)))
+                }
+                Ok(StreamEvent::MessageDelta { usage }) => Some(Ok(ModelStreamItem::Usage(
+                    ModelUsage::new(pricing, input_tokens, usage.output_tokens),
+                ))),
+                Ok(StreamEvent::Error { error }) => {
+                    Some(Err(eyre!("{}: {}", error.r#type, error.message)))
+                }
+                Ok(StreamEvent::Other) => None,
+                Err(error) => Some(Err(error)),
+            };
+            async move { item }
+        });
+
+        Ok(stream.boxed())
     }
 }
 
@@ -259,8 +361,24 @@ struct ModelSpec {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase", crate = "model::common::serde")]
 enum ContentPart {
-    Text { text: String },
-    Image { source: ImageSource },
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    #[serde(rename = "tool_use")]
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    #[serde(rename = "tool_result")]
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        is_error: bool,
+    },
 }
 
 /// An images source
@@ -272,6 +390,17 @@ struct ImageSource {
     data: String,
 }
 
+/// A tool definition in a Messages API request
+///
+/// Based on https://docs.anthropic.com/en/docs/build-with-claude/tool-use.
+#[derive(Debug, Serialize)]
+#[serde(crate = "model::common::serde")]
+struct Tool {
+    name: String,
+    description: String,
+    input_schema: Value,
+}
+
 /// A Messages API message
 ///
 /// Note: at present only text content is handled
@@ -297,6 +426,8 @@ struct MessagesRequest {
     temperature: Option<f32>,
     top_k: Option<u32>,
     top_p: Option<f32>,
+    stream: Option<bool>,
+    tools: Option<Vec<Tool>>,
 }
 
 /// A Messages API response body
@@ -308,12 +439,97 @@ struct MessagesRequest {
 #[serde(crate = "model::common::serde")]
 struct MessagesResponse {
     content: Vec<ContentPart>,
+    usage: Option<MessagesUsage>,
+}
+
+/// The token usage of a Messages API response
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct MessagesUsage {
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// A server-sent event when streaming a Messages API response
+///
+/// Based on https://docs.anthropic.com/en/api/messages-streaming.
+/// Note: only the usage, text deltas and errors are handled, other events are ignored.
+#[derive(Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    crate = "model::common::serde"
+)]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    MessageDelta {
+        usage: StreamUsage,
+    },
+    Error {
+        error: StreamError,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// The message of a `message_start` event
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct StreamMessage {
+    usage: MessagesUsage,
+}
+
+/// The usage of a `message_delta` event
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct StreamUsage {
+    output_tokens: u64,
+}
+
+/// The delta of a `content_block_delta` event
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct StreamDelta {
+    text: Option<String>,
+}
+
+/// The error of an `error` event
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct StreamError {
+    r#type: String,
+    message: String,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use model::{common::tokio, test_task_repeat_word};
+    use model::{common::tokio, test_collect_stream, test_task_repeat_word};
+
+    #[test]
+    fn stream_events() -> Result<()> {
+        let event: StreamEvent = serde_json::from_str(
+            r#"{"type":"message_start","message":{"id":"msg_1","usage":{"input_tokens":25,"output_tokens":1}}}"#,
+        )?;
+        assert!(
+            matches!(event, StreamEvent::MessageStart { message } if message.usage.input_tokens == 25)
+        );
+
+        let event: StreamEvent = serde_json::from_str(
+            r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":15}}"#,
+        )?;
+        assert!(matches!(event, StreamEvent::MessageDelta { usage } if usage.output_tokens == 15));
+
+        let event: StreamEvent = serde_json::from_str(r#"{"type":"ping"}"#)?;
+        assert!(matches!(event, StreamEvent::Other));
+
+        Ok(())
+    }
 
     #[tokio::test]
     async fn list_models() -> Result<()> {
@@ -341,4 +557,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn perform_task_stream() -> Result<()> {
+        if secrets::env_or_get(API_KEY).is_err() {
+            return Ok(());
+        }
+
+        let model = AnthropicModel::new("claude-3-5-sonnet-20240620", 0);
+        let (text, usage) =
+            test_collect_stream(model.perform_task_stream(&test_task_repeat_word()).await?).await?;
+
+        assert_eq!(text.trim(), "HELLO");
+        assert!(usage.is_some());
+
+        Ok(())
+    }
 }
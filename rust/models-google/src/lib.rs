@@ -6,14 +6,18 @@ use model::{
     common::{
         async_trait::async_trait,
         eyre::{bail, Result},
+        futures::{stream, StreamExt},
         itertools::Itertools,
-        reqwest::Client,
+        reqwest::{Client, Response},
         serde::{Deserialize, Serialize},
+        serde_json,
         serde_with::skip_serializing_none,
         tracing,
     },
+    json_schema_inlined,
     schema::{ImageObject, MessagePart, MessageRole},
-    secrets, Model, ModelIO, ModelOutput, ModelTask, ModelType,
+    secrets, sse_data, Model, ModelApiError, ModelIO, ModelOutput, ModelStream, ModelStreamItem,
+    ModelTask, ModelType, ModelUsage,
 };
 
 const BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
@@ -41,36 +45,13 @@ impl GoogleModel {
             client: Client::new(),
         }
     }
-}
-
-#[async_trait]
-impl Model for GoogleModel {
-    fn id(&self) -> String {
-        format!("google/{}", self.model)
-    }
-
-    fn r#type(&self) -> ModelType {
-        ModelType::Remote
-    }
-
-    fn context_length(&self) -> usize {
-        self.context_length
-    }
 
-    fn supported_inputs(&self) -> &[ModelIO] {
-        use ModelIO::*;
-        match self.model.as_str() {
-            "gemini-pro-vision" => &[Text, Video],
-            _ => &[Text],
+    /// Create a generate content request for a task
+    fn request(&self, task: &ModelTask) -> GenerateContentRequest {
+        if !task.tools.is_empty() {
+            tracing::warn!("Tools are not yet supported by model `{}`", self.id());
         }
-    }
-
-    fn supported_outputs(&self) -> &[ModelIO] {
-        &[ModelIO::Text]
-    }
 
-    #[tracing::instrument(skip(self))]
-    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
         let mut system_instruction = None;
         let contents = task
             .messages
@@ -134,7 +115,7 @@ impl Model for GoogleModel {
             })
             .collect_vec();
 
-        let request = GenerateContentRequest {
+        GenerateContentRequest {
             contents,
             system_instruction,
             generation_config: Some(GenerationConfig {
@@ -142,31 +123,87 @@ impl Model for GoogleModel {
                 temperature: task.temperature,
                 top_p: task.top_p,
                 top_k: task.top_k,
+                // The response schema supported by the API does not allow references
+                // so they are inlined (up to a depth which keeps the schema to a reasonable
+                // size). The output is also validated by the caller against the full schema.
+                response_mime_type: task
+                    .json_schema
+                    .is_some()
+                    .then(|| "application/json".to_string()),
+                response_schema: task
+                    .json_schema
+                    .as_ref()
+                    .map(|schema| json_schema_inlined(schema, 6)),
                 ..Default::default()
             }),
-        };
+        }
+    }
 
-        if task.dry_run {
-            return ModelOutput::empty(self);
+    /// Send a request to a method of the model
+    ///
+    /// Used for both the `generateContent` and `streamGenerateContent` methods.
+    async fn send(
+        &self,
+        method: &str,
+        request: &GenerateContentRequest,
+        stream: bool,
+    ) -> Result<Response> {
+        let mut query = vec![("key", secrets::env_or_get(API_KEY)?)];
+        if stream {
+            query.push(("alt", "sse".to_string()));
         }
 
         let response = self
             .client
-            .post(format!(
-                "{}/models/{}:generateContent",
-                BASE_URL, self.model
-            ))
-            .query(&[("key", secrets::env_or_get(API_KEY)?)])
-            .json(&request)
+            .post(format!("{}/models/{}:{method}", BASE_URL, self.model))
+            .query(&query)
+            .json(request)
             .send()
             .await?;
 
-        if let Err(error) = response.error_for_status_ref() {
-            let message = response.text().await?;
-            bail!("{error}: {message}");
+        ModelApiError::check(response).await
+    }
+}
+
+#[async_trait]
+impl Model for GoogleModel {
+    fn id(&self) -> String {
+        format!("google/{}", self.model)
+    }
+
+    fn r#type(&self) -> ModelType {
+        ModelType::Remote
+    }
+
+    fn context_length(&self) -> usize {
+        self.context_length
+    }
+
+    fn supported_inputs(&self) -> &[ModelIO] {
+        use ModelIO::*;
+        match self.model.as_str() {
+            "gemini-pro-vision" => &[Text, Video],
+            _ => &[Text],
+        }
+    }
+
+    fn supported_outputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
+        let request = self.request(task);
+
+        if task.dry_run {
+            return ModelOutput::empty(self);
         }
 
-        let mut response: GenerateContentResponse = response.json().await?;
+        let mut response: GenerateContentResponse = self
+            .send("generateContent", &request, false)
+            .await?
+            .json()
+            .await?;
 
         let content = response
             .candidates
@@ -175,19 +212,79 @@ impl Model for GoogleModel {
             .parts
             .swap_remove(0);
 
-        match content {
+        let mut output = match content {
             Part {
                 text: Some(text), ..
-            } => ModelOutput::from_text(self, &task.format, text).await,
+            } => ModelOutput::from_text(self, &task.format, text).await?,
             Part {
                 inline_data: Some(Blob { mime_type, data }),
                 ..
             } => {
                 ModelOutput::from_url(self, &mime_type, format!("{};base64,{}", mime_type, data))
-                    .await
+                    .await?
             }
             _ => bail!("Unexpected response content part"),
+        };
+        output.usage = response.usage_metadata.map(|usage| {
+            self.usage(
+                usage.prompt_token_count,
+                usage.candidates_token_count.unwrap_or_default(),
+            )
+        });
+
+        Ok(output)
+    }
+
+    #[tracing::instrument(skip(self))]
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        let request = self.request(task);
+
+        if task.dry_run {
+            return Ok(stream::empty().boxed());
         }
+
+        let response = self.send("streamGenerateContent", &request, true).await?;
+
+        // Each chunk may have usage metadata, with that of the final chunk being
+        // for the complete response
+        let pricing = self.pricing();
+        let stream = sse_data(response).flat_map(move |data| {
+            let items = match data
+                .and_then(|data| Ok(serde_json::from_str::<GenerateContentResponse>(&data)?))
+            {
+                Ok(response) => {
+                    let text = response
+                        .candidates
+                        .into_iter()
+                        .next()
+                        .map(|candidate| {
+                            candidate
+                                .content
+                                .parts
+                                .into_iter()
+                                .filter_map(|part| part.text)
+                                .join("")
+                        })
+                        .unwrap_or_default();
+
+                    let usage = response.usage_metadata.map(|usage| {
+                        Ok(ModelStreamItem::Usage(ModelUsage::new(
+                            pricing,
+                            usage.prompt_token_count,
+                            usage.candidates_token_count.unwrap_or_default(),
+                        )))
+                    });
+
+                    std::iter::once(Ok(ModelStreamItem::Text(text)))
+                        .chain(usage)
+                        .collect_vec()
+                }
+                Err(error) => vec![Err(error)],
+            };
+            stream::iter(items)
+        });
+
+        Ok(stream.boxed())
     }
 }
 
@@ -227,11 +324,25 @@ struct GenerateContentRequest {
 /// A generate content response
 ///
 /// Based on https://ai.google.dev/api/rest/v1beta/GenerateContentResponse.
-/// Note: at present the `promptFeedback` field ignored.
+/// Note: at present the `promptFeedback` field ignored. When streaming, the
+/// final chunk may not have any candidates.
 #[derive(Deserialize)]
-#[serde(crate = "model::common::serde")]
+#[serde(rename_all = "camelCase", crate = "model::common::serde")]
 struct GenerateContentResponse {
+    #[serde(default)]
     candidates: Vec<Candidate>,
+    usage_metadata: Option<UsageMetadata>,
+}
+
+/// The token usage of a generate content response
+///
+/// Based on https://ai.google.dev/api/generate-content#UsageMetadata.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", crate = "model::common::serde")]
+struct UsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u64,
+    candidates_token_count: Option<u64>,
 }
 
 /// A candidate in a generate content response
@@ -322,6 +433,8 @@ struct GenerationConfig {
     temperature: Option<f32>,
     top_p: Option<f32>,
     top_k: Option<u32>,
+    response_mime_type: Option<String>,
+    response_schema: Option<serde_json::Value>,
 }
 
 /// Get a list of available Google AI assistants
@@ -391,7 +504,7 @@ async fn list_google_models() -> Result<ModelsResponse> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use model::{common::tokio, test_task_repeat_word};
+    use model::{common::tokio, test_collect_stream, test_task_repeat_word};
 
     #[tokio::test]
     async fn list_models() -> Result<()> {
@@ -419,4 +532,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn perform_task_stream() -> Result<()> {
+        if secrets::env_or_get(API_KEY).is_err() {
+            return Ok(());
+        }
+
+        let model = GoogleModel::new("gemini-1.5-pro-001", 0);
+        let (text, usage) =
+            test_collect_stream(model.perform_task_stream(&test_task_repeat_word()).await?).await?;
+
+        assert_eq!(text.trim(), "HELLO");
+        assert!(usage.is_some());
+
+        Ok(())
+    }
 }
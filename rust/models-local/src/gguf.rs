@@ -0,0 +1,236 @@
+//! Reading of metadata from GGUF model files
+//!
+//! Only the key-value metadata section at the start of the file is read so
+//! that information about a model (e.g. its context length) can be obtained
+//! without loading the model's tensors.
+//!
+//! See https://github.com/ggerganov/ggml/blob/master/docs/gguf.md for the format.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use model::common::eyre::{bail, Result};
+
+/// Metadata read from a GGUF file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metadata {
+    /// The architecture of the model e.g. `llama`
+    pub architecture: Option<String>,
+
+    /// The name of the model (if specified in the file)
+    pub name: Option<String>,
+
+    /// The context length that the model was trained with
+    pub context_length: Option<usize>,
+}
+
+/// Read the metadata from a GGUF file
+pub fn read_metadata(path: &Path) -> Result<Metadata> {
+    let file = File::open(path)?;
+    read(&mut BufReader::new(file))
+}
+
+/// Read the metadata from a reader of a GGUF file
+fn read<R: Read>(reader: &mut R) -> Result<Metadata> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"GGUF" {
+        bail!("Not a GGUF file")
+    }
+
+    let version = read_u32(reader)?;
+    if version < 2 {
+        bail!("Unsupported GGUF version {version}")
+    }
+
+    let _tensor_count = read_u64(reader)?;
+    let kv_count = read_u64(reader)?;
+
+    let mut metadata = Metadata::default();
+    let mut context_lengths = Vec::new();
+    for _ in 0..kv_count {
+        let key = read_string(reader)?;
+        let value_type = read_u32(reader)?;
+
+        if key == "general.architecture" && value_type == STRING {
+            metadata.architecture = Some(read_string(reader)?);
+        } else if key == "general.name" && value_type == STRING {
+            metadata.name = Some(read_string(reader)?);
+        } else if key.ends_with(".context_length") {
+            match read_integer(reader, value_type)? {
+                Some(length) => context_lengths.push((key, length)),
+                None => skip_value(reader, value_type)?,
+            }
+        } else {
+            skip_value(reader, value_type)?;
+        }
+    }
+
+    // Prefer the context length for the architecture, falling back to any
+    metadata.context_length = context_lengths
+        .iter()
+        .find(|(key, ..)| {
+            metadata
+                .architecture
+                .as_ref()
+                .map_or(false, |arch| key == &[arch, ".context_length"].concat())
+        })
+        .or(context_lengths.first())
+        .map(|(.., length)| *length);
+
+    Ok(metadata)
+}
+
+// Value types as defined in the GGUF spec
+const UINT8: u32 = 0;
+const INT8: u32 = 1;
+const UINT16: u32 = 2;
+const INT16: u32 = 3;
+const UINT32: u32 = 4;
+const INT32: u32 = 5;
+const FLOAT32: u32 = 6;
+const BOOL: u32 = 7;
+const STRING: u32 = 8;
+const ARRAY: u32 = 9;
+const UINT64: u32 = 10;
+const INT64: u32 = 11;
+const FLOAT64: u32 = 12;
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let length = read_u64(reader)?;
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        bail!("Unexpected end of GGUF file")
+    }
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// Read an unsigned integer value, returning `None` if the value is not an unsigned integer
+fn read_integer<R: Read>(reader: &mut R, value_type: u32) -> Result<Option<usize>> {
+    Ok(match value_type {
+        UINT32 => Some(read_u32(reader)? as usize),
+        UINT64 => Some(read_u64(reader)? as usize),
+        _ => None,
+    })
+}
+
+/// The size, in bytes, of values of fixed size types
+fn fixed_size(value_type: u32) -> Option<u64> {
+    match value_type {
+        UINT8 | INT8 | BOOL => Some(1),
+        UINT16 | INT16 => Some(2),
+        UINT32 | INT32 | FLOAT32 => Some(4),
+        UINT64 | INT64 | FLOAT64 => Some(8),
+        _ => None,
+    }
+}
+
+/// Skip over a number of bytes
+fn skip<R: Read>(reader: &mut R, bytes: u64) -> Result<()> {
+    let skipped = std::io::copy(&mut reader.take(bytes), &mut std::io::sink())?;
+    if skipped != bytes {
+        bail!("Unexpected end of GGUF file")
+    }
+    Ok(())
+}
+
+/// Skip over a value of the given type
+fn skip_value<R: Read>(reader: &mut R, value_type: u32) -> Result<()> {
+    if let Some(size) = fixed_size(value_type) {
+        return skip(reader, size);
+    }
+
+    match value_type {
+        STRING => {
+            let length = read_u64(reader)?;
+            skip(reader, length)
+        }
+        ARRAY => {
+            let item_type = read_u32(reader)?;
+            let length = read_u64(reader)?;
+            if let Some(size) = fixed_size(item_type) {
+                skip(reader, size * length)
+            } else {
+                for _ in 0..length {
+                    skip_value(reader, item_type)?;
+                }
+                Ok(())
+            }
+        }
+        _ => bail!("Unknown GGUF value type {value_type}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create the bytes of a GGUF string
+    fn string(value: &str) -> Vec<u8> {
+        [&(value.len() as u64).to_le_bytes(), value.as_bytes()].concat()
+    }
+
+    #[test]
+    fn read_metadata() -> Result<()> {
+        let mut bytes = b"GGUF".to_vec();
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(0u64.to_le_bytes());
+        bytes.extend(5u64.to_le_bytes());
+
+        bytes.extend(string("general.architecture"));
+        bytes.extend(STRING.to_le_bytes());
+        bytes.extend(string("llama"));
+
+        bytes.extend(string("general.name"));
+        bytes.extend(STRING.to_le_bytes());
+        bytes.extend(string("Tiny Llama"));
+
+        bytes.extend(string("tokenizer.ggml.tokens"));
+        bytes.extend(ARRAY.to_le_bytes());
+        bytes.extend(STRING.to_le_bytes());
+        bytes.extend(2u64.to_le_bytes());
+        bytes.extend(string("<s>"));
+        bytes.extend(string("</s>"));
+
+        bytes.extend(string("tokenizer.ggml.scores"));
+        bytes.extend(ARRAY.to_le_bytes());
+        bytes.extend(FLOAT32.to_le_bytes());
+        bytes.extend(2u64.to_le_bytes());
+        bytes.extend(0f32.to_le_bytes());
+        bytes.extend(0f32.to_le_bytes());
+
+        bytes.extend(string("llama.context_length"));
+        bytes.extend(UINT32.to_le_bytes());
+        bytes.extend(2048u32.to_le_bytes());
+
+        let metadata = read(&mut bytes.as_slice())?;
+        assert_eq!(
+            metadata,
+            Metadata {
+                architecture: Some("llama".to_string()),
+                name: Some("Tiny Llama".to_string()),
+                context_length: Some(2048)
+            }
+        );
+
+        assert!(read(&mut b"GGML".as_slice()).is_err());
+
+        Ok(())
+    }
+}
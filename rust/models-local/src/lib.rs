@@ -0,0 +1,434 @@
+use std::{
+    collections::HashMap,
+    fs::read_dir,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use app::{get_app_dir, DirType};
+use cached::proc_macro::cached;
+use llama_cpp_2::{
+    context::params::LlamaContextParams,
+    llama_backend::LlamaBackend,
+    llama_batch::LlamaBatch,
+    model::{params::LlamaModelParams, AddBos, LlamaChatMessage, LlamaModel, Special},
+    sampling::LlamaSampler,
+};
+
+use model::{
+    common::{
+        async_trait::async_trait,
+        eyre::{bail, eyre, Result},
+        futures::{stream, StreamExt},
+        once_cell::sync::{Lazy, OnceCell},
+        rand,
+        tokio::{sync::mpsc, task::spawn_blocking},
+        tracing,
+    },
+    schema::{MessagePart, MessageRole},
+    Model, ModelAvailability, ModelIO, ModelOutput, ModelStream, ModelStreamItem, ModelTask,
+    ModelType,
+};
+
+mod gguf;
+
+/// The default size of the context window used for inference
+///
+/// Used if the task does not specify `num_ctx`. Capped to the context length
+/// of the model. Using the full context length of a model can require a lot of
+/// memory so a smaller default is used.
+const DEFAULT_CONTEXT: usize = 4096;
+
+/// A GGUF model file in the Stencila models directory which is run in-process on CPU
+///
+/// Models are loaded from the `models` directory in the Stencila config directory
+/// (or `STENCILA_MODELS_DIR` if set). To add a model, download a GGUF file (e.g. from
+/// Hugging Face) into that directory. A model is listed for each `.gguf` file.
+/// Inference uses llama.cpp so any model architecture supported by it can be used.
+#[derive(Clone)]
+pub struct LocalModel {
+    /// The name of the model (the stem of the GGUF file name)
+    name: String,
+
+    /// The path to the GGUF file
+    path: PathBuf,
+
+    /// The context length of the model
+    ///
+    /// Read from the metadata of the GGUF file. Zero if that could not be read,
+    /// in which case the model is unavailable.
+    context_length: usize,
+}
+
+impl LocalModel {
+    /// Create a local model from a GGUF file
+    pub fn new(path: &Path) -> Self {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let context_length = match gguf::read_metadata(path) {
+            Ok(metadata) => metadata.context_length.unwrap_or(DEFAULT_CONTEXT),
+            Err(error) => {
+                tracing::warn!("While reading GGUF file `{}`: {error}", path.display());
+                0
+            }
+        };
+
+        Self {
+            name,
+            path: path.to_path_buf(),
+            context_length,
+        }
+    }
+
+    /// Render the messages of a task into a prompt using the model's chat template
+    fn prompt(&self, model: &LlamaModel, task: &ModelTask) -> Result<String> {
+        if !task.tools.is_empty() {
+            tracing::warn!("Tools are not yet supported by model `{}`", self.id());
+        }
+
+        let mut messages = Vec::new();
+        for message in &task.messages {
+            let role = match message.role.clone().unwrap_or_default() {
+                MessageRole::System => "system",
+                MessageRole::User => "user",
+                MessageRole::Model => "assistant",
+            };
+
+            let mut content = String::new();
+            for part in &message.parts {
+                match part {
+                    MessagePart::Text(text) => content += &text.value,
+                    _ => {
+                        tracing::warn!("Message part `{part}` is ignored by model `{}`", self.id())
+                    }
+                }
+            }
+
+            messages.push(LlamaChatMessage::new(role.to_string(), content)?);
+        }
+
+        let template = model.get_chat_template()?;
+        Ok(model.apply_chat_template(&template, &messages, true)?)
+    }
+
+    /// Perform a task, calling `on_text` with each piece of text generated
+    ///
+    /// Blocks while the model is loaded and inference is performed so should be
+    /// called within `spawn_blocking`. Generation stops early if `on_text` returns `false`.
+    /// Returns the generated text and the number of tokens in the prompt and generated.
+    fn generate<F>(&self, task: &ModelTask, mut on_text: F) -> Result<(String, u64, u64)>
+    where
+        F: FnMut(&str) -> bool,
+    {
+        macro_rules! ignore_option {
+            ($name:ident) => {
+                if task.$name.is_some() {
+                    tracing::warn!(
+                        "Option `{}` is ignored by model `{}`",
+                        stringify!($name),
+                        self.name()
+                    )
+                }
+            };
+        }
+        ignore_option!(mirostat);
+        ignore_option!(mirostat_eta);
+        ignore_option!(mirostat_tau);
+        ignore_option!(num_gqa);
+        ignore_option!(num_gpu);
+        ignore_option!(tfs_z);
+        ignore_option!(image_size);
+        ignore_option!(image_quality);
+        ignore_option!(image_style);
+
+        let backend = backend()?;
+        let model = load(backend, &self.path)?;
+
+        let prompt = self.prompt(&model, task)?;
+
+        // Create a context for inference
+        let n_ctx = task
+            .num_ctx
+            .map(|num| num as usize)
+            .unwrap_or(DEFAULT_CONTEXT.min(self.context_length.max(1)));
+        let mut params = LlamaContextParams::default().with_n_ctx(NonZeroU32::new(n_ctx as u32));
+        if let Some(threads) = task.num_thread {
+            params = params
+                .with_n_threads(threads as i32)
+                .with_n_threads_batch(threads as i32);
+        }
+        let mut context = model.new_context(backend, params)?;
+
+        // Evaluate the prompt
+        let tokens = model.str_to_token(&prompt, AddBos::Always)?;
+        if tokens.len() >= n_ctx {
+            bail!(
+                "Prompt is {} tokens which exceeds the context size ({n_ctx}) for model `{}`",
+                tokens.len(),
+                self.id()
+            )
+        }
+        let mut batch = LlamaBatch::new(tokens.len().max(512), 1);
+        let last = tokens.len() as i32 - 1;
+        for (index, token) in (0_i32..).zip(tokens.iter()) {
+            batch.add(*token, index, &[0], index == last)?;
+        }
+        context.decode(&mut batch)?;
+
+        // Create a sampler based on the sampling parameters of the task
+        let mut sampler = match task.temperature {
+            Some(temperature) if temperature <= 0. => LlamaSampler::greedy(),
+            temperature => LlamaSampler::chain_simple([
+                LlamaSampler::top_k(task.top_k.map_or(40, |top_k| top_k as i32)),
+                LlamaSampler::top_p(task.top_p.unwrap_or(0.95), 1),
+                LlamaSampler::temp(temperature.unwrap_or(0.8)),
+                LlamaSampler::dist(task.seed.map_or_else(rand::random, |seed| seed as u32)),
+            ]),
+        };
+
+        // Generation can not go beyond the end of the context
+        let available = n_ctx - tokens.len();
+        let max_tokens = task
+            .max_tokens
+            .map_or(available, |max| (max as usize).min(available));
+        let stop = task.stop.as_deref().filter(|stop| !stop.is_empty());
+
+        let mut text = String::new();
+        let mut pending = Vec::new();
+        let mut emitted = 0;
+        let mut position = batch.n_tokens();
+        let mut generated = 0;
+        for _ in 0..max_tokens {
+            let token = sampler.sample(&context, batch.n_tokens() - 1);
+            sampler.accept(token);
+            generated += 1;
+
+            if model.is_eog_token(token) {
+                break;
+            }
+
+            // Tokens may be partial UTF-8 characters, so only convert complete characters
+            pending.append(&mut model.token_to_bytes(token, Special::Tokenize)?);
+            match std::str::from_utf8(&pending) {
+                Ok(piece) => {
+                    text.push_str(piece);
+                    pending.clear();
+                }
+                Err(error) if error.error_len().is_none() => {}
+                Err(..) => {
+                    text.push_str(&String::from_utf8_lossy(&pending));
+                    pending.clear();
+                }
+            }
+
+            // Stop if the stop sequence has been generated
+            if let Some(stop) = stop {
+                if let Some(index) = text.find(stop) {
+                    text.truncate(index);
+                    break;
+                }
+            }
+
+            // Emit text, holding back enough to be able to remove a partial stop sequence
+            let mut end = text.len() - stop.map_or(0, |stop| stop.len()).min(text.len());
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            if end > emitted {
+                if !on_text(&text[emitted..end]) {
+                    return Ok((text, tokens.len() as u64, generated));
+                }
+                emitted = end;
+            }
+
+            batch.clear();
+            batch.add(token, position, &[0], true)?;
+            position += 1;
+            context.decode(&mut batch)?;
+        }
+
+        if text.len() > emitted {
+            on_text(&text[emitted..]);
+        }
+
+        Ok((text, tokens.len() as u64, generated))
+    }
+}
+
+#[async_trait]
+impl Model for LocalModel {
+    fn id(&self) -> String {
+        format!("local/{}", self.name)
+    }
+
+    fn r#type(&self) -> ModelType {
+        ModelType::Local
+    }
+
+    fn availability(&self) -> ModelAvailability {
+        if self.context_length > 0 {
+            ModelAvailability::Available
+        } else {
+            ModelAvailability::Unavailable
+        }
+    }
+
+    fn context_length(&self) -> usize {
+        self.context_length
+    }
+
+    fn supported_inputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    fn supported_outputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
+        if task.dry_run {
+            return ModelOutput::empty(self);
+        }
+
+        let model = self.clone();
+        let task_clone = task.clone();
+        let (text, prompt_tokens, completion_tokens) =
+            spawn_blocking(move || model.generate(&task_clone, |_| true)).await??;
+
+        let mut output = ModelOutput::from_text(self, &task.format, text).await?;
+        output.usage = Some(self.usage(prompt_tokens, completion_tokens));
+
+        Ok(output)
+    }
+
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        if task.dry_run {
+            return Ok(stream::empty().boxed());
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let model = self.clone();
+        let task = task.clone();
+        spawn_blocking(move || {
+            let result = model.generate(&task, |text| {
+                sender
+                    .send(Ok(ModelStreamItem::Text(text.to_string())))
+                    .is_ok()
+            });
+            let item = result.map(|(.., prompt_tokens, completion_tokens)| {
+                ModelStreamItem::Usage(model.usage(prompt_tokens, completion_tokens))
+            });
+            sender.send(item).ok();
+        });
+
+        let stream = stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        });
+
+        Ok(stream.boxed())
+    }
+}
+
+/// Get the llama.cpp backend
+///
+/// The backend can only be initialized once per process.
+fn backend() -> Result<&'static LlamaBackend> {
+    static BACKEND: OnceCell<LlamaBackend> = OnceCell::new();
+    BACKEND.get_or_try_init(|| Ok(LlamaBackend::init()?))
+}
+
+/// Load a model from a GGUF file
+///
+/// Loaded models are kept in memory so that they do not need to be loaded
+/// for each task.
+fn load(backend: &LlamaBackend, path: &Path) -> Result<Arc<LlamaModel>> {
+    static MODELS: Lazy<Mutex<HashMap<PathBuf, Arc<LlamaModel>>>> = Lazy::new(Mutex::default);
+
+    let mut models = MODELS
+        .lock()
+        .map_err(|_| eyre!("Unable to lock loaded models"))?;
+
+    if let Some(model) = models.get(path) {
+        return Ok(model.clone());
+    }
+
+    tracing::debug!("Loading model from `{}`", path.display());
+    let model = Arc::new(LlamaModel::load_from_file(
+        backend,
+        path,
+        &LlamaModelParams::default(),
+    )?);
+    models.insert(path.to_path_buf(), model.clone());
+
+    Ok(model)
+}
+
+/// Get a list of local models
+///
+/// Lists a model for each GGUF file in the Stencila models directory.
+/// Cached for a short time so that newly downloaded models appear.
+#[cached(time = 120, result = true)]
+pub async fn list() -> Result<Vec<Arc<dyn Model>>> {
+    let dir = get_app_dir(DirType::Models, false)?;
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut models = Vec::new();
+    for entry in read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().map_or(false, |ext| ext == "gguf") {
+            models.push(Arc::new(LocalModel::new(&path)) as Arc<dyn Model>);
+        }
+    }
+
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use model::{common::tokio, test_collect_stream, test_task_repeat_word};
+
+    #[tokio::test]
+    async fn list_models() -> Result<()> {
+        // Just check this does not error since list may be empty if
+        // no models have been downloaded.
+        list().await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn perform_task() -> Result<()> {
+        let list = list().await?;
+        let Some(model) = list.iter().find(|model| model.is_available()) else {
+            return Ok(());
+        };
+        let output = model.perform_task(&test_task_repeat_word()).await?;
+
+        assert_eq!(output.content.trim(), "HELLO".to_string());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn perform_task_stream() -> Result<()> {
+        let list = list().await?;
+        let Some(model) = list.iter().find(|model| model.is_available()) else {
+            return Ok(());
+        };
+        let (text, usage) =
+            test_collect_stream(model.perform_task_stream(&test_task_repeat_word()).await?).await?;
+
+        assert_eq!(text.trim(), "HELLO");
+        assert!(usage.is_some());
+
+        Ok(())
+    }
+}
@@ -6,15 +6,18 @@ use model::{
     common::{
         async_trait::async_trait,
         eyre::{bail, Result},
+        futures::{stream, StreamExt},
         inflector::Inflector,
         itertools::Itertools,
-        reqwest::Client,
+        reqwest::{Client, Response},
         serde::{Deserialize, Serialize},
+        serde_json::{self, Value},
         serde_with::skip_serializing_none,
         tracing,
     },
     schema::{MessagePart, MessageRole},
-    secrets, Model, ModelIO, ModelOutput, ModelTask, ModelType,
+    secrets, sse_data, Model, ModelApiError, ModelIO, ModelOutput, ModelStream, ModelStreamItem,
+    ModelTask, ModelToolCall, ModelType, ModelUsage,
 };
 
 const BASE_URL: &str = "https://api.mistral.ai/v1";
@@ -42,6 +45,114 @@ impl MistralModel {
             client: Client::new(),
         }
     }
+
+    /// Create a chat completion request for a task
+    fn request(&self, task: &ModelTask, stream: bool) -> ChatCompletionRequest {
+        let mut messages: Vec<ChatMessage> = task
+            .messages
+            .iter()
+            .map(|message| {
+                let role = match message.role.clone().unwrap_or_default() {
+                    MessageRole::Model => ChatRole::Assistant,
+                    MessageRole::System => ChatRole::System,
+                    MessageRole::User => ChatRole::User,
+                };
+
+                let content = message
+                    .parts
+                    .iter()
+                    .filter_map(|part: &MessagePart| match part {
+                        MessagePart::Text(text) => Some(text.to_value_string()),
+                        _ => {
+                            tracing::warn!(
+                                "Message part of type `{part}` is ignored by model `{}`",
+                                self.id()
+                            );
+                            None
+                        }
+                    })
+                    .join("");
+
+                ChatMessage {
+                    role,
+                    content: Some(content),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        // Add an assistant message with the tool calls, and a tool message with the
+        // result of each call, for each round of tool calls
+        for round in &task.tool_rounds {
+            messages.push(ChatMessage {
+                role: ChatRole::Assistant,
+                content: (!round.text.is_empty()).then(|| round.text.clone()),
+                tool_calls: Some(
+                    round
+                        .calls
+                        .iter()
+                        .map(|call| ToolCall {
+                            id: call.id.clone(),
+                            function: FunctionCall {
+                                name: call.name.clone(),
+                                arguments: call.arguments.to_string(),
+                            },
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            });
+            for result in &round.results {
+                messages.push(ChatMessage {
+                    role: ChatRole::Tool,
+                    content: Some(result.content.clone()),
+                    name: Some(result.name.clone()),
+                    tool_call_id: Some(result.call_id.clone()),
+                    ..Default::default()
+                });
+            }
+        }
+
+        let tools = task
+            .tools
+            .iter()
+            .map(|tool| Tool {
+                r#type: "function".into(),
+                function: Function {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: tool.parameters.clone(),
+                },
+            })
+            .collect_vec();
+
+        ChatCompletionRequest {
+            model: self.model.clone(),
+            messages,
+            temperature: task.temperature,
+            top_p: task.top_p,
+            max_tokens: task.max_tokens,
+            random_seed: task.seed,
+            stream: stream.then_some(true),
+            tools: (!tools.is_empty()).then_some(tools),
+            response_format: task.json_schema.is_some().then(|| ResponseFormat {
+                r#type: "json_object".to_string(),
+            }),
+        }
+    }
+
+    /// Send a chat completion request
+    async fn send(&self, request: &ChatCompletionRequest) -> Result<Response> {
+        let response = self
+            .client
+            .post(format!("{BASE_URL}/chat/completions"))
+            .bearer_auth(secrets::env_or_get(API_KEY)?)
+            .json(request)
+            .send()
+            .await?;
+
+        ModelApiError::check(response).await
+    }
 }
 
 #[async_trait]
@@ -87,66 +198,78 @@ impl Model for MistralModel {
 
     #[tracing::instrument(skip(self))]
     async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
-        let messages = task
-            .messages
-            .iter()
-            .map(|message| {
-                let role = match message.role.clone().unwrap_or_default() {
-                    MessageRole::Model => ChatRole::Assistant,
-                    MessageRole::System => ChatRole::System,
-                    MessageRole::User => ChatRole::User,
-                };
+        let request = self.request(task, false);
 
-                let content = message
-                    .parts
-                    .iter()
-                    .filter_map(|part: &MessagePart| match part {
-                        MessagePart::Text(text) => Some(text.to_value_string()),
-                        _ => {
-                            tracing::warn!(
-                                "Message part of type `{part}` is ignored by model `{}`",
-                                self.id()
-                            );
-                            None
-                        }
-                    })
-                    .join("");
+        if task.dry_run {
+            return ModelOutput::empty(self);
+        }
+
+        let mut response: ChatCompletionResponse = self.send(&request).await?.json().await?;
 
-                ChatMessage { role, content }
+        let message = response.choices.swap_remove(0).message;
+
+        let tool_calls = message
+            .tool_calls
+            .into_iter()
+            .flatten()
+            .map(|call| {
+                Ok(ModelToolCall {
+                    id: call.id,
+                    name: call.function.name,
+                    arguments: serde_json::from_str(&call.function.arguments)?,
+                })
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
-        let request = ChatCompletionRequest {
-            model: self.model.clone(),
-            messages,
-            temperature: task.temperature,
-            top_p: task.top_p,
-            max_tokens: task.max_tokens,
-            random_seed: task.seed,
-        };
+        let text = message.content.unwrap_or_default();
+        let mut output = ModelOutput::from_text(self, &task.format, text).await?;
+        output.tool_calls = tool_calls;
+        output.usage = response
+            .usage
+            .map(|usage| self.usage(usage.prompt_tokens, usage.completion_tokens));
 
-        if task.dry_run {
-            return ModelOutput::empty(self);
-        }
+        Ok(output)
+    }
 
-        let response = self
-            .client
-            .post(format!("{BASE_URL}/chat/completions"))
-            .bearer_auth(secrets::env_or_get(API_KEY)?)
-            .json(&request)
-            .send()
-            .await?;
+    #[tracing::instrument(skip(self))]
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        let request = self.request(task, true);
 
-        if let Err(error) = response.error_for_status_ref() {
-            let message = response.text().await?;
-            bail!("{error}: {message}");
+        if task.dry_run {
+            return Ok(stream::empty().boxed());
         }
 
-        let mut response: ChatCompletionResponse = response.json().await?;
-
-        let text = response.choices.swap_remove(0).message.content;
+        let response = self.send(&request).await?;
+
+        // Get the content of the first choice in each chunk, and the usage
+        // reported in the final chunk
+        let pricing = self.pricing();
+        let stream = sse_data(response).flat_map(move |data| {
+            let items = match data
+                .and_then(|data| Ok(serde_json::from_str::<ChatCompletionChunk>(&data)?))
+            {
+                Ok(chunk) => {
+                    let text = chunk
+                        .choices
+                        .into_iter()
+                        .next()
+                        .and_then(|choice| choice.delta.content)
+                        .map(|text| Ok(ModelStreamItem::Text(text)));
+                    let usage = chunk.usage.map(|usage| {
+                        Ok(ModelStreamItem::Usage(ModelUsage::new(
+                            pricing,
+                            usage.prompt_tokens,
+                            usage.completion_tokens,
+                        )))
+                    });
+                    text.into_iter().chain(usage).collect_vec()
+                }
+                Err(error) => vec![Err(error)],
+            };
+            stream::iter(items)
+        });
 
-        ModelOutput::from_text(self, &task.format, text).await
+        Ok(stream.boxed())
     }
 }
 
@@ -181,6 +304,19 @@ struct ChatCompletionRequest {
     top_p: Option<f32>,
     max_tokens: Option<u16>,
     random_seed: Option<i32>,
+    stream: Option<bool>,
+    tools: Option<Vec<Tool>>,
+    response_format: Option<ResponseFormat>,
+}
+
+/// The format of the response to a chat completion request
+///
+/// Mistral only supports JSON mode, not JSON Schemas, so the output is
+/// validated by the caller.
+#[derive(Serialize)]
+#[serde(crate = "model::common::serde")]
+struct ResponseFormat {
+    r#type: String,
 }
 
 /// A chat completion response
@@ -191,6 +327,15 @@ struct ChatCompletionRequest {
 #[serde(crate = "model::common::serde")]
 struct ChatCompletionResponse {
     choices: Vec<ChatCompletionChoice>,
+    usage: Option<ChatCompletionUsage>,
+}
+
+/// The token usage of a `ChatCompletionResponse`
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct ChatCompletionUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
 }
 
 /// A choice within a `ChatCompletionResponse`
@@ -203,22 +348,88 @@ struct ChatCompletionChoice {
     message: ChatMessage,
 }
 
+/// A chunk of a streamed chat completion response
+///
+/// Note: at present several other fields are ignored.
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct ChatCompletionChunk {
+    choices: Vec<ChatCompletionChunkChoice>,
+    usage: Option<ChatCompletionUsage>,
+}
+
+/// A choice within a `ChatCompletionChunk`
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct ChatCompletionChunkChoice {
+    delta: ChatCompletionDelta,
+}
+
+/// The change to the content of a message within a `ChatCompletionChunkChoice`
+#[derive(Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct ChatCompletionDelta {
+    content: Option<String>,
+}
+
 /// A chat message within a `ChatCompletionRequest` or a `ChatCompletionResponse`
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 #[serde(crate = "model::common::serde")]
 struct ChatMessage {
     role: ChatRole,
-    content: String,
+    content: Option<String>,
+    name: Option<String>,
+    tool_calls: Option<Vec<ToolCall>>,
+    tool_call_id: Option<String>,
 }
 
 /// A role in a `ChatMessage`
-#[derive(Serialize, Deserialize)]
+#[derive(Default, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase", crate = "model::common::serde")]
 enum ChatRole {
     System,
+    #[default]
     User,
     Assistant,
+    Tool,
+}
+
+/// A tool within a `ChatCompletionRequest`
+///
+/// Based on https://docs.mistral.ai/capabilities/function_calling/
+#[derive(Serialize)]
+#[serde(crate = "model::common::serde")]
+struct Tool {
+    r#type: String,
+    function: Function,
+}
+
+/// The function of a `Tool`
+#[derive(Serialize)]
+#[serde(crate = "model::common::serde")]
+struct Function {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+/// A call to a tool within a `ChatMessage`
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct ToolCall {
+    id: String,
+    function: FunctionCall,
+}
+
+/// The function called in a `ToolCall`
+///
+/// The `arguments` are a JSON object encoded as a string.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct FunctionCall {
+    name: String,
+    arguments: String,
 }
 
 /// Get a list of available Mistral models
@@ -289,7 +500,7 @@ async fn list_mistral_models() -> Result<ModelsResponse> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use model::{common::tokio, test_task_repeat_word};
+    use model::{common::tokio, test_collect_stream, test_task_repeat_word};
 
     #[tokio::test]
     async fn list_models() -> Result<()> {
@@ -317,4 +528,20 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn perform_task_stream() -> Result<()> {
+        if secrets::env_or_get(API_KEY).is_err() {
+            return Ok(());
+        }
+
+        let model = MistralModel::new("mistral-large-latest", 0);
+        let (text, usage) =
+            test_collect_stream(model.perform_task_stream(&test_task_repeat_word()).await?).await?;
+
+        assert_eq!(text.trim(), "HELLO");
+        assert!(usage.is_some());
+
+        Ok(())
+    }
 }
@@ -2,10 +2,12 @@ use std::sync::Arc;
 
 use cached::proc_macro::cached;
 use ollama_rs::{
+    error::OllamaError,
     generation::{
         chat::{request::ChatMessageRequest, ChatMessage, MessageRole},
         images::Image,
         options::GenerationOptions,
+        parameters::FormatType,
     },
     models::LocalModel,
     Ollama,
@@ -14,12 +16,15 @@ use ollama_rs::{
 use model::{
     common::{
         async_trait::async_trait,
-        eyre::{eyre, Result},
+        eyre::{eyre, Report, Result},
+        futures::{stream, StreamExt},
         inflector::Inflector,
+        reqwest::StatusCode,
         tracing,
     },
     schema::{self, ImageObject, MessagePart},
-    Model, ModelIO, ModelOutput, ModelTask, ModelType,
+    Model, ModelApiError, ModelIO, ModelOutput, ModelStream, ModelStreamItem, ModelTask, ModelType,
+    ModelUsage,
 };
 
 /// A model running on a Ollama (https://github.com/jmorganca/ollama/) server
@@ -74,49 +79,13 @@ impl OllamaModel {
             client,
         }
     }
-}
-
-#[async_trait]
-impl Model for OllamaModel {
-    fn id(&self) -> String {
-        format!("ollama/{}", self.model)
-    }
-
-    fn r#type(&self) -> ModelType {
-        ModelType::Local
-    }
-
-    fn name(&self) -> String {
-        let name = self.id();
-        let name = name
-            .rsplit_once('/')
-            .map(|(.., name)| name.split_once(':').map_or(name, |(name, ..)| name))
-            .unwrap_or(&name);
-        name.to_title_case()
-    }
-
-    fn version(&self) -> String {
-        let name = self.id();
-        let version = name
-            .split_once(':')
-            .map(|(.., version)| version)
-            .unwrap_or(&name);
-        version.to_string()
-    }
-
-    fn context_length(&self) -> usize {
-        self.context_length
-    }
-
-    fn supported_inputs(&self) -> &[ModelIO] {
-        &[ModelIO::Text]
-    }
 
-    fn supported_outputs(&self) -> &[ModelIO] {
-        &[ModelIO::Text]
-    }
+    /// Create a chat request for a task
+    fn request(&self, task: &ModelTask) -> ChatMessageRequest {
+        if !task.tools.is_empty() {
+            tracing::warn!("Tools are not yet supported by model `{}`", self.id());
+        }
 
-    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
         let messages = task
             .messages
             .iter()
@@ -217,6 +186,58 @@ impl Model for OllamaModel {
 
         request.options = Some(options);
 
+        // Constrain the output to the JSON Schema, if any, of the task
+        if let Some(schema) = &task.json_schema {
+            request = request.format(FormatType::Json(schema.clone()));
+        }
+
+        request
+    }
+}
+
+#[async_trait]
+impl Model for OllamaModel {
+    fn id(&self) -> String {
+        format!("ollama/{}", self.model)
+    }
+
+    fn r#type(&self) -> ModelType {
+        ModelType::Local
+    }
+
+    fn name(&self) -> String {
+        let name = self.id();
+        let name = name
+            .rsplit_once('/')
+            .map(|(.., name)| name.split_once(':').map_or(name, |(name, ..)| name))
+            .unwrap_or(&name);
+        name.to_title_case()
+    }
+
+    fn version(&self) -> String {
+        let name = self.id();
+        let version = name
+            .split_once(':')
+            .map(|(.., version)| version)
+            .unwrap_or(&name);
+        version.to_string()
+    }
+
+    fn context_length(&self) -> usize {
+        self.context_length
+    }
+
+    fn supported_inputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    fn supported_outputs(&self) -> &[ModelIO] {
+        &[ModelIO::Text]
+    }
+
+    async fn perform_task(&self, task: &ModelTask) -> Result<ModelOutput> {
+        let request = self.request(task);
+
         if task.dry_run {
             return ModelOutput::empty(self);
         }
@@ -225,14 +246,89 @@ impl Model for OllamaModel {
             .client
             .send_chat_messages(request)
             .await
-            .map_err(|error| eyre!(error))?;
+            .map_err(api_error)?;
 
         let text = response
             .message
             .map(|message| message.content)
             .unwrap_or_default();
 
-        ModelOutput::from_text(self, &task.format, text).await
+        let mut output = ModelOutput::from_text(self, &task.format, text).await?;
+        output.usage = response
+            .final_data
+            .map(|data| self.usage(data.prompt_eval_count as u64, data.eval_count as u64));
+
+        Ok(output)
+    }
+
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        let request = self.request(task);
+
+        if task.dry_run {
+            return Ok(stream::empty().boxed());
+        }
+
+        let response = self
+            .client
+            .send_chat_messages_stream(request)
+            .await
+            .map_err(api_error)?;
+
+        // The final response has the counts of tokens
+        let pricing = self.pricing();
+        let stream = response.flat_map(move |response| {
+            let items = match response {
+                Ok(response) => {
+                    let text = response
+                        .message
+                        .map(|message| message.content)
+                        .unwrap_or_default();
+                    let usage = response.final_data.map(|data| {
+                        Ok(ModelStreamItem::Usage(ModelUsage::new(
+                            pricing,
+                            data.prompt_eval_count as u64,
+                            data.eval_count as u64,
+                        )))
+                    });
+                    std::iter::once(Ok(ModelStreamItem::Text(text)))
+                        .chain(usage)
+                        .collect::<Vec<_>>()
+                }
+                Err(..) => vec![Err(eyre!("Error while streaming response from Ollama"))],
+            };
+            stream::iter(items)
+        });
+
+        Ok(stream.boxed())
+    }
+}
+
+/// Convert an error from `ollama-rs` into a report
+///
+/// `ollama-rs` converts errors into strings, losing the status of unsuccessful
+/// responses and the kind of request errors. Errors sending requests (e.g. timeouts
+/// or the server not being ready) and the server being busy (e.g. when the maximum
+/// number of queued requests is reached) are converted into a [`ModelApiError`]
+/// so that they are recognized as transient.
+fn api_error(error: OllamaError) -> Report {
+    let message = error.to_string();
+
+    let status = if message.contains("timed out") {
+        Some(StatusCode::GATEWAY_TIMEOUT)
+    } else if message.contains("error sending request") || message.contains("server busy") {
+        Some(StatusCode::SERVICE_UNAVAILABLE)
+    } else {
+        None
+    };
+
+    match status {
+        Some(status) => ModelApiError {
+            status,
+            retry_after: None,
+            message,
+        }
+        .into(),
+        None => eyre!(message),
     }
 }
 
@@ -275,7 +371,29 @@ async fn list_ollama_models(_unused: u8) -> Result<Vec<LocalModel>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use model::{common::tokio, test_task_repeat_word};
+    use model::{common::tokio, test_collect_stream, test_task_repeat_word};
+
+    #[test]
+    fn api_errors() {
+        let status = |message: &str| {
+            api_error(OllamaError::from(message.to_string()))
+                .downcast_ref::<ModelApiError>()
+                .map(|error| error.status.as_u16())
+        };
+
+        assert_eq!(
+            status("error sending request for url (http://127.0.0.1:11434/api/chat)"),
+            Some(503)
+        );
+        assert_eq!(status("operation timed out"), Some(504));
+        assert_eq!(
+            status(
+                r#"{"error":"server busy, please try again.  maximum pending requests exceeded"}"#
+            ),
+            Some(503)
+        );
+        assert_eq!(status(r#"{"error":"model 'llama9' not found"}"#), None);
+    }
 
     #[tokio::test]
     async fn list_models() -> Result<()> {
@@ -298,4 +416,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn perform_task_stream() -> Result<()> {
+        let list = list().await?;
+        let Some(model) = list.first() else {
+            return Ok(());
+        };
+        let (text, usage) =
+            test_collect_stream(model.perform_task_stream(&test_task_repeat_word()).await?).await?;
+
+        assert_eq!(text.trim(), "HELLO");
+        assert!(usage.is_some());
+
+        Ok(())
+    }
 }
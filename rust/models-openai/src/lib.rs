@@ -1,14 +1,19 @@
 use std::sync::Arc;
 
 use async_openai::{
-    config::OpenAIConfig,
+    config::{Config, OpenAIConfig},
+    error::OpenAIError,
     types::{
-        ChatCompletionRequestAssistantMessage, ChatCompletionRequestMessage,
-        ChatCompletionRequestMessageContentPart, ChatCompletionRequestMessageContentPartImage,
-        ChatCompletionRequestMessageContentPartText, ChatCompletionRequestSystemMessage,
+        ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessage,
+        ChatCompletionRequestMessage, ChatCompletionRequestMessageContentPart,
+        ChatCompletionRequestMessageContentPartImage, ChatCompletionRequestMessageContentPartText,
+        ChatCompletionRequestSystemMessage, ChatCompletionRequestToolMessageArgs,
         ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent,
-        CreateChatCompletionRequest, CreateImageRequestArgs, Image, ImageDetail, ImageQuality,
-        ImageSize, ImageStyle, ImageUrl, ListModelResponse, ResponseFormat, Stop,
+        ChatCompletionResponseFormat, ChatCompletionResponseFormatType,
+        ChatCompletionStreamOptions, ChatCompletionToolArgs, ChatCompletionToolType,
+        CreateChatCompletionRequest, CreateChatCompletionResponse, CreateImageRequestArgs,
+        FunctionCall, FunctionObjectArgs, Image, ImageDetail, ImageQuality, ImageSize, ImageStyle,
+        ImageUrl, ListModelResponse, ResponseFormat, Stop,
     },
     Client,
 };
@@ -17,13 +22,18 @@ use cached::proc_macro::cached;
 use model::{
     common::{
         async_trait::async_trait,
-        eyre::{bail, Result},
+        eyre::{bail, Report, Result},
+        futures::{stream, StreamExt},
         inflector::Inflector,
         itertools::Itertools,
+        reqwest::{self, StatusCode},
+        serde_json::{self, json, Value},
         tracing,
     },
+    json_schema_strict,
     schema::{ImageObject, MessagePart, MessageRole},
-    secrets, Model, ModelIO, ModelOutput, ModelTask, ModelTaskKind, ModelType,
+    secrets, Model, ModelApiError, ModelIO, ModelOutput, ModelStream, ModelStreamItem, ModelTask,
+    ModelTaskKind, ModelToolCall, ModelType, ModelUsage,
 };
 
 /// The name of the env var or secret for the API key
@@ -126,6 +136,16 @@ impl Model for OpenAIModel {
             ModelTaskKind::ImageGeneration => self.image_generation(task).await,
         }
     }
+
+    async fn perform_task_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        match task.kind {
+            ModelTaskKind::MessageGeneration => self.message_generation_stream(task).await,
+            ModelTaskKind::ImageGeneration => {
+                let output = self.image_generation(task).await?;
+                Ok(stream::once(async move { Ok(ModelStreamItem::Text(output.content)) }).boxed())
+            }
+        }
+    }
 }
 
 impl OpenAIModel {
@@ -137,11 +157,37 @@ impl OpenAIModel {
         ))
     }
 
-    #[tracing::instrument(skip_all)]
-    async fn message_generation(&self, task: &ModelTask) -> Result<ModelOutput> {
-        tracing::debug!("Sending chat completion request");
+    /// Create a chat completion with a strict JSON Schema for the response
+    ///
+    /// This version of `async-openai` does not support the `json_schema` response
+    /// format so the request is serialized and the format set before it is sent.
+    async fn create_with_schema(
+        request: CreateChatCompletionRequest,
+        schema: &Value,
+    ) -> Result<CreateChatCompletionResponse> {
+        let mut body = serde_json::to_value(request)?;
+        body["response_format"] = json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": "output",
+                "strict": true,
+                "schema": json_schema_strict(schema)
+            }
+        });
 
-        let messages = task
+        let response = reqwest::Client::new()
+            .post(OpenAIConfig::new().url("/chat/completions"))
+            .bearer_auth(secrets::env_or_get(API_KEY)?)
+            .json(&body)
+            .send()
+            .await?;
+
+        Ok(ModelApiError::check(response).await?.json().await?)
+    }
+
+    /// Create a chat completion request for a task
+    fn chat_request(&self, task: &ModelTask) -> Result<CreateChatCompletionRequest> {
+        let mut messages: Vec<ChatCompletionRequestMessage> = task
             .messages
             .iter()
             .map(|message| match message.role.clone().unwrap_or_default() {
@@ -225,6 +271,58 @@ impl OpenAIModel {
             })
             .collect();
 
+        // Add an assistant message with the tool calls, and a tool message with the
+        // result of each call, for each round of tool calls
+        for round in &task.tool_rounds {
+            messages.push(ChatCompletionRequestMessage::Assistant(
+                ChatCompletionRequestAssistantMessage {
+                    content: (!round.text.is_empty()).then(|| round.text.clone()),
+                    tool_calls: Some(
+                        round
+                            .calls
+                            .iter()
+                            .map(|call| ChatCompletionMessageToolCall {
+                                id: call.id.clone(),
+                                r#type: ChatCompletionToolType::Function,
+                                function: FunctionCall {
+                                    name: call.name.clone(),
+                                    arguments: call.arguments.to_string(),
+                                },
+                            })
+                            .collect(),
+                    ),
+                    ..Default::default()
+                },
+            ));
+            for result in &round.results {
+                messages.push(
+                    ChatCompletionRequestToolMessageArgs::default()
+                        .content(result.content.clone())
+                        .tool_call_id(result.call_id.clone())
+                        .build()?
+                        .into(),
+                );
+            }
+        }
+
+        // Create the tools
+        let tools = task
+            .tools
+            .iter()
+            .map(|tool| {
+                ChatCompletionToolArgs::default()
+                    .r#type(ChatCompletionToolType::Function)
+                    .function(
+                        FunctionObjectArgs::default()
+                            .name(tool.name.clone())
+                            .description(tool.description.clone())
+                            .parameters(tool.parameters.clone())
+                            .build()?,
+                    )
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         // Create the request
         let request = CreateChatCompletionRequest {
             model: self.model.clone(),
@@ -235,6 +333,16 @@ impl OpenAIModel {
             max_tokens: task.max_tokens.map(|tokens| tokens as u32),
             top_p: task.top_p,
             stop: task.stop.clone().map(Stop::String),
+            tools: (!tools.is_empty()).then_some(tools),
+            // This version of `async-openai` only supports JSON mode, not JSON Schemas,
+            // so for non-streaming requests this is replaced with a strict JSON Schema
+            // in `create_with_schema`. The output is also validated by the caller.
+            response_format: task
+                .json_schema
+                .is_some()
+                .then_some(ChatCompletionResponseFormat {
+                    r#type: ChatCompletionResponseFormatType::JsonObject,
+                }),
             ..Default::default()
         };
 
@@ -266,22 +374,105 @@ impl OpenAIModel {
             top_k
         );
 
+        Ok(request)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn message_generation(&self, task: &ModelTask) -> Result<ModelOutput> {
+        tracing::debug!("Sending chat completion request");
+
+        let request = self.chat_request(task)?;
+
         if task.dry_run {
             return ModelOutput::empty(self);
         }
 
         // Send the request
-        let client = Self::client()?;
-        let mut response = client.chat().create(request).await?;
+        let mut response = match &task.json_schema {
+            Some(schema) => Self::create_with_schema(request, schema).await?,
+            None => Self::client()?
+                .chat()
+                .create(request)
+                .await
+                .map_err(api_error)?,
+        };
 
-        // Get the content of the first message
-        let text = response
-            .choices
-            .pop()
-            .and_then(|choice| choice.message.content)
-            .unwrap_or_default();
+        // Get the content, and any tool calls, of the first message
+        let Some(choice) = response.choices.pop() else {
+            return ModelOutput::from_text(self, &task.format, String::new()).await;
+        };
+        let text = choice.message.content.unwrap_or_default();
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .into_iter()
+            .flatten()
+            .map(|call| {
+                Ok(ModelToolCall {
+                    id: call.id,
+                    name: call.function.name,
+                    arguments: serde_json::from_str(&call.function.arguments)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let mut output = ModelOutput::from_text(self, &task.format, text).await?;
+        output.tool_calls = tool_calls;
+        output.usage = response
+            .usage
+            .map(|usage| self.usage(usage.prompt_tokens as u64, usage.completion_tokens as u64));
+
+        Ok(output)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn message_generation_stream(&self, task: &ModelTask) -> Result<ModelStream> {
+        tracing::debug!("Sending streaming chat completion request");
 
-        ModelOutput::from_text(self, &task.format, text).await
+        let mut request = self.chat_request(task)?;
+        request.stream = Some(true);
+        request.stream_options = Some(ChatCompletionStreamOptions {
+            include_usage: true,
+        });
+
+        if task.dry_run {
+            return Ok(stream::empty().boxed());
+        }
+
+        // Send the request
+        let client = Self::client()?;
+        let response = client
+            .chat()
+            .create_stream(request)
+            .await
+            .map_err(api_error)?;
+
+        // Get the content of the first choice in each chunk, and the usage
+        // reported in the final chunk
+        let pricing = self.pricing();
+        let stream = response.flat_map(move |chunk| {
+            let items = match chunk {
+                Ok(mut chunk) => {
+                    let text = chunk
+                        .choices
+                        .pop()
+                        .and_then(|choice| choice.delta.content)
+                        .map(|text| Ok(ModelStreamItem::Text(text)));
+                    let usage = chunk.usage.map(|usage| {
+                        Ok(ModelStreamItem::Usage(ModelUsage::new(
+                            pricing,
+                            usage.prompt_tokens as u64,
+                            usage.completion_tokens as u64,
+                        )))
+                    });
+                    text.into_iter().chain(usage).collect_vec()
+                }
+                Err(error) => vec![Err(error.into())],
+            };
+            stream::iter(items)
+        });
+
+        Ok(stream.boxed())
     }
 
     #[tracing::instrument(skip_all)]
@@ -401,7 +592,7 @@ impl OpenAIModel {
 
         // Send the request
         let client = Self::client()?;
-        let mut response = client.images().create(request).await?;
+        let mut response = client.images().create(request).await.map_err(api_error)?;
 
         // Get the output
         if response.data.is_empty() {
@@ -418,6 +609,31 @@ impl OpenAIModel {
     }
 }
 
+/// Convert an error from `async-openai` into a report
+///
+/// `async-openai` does not retain the status of unsuccessful responses so API
+/// errors for server errors and rate limiting are converted into a [`ModelApiError`]
+/// (based on their type and code) so that they are recognized as transient.
+fn api_error(error: OpenAIError) -> Report {
+    if let OpenAIError::ApiError(api_error) = &error {
+        let status = match (api_error.r#type.as_deref(), api_error.code.as_deref()) {
+            (Some("server_error"), ..) => Some(StatusCode::INTERNAL_SERVER_ERROR),
+            (.., Some("rate_limit_exceeded")) => Some(StatusCode::TOO_MANY_REQUESTS),
+            _ => None,
+        };
+        if let Some(status) = status {
+            return ModelApiError {
+                status,
+                retry_after: None,
+                message: api_error.to_string(),
+            }
+            .into();
+        }
+    }
+
+    error.into()
+}
+
 /// Get a list of all available OpenAI models
 ///
 /// If the OpenAI API key is not available returns an empty list.
@@ -515,7 +731,36 @@ async fn list_openai_models(_unused: u8) -> Result<ListModelResponse> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use model::{common::tokio, test_task_repeat_word};
+    use model::{common::tokio, test_collect_stream, test_task_repeat_word};
+
+    #[test]
+    fn api_errors() {
+        let error = |r#type: &str, code: Option<&str>| {
+            api_error(OpenAIError::ApiError(async_openai::error::ApiError {
+                message: "Oops".into(),
+                r#type: Some(r#type.into()),
+                param: None,
+                code: code.map(String::from),
+            }))
+        };
+
+        let status = |report: Report| {
+            report
+                .downcast_ref::<ModelApiError>()
+                .map(|error| error.status.as_u16())
+        };
+
+        assert_eq!(status(error("server_error", None)), Some(500));
+        assert_eq!(
+            status(error("requests", Some("rate_limit_exceeded"))),
+            Some(429)
+        );
+        assert_eq!(
+            status(error("insufficient_quota", Some("insufficient_quota"))),
+            None
+        );
+        assert_eq!(status(error("invalid_request_error", None)), None);
+    }
 
     #[tokio::test]
     async fn list_models() -> Result<()> {
@@ -547,4 +792,24 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn perform_task_stream() -> Result<()> {
+        if secrets::env_or_get(API_KEY).is_err() {
+            return Ok(());
+        }
+
+        let list = list().await?;
+        let model = list
+            .iter()
+            .find(|model| model.name().starts_with("GPT"))
+            .expect("model should exists");
+        let (text, usage) =
+            test_collect_stream(model.perform_task_stream(&test_task_repeat_word()).await?).await?;
+
+        assert_eq!(text.trim(), "HELLO");
+        assert!(usage.is_some());
+
+        Ok(())
+    }
 }
@@ -11,7 +11,7 @@ use model::{
         reqwest::Client,
         serde::{Deserialize, Serialize},
     },
-    Model, ModelAvailability, ModelIO, ModelOutput, ModelTask, ModelType,
+    Model, ModelApiError, ModelAvailability, ModelIO, ModelOutput, ModelTask, ModelType,
 };
 
 /// A model available via Stencila Cloud
@@ -98,12 +98,7 @@ impl Model for StencilaModel {
             .send()
             .await?;
 
-        if let Err(error) = response.error_for_status_ref() {
-            let message = response.text().await?;
-            bail!("{error}: {message}");
-        }
-
-        let output: ModelOutput = response.json().await?;
+        let output: ModelOutput = ModelApiError::check(response).await?.json().await?;
 
         Ok(output)
     }
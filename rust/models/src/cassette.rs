@@ -0,0 +1,465 @@
+//! Recording, and replaying, of the outputs of model tasks
+//!
+//! A cassette is a JSON file containing the outputs of model tasks keyed by a hash
+//! of the task. In `record` mode, the output of each task performed is stored in the
+//! cassette. In `replay` mode, recorded outputs are returned without any requests to
+//! models and it is an error if there is no recorded output for a task. This allows
+//! documents containing instructions and chats to be executed deterministically, and
+//! without network access, in tests and CI.
+//!
+//! A cassette can be used for all tasks in the process by setting the `STENCILA_MODELS_CASSETTE`
+//! environment variable to the path of the cassette file (and optionally
+//! `STENCILA_MODELS_CASSETTE_MODE` to `record` or `replay`), or for the tasks performed
+//! within a future by loading a [`ScopedCassette`] and using its [`ScopedCassette::scope`] method.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    future::Future,
+    hash::Hasher,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use model::{
+    common::{
+        clap::{self, ValueEnum},
+        eyre::{bail, Result},
+        once_cell::sync::Lazy,
+        seahash::SeaHasher,
+        serde::{Deserialize, Serialize},
+        serde_json,
+        strum::{Display, EnumString},
+        tokio::{self, fs, sync::RwLock},
+        tracing,
+    },
+    ModelOutput, ModelTask,
+};
+
+/// Whether to record outputs to, or replay outputs from, a cassette
+#[derive(
+    Debug,
+    Display,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    ValueEnum,
+    EnumString,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase", crate = "model::common::serde")]
+#[strum(serialize_all = "lowercase", crate = "model::common::strum")]
+pub enum CassetteMode {
+    /// Perform tasks using models and record their outputs
+    Record,
+
+    /// Return recorded outputs, failing if there is no output for a task
+    #[default]
+    Replay,
+}
+
+/// A recorded task and its output
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(crate = "model::common::serde")]
+struct CassetteEntry {
+    /// The task (recorded to aid debugging of cassette misses)
+    task: ModelTask,
+
+    /// The output of the task
+    output: ModelOutput,
+}
+
+/// A cassette of recorded model outputs
+#[derive(Debug)]
+struct Cassette {
+    /// The path of the cassette file
+    path: PathBuf,
+
+    /// The mode of the cassette
+    mode: CassetteMode,
+
+    /// The recorded entries, keyed by the hash of their task
+    entries: BTreeMap<String, CassetteEntry>,
+}
+
+impl Cassette {
+    /// Load a cassette from a file
+    ///
+    /// If the file does not exist, the cassette will have no entries.
+    pub async fn load(path: &Path, mode: CassetteMode) -> Result<Self> {
+        let entries = if path.exists() {
+            let json = fs::read_to_string(path).await?;
+            serde_json::from_str(&json)?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            mode,
+            entries,
+        })
+    }
+
+    /// Save the cassette to its file
+    async fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        fs::write(&self.path, json).await?;
+
+        Ok(())
+    }
+
+    /// Generate a key for a task
+    ///
+    /// The ids, authors and provenance of messages are ignored because these
+    /// can vary between executions (e.g. the last modified time of an author)
+    /// without affecting the output of the model.
+    fn key(task: &ModelTask) -> Result<String> {
+        let mut task = task.clone();
+        for message in task.messages.iter_mut() {
+            message.id = None;
+            message.authors = None;
+            message.provenance = None;
+        }
+
+        let json = serde_json::to_string(&task)?;
+
+        let mut hasher = SeaHasher::new();
+        hasher.write(json.as_bytes());
+        Ok(format!("{:016x}", hasher.finish()))
+    }
+
+    /// Get the recorded output for a task
+    ///
+    /// Returns `None` if the cassette is in record mode. Errors if the cassette is
+    /// in replay mode and there is no recorded output for the task.
+    fn replay(&self, task: &ModelTask) -> Result<Option<ModelOutput>> {
+        if self.mode != CassetteMode::Replay {
+            return Ok(None);
+        }
+
+        let key = Self::key(task)?;
+        match self.entries.get(&key) {
+            Some(entry) => Ok(Some(entry.output.clone())),
+            None => bail!(
+                "No recorded output for model task `{key}` in cassette `{}`; record it using `STENCILA_MODELS_CASSETTE_MODE=record`",
+                self.path.display()
+            ),
+        }
+    }
+
+    /// Find the id of a model, matching an id or id pattern, in the recorded tasks
+    ///
+    /// Returns `None` if the cassette is in record mode. Exact matches are
+    /// preferred over substring matches.
+    fn model_id(&self, model: &str) -> Option<String> {
+        if self.mode != CassetteMode::Replay {
+            return None;
+        }
+
+        let ids: Vec<&String> = self
+            .entries
+            .values()
+            .filter_map(|entry| entry.task.model_parameters.as_ref()?.model_ids.as_ref())
+            .flatten()
+            .collect();
+
+        ids.iter()
+            .find(|id| id.as_str() == model)
+            .or_else(|| ids.iter().find(|id| id.contains(model)))
+            .map(|id| id.to_string())
+    }
+
+    /// Record the output of a task
+    ///
+    /// Does nothing if the cassette is in replay mode.
+    async fn record(&mut self, task: &ModelTask, output: &ModelOutput) -> Result<()> {
+        if self.mode != CassetteMode::Record {
+            return Ok(());
+        }
+
+        let key = Self::key(task)?;
+        self.entries.insert(
+            key,
+            CassetteEntry {
+                task: task.clone(),
+                output: output.clone(),
+            },
+        );
+
+        self.save().await
+    }
+}
+
+/// The cassette set using environment variables (if any)
+///
+/// Used for tasks that are not performed within the scope of a [`ScopedCassette`].
+static CASSETTE: Lazy<RwLock<Option<Cassette>>> = Lazy::new(|| {
+    let Ok(path) = env::var("STENCILA_MODELS_CASSETTE") else {
+        return RwLock::new(None);
+    };
+
+    let mode = env::var("STENCILA_MODELS_CASSETTE_MODE")
+        .ok()
+        .and_then(|mode| mode.parse().ok())
+        .unwrap_or_default();
+
+    let path = PathBuf::from(path);
+    let entries = match std::fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).unwrap_or_else(|error| {
+            tracing::error!("While reading cassette `{}`: {error}", path.display());
+            BTreeMap::new()
+        }),
+        Err(..) => BTreeMap::new(),
+    };
+
+    RwLock::new(Some(Cassette {
+        path,
+        mode,
+        entries,
+    }))
+});
+
+tokio::task_local! {
+    /// The cassette scoped to the current task (if any)
+    static SCOPED: ScopedCassette;
+}
+
+/// A cassette used only for the model tasks performed within a scope
+///
+/// Unlike the cassette set using environment variables, this does not affect
+/// other tasks in the process (e.g. the execution of other documents). Cloning
+/// is cheap and clones share the same recorded entries.
+#[derive(Debug, Clone)]
+pub struct ScopedCassette(Arc<RwLock<Cassette>>);
+
+impl ScopedCassette {
+    /// Load a scoped cassette from a file
+    pub async fn load(path: &Path, mode: CassetteMode) -> Result<Self> {
+        let cassette = Cassette::load(path, mode).await?;
+        Ok(Self(Arc::new(RwLock::new(cassette))))
+    }
+
+    /// Run a future using this cassette for the model tasks that it performs
+    ///
+    /// Note that tasks spawned from within the future are not in scope and
+    /// need to be run using this method themselves.
+    pub async fn scope<F: Future>(&self, future: F) -> F::Output {
+        SCOPED.scope(self.clone(), future).await
+    }
+
+    /// Get the cassette scoped to the current task (if any)
+    ///
+    /// Allows the cassette to be passed to, and scoped within, other tasks.
+    pub fn current() -> Option<Self> {
+        SCOPED.try_with(|cassette| cassette.clone()).ok()
+    }
+}
+
+impl PartialEq for ScopedCassette {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Get the recorded output for a task from the current cassette (if any)
+///
+/// Dry run tasks are never replayed.
+pub(crate) async fn replay(task: &ModelTask) -> Result<Option<ModelOutput>> {
+    if task.dry_run {
+        return Ok(None);
+    }
+
+    if let Some(ScopedCassette(cassette)) = ScopedCassette::current() {
+        return cassette.read().await.replay(task);
+    }
+
+    match CASSETTE.read().await.as_ref() {
+        Some(cassette) => cassette.replay(task),
+        None => Ok(None),
+    }
+}
+
+/// Get the id of a model, matching an id or id pattern, recorded in the current cassette
+///
+/// Callers which resolve id patterns to the ids of available models (e.g. when evaluating
+/// prompts) record the resolved ids in tasks. When replaying, models may not be available,
+/// so this allows the same ids to be resolved so that tasks match those recorded. Returns
+/// `None` if there is no cassette, it is in record mode, or no recorded task has a matching id.
+pub async fn model_id(model: &str) -> Option<String> {
+    if let Some(ScopedCassette(cassette)) = ScopedCassette::current() {
+        return cassette.read().await.model_id(model);
+    }
+
+    CASSETTE.read().await.as_ref()?.model_id(model)
+}
+
+/// Record the output of a task in the current cassette (if any)
+///
+/// Dry run tasks are never recorded.
+pub(crate) async fn record(task: &ModelTask, output: &ModelOutput) -> Result<()> {
+    if task.dry_run {
+        return Ok(());
+    }
+
+    if let Some(ScopedCassette(cassette)) = ScopedCassette::current() {
+        return cassette.write().await.record(task, output).await;
+    }
+
+    match CASSETTE.write().await.as_mut() {
+        Some(cassette) => cassette.record(task, output).await,
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use model::{
+        common::{eyre::Report, tempfile::tempdir, tokio},
+        schema::{Author, AuthorRole, InstructionMessage, MessageRole, ModelParameters},
+    };
+
+    use super::*;
+
+    #[test]
+    fn key_ignores_authors() -> Result<()> {
+        let mut task = ModelTask {
+            messages: vec![InstructionMessage {
+                role: Some(MessageRole::User),
+                parts: vec!["Hello".into()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let key = Cassette::key(&task)?;
+
+        task.messages[0].authors = Some(vec![Author::AuthorRole(AuthorRole::default())]);
+        assert_eq!(Cassette::key(&task)?, key);
+
+        task.messages[0].parts = vec!["Goodbye".into()];
+        assert_ne!(Cassette::key(&task)?, key);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn record_then_replay() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cassette.json");
+
+        let task = ModelTask {
+            messages: vec![InstructionMessage::from("Hello".to_string())],
+            ..Default::default()
+        };
+        let output = ModelOutput {
+            content: "Hi!".to_string(),
+            ..Default::default()
+        };
+
+        let mut cassette = Cassette::load(&path, CassetteMode::Record).await?;
+        assert!(cassette.replay(&task)?.is_none());
+        cassette.record(&task, &output).await?;
+
+        let cassette = Cassette::load(&path, CassetteMode::Replay).await?;
+        let replayed = cassette.replay(&task)?;
+        assert_eq!(
+            replayed.map(|output| output.content),
+            Some("Hi!".to_string())
+        );
+
+        let other = ModelTask {
+            messages: vec![InstructionMessage::from("Bye".to_string())],
+            ..Default::default()
+        };
+        assert!(cassette.replay(&other).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn record_then_replay_model_id() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cassette.json");
+
+        let task = |model: &str| ModelTask {
+            model_parameters: Some(ModelParameters {
+                model_ids: Some(vec![model.to_string()]),
+                ..Default::default()
+            }),
+            messages: vec![InstructionMessage::from("Hello".to_string())],
+            ..Default::default()
+        };
+        let output = ModelOutput {
+            content: "Hi!".to_string(),
+            ..Default::default()
+        };
+
+        // Record a task with the id that a pattern was resolved to
+        let cassette = ScopedCassette::load(&path, CassetteMode::Record).await?;
+        cassette
+            .scope(async {
+                assert_eq!(model_id("gpt-4o").await, None);
+                record(&task("openai/gpt-4o-2024-08-06"), &output).await
+            })
+            .await?;
+
+        // When replaying, the pattern resolves to the recorded id so the task matches
+        let cassette = ScopedCassette::load(&path, CassetteMode::Replay).await?;
+        cassette
+            .scope(async {
+                let id = model_id("gpt-4o").await;
+                assert_eq!(id.as_deref(), Some("openai/gpt-4o-2024-08-06"));
+                assert_eq!(model_id("claude").await, None);
+
+                let replayed = replay(&task(&id.unwrap_or_default())).await?;
+                assert_eq!(
+                    replayed.map(|output| output.content),
+                    Some("Hi!".to_string())
+                );
+
+                assert!(replay(&task("gpt-4o")).await.is_err());
+
+                Ok::<_, Report>(())
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn scoped_cassette() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("cassette.json");
+
+        let task = ModelTask {
+            messages: vec![InstructionMessage::from("Hello".to_string())],
+            ..Default::default()
+        };
+        let output = ModelOutput {
+            content: "Hi!".to_string(),
+            ..Default::default()
+        };
+
+        // Only tasks performed within the scope are recorded
+        let cassette = ScopedCassette::load(&path, CassetteMode::Record).await?;
+        cassette.scope(record(&task, &output)).await?;
+        assert!(path.exists());
+        assert!(ScopedCassette::current().is_none());
+
+        let cassette = ScopedCassette::load(&path, CassetteMode::Replay).await?;
+        let replayed = cassette.scope(replay(&task)).await?;
+        assert_eq!(
+            replayed.map(|output| output.content),
+            Some("Hi!".to_string())
+        );
+
+        Ok(())
+    }
+}
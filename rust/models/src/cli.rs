@@ -11,10 +11,10 @@ use model::{
     },
     format::Format,
     schema::{InstructionMessage, ModelParameters},
-    ModelAvailability, ModelSpecification, ModelTask, ModelType,
+    ModelAvailability, ModelPricing, ModelSpecification, ModelTask, ModelType,
 };
 
-use crate::select;
+use crate::{select, usage};
 
 /// Manage generative models
 #[derive(Debug, Parser)]
@@ -46,6 +46,9 @@ impl Cli {
 }
 
 /// List the models available
+///
+/// The pricing of each model, where known, is shown in US dollars per
+/// million input / output tokens.
 #[derive(Default, Debug, Args)]
 struct List {
     /// Output the list as JSON or YAML
@@ -77,6 +80,7 @@ impl List {
             "Name",
             "Version",
             "I/O",
+            "Pricing",
         ]);
 
         for model in list {
@@ -97,6 +101,14 @@ impl List {
 
             let io = [&inputs, "/", &outputs].concat();
 
+            let pricing = match model.pricing() {
+                Some(pricing) if pricing == ModelPricing::FREE => {
+                    Cell::new("free").fg(Color::Green)
+                }
+                Some(ModelPricing { input, output }) => Cell::new(format!("${input} / ${output}")),
+                None => Cell::new(""),
+            };
+
             table.add_row([
                 Cell::new(model.id()).add_attribute(Attribute::Bold),
                 match model.r#type() {
@@ -120,6 +132,7 @@ impl List {
                 Cell::new(model.name()),
                 Cell::new(model.version()),
                 Cell::new(io),
+                pricing,
             ]);
         }
 
@@ -131,7 +144,8 @@ impl List {
 
 /// Execute a model task
 ///
-/// Mainly intended for testing of model selection and routing.
+/// Mainly intended for testing of model selection and routing. The usage of
+/// models during the session (i.e. this command) is shown after the output.
 #[derive(Debug, Args)]
 struct Execute {
     prompt: String,
@@ -185,10 +199,16 @@ impl Execute {
         .to_stdout();
 
         let output = model.perform_task(&task).await?;
+        if let Some(usage) = &output.usage {
+            usage::record(usage);
+        }
 
         Code::new(Format::Markdown, "# Generated output\n").to_stdout();
         Code::new(Format::Yaml, &serde_yaml::to_string(&output)?).to_stdout();
 
+        Code::new(Format::Markdown, "# Session usage\n").to_stdout();
+        Code::new(Format::Yaml, &serde_yaml::to_string(&usage::session())?).to_stdout();
+
         Ok(())
     }
 }
@@ -3,22 +3,31 @@
 use std::{cmp::Ordering, collections::HashMap, sync::Arc};
 
 use model::common::{
-    eyre::{bail, Result},
-    futures::future::join_all,
+    eyre::{bail, OptionExt, Result},
+    futures::{future::join_all, StreamExt},
     itertools::Itertools,
+    tokio::time::sleep,
     tracing,
 };
 
 pub use model::{
-    Model, ModelAvailability, ModelOutput, ModelOutputKind, ModelSpecification, ModelTask,
-    ModelType,
+    Model, ModelAvailability, ModelOutput, ModelOutputKind, ModelPricing, ModelSpecification,
+    ModelStream, ModelStreamItem, ModelTask, ModelTaskKind, ModelTool, ModelToolCall,
+    ModelToolResult, ModelToolRound, ModelType, ModelUsage,
 };
 
+pub mod cassette;
 pub mod cli;
+pub mod usage;
+
+mod policy;
+
+pub use policy::ids_for_instruction;
+use policy::{Attempts, Next};
 
 /// Get a list of available models
 pub async fn list() -> Vec<Arc<dyn Model>> {
-    let futures = (0..=6).map(|provider| async move {
+    let futures = (0..=7).map(|provider| async move {
         let (provider, result) = match provider {
             0 => ("Anthropic", models_anthropic::list().await),
             1 => ("Google", models_google::list().await),
@@ -27,6 +36,8 @@ pub async fn list() -> Vec<Arc<dyn Model>> {
             4 => ("OpenAI", models_openai::list().await),
             5 => ("Plugins", plugins::models::list().await),
             6 => ("Stencila", models_stencila::list().await),
+            #[cfg(feature = "models-local")]
+            7 => ("Local", models_local::list().await),
             _ => return vec![],
         };
 
@@ -73,10 +84,33 @@ pub async fn list() -> Vec<Arc<dyn Model>> {
         .collect_vec()
 }
 
+/// The maximum number of models selected for a task
+///
+/// The first model is the preferred model and the others are fallbacks
+/// used if it fails.
+const MAX_MODELS: usize = 3;
+
 /// Select a model based on selection criteria of the `ModelParameters`
 #[tracing::instrument(skip_all)]
 pub async fn select(task: &ModelTask) -> Result<Arc<dyn Model>> {
-    tracing::trace!("Selecting a model for task");
+    select_all(task)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_eyre("No AI models available that support this task")
+}
+
+/// Select the models to use for a task, in order of preference
+///
+/// If the task includes model ids then, for each id in order, the available model
+/// with exactly that id, or otherwise the first available model with an id containing
+/// it, is selected (see [`ids_for_instruction`] for how ids can be restricted to an
+/// instruction type). Otherwise, the first available model router is selected
+/// followed by available models that support the task.
+/// At most [`MAX_MODELS`] models are selected.
+#[tracing::instrument(skip_all)]
+pub async fn select_all(task: &ModelTask) -> Result<Vec<Arc<dyn Model>>> {
+    tracing::trace!("Selecting models for task");
 
     // Get the list models
     let models = list().await;
@@ -94,59 +128,199 @@ pub async fn select(task: &ModelTask) -> Result<Arc<dyn Model>> {
         bail!(message)
     }
 
-    // If the task includes model ids, use the first model matching on of the
-    // ids (there should normally only be one) and error if not found
+    let models = models
+        .into_iter()
+        .filter(|model| model.is_available())
+        .collect_vec();
+
+    // If the task includes model ids, use the first model matching each of the
+    // ids, in order, and error if none are found
     if let Some(model_ids) = task
         .model_parameters
         .as_ref()
         .and_then(|pars| pars.model_ids.as_ref())
     {
-        for model in models {
-            if !model.is_available() {
-                continue;
-            }
-            for id in model_ids {
-                if id == "*" || model.id().contains(id) {
-                    return Ok(model);
-                }
+        let model_ids = ids_for_instruction(model_ids, task.instruction_type.as_ref());
+
+        let mut selected: Vec<Arc<dyn Model>> = Vec::new();
+        for id in &model_ids {
+            let unselected =
+                |model: &&Arc<dyn Model>| !selected.iter().any(|other| other.id() == model.id());
+
+            // Prefer a model with exactly the id (e.g. so that `openai/gpt-4o` does
+            // not select `openai/gpt-4o-mini` if that is listed first)
+            if let Some(model) = models
+                .iter()
+                .filter(unselected)
+                .find(|model| model.id() == *id)
+                .or_else(|| {
+                    models
+                        .iter()
+                        .filter(unselected)
+                        .find(|model| id == "*" || model.id().contains(id))
+                })
+            {
+                selected.push(model.clone());
             }
         }
 
-        bail!("No model with id matching '{}'", model_ids.join(","))
-    }
+        if selected.is_empty() {
+            bail!("No model with id matching '{}'", model_ids.join(","))
+        }
 
-    // If the task does not specify model ids and a model router is available
-    // then use the first router
-    if let Some(model) = models
-        .iter()
-        .find(|model| model.is_available() && matches!(model.r#type(), ModelType::Router))
-    {
-        return Ok(model.clone());
+        if selected.len() > MAX_MODELS {
+            tracing::warn!(
+                "Only the first {MAX_MODELS} of the {} models matching '{}' will be used: ignoring {}",
+                selected.len(),
+                model_ids.join(","),
+                selected[MAX_MODELS..].iter().map(|model| model.id()).join(", ")
+            );
+            selected.truncate(MAX_MODELS);
+        }
+        return Ok(selected);
     }
 
-    // Filter according to whether the task is supported
-    let mut models = models
-        .into_iter()
-        .filter(|model| {
-            if !model.is_available() || !model.supports_task(task) {
-                return false;
-            }
-            true
-        })
+    // If the task does not specify model ids then use the first model router
+    // (if any) followed by the models that support the task
+    let routers = models
+        .iter()
+        .filter(|model| matches!(model.r#type(), ModelType::Router))
+        .take(1);
+    let supporting = models
+        .iter()
+        .filter(|model| !matches!(model.r#type(), ModelType::Router) && model.supports_task(task));
+    let selected = routers
+        .chain(supporting)
+        .take(MAX_MODELS)
+        .cloned()
         .collect_vec();
 
-    if models.is_empty() {
+    if selected.is_empty() {
         bail!("No AI models available that support this task")
     }
 
-    Ok(models.swap_remove(0))
+    Ok(selected)
 }
 
 /// Perform a model task
+///
+/// If the preferred model for the task fails, the task is retried and/or
+/// performed by fallback models.
 #[tracing::instrument(skip_all)]
 pub async fn perform_task(task: ModelTask) -> Result<ModelOutput> {
     tracing::debug!("Performing model task");
 
-    let model = select(&task).await?;
-    model.perform_task(&task).await
+    if let Some(output) = cassette::replay(&task).await? {
+        return Ok(output);
+    }
+
+    perform(&task, None::<fn(&str)>).await
+}
+
+/// Perform a model task, streaming the generated text
+///
+/// The `on_text` callback is called with all the text generated so far each
+/// time a new chunk arrives. For tasks other than message generation (e.g. image
+/// generation), and tasks with tools (so that tool calls are returned in the output),
+/// the task is performed without streaming.
+///
+/// The usage of streamed outputs is that reported by the model at the end of the
+/// stream or, if the model does not report it, estimated from the length of the text.
+///
+/// As for [`perform_task`], the task is retried and/or performed by fallback
+/// models if necessary.
+#[tracing::instrument(skip_all)]
+pub async fn perform_task_stream<F>(task: ModelTask, mut on_text: F) -> Result<ModelOutput>
+where
+    F: FnMut(&str) + Send,
+{
+    tracing::debug!("Performing model task with streaming");
+
+    if let Some(output) = cassette::replay(&task).await? {
+        if output.kind == ModelOutputKind::Text {
+            on_text(&output.content);
+        }
+        return Ok(output);
+    }
+
+    perform(&task, Some(on_text)).await
+}
+
+/// Perform a model task with the selected models, retrying and falling back as necessary
+///
+/// Messages about any retries and fallbacks are added to the output.
+async fn perform<F>(task: &ModelTask, mut on_text: Option<F>) -> Result<ModelOutput>
+where
+    F: FnMut(&str) + Send,
+{
+    let mut attempts = Attempts::default();
+    for model in select_all(task).await? {
+        if !attempts.start(model.as_ref()) {
+            continue;
+        }
+
+        loop {
+            match attempt(model.as_ref(), task, on_text.as_mut()).await {
+                Ok(mut output) => {
+                    output.messages = attempts.succeeded(model.as_ref());
+                    return Ok(output);
+                }
+                Err(error) => match attempts.failed(model.as_ref(), error) {
+                    Next::Retry(delay) => sleep(delay).await,
+                    Next::Fallback => break,
+                },
+            }
+        }
+    }
+
+    Err(attempts.error())
+}
+
+/// Make a single attempt at performing a task with a model
+///
+/// The task is streamed if `on_text` is supplied and the task is message
+/// generation without tools. The usage of the model is recorded, and the output
+/// recorded to any cassette, if the attempt succeeds.
+#[tracing::instrument(skip_all, fields(model = %model.id()))]
+async fn attempt<F>(model: &dyn Model, task: &ModelTask, on_text: Option<F>) -> Result<ModelOutput>
+where
+    F: FnMut(&str) + Send,
+{
+    let streamable = task.kind == ModelTaskKind::MessageGeneration && task.tools.is_empty();
+    let Some(mut on_text) = on_text.filter(|_| streamable) else {
+        let output = model.perform_task(task).await?;
+        if let Some(usage) = &output.usage {
+            usage::record(usage);
+        }
+        cassette::record(task, &output).await?;
+        return Ok(output);
+    };
+
+    let mut stream = model.perform_task_stream(task).await?;
+    let mut text = String::new();
+    let mut reported = None;
+    while let Some(item) = stream.next().await {
+        match item? {
+            ModelStreamItem::Text(chunk) => {
+                if chunk.is_empty() {
+                    continue;
+                }
+
+                text.push_str(&chunk);
+                on_text(&text);
+            }
+            ModelStreamItem::Usage(usage) => reported = Some(usage),
+        }
+    }
+
+    let mut output = ModelOutput::from_text(model, &task.format, text).await?;
+    if !task.dry_run {
+        let usage = reported.unwrap_or_else(|| usage::estimate(model, task, &output.content));
+        usage::record(&usage);
+        output.usage = Some(usage);
+    }
+
+    cassette::record(task, &output).await?;
+
+    Ok(output)
 }
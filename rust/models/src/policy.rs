@@ -0,0 +1,355 @@
+//! Retrying, falling back and circuit breaking when performing model tasks
+//!
+//! Transient failures (e.g. rate limiting or server errors) are retried with
+//! exponential backoff, or after the delay requested by the provider. If a model
+//! keeps failing, or fails with an error that is not transient, the next model in
+//! the fallback chain for the task is tried. Providers that fail repeatedly are
+//! skipped for a while (i.e. their "circuit" is opened) so that tasks do not wait
+//! on retries of a provider that is likely to fail again.
+
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use model::{
+    common::{
+        eyre::{eyre, Report},
+        itertools::Itertools,
+        once_cell::sync::Lazy,
+        rand, reqwest, tracing,
+    },
+    schema::{ExecutionMessage, InstructionType, MessageLevel},
+    Model, ModelApiError,
+};
+
+/// The maximum number of times a task is retried with the same model
+const MAX_RETRIES: u32 = 3;
+
+/// The delay before the first retry, doubled for each subsequent retry
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The maximum delay before a retry
+///
+/// Also applies to delays requested by providers so that a task is not
+/// blocked for long when another model could be used instead.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The number of consecutive transient failures of a provider after which its circuit is opened
+const CIRCUIT_THRESHOLD: u32 = 5;
+
+/// The time for which the circuit of a provider stays open
+const CIRCUIT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// The state of the circuit of a provider
+#[derive(Default)]
+struct Circuit {
+    /// The number of consecutive transient failures
+    failures: u32,
+
+    /// When the circuit was last opened
+    opened: Option<Instant>,
+}
+
+/// The circuits of providers, keyed by provider name
+static CIRCUITS: Lazy<Mutex<HashMap<String, Circuit>>> = Lazy::new(Mutex::default);
+
+/// Whether the circuit of a provider is open (i.e. its models should not be used)
+///
+/// Once the cooldown has passed the provider is tried again: its circuit is closed
+/// if the task succeeds and opened again if it fails.
+fn is_open(provider: &str) -> bool {
+    let Ok(circuits) = CIRCUITS.lock() else {
+        return false;
+    };
+
+    circuits
+        .get(provider)
+        .and_then(|circuit| circuit.opened)
+        .map_or(false, |opened| opened.elapsed() < CIRCUIT_COOLDOWN)
+}
+
+/// Close the circuit of a provider after a success
+fn close(provider: &str) {
+    if let Ok(mut circuits) = CIRCUITS.lock() {
+        circuits.remove(provider);
+    }
+}
+
+/// Record a transient failure of a provider, opening its circuit if necessary
+fn trip(provider: &str) {
+    let Ok(mut circuits) = CIRCUITS.lock() else {
+        return;
+    };
+
+    let circuit = circuits.entry(provider.to_string()).or_default();
+    circuit.failures += 1;
+    if circuit.failures >= CIRCUIT_THRESHOLD {
+        tracing::warn!(
+            "Skipping {provider} models for {}s after {} consecutive failures",
+            CIRCUIT_COOLDOWN.as_secs(),
+            circuit.failures
+        );
+        circuit.opened = Some(Instant::now());
+    }
+}
+
+/// Whether an error is transient and, if so, any delay requested by the provider
+///
+/// The chain of errors is searched so that errors wrapped by provider client
+/// libraries (e.g. a `reqwest` timeout wrapped in an `async-openai` error) are recognized.
+fn transient(error: &Report) -> Option<Option<Duration>> {
+    for error in error.chain() {
+        if let Some(error) = error.downcast_ref::<ModelApiError>() {
+            return error.is_transient().then_some(error.retry_after);
+        }
+
+        if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+            let transient = error.is_timeout()
+                || error.is_connect()
+                || error.status().map_or(false, |status| {
+                    status.as_u16() == 429 || status.is_server_error()
+                });
+            return transient.then_some(None);
+        }
+    }
+
+    None
+}
+
+/// The delay before a retry
+///
+/// Uses the delay requested by the provider, if any, or exponential backoff with
+/// jitter (so that concurrent tasks do not all retry at the same time).
+fn backoff(retries: u32, requested: Option<Duration>) -> Duration {
+    let delay = requested.unwrap_or_else(|| {
+        (INITIAL_BACKOFF * 2u32.saturating_pow(retries)).mul_f64(1.0 + rand::random::<f64>() / 4.0)
+    });
+    delay.min(MAX_BACKOFF)
+}
+
+/// Get the model ids that apply to a type of instruction, in order of preference
+///
+/// Ids can be restricted to a type of instruction by prefixing them with the type
+/// e.g. `fix:mistral/codestral`. Ids restricted to the instruction type come first,
+/// followed by those not restricted to any type. This allows a fallback chain to
+/// be configured for each type of instruction, as well as one for all types.
+pub fn ids_for_instruction(
+    ids: &[String],
+    instruction_type: Option<&InstructionType>,
+) -> Vec<String> {
+    let mut restricted = Vec::new();
+    let mut unrestricted = Vec::new();
+    for id in ids {
+        let prefixed = id.split_once(':').and_then(|(prefix, id)| {
+            InstructionType::from_str(prefix.trim())
+                .ok()
+                .map(|prefix| (prefix, id.trim()))
+        });
+        match prefixed {
+            Some((prefix, id)) => {
+                if Some(&prefix) == instruction_type {
+                    restricted.push(id.to_string());
+                }
+            }
+            None => unrestricted.push(id.clone()),
+        }
+    }
+
+    restricted.into_iter().chain(unrestricted).collect()
+}
+
+/// What to do after an attempt at performing a task fails
+pub(crate) enum Next {
+    /// Retry with the same model after a delay
+    Retry(Duration),
+
+    /// Try the next model in the fallback chain
+    Fallback,
+}
+
+/// The attempts at performing a task
+#[derive(Default)]
+pub(crate) struct Attempts {
+    /// The number of times the task has been retried with the current model
+    retries: u32,
+
+    /// Messages about retries and fallbacks
+    messages: Vec<ExecutionMessage>,
+
+    /// The models that failed, and the error from their last attempt
+    failures: Vec<(String, Report)>,
+
+    /// The models that were skipped because their provider's circuit was open
+    skipped: Vec<String>,
+}
+
+impl Attempts {
+    /// Start attempts with a model
+    ///
+    /// Returns `false` if the model should be skipped because the circuit
+    /// of its provider is open.
+    pub fn start(&mut self, model: &dyn Model) -> bool {
+        if is_open(&model.provider()) {
+            tracing::debug!("Skipping model `{}` because circuit is open", model.id());
+            self.skipped.push(model.id());
+            return false;
+        }
+
+        self.retries = 0;
+        true
+    }
+
+    /// Record that an attempt with a model succeeded
+    ///
+    /// Returns messages about any retries and fallbacks made.
+    pub fn succeeded(mut self, model: &dyn Model) -> Option<Vec<ExecutionMessage>> {
+        close(&model.provider());
+
+        if !self.failures.is_empty() || !self.skipped.is_empty() {
+            self.messages.push(ExecutionMessage::new(
+                MessageLevel::Warning,
+                format!("Used fallback model `{}` ({})", model.id(), self.summary()),
+            ));
+        }
+
+        (!self.messages.is_empty()).then_some(self.messages)
+    }
+
+    /// Record that an attempt with a model failed and decide what to do next
+    pub fn failed(&mut self, model: &dyn Model, error: Report) -> Next {
+        let id = model.id();
+        let provider = model.provider();
+
+        let transient = transient(&error);
+        if transient.is_some() {
+            trip(&provider);
+        }
+
+        match transient {
+            Some(requested) if self.retries < MAX_RETRIES && !is_open(&provider) => {
+                let delay = backoff(self.retries, requested);
+                self.retries += 1;
+
+                tracing::warn!("Retrying model `{id}` in {delay:?} after error: {error}");
+                self.messages.push(ExecutionMessage::new(
+                    MessageLevel::Info,
+                    format!(
+                        "Retried model `{id}` after {:.1}s because of error: {error}",
+                        delay.as_secs_f32()
+                    ),
+                ));
+
+                Next::Retry(delay)
+            }
+            _ => {
+                tracing::warn!("Model `{id}` failed: {error}");
+                self.failures.push((id, error));
+
+                Next::Fallback
+            }
+        }
+    }
+
+    /// Get the error to return when all models have failed or been skipped
+    pub fn error(mut self) -> Report {
+        if self.failures.len() == 1 && self.skipped.is_empty() {
+            if let Some((.., error)) = self.failures.pop() {
+                return error;
+            }
+        }
+
+        eyre!("No model was able to perform the task ({})", self.summary())
+    }
+
+    /// Summarize the models that failed or were skipped
+    fn summary(&self) -> String {
+        self.failures
+            .iter()
+            .map(|(id, error)| format!("`{id}` failed: {error}"))
+            .chain(
+                self.skipped
+                    .iter()
+                    .map(|id| format!("`{id}` skipped: provider has failed repeatedly")),
+            )
+            .join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instruction_ids() {
+        let ids = [
+            "openai/gpt-4o",
+            "fix:mistral/codestral",
+            "Edit: anthropic/claude",
+            "ollama/llama3.1:8b",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            ids_for_instruction(&ids, Some(&InstructionType::Fix)),
+            vec!["mistral/codestral", "openai/gpt-4o", "ollama/llama3.1:8b"]
+        );
+        assert_eq!(
+            ids_for_instruction(&ids, Some(&InstructionType::Edit)),
+            vec!["anthropic/claude", "openai/gpt-4o", "ollama/llama3.1:8b"]
+        );
+        assert_eq!(
+            ids_for_instruction(&ids, None),
+            vec!["openai/gpt-4o", "ollama/llama3.1:8b"]
+        );
+    }
+
+    #[test]
+    fn backoff_delays() {
+        assert!(backoff(0, None) >= INITIAL_BACKOFF);
+        assert!(backoff(2, None) >= INITIAL_BACKOFF * 4);
+        assert_eq!(backoff(10, None), MAX_BACKOFF);
+        assert_eq!(
+            backoff(0, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(backoff(0, Some(Duration::from_secs(600))), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn circuit_opens_and_closes() {
+        let provider = "TestProvider";
+        assert!(!is_open(provider));
+
+        for _ in 0..CIRCUIT_THRESHOLD {
+            trip(provider);
+        }
+        assert!(is_open(provider));
+
+        close(provider);
+        assert!(!is_open(provider));
+    }
+
+    #[test]
+    fn transient_errors() {
+        let error = |status: u16| -> Report {
+            ModelApiError {
+                status: reqwest::StatusCode::from_u16(status).expect("valid status"),
+                retry_after: Some(Duration::from_secs(2)),
+                message: String::new(),
+            }
+            .into()
+        };
+
+        assert_eq!(transient(&error(429)), Some(Some(Duration::from_secs(2))));
+        assert!(transient(&error(503)).is_some());
+        assert!(transient(&error(401)).is_none());
+        assert!(transient(&eyre!("Output is not valid")).is_none());
+
+        // Errors wrapped by client libraries or with context are also recognized
+        assert!(transient(&error(502).wrap_err("Request to provider failed")).is_some());
+        assert!(transient(&error(400).wrap_err("Request to provider failed")).is_none());
+    }
+}
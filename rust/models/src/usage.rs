@@ -0,0 +1,51 @@
+//! Accounting of the usage of models during the current session
+//!
+//! The usage (tokens and estimated cost) of each model task performed is added to a
+//! running total for the session (i.e. the lifetime of the current process). Outputs
+//! replayed from a cassette are not counted since they do not involve a model.
+
+use std::sync::Mutex;
+
+use model::{
+    common::{once_cell::sync::Lazy, tracing},
+    schema::MessagePart,
+    Model, ModelTask, ModelUsage,
+};
+
+/// The usage of models during the current session
+static SESSION: Lazy<Mutex<ModelUsage>> = Lazy::new(Mutex::default);
+
+/// Get the usage of models during the current session
+pub fn session() -> ModelUsage {
+    SESSION
+        .lock()
+        .map(|usage| usage.clone())
+        .unwrap_or_default()
+}
+
+/// Add the usage of a model task to that of the session
+pub(crate) fn record(usage: &ModelUsage) {
+    match SESSION.lock() {
+        Ok(mut session) => *session += usage,
+        Err(..) => tracing::error!("Unable to lock session model usage"),
+    }
+}
+
+/// Estimate the usage of a task whose output was streamed
+///
+/// Used when a model does not report its usage at the end of a stream. The usage
+/// is estimated from the text of the task's messages and the generated text.
+pub(crate) fn estimate(model: &dyn Model, task: &ModelTask, text: &str) -> ModelUsage {
+    let prompt = task
+        .messages
+        .iter()
+        .flat_map(|message| message.parts.iter())
+        .filter_map(|part| match part {
+            MessagePart::Text(text) => Some(text.value.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .concat();
+
+    ModelUsage::estimate(model.pricing(), &prompt, text)
+}
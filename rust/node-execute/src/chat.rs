@@ -1,7 +1,6 @@
 use common::{
     futures::{stream::FuturesUnordered, StreamExt},
     itertools::Itertools,
-    tokio,
 };
 use models::ModelTask;
 use schema::{
@@ -14,9 +13,10 @@ use schema::{
 
 use crate::{
     interrupt_impl,
+    model_tools::{model_task_with_tools_to_blocks_and_authors, ModelTools},
     model_utils::{
         blocks_to_message_part, blocks_to_system_message, file_to_message_part,
-        model_task_to_blocks_and_authors,
+        model_task_to_blocks_and_authors, PartialText,
     },
     prelude::*,
 };
@@ -111,6 +111,17 @@ impl Executable for Chat {
 
         tracing::debug!("Executing Chat {node_id}");
 
+        if let Some(message) = executor.budget_exceeded().await {
+            executor.patch(
+                &node_id,
+                [
+                    set(NodeProperty::ExecutionStatus, ExecutionStatus::Errors),
+                    set(NodeProperty::ExecutionMessages, vec![message]),
+                ],
+            );
+            return WalkControl::Break;
+        }
+
         // Set status to running and clear execution messages
         executor.patch(
             &node_id,
@@ -190,8 +201,13 @@ impl Executable for Chat {
         // Add a new model message, or message group, to the chat (with no content)
         // so the user can see it as running
 
-        let model_ids = match &self.model_parameters.model_ids {
-            Some(ids) => ids.clone(),
+        let model_ids = match self
+            .model_parameters
+            .model_ids
+            .as_ref()
+            .or(executor.options.models.as_ref())
+        {
+            Some(ids) => models::ids_for_instruction(ids, self.prompt.instruction_type.as_ref()),
             // If no model ids specified, use the first available model
             None => models::list()
                 .await
@@ -248,6 +264,19 @@ impl Executable for Chat {
         };
         executor.patch(&node_id, [push(NodeProperty::Content, block)]);
 
+        // Models are only given tools to inspect and run code in the document's kernels
+        // if execution bounds are explicitly set (and execution is not skipped). Tool calls
+        // are not streamed, so otherwise the model's output is streamed as it is generated.
+        let execution_bounds = self
+            .execution_bounds
+            .clone()
+            .or_else(|| executor.options.execution_bounds.clone());
+        let use_tools = execution_bounds
+            .as_ref()
+            .map_or(false, |bounds| !matches!(bounds, ExecutionBounds::Skip));
+        let execution_bounds = execution_bounds.unwrap_or_default();
+        let budget = executor.model_budget();
+
         // Create futures for each message
         let mut futures = FuturesUnordered::new();
         for (model_id, message_id) in model_ids.into_iter().zip(message_ids.into_iter()) {
@@ -261,21 +290,55 @@ impl Executable for Chat {
                 },
                 instruction_messages.clone(),
             );
+            let patch = executor.patcher();
+            let mut tools = use_tools.then(|| ModelTools::new(executor));
+            let budget = budget.clone();
             futures.push(async move {
                 let started = Timestamp::now();
-                let result = model_task_to_blocks_and_authors(task).await;
+                let mut records = Vec::new();
+                let result = match &mut tools {
+                    Some(tools) => {
+                        let result = model_task_with_tools_to_blocks_and_authors(
+                            task,
+                            tools,
+                            &budget,
+                            |block| {
+                                // Record each tool call in the message as it is completed
+                                patch(
+                                    &message_id,
+                                    vec![push(NodeProperty::Content, block.clone())],
+                                );
+                                records.push(block);
+                            },
+                        )
+                        .await;
+                        tools.stop().await;
+                        result
+                    }
+                    None => {
+                        let mut partial = PartialText::default();
+                        model_task_to_blocks_and_authors(task, |text| {
+                            // Update the content of the message as the model's output is streamed
+                            if let Some(content) = partial.decode(text) {
+                                patch(&message_id, vec![set(NodeProperty::Content, content)]);
+                            }
+                        })
+                        .await
+                    }
+                };
                 let ended = Timestamp::now();
-                (message_id, started, ended, result)
+                (message_id, started, ended, records, result)
             })
         }
 
         // Wait for each future to complete and patch content
-        let execution_bounds = self.execution_bounds.clone().unwrap_or_default();
-        while let Some((message_id, started, ended, result)) = futures.next().await {
+        while let Some((message_id, started, ended, records, result)) = futures.next().await {
             tracing::trace!("Model message finished {message_id}");
 
-            let (content, messages) = match result {
-                Ok((mut content, mut authors)) => {
+            let (mut content, messages) = match result {
+                Ok((mut content, mut authors, usage)) => {
+                    executor.add_usage(&usage).await;
+
                     // Apply model and user authorship to blocks
                     if let Some(role) = &user_author_role {
                         authors.push(role.clone());
@@ -293,7 +356,7 @@ impl Executable for Chat {
                                 tracing::error!("While executing content: {error}");
                             }
                         };
-                        tokio::spawn(execute);
+                        executor.spawn(execute);
                     }
 
                     (content, None)
@@ -311,6 +374,12 @@ impl Executable for Chat {
                 ),
             };
 
+            // Keep the records of any tool calls before the content so that
+            // they remain in the message (but are not executed)
+            if !records.is_empty() {
+                content = [records, content].concat();
+            }
+
             let status = execution_status(&messages);
             let required = execution_required_status(&status);
             let duration = execution_duration(&started, &ended);
@@ -323,7 +392,7 @@ impl Executable for Chat {
                     set(NodeProperty::ExecutionMessages, messages),
                     set(NodeProperty::ExecutionDuration, duration),
                     set(NodeProperty::ExecutionEnded, ended),
-                    append(NodeProperty::Content, content.clone()),
+                    set(NodeProperty::Content, content),
                 ],
             );
         }
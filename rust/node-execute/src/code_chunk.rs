@@ -1,6 +1,6 @@
 use schema::{CodeChunk, ExecutionBounds, LabelType, NodeProperty};
 
-use crate::{interrupt_impl, prelude::*};
+use crate::{interrupt_impl, prelude::*, repair::repair_code_chunk};
 
 impl Executable for CodeChunk {
     #[tracing::instrument(skip_all)]
@@ -179,7 +179,7 @@ impl Executable for CodeChunk {
             executor.patch(
                 &node_id,
                 [
-                    set(NodeProperty::ExecutionStatus, status),
+                    set(NodeProperty::ExecutionStatus, status.clone()),
                     set(NodeProperty::ExecutionInstance, instance),
                     set(NodeProperty::ExecutionBounded, bounded),
                     set(NodeProperty::ExecutionRequired, required),
@@ -190,6 +190,16 @@ impl Executable for CodeChunk {
                     set(NodeProperty::ExecutionDigest, compilation_digest),
                 ],
             );
+
+            // Attempt to repair the code chunk if it failed
+            if executor.options.repair
+                && matches!(
+                    status,
+                    ExecutionStatus::Errors | ExecutionStatus::Exceptions
+                )
+            {
+                repair_code_chunk(self, executor).await;
+            }
         } else {
             executor.patch(
                 &node_id,
@@ -18,7 +18,7 @@ impl Executable for IncludeBlock {
 
         // Get the content from the source
         let (content, pop_dir, mut messages) =
-            source_to_content(&self.source, &self.media_type, executor).await;
+            source_to_content(&self.source, &self.media_type, &self.select, executor).await;
 
         // Add the content to the include block
         if let Some(content) = content {
@@ -165,6 +165,7 @@ impl Executable for IncludeBlock {
 async fn source_to_content(
     source: &str,
     media_type: &Option<String>,
+    select: &Option<String>,
     executor: &mut Executor,
 ) -> (Option<Vec<Block>>, bool, Vec<CompilationMessage>) {
     let mut messages = Vec::new();
@@ -198,6 +199,7 @@ async fn source_to_content(
         &url,
         Some(DecodeOptions {
             media_type: media_type.clone(),
+            select: select.clone(),
             ..Default::default()
         }),
     )
@@ -207,6 +209,7 @@ async fn source_to_content(
             // Transform the decoded node into a blocks
             match node {
                 Node::Article(Article { content, .. }) => Some(content),
+                Node::Datatable(datatable) => Some(vec![Block::Table(datatable.to_table())]),
                 _ => {
                     messages.push(CompilationMessage::new(
                         MessageLevel::Error,
@@ -222,7 +225,8 @@ async fn source_to_content(
         }
     };
 
-    // TODO: Implement sub-selecting from included based on `select`
+    // TODO: Implement sub-selecting from included articles based on `select`
+    // (currently only used by spreadsheet codecs to select a sheet and range)
 
     (content, pop_dir, messages)
 }
@@ -4,14 +4,15 @@ use codecs::Format;
 use common::{
     futures::stream::{FuturesUnordered, StreamExt},
     itertools::Itertools,
-    tokio,
 };
 use schema::{
     Author, AuthorRole, AuthorRoleAuthor, AuthorRoleName, CompilationDigest, ExecutionBounds,
-    InstructionBlock, SoftwareApplication,
+    InstructionBlock, SoftwareApplication, SuggestionBlock, SuggestionStatus,
 };
 
-use crate::{interrupt_impl, prelude::*, state_digest};
+use models::ModelUsage;
+
+use crate::{interrupt_impl, model_utils::PartialText, prelude::*, state_digest};
 
 impl Executable for InstructionBlock {
     #[tracing::instrument(skip_all)]
@@ -77,7 +78,11 @@ impl Executable for InstructionBlock {
 
         // Get options which may be overridden if this is a revision
         // Note: to avoid accidentally generating many replicates, hard code maximum 10 here
-        let mut model_ids = self.model_parameters.model_ids.clone().clone();
+        let mut model_ids = self
+            .model_parameters
+            .model_ids
+            .clone()
+            .or_else(|| executor.options.models.clone());
         let mut replicates = (self.model_parameters.replicates.unwrap_or(1) as usize).min(10);
 
         // If this is a revision (i.e. a retry, possibly with feedback already added to suggestions)
@@ -120,6 +125,17 @@ impl Executable for InstructionBlock {
 
         tracing::debug!("Executing InstructionBlock {node_id}");
 
+        if let Some(message) = executor.budget_exceeded().await {
+            executor.patch(
+                &node_id,
+                [
+                    set(NodeProperty::ExecutionStatus, ExecutionStatus::Errors),
+                    set(NodeProperty::ExecutionMessages, vec![message]),
+                ],
+            );
+            return WalkControl::Continue;
+        }
+
         executor.patch(
             &node_id,
             [
@@ -187,32 +203,96 @@ impl Executable for InstructionBlock {
                 // Apply the model id for revisions
                 instruction.model_parameters.model_ids = Some(model_ids);
             };
+            let node_id = node_id.clone();
+            let patch = executor.patcher();
             futures.push(async move {
-                prompts::execute_instruction_block(
+                // Push a suggestion when the model starts generating text, and update
+                // its content as the model's output is streamed
+                let mut streaming: Option<SuggestionBlock> = None;
+                let mut partial = PartialText::default();
+                let result = prompts::execute_instruction_block(
                     instructors,
                     prompter,
                     &system_prompt,
                     &instruction,
                     dry_run,
+                    |text| {
+                        let Some(content) = partial.decode(text) else {
+                            return;
+                        };
+                        match &streaming {
+                            Some(suggestion) => patch(
+                                &suggestion.node_id(),
+                                vec![set(NodeProperty::Content, content)],
+                            ),
+                            None => {
+                                let suggestion = SuggestionBlock::new(content);
+                                patch(
+                                    &node_id,
+                                    vec![push(NodeProperty::Suggestions, suggestion.clone())],
+                                );
+                                streaming = Some(suggestion);
+                            }
+                        }
+                    },
                 )
-                .await
+                .await;
+                (streaming, result)
             })
         }
 
         // Wait for each future, adding the suggestion (or error message) to the instruction
         // as it arrives, and then (optionally) executing the suggestion
-        let bounds = self.execution_bounds.clone().unwrap_or_default();
-        while let Some(result) = futures.next().await {
+        let bounds = self
+            .execution_bounds
+            .clone()
+            .or_else(|| executor.options.execution_bounds.clone())
+            .unwrap_or_default();
+        let mut usage = ModelUsage::default();
+        while let Some((streaming, result)) = futures.next().await {
             match result {
-                Ok(mut suggestion) => {
-                    executor.patch(
-                        &node_id,
-                        [push(NodeProperty::Suggestions, suggestion.clone())],
+                Ok((mut suggestion, notices)) => {
+                    // Add any messages about retries and fallbacks to those of the instruction
+                    messages.extend(notices);
+
+                    usage += &ModelUsage::with_cost(
+                        suggestion.prompt_tokens.unwrap_or_default(),
+                        suggestion.completion_tokens.unwrap_or_default(),
+                        suggestion.cost,
                     );
 
+                    if let Some(streaming) = streaming {
+                        // Update the suggestion that was pushed while streaming
+                        suggestion.uid = streaming.uid;
+                        executor.patch(
+                            &suggestion.node_id(),
+                            [
+                                set(NodeProperty::Content, suggestion.content.clone()),
+                                set(NodeProperty::Authors, suggestion.authors.clone()),
+                                set(NodeProperty::Provenance, suggestion.provenance.clone()),
+                                set(
+                                    NodeProperty::ExecutionDuration,
+                                    suggestion.execution_duration.clone(),
+                                ),
+                                set(
+                                    NodeProperty::ExecutionEnded,
+                                    suggestion.execution_ended.clone(),
+                                ),
+                                set(NodeProperty::PromptTokens, suggestion.prompt_tokens),
+                                set(NodeProperty::CompletionTokens, suggestion.completion_tokens),
+                                set(NodeProperty::Cost, suggestion.cost),
+                            ],
+                        );
+                    } else {
+                        executor.patch(
+                            &node_id,
+                            [push(NodeProperty::Suggestions, suggestion.clone())],
+                        );
+                    }
+
                     if !matches!(bounds, ExecutionBounds::Skip) {
                         let mut fork = executor.fork_for_all();
-                        tokio::spawn(async move {
+                        executor.spawn(async move {
                             if let Err(error) = fork.compile_prepare_execute(&mut suggestion).await
                             {
                                 tracing::error!("While executing suggestion: {error}");
@@ -220,13 +300,36 @@ impl Executable for InstructionBlock {
                         });
                     }
                 }
-                Err(error) => messages.push(error_to_execution_message(
-                    "While executing instruction",
-                    error,
-                )),
+                Err(error) => {
+                    // Mark any partially streamed suggestion as rejected so that it
+                    // is not mistaken for a complete suggestion
+                    if let Some(streaming) = streaming {
+                        executor.patch(
+                            &streaming.node_id(),
+                            [
+                                set(NodeProperty::SuggestionStatus, SuggestionStatus::Rejected),
+                                set(
+                                    NodeProperty::Feedback,
+                                    format!("Generation of suggestion failed: {error}"),
+                                ),
+                            ],
+                        );
+                    }
+
+                    messages.push(error_to_execution_message(
+                        "While executing instruction",
+                        error,
+                    ))
+                }
             }
         }
 
+        // Add the usage of models for all replicates to that of the execution
+        if usage.total_tokens() > 0 {
+            tracing::debug!("Model usage for InstructionBlock {node_id}: {usage}");
+            executor.add_usage(&usage).await;
+        }
+
         let messages = (!messages.is_empty()).then_some(messages);
 
         let ended = Timestamp::now();
@@ -1,26 +1,35 @@
 #![recursion_limit = "256"]
 
-use std::{path::PathBuf, sync::Arc};
+use std::{future::Future, path::PathBuf, sync::Arc};
 
 use common::{
     clap::{self, Args},
     eyre::Result,
     itertools::Itertools,
     serde::{Deserialize, Serialize},
-    tokio::sync::{mpsc::UnboundedSender, RwLock, RwLockWriteGuard},
+    tokio::{
+        self,
+        sync::{mpsc::UnboundedSender, RwLock, RwLockWriteGuard},
+    },
     tracing,
 };
 use kernels::Kernels;
+use models::{
+    cassette::{CassetteMode, ScopedCassette},
+    ModelUsage,
+};
 use prompts::prompt::{DocumentContext, InstructionContext};
 use schema::{
-    AuthorRole, AuthorRoleName, Block, CompilationDigest, ExecutionBounds, ExecutionMode,
-    ExecutionStatus, Inline, Link, List, ListItem, ListOrder, Node, NodeId, NodeProperty, NodeType,
-    Paragraph, Patch, PatchOp, PatchPath, Timestamp, VisitorAsync, WalkControl, WalkNode,
+    AuthorRole, AuthorRoleName, Block, CompilationDigest, ExecutionBounds, ExecutionMessage,
+    ExecutionMode, ExecutionStatus, Inline, Link, List, ListItem, ListOrder, MessageLevel, Node,
+    NodeId, NodeProperty, NodeType, Paragraph, Patch, PatchOp, PatchPath, Timestamp, VisitorAsync,
+    WalkControl, WalkNode,
 };
 
 type NodeIds = Vec<NodeId>;
 
 mod prelude;
+pub use prelude::execution_required_digests;
 
 mod article;
 mod call_block;
@@ -36,11 +45,13 @@ mod instruction_block;
 mod instruction_inline;
 mod math_block;
 mod math_inline;
+mod model_tools;
 mod model_utils;
 mod paragraph;
 mod parameter;
 mod prompt_block;
 mod raw_block;
+mod repair;
 mod section;
 mod styled_block;
 mod styled_inline;
@@ -70,10 +81,31 @@ pub async fn execute(
     node_ids: Option<NodeIds>,
     options: Option<ExecuteOptions>,
 ) -> Result<()> {
-    let mut root = root.read().await.clone();
     let mut executor = Executor::new(home, kernels, patch_sender, node_ids, options);
-    executor.prepare(&mut root).await?;
-    executor.execute(&mut root).await
+    executor.root = Some(root.clone());
+    executor.cassette = match &executor.options {
+        ExecuteOptions {
+            scoped_cassette: Some(cassette),
+            ..
+        } => Some(cassette.clone()),
+        ExecuteOptions {
+            cassette: Some(path),
+            cassette_mode,
+            ..
+        } => Some(ScopedCassette::load(path, cassette_mode.unwrap_or_default()).await?),
+        _ => None,
+    };
+
+    let cassette = executor.cassette.clone();
+    let mut root = root.read().await.clone();
+    let execution = async {
+        executor.prepare(&mut root).await?;
+        executor.execute(&mut root).await
+    };
+    match cassette {
+        Some(cassette) => cassette.scope(execution).await,
+        None => execution.await,
+    }
 }
 
 /// Walk over a root node and interrupt it and child nodes
@@ -136,6 +168,12 @@ pub struct Executor {
     /// The kernels that will be used for execution
     kernels: Arc<RwLock<Kernels>>,
 
+    /// The root node being executed
+    ///
+    /// Used by model tools to find nodes within the document. Only
+    /// available when executing (not when compiling or interrupting).
+    root: Option<Arc<RwLock<Node>>>,
+
     /// A sender for a [`NodePatch`] channel
     ///
     /// Patches reflecting the state of nodes during execution should be sent
@@ -192,6 +230,65 @@ pub struct Executor {
 
     /// Options for execution
     options: ExecuteOptions,
+
+    /// The usage of models by instructions and chats during execution
+    ///
+    /// Shared between forks of the executor so that the usage of models when
+    /// executing suggestions counts towards the budget for the execution.
+    usage: Arc<RwLock<ModelUsage>>,
+
+    /// The cassette used to record, or replay, the outputs of models
+    ///
+    /// Loaded from the `cassette` option and scoped to the execution (and tasks
+    /// spawned by the executor) so that it does not affect other executions.
+    cassette: Option<ScopedCassette>,
+}
+
+/// The budget for model tasks during an execution
+///
+/// Can be moved into the futures of model tasks so that tasks which perform
+/// several rounds (e.g. with tool calls) can check the budget between rounds.
+#[derive(Clone)]
+pub(crate) struct ModelBudget {
+    /// The budget for the execution, if any
+    budget: Option<f64>,
+
+    /// The usage of models for the execution
+    usage: Arc<RwLock<ModelUsage>>,
+}
+
+impl ModelBudget {
+    /// Get an error message if the budget has been exceeded
+    ///
+    /// The `pending` usage is usage, not yet added to that of the execution,
+    /// which should be included when checking the budget.
+    ///
+    /// Because the budget can not be enforced for models whose pricing is unknown,
+    /// it is treated as exceeded once any such models have been used.
+    pub async fn exceeded(&self, pending: &ModelUsage) -> Option<ExecutionMessage> {
+        let budget = self.budget?;
+        let usage = self.usage.read().await;
+
+        let unpriced = usage.unpriced_tokens + pending.unpriced_tokens;
+        if unpriced > 0 {
+            return Some(ExecutionMessage {
+                level: MessageLevel::Error,
+                message: format!(
+                    "Budget for model tasks of ${budget:.2} can not be enforced because {unpriced} tokens were used by models with unknown pricing"
+                ),
+                ..Default::default()
+            });
+        }
+
+        let cost = usage.cost.unwrap_or_default() + pending.cost.unwrap_or_default();
+        (cost >= budget).then(|| ExecutionMessage {
+            level: MessageLevel::Error,
+            message: format!(
+                "Budget for model tasks of ${budget:.2} has been exceeded (estimated cost ${cost:.4})"
+            ),
+            ..Default::default()
+        })
+    }
 }
 
 /// Records information about a heading in order to created
@@ -264,7 +361,7 @@ impl HeadingInfo {
     }
 }
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Args)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Args)]
 #[serde(default, crate = "common::serde")]
 pub struct ExecuteOptions {
     /// Re-execute all node types regardless of current state
@@ -315,12 +412,81 @@ pub struct ExecuteOptions {
     #[arg(long)]
     pub skip_rejected: bool,
 
+    /// Automatically repair code chunks that fail
+    ///
+    /// When a code chunk has errors or exceptions, wrap it in a fix instruction and
+    /// execute its suggestions in a fork of the kernels, keeping only those that run
+    /// without errors or exceptions.
+    #[arg(long)]
+    pub repair: bool,
+
+    /// The maximum number of attempts to repair a failing code chunk
+    ///
+    /// Each attempt generates a new suggestion using the errors from the previous
+    /// attempt. Defaults to 3.
+    #[arg(long)]
+    pub repair_attempts: Option<u8>,
+
+    /// The cassette file to record the outputs of models to, or replay them from
+    ///
+    /// Allows instructions and chats to be executed deterministically, and without
+    /// network access, for example in tests. Can also be set using the
+    /// `STENCILA_MODELS_CASSETTE` environment variable.
+    #[arg(long)]
+    #[serde(skip)]
+    pub cassette: Option<PathBuf>,
+
+    /// Whether to record the outputs of models to the cassette, or replay them from it
+    ///
+    /// Defaults to `replay`, in which case a model task with no recorded output fails.
+    #[arg(long, requires = "cassette")]
+    #[serde(skip)]
+    pub cassette_mode: Option<CassetteMode>,
+
+    /// A cassette, already loaded by the caller, to use instead of `cassette`
+    ///
+    /// Allows a caller that performs model tasks within the scope of a cassette to
+    /// share it with the execution (which may be performed in another task).
+    #[arg(skip)]
+    #[serde(skip)]
+    pub scoped_cassette: Option<ScopedCassette>,
+
+    /// The path of the document being executed
+    ///
+    /// Used to exclude the document from searches of the passages in its workspace.
+    /// Set by the document rather than on the command line.
+    #[arg(skip)]
+    #[serde(skip)]
+    pub document_path: Option<PathBuf>,
+
     /// Prepare, but do not actually perform, execution tasks
     ///
     /// Currently only supported by instructions where it is useful for debugging the
     /// rendering of prompts without making a potentially slow generative model API request.
     #[arg(long)]
     pub dry_run: bool,
+
+    /// The ids of the models to use for instructions and chats that do not specify any
+    ///
+    /// Usually resolved from the `models` property of the document, workspace or user
+    /// configuration, but can be specified here to override those.
+    #[arg(long, value_delimiter = ',')]
+    pub models: Option<Vec<String>>,
+
+    /// The bounds on the execution of suggestions and other child nodes
+    ///
+    /// Used for nodes that do not specify bounds themselves. Usually resolved from the
+    /// `executionBounds` property of the document, workspace or user configuration.
+    #[arg(long)]
+    pub execution_bounds: Option<ExecutionBounds>,
+
+    /// The maximum cost, in US dollars, of model tasks when executing the document
+    ///
+    /// Once the estimated cost of the model tasks performed exceeds this budget, no further
+    /// instructions or chats are executed. Usually resolved from the `budget` property of
+    /// the document, workspace or user configuration.
+    #[arg(long)]
+    pub budget: Option<f64>,
 }
 
 /// A phase of an [`Executor`]
@@ -347,6 +513,7 @@ impl Executor {
         Self {
             directory_stack: vec![home],
             kernels,
+            root: None,
             patch_sender,
             node_ids,
             phase: Phase::Prepare,
@@ -362,6 +529,8 @@ impl Executor {
             temporaries: Vec::new(),
             is_last: false,
             options: options.unwrap_or_default(),
+            usage: Arc::new(RwLock::new(ModelUsage::default())),
+            cassette: None,
         }
     }
 
@@ -457,6 +626,11 @@ impl Executor {
             self.visit_node(&mut node).await?;
         }
 
+        let usage = self.usage.read().await;
+        if usage.total_tokens() > 0 {
+            tracing::info!("Model usage for execution: {usage}");
+        }
+
         Ok(())
     }
 
@@ -481,6 +655,20 @@ impl Executor {
         Ok(())
     }
 
+    /// Spawn a task, within the scope of the executor's cassette (if any)
+    ///
+    /// Used for tasks that may perform model tasks (e.g. executing suggestions)
+    /// so that they use the same cassette as the executor.
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self.cassette.clone() {
+            Some(cassette) => tokio::spawn(async move { cassette.scope(future).await }),
+            None => tokio::spawn(future),
+        };
+    }
+
     /// Obtain a write lock to the kernels
     ///
     /// Used by [`Executable`] nodes to execute and evaluate code and manage variables.
@@ -488,6 +676,27 @@ impl Executor {
         self.kernels.write().await
     }
 
+    /// Add the usage of a model task to the usage for the execution
+    async fn add_usage(&self, usage: &ModelUsage) {
+        *self.usage.write().await += usage;
+    }
+
+    /// Get an error message if the budget for model tasks has been exceeded
+    ///
+    /// Used by instructions and chats to avoid performing further model tasks
+    /// once the budget has been spent.
+    async fn budget_exceeded(&self) -> Option<ExecutionMessage> {
+        self.model_budget().exceeded(&ModelUsage::default()).await
+    }
+
+    /// Get a [`ModelBudget`] for checking the budget from within model tasks
+    fn model_budget(&self) -> ModelBudget {
+        ModelBudget {
+            budget: self.options.budget,
+            usage: self.usage.clone(),
+        }
+    }
+
     /// Get the execution status for a node based on state of node
     /// and options of the executor
     pub fn node_execution_status(
@@ -584,6 +793,34 @@ impl Executor {
         self.send_patch_ops(node_id, Some(authors), pairs)
     }
 
+    /// Create a function for patching several properties of a node
+    ///
+    /// Used where a node needs to be patched from within a future which can not
+    /// borrow the executor (e.g. when updating content as it is streamed from a model).
+    pub fn patcher(&self) -> impl Fn(&NodeId, Vec<(NodeProperty, PatchOp)>) + Send + Sync {
+        let sender = self.patch_sender.clone();
+        move |node_id, pairs| {
+            let Some(sender) = &sender else {
+                return;
+            };
+
+            let ops = pairs
+                .into_iter()
+                .map(|(property, op)| (PatchPath::from(property), op))
+                .collect();
+
+            let patch = Patch {
+                node_id: Some(node_id.clone()),
+                ops,
+                ..Default::default()
+            };
+
+            if let Err(error) = sender.send(patch) {
+                tracing::error!("When sending execution node patch: {error}")
+            }
+        }
+    }
+
     /// Send patch operations reflecting a change in the state of a node during execution
     fn send_patch_ops<P>(
         &self,
@@ -0,0 +1,398 @@
+use std::sync::Arc;
+
+use codec_markdown_trait::to_markdown;
+use common::{
+    eyre::{bail, eyre, Result},
+    itertools::Itertools,
+    serde_json::{self, json, Value},
+    tokio::sync::RwLock,
+    tracing,
+};
+use kernels::Kernels;
+use models::{ModelTask, ModelTool, ModelToolCall, ModelToolResult, ModelToolRound, ModelUsage};
+use schema::{
+    AuthorRole, Block, CodeBlock, CodeChunk, CodeChunkOptions, ExecutionBounds, ExecutionMode,
+    NodeId,
+};
+
+use crate::{model_utils::model_output_to_blocks_and_authors, prelude::*, ModelBudget};
+
+/// The maximum number of rounds of tool calls that a model can make for a task
+///
+/// Prevents a model from calling tools indefinitely.
+const MAX_TOOL_ROUNDS: usize = 10;
+
+/// The tools available to models when executing a [`Chat`](schema::Chat)
+///
+/// Variables are listed and fetched from the document's kernels. Code is
+/// executed in a fork of those kernels (created on the first call) so that
+/// exploratory code run by a model does not affect the document's state.
+pub(super) struct ModelTools {
+    /// The kernels of the document
+    kernels: Arc<RwLock<Kernels>>,
+
+    /// The fork of the kernels in which code is executed
+    fork: Option<Kernels>,
+
+    /// The root node of the document
+    root: Option<Arc<RwLock<Node>>>,
+}
+
+impl ModelTools {
+    /// Create a new set of tools for an executor
+    pub fn new(executor: &Executor) -> Self {
+        Self {
+            kernels: executor.kernels.clone(),
+            fork: None,
+            root: executor.root.clone(),
+        }
+    }
+
+    /// Get the definitions of the tools to be sent to the model
+    pub fn definitions() -> Vec<ModelTool> {
+        let code_parameters = json!({
+            "type": "object",
+            "properties": {
+                "code": {
+                    "type": "string",
+                    "description": "The code to run"
+                },
+                "language": {
+                    "type": "string",
+                    "description": "The programming language of the code (e.g. python, r). Defaults to the language of the document's main kernel."
+                }
+            },
+            "required": ["code"]
+        });
+
+        vec![
+            ModelTool {
+                name: "list_variables".to_string(),
+                description: "List the variables in the document's kernels, including their type.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {}
+                }),
+            },
+            ModelTool {
+                name: "get_variable".to_string(),
+                description: "Get the value of a variable in the document's kernels.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "The name of the variable"
+                        }
+                    },
+                    "required": ["name"]
+                }),
+            },
+            ModelTool {
+                name: "execute_code".to_string(),
+                description: "Execute code in a fork of the document's kernels and get its outputs. Changes made by the code do not affect the document.".to_string(),
+                parameters: code_parameters.clone(),
+            },
+            ModelTool {
+                name: "evaluate_code".to_string(),
+                description: "Evaluate a code expression in a fork of the document's kernels and get its value.".to_string(),
+                parameters: code_parameters,
+            },
+            ModelTool {
+                name: "find_node".to_string(),
+                description: "Get the content of a node in the document, as Markdown, using its id.".to_string(),
+                parameters: json!({
+                    "type": "object",
+                    "properties": {
+                        "node_id": {
+                            "type": "string",
+                            "description": "The id of the node"
+                        }
+                    },
+                    "required": ["node_id"]
+                }),
+            },
+        ]
+    }
+
+    /// Perform a call to a tool
+    ///
+    /// Returns the result of the call, to be sent back to the model, and a block
+    /// recording the call, to be added to the chat. Calls to run code are recorded
+    /// as locked [`CodeChunk`]s (so they are not re-executed) and other calls as
+    /// JSON [`CodeBlock`]s.
+    pub async fn call(&mut self, call: &ModelToolCall) -> (ModelToolResult, Block) {
+        tracing::debug!("Calling tool `{}`", call.name);
+
+        let (content, is_error, block) = match call.name.as_str() {
+            "execute_code" => self.run_code(&call.arguments, false).await,
+            "evaluate_code" => self.run_code(&call.arguments, true).await,
+            name => {
+                let result = match name {
+                    "list_variables" => self.list_variables().await,
+                    "get_variable" => self.get_variable(&call.arguments).await,
+                    "find_node" => self.find_node(&call.arguments).await,
+                    _ => Err(eyre!("Unknown tool `{name}`")),
+                };
+
+                let (content, is_error) = match result {
+                    Ok(content) => (content, false),
+                    Err(error) => (error.to_string(), true),
+                };
+
+                let record = json!({
+                    "tool": call.name,
+                    "arguments": call.arguments,
+                    "result": content
+                });
+                let block = Block::CodeBlock(CodeBlock {
+                    code: serde_json::to_string_pretty(&record)
+                        .unwrap_or_default()
+                        .into(),
+                    programming_language: Some("json".to_string()),
+                    ..Default::default()
+                });
+
+                (content, is_error, block)
+            }
+        };
+
+        let result = ModelToolResult {
+            call_id: call.id.clone(),
+            name: call.name.clone(),
+            content,
+            is_error,
+        };
+
+        (result, block)
+    }
+
+    /// Stop the fork of the kernels, if any
+    ///
+    /// Should be called when the tools are no longer needed so that the
+    /// kernel instances in the fork do not continue running.
+    pub async fn stop(&mut self) {
+        if let Some(fork) = self.fork.take() {
+            if let Err(error) = fork.stop().await {
+                tracing::warn!("While stopping forked kernels: {error}");
+            }
+        }
+    }
+
+    /// Get the fork of the kernels, creating it if necessary
+    async fn fork(&mut self) -> Result<&mut Kernels> {
+        if self.fork.is_none() {
+            self.fork = Some(self.kernels.read().await.fork().await?);
+        }
+
+        self.fork
+            .as_mut()
+            .ok_or_else(|| eyre!("Unable to fork kernels"))
+    }
+
+    /// List the variables in the kernels
+    ///
+    /// Uses the fork, if any, so that variables assigned by code run by the
+    /// model are included.
+    async fn list_variables(&mut self) -> Result<String> {
+        let variables = match &mut self.fork {
+            Some(fork) => fork.list().await?,
+            None => self.kernels.write().await.list().await?,
+        };
+
+        if variables.is_empty() {
+            return Ok("There are no variables".to_string());
+        }
+
+        let list = variables
+            .into_iter()
+            .map(|variable| {
+                let mut line = format!("- `{}`", variable.name);
+                if let Some(native_type) = variable.native_type {
+                    line.push_str(&format!(": {native_type}"));
+                }
+                if let Some(lang) = variable.programming_language {
+                    line.push_str(&format!(" ({lang})"));
+                }
+                line
+            })
+            .join("\n");
+
+        Ok(list)
+    }
+
+    /// Get the value of a variable from the kernels
+    async fn get_variable(&mut self, arguments: &Value) -> Result<String> {
+        let name = string_argument(arguments, "name")?;
+
+        let value = match &mut self.fork {
+            Some(fork) => fork.get(name).await?,
+            None => self.kernels.write().await.get(name).await?,
+        };
+
+        match value {
+            Some(value) => Ok(to_markdown(&value)),
+            None => bail!("No variable named `{name}`"),
+        }
+    }
+
+    /// Find a node in the document and return it as Markdown
+    async fn find_node(&mut self, arguments: &Value) -> Result<String> {
+        let node_id: NodeId = string_argument(arguments, "node_id")?.parse()?;
+
+        let Some(root) = &self.root else {
+            bail!("No document available to find node in")
+        };
+        let root = root.read().await;
+
+        match node_find::find(&*root, node_id.clone()) {
+            Some(node) => Ok(to_markdown(&node)),
+            None => bail!("No node with id `{node_id}`"),
+        }
+    }
+
+    /// Execute, or evaluate, code in the fork of the kernels
+    async fn run_code(&mut self, arguments: &Value, evaluate: bool) -> (String, bool, Block) {
+        let code = arguments
+            .get("code")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let language = arguments
+            .get("language")
+            .and_then(Value::as_str)
+            .map(String::from);
+
+        let started = Timestamp::now();
+        let result = match self.fork().await {
+            Ok(kernels) => {
+                if evaluate {
+                    kernels
+                        .evaluate(&code, language.as_deref())
+                        .await
+                        .map(|(output, messages, instance)| (vec![output], messages, instance))
+                } else {
+                    kernels.execute(&code, language.as_deref()).await
+                }
+            }
+            Err(error) => Err(error),
+        };
+        let ended = Timestamp::now();
+
+        let (outputs, messages, instance) = match result {
+            Ok(result) => result,
+            Err(error) => (
+                Vec::new(),
+                vec![error_to_execution_message("While running code", error)],
+                String::new(),
+            ),
+        };
+
+        let mut content = outputs.iter().map(to_markdown).join("\n\n");
+        for message in &messages {
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&message.formatted());
+        }
+        if content.trim().is_empty() {
+            content = "The code ran successfully with no outputs".to_string();
+        }
+
+        let messages = (!messages.is_empty()).then_some(messages);
+        let status = execution_status(&messages);
+        let is_error = matches!(
+            status,
+            ExecutionStatus::Errors | ExecutionStatus::Exceptions
+        );
+
+        let block = Block::CodeChunk(CodeChunk {
+            code: code.into(),
+            programming_language: language,
+            execution_mode: Some(ExecutionMode::Lock),
+            execution_bounds: Some(ExecutionBounds::Fork),
+            outputs: (!outputs.is_empty()).then_some(outputs),
+            options: Box::new(CodeChunkOptions {
+                execution_status: Some(status),
+                execution_instance: (!instance.is_empty()).then_some(instance),
+                execution_messages: messages,
+                execution_duration: Some(execution_duration(&started, &ended)),
+                execution_ended: Some(ended),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        (content, is_error, block)
+    }
+}
+
+/// Get a string argument of a tool call
+fn string_argument<'a>(arguments: &'a Value, name: &str) -> Result<&'a str> {
+    arguments
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| eyre!("Missing argument `{name}`"))
+}
+
+/// Performs a model task, with tools, and converts the output to blocks
+///
+/// The task is performed repeatedly until the model responds without any tool
+/// calls (or the maximum number of rounds is reached, or the budget for model
+/// tasks is exceeded). The `on_call` callback is
+/// called with the block recording each tool call as it is completed.
+/// Returns the blocks of the model's final answer, the list of author roles, and
+/// the usage of the model summed over all rounds.
+#[tracing::instrument(skip_all)]
+pub(super) async fn model_task_with_tools_to_blocks_and_authors<F>(
+    mut task: ModelTask,
+    tools: &mut ModelTools,
+    budget: &ModelBudget,
+    mut on_call: F,
+) -> Result<(Vec<Block>, Vec<AuthorRole>, ModelUsage)>
+where
+    F: FnMut(Block) + Send,
+{
+    task.tools = ModelTools::definitions();
+
+    let mut usage = ModelUsage::default();
+    loop {
+        let mut output = models::perform_task(task.clone()).await?;
+
+        if let Some(round) = &output.usage {
+            usage += round;
+        }
+
+        if output.tool_calls.is_empty() {
+            output.usage = Some(usage);
+            return model_output_to_blocks_and_authors(output).await;
+        }
+
+        if task.tool_rounds.len() >= MAX_TOOL_ROUNDS {
+            tracing::warn!("Maximum number of tool rounds reached; ignoring further tool calls");
+            output.tool_calls.clear();
+            output.usage = Some(usage);
+            return model_output_to_blocks_and_authors(output).await;
+        }
+
+        if let Some(message) = budget.exceeded(&usage).await {
+            tracing::warn!("{}; ignoring further tool calls", message.message);
+            output.tool_calls.clear();
+            output.usage = Some(usage);
+            return model_output_to_blocks_and_authors(output).await;
+        }
+
+        let mut results = Vec::new();
+        for call in &output.tool_calls {
+            let (result, block) = tools.call(call).await;
+            on_call(block);
+            results.push(result);
+        }
+
+        task.tool_rounds.push(ModelToolRound {
+            text: output.content,
+            calls: output.tool_calls,
+            results,
+        });
+    }
+}
@@ -1,10 +1,12 @@
+use std::time::{Duration, Instant};
+
 use codec_markdown_trait::{to_markdown, MarkdownCodec, MarkdownEncodeContext};
 use codecs::{DecodeOptions, Format};
 use common::{
     eyre::{bail, Result},
     tracing,
 };
-use models::{ModelOutput, ModelOutputKind, ModelTask};
+use models::{ModelOutput, ModelOutputKind, ModelTask, ModelUsage};
 use schema::{
     shortcuts::p, Article, AudioObject, AuthorRole, Block, File, ImageObject, Inline,
     InstructionMessage, Link, MessagePart, MessageRole, Node, Text, VideoObject,
@@ -85,19 +87,92 @@ pub(super) fn file_to_message_part(file: &File) -> Option<MessagePart> {
     }
 }
 
+/// Decoder of partial text generated by a model into blocks
+///
+/// Used to progressively update content as the output of a model is streamed.
+/// Because the entire text is re-decoded, and the content re-patched, each time,
+/// this is throttled to at most once every [`PartialText::CHARS`] characters or
+/// [`PartialText::MILLIS`] milliseconds (whichever comes first).
+#[derive(Default)]
+pub(super) struct PartialText {
+    /// The length of the text when it was last decoded
+    length: usize,
+
+    /// The time that the text was last decoded
+    decoded: Option<Instant>,
+}
+
+impl PartialText {
+    /// The number of characters after which the text is re-decoded
+    const CHARS: usize = 200;
+
+    /// The number of milliseconds after which the text is re-decoded
+    const MILLIS: u64 = 300;
+
+    /// Decode the text into blocks
+    ///
+    /// The text is always decoded as Markdown (the default output format of models).
+    /// Returns `None` if the text was decoded recently, or if it could not be
+    /// decoded (e.g. because it is incomplete).
+    pub fn decode(&mut self, text: &str) -> Option<Vec<Block>> {
+        if let Some(decoded) = self.decoded {
+            if text.len() < self.length + Self::CHARS
+                && decoded.elapsed() < Duration::from_millis(Self::MILLIS)
+            {
+                return None;
+            }
+        }
+
+        let (node, ..) = codec_markdown::decode(
+            text,
+            Some(DecodeOptions {
+                format: Some(Format::Markdown),
+                ..Default::default()
+            }),
+        )
+        .ok()?;
+
+        self.length = text.len();
+        self.decoded = Some(Instant::now());
+
+        match node {
+            Node::Article(Article { content, .. }) => Some(content),
+            _ => None,
+        }
+    }
+}
+
 /// Performs a model task and converts the output to blocks
 ///
-/// Returns the block and the list of author roles.
+/// The `on_text` callback is called with the text generated so far as the output of
+/// the model is streamed. Returns the block, the list of author roles, and the usage
+/// of the model.
 #[tracing::instrument(skip_all)]
-pub(super) async fn model_task_to_blocks_and_authors(
+pub(super) async fn model_task_to_blocks_and_authors<F>(
     task: ModelTask,
-) -> Result<(Vec<Block>, Vec<AuthorRole>)> {
+    on_text: F,
+) -> Result<(Vec<Block>, Vec<AuthorRole>, ModelUsage)>
+where
+    F: FnMut(&str) + Send,
+{
+    let output = models::perform_task_stream(task, on_text).await?;
+    model_output_to_blocks_and_authors(output).await
+}
+
+/// Converts the output of a model to blocks
+///
+/// Returns the blocks, the list of author roles, and the usage of the model.
+pub(super) async fn model_output_to_blocks_and_authors(
+    output: ModelOutput,
+) -> Result<(Vec<Block>, Vec<AuthorRole>, ModelUsage)> {
     let ModelOutput {
         authors,
         kind,
         format,
         content,
-    } = models::perform_task(task).await?;
+        usage,
+        ..
+    } = output;
 
     let blocks = match kind {
         ModelOutputKind::Text => {
@@ -153,5 +228,5 @@ pub(super) async fn model_task_to_blocks_and_authors(
         }
     };
 
-    Ok((blocks, authors))
+    Ok((blocks, authors, usage.unwrap_or_default()))
 }
@@ -5,7 +5,7 @@ use common::{
     tokio::sync::RwLock,
 };
 use kernels::Kernels;
-use prompts::prompt::{KernelsContext, PromptContext};
+use prompts::prompt::{KernelsContext, PromptContext, WorkspaceContext};
 use schema::{replicate, CompilationDigest, PromptBlock};
 
 use crate::{prelude::*, state_digest};
@@ -200,6 +200,7 @@ async fn prompt_executor(executor: &Executor, home: PathBuf) -> Result<Executor>
         instruction: executor.instruction_context.clone(),
         document: Some(executor.document_context.clone()),
         kernels: Some(KernelsContext::from_kernels(executor.kernels.read().await.deref()).await?),
+        workspace: Some(workspace_context(executor).await),
     };
 
     // Create a new kernel instance for the prompt context
@@ -229,3 +230,28 @@ async fn prompt_executor(executor: &Executor, home: PathBuf) -> Result<Executor>
 
     Ok(executor)
 }
+
+/// Create a workspace context for the directory of the document being executed
+///
+/// Errors while getting the workspace index are logged rather than returned so
+/// that prompts can still be executed, albeit without passages from the workspace.
+async fn workspace_context(executor: &Executor) -> WorkspaceContext {
+    let Some(dir) = executor.directory_stack.last() else {
+        return WorkspaceContext::default();
+    };
+
+    match workspace_index::get(dir).await {
+        Ok(index) => {
+            let document = executor
+                .options
+                .document_path
+                .as_ref()
+                .and_then(|path| index.relative_path(path));
+            WorkspaceContext::new(index, document)
+        }
+        Err(error) => {
+            tracing::warn!("While indexing workspace: {error}");
+            WorkspaceContext::default()
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use codec_markdown_trait::{MarkdownCodec, MarkdownEncodeContext};
+use codecs::Format;
+use common::eyre::Result;
+use kernels::Kernels;
+use models::ModelUsage;
+use schema::{
+    AuthorRole, Block, CodeChunk, ExecutionBounds, InstructionBlock, InstructionMessage,
+    InstructionType, NodeType, Patch, PatchPath, PromptBlock, SuggestionBlock,
+};
+
+use crate::prelude::*;
+
+/// The default maximum number of attempts to repair a code chunk
+const REPAIR_ATTEMPTS: u8 = 3;
+
+/// The prompt used to repair code chunks
+const REPAIR_PROMPT: &str = "stencila/fix/block";
+
+/// Attempt to repair a code chunk that has errors or exceptions
+///
+/// Wraps the code chunk in a `Fix` [`InstructionBlock`] and then, up to the
+/// maximum number of attempts, generates a suggestion and executes it in a fork
+/// of the kernels. The first suggestion that runs without errors or exceptions is
+/// added to the instruction and others are discarded. Each attempt uses the code,
+/// and errors, of the previous attempt so that the model can iterate on a fix.
+///
+/// Code chunks that are already within an instruction or suggestion are not
+/// repaired (to avoid repairing repairs).
+pub(super) async fn repair_code_chunk(chunk: &CodeChunk, executor: &mut Executor) {
+    let chunk_id = chunk.node_id();
+
+    // Find the position of the code chunk so that it can be wrapped
+    let Some(root) = executor.root.clone() else {
+        return;
+    };
+    let position = node_find::find_position(&*root.read().await, chunk_id.clone());
+    let Some(position) = position else {
+        tracing::debug!("Unable to find position of CodeChunk {chunk_id} for repair");
+        return;
+    };
+    if matches!(
+        position.parent_type,
+        NodeType::InstructionBlock | NodeType::SuggestionBlock
+    ) {
+        return;
+    }
+
+    tracing::debug!("Repairing CodeChunk {chunk_id}");
+
+    let mut instruction = InstructionBlock {
+        instruction_type: InstructionType::Fix,
+        message: InstructionMessage::from("Fix the errors in this code".to_string()),
+        prompt: PromptBlock {
+            instruction_type: Some(InstructionType::Fix),
+            target: Some(REPAIR_PROMPT.to_string()),
+            ..Default::default()
+        },
+        execution_bounds: Some(ExecutionBounds::Fork),
+        ..Default::default()
+    };
+    let node_id = instruction.node_id();
+
+    // Wrap the code chunk in the instruction
+    let value = Block::InstructionBlock(instruction.clone())
+        .to_value()
+        .unwrap_or_default();
+    let index = position.index;
+    executor.send_patch(Patch {
+        node_id: Some(position.parent_id),
+        ops: vec![(
+            PatchPath::from(position.property),
+            PatchOp::Wrap((index..(index + 1), value, NodeProperty::Content)),
+        )],
+        ..Default::default()
+    });
+    instruction.content = Some(vec![Block::CodeChunk(chunk.clone())]);
+
+    executor.patch(
+        &node_id,
+        [set(NodeProperty::ExecutionStatus, ExecutionStatus::Running)],
+    );
+
+    let started = Timestamp::now();
+    let mut messages = Vec::new();
+
+    instruction.prompt.compile(executor).await;
+
+    let attempts = executor
+        .options
+        .repair_attempts
+        .unwrap_or(REPAIR_ATTEMPTS)
+        .max(1);
+    let mut repaired = false;
+    for attempt in 1..=attempts {
+        tracing::trace!("Repair attempt {attempt} for CodeChunk {chunk_id}");
+
+        // Check the budget before each attempt since each one is a model task
+        if let Some(message) = executor.budget_exceeded().await {
+            messages.push(message);
+            break;
+        }
+
+        // Execute the prompt, with the instruction context, and render it into a system prompt
+        executor.instruction_context = Some((&instruction).into());
+        instruction.prompt.execute(executor).await;
+        executor.instruction_context = None;
+
+        let mut context = MarkdownEncodeContext::new(Some(Format::Markdown), Some(true));
+        instruction.prompt.content.to_markdown(&mut context);
+        let system_prompt = context.content;
+
+        let prompter = AuthorRole {
+            last_modified: Some(Timestamp::now()),
+            ..Default::default()
+        };
+
+        let mut suggestion = match prompts::execute_instruction_block(
+            Vec::new(),
+            prompter,
+            &system_prompt,
+            &instruction,
+            executor.options.dry_run,
+            |_| {},
+        )
+        .await
+        {
+            Ok((suggestion, notices)) => {
+                messages.extend(notices);
+                executor
+                    .add_usage(&ModelUsage::with_cost(
+                        suggestion.prompt_tokens.unwrap_or_default(),
+                        suggestion.completion_tokens.unwrap_or_default(),
+                        suggestion.cost,
+                    ))
+                    .await;
+                suggestion
+            }
+            Err(error) => {
+                messages.push(error_to_execution_message("While generating repair", error));
+                break;
+            }
+        };
+
+        match execute_suggestion(&mut suggestion, executor).await {
+            Ok(true) => {
+                executor.patch(&node_id, [push(NodeProperty::Suggestions, suggestion)]);
+                repaired = true;
+                break;
+            }
+            Ok(false) => {
+                // Use the first failed code chunk, and its errors, for the next attempt
+                if let Some(failed) = suggestion.content.into_iter().find(|block| {
+                    matches!(
+                        block,
+                        Block::CodeChunk(chunk) if has_errors(&chunk.options.execution_status)
+                    )
+                }) {
+                    instruction.content = Some(vec![failed]);
+                }
+            }
+            Err(error) => {
+                messages.push(error_to_execution_message("While executing repair", error));
+                break;
+            }
+        }
+    }
+
+    if !repaired
+        && !messages
+            .iter()
+            .any(|message| message.level >= MessageLevel::Error)
+    {
+        messages.push(ExecutionMessage {
+            level: MessageLevel::Warning,
+            message: format!(
+                "Unable to repair code chunk in {attempts} attempt{}",
+                if attempts == 1 { "" } else { "s" }
+            ),
+            ..Default::default()
+        });
+    }
+
+    let messages = (!messages.is_empty()).then_some(messages);
+
+    let ended = Timestamp::now();
+    let status = execution_status(&messages);
+    let required = execution_required_status(&status);
+    let duration = execution_duration(&started, &ended);
+
+    executor.patch(
+        &node_id,
+        [
+            set(NodeProperty::ExecutionStatus, status),
+            set(NodeProperty::ExecutionRequired, required),
+            set(NodeProperty::ExecutionMessages, messages),
+            set(NodeProperty::ExecutionDuration, duration),
+            set(NodeProperty::ExecutionEnded, ended),
+            set(NodeProperty::ExecutionCount, 1i64),
+        ],
+    );
+}
+
+/// Execute the code chunks in a suggestion in a fork of the kernels
+///
+/// Updates the outputs and execution properties of each code chunk. Returns `true`
+/// if all code chunks ran without errors or exceptions. Suggestions without any
+/// code chunks are treated as failed because they can not be verified.
+async fn execute_suggestion(suggestion: &mut SuggestionBlock, executor: &Executor) -> Result<bool> {
+    let mut kernels = executor.kernels().await.fork().await?;
+
+    let result = execute_suggestion_chunks(suggestion, &mut kernels).await;
+
+    // Stop the forked kernels regardless of whether execution succeeded
+    if let Err(error) = kernels.stop().await {
+        tracing::warn!("While stopping forked kernels: {error}");
+    }
+
+    result
+}
+
+/// Execute the code chunks of a suggestion in a fork of the kernels
+async fn execute_suggestion_chunks(
+    suggestion: &mut SuggestionBlock,
+    kernels: &mut Kernels,
+) -> Result<bool> {
+    let mut succeeded = true;
+    let mut count = 0;
+    for block in suggestion.content.iter_mut() {
+        let Block::CodeChunk(chunk) = block else {
+            continue;
+        };
+        count += 1;
+
+        let started = Timestamp::now();
+        let (outputs, messages, instance) = kernels
+            .execute(&chunk.code, chunk.programming_language.as_deref())
+            .await?;
+        let ended = Timestamp::now();
+
+        let outputs = (!outputs.is_empty()).then_some(outputs);
+        let messages = (!messages.is_empty()).then_some(messages);
+        let status = Some(execution_status(&messages));
+
+        if has_errors(&status) {
+            succeeded = false;
+        }
+
+        chunk.outputs = outputs;
+        chunk.options.execution_messages = messages;
+        chunk.options.execution_status = status;
+        chunk.options.execution_instance = Some(instance);
+        chunk.options.execution_bounded = Some(ExecutionBounds::Fork);
+        chunk.options.execution_duration = Some(execution_duration(&started, &ended));
+        chunk.options.execution_ended = Some(ended);
+    }
+
+    Ok(succeeded && count > 0)
+}
+
+/// Whether an execution status indicates errors or exceptions
+fn has_errors(status: &Option<ExecutionStatus>) -> bool {
+    matches!(
+        status,
+        Some(ExecutionStatus::Errors | ExecutionStatus::Exceptions)
+    )
+}
@@ -0,0 +1,131 @@
+#![recursion_limit = "256"]
+
+//! Tests of the repair of failing code chunks using model outputs replayed from a cassette
+
+use std::{collections::BTreeMap, path::Path, sync::Arc, thread};
+
+use codecs::Format;
+use common::{
+    eyre::{eyre, OptionExt, Result},
+    serde_json::{self, json},
+    tempfile::tempdir,
+    tokio::{
+        self,
+        fs::write,
+        sync::{mpsc, RwLock},
+    },
+};
+use kernels::Kernels;
+use models::{cassette::CassetteMode, ModelOutput, ModelOutputKind, ModelTask};
+use node_execute::{execute, ExecuteOptions};
+use schema::{
+    shortcuts::{art, cc},
+    Node, NodeProperty, Patch, PatchOp, PatchPath,
+};
+
+/// The stack size of the threads used for the test
+const STACK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Execute a document and collect the patches sent by the executor
+async fn patches(home: &Path, root: &Node, options: &ExecuteOptions) -> Result<Vec<Patch>> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let kernels = Kernels::new(home);
+    execute(
+        home.to_path_buf(),
+        Arc::new(RwLock::new(root.clone())),
+        Arc::new(RwLock::new(kernels)),
+        Some(sender),
+        None,
+        Some(options.clone()),
+    )
+    .await?;
+
+    let mut patches = Vec::new();
+    while let Ok(patch) = receiver.try_recv() {
+        patches.push(patch);
+    }
+    Ok(patches)
+}
+
+/// Test that a failing code chunk is repaired using a suggestion from a model
+///
+/// The first execution, with an empty cassette, fails to generate a repair and is
+/// used to get the key of the model task. The second execution replays a fix for
+/// that task and should result in a suggestion being added to the fix instruction.
+///
+/// Run on a thread with a larger stack than the default for tests because walking
+/// and executing the document in unoptimized builds uses a lot of stack.
+#[test]
+fn repair_code_chunk() -> Result<()> {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .thread_stack_size(STACK_SIZE)
+                .build()?
+                .block_on(repair_code_chunk_async())
+        })?
+        .join()
+        .map_err(|_| eyre!("test thread panicked"))?
+}
+
+async fn repair_code_chunk_async() -> Result<()> {
+    let dir = tempdir()?;
+    let home = dir.path();
+    let cassette = home.join("cassette.json");
+
+    let root = art([cc("undefinedVariable", Some("quickjs"))]);
+    let options = ExecuteOptions {
+        repair: true,
+        repair_attempts: Some(1),
+        cassette: Some(cassette.clone()),
+        cassette_mode: Some(CassetteMode::Replay),
+        ..Default::default()
+    };
+
+    // Without a recorded output the repair fails with a message containing the key of the task
+    let first = format!("{:?}", patches(home, &root, &options).await?);
+    let key = first
+        .split("No recorded output for model task `")
+        .nth(1)
+        .and_then(|rest| rest.split('`').next())
+        .ok_or_eyre("should have a message with the key of the task")?;
+
+    // Record a fix for the task and execute again
+    let output = ModelOutput {
+        kind: ModelOutputKind::Text,
+        format: Format::Markdown,
+        content: "```js exec\n1 + 1\n```\n".to_string(),
+        ..Default::default()
+    };
+    write(
+        &cassette,
+        serde_json::to_string_pretty(&BTreeMap::from([(
+            key,
+            json!({
+                "task": ModelTask::default(),
+                "output": output,
+            }),
+        )]))?,
+    )
+    .await?;
+
+    let second = patches(home, &root, &options).await?;
+    let repaired = second.iter().any(|patch| {
+        patch.ops.iter().any(|(path, op)| {
+            path == &PatchPath::from(NodeProperty::Suggestions) && matches!(op, PatchOp::Push(..))
+        })
+    });
+    assert!(
+        repaired,
+        "should have pushed a suggestion to the instruction"
+    );
+
+    let second = format!("{second:?}");
+    assert!(!second.contains("No recorded output"));
+    assert!(!second.contains("Unable to repair code chunk"));
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use schema::{Node, NodeId, Visitor, WalkControl, WalkNode};
+use schema::{Node, NodeId, NodeProperty, NodeType, Visitor, WalkControl, WalkNode};
 
 /// Find a node with a given [`NodeId`] within another node
 pub fn find<T>(node: &T, node_id: NodeId) -> Option<Node>
@@ -142,3 +142,305 @@ impl Visitor for Finder {
         WalkControl::Continue
     }
 }
+
+/// Find all nodes with one of the given [`NodeType`]s within another node
+///
+/// Nodes are returned in the order that they are encountered in a
+/// depth-first walk of `node`.
+pub fn find_all<T>(node: &T, node_types: &[NodeType]) -> Vec<Node>
+where
+    T: WalkNode,
+{
+    let mut collector = Collector {
+        node_types,
+        nodes: Vec::new(),
+    };
+    collector.visit(node);
+    collector.nodes
+}
+
+/// A visitor that walks over a node and collects descendants having
+/// one of the given node types
+struct Collector<'lt> {
+    node_types: &'lt [NodeType],
+    nodes: Vec<Node>,
+}
+
+impl Collector<'_> {
+    /// Should a node of the given type be collected?
+    fn matches(&self, node_type: NodeType) -> bool {
+        self.node_types.contains(&node_type)
+    }
+}
+
+impl Visitor for Collector<'_> {
+    fn visit_node(&mut self, node: &Node) -> WalkControl {
+        if self.matches(node.node_type()) {
+            self.nodes.push(node.clone());
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_block(&mut self, block: &schema::Block) -> WalkControl {
+        if self.matches(block.node_type()) {
+            self.nodes.push(block.clone().into());
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &schema::Inline) -> WalkControl {
+        if self.matches(inline.node_type()) {
+            self.nodes.push(inline.clone().into());
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_if_block_clause(&mut self, clause: &schema::IfBlockClause) -> WalkControl {
+        if self.matches(NodeType::IfBlockClause) {
+            self.nodes.push(Node::IfBlockClause(clause.clone()));
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_list_item(&mut self, list_item: &schema::ListItem) -> WalkControl {
+        if self.matches(NodeType::ListItem) {
+            self.nodes.push(Node::ListItem(list_item.clone()));
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_suggestion_block(&mut self, block: &schema::SuggestionBlock) -> WalkControl {
+        if self.matches(block.node_type()) {
+            self.nodes.push(Node::SuggestionBlock(block.clone()));
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_suggestion_inline(&mut self, inline: &schema::SuggestionInline) -> WalkControl {
+        if self.matches(inline.node_type()) {
+            self.nodes.push(Node::SuggestionInline(inline.clone()));
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_table_row(&mut self, table_row: &schema::TableRow) -> WalkControl {
+        if self.matches(NodeType::TableRow) {
+            self.nodes.push(Node::TableRow(table_row.clone()));
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_table_cell(&mut self, table_cell: &schema::TableCell) -> WalkControl {
+        if self.matches(NodeType::TableCell) {
+            self.nodes.push(Node::TableCell(table_cell.clone()));
+        }
+
+        WalkControl::Continue
+    }
+
+    fn visit_walkthrough_step(
+        &mut self,
+        walkthrough_step: &schema::WalkthroughStep,
+    ) -> WalkControl {
+        if self.matches(NodeType::WalkthroughStep) {
+            self.nodes
+                .push(Node::WalkthroughStep(walkthrough_step.clone()));
+        }
+
+        WalkControl::Continue
+    }
+}
+
+/// The position of a block or inline node within its parent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// The type of the parent node
+    pub parent_type: NodeType,
+
+    /// The id of the parent node
+    pub parent_id: NodeId,
+
+    /// The property of the parent node that the node is within
+    pub property: NodeProperty,
+
+    /// The index of the node within the property
+    pub index: usize,
+}
+
+/// Find the position of a block or inline node with a given [`NodeId`]
+/// within another node
+///
+/// Useful for generating patches that replace or wrap the node (which
+/// need to be applied to the parent).
+pub fn find_position<T>(node: &T, node_id: NodeId) -> Option<Position>
+where
+    T: WalkNode,
+{
+    let mut locator = Locator {
+        node_id,
+        structs: Vec::new(),
+        properties: Vec::new(),
+        indices: Vec::new(),
+        position: None,
+    };
+    locator.visit(node);
+    locator.position
+}
+
+/// A visitor that walks over a node, keeping track of the current path,
+/// and attempts to find the position of a descendant with the given node id
+struct Locator {
+    node_id: NodeId,
+    structs: Vec<(NodeType, NodeId)>,
+    properties: Vec<NodeProperty>,
+    indices: Vec<usize>,
+    position: Option<Position>,
+}
+
+impl Locator {
+    /// Record the position if the node has the node id
+    fn locate(&mut self, node_id: Option<NodeId>) -> WalkControl {
+        if self.position.is_some() {
+            return WalkControl::Break;
+        }
+
+        if node_id.as_ref() == Some(&self.node_id) {
+            if let (Some((parent_type, parent_id)), Some(property), Some(index)) = (
+                self.structs.last(),
+                self.properties.last(),
+                self.indices.last(),
+            ) {
+                self.position = Some(Position {
+                    parent_type: *parent_type,
+                    parent_id: parent_id.clone(),
+                    property: *property,
+                    index: *index,
+                });
+            }
+            return WalkControl::Break;
+        }
+
+        WalkControl::Continue
+    }
+
+    /// Break walk if node has been found
+    fn walk_control(&self) -> WalkControl {
+        match self.position {
+            Some(..) => WalkControl::Break,
+            None => WalkControl::Continue,
+        }
+    }
+}
+
+impl Visitor for Locator {
+    fn enter_struct(&mut self, node_type: NodeType, node_id: NodeId) -> WalkControl {
+        self.structs.push((node_type, node_id));
+        self.walk_control()
+    }
+
+    fn exit_struct(&mut self) {
+        self.structs.pop();
+    }
+
+    fn enter_property(&mut self, property: NodeProperty) -> WalkControl {
+        self.properties.push(property);
+        self.walk_control()
+    }
+
+    fn exit_property(&mut self) {
+        self.properties.pop();
+    }
+
+    fn enter_index(&mut self, index: usize) -> WalkControl {
+        self.indices.push(index);
+        self.walk_control()
+    }
+
+    fn exit_index(&mut self) {
+        self.indices.pop();
+    }
+
+    fn visit_block(&mut self, block: &schema::Block) -> WalkControl {
+        self.locate(block.node_id())
+    }
+
+    fn visit_inline(&mut self, inline: &schema::Inline) -> WalkControl {
+        self.locate(inline.node_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schema::shortcuts::{art, cc, p, sec, t};
+
+    use super::*;
+
+    #[test]
+    fn find_position_top_level() {
+        let block = p([t("b")]);
+        let node_id = block.node_id().expect("has id");
+        let article = art([p([t("a")]), block]);
+
+        let position = find_position(&article, node_id).expect("should be found");
+        assert_eq!(position.parent_type, NodeType::Article);
+        assert_eq!(position.parent_id, article.node_id().expect("has id"));
+        assert_eq!(position.property, NodeProperty::Content);
+        assert_eq!(position.index, 1);
+    }
+
+    #[test]
+    fn find_position_nested_sections() {
+        let chunk = cc("1 + 1", Some("rhai"));
+        let chunk_id = chunk.node_id().expect("has id");
+
+        let inner = sec([p([t("a")]), p([t("b")]), chunk]);
+        let inner_id = inner.node_id().expect("has id");
+
+        let outer = sec([p([t("c")]), inner]);
+        let outer_id = outer.node_id().expect("has id");
+
+        let article = art([p([t("d")]), p([t("e")]), outer, p([t("f")])]);
+
+        let position = find_position(&article, chunk_id).expect("should be found");
+        assert_eq!(
+            position,
+            Position {
+                parent_type: NodeType::Section,
+                parent_id: inner_id.clone(),
+                property: NodeProperty::Content,
+                index: 2,
+            }
+        );
+
+        let position = find_position(&article, inner_id).expect("should be found");
+        assert_eq!(
+            position,
+            Position {
+                parent_type: NodeType::Section,
+                parent_id: outer_id.clone(),
+                property: NodeProperty::Content,
+                index: 1,
+            }
+        );
+
+        let position = find_position(&article, outer_id).expect("should be found");
+        assert_eq!(position.parent_type, NodeType::Article);
+        assert_eq!(position.index, 2);
+    }
+
+    #[test]
+    fn find_position_missing() {
+        let article = art([sec([p([t("a")])])]);
+        let other = p([t("b")]).node_id().expect("has id");
+
+        assert_eq!(find_position(&article, other), None);
+    }
+}
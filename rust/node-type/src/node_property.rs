@@ -25,6 +25,7 @@ pub enum NodeProperty {
     AvailableLanguages,
     Bitrate,
     Brands,
+    Budget,
     Caption,
     CellType,
     Cells,
@@ -49,6 +50,7 @@ pub enum NodeProperty {
     Comments,
     CompilationDigest,
     CompilationMessages,
+    CompletionTokens,
     Config,
     ContactPoints,
     Contains,
@@ -56,6 +58,7 @@ pub enum NodeProperty {
     ContentSize,
     ContentUrl,
     Contributors,
+    Cost,
     CostWeight,
     Css,
     DateAccepted,
@@ -127,6 +130,7 @@ pub enum NodeProperty {
     InstructionTypes,
     IsActive,
     IsChecked,
+    IsCodeHidden,
     IsCollapsed,
     IsDisabled,
     IsFolded,
@@ -146,6 +150,7 @@ pub enum NodeProperty {
     ItemsValidator,
     Iterations,
     JobTitle,
+    Kernels,
     Keys,
     Keywords,
     Label,
@@ -174,6 +179,7 @@ pub enum NodeProperty {
     MinimumScore,
     ModelIds,
     ModelParameters,
+    Models,
     MultipleOf,
     Name,
     NativeHint,
@@ -207,6 +213,7 @@ pub enum NodeProperty {
     ProductId,
     ProgrammingLanguage,
     Prompt,
+    PromptTokens,
     PropertyId,
     Provenance,
     ProvenanceCategory,
@@ -27,11 +27,13 @@ pub enum NodeType {
     Article,
     AudioObject,
     AuthorRole,
+    Book,
     BooleanValidator,
     Brand,
     Button,
     CallArgument,
     CallBlock,
+    Chapter,
     Chat,
     ChatMessage,
     ChatMessageGroup,
@@ -142,6 +144,7 @@ pub enum NodeType {
     TableRow,
     Text,
     ThematicBreak,
+    Thesis,
     Thing,
     Time,
     TimeValidator,
@@ -172,12 +175,14 @@ impl TryFrom<&NodeId> for NodeType {
             "art" => Article,
             "aud" => AudioObject,
             "aut" => AuthorRole,
+            "bok" => Book,
             "boo" => Boolean,
             "bov" => BooleanValidator,
             "bra" => Brand,
             "but" => Button,
             "cla" => CallArgument,
             "clb" => CallBlock,
+            "chp" => Chapter,
             "cht" => Chat,
             "chm" => ChatMessage,
             "cmg" => ChatMessageGroup,
@@ -294,6 +299,7 @@ impl TryFrom<&NodeId> for NodeType {
             "tbr" => TableRow,
             "txt" => Text,
             "thb" => ThematicBreak,
+            "ths" => Thesis,
             "thi" => Thing,
             "tme" => Time,
             "tmv" => TimeValidator,
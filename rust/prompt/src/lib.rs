@@ -8,11 +8,13 @@ mod document;
 mod instruction;
 mod kernels;
 mod prelude;
+mod workspace;
 
 // Export parts of context with renaming to avoid ambiguities
 pub use document::Document as DocumentContext;
 pub use instruction::Instruction as InstructionContext;
 pub use kernels::Kernels as KernelsContext;
+pub use workspace::Workspace as WorkspaceContext;
 
 /// The execution context for a prompt
 ///
@@ -28,6 +30,9 @@ pub struct PromptContext {
 
     /// The execution kernels associated with the document
     pub kernels: Option<kernels::Kernels>,
+
+    /// The workspace that the document is in
+    pub workspace: Option<workspace::Workspace>,
 }
 
 impl PromptContext {
@@ -48,6 +53,9 @@ impl PromptContext {
                 if let Some(kernels) = self.kernels {
                     globals.set("kernels", kernels)?;
                 }
+                if let Some(workspace) = self.workspace {
+                    globals.set("workspace", workspace)?;
+                }
                 Ok::<(), Error>(())
             })
             .await?;
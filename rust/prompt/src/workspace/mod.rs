@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use rquickjs::function::Opt;
+use workspace_index::Index;
+
+use crate::prelude::*;
+
+#[cfg(test)]
+mod tests;
+
+/// The default number of passages returned by a search
+const SEARCH_COUNT: usize = 5;
+
+/// The workspace that the current document is in
+#[derive(Default, Clone, Trace)]
+#[rquickjs::class]
+pub struct Workspace {
+    /// The index of passages in the documents of the workspace
+    #[qjs(skip_trace)]
+    index: Arc<Index>,
+
+    /// The path of the current document relative to the root of the workspace
+    ///
+    /// Passages from the current document are excluded from searches.
+    #[qjs(skip_trace)]
+    document: Option<String>,
+}
+
+impl Workspace {
+    /// Create a new workspace context from an index and the path of the current document
+    pub fn new(index: Arc<Index>, document: Option<String>) -> Self {
+        Self { index, document }
+    }
+}
+
+#[rquickjs::methods]
+impl Workspace {
+    /// Get the count of all passages in the workspace
+    #[qjs(get)]
+    fn count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Search other documents for the passages most relevant to a query
+    ///
+    /// Returns up to `count` passages (defaulting to five) in descending
+    /// order of relevance. Relevance is based on the meaning of passages when
+    /// an embedding model is available locally, and otherwise on the words that
+    /// passages share with the query.
+    #[qjs()]
+    fn search(&self, query: String, count: Opt<usize>) -> Vec<Passage> {
+        self.index
+            .search(
+                &query,
+                count.0.unwrap_or(SEARCH_COUNT),
+                self.document.as_deref(),
+            )
+            .into_iter()
+            .map(Passage::from)
+            .collect()
+    }
+}
+
+/// A passage from a document in the workspace
+#[derive(Default, Clone, Trace)]
+#[rquickjs::class(rename_all = "camelCase")]
+pub struct Passage {
+    /// The path of the document, relative to the root of the workspace
+    #[qjs(get, enumerable)]
+    path: String,
+
+    /// The type of node that the passage is from e.g. `Paragraph`
+    #[qjs(get, enumerable)]
+    node_type: String,
+
+    /// The text of the passage
+    #[qjs(get, enumerable)]
+    text: String,
+
+    /// The relevance of the passage to the query
+    #[qjs(get, enumerable)]
+    score: f64,
+}
+
+impl From<workspace_index::Passage> for Passage {
+    fn from(passage: workspace_index::Passage) -> Self {
+        Self {
+            path: passage.path,
+            node_type: passage.node_type,
+            text: passage.text,
+            score: passage.score as f64,
+        }
+    }
+}
+
+#[rquickjs::methods]
+impl Passage {
+    #[qjs(rename = PredefinedAtom::ToJSON)]
+    fn to_json<'js>(&self, ctx: Ctx<'js>) -> Result<Object<'js>, Error> {
+        let obj = Object::new(ctx)?;
+
+        obj.set("path", self.path.clone())?;
+        obj.set("nodeType", self.node_type.clone())?;
+        obj.set("text", self.text.clone())?;
+        obj.set("score", self.score)?;
+
+        Ok(obj)
+    }
+}
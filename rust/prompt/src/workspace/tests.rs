@@ -0,0 +1,65 @@
+use std::{path::Path, sync::Arc};
+
+use crate::{prelude::*, PromptContext};
+
+use common_dev::pretty_assertions::assert_eq;
+use kernel_quickjs::kernel::common::tokio;
+use schema::Node;
+use workspace_index::{Chunk, Index};
+
+use super::Workspace;
+
+#[tokio::test]
+async fn search() -> Result<()> {
+    let mut index = Index::new(Path::new("/workspace"));
+    index.insert(
+        "results.md",
+        0,
+        vec![
+            Chunk::new("Paragraph", "Bacteria grow slowly at high temperatures."),
+            Chunk::new(
+                "Paragraph",
+                "The election results were announced on Tuesday.",
+            ),
+        ],
+    );
+    index.insert(
+        "current.md",
+        0,
+        vec![Chunk::new(
+            "Paragraph",
+            "The temperature of bacteria in this document.",
+        )],
+    );
+
+    let context = PromptContext {
+        workspace: Some(Workspace::new(
+            Arc::new(index),
+            Some("current.md".to_string()),
+        )),
+        ..Default::default()
+    };
+
+    let mut kernel = context.into_kernel().await?;
+
+    let (output, messages) = kernel.evaluate("workspace.count").await?;
+    assert_eq!(messages, []);
+    assert_eq!(output, Node::Integer(3));
+
+    let (output, messages) = kernel
+        .evaluate("workspace.search('temperature of bacteria', 1).map(p => `${p.path}: ${p.text}`).join()")
+        .await?;
+    assert_eq!(messages, []);
+    assert_eq!(
+        output,
+        Node::String("results.md: Bacteria grow slowly at high temperatures.".into())
+    );
+
+    let (output, messages) = kernel
+        .evaluate("workspace.search('temperature of bacteria')[0].nodeType")
+        .await?;
+    assert_eq!(messages, []);
+    assert_eq!(output, Node::String("Paragraph".into()));
+
+    Ok(())
+}
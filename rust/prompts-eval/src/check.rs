@@ -0,0 +1,308 @@
+use std::path::Path;
+
+use codec_markdown::to_markdown;
+use common::{
+    eyre::{bail, eyre, Result},
+    once_cell::sync::Lazy,
+    regex::Regex,
+    serde::Serialize,
+};
+use document::{CommandWait, Document};
+use models::ModelTask;
+use node_execute::ExecuteOptions;
+use schema::{
+    Article, Block, ExecutionMessage, Inline, InstructionBlock, InstructionMessage,
+    InstructionType, MessageLevel, ModelParameters, Node, NodeType, SuggestionBlock, Visitor,
+    WalkControl,
+};
+
+use crate::suite::Check;
+
+/// The system prompt used for `judge` checks
+const JUDGE_PROMPT: &str = "You are evaluating content generated by an AI assistant in response to an instruction. \
+Score how well the content meets the given criteria on a scale from 0 (does not meet the criteria at all) \
+to 10 (fully meets the criteria). Respond with a brief justification followed by the score on a final line \
+of the form `Score: N` e.g. `Score: 7`.";
+
+/// The result of a check
+#[derive(Debug, Clone, Serialize)]
+#[serde(crate = "common::serde")]
+pub struct CheckResult {
+    /// A description of the check
+    pub check: String,
+
+    /// The score for the check, between 0 and 1
+    pub score: f64,
+
+    /// A note explaining the score (e.g. the reason a check failed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Run a check on an executed instruction
+///
+/// The `context` is the content preceding the instruction (e.g. from a fixture
+/// document) which is executed, in the `home` directory, before the suggestion.
+/// The `judge` is the id of the model used for `judge` checks (if not specified,
+/// the default model is used).
+pub(crate) async fn check(
+    check: &Check,
+    instruction: &InstructionBlock,
+    context: &[Block],
+    node_types: &[String],
+    home: &Path,
+    judge: Option<&str>,
+) -> CheckResult {
+    let suggestion = instruction.suggestions.iter().flatten().last();
+
+    let (score, note) = match (check, suggestion) {
+        (Check::Decodes, ..) => decodes(instruction, node_types),
+        (.., None) => (0.0, Some("No suggestion".to_string())),
+        (Check::Executes, Some(suggestion)) => match executes(context, suggestion, home).await {
+            Ok(errors) if errors.is_empty() => (1.0, None),
+            Ok(errors) => (0.0, Some(errors.join("; "))),
+            Err(error) => (0.0, Some(error.to_string())),
+        },
+        (Check::Matches(pattern), Some(suggestion)) => {
+            matches(pattern, &to_markdown(&suggestion.content), true)
+        }
+        (Check::Excludes(pattern), Some(suggestion)) => {
+            matches(pattern, &to_markdown(&suggestion.content), false)
+        }
+        (Check::Judge(criteria), Some(suggestion)) => {
+            match judged(criteria, instruction, suggestion, judge).await {
+                Ok((score, reason)) => (score, Some(reason)),
+                Err(error) => (0.0, Some(error.to_string())),
+            }
+        }
+    };
+
+    CheckResult {
+        check: check.to_string(),
+        score,
+        note,
+    }
+}
+
+/// Check that an instruction generated a suggestion without errors and of the expected node types
+fn decodes(instruction: &InstructionBlock, node_types: &[String]) -> (f64, Option<String>) {
+    if let Some(error) = instruction
+        .options
+        .execution_messages
+        .iter()
+        .flatten()
+        .find(|message| message.level >= MessageLevel::Error)
+    {
+        return (0.0, Some(error.message.clone()));
+    }
+
+    let Some(suggestion) = instruction.suggestions.iter().flatten().last() else {
+        return (0.0, Some("No suggestion".to_string()));
+    };
+
+    if suggestion.content.is_empty() {
+        return (0.0, Some("Suggestion has no content".to_string()));
+    }
+
+    if !node_types.is_empty() {
+        if let Some(block) = suggestion
+            .content
+            .iter()
+            .find(|block| !node_types.contains(&block.node_type().to_string()))
+        {
+            return (
+                0.0,
+                Some(format!(
+                    "Suggestion contains a `{}` but expected {}",
+                    block.node_type(),
+                    node_types.join(", ")
+                )),
+            );
+        }
+    }
+
+    (1.0, None)
+}
+
+/// Check whether Markdown matches a regular expression
+fn matches(pattern: &str, markdown: &str, expected: bool) -> (f64, Option<String>) {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(error) => return (0.0, Some(format!("Invalid regular expression: {error}"))),
+    };
+
+    if regex.is_match(markdown) == expected {
+        (1.0, None)
+    } else if expected {
+        (0.0, Some("Suggestion does not match".to_string()))
+    } else {
+        (0.0, Some("Suggestion matches".to_string()))
+    }
+}
+
+/// Execute a suggestion, after its context, in a new document and return any errors
+///
+/// Only errors in the suggestion are returned so that errors in the context
+/// do not count against it.
+async fn executes(
+    context: &[Block],
+    suggestion: &SuggestionBlock,
+    home: &Path,
+) -> Result<Vec<String>> {
+    let content = context
+        .iter()
+        .chain(suggestion.content.iter())
+        .cloned()
+        .collect();
+
+    let doc = Document::init(home.to_path_buf(), None, Some(NodeType::Article))?;
+    doc.assign(Node::Article(Article::new(content)), None)
+        .await?;
+    doc.compile(CommandWait::Yes).await?;
+    doc.execute(ExecuteOptions::default(), CommandWait::Yes)
+        .await?;
+
+    let Node::Article(mut article) = doc.root().await else {
+        bail!("Expected document root to be an article")
+    };
+
+    let mut errors = Errors::default();
+    let at = context.len().min(article.content.len());
+    errors.visit(&article.content.split_off(at));
+
+    Ok(errors.0)
+}
+
+/// A visitor that collects the errors from the execution of nodes
+#[derive(Default)]
+struct Errors(Vec<String>);
+
+impl Errors {
+    fn collect(&mut self, node_type: NodeType, messages: &Option<Vec<ExecutionMessage>>) {
+        for message in messages.iter().flatten() {
+            if message.level >= MessageLevel::Error {
+                self.0.push(format!("{node_type}: {}", message.message));
+            }
+        }
+    }
+}
+
+impl Visitor for Errors {
+    fn visit_block(&mut self, block: &Block) -> WalkControl {
+        if let Block::CodeChunk(chunk) = block {
+            self.collect(NodeType::CodeChunk, &chunk.options.execution_messages);
+        }
+        WalkControl::Continue
+    }
+
+    fn visit_inline(&mut self, inline: &Inline) -> WalkControl {
+        if let Inline::CodeExpression(expr) = inline {
+            self.collect(NodeType::CodeExpression, &expr.options.execution_messages);
+        }
+        WalkControl::Continue
+    }
+}
+
+/// Ask a model to judge how well a suggestion meets some criteria
+///
+/// Returns the score, scaled to between 0 and 1, and the model's justification.
+async fn judged(
+    criteria: &str,
+    instruction: &InstructionBlock,
+    suggestion: &SuggestionBlock,
+    model: Option<&str>,
+) -> Result<(f64, String)> {
+    let message = format!(
+        "# Instruction\n\n{}\n\n# Criteria\n\n{criteria}\n\n# Content\n\n{}",
+        to_markdown(&instruction.message),
+        to_markdown(&suggestion.content)
+    );
+
+    let task = ModelTask::new(
+        InstructionType::Discuss,
+        ModelParameters {
+            model_ids: model.map(|id| vec![id.to_string()]),
+            temperature: Some(0),
+            ..Default::default()
+        },
+        vec![
+            InstructionMessage::system(JUDGE_PROMPT, None),
+            InstructionMessage::user(message, None),
+        ],
+    );
+
+    let output = models::perform_task(task).await?;
+    let score = score(&output.content).ok_or_else(|| {
+        eyre!(
+            "Judge did not respond with a `Score: N` line: {}",
+            output.content
+        )
+    })?;
+
+    Ok((score / 10.0, output.content.trim().to_string()))
+}
+
+/// Parse the score from the response of a judge
+///
+/// Uses the last line of the form `Score: N`, where N is an integer between 0 and 10,
+/// so that numbers in the justification (e.g. "2 of the 3 criteria") are not
+/// mistaken for the score.
+fn score(response: &str) -> Option<f64> {
+    static REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?mi)^\W*score\W*:\W*(10|\d)\W*(?:/\s*10\W*)?$").expect("invalid regex")
+    });
+
+    REGEX
+        .captures_iter(response)
+        .last()
+        .and_then(|captures| captures[1].parse::<f64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use schema::shortcuts::{p, t};
+
+    use super::*;
+
+    #[test]
+    fn decodes_suggestions() {
+        let mut instruction = InstructionBlock::default();
+        assert_eq!(decodes(&instruction, &[]).0, 0.0);
+
+        instruction.suggestions = Some(vec![SuggestionBlock::new(vec![p([t("Hello")])])]);
+        assert_eq!(decodes(&instruction, &[]), (1.0, None));
+        assert_eq!(
+            decodes(&instruction, &["Paragraph".to_string()]),
+            (1.0, None)
+        );
+        assert_eq!(decodes(&instruction, &["Table".to_string()]).0, 0.0);
+
+        instruction.options.execution_messages = Some(vec![ExecutionMessage::new(
+            MessageLevel::Error,
+            "Rate limited".to_string(),
+        )]);
+        assert_eq!(
+            decodes(&instruction, &[]),
+            (0.0, Some("Rate limited".to_string()))
+        );
+    }
+
+    #[test]
+    fn matches_markdown() {
+        assert_eq!(matches("Everest", "| Everest | 8849 |", true).0, 1.0);
+        assert_eq!(matches("Everest", "| K2 | 8611 |", true).0, 0.0);
+        assert_eq!(matches("Everest", "| K2 | 8611 |", false).0, 1.0);
+        assert_eq!(matches("(", "", true).0, 0.0);
+    }
+
+    #[test]
+    fn judge_scores() {
+        assert_eq!(score("Mostly meets the criteria\nScore: 8"), Some(8.0));
+        assert_eq!(score("Meets 2 of 3 criteria.\n\n**Score:** 7"), Some(7.0));
+        assert_eq!(score("Score: 10/10"), Some(10.0));
+        assert_eq!(score("score: 3\nOn reflection...\nScore: 5"), Some(5.0));
+        assert_eq!(score("8\nMostly meets the criteria"), None);
+        assert_eq!(score("Meets 2 of 3 criteria"), None);
+        assert_eq!(score("No score"), None);
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use cli_utils::ToStdout;
+use common::{
+    clap::{self, Args, Parser, Subcommand},
+    eyre::Result,
+    serde_json, serde_yaml,
+    tokio::fs::write,
+};
+use models::cassette::{CassetteMode, ScopedCassette};
+
+use crate::{evaluate, Suite};
+
+/// Manage prompts
+#[derive(Debug, Parser)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Prompts(prompts::cli::Command),
+
+    Eval(Eval),
+}
+
+impl Cli {
+    pub async fn run(self) -> Result<()> {
+        match self.command {
+            Some(Command::Prompts(command)) => command.run().await,
+            Some(Command::Eval(eval)) => eval.run().await,
+            None => prompts::cli::Cli::default().run().await,
+        }
+    }
+}
+
+/// Evaluate a prompt on a suite of cases
+///
+/// Runs the prompt on each case in the suite with each model, scores the
+/// suggestions generated using the checks of the case, and displays a table
+/// of scores for comparing models.
+#[derive(Debug, Args)]
+struct Eval {
+    /// The path of the suite file (YAML or JSON)
+    suite: PathBuf,
+
+    /// The name of the prompt to evaluate
+    ///
+    /// Overrides the prompt specified in the suite.
+    #[arg(long)]
+    prompt: Option<String>,
+
+    /// The ids of the models to evaluate the prompt with (comma separated)
+    ///
+    /// Overrides the models specified in the suite.
+    #[arg(long, value_delimiter = ',')]
+    models: Vec<String>,
+
+    /// The id of the model to use for `judge` checks
+    ///
+    /// Overrides the judge specified in the suite.
+    #[arg(long)]
+    judge: Option<String>,
+
+    /// The path to write the report to
+    ///
+    /// The report is written as JSON or YAML if the path has the corresponding
+    /// extension, and as Markdown otherwise.
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// The cassette file to record the outputs of models to, or replay them from
+    ///
+    /// Allows a suite to be evaluated deterministically, and without network access,
+    /// for example in continuous integration.
+    #[arg(long)]
+    cassette: Option<PathBuf>,
+
+    /// Whether to record the outputs of models to the cassette, or replay them from it
+    ///
+    /// Defaults to `replay`.
+    #[arg(long, requires = "cassette")]
+    cassette_mode: Option<CassetteMode>,
+}
+
+impl Eval {
+    async fn run(self) -> Result<()> {
+        let suite = Suite::load(&self.suite).await?;
+        let evaluation = evaluate(
+            &suite,
+            self.prompt.as_deref(),
+            &self.models,
+            self.judge.as_deref(),
+        );
+        let report = match &self.cassette {
+            Some(path) => {
+                ScopedCassette::load(path, self.cassette_mode.unwrap_or_default())
+                    .await?
+                    .scope(evaluation)
+                    .await?
+            }
+            None => evaluation.await?,
+        };
+
+        if let Some(path) = &self.report {
+            let content = match path.extension().and_then(|ext| ext.to_str()) {
+                Some("json") => serde_json::to_string_pretty(&report)?,
+                Some("yaml" | "yml") => serde_yaml::to_string(&report)?,
+                _ => report.to_markdown(),
+            };
+            write(path, content).await?;
+        }
+
+        report.to_table().to_stdout();
+
+        Ok(())
+    }
+}
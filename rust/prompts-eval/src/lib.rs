@@ -0,0 +1,257 @@
+//! Evaluation of prompts
+//!
+//! A prompt is evaluated by running it on each of the cases in a [`Suite`] with
+//! each of a set of models. For each case, an instruction using the prompt is
+//! executed (after the content of any fixture document) and the suggestion it
+//! generates is scored using the case's checks. The scores for each case and
+//! model are collected into a [`Report`] so that models can be compared.
+//!
+//! Suites can be evaluated without network access by replaying model outputs
+//! recorded to a cassette (see `models::cassette`).
+
+use codecs::{DecodeOptions, Format};
+use common::{
+    eyre::{bail, eyre, Result},
+    tracing,
+};
+use document::{CommandWait, Document};
+use models::cassette::ScopedCassette;
+use node_execute::ExecuteOptions;
+use schema::{
+    Article, Block, ExecutionBounds, InstructionBlock, InstructionMessage, InstructionType,
+    ModelParameters, Node, NodeType, PromptBlock,
+};
+
+mod check;
+mod report;
+mod suite;
+
+pub mod cli;
+
+pub use check::CheckResult;
+pub use report::{CaseResult, Report};
+pub use suite::{Case, Check, Suite};
+
+/// Evaluate a prompt on a suite of cases with each of a set of models
+///
+/// The `prompt`, `models` and `judge` arguments override those in the suite.
+pub async fn evaluate(
+    suite: &Suite,
+    prompt: Option<&str>,
+    models: &[String],
+    judge: Option<&str>,
+) -> Result<Report> {
+    let Some(name) = prompt.or(suite.prompt.as_deref()) else {
+        bail!("No prompt specified in suite or arguments")
+    };
+    let prompt = prompts::get(name).await?;
+    let instruction_type = prompt
+        .instruction_types
+        .first()
+        .cloned()
+        .unwrap_or_default();
+    let node_types = prompt.node_types.clone().unwrap_or_default();
+
+    let models = if models.is_empty() {
+        suite.models.clone()
+    } else {
+        models.to_vec()
+    };
+    if models.is_empty() {
+        bail!("No models specified in suite or arguments")
+    }
+    let mut pinned = Vec::new();
+    for model in &models {
+        pinned.push(pin(model).await);
+    }
+    let models = pinned;
+
+    let judge = match judge.or(suite.judge.as_deref()) {
+        Some(judge) => Some(pin(judge).await),
+        None => None,
+    };
+    let judge = judge.as_deref();
+
+    let mut results = Vec::new();
+    for case in &suite.cases {
+        for model in &models {
+            tracing::info!("Evaluating case `{}` with model `{model}`", case.name);
+
+            let result = match run(suite, case, name, &instruction_type, model).await {
+                Ok((instruction, context)) => {
+                    let mut checks = Vec::new();
+                    for check in &case.checks {
+                        checks.push(
+                            check::check(
+                                check,
+                                &instruction,
+                                &context,
+                                &node_types,
+                                &suite.dir,
+                                judge,
+                            )
+                            .await,
+                        );
+                    }
+
+                    let score = if checks.is_empty() {
+                        0.0
+                    } else {
+                        checks.iter().map(|check| check.score).sum::<f64>() / checks.len() as f64
+                    };
+
+                    let cost = instruction
+                        .suggestions
+                        .iter()
+                        .flatten()
+                        .last()
+                        .and_then(|suggestion| suggestion.cost);
+
+                    CaseResult {
+                        case: case.name.clone(),
+                        model: model.clone(),
+                        score,
+                        cost,
+                        error: None,
+                        checks,
+                    }
+                }
+                Err(error) => CaseResult {
+                    case: case.name.clone(),
+                    model: model.clone(),
+                    score: 0.0,
+                    cost: None,
+                    error: Some(error.to_string()),
+                    checks: Vec::new(),
+                },
+            };
+
+            results.push(result);
+        }
+    }
+
+    Ok(Report {
+        prompt: name.to_string(),
+        models,
+        cases: suite.cases.iter().map(|case| case.name.clone()).collect(),
+        results,
+    })
+}
+
+/// Pin a model id, or id pattern, to the exact id of an available model
+///
+/// Model ids are matched as substrings when selecting models, so without pinning,
+/// a pattern could match different models as the list of available models changes.
+/// Since each instruction only specifies the pinned id, it is performed by that model
+/// alone, without falling back to others. When replaying model outputs from a cassette,
+/// the id recorded in the cassette is used so that tasks match those recorded (models
+/// may not be available, or may have changed, since the cassette was recorded). Otherwise,
+/// ids that do not match an available model are used as is.
+async fn pin(model: &str) -> String {
+    if let Some(id) = models::cassette::model_id(model).await {
+        return id;
+    }
+
+    let ids = models::list()
+        .await
+        .into_iter()
+        .filter(|candidate| candidate.is_available())
+        .map(|candidate| candidate.id())
+        .collect::<Vec<_>>();
+
+    if ids.iter().any(|id| id == model) {
+        return model.to_string();
+    }
+
+    match ids.into_iter().find(|id| id.contains(model)) {
+        Some(id) => {
+            tracing::info!("Using model `{id}` for `{model}`");
+            id
+        }
+        None => model.to_string(),
+    }
+}
+
+/// Run a case with a model
+///
+/// Appends an instruction using the prompt to the content of the case's fixture
+/// document (if any), executes the document, and returns the executed instruction
+/// and the content that preceded it. Suggestions are not executed within the
+/// document so that the `executes` check can do that independently.
+async fn run(
+    suite: &Suite,
+    case: &Case,
+    prompt: &str,
+    instruction_type: &InstructionType,
+    model: &str,
+) -> Result<(InstructionBlock, Vec<Block>)> {
+    let context = match &case.document {
+        Some(path) => match codecs::from_path(&suite.dir.join(path), None).await? {
+            Node::Article(article) => article.content,
+            _ => bail!("Fixture document `{}` is not an article", path.display()),
+        },
+        None => Vec::new(),
+    };
+
+    let content = match &case.content {
+        Some(md) => Some(decode_markdown(md).await?),
+        None => None,
+    };
+
+    let instruction = InstructionBlock {
+        instruction_type: instruction_type.clone(),
+        prompt: PromptBlock {
+            instruction_type: Some(instruction_type.clone()),
+            target: Some(prompt.to_string()),
+            query: Some(case.message.clone()),
+            ..Default::default()
+        },
+        message: InstructionMessage::from(case.message.clone()),
+        model_parameters: Box::new(ModelParameters {
+            model_ids: Some(vec![model.to_string()]),
+            ..Default::default()
+        }),
+        content,
+        execution_bounds: Some(ExecutionBounds::Skip),
+        ..Default::default()
+    };
+    let node_id = instruction.node_id();
+
+    let mut blocks = context.clone();
+    blocks.push(Block::InstructionBlock(instruction));
+
+    let doc = Document::init(suite.dir.clone(), None, Some(NodeType::Article))?;
+    doc.assign(Node::Article(Article::new(blocks)), None)
+        .await?;
+    doc.compile(CommandWait::Yes).await?;
+    doc.execute(
+        ExecuteOptions {
+            scoped_cassette: ScopedCassette::current(),
+            ..Default::default()
+        },
+        CommandWait::Yes,
+    )
+    .await?;
+
+    match doc.find(node_id).await {
+        Some(Node::InstructionBlock(instruction)) => Ok((instruction, context)),
+        _ => Err(eyre!("Unable to find executed instruction")),
+    }
+}
+
+/// Decode Markdown into blocks
+async fn decode_markdown(md: &str) -> Result<Vec<Block>> {
+    let node = codecs::from_str(
+        md,
+        Some(DecodeOptions {
+            format: Some(Format::Markdown),
+            ..Default::default()
+        }),
+    )
+    .await?;
+
+    match node {
+        Node::Article(article) => Ok(article.content),
+        _ => bail!("Expected Markdown to decode to an article"),
+    }
+}
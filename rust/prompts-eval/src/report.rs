@@ -0,0 +1,209 @@
+use cli_utils::table::{self, Attribute, Cell, CellAlignment, Color, Table};
+use common::{itertools::Itertools, serde::Serialize};
+
+use crate::check::CheckResult;
+
+/// A report of the evaluation of a prompt
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct Report {
+    /// The name of the prompt evaluated
+    pub prompt: String,
+
+    /// The ids of the models the prompt was evaluated with
+    pub models: Vec<String>,
+
+    /// The names of the cases the prompt was evaluated on
+    pub cases: Vec<String>,
+
+    /// The results for each case and model
+    pub results: Vec<CaseResult>,
+}
+
+/// The result of evaluating a prompt on a case with a model
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct CaseResult {
+    /// The name of the case
+    pub case: String,
+
+    /// The id of the model
+    pub model: String,
+
+    /// The score for the case, the mean of the scores of its checks
+    pub score: f64,
+
+    /// The cost, in US dollars, of generating the suggestion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+
+    /// The error, if the instruction could not be executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// The results of each check
+    pub checks: Vec<CheckResult>,
+}
+
+impl Report {
+    /// Get the result for a case and model
+    fn result(&self, case: &str, model: &str) -> Option<&CaseResult> {
+        self.results
+            .iter()
+            .find(|result| result.case == case && result.model == model)
+    }
+
+    /// Get the mean score, and total cost, for a model across all cases
+    fn totals(&self, model: &str) -> (f64, Option<f64>) {
+        let results = self
+            .results
+            .iter()
+            .filter(|result| result.model == model)
+            .collect_vec();
+
+        let score = if results.is_empty() {
+            0.0
+        } else {
+            results.iter().map(|result| result.score).sum::<f64>() / results.len() as f64
+        };
+
+        let cost = results
+            .iter()
+            .filter_map(|result| result.cost)
+            .fold(None, |sum, cost| Some(sum.unwrap_or(0.0) + cost));
+
+        (score, cost)
+    }
+
+    /// Render the report as a Markdown document
+    ///
+    /// Has a table of scores for each case (rows) and model (columns), followed
+    /// by the details of any failed checks.
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# Evaluation of `{}`\n\n", self.prompt);
+
+        md += &format!(
+            "| Case | {} |\n|---|{}\n",
+            self.models
+                .iter()
+                .map(|model| format!("`{model}`"))
+                .join(" | "),
+            "---:|".repeat(self.models.len())
+        );
+        for case in &self.cases {
+            md += &format!(
+                "| {case} | {} |\n",
+                self.models
+                    .iter()
+                    .map(|model| self
+                        .result(case, model)
+                        .map(|result| format!("{:.2}", result.score))
+                        .unwrap_or_default())
+                    .join(" | ")
+            );
+        }
+        md += &format!(
+            "| **Mean** | {} |\n| **Cost** | {} |\n",
+            self.models
+                .iter()
+                .map(|model| format!("**{:.2}**", self.totals(model).0))
+                .join(" | "),
+            self.models
+                .iter()
+                .map(|model| self
+                    .totals(model)
+                    .1
+                    .map(|cost| format!("${cost:.4}"))
+                    .unwrap_or_default())
+                .join(" | ")
+        );
+
+        let failures = self
+            .results
+            .iter()
+            .filter(|result| {
+                result.error.is_some() || result.checks.iter().any(|check| check.score < 1.0)
+            })
+            .collect_vec();
+        if !failures.is_empty() {
+            md += "\n## Failures\n";
+            for result in failures {
+                md += &format!("\n### {} with `{}`\n\n", result.case, result.model);
+                if let Some(error) = &result.error {
+                    md += &format!("- Error: {error}\n");
+                }
+                for check in result.checks.iter().filter(|check| check.score < 1.0) {
+                    md += &format!("- {} ({:.2})", check.check, check.score);
+                    if let Some(note) = &check.note {
+                        md += &format!(": {}", note.lines().join(" "));
+                    }
+                    md += "\n";
+                }
+            }
+        }
+
+        md
+    }
+
+    /// Render the report as a table for display in the terminal
+    pub fn to_table(&self) -> Table {
+        let mut table = table::new();
+
+        table.set_header(
+            ["Case".to_string()]
+                .into_iter()
+                .chain(self.models.iter().cloned()),
+        );
+
+        let score = |score: f64| {
+            let color = if score >= 0.8 {
+                Color::Green
+            } else if score >= 0.5 {
+                Color::Yellow
+            } else {
+                Color::Red
+            };
+            Cell::new(format!("{score:.2}"))
+                .fg(color)
+                .set_alignment(CellAlignment::Right)
+        };
+
+        for case in &self.cases {
+            table.add_row(
+                [Cell::new(case)]
+                    .into_iter()
+                    .chain(self.models.iter().map(|model| {
+                        self.result(case, model)
+                            .map(|result| score(result.score))
+                            .unwrap_or_else(|| Cell::new(""))
+                    })),
+            );
+        }
+
+        table.add_row(
+            [Cell::new("Mean").add_attribute(Attribute::Bold)]
+                .into_iter()
+                .chain(
+                    self.models
+                        .iter()
+                        .map(|model| score(self.totals(model).0).add_attribute(Attribute::Bold)),
+                ),
+        );
+
+        table.add_row(
+            [Cell::new("Cost").add_attribute(Attribute::Bold)]
+                .into_iter()
+                .chain(self.models.iter().map(|model| {
+                    Cell::new(
+                        self.totals(model)
+                            .1
+                            .map(|cost| format!("${cost:.4}"))
+                            .unwrap_or_default(),
+                    )
+                    .set_alignment(CellAlignment::Right)
+                })),
+        );
+
+        table
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use common::{
+    eyre::{Context, Result},
+    serde::{Deserialize, Serialize},
+    serde_json, serde_yaml,
+    tokio::fs::read_to_string,
+};
+
+/// A suite of cases for evaluating a prompt
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields, crate = "common::serde")]
+pub struct Suite {
+    /// The name of the prompt to evaluate e.g. `stencila/create/table-filled`
+    pub prompt: Option<String>,
+
+    /// The ids of the models to evaluate the prompt with
+    #[serde(default)]
+    pub models: Vec<String>,
+
+    /// The id of the model to use for `judge` checks
+    pub judge: Option<String>,
+
+    /// The cases in the suite
+    pub cases: Vec<Case>,
+
+    /// The directory of the suite file
+    ///
+    /// Used to resolve the paths of fixture documents.
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+impl Suite {
+    /// Load a suite from a YAML or JSON file
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = read_to_string(path)
+            .await
+            .wrap_err_with(|| format!("Unable to read suite `{}`", path.display()))?;
+
+        let mut suite: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            _ => Self::from_yaml(&content)?,
+        };
+        suite.dir = path
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_default();
+
+        Ok(suite)
+    }
+
+    /// Deserialize a suite from YAML
+    ///
+    /// Deserializes via JSON because `serde_yaml` expects enum variants with values
+    /// to be YAML tags (e.g. `!matches Everest`) rather than maps (e.g. `matches: Everest`).
+    fn from_yaml(yaml: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_yaml::from_str(yaml)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// A case in a suite
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields, crate = "common::serde")]
+pub struct Case {
+    /// The name of the case
+    pub name: String,
+
+    /// The message of the instruction
+    pub message: String,
+
+    /// The content of the instruction, as Markdown
+    ///
+    /// Used for instructions that operate on existing content (e.g. edit and fix).
+    pub content: Option<String>,
+
+    /// The path of a fixture document, relative to the suite file
+    ///
+    /// The instruction is appended to the document so that the document's
+    /// content (e.g. code chunks and their variables) is available as context.
+    pub document: Option<PathBuf>,
+
+    /// The checks used to score the suggestion generated for the instruction
+    #[serde(default = "Case::default_checks")]
+    pub checks: Vec<Check>,
+}
+
+impl Case {
+    fn default_checks() -> Vec<Check> {
+        vec![Check::Decodes]
+    }
+}
+
+/// A check of the suggestion generated for an instruction
+///
+/// Each check scores the suggestion between 0 and 1.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", crate = "common::serde")]
+pub enum Check {
+    /// The instruction generated a suggestion, without errors, whose blocks are
+    /// of the node types of the prompt (if the prompt specifies any)
+    Decodes,
+
+    /// The executable nodes in the suggestion execute without errors
+    Executes,
+
+    /// The suggestion, as Markdown, matches a regular expression
+    Matches(String),
+
+    /// The suggestion, as Markdown, does not match a regular expression
+    Excludes(String),
+
+    /// A model judges how well the suggestion meets some criteria
+    Judge(String),
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::Decodes => write!(f, "decodes"),
+            Check::Executes => write!(f, "executes"),
+            Check::Matches(pattern) => write!(f, "matches `{pattern}`"),
+            Check::Excludes(pattern) => write!(f, "excludes `{pattern}`"),
+            Check::Judge(criteria) => write!(f, "judge: {criteria}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_suite() -> Result<()> {
+        let suite = Suite::from_yaml(
+            r#"
+prompt: stencila/create/table-filled
+models: [openai/gpt-4o-mini, anthropic/claude-3-5-haiku]
+cases:
+  - name: mountains
+    message: top five highest mountains, with height in metres
+    checks:
+      - decodes
+      - matches: Everest
+      - judge: Lists the five highest mountains in descending order of height
+  - name: defaults
+    message: a table of planets
+"#,
+        )?;
+
+        assert_eq!(suite.models.len(), 2);
+        assert_eq!(suite.cases.len(), 2);
+        assert!(matches!(
+            suite.cases[0].checks.as_slice(),
+            [Check::Decodes, Check::Matches(..), Check::Judge(..)]
+        ));
+        assert!(matches!(suite.cases[1].checks.as_slice(), [Check::Decodes]));
+
+        Ok(())
+    }
+}
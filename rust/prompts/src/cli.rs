@@ -13,14 +13,18 @@ use model::{
 };
 
 /// Manage prompts
-#[derive(Debug, Parser)]
+#[derive(Debug, Default, Parser)]
 pub struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
 }
 
+/// The subcommands for managing prompts
+///
+/// Public so that they can be extended by other crates (e.g. with commands
+/// which have dependencies that this crate can not have).
 #[derive(Debug, Subcommand)]
-enum Command {
+pub enum Command {
     List(List),
     Show(Show),
     Infer(Infer),
@@ -30,17 +34,21 @@ enum Command {
 
 impl Cli {
     pub async fn run(self) -> Result<()> {
-        let Some(command) = self.command else {
-            List::default().run().await?;
-            return Ok(());
-        };
+        match self.command {
+            Some(command) => command.run().await,
+            None => List::default().run().await,
+        }
+    }
+}
 
-        match command {
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        match self {
             Command::List(list) => list.run().await?,
             Command::Show(show) => show.run().await?,
             Command::Infer(infer) => infer.run().await?,
             Command::Update(update) => update.run().await?,
-            Command::Reset(update) => update.run().await?,
+            Command::Reset(reset) => reset.run().await?,
         }
 
         Ok(())
@@ -49,7 +57,7 @@ impl Cli {
 
 /// List the prompts available
 #[derive(Default, Debug, Args)]
-struct List {
+pub struct List {
     /// Output the list as JSON or YAML
     #[arg(long, short)]
     r#as: Option<AsFormat>,
@@ -105,7 +113,7 @@ impl List {
 
 /// Show a prompt
 #[derive(Debug, Args)]
-struct Show {
+pub struct Show {
     /// The name of the prompt to show
     name: String,
 
@@ -138,7 +146,7 @@ impl Show {
 /// Useful for checking which prompt will be matched to a given
 /// instruction type, node types, and/or query
 #[derive(Debug, Args)]
-struct Infer {
+pub struct Infer {
     /// The instruction type
     #[arg(short, long)]
     instruction_type: Option<InstructionType>,
@@ -169,7 +177,7 @@ impl Infer {
 
 /// Update builtin prompts
 #[derive(Debug, Args)]
-struct Update {}
+pub struct Update {}
 
 impl Update {
     async fn run(self) -> Result<()> {
@@ -182,7 +190,7 @@ impl Update {
 /// Re-initializes the builtin prompts directory to those prompts
 /// embedded in this version of Stencila
 #[derive(Debug, Args)]
-struct Reset {}
+pub struct Reset {}
 
 impl Reset {
     async fn run(self) -> Result<()> {
@@ -35,10 +35,10 @@ use model::{
     schema::{
         authorship, shortcuts::p, Article, AudioObject, Author, AuthorRole, Block,
         CompilationMessage, ExecutionMessage, ImageObject, Inline, InstructionBlock,
-        InstructionMessage, InstructionType, Link, MessageLevel, MessagePart, Node, Prompt,
-        SuggestionBlock, SuggestionStatus, Timestamp, UnsignedIntegerOrString, VideoObject,
+        InstructionMessage, InstructionType, Link, MessageLevel, MessagePart, Node, NodeType,
+        Prompt, SuggestionBlock, SuggestionStatus, Timestamp, UnsignedIntegerOrString, VideoObject,
     },
-    ModelOutput, ModelOutputKind, ModelTask,
+    ModelOutput, ModelOutputKind, ModelTask, ModelUsage,
 };
 
 pub mod cli;
@@ -551,14 +551,118 @@ async fn update_builtin() -> Result<()> {
     Ok(())
 }
 
+/// Node types which are generated as structured output (JSON) rather than Markdown
+///
+/// These are node types which are complex to represent in Markdown and for which
+/// models often generate Markdown that does not decode to the expected node.
+///
+/// `Datatable`s are not included because they are not blocks and so can not be
+/// inserted into a document as a suggestion.
+const STRUCTURED_NODE_TYPES: [NodeType; 2] = [NodeType::Figure, NodeType::Table];
+
+/// The maximum number of attempts at generating valid structured output
+const STRUCTURED_ATTEMPTS: usize = 3;
+
+/// Get the node type to generate as structured output for an instruction, if any
+///
+/// Uses the node types of the instruction's prompt, or if those are not
+/// specified, the node types of the targeted prompt. Only returns a node type
+/// if there is a single node type and it is one of [`STRUCTURED_NODE_TYPES`].
+async fn structured_node_type(instruction: &InstructionBlock) -> Option<NodeType> {
+    let node_types = match (&instruction.prompt.node_types, &instruction.prompt.target) {
+        (Some(node_types), ..) => node_types.clone(),
+        (None, Some(target)) => get(&expand(target, &Some(instruction.instruction_type.clone())))
+            .await
+            .ok()?
+            .node_types
+            .clone()?,
+        (None, None) => return None,
+    };
+
+    let [node_type] = node_types.as_slice() else {
+        return None;
+    };
+
+    NodeType::from_str(node_type)
+        .ok()
+        .filter(|node_type| STRUCTURED_NODE_TYPES.contains(node_type))
+}
+
+/// Perform a model task which should generate structured output for a node type
+///
+/// If the output can not be deserialized into a node of the type, the output
+/// and the error are appended to the messages of the task and it is performed
+/// again, up to [`STRUCTURED_ATTEMPTS`] times. Returns the output of the last
+/// attempt, with usage and messages combined across all attempts, and the block.
+async fn perform_structured_task(
+    mut task: ModelTask,
+    node_type: NodeType,
+) -> Result<(ModelOutput, Block)> {
+    let mut usage: Option<ModelUsage> = None;
+    let mut messages = Vec::new();
+    let mut attempt = 1;
+    loop {
+        let mut output = models::perform_task(task.clone()).await?;
+        if let Some(output_usage) = &output.usage {
+            *usage.get_or_insert_with(ModelUsage::default) += output_usage;
+        }
+        messages.extend(output.messages.take().into_iter().flatten());
+
+        match model::block_from_json(node_type, &output.content) {
+            Ok(block) => {
+                output.usage = usage;
+                output.messages = (!messages.is_empty()).then_some(messages);
+                return Ok((output, block));
+            }
+            Err(error) => {
+                if attempt >= STRUCTURED_ATTEMPTS {
+                    bail!("{error} (after {attempt} attempts)")
+                }
+
+                tracing::debug!("Retrying structured output after attempt {attempt}: {error}");
+                task.messages
+                    .push(InstructionMessage::assistant(&output.content, None));
+                task.messages.push(InstructionMessage::user(
+                    format!("{error}\n\nPlease try again, responding only with a JSON object conforming to the JSON Schema."),
+                    None,
+                ));
+                attempt += 1;
+            }
+        }
+    }
+}
+
 /// Execute an [`InstructionBlock`]
-pub async fn execute_instruction_block(
+///
+/// The `on_text` callback is called with the text generated so far as the
+/// output of the model is streamed. Node types in [`STRUCTURED_NODE_TYPES`] are
+/// generated as JSON conforming to their JSON Schema and are not streamed.
+///
+/// Returns the suggestion and any messages about how the model task was
+/// performed (e.g. retries or the use of a fallback model).
+pub async fn execute_instruction_block<F>(
     mut instructors: Vec<AuthorRole>,
     prompter: AuthorRole,
     system_prompt: &str,
     instruction: &InstructionBlock,
     dry_run: bool,
-) -> Result<SuggestionBlock> {
+    on_text: F,
+) -> Result<(SuggestionBlock, Vec<ExecutionMessage>)>
+where
+    F: FnMut(&str) + Send,
+{
+    // If the node type should be generated as structured output then get its
+    // JSON Schema and append it to the system prompt
+    let node_type = structured_node_type(instruction).await;
+    let json_schema = node_type.map(model::json_schema).transpose()?;
+    let system_prompt = match &json_schema {
+        Some(schema) => format!(
+            "{system_prompt}\n\n# Output format\n\nIgnore any instructions above regarding the format of your response. Respond only with a JSON object, without any comments or explanation, conforming to this JSON Schema:\n\n```json\n{}\n```\n",
+            serde_json::to_string(schema)?
+        ),
+        None => system_prompt.to_string(),
+    };
+
     // Create a vector of messages beginning with the system message
     let mut messages = vec![InstructionMessage::system(
         system_prompt,
@@ -670,19 +774,37 @@ pub async fn execute_instruction_block(
         messages,
     );
     task.dry_run = dry_run;
+    if json_schema.is_some() {
+        task.format = Format::Json;
+        task.json_schema = json_schema;
+    }
 
-    // Perform the task
+    // Perform the task. Structured output is not streamed because partial JSON
+    // can not be decoded into a node.
     let started = Timestamp::now();
-    let ModelOutput {
-        mut authors,
-        kind,
-        format,
-        content,
-    } = models::perform_task(task).await?;
+    let (
+        ModelOutput {
+            mut authors,
+            kind,
+            format,
+            content,
+            usage,
+            messages: execution_messages,
+            ..
+        },
+        block,
+    ) = match node_type {
+        Some(node_type) if !dry_run => {
+            let (output, block) = perform_structured_task(task, node_type).await?;
+            (output, Some(block))
+        }
+        _ => (models::perform_task_stream(task, on_text).await?, None),
+    };
     let ended = Timestamp::now();
 
-    let blocks = match kind {
-        ModelOutputKind::Text => {
+    let blocks = match (block, kind) {
+        (Some(block), ..) => vec![block],
+        (None, ModelOutputKind::Text) => {
             // Decode the model output into blocks
             let node = codecs::from_str(
                 &content,
@@ -702,7 +824,7 @@ pub async fn execute_instruction_block(
 
             content
         }
-        ModelOutputKind::Url => {
+        (None, ModelOutputKind::Url) => {
             let content_url = content;
             let media_type = Some(format.media_type());
 
@@ -746,10 +868,18 @@ pub async fn execute_instruction_block(
     suggestion.execution_duration = Some(duration);
     suggestion.execution_ended = Some(ended);
 
+    // Record the usage of the model for the suggestion
+    if let Some(usage) = usage {
+        suggestion.prompt_tokens = Some(usage.prompt_tokens);
+        suggestion.completion_tokens = Some(usage.completion_tokens);
+        // The cost is unknown if the pricing of any of the models used is unknown
+        suggestion.cost = usage.cost.filter(|_| usage.unpriced_tokens == 0);
+    }
+
     // Apply authorship to the suggestion.
     authors.append(&mut instructors);
     authors.push(prompter);
     authorship(&mut suggestion, authors)?;
 
-    Ok(suggestion)
+    Ok((suggestion, execution_messages.unwrap_or_default()))
 }
@@ -431,6 +431,15 @@ pub enum NodeProperty {{
             }
 
             derives.push("TextCodec");
+
+            if schema
+                .typst
+                .as_ref()
+                .map(|spec| spec.derive)
+                .unwrap_or(true)
+            {
+                derives.push("TypstCodec");
+            }
         }
 
         attrs.push(format!("#[derive({})]", derives.join(", ")));
@@ -606,6 +615,21 @@ pub enum NodeProperty {{
             }
         }
 
+        // Add #[typst] attribute for main struct if necessary
+        if let Some(typst) = &schema.typst {
+            if typst.derive {
+                let mut args = Vec::new();
+
+                if let Some(function) = &typst.function {
+                    args.push(format!("function = \"{function}\""));
+                }
+
+                if !args.is_empty() {
+                    attrs.push(format!("#[typst({})]", args.join(", ")))
+                }
+            }
+        }
+
         let attrs = attrs.join("\n");
         let options_attrs = options_attrs.join("\n");
 
@@ -1232,6 +1256,15 @@ impl {title} {{
             }
 
             derives.push("TextCodec");
+
+            if schema
+                .typst
+                .as_ref()
+                .map(|spec| spec.derive)
+                .unwrap_or(true)
+            {
+                derives.push("TypstCodec");
+            }
         }
 
         attrs.push(format!("#[derive({})]", derives.join(", ")));
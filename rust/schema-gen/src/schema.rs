@@ -151,6 +151,9 @@ pub struct Schema {
     /// Options for encoding the type or property to Markdown
     pub markdown: Option<MarkdownOptions>,
 
+    /// Options for encoding the type or property to Typst
+    pub typst: Option<TypstOptions>,
+
     /// Options for property testing
     pub proptest: Option<BTreeMap<ProptestLevel, ProptestOptions>>,
 
@@ -747,6 +750,25 @@ pub struct LatexOptions {
     pub command: Option<String>,
 }
 
+/// Options for deriving the `TypstCodec` trait
+#[skip_serializing_none]
+#[derive(Debug, Clone, SmartDefault, Deserialize, Serialize, JsonSchema)]
+#[serde(
+    default,
+    rename_all = "camelCase",
+    deny_unknown_fields,
+    crate = "common::serde"
+)]
+pub struct TypstOptions {
+    /// Whether the `TypstCodec` trait should be derived for the type
+    #[serde(skip_serializing_if = "is_true")]
+    #[default = true]
+    pub derive: bool,
+
+    /// The name of the function to wrap the node in
+    pub function: Option<String>,
+}
+
 /// Options for deriving the `MarkdownCodec` trait
 #[skip_serializing_none]
 #[derive(Debug, Clone, SmartDefault, Deserialize, Serialize, JsonSchema)]
@@ -78,6 +78,14 @@ impl LatexCodec for Array {
     }
 }
 
+impl TypstCodec for Array {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        let (text, losses) = self.to_text();
+        context.str(&text);
+        context.merge_losses(losses);
+    }
+}
+
 impl MarkdownCodec for Array {
     fn to_markdown(&self, context: &mut MarkdownEncodeContext) {
         let (text, losses) = self.to_text();
@@ -2,7 +2,7 @@ use codec_markdown_trait::to_markdown;
 use common::serde_yaml;
 use node_strip::{StripNode, StripTargets};
 
-use crate::{prelude::*, Article, Author, Block, Section, SectionType};
+use crate::{prelude::*, Article, Author, Block, Inline, Paragraph, Section, SectionType};
 
 impl Article {
     pub fn to_jats_special(&self) -> (String, Losses) {
@@ -147,6 +147,20 @@ impl MarkdownCodec for Article {
             }
         }
 
+        // In R Markdown, a leading paragraph containing only parameters is
+        // encoded as `params` in the YAML header
+        let params = match (&context.format, self.content.first()) {
+            (Format::Rmd, Some(Block::Paragraph(Paragraph { content, .. })))
+                if !content.is_empty()
+                    && content
+                        .iter()
+                        .all(|inline| matches!(inline, Inline::Parameter(..))) =>
+            {
+                Some(content)
+            }
+            _ => None,
+        };
+
         let mut yaml = serde_yaml::to_value(header).unwrap_or_default();
         if let Some(yaml) = yaml.as_mapping_mut() {
             // Remove the type and (the now empty) content array
@@ -159,6 +173,34 @@ impl MarkdownCodec for Article {
                 yaml.insert(Value::from("title"), Value::from(title));
             }
 
+            // Set params if any
+            if let Some(params) = params {
+                let mut mapping = serde_yaml::Mapping::new();
+                for inline in params {
+                    let Inline::Parameter(param) = inline else {
+                        continue;
+                    };
+
+                    let value = serde_yaml::to_value(
+                        param.value.as_ref().or(param.options.default.as_ref()),
+                    )
+                    .unwrap_or_default();
+
+                    let value = match &param.options.label {
+                        Some(label) => {
+                            let mut object = serde_yaml::Mapping::new();
+                            object.insert(Value::from("label"), Value::from(label.as_str()));
+                            object.insert(Value::from("value"), value);
+                            Value::Mapping(object)
+                        }
+                        None => value,
+                    };
+
+                    mapping.insert(Value::from(param.name.as_str()), value);
+                }
+                yaml.insert(Value::from("params"), Value::Mapping(mapping));
+            }
+
             // Only add a YAML header if there are remaining keys
             if !yaml.is_empty() {
                 let yaml = serde_yaml::to_string(&yaml).unwrap_or_default();
@@ -169,7 +211,14 @@ impl MarkdownCodec for Article {
         }
 
         context.push_prop_fn(NodeProperty::Content, |context| {
-            self.content.to_markdown(context)
+            let content = if params.is_some() {
+                &self.content[1..]
+            } else {
+                &self.content[..]
+            };
+            for block in content {
+                block.to_markdown(context)
+            }
         });
 
         context.append_footnotes();
@@ -177,3 +226,13 @@ impl MarkdownCodec for Article {
         context.exit_node_final();
     }
 }
+
+impl TypstCodec for Article {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context.enter_node(self.node_type(), self.node_id());
+
+        self.content.to_typst(context);
+
+        context.exit_node_final();
+    }
+}
@@ -84,3 +84,38 @@ impl MarkdownCodec for CodeBlock {
         context.push_str(&backticks).newline().exit_node().newline();
     }
 }
+
+impl TypstCodec for CodeBlock {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        let backticks = "`".repeat(3.max(longest_backticks(&self.code) + 1));
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id))
+            .str(&backticks);
+
+        if let Some(lang) = &self.programming_language {
+            context.property_str(NodeProperty::ProgrammingLanguage, lang);
+        }
+
+        context
+            .newline()
+            .property_fn(NodeProperty::Code, |context| self.code.to_typst(context));
+
+        if !self.code.ends_with('\n') {
+            context.newline();
+        }
+
+        context.str(&backticks).newline().exit_node().newline();
+    }
+}
+
+/// Get the length of the longest run of backticks in some code
+///
+/// Used to determine how many backticks are needed to delimit Typst raw blocks.
+pub(crate) fn longest_backticks(code: &str) -> usize {
+    code.split(|char| char != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or_default()
+}
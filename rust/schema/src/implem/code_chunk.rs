@@ -380,6 +380,80 @@ impl MarkdownCodec for CodeChunk {
                     }
                 })
                 .push_str("```\n\n");
+        } else if matches!(context.format, Format::Rmd) {
+            context.push_str("```{").push_prop_str(
+                NodeProperty::ProgrammingLanguage,
+                self.programming_language.as_deref().unwrap_or("r"),
+            );
+
+            if !self.label_automatically.unwrap_or(true) {
+                if let Some(label) = &self.label {
+                    context
+                        .push_str(" ")
+                        .push_prop_str(NodeProperty::Label, label);
+                }
+            }
+
+            match (
+                matches!(self.is_invisible, Some(true)),
+                matches!(self.is_code_hidden, Some(true)),
+            ) {
+                (true, true) => {
+                    context
+                        .push_str(", ")
+                        .push_prop_str(NodeProperty::IsInvisible, "include=FALSE");
+                }
+                (true, false) => {
+                    context
+                        .push_str(", ")
+                        .push_prop_str(NodeProperty::IsInvisible, "results='hide'");
+                }
+                (false, true) => {
+                    context
+                        .push_str(", ")
+                        .push_prop_str(NodeProperty::IsCodeHidden, "echo=FALSE");
+                }
+                (false, false) => {}
+            }
+
+            match &self.execution_mode {
+                None | Some(ExecutionMode::Default) => {}
+                Some(ExecutionMode::Need) => {
+                    context
+                        .push_str(", ")
+                        .push_prop_str(NodeProperty::ExecutionMode, "cache=TRUE");
+                }
+                Some(..) => {
+                    context.add_loss("CodeChunk.executionMode");
+                }
+            }
+
+            if let Some(caption) = &self.caption {
+                context
+                    .push_str(match &self.label_type {
+                        Some(LabelType::TableLabel) => ", tab",
+                        _ => ", fig",
+                    })
+                    .push_str(".cap=\"")
+                    .push_prop_str(
+                        NodeProperty::Caption,
+                        &to_markdown(caption)
+                            .trim_end()
+                            .replace('\n', " ")
+                            .replace('"', "\\\""),
+                    )
+                    .push_str("\"");
+            }
+
+            context
+                .push_str("}\n")
+                .push_prop_fn(NodeProperty::Code, |context| {
+                    self.code.to_markdown(context);
+                    if !self.code.ends_with('\n') {
+                        context.newline();
+                    }
+                })
+                .push_str("```\n");
         } else {
             let wrapped =
                 if self.label_type.is_some() || self.label.is_some() || self.caption.is_some() {
@@ -469,3 +543,56 @@ impl MarkdownCodec for CodeChunk {
         context.exit_node().newline();
     }
 }
+
+impl TypstCodec for CodeChunk {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use super::code_block::longest_backticks;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, label_type, label))
+            .merge_losses(lost_exec_options!(self));
+
+        // Typst documents are not executable so, unless rendering, encode
+        // the code as a raw block so that it is not lost
+        if !context.render {
+            let backticks = "`".repeat(3.max(longest_backticks(&self.code) + 1));
+
+            context.str(&backticks);
+            if let Some(lang) = &self.programming_language {
+                context.property_str(NodeProperty::ProgrammingLanguage, lang);
+            }
+            context
+                .newline()
+                .property_fn(NodeProperty::Code, |context| self.code.to_typst(context));
+            if !self.code.ends_with('\n') {
+                context.newline();
+            }
+            context.str(&backticks).newline().newline();
+        }
+
+        // Encode outputs (unless invisible), wrapped in a figure if there is a caption
+        if !matches!(self.is_invisible, Some(true)) {
+            if self.caption.is_some() {
+                context.str("#figure([\n");
+            }
+
+            for output in self.outputs.iter().flatten() {
+                output.to_typst(context);
+                if !context.content.ends_with("\n\n") {
+                    context.str("\n\n");
+                }
+            }
+
+            if let Some(caption) = &self.caption {
+                context
+                    .str("], caption: [\n")
+                    .property_fn(NodeProperty::Caption, |context| caption.to_typst(context))
+                    .trim_end()
+                    .str("\n])\n\n");
+            }
+        }
+
+        context.exit_node();
+    }
+}
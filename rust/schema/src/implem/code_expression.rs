@@ -52,6 +52,20 @@ impl MarkdownCodec for CodeExpression {
             context
                 .push_prop_fn(NodeProperty::Code, |context| self.code.to_markdown(context))
                 .push_str("`");
+        } else if matches!(context.format, Format::Rmd) {
+            // Knitr only supports R for inline code
+            if self
+                .programming_language
+                .as_ref()
+                .map_or(false, |lang| !lang.eq_ignore_ascii_case("r"))
+            {
+                context.add_loss("CodeExpression.programmingLanguage");
+            }
+
+            context
+                .push_str("`r ")
+                .push_prop_fn(NodeProperty::Code, |context| self.code.to_markdown(context))
+                .push_str("`");
         } else {
             context
                 .push_str("`")
@@ -81,3 +95,29 @@ impl MarkdownCodec for CodeExpression {
         context.exit_node();
     }
 }
+
+impl TypstCodec for CodeExpression {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::quote;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_exec_options!(self));
+
+        // Typst documents are not executable so encode the output if there is one,
+        // falling back to the code
+        if let Some(output) = &self.output {
+            context
+                .merge_losses(lost_options!(self, id))
+                .property_fn(NodeProperty::Output, |context| output.to_typst(context));
+        } else {
+            context
+                .merge_losses(lost_options!(self, id, programming_language))
+                .str("#raw(")
+                .property_str(NodeProperty::Code, &quote(&self.code))
+                .str(")");
+        }
+
+        context.exit_node();
+    }
+}
@@ -49,3 +49,34 @@ impl MarkdownCodec for CodeInline {
         context.exit_node();
     }
 }
+
+impl TypstCodec for CodeInline {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::quote;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id));
+
+        if self.programming_language.is_none() && !self.code.contains('`') {
+            context
+                .str("`")
+                .property_fn(NodeProperty::Code, |context| self.code.to_typst(context))
+                .str("`");
+        } else {
+            context
+                .str("#raw(")
+                .property_str(NodeProperty::Code, &quote(&self.code));
+
+            if let Some(lang) = &self.programming_language {
+                context
+                    .str(", lang: ")
+                    .property_str(NodeProperty::ProgrammingLanguage, &quote(lang));
+            }
+
+            context.str(")");
+        }
+
+        context.exit_node();
+    }
+}
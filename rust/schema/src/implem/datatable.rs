@@ -1,6 +1,10 @@
 use codec_info::lost_options;
 
-use crate::{prelude::*, ArrayValidator, Datatable, Primitive};
+use crate::{
+    prelude::*,
+    shortcuts::{t, td, th, tr},
+    ArrayValidator, Datatable, DatatableHint, Primitive, Table, TableRow, TableRowType,
+};
 
 impl Datatable {
     /// Get the number of rows in the `Datatable`
@@ -9,6 +13,43 @@ impl Datatable {
             .iter()
             .fold(0usize, |rows, column| rows.max(column.values.len()))
     }
+
+    /// Generate a [`DatatableHint`] summarizing the `Datatable`
+    pub fn hint(&self) -> DatatableHint {
+        DatatableHint::new(
+            self.rows() as i64,
+            self.columns.iter().map(|column| column.hint()).collect(),
+        )
+    }
+
+    /// Convert the `Datatable` to a [`Table`]
+    ///
+    /// Used when a `Datatable` needs to be placed in a document's content
+    /// (e.g. when included from a spreadsheet) because it is not a block.
+    pub fn to_table(&self) -> Table {
+        let header = TableRow {
+            row_type: Some(TableRowType::HeaderRow),
+            ..tr(self
+                .columns
+                .iter()
+                .map(|column| th([t(column.name.clone())]))
+                .collect_vec())
+        };
+
+        let rows = (0..self.rows()).map(|row| {
+            tr(self
+                .columns
+                .iter()
+                .map(|column| match column.values.get(row) {
+                    Some(Primitive::Null(..)) | None => td([]),
+                    Some(Primitive::String(value)) => td([t(value.clone())]),
+                    Some(value) => td([t(value.to_text().0)]),
+                })
+                .collect_vec())
+        });
+
+        Table::new(std::iter::once(header).chain(rows).collect())
+    }
 }
 
 impl DomCodec for Datatable {
@@ -167,3 +208,37 @@ impl MarkdownCodec for Datatable {
         context.exit_node().newline();
     }
 }
+
+impl TypstCodec for Datatable {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::escape;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id))
+            .str(&format!(
+                "#table(\n  columns: {},\n  table.header(",
+                self.columns.len().max(1)
+            ));
+
+        for column in &self.columns {
+            context.str("[*").str(&escape(&column.name)).str("*], ");
+        }
+        context.trim_end().str("),\n");
+
+        for row in 0..self.rows() {
+            context.str("  ");
+            for column in &self.columns {
+                let text = match column.values.get(row) {
+                    Some(Primitive::String(value)) => value.clone(),
+                    Some(value) => value.to_text().0,
+                    None => String::new(),
+                };
+                context.str("[").str(&escape(&text)).str("], ");
+            }
+            context.trim_end().newline();
+        }
+
+        context.str(")").newline().exit_node().newline();
+    }
+}
@@ -1,4 +1,187 @@
-use crate::{prelude::*, DatatableColumn};
+use common::chrono::NaiveDate;
+
+use crate::{
+    prelude::*, ArrayValidator, BooleanValidator, DatatableColumn, DatatableColumnHint, Date,
+    DateValidator, EnumValidator, IntegerValidator, Node, NumberValidator, Primitive,
+    StringValidator, Validator,
+};
+
+/// The maximum number of unique values in a column of strings for it to be
+/// considered an enumeration
+const ENUM_MAX_VALUES: usize = 10;
+
+impl DatatableColumn {
+    /// Create a new column and infer a validator from its values
+    ///
+    /// Nulls are ignored when inferring the validator for items (but if there are
+    /// any, `items_nullable` is set). Strings which are all ISO 8601 dates are given a
+    /// `DateValidator`, and those with a small number of repeated values are given
+    /// an `EnumValidator`.
+    pub fn from_values(name: String, values: Vec<Primitive>) -> Self {
+        let validator = infer_validator(&values);
+        Self {
+            validator,
+            ..Self::new(name, values)
+        }
+    }
+
+    /// Generate a [`DatatableColumnHint`] summarizing the column
+    pub fn hint(&self) -> DatatableColumnHint {
+        let item_type = match self
+            .validator
+            .as_ref()
+            .and_then(|validator| validator.items_validator.as_deref())
+        {
+            Some(Validator::EnumValidator(..)) => "String".to_string(),
+            Some(validator) => validator
+                .to_string()
+                .trim_end_matches("Validator")
+                .to_string(),
+            None => self
+                .values
+                .iter()
+                .find(|value| !matches!(value, Primitive::Null(..)))
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "Null".to_string()),
+        };
+
+        let nulls = self
+            .values
+            .iter()
+            .filter(|value| matches!(value, Primitive::Null(..)))
+            .count();
+
+        let (minimum, maximum) = match item_type.as_str() {
+            "Integer" | "Number" => {
+                let numbers = self.values.iter().filter_map(|value| match value {
+                    Primitive::Integer(value) => Some(*value as f64),
+                    Primitive::Number(value) => Some(*value),
+                    _ => None,
+                });
+                let min = numbers.clone().reduce(f64::min);
+                let max = numbers.reduce(f64::max);
+                if item_type == "Integer" {
+                    (
+                        min.map(|min| Primitive::Integer(min as i64)),
+                        max.map(|max| Primitive::Integer(max as i64)),
+                    )
+                } else {
+                    (min.map(Primitive::Number), max.map(Primitive::Number))
+                }
+            }
+            "Boolean" => {
+                let booleans = self.values.iter().filter_map(|value| match value {
+                    Primitive::Boolean(value) => Some(*value),
+                    _ => None,
+                });
+                (
+                    booleans.clone().min().map(Primitive::Boolean),
+                    booleans.max().map(Primitive::Boolean),
+                )
+            }
+            "Date" => {
+                let dates = self.values.iter().filter_map(|value| match value {
+                    Primitive::String(value) => Some(value),
+                    _ => None,
+                });
+                (
+                    dates.clone().min().cloned().map(Primitive::String),
+                    dates.max().cloned().map(Primitive::String),
+                )
+            }
+            _ => (None, None),
+        };
+
+        DatatableColumnHint {
+            minimum,
+            maximum,
+            nulls: Some(nulls as i64),
+            ..DatatableColumnHint::new(self.name.clone(), item_type)
+        }
+    }
+}
+
+/// Infer an [`ArrayValidator`] for the values of a column
+fn infer_validator(values: &[Primitive]) -> Option<ArrayValidator> {
+    let non_null = values
+        .iter()
+        .filter(|value| !matches!(value, Primitive::Null(..)))
+        .collect_vec();
+    if non_null.is_empty() {
+        return None;
+    }
+
+    let numbers = || {
+        non_null.iter().filter_map(|value| match value {
+            Primitive::Integer(value) => Some(*value as f64),
+            Primitive::Number(value) => Some(*value),
+            _ => None,
+        })
+    };
+    let strings = || {
+        non_null.iter().filter_map(|value| match value {
+            Primitive::String(value) => Some(value),
+            _ => None,
+        })
+    };
+
+    let items_validator = if non_null
+        .iter()
+        .all(|value| matches!(value, Primitive::Boolean(..)))
+    {
+        Validator::BooleanValidator(BooleanValidator::new())
+    } else if non_null
+        .iter()
+        .all(|value| matches!(value, Primitive::Integer(..)))
+    {
+        Validator::IntegerValidator(IntegerValidator {
+            minimum: numbers().reduce(f64::min),
+            maximum: numbers().reduce(f64::max),
+            ..Default::default()
+        })
+    } else if non_null
+        .iter()
+        .all(|value| matches!(value, Primitive::Integer(..) | Primitive::Number(..)))
+    {
+        Validator::NumberValidator(NumberValidator {
+            minimum: numbers().reduce(f64::min),
+            maximum: numbers().reduce(f64::max),
+            ..Default::default()
+        })
+    } else if non_null
+        .iter()
+        .all(|value| matches!(value, Primitive::String(..)))
+    {
+        if strings().all(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()) {
+            // ISO 8601 dates sort lexicographically
+            Validator::DateValidator(DateValidator {
+                minimum: strings().min().cloned().map(Date::new),
+                maximum: strings().max().cloned().map(Date::new),
+                ..Default::default()
+            })
+        } else {
+            let unique = strings().unique().collect_vec();
+            if unique.len() <= ENUM_MAX_VALUES && unique.len() * 2 <= non_null.len() {
+                Validator::EnumValidator(EnumValidator::new(
+                    unique
+                        .into_iter()
+                        .map(|value| Node::String(value.clone()))
+                        .collect(),
+                ))
+            } else {
+                Validator::StringValidator(StringValidator::new())
+            }
+        }
+    } else {
+        return None;
+    };
+
+    Some(ArrayValidator {
+        items_nullable: Some(non_null.len() < values.len()),
+        items_validator: Some(Box::new(items_validator)),
+        ..Default::default()
+    })
+}
 
 impl DomCodec for DatatableColumn {
     fn to_dom(&self, context: &mut DomEncodeContext) {
@@ -103,3 +103,27 @@ impl MarkdownCodec for Figure {
         context.exit_node().newline();
     }
 }
+
+impl TypstCodec for Figure {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, label, authors, provenance))
+            .str("#figure([\n")
+            .property_fn(NodeProperty::Content, |context| {
+                self.content.to_typst(context)
+            })
+            .trim_end()
+            .str("\n]");
+
+        if let Some(caption) = &self.caption {
+            context
+                .str(", caption: [\n")
+                .property_fn(NodeProperty::Caption, |context| caption.to_typst(context))
+                .trim_end()
+                .str("\n]");
+        }
+
+        context.str(")").newline().exit_node().newline();
+    }
+}
@@ -95,3 +95,19 @@ impl MarkdownCodec for Heading {
             .newline();
     }
 }
+
+impl TypstCodec for Heading {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id))
+            .str(&"=".repeat(self.level.clamp(1, 6) as usize))
+            .space()
+            .property_fn(NodeProperty::Content, |context| {
+                self.content.to_typst(context)
+            })
+            .newline()
+            .exit_node()
+            .newline();
+    }
+}
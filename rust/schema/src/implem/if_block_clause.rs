@@ -9,7 +9,10 @@ impl MarkdownCodec for IfBlockClause {
             .merge_losses(lost_exec_options!(self))
             .push_prop_fn(NodeProperty::Code, |context| self.code.to_markdown(context));
 
-        if matches!(context.format, Format::Markdown | Format::Smd | Format::Qmd) {
+        if matches!(
+            context.format,
+            Format::Markdown | Format::Smd | Format::Qmd | Format::Rmd
+        ) {
             if let Some(lang) = &self.programming_language {
                 if !lang.is_empty() {
                     context
@@ -69,3 +69,21 @@ impl MarkdownCodec for Link {
         context.push_str(")").exit_node();
     }
 }
+
+impl TypstCodec for Link {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::quote;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, title, rel))
+            .str("#link(")
+            .property_str(NodeProperty::Target, &quote(&self.target))
+            .str(")[")
+            .property_fn(NodeProperty::Content, |context| {
+                self.content.to_typst(context)
+            })
+            .str("]")
+            .exit_node();
+    }
+}
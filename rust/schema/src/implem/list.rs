@@ -101,3 +101,29 @@ impl MarkdownCodec for List {
         context.trim_end().newline().exit_node().newline();
     }
 }
+
+impl TypstCodec for List {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id));
+
+        let marker = match self.order {
+            ListOrder::Ascending => "+ ",
+            _ => "- ",
+        };
+
+        let tight = self.items.iter().all(|item| item.content.len() == 1);
+
+        for item in self.items.iter() {
+            context.str(marker);
+            item.to_typst(context);
+
+            if tight {
+                context.trim_end().newline();
+            }
+        }
+
+        context.trim_end().newline().exit_node().newline();
+    }
+}
@@ -49,3 +49,17 @@ impl MarkdownCodec for ListItem {
             .exit_node();
     }
 }
+
+impl TypstCodec for ListItem {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, item, position, is_checked))
+            .increase_depth()
+            .property_fn(NodeProperty::Content, |context| {
+                self.content.to_typst(context)
+            })
+            .decrease_depth()
+            .exit_node();
+    }
+}
@@ -121,3 +121,36 @@ impl MarkdownCodec for MathBlock {
         context.newline().exit_node().newline();
     }
 }
+
+impl TypstCodec for MathBlock {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use super::math_inline::typst_math;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id));
+
+        if let Some(math) = typst_math(self.math_language.as_deref(), &self.code) {
+            context
+                .str("$ ")
+                .property_str(NodeProperty::Code, &math)
+                .str(" $");
+        } else {
+            // Math in other languages (e.g. MathML), or TeX which can not be
+            // converted to Typst, is encoded as a raw block
+            let lang = self.math_language.as_deref().unwrap_or("tex");
+            context
+                .add_loss("MathBlock.code")
+                .str("```")
+                .property_str(NodeProperty::MathLanguage, lang)
+                .newline()
+                .property_fn(NodeProperty::Code, |context| self.code.to_typst(context));
+            if !self.code.ends_with('\n') {
+                context.newline();
+            }
+            context.str("```");
+        }
+
+        context.newline().exit_node().newline();
+    }
+}
@@ -99,3 +99,42 @@ impl MarkdownCodec for MathInline {
         context.exit_node();
     }
 }
+
+impl TypstCodec for MathInline {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::quote;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id));
+
+        if let Some(math) = typst_math(self.math_language.as_deref(), &self.code) {
+            context
+                .str("$")
+                .property_str(NodeProperty::Code, &math)
+                .str("$");
+        } else {
+            // Math in other languages (e.g. MathML), or TeX which can not be
+            // converted to Typst, is encoded as raw inline code
+            context
+                .add_loss("MathInline.code")
+                .str("#raw(")
+                .property_str(NodeProperty::Code, &quote(&self.code))
+                .str(")");
+        }
+
+        context.exit_node();
+    }
+}
+
+/// Get the Typst math for math code, if possible
+///
+/// Math which is declared to be Typst is used as is. TeX math (the default math
+/// language) is converted to Typst math if it only uses supported commands.
+pub(super) fn typst_math(math_language: Option<&str>, code: &str) -> Option<String> {
+    match math_language {
+        Some("typst") => Some(code.trim().to_string()),
+        Some("tex") | Some("latex") | None => codec_typst_trait::tex_to_typst(code),
+        _ => None,
+    }
+}
@@ -268,3 +268,17 @@ impl MarkdownCodec for VideoObject {
         )
     }
 }
+
+impl TypstCodec for ImageObject {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::quote;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, caption, title))
+            .str("#image(")
+            .property_str(NodeProperty::ContentUrl, &quote(&self.content_url))
+            .str(")")
+            .exit_node();
+    }
+}
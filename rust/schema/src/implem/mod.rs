@@ -84,6 +84,7 @@ mod table;
 mod table_cell;
 mod table_row;
 mod text;
+mod thematic_break;
 mod time;
 mod timestamp;
 mod validators;
@@ -23,10 +23,12 @@ impl Node {
             AuthorRole,
             Boolean,
             BooleanValidator,
+            Book,
             Brand,
             Button,
             CallArgument,
             CallBlock,
+            Chapter,
             Chat,
             ChatMessage,
             ChatMessageGroup,
@@ -143,6 +145,7 @@ impl Node {
             TableRow,
             Text,
             ThematicBreak,
+            Thesis,
             Thing,
             Time,
             Timestamp,
@@ -187,10 +190,12 @@ impl Node {
             AudioObject,
             AuthorRole,
             BooleanValidator,
+            Book,
             Brand,
             Button,
             CallArgument,
             CallBlock,
+            Chapter,
             Chat,
             ChatMessage,
             ChatMessageGroup,
@@ -301,6 +306,7 @@ impl Node {
             TableRow,
             Text,
             ThematicBreak,
+            Thesis,
             Thing,
             Time,
             Timestamp,
@@ -385,10 +391,12 @@ impl ReadNode for Node {
             AudioObject,
             AuthorRole,
             BooleanValidator,
+            Book,
             Brand,
             Button,
             CallArgument,
             CallBlock,
+            Chapter,
             Chat,
             ChatMessage,
             ChatMessageGroup,
@@ -500,6 +508,7 @@ impl ReadNode for Node {
             TableRow,
             Text,
             ThematicBreak,
+            Thesis,
             Thing,
             Time,
             Timestamp,
@@ -63,6 +63,14 @@ impl LatexCodec for Null {
     }
 }
 
+impl TypstCodec for Null {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        let (text, losses) = self.to_text();
+        context.str(&text);
+        context.merge_losses(losses);
+    }
+}
+
 impl MarkdownCodec for Null {
     fn to_markdown(&self, context: &mut MarkdownEncodeContext) {
         let (text, losses) = self.to_text();
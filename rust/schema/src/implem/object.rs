@@ -134,6 +134,14 @@ impl LatexCodec for Object {
     }
 }
 
+impl TypstCodec for Object {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        let (text, losses) = self.to_text();
+        context.str(&text);
+        context.merge_losses(losses);
+    }
+}
+
 impl MarkdownCodec for Object {
     fn to_markdown(&self, context: &mut MarkdownEncodeContext) {
         let (text, losses) = self.to_text();
@@ -12,3 +12,16 @@ impl LatexCodec for Paragraph {
             .newline();
     }
 }
+
+impl TypstCodec for Paragraph {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .property_fn(NodeProperty::Content, |context| {
+                self.content.to_typst(context)
+            })
+            .newline()
+            .exit_node()
+            .newline();
+    }
+}
@@ -25,3 +25,20 @@ impl MarkdownCodec for QuoteBlock {
         context.newline().exit_node().newline();
     }
 }
+
+impl TypstCodec for QuoteBlock {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, cite))
+            .str("#quote(block: true)[\n")
+            .property_fn(NodeProperty::Content, |context| {
+                self.content.to_typst(context)
+            })
+            .trim_end()
+            .str("\n]")
+            .newline()
+            .exit_node()
+            .newline();
+    }
+}
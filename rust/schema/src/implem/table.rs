@@ -305,3 +305,70 @@ impl MarkdownCodec for Table {
         context.exit_node().newline();
     }
 }
+
+impl TypstCodec for Table {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .merge_losses(lost_options!(self, id, label, authors, provenance));
+
+        if self.caption.is_some() {
+            context.str("#figure(\n");
+        } else {
+            context.str("#");
+        }
+
+        let columns = self
+            .rows
+            .iter()
+            .map(|row| row.cells.len())
+            .max()
+            .unwrap_or(1);
+        context.str(&format!("table(\n  columns: {columns},\n"));
+
+        for row in &self.rows {
+            context.enter_node(row.node_type(), row.node_id());
+
+            let (start, end) = match row.row_type {
+                Some(TableRowType::HeaderRow) => ("table.header(", "),\n"),
+                Some(TableRowType::FooterRow) => ("table.footer(", "),\n"),
+                _ => ("", "\n"),
+            };
+            context.str("  ").str(start);
+
+            for cell in &row.cells {
+                context
+                    .enter_node(cell.node_type(), cell.node_id())
+                    .str("[")
+                    .property_fn(NodeProperty::Content, |context| {
+                        cell.content.to_typst(context)
+                    })
+                    .trim_end()
+                    .str("], ")
+                    .exit_node();
+            }
+
+            context.trim_end().str(end).exit_node();
+        }
+
+        context.str(")");
+
+        if let Some(caption) = &self.caption {
+            context
+                .str(",\n  caption: [\n")
+                .property_fn(NodeProperty::Caption, |context| caption.to_typst(context))
+                .trim_end()
+                .str("\n  ]\n)");
+        }
+
+        context.newline();
+
+        if let Some(notes) = &self.notes {
+            context
+                .newline()
+                .property_fn(NodeProperty::Notes, |context| notes.to_typst(context));
+        }
+
+        context.exit_node().newline();
+    }
+}
@@ -39,3 +39,14 @@ impl MarkdownCodec for Text {
             .exit_node();
     }
 }
+
+impl TypstCodec for Text {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        use codec_typst_trait::escape;
+
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .property_str(NodeProperty::Value, &escape(self.value.as_str()))
+            .exit_node();
+    }
+}
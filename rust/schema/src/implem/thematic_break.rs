@@ -0,0 +1,12 @@
+use crate::{prelude::*, ThematicBreak};
+
+impl TypstCodec for ThematicBreak {
+    fn to_typst(&self, context: &mut TypstEncodeContext) {
+        context
+            .enter_node(self.node_type(), self.node_id())
+            .str("#line(length: 100%)")
+            .newline()
+            .exit_node()
+            .newline();
+    }
+}
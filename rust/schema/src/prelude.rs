@@ -20,6 +20,7 @@ pub use codec_jats_trait::JatsCodec;
 pub use codec_latex_trait::{LatexCodec, LatexEncodeContext};
 pub use codec_markdown_trait::{MarkdownCodec, MarkdownEncodeContext};
 pub use codec_text_trait::TextCodec;
+pub use codec_typst_trait::{TypstCodec, TypstEncodeContext};
 pub use format::Format;
 pub use node_id::{NodeId, NodeUid};
 pub use node_patch_derive::PatchNode;
@@ -12,12 +12,14 @@ mod author_role;
 mod author_role_author;
 mod author_role_name;
 mod block;
+mod book;
 mod boolean;
 mod boolean_validator;
 mod brand;
 mod button;
 mod call_argument;
 mod call_block;
+mod chapter;
 mod chat;
 mod chat_message;
 mod chat_message_group;
@@ -176,6 +178,7 @@ mod table_row;
 mod table_row_type;
 mod text;
 mod thematic_break;
+mod thesis;
 mod thing;
 mod thing_type;
 mod time;
@@ -208,12 +211,14 @@ pub use author_role::*;
 pub use author_role_author::*;
 pub use author_role_name::*;
 pub use block::*;
+pub use book::*;
 pub use boolean::*;
 pub use boolean_validator::*;
 pub use brand::*;
 pub use button::*;
 pub use call_argument::*;
 pub use call_block::*;
+pub use chapter::*;
 pub use chat::*;
 pub use chat_message::*;
 pub use chat_message_group::*;
@@ -372,6 +377,7 @@ pub use table_row::*;
 pub use table_row_type::*;
 pub use text::*;
 pub use thematic_break::*;
+pub use thesis::*;
 pub use thing::*;
 pub use thing_type::*;
 pub use time::*;
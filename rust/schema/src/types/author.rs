@@ -8,7 +8,7 @@ use super::person::Person;
 use super::software_application::SoftwareApplication;
 
 /// Union type for things that can be an author of a `CreativeWork` or other type.
-#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec)]
+#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
 #[serde(untagged, crate = "common::serde")]
 pub enum Author {
     #[default]
@@ -0,0 +1,269 @@
+// Generated file; do not edit. See `schema-gen` crate.
+
+use crate::prelude::*;
+
+use super::author::Author;
+use super::block::Block;
+use super::comment::Comment;
+use super::creative_work_type::CreativeWorkType;
+use super::creative_work_type_or_text::CreativeWorkTypeOrText;
+use super::date::Date;
+use super::grant_or_monetary_grant::GrantOrMonetaryGrant;
+use super::image_object::ImageObject;
+use super::inline::Inline;
+use super::object::Object;
+use super::person::Person;
+use super::person_or_organization::PersonOrOrganization;
+use super::property_value_or_string::PropertyValueOrString;
+use super::provenance_count::ProvenanceCount;
+use super::string::String;
+use super::string_or_number::StringOrNumber;
+use super::text::Text;
+use super::thing_type::ThingType;
+
+/// A book.
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+#[derive(derive_more::Display)]
+#[display(fmt = "Book")]
+#[patch(authors_on = "self")]
+pub struct Book {
+    /// The type of this item.
+    pub r#type: MustBe!("Book"),
+
+    /// The identifier for this item.
+    #[strip(metadata)]
+    #[html(attr = "id")]
+    pub id: Option<String>,
+
+    /// The authors of the `CreativeWork`.
+    #[serde(alias = "author")]
+    #[serde(default, deserialize_with = "option_one_or_many_string_or_object")]
+    #[strip(authors)]
+    #[dom(elem = "section")]
+    pub authors: Option<Vec<Author>>,
+
+    /// Date of first publication.
+    #[serde(alias = "date", alias = "date-published", alias = "date_published")]
+    #[serde(default, deserialize_with = "option_string_or_object")]
+    #[strip(metadata)]
+    pub date_published: Option<Date>,
+
+    /// The title of the creative work.
+    #[serde(alias = "headline")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    #[patch(format = "md", format = "smd", format = "myst", format = "ipynb", format = "qmd")]
+    #[dom(elem = "h1")]
+    pub title: Option<Vec<Inline>>,
+
+    /// Non-core optional fields
+    #[serde(flatten)]
+    #[html(flatten)]
+    #[jats(flatten)]
+    pub options: Box<BookOptions>,
+
+    /// A unique identifier for a node within a document
+    
+    #[serde(skip)]
+    pub uid: NodeUid
+}
+
+#[skip_serializing_none]
+#[serde_as]
+#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct BookOptions {
+    /// Alternate names (aliases) for the item.
+    #[serde(alias = "alternate-names", alias = "alternate_names", alias = "alternateName", alias = "alternate-name", alias = "alternate_name")]
+    #[serde(default, deserialize_with = "option_csv_or_array")]
+    #[strip(metadata)]
+    pub alternate_names: Option<Vec<String>>,
+
+    /// A description of the item.
+    #[strip(metadata)]
+    #[patch(format = "md", format = "smd", format = "myst", format = "ipynb", format = "qmd")]
+    pub description: Option<String>,
+
+    /// Any kind of identifier for any kind of Thing.
+    #[serde(alias = "identifier")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    pub identifiers: Option<Vec<PropertyValueOrString>>,
+
+    /// Images of the item.
+    #[serde(alias = "image")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    pub images: Option<Vec<ImageObject>>,
+
+    /// The name of the item.
+    #[strip(metadata)]
+    pub name: Option<String>,
+
+    /// The URL of the item.
+    #[strip(metadata)]
+    pub url: Option<String>,
+
+    /// The subject matter of the content.
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    pub about: Option<Vec<ThingType>>,
+
+    /// A a short description that summarizes a `CreativeWork`.
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub r#abstract: Option<Vec<Block>>,
+
+    /// A summary of the provenance of the content within the work.
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(provenance)]
+    #[dom(elem = "div")]
+    pub provenance: Option<Vec<ProvenanceCount>>,
+
+    /// A secondary contributor to the `CreativeWork`.
+    #[serde(alias = "contributor")]
+    #[serde(default, deserialize_with = "option_one_or_many_string_or_object")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub contributors: Option<Vec<Author>>,
+
+    /// People who edited the `CreativeWork`.
+    #[serde(alias = "editor")]
+    #[serde(default, deserialize_with = "option_one_or_many_string_or_object")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub editors: Option<Vec<Person>>,
+
+    /// The maintainers of the `CreativeWork`.
+    #[serde(alias = "maintainer")]
+    #[serde(default, deserialize_with = "option_one_or_many_string_or_object")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub maintainers: Option<Vec<PersonOrOrganization>>,
+
+    /// Comments about this creative work.
+    #[serde(alias = "comment")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub comments: Option<Vec<Comment>>,
+
+    /// Date/time of creation.
+    #[serde(alias = "date-created", alias = "date_created")]
+    #[serde(default, deserialize_with = "option_string_or_object")]
+    #[strip(metadata)]
+    pub date_created: Option<Date>,
+
+    /// Date/time that work was received.
+    #[serde(alias = "date-received", alias = "date_received")]
+    #[serde(default, deserialize_with = "option_string_or_object")]
+    #[strip(metadata)]
+    pub date_received: Option<Date>,
+
+    /// Date/time of acceptance.
+    #[serde(alias = "date-accepted", alias = "date_accepted")]
+    #[serde(default, deserialize_with = "option_string_or_object")]
+    #[strip(metadata)]
+    pub date_accepted: Option<Date>,
+
+    /// Date/time of most recent modification.
+    #[serde(alias = "date-modified", alias = "date_modified")]
+    #[serde(default, deserialize_with = "option_string_or_object")]
+    #[strip(metadata)]
+    pub date_modified: Option<Date>,
+
+    /// People or organizations that funded the `CreativeWork`.
+    #[serde(alias = "funder")]
+    #[serde(default, deserialize_with = "option_one_or_many_string_or_object")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub funders: Option<Vec<PersonOrOrganization>>,
+
+    /// Grants that funded the `CreativeWork`; reverse of `fundedItems`.
+    #[serde(alias = "funded-by", alias = "funded_by")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub funded_by: Option<Vec<GrantOrMonetaryGrant>>,
+
+    /// Genre of the creative work, broadcast channel or group.
+    #[serde(default, deserialize_with = "option_csv_or_array")]
+    #[strip(metadata)]
+    #[patch(format = "md", format = "smd", format = "myst", format = "ipynb", format = "qmd")]
+    pub genre: Option<Vec<String>>,
+
+    /// Keywords or tags used to describe this content. Multiple entries in a keywords list are typically delimited by commas.
+    #[serde(alias = "keyword")]
+    #[serde(default, deserialize_with = "option_csv_or_array")]
+    #[strip(metadata)]
+    #[patch(format = "md", format = "smd", format = "myst", format = "ipynb", format = "qmd")]
+    pub keywords: Option<Vec<String>>,
+
+    /// An item or other CreativeWork that this CreativeWork is a part of.
+    #[serde(alias = "is-part-of", alias = "is_part_of")]
+    #[strip(metadata)]
+    pub is_part_of: Option<CreativeWorkType>,
+
+    /// License documents that applies to this content, typically indicated by URL.
+    #[serde(alias = "license")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub licenses: Option<Vec<CreativeWorkTypeOrText>>,
+
+    /// Elements of the collection which can be a variety of different elements, such as Articles, Datatables, Tables and more.
+    #[serde(alias = "hasParts", alias = "part")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(content)]
+    #[dom(elem = "section")]
+    pub parts: Option<Vec<CreativeWorkType>>,
+
+    /// A publisher of the CreativeWork.
+    #[serde(default, deserialize_with = "option_string_or_object")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub publisher: Option<PersonOrOrganization>,
+
+    /// References to other creative works, such as another publication, web page, scholarly article, etc.
+    #[serde(alias = "citations", alias = "reference")]
+    #[serde(default, deserialize_with = "option_one_or_many")]
+    #[strip(metadata)]
+    #[dom(elem = "section")]
+    pub references: Option<Vec<CreativeWorkTypeOrText>>,
+
+    /// The textual content of this creative work.
+    #[strip(content)]
+    pub text: Option<Text>,
+
+    /// The version of the creative work.
+    #[strip(metadata)]
+    pub version: Option<StringOrNumber>,
+
+    /// Additional metadata for the book.
+    #[serde(flatten, deserialize_with = "empty_object_is_none")]
+    #[strip(metadata)]
+    #[dom(skip)]
+    pub extra: Option<Object>,
+}
+
+impl Book {
+    const NICK: [u8; 3] = [98, 111, 107];
+    
+    pub fn node_type(&self) -> NodeType {
+        NodeType::Book
+    }
+
+    pub fn node_id(&self) -> NodeId {
+        NodeId::new(&Self::NICK, &self.uid)
+    }
+    
+    pub fn new() -> Self {
+        Self {
+            ..Default::default()
+        }
+    }
+}
@@ -144,6 +144,12 @@ pub struct CodeChunk {
     #[cfg_attr(feature = "proptest", proptest(value = "None"))]
     pub is_invisible: Option<Boolean>,
 
+    /// Whether the code of the code chunk should be hidden from the reader.
+    #[serde(alias = "is-code-hidden", alias = "is_code_hidden")]
+    #[patch(format = "rmd", format = "org")]
+    #[cfg_attr(feature = "proptest", proptest(value = "None"))]
+    pub is_code_hidden: Option<Boolean>,
+
     /// Non-core optional fields
     #[serde(flatten)]
     #[html(flatten)]
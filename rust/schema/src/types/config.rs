@@ -2,12 +2,14 @@
 
 use crate::prelude::*;
 
+use super::execution_bounds::ExecutionBounds;
+use super::number::Number;
 use super::string::String;
 
 /// Stencila document configuration options.
 #[skip_serializing_none]
 #[serde_as]
-#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec)]
+#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
 #[serde(rename_all = "camelCase", crate = "common::serde")]
 #[derive(derive_more::Display)]
 #[display(fmt = "Config")]
@@ -24,6 +26,27 @@ pub struct Config {
     #[patch(format = "all")]
     pub theme: Option<String>,
 
+    /// The names of the kernels to use for executing code, in order of preference.
+    #[serde(alias = "kernel")]
+    #[serde(default, deserialize_with = "option_csv_or_array")]
+    #[patch(format = "all")]
+    pub kernels: Option<Vec<String>>,
+
+    /// The ids of the models to use for instructions, in order of preference.
+    #[serde(alias = "model")]
+    #[serde(default, deserialize_with = "option_csv_or_array")]
+    #[patch(format = "all")]
+    pub models: Option<Vec<String>>,
+
+    /// The default bounds on the execution of suggestions and other child nodes.
+    #[serde(alias = "execution-bounds", alias = "execution_bounds")]
+    #[patch(format = "all")]
+    pub execution_bounds: Option<ExecutionBounds>,
+
+    /// The maximum cost, in US dollars, of model tasks when executing the document.
+    #[patch(format = "all")]
+    pub budget: Option<Number>,
+
     /// A unique identifier for a node within a document
     
     #[serde(skip)]
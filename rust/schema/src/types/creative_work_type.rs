@@ -4,6 +4,8 @@ use crate::prelude::*;
 
 use super::article::Article;
 use super::audio_object::AudioObject;
+use super::book::Book;
+use super::chapter::Chapter;
 use super::chat::Chat;
 use super::claim::Claim;
 use super::collection::Collection;
@@ -20,10 +22,11 @@ use super::review::Review;
 use super::software_application::SoftwareApplication;
 use super::software_source_code::SoftwareSourceCode;
 use super::table::Table;
+use super::thesis::Thesis;
 use super::video_object::VideoObject;
 
 /// Union type for all types that are descended from `CreativeWork`
-#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec)]
+#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
 #[serde(untagged, crate = "common::serde")]
 pub enum CreativeWorkType {
     #[default]
@@ -31,6 +34,10 @@ pub enum CreativeWorkType {
 
     AudioObject(AudioObject),
 
+    Book(Book),
+
+    Chapter(Chapter),
+
     Chat(Chat),
 
     Claim(Claim),
@@ -63,5 +70,7 @@ pub enum CreativeWorkType {
 
     Table(Table),
 
+    Thesis(Thesis),
+
     VideoObject(VideoObject),
 }
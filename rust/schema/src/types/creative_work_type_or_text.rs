@@ -6,7 +6,7 @@ use super::creative_work_type::CreativeWorkType;
 use super::text::Text;
 
 /// [`CreativeWorkType`] or [`Text`]
-#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec)]
+#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
 #[serde(untagged, crate = "common::serde")]
 pub enum CreativeWorkTypeOrText {
     #[default]
@@ -5,15 +5,17 @@ use crate::prelude::*;
 use super::author::Author;
 use super::block::Block;
 use super::duration::Duration;
+use super::number::Number;
 use super::provenance_count::ProvenanceCount;
 use super::string::String;
 use super::suggestion_status::SuggestionStatus;
 use super::timestamp::Timestamp;
+use super::unsigned_integer::UnsignedInteger;
 
 /// A suggestion to insert some block content.
 #[skip_serializing_none]
 #[serde_as]
-#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, TextCodec)]
+#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, TextCodec, TypstCodec)]
 #[serde(rename_all = "camelCase", crate = "common::serde")]
 #[cfg_attr(feature = "proptest", derive(Arbitrary))]
 #[derive(derive_more::Display)]
@@ -83,6 +85,23 @@ pub struct InsertBlock {
     #[dom(elem = "div")]
     pub content: Vec<Block>,
 
+    /// The number of tokens in the prompt used to generate the suggestion.
+    #[serde(alias = "prompt-tokens", alias = "prompt_tokens")]
+    #[strip(provenance)]
+    #[cfg_attr(feature = "proptest", proptest(value = "None"))]
+    pub prompt_tokens: Option<UnsignedInteger>,
+
+    /// The number of tokens generated for the suggestion.
+    #[serde(alias = "completion-tokens", alias = "completion_tokens")]
+    #[strip(provenance)]
+    #[cfg_attr(feature = "proptest", proptest(value = "None"))]
+    pub completion_tokens: Option<UnsignedInteger>,
+
+    /// The estimated cost, in US dollars, of generating the suggestion.
+    #[strip(provenance)]
+    #[cfg_attr(feature = "proptest", proptest(value = "None"))]
+    pub cost: Option<Number>,
+
     /// A unique identifier for a node within a document
     #[cfg_attr(feature = "proptest", proptest(value = "Default::default()"))]
     #[serde(skip)]
@@ -10,12 +10,14 @@ use super::array_validator::ArrayValidator;
 use super::article::Article;
 use super::audio_object::AudioObject;
 use super::author_role::AuthorRole;
+use super::book::Book;
 use super::boolean::Boolean;
 use super::boolean_validator::BooleanValidator;
 use super::brand::Brand;
 use super::button::Button;
 use super::call_argument::CallArgument;
 use super::call_block::CallBlock;
+use super::chapter::Chapter;
 use super::chat::Chat;
 use super::chat_message::ChatMessage;
 use super::chat_message_group::ChatMessageGroup;
@@ -132,6 +134,7 @@ use super::table_cell::TableCell;
 use super::table_row::TableRow;
 use super::text::Text;
 use super::thematic_break::ThematicBreak;
+use super::thesis::Thesis;
 use super::thing::Thing;
 use super::time::Time;
 use super::time_validator::TimeValidator;
@@ -147,7 +150,7 @@ use super::walkthrough::Walkthrough;
 use super::walkthrough_step::WalkthroughStep;
 
 /// Union type for all types in this schema, including primitives and entities
-#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec)]
+#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
 #[serde(untagged, crate = "common::serde")]
 pub enum Node {
     #[default]
@@ -181,6 +184,8 @@ pub enum Node {
 
     AuthorRole(AuthorRole),
 
+    Book(Book),
+
     BooleanValidator(BooleanValidator),
 
     Brand(Brand),
@@ -191,6 +196,8 @@ pub enum Node {
 
     CallBlock(CallBlock),
 
+    Chapter(Chapter),
+
     Chat(Chat),
 
     ChatMessage(ChatMessage),
@@ -411,6 +418,8 @@ pub enum Node {
 
     ThematicBreak(ThematicBreak),
 
+    Thesis(Thesis),
+
     Thing(Thing),
 
     Time(Time),
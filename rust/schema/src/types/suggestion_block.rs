@@ -5,15 +5,17 @@ use crate::prelude::*;
 use super::author::Author;
 use super::block::Block;
 use super::duration::Duration;
+use super::number::Number;
 use super::provenance_count::ProvenanceCount;
 use super::string::String;
 use super::suggestion_status::SuggestionStatus;
 use super::timestamp::Timestamp;
+use super::unsigned_integer::UnsignedInteger;
 
 /// Abstract base type for nodes that indicate a suggested change to block content.
 #[skip_serializing_none]
 #[serde_as]
-#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, TextCodec)]
+#[derive(Debug, SmartDefault, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, TextCodec, TypstCodec)]
 #[serde(rename_all = "camelCase", crate = "common::serde")]
 #[derive(derive_more::Display)]
 #[display(fmt = "SuggestionBlock")]
@@ -69,6 +71,20 @@ pub struct SuggestionBlock {
     #[dom(elem = "div")]
     pub content: Vec<Block>,
 
+    /// The number of tokens in the prompt used to generate the suggestion.
+    #[serde(alias = "prompt-tokens", alias = "prompt_tokens")]
+    #[strip(provenance)]
+    pub prompt_tokens: Option<UnsignedInteger>,
+
+    /// The number of tokens generated for the suggestion.
+    #[serde(alias = "completion-tokens", alias = "completion_tokens")]
+    #[strip(provenance)]
+    pub completion_tokens: Option<UnsignedInteger>,
+
+    /// The estimated cost, in US dollars, of generating the suggestion.
+    #[strip(provenance)]
+    pub cost: Option<Number>,
+
     /// A unique identifier for a node within a document
     
     #[serde(skip)]
@@ -4,7 +4,9 @@ use crate::prelude::*;
 
 use super::article::Article;
 use super::audio_object::AudioObject;
+use super::book::Book;
 use super::brand::Brand;
+use super::chapter::Chapter;
 use super::chat::Chat;
 use super::claim::Claim;
 use super::collection::Collection;
@@ -33,10 +35,11 @@ use super::review::Review;
 use super::software_application::SoftwareApplication;
 use super::software_source_code::SoftwareSourceCode;
 use super::table::Table;
+use super::thesis::Thesis;
 use super::video_object::VideoObject;
 
 /// Union type for all types that are descended from `Thing`
-#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec)]
+#[derive(Debug, strum::Display, Clone, PartialEq, Serialize, Deserialize, StripNode, WalkNode, WriteNode, SmartDefault, ReadNode, PatchNode, DomCodec, HtmlCodec, JatsCodec, LatexCodec, MarkdownCodec, TextCodec, TypstCodec)]
 #[serde(untagged, crate = "common::serde")]
 pub enum ThingType {
     #[default]
@@ -44,8 +47,12 @@ pub enum ThingType {
 
     AudioObject(AudioObject),
 
+    Book(Book),
+
     Brand(Brand),
 
+    Chapter(Chapter),
+
     Chat(Chat),
 
     Claim(Claim),
@@ -102,5 +109,7 @@ pub enum ThingType {
 
     Table(Table),
 
+    Thesis(Thesis),
+
     VideoObject(VideoObject),
 }
@@ -0,0 +1,893 @@
+//! A headless REST/JSON API for document operations
+//!
+//! Unlike the routes in `documents`, which are designed to support browser
+//! based views of documents, these routes are intended for programmatic use
+//! (e.g. by CI services) and accept and return JSON. An OpenAPI description of
+//! the API is generated from the request and response types in this module and
+//! is served at `/~api/openapi.json`.
+
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{Schema, SchemaObject},
+    JsonSchema,
+};
+
+use codecs::{DecodeOptions, EncodeOptions};
+use common::{
+    serde::{Deserialize, Serialize},
+    serde_json::{self, json},
+};
+use document::{
+    schema::{Node, NodeId, NodeType, Patch},
+    Command, CommandNodes, CommandScope, CommandStatus, CommandWait, Document,
+};
+use format::Format;
+use node_execute::ExecuteOptions;
+use node_find::{find, find_all};
+
+use crate::{
+    documents::{doc_by_id, Documents},
+    errors::InternalError,
+    jobs::{self, JobInfo, JobRequest},
+    server::{ServerState, STENCILA_VERSION},
+};
+
+/// Create a router for API routes
+pub fn router() -> Router<ServerState> {
+    Router::new()
+        .route("/openapi.json", get(openapi_json))
+        .route("/convert", post(convert))
+        .route("/documents", post(create_document))
+        .route("/documents/{id}", get(get_document).delete(delete_document))
+        .route("/documents/{id}/nodes", get(find_nodes))
+        .route("/documents/{id}/nodes/{node_id}", get(get_node))
+        .route("/documents/{id}/outputs", get(get_outputs))
+        .route("/documents/{id}/patch", post(patch_document))
+        .route("/documents/{id}/execute", post(execute_document))
+        .route("/documents/{id}/interrupt", post(interrupt_document))
+        .route(
+            "/documents/{id}/commands/{command_id}",
+            get(get_command_status),
+        )
+        .merge(jobs::router())
+}
+
+/// A request to convert content from one format to another
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+struct ConvertRequest {
+    /// The content to convert
+    content: String,
+
+    /// The format of the content e.g. `md`, `jats`, `ipynb`
+    from: String,
+
+    /// The format to convert the content to
+    ///
+    /// Defaults to `json`.
+    to: Option<String>,
+
+    /// Whether to encode as a standalone document
+    standalone: Option<bool>,
+}
+
+/// The response to a conversion request
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+struct ConvertResponse {
+    /// The converted content
+    content: String,
+}
+
+/// A request to create a document from content
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+struct CreateRequest {
+    /// The content of the document
+    content: String,
+
+    /// The format of the content e.g. `md`, `smd`, `json`
+    format: String,
+}
+
+/// The response to a request which creates a document
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+struct CreateResponse {
+    /// The id of the created document
+    id: String,
+}
+
+/// Query parameters for getting a document
+#[derive(Deserialize, JsonSchema)]
+#[serde(crate = "common::serde")]
+struct GetQuery {
+    /// The format to return the document in
+    ///
+    /// Defaults to `json`.
+    format: Option<String>,
+}
+
+/// Query parameters for finding nodes within a document
+#[derive(Deserialize, JsonSchema)]
+#[serde(crate = "common::serde")]
+struct FindQuery {
+    /// A comma separated list of node types e.g. `CodeChunk,Figure`
+    r#type: String,
+}
+
+/// A request to execute a document, or nodes within it
+#[derive(Default, Deserialize, JsonSchema)]
+#[serde(default, rename_all = "camelCase", crate = "common::serde")]
+struct ExecuteRequest {
+    /// The ids of the nodes to execute
+    ///
+    /// If empty, the entire document is executed.
+    node_ids: Vec<String>,
+
+    /// The scope of execution when `nodeIds` are specified
+    #[schemars(schema_with = "object_schema")]
+    scope: Option<CommandScope>,
+
+    /// Options for execution
+    #[schemars(schema_with = "object_schema")]
+    options: ExecuteOptions,
+}
+
+/// The response to a request which sends a command to a document
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+struct CommandResponse {
+    /// The id of the command, used to poll for its status
+    command_id: u64,
+}
+
+/// The status of a command sent to a document
+#[derive(Serialize, JsonSchema)]
+#[serde(
+    tag = "status",
+    content = "error",
+    rename_all = "kebab-case",
+    crate = "common::serde"
+)]
+enum CommandStatusResponse {
+    Ignored,
+    Waiting,
+    Running,
+    Succeeded,
+    Failed(String),
+    Interrupted,
+}
+
+impl From<CommandStatus> for CommandStatusResponse {
+    fn from(status: CommandStatus) -> Self {
+        match status {
+            CommandStatus::Ignored => Self::Ignored,
+            CommandStatus::Waiting => Self::Waiting,
+            CommandStatus::Running => Self::Running,
+            CommandStatus::Succeeded => Self::Succeeded,
+            CommandStatus::Failed(error) => Self::Failed(error),
+            CommandStatus::Interrupted => Self::Interrupted,
+        }
+    }
+}
+
+/// The outputs of an executable node
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+struct OutputsEntry {
+    /// The id of the node
+    node_id: String,
+
+    /// The type of the node e.g. `CodeChunk`
+    node_type: String,
+
+    /// The outputs of the node
+    #[schemars(schema_with = "nodes_schema")]
+    outputs: Vec<Node>,
+}
+
+/// An error response
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "common::serde")]
+struct ErrorResponse {
+    /// A description of the error
+    error: String,
+}
+
+/// Create a `400 Bad Request` response with a JSON body
+pub(crate) fn bad_request(error: impl ToString) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+/// Create a `404 Not Found` response with a JSON body
+pub(crate) fn not_found(error: impl ToString) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: error.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+/// Get a document by id, or a `404 Not Found` response
+async fn document(docs: &Arc<Documents>, id: &str) -> Result<Arc<Document>, Response> {
+    doc_by_id(docs, id)
+        .await
+        .map_err(|_| not_found(format!("No document with id `{id}`")))
+}
+
+/// Handle a request for the OpenAPI description of the API
+async fn openapi_json() -> Json<serde_json::Value> {
+    Json(openapi())
+}
+
+/// Handle a request to convert content between formats
+async fn convert(Json(request): Json<ConvertRequest>) -> Result<Response, InternalError> {
+    let decode_options = DecodeOptions {
+        format: Some(Format::from_name(&request.from)),
+        ..Default::default()
+    };
+    let node = match codecs::from_str(&request.content, Some(decode_options)).await {
+        Ok(node) => node,
+        Err(error) => return Ok(bad_request(error)),
+    };
+
+    let encode_options = EncodeOptions {
+        format: Some(Format::from_name(request.to.as_deref().unwrap_or("json"))),
+        standalone: request.standalone,
+        ..Default::default()
+    };
+    let content = codecs::to_string(&node, Some(encode_options))
+        .await
+        .map_err(InternalError::new)?;
+
+    Ok(Json(ConvertResponse { content }).into_response())
+}
+
+/// Handle a request to create a document from content
+async fn create_document(
+    State(ServerState { dir, docs, .. }): State<ServerState>,
+    Json(request): Json<CreateRequest>,
+) -> Result<Response, InternalError> {
+    let doc = Document::init(dir, None, None).map_err(InternalError::new)?;
+
+    let options = DecodeOptions {
+        format: Some(Format::from_name(&request.format)),
+        ..Default::default()
+    };
+    if let Err(error) = doc.load(&request.content, Some(options), None).await {
+        return Ok(bad_request(error));
+    }
+
+    // Compile the document so that it is ready for execution and export
+    doc.compile(CommandWait::Yes)
+        .await
+        .map_err(InternalError::new)?;
+
+    let doc = docs.insert(doc).await.map_err(InternalError::new)?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateResponse {
+            id: doc.id().to_string(),
+        }),
+    )
+        .into_response())
+}
+
+/// Handle a request to get a document
+///
+/// Returns the root node of the document as JSON, or, if the `format` query
+/// parameter is supplied, the document encoded in that format.
+async fn get_document(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+    Query(query): Query<GetQuery>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let Some(format) = query
+        .format
+        .map(|format| Format::from_name(&format))
+        .filter(|format| !matches!(format, Format::Json))
+    else {
+        return Ok(Json(doc.root().await).into_response());
+    };
+
+    let content = doc.dump(format, None).await.map_err(InternalError::new)?;
+
+    Ok(Json(ConvertResponse { content }).into_response())
+}
+
+/// Handle a request to delete (close) a document
+async fn delete_document(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    docs.close(doc.id()).await.map_err(InternalError::new)?;
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Handle a request to find nodes of one or more types within a document
+async fn find_nodes(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+    Query(query): Query<FindQuery>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let mut node_types = Vec::new();
+    for name in query.r#type.split(',') {
+        match NodeType::from_str(name.trim()) {
+            Ok(node_type) => node_types.push(node_type),
+            Err(..) => return Ok(bad_request(format!("Unknown node type `{name}`"))),
+        }
+    }
+
+    let nodes = doc.inspect(|root| find_all(root, &node_types)).await;
+
+    Ok(Json(nodes).into_response())
+}
+
+/// Handle a request to get a node within a document by its id
+async fn get_node(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path((id, node_id)): Path<(String, String)>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let Ok(node_id) = NodeId::from_str(&node_id) else {
+        return Ok(bad_request(format!("Invalid node id `{node_id}`")));
+    };
+
+    match doc.inspect(|root| find(root, node_id.clone())).await {
+        Some(node) => Ok(Json(node).into_response()),
+        None => Ok(not_found(format!("No node with id `{node_id}`"))),
+    }
+}
+
+/// Handle a request to get the outputs of executable nodes within a document
+async fn get_outputs(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let outputs: Vec<OutputsEntry> = doc
+        .inspect(|root| {
+            find_all(root, &[NodeType::CodeChunk, NodeType::CodeExpression])
+                .into_iter()
+                .filter_map(|node| match node {
+                    Node::CodeChunk(chunk) => Some(OutputsEntry {
+                        node_id: chunk.node_id().to_string(),
+                        node_type: NodeType::CodeChunk.to_string(),
+                        outputs: chunk.outputs.unwrap_or_default(),
+                    }),
+                    Node::CodeExpression(expr) => Some(OutputsEntry {
+                        node_id: expr.node_id().to_string(),
+                        node_type: NodeType::CodeExpression.to_string(),
+                        outputs: expr.output.map(|output| vec![*output]).unwrap_or_default(),
+                    }),
+                    _ => None,
+                })
+                .collect()
+        })
+        .await;
+
+    Ok(Json(outputs).into_response())
+}
+
+/// Handle a request to apply a patch to a document
+async fn patch_document(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+    Json(patch): Json<Patch>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let command_id = docs
+        .command(&doc, Command::PatchNode(patch))
+        .await
+        .map_err(InternalError::new)?;
+
+    Ok(accepted(command_id))
+}
+
+/// Handle a request to execute a document, or nodes within it
+///
+/// Execution happens in the background. Use the returned command id to poll
+/// for the status of the execution.
+async fn execute_document(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+    Json(request): Json<ExecuteRequest>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let ExecuteRequest {
+        node_ids,
+        scope,
+        options,
+    } = request;
+
+    let command = if node_ids.is_empty() {
+        Command::ExecuteDocument(options)
+    } else {
+        let mut ids = Vec::with_capacity(node_ids.len());
+        for node_id in node_ids {
+            match NodeId::from_str(&node_id) {
+                Ok(node_id) => ids.push(node_id),
+                Err(..) => return Ok(bad_request(format!("Invalid node id `{node_id}`"))),
+            }
+        }
+        Command::ExecuteNodes((CommandNodes::new(ids, scope.unwrap_or_default()), options))
+    };
+
+    let command_id = docs
+        .command(&doc, command)
+        .await
+        .map_err(InternalError::new)?;
+
+    Ok(accepted(command_id))
+}
+
+/// Handle a request to interrupt execution of a document
+async fn interrupt_document(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    let command_id = docs
+        .command(&doc, Command::InterruptDocument)
+        .await
+        .map_err(InternalError::new)?;
+
+    Ok(accepted(command_id))
+}
+
+/// Create a `202 Accepted` response for a command
+fn accepted(command_id: u64) -> Response {
+    (StatusCode::ACCEPTED, Json(CommandResponse { command_id })).into_response()
+}
+
+/// Handle a request for the status of a command
+async fn get_command_status(
+    State(ServerState { docs, .. }): State<ServerState>,
+    Path((id, command_id)): Path<(String, u64)>,
+) -> Result<Response, InternalError> {
+    let doc = match document(&docs, &id).await {
+        Ok(doc) => doc,
+        Err(response) => return Ok(response),
+    };
+
+    match docs.command_status(doc.id(), command_id).await {
+        Some(status) => Ok(Json(CommandStatusResponse::from(status)).into_response()),
+        None => Ok(not_found(format!("No command with id `{command_id}`"))),
+    }
+}
+
+/// A JSON Schema for a Stencila Schema node
+///
+/// Rather than generating (very large) schemas for node types, refer
+/// to the published JSON Schema.
+fn node_schema(_gen: &mut SchemaGenerator) -> Schema {
+    Schema::Object(SchemaObject {
+        reference: Some("https://stencila.org/Node.schema.json".to_string()),
+        ..Default::default()
+    })
+}
+
+/// A JSON Schema for an array of Stencila Schema nodes
+fn nodes_schema(gen: &mut SchemaGenerator) -> Schema {
+    let items = node_schema(gen);
+    serde_json::from_value(json!({ "type": "array", "items": items })).unwrap_or(Schema::Bool(true))
+}
+
+/// A JSON Schema for types that are defined outside of this crate
+pub(crate) fn object_schema(_gen: &mut SchemaGenerator) -> Schema {
+    serde_json::from_value(json!({ "type": "object" })).unwrap_or(Schema::Bool(true))
+}
+
+/// Generate an OpenAPI description of the API
+pub(crate) fn openapi() -> serde_json::Value {
+    let mut gen = SchemaGenerator::new(SchemaSettings::openapi3());
+
+    let content = |schema: Schema| json!({ "application/json": { "schema": schema } });
+    let response = |description: &str, schema: Schema| json!({ "description": description, "content": content(schema) });
+
+    let error = gen.subschema_for::<ErrorResponse>();
+    let errors = json!({
+        "400": response("Bad request", error.clone()),
+        "404": response("Not found", error),
+    });
+
+    let id = json!({ "name": "id", "in": "path", "required": true, "schema": { "type": "string" }, "description": "The id of the document" });
+    let node_id = json!({ "name": "node_id", "in": "path", "required": true, "schema": { "type": "string" }, "description": "The id of the node" });
+    let command_id = json!({ "name": "command_id", "in": "path", "required": true, "schema": { "type": "integer" }, "description": "The id of the command" });
+    let format = json!({ "name": "format", "in": "query", "required": false, "schema": { "type": "string" }, "description": "The format to return the document in" });
+    let job_id = json!({ "name": "job_id", "in": "path", "required": true, "schema": { "type": "integer" }, "description": "The id of the job" });
+    let r#type = json!({ "name": "type", "in": "query", "required": true, "schema": { "type": "string" }, "description": "A comma separated list of node types" });
+
+    let node = node_schema(&mut gen);
+    let nodes = nodes_schema(&mut gen);
+    let command = gen.subschema_for::<CommandResponse>();
+
+    let paths = json!({
+        "/convert": {
+            "post": {
+                "summary": "Convert content from one format to another",
+                "requestBody": { "required": true, "content": content(gen.subschema_for::<ConvertRequest>()) },
+                "responses": { "200": response("The converted content", gen.subschema_for::<ConvertResponse>()) },
+            }
+        },
+        "/documents": {
+            "post": {
+                "summary": "Create a document from content",
+                "requestBody": { "required": true, "content": content(gen.subschema_for::<CreateRequest>()) },
+                "responses": { "201": response("The document was created", gen.subschema_for::<CreateResponse>()) },
+            }
+        },
+        "/documents/{id}": {
+            "get": {
+                "summary": "Get a document",
+                "parameters": [id, format],
+                "responses": { "200": response("The root node of the document", node.clone()), "404": errors["404"] },
+            },
+            "delete": {
+                "summary": "Close a document",
+                "parameters": [id],
+                "responses": { "204": { "description": "The document was closed" }, "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/nodes": {
+            "get": {
+                "summary": "Find nodes of one or more types within a document",
+                "parameters": [id, r#type],
+                "responses": { "200": response("The matching nodes", nodes.clone()), "400": errors["400"], "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/nodes/{node_id}": {
+            "get": {
+                "summary": "Get a node within a document",
+                "parameters": [id, node_id],
+                "responses": { "200": response("The node", node), "400": errors["400"], "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/outputs": {
+            "get": {
+                "summary": "Get the outputs of executable nodes within a document",
+                "parameters": [id],
+                "responses": { "200": response("The outputs of each executable node", gen.subschema_for::<Vec<OutputsEntry>>()), "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/patch": {
+            "post": {
+                "summary": "Apply a patch to a document",
+                "parameters": [id],
+                "requestBody": { "required": true, "content": content(object_schema(&mut gen)) },
+                "responses": { "202": response("The patch was accepted", command.clone()), "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/execute": {
+            "post": {
+                "summary": "Execute a document, or nodes within it",
+                "parameters": [id],
+                "requestBody": { "required": true, "content": content(gen.subschema_for::<ExecuteRequest>()) },
+                "responses": { "202": response("Execution was started", command.clone()), "400": errors["400"], "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/interrupt": {
+            "post": {
+                "summary": "Interrupt execution of a document",
+                "parameters": [id],
+                "responses": { "202": response("The interrupt was accepted", command), "404": errors["404"] },
+            }
+        },
+        "/documents/{id}/commands/{command_id}": {
+            "get": {
+                "summary": "Get the status of a command",
+                "parameters": [id, command_id],
+                "responses": { "200": response("The status of the command", gen.subschema_for::<CommandStatusResponse>()), "404": errors["404"] },
+            }
+        },
+        "/jobs": {
+            "get": {
+                "summary": "List execution jobs",
+                "responses": { "200": response("The jobs, in the order they were submitted", gen.subschema_for::<Vec<JobInfo>>()) },
+            },
+            "post": {
+                "summary": "Submit an execution job",
+                "requestBody": { "required": true, "content": content(gen.subschema_for::<JobRequest>()) },
+                "responses": { "202": response("The job was queued", gen.subschema_for::<JobInfo>()), "400": errors["400"], "404": errors["404"] },
+            }
+        },
+        "/jobs/{job_id}": {
+            "get": {
+                "summary": "Get an execution job",
+                "parameters": [job_id],
+                "responses": { "200": response("The job", gen.subschema_for::<JobInfo>()), "404": errors["404"] },
+            }
+        },
+        "/jobs/{job_id}/events": {
+            "get": {
+                "summary": "Stream the events of an execution job as Server-Sent Events",
+                "parameters": [job_id],
+                "responses": { "200": { "description": "A stream of job events", "content": { "text/event-stream": { "schema": gen.subschema_for::<jobs::JobEvent>() } } }, "404": errors["404"] },
+            }
+        },
+        "/jobs/{job_id}/cancel": {
+            "post": {
+                "summary": "Cancel an execution job",
+                "parameters": [job_id],
+                "responses": { "202": { "description": "Cancellation was requested" }, "404": errors["404"] },
+            }
+        },
+    });
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Stencila API",
+            "version": STENCILA_VERSION,
+        },
+        "servers": [{ "url": "/~api" }],
+        "paths": paths,
+        "components": {
+            "schemas": gen.take_definitions(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use common::{
+        eyre::{OptionExt, Result},
+        tokio::{
+            self,
+            time::{sleep, Duration},
+        },
+    };
+
+    use super::*;
+
+    /// Get the JSON body of a response
+    async fn body(response: Response) -> Result<serde_json::Value> {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Create a document with Markdown content and return its id
+    async fn create(state: &ServerState, content: &str) -> Result<String> {
+        let response = create_document(
+            State(state.clone()),
+            Json(CreateRequest {
+                content: content.to_string(),
+                format: "md".to_string(),
+            }),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let id = body(response).await?["id"]
+            .as_str()
+            .ok_or_eyre("no id")?
+            .to_string();
+        Ok(id)
+    }
+
+    /// Test converting content between formats
+    #[tokio::test]
+    async fn convert_content() -> Result<()> {
+        let response = convert(Json(ConvertRequest {
+            content: "# Heading".to_string(),
+            from: "md".to_string(),
+            to: Some("html".to_string()),
+            standalone: None,
+        }))
+        .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let content = body(response).await?["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        assert!(content.contains("<h1"));
+
+        Ok(())
+    }
+
+    /// Test creating, getting, and deleting a document
+    #[tokio::test]
+    async fn document_lifecycle() -> Result<()> {
+        let state = ServerState::default();
+
+        let id = create(&state, "Hello world").await?;
+
+        let response = get_document(
+            State(state.clone()),
+            Path(id.clone()),
+            Query(GetQuery {
+                format: Some("md".to_string()),
+            }),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body(response).await?["content"]
+            .as_str()
+            .map_or(false, |content| content.contains("Hello world")));
+
+        let response = delete_document(State(state.clone()), Path(id.clone())).await?;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = get_document(
+            State(state.clone()),
+            Path(id),
+            Query(GetQuery { format: None }),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    /// Test that requests for unknown documents, nodes and commands return `404 Not Found`
+    /// and that invalid arguments return `400 Bad Request`
+    #[tokio::test]
+    async fn not_found_and_bad_requests() -> Result<()> {
+        let state = ServerState::default();
+
+        let response = get_outputs(State(state.clone()), Path("doc_unknown".to_string())).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let id = create(&state, "Hello world").await?;
+
+        let response = get_command_status(State(state.clone()), Path((id.clone(), 999))).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = find_nodes(
+            State(state.clone()),
+            Path(id.clone()),
+            Query(FindQuery {
+                r#type: "NotANodeType".to_string(),
+            }),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = execute_document(
+            State(state.clone()),
+            Path(id),
+            Json(ExecuteRequest {
+                node_ids: vec!["not-a-node-id".to_string()],
+                ..Default::default()
+            }),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    /// Test executing a document and polling for the status of the command
+    #[tokio::test]
+    async fn execute_and_poll() -> Result<()> {
+        let state = ServerState::default();
+
+        let id = create(&state, "Hello world").await?;
+
+        let response = execute_document(
+            State(state.clone()),
+            Path(id.clone()),
+            Json(ExecuteRequest::default()),
+        )
+        .await?;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let command_id = body(response).await?["commandId"]
+            .as_u64()
+            .ok_or_eyre("no command id")?;
+
+        let mut status = serde_json::Value::Null;
+        for _ in 0..50 {
+            let response =
+                get_command_status(State(state.clone()), Path((id.clone(), command_id))).await?;
+            assert_eq!(response.status(), StatusCode::OK);
+            status = body(response).await?["status"].clone();
+            if status == "succeeded" {
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(status, "succeeded");
+
+        Ok(())
+    }
+
+    /// Test that the OpenAPI description includes all routes and component schemas
+    #[test]
+    fn openapi_paths() {
+        let openapi = openapi();
+
+        for path in [
+            "/convert",
+            "/documents",
+            "/documents/{id}",
+            "/documents/{id}/nodes",
+            "/documents/{id}/nodes/{node_id}",
+            "/documents/{id}/outputs",
+            "/documents/{id}/patch",
+            "/documents/{id}/execute",
+            "/documents/{id}/interrupt",
+            "/documents/{id}/commands/{command_id}",
+            "/jobs",
+            "/jobs/{job_id}",
+            "/jobs/{job_id}/events",
+            "/jobs/{job_id}/cancel",
+        ] {
+            assert!(
+                openapi["paths"].get(path).is_some(),
+                "missing path `{path}`"
+            );
+        }
+
+        for schema in [
+            "ConvertRequest",
+            "CreateRequest",
+            "ExecuteRequest",
+            "CommandStatusResponse",
+            "ErrorResponse",
+            "JobInfo",
+            "JobRequest",
+        ] {
+            assert!(
+                openapi["components"]["schemas"].get(schema).is_some(),
+                "missing schema `{schema}`"
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@ use std::{
     path::{Component, PathBuf},
     str::FromStr,
     sync::Arc,
-    time::UNIX_EPOCH,
+    time::{Duration, Instant, UNIX_EPOCH},
 };
 
 use axum::{
@@ -34,6 +34,7 @@ use common::{
         self,
         fs::read,
         sync::{
+            broadcast::error::RecvError,
             mpsc::{channel, Receiver, Sender},
             RwLock,
         },
@@ -41,7 +42,7 @@ use common::{
     tracing,
     uuid::Uuid,
 };
-use document::{Command, CommandWait, Document, DocumentId, SyncDirection};
+use document::{Command, CommandStatus, CommandWait, Document, DocumentId, SyncDirection};
 use format::Format;
 
 use crate::{
@@ -49,6 +50,16 @@ use crate::{
     server::{ServerState, STENCILA_VERSION},
 };
 
+/// The time for which the status of a finished command is kept
+///
+/// Statuses are kept for a while after a command finishes so that clients
+/// polling for the status can retrieve it, and then pruned so that they do
+/// not accumulate in long running servers.
+const STATUS_TTL: Duration = Duration::from_secs(600);
+
+/// The latest status of commands, and when it was last updated, keyed by document and command id
+type CommandStatuses = Arc<RwLock<HashMap<(Uuid, u64), (CommandStatus, Instant)>>>;
+
 /// A store of documents
 #[derive(Debug, Default)]
 pub(crate) struct Documents {
@@ -58,6 +69,10 @@ pub(crate) struct Documents {
 
     /// A mapping of document ids to [`Document`]s
     docs: RwLock<HashMap<Uuid, Arc<Document>>>,
+
+    /// The latest status of commands sent via [`Documents::command`], and
+    /// when it was last updated, keyed by document and command id
+    statuses: CommandStatuses,
 }
 
 impl Documents {
@@ -97,6 +112,18 @@ impl Documents {
         self.by_uuid(&uuid).await
     }
 
+    /// Add a document which is not associated with a path
+    ///
+    /// Used for documents created from content (e.g. uploaded via the API)
+    /// rather than opened from the file system.
+    pub async fn insert(&self, doc: Document) -> Result<Arc<Document>> {
+        let uuid = doc.id().uuid();
+
+        self.docs.write().await.insert(uuid, Arc::new(doc));
+
+        self.by_uuid(&uuid).await
+    }
+
     /// Get a document by [`DocumentId`]
     pub async fn by_id(&self, id: &DocumentId) -> Result<Arc<Document>> {
         self.by_uuid(&id.uuid()).await
@@ -127,8 +154,66 @@ impl Documents {
             .await
             .retain(|_, entry_uuid| entry_uuid != &uuid);
 
+        self.statuses
+            .write()
+            .await
+            .retain(|(doc_uuid, _), _| doc_uuid != &uuid);
+
         Ok(())
     }
+
+    /// Send a command to a document without waiting for it to finish
+    ///
+    /// Returns the id of the command which can be used with [`Documents::command_status`]
+    /// to poll for its status.
+    pub async fn command(&self, doc: &Document, command: Command) -> Result<u64> {
+        let uuid = doc.id().uuid();
+
+        let (command_id, mut status_receiver) = doc.command_subscribe(command).await?;
+        {
+            let mut statuses = self.statuses.write().await;
+            statuses.retain(|_, (status, updated)| {
+                !status.finished() || updated.elapsed() < STATUS_TTL
+            });
+            statuses.insert((uuid, command_id), (CommandStatus::Waiting, Instant::now()));
+        }
+
+        let statuses = self.statuses.clone();
+        tokio::spawn(async move {
+            loop {
+                let (id, status) = match status_receiver.recv().await {
+                    Ok(message) => message,
+                    // Missing statuses of other commands is fine, and a later status
+                    // of this command will supersede any missed
+                    Err(RecvError::Lagged(..)) => continue,
+                    Err(RecvError::Closed) => break,
+                };
+                if id != command_id {
+                    continue;
+                }
+
+                let finished = status.finished();
+                statuses
+                    .write()
+                    .await
+                    .insert((uuid, command_id), (status, Instant::now()));
+                if finished {
+                    break;
+                }
+            }
+        });
+
+        Ok(command_id)
+    }
+
+    /// Get the status of a command sent using [`Documents::command`]
+    pub async fn command_status(&self, id: &DocumentId, command_id: u64) -> Option<CommandStatus> {
+        self.statuses
+            .read()
+            .await
+            .get(&(id.uuid(), command_id))
+            .map(|(status, ..)| status.clone())
+    }
 }
 
 /// Resolve a URL path into a file or directory path
@@ -469,7 +554,7 @@ async fn open_document(
 }
 
 /// Parse a string as a `DocumentId` and return the corresponding document
-async fn doc_by_id(docs: &Arc<Documents>, id: &str) -> Result<Arc<Document>> {
+pub(crate) async fn doc_by_id(docs: &Arc<Documents>, id: &str) -> Result<Arc<Document>> {
     let id = DocumentId::from_str(id)?;
     docs.by_id(&id).await
 }
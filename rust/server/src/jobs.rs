@@ -0,0 +1,982 @@
+//! A queue of document execution jobs
+//!
+//! Rather than blocking a request until execution finishes (as happens when
+//! using `CommandWait::Yes`), execution commands can be submitted as jobs. Jobs
+//! are queued and run with a limit on the number that run concurrently across all
+//! documents. The status of each node executed by a job is tracked from the
+//! `executionStatus` patches that are applied to the document, and job events
+//! (node status changes and execution messages) can be streamed to clients as
+//! Server-Sent Events.
+//!
+//! Only a limited number of finished jobs, and of events for each job, are
+//! retained so that the memory used by the queue does not grow without bound.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    convert::Infallible,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Json, Router,
+};
+use schemars::JsonSchema;
+
+use common::{
+    chrono::{DateTime, Utc},
+    futures::{stream, Stream, StreamExt},
+    indexmap::IndexMap,
+    serde::{Deserialize, Serialize},
+    tokio::{
+        self,
+        sync::{
+            broadcast::{self, error::RecvError},
+            Notify, RwLock, Semaphore,
+        },
+    },
+    tracing,
+};
+use document::{
+    schema::{
+        ExecutionMessage, ExecutionStatus, NodeId, NodeProperty, Patch, PatchNode, PatchOp,
+        PatchSlot,
+    },
+    Command, CommandNodes, CommandScope, CommandStatus, CommandWait, Document,
+};
+use node_execute::ExecuteOptions;
+use node_find::find;
+use node_map::node_map;
+
+use crate::{
+    api::{bad_request, not_found},
+    documents::doc_by_id,
+    errors::InternalError,
+    server::ServerState,
+};
+
+/// The default maximum number of jobs that can run concurrently
+pub(crate) const DEFAULT_MAX_JOBS: usize = 4;
+
+/// The maximum number of finished jobs to retain
+const MAX_FINISHED_JOBS: usize = 100;
+
+/// The maximum number of events to retain for each job
+const MAX_EVENTS: usize = 10_000;
+
+/// A queue of execution jobs
+#[derive(Debug)]
+pub(crate) struct Jobs {
+    /// A counter used to create unique job ids
+    counter: AtomicU64,
+
+    /// The jobs, in the order they were submitted
+    jobs: RwLock<IndexMap<u64, Arc<Job>>>,
+
+    /// Permits limiting the number of jobs that run concurrently
+    permits: Arc<Semaphore>,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_JOBS)
+    }
+}
+
+impl Jobs {
+    /// Create a new job queue allowing `max_jobs` jobs to run concurrently
+    pub fn new(max_jobs: usize) -> Self {
+        Self {
+            counter: AtomicU64::new(1),
+            jobs: RwLock::default(),
+            permits: Arc::new(Semaphore::new(max_jobs.max(1))),
+        }
+    }
+
+    /// Submit an execution command for a document as a job
+    ///
+    /// The job is queued and started once a permit is available.
+    pub async fn submit(&self, doc: Arc<Document>, command: Command) -> Arc<Job> {
+        let id = self.counter.fetch_add(1, Ordering::SeqCst);
+        let job = Arc::new(Job::new(id, doc, command));
+
+        self.prune(MAX_FINISHED_JOBS).await;
+        self.jobs.write().await.insert(id, job.clone());
+
+        {
+            let job = job.clone();
+            let permits = self.permits.clone();
+            tokio::spawn(async move { job.run(permits).await });
+        }
+
+        job
+    }
+
+    /// Get a job by id
+    pub async fn get(&self, id: u64) -> Option<Arc<Job>> {
+        self.jobs.read().await.get(&id).cloned()
+    }
+
+    /// List all jobs
+    pub async fn list(&self) -> Vec<Arc<Job>> {
+        self.jobs.read().await.values().cloned().collect()
+    }
+
+    /// Remove the oldest finished jobs so that at most `keep` finished jobs are retained
+    async fn prune(&self, keep: usize) {
+        let mut jobs = self.jobs.write().await;
+
+        let mut finished = Vec::new();
+        for (id, job) in jobs.iter() {
+            if job.state.read().await.status.finished() {
+                finished.push(*id);
+            }
+        }
+
+        let excess = finished.len().saturating_sub(keep);
+        for id in finished.into_iter().take(excess) {
+            jobs.shift_remove(&id);
+        }
+    }
+}
+
+/// The status of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case", crate = "common::serde")]
+pub(crate) enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Has the job finished?
+    fn finished(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// An event during a job
+///
+/// Events are recorded so that they can be replayed to clients that
+/// subscribe after the job has started.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "camelCase", crate = "common::serde")]
+pub(crate) enum JobEvent {
+    /// The status of the job changed
+    #[serde(rename_all = "camelCase")]
+    Job { status: JobStatus, time: String },
+
+    /// The execution status of a node changed
+    #[serde(rename_all = "camelCase")]
+    Node {
+        node_id: String,
+        status: String,
+        time: String,
+    },
+
+    /// A node produced an execution message
+    #[serde(rename_all = "camelCase")]
+    Message {
+        node_id: String,
+        level: String,
+        message: String,
+        time: String,
+    },
+}
+
+impl JobEvent {
+    /// The name of the event when sent as a Server-Sent Event
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Job { .. } => "job",
+            Self::Node { .. } => "node",
+            Self::Message { .. } => "message",
+        }
+    }
+}
+
+/// The mutable state of a job
+#[derive(Debug)]
+struct JobState {
+    status: JobStatus,
+    started: Option<DateTime<Utc>>,
+    ended: Option<DateTime<Utc>>,
+    error: Option<String>,
+    nodes: IndexMap<NodeId, ExecutionStatus>,
+    events: VecDeque<JobEvent>,
+}
+
+/// An execution job
+#[derive(Debug)]
+pub(crate) struct Job {
+    /// The id of the job
+    id: u64,
+
+    /// The document being executed
+    doc: Arc<Document>,
+
+    /// The execution command
+    command: Command,
+
+    /// When the job was created
+    created: DateTime<Utc>,
+
+    /// The current state of the job
+    state: RwLock<JobState>,
+
+    /// A channel for sending events to subscribers
+    events: broadcast::Sender<JobEvent>,
+
+    /// Used to signal that the job should be cancelled
+    cancel: Notify,
+}
+
+impl Job {
+    fn new(id: u64, doc: Arc<Document>, command: Command) -> Self {
+        let (events, ..) = broadcast::channel(1024);
+
+        Self {
+            id,
+            doc,
+            command,
+            created: Utc::now(),
+            state: RwLock::new(JobState {
+                status: JobStatus::Queued,
+                started: None,
+                ended: None,
+                error: None,
+                nodes: IndexMap::new(),
+                events: VecDeque::new(),
+            }),
+            events,
+            cancel: Notify::new(),
+        }
+    }
+
+    /// Record an event and send it to any subscribers
+    ///
+    /// If the maximum number of events has been reached, the oldest event is dropped.
+    async fn event(&self, event: JobEvent) {
+        let mut state = self.state.write().await;
+        if state.events.len() >= MAX_EVENTS {
+            state.events.pop_front();
+        }
+        state.events.push_back(event.clone());
+        self.events.send(event).ok();
+    }
+
+    /// Update the status of the job
+    async fn status(&self, status: JobStatus, error: Option<String>) {
+        {
+            let mut state = self.state.write().await;
+            state.status = status;
+            if status == JobStatus::Running {
+                state.started = Some(Utc::now());
+            } else if status.finished() {
+                state.ended = Some(Utc::now());
+            }
+            if error.is_some() {
+                state.error = error;
+            }
+        }
+
+        self.event(JobEvent::Job {
+            status,
+            time: Utc::now().to_rfc3339(),
+        })
+        .await;
+    }
+
+    /// Request that the job be cancelled
+    ///
+    /// Queued jobs will not be started. Running jobs are interrupted.
+    pub fn cancel(&self) {
+        self.cancel.notify_one();
+    }
+
+    /// Run the job
+    #[tracing::instrument(skip_all, fields(job = self.id))]
+    async fn run(&self, permits: Arc<Semaphore>) {
+        // Wait for a permit, or for cancellation while queued
+        let permit = tokio::select! {
+            permit = permits.acquire_owned() => permit,
+            _ = self.cancel.notified() => {
+                self.status(JobStatus::Cancelled, None).await;
+                return;
+            }
+        };
+        let Ok(_permit) = permit else {
+            self.status(JobStatus::Failed, Some("Job queue closed".to_string()))
+                .await;
+            return;
+        };
+
+        // Get the ids of the nodes that may be executed by the command so that
+        // patches to other nodes (e.g. by other executions) are ignored
+        let scope = self.scope().await;
+
+        // Subscribe to patches before sending the command so that no
+        // status updates are missed
+        let mut patches = self.doc.subscribe_patches();
+        let mut patches_open = true;
+
+        let (command_id, mut statuses) =
+            match self.doc.command_subscribe(self.command.clone()).await {
+                Ok(subscription) => subscription,
+                Err(error) => {
+                    self.status(JobStatus::Failed, Some(error.to_string()))
+                        .await;
+                    return;
+                }
+            };
+        self.status(JobStatus::Running, None).await;
+
+        // Patches are only recorded once the command is running so that those
+        // from commands that were run before it are ignored
+        let mut running = false;
+        let mut cancelled = false;
+        loop {
+            tokio::select! {
+                result = patches.recv(), if patches_open => match result {
+                    Ok(patch) => if running {
+                        self.patch(patch, scope.as_ref()).await
+                    },
+                    Err(RecvError::Lagged(count)) => {
+                        tracing::warn!("Job lagged by {count} patches");
+                    }
+                    Err(RecvError::Closed) => patches_open = false,
+                },
+                result = statuses.recv() => {
+                    let finished = match result {
+                        Ok((id, CommandStatus::Running)) if id == command_id => {
+                            // Discard patches applied before the command started
+                            while patches.try_recv().is_ok() {}
+                            running = true;
+                            None
+                        }
+                        Ok((id, status)) if id == command_id && status.finished() => Some(status),
+                        Ok(..) => None,
+                        Err(RecvError::Lagged(count)) => {
+                            // The statuses of the command may have been missed, including
+                            // when it finished, so get its latest status from the document
+                            tracing::warn!("Job lagged by {count} command statuses");
+                            match self.doc.command_status(command_id) {
+                                Some(status) if status.finished() => Some(status),
+                                Some(CommandStatus::Running) => {
+                                    running = true;
+                                    None
+                                }
+                                _ => None,
+                            }
+                        }
+                        Err(RecvError::Closed) => {
+                            self.status(JobStatus::Failed, Some("Document closed".to_string())).await;
+                            break;
+                        }
+                    };
+
+                    if let Some(status) = finished {
+                        let (status, error) = match status {
+                            CommandStatus::Succeeded | CommandStatus::Ignored => (JobStatus::Succeeded, None),
+                            CommandStatus::Failed(error) => (JobStatus::Failed, Some(error)),
+                            _ => (JobStatus::Cancelled, None),
+                        };
+                        let status = if cancelled { JobStatus::Cancelled } else { status };
+
+                        // Process any patches applied before the command finished
+                        while let Ok(patch) = patches.try_recv() {
+                            self.patch(patch, scope.as_ref()).await;
+                        }
+
+                        self.status(status, error).await;
+                        break;
+                    }
+                }
+                _ = self.cancel.notified(), if !cancelled => {
+                    cancelled = true;
+
+                    let interrupt = match &self.command {
+                        Command::ExecuteNodes((nodes, ..)) => Command::InterruptNodes(nodes.clone()),
+                        _ => Command::InterruptDocument,
+                    };
+                    if let Err(error) = self.doc.command(interrupt, CommandWait::No).await {
+                        tracing::error!("While interrupting job: {error}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get the ids of the nodes that may be executed by the job's command
+    ///
+    /// For `ExecuteNodes` commands, these are the listed nodes and their descendants.
+    /// Returns `None` for commands that execute the entire document.
+    async fn scope(&self) -> Option<HashSet<NodeId>> {
+        let Command::ExecuteNodes((nodes, ..)) = &self.command else {
+            return None;
+        };
+
+        let node_ids = self
+            .doc
+            .inspect(|root| {
+                let mut node_ids: HashSet<NodeId> = nodes.node_ids().iter().cloned().collect();
+                for node_id in nodes.node_ids() {
+                    if let Some(node) = find(root, node_id.clone()) {
+                        node_ids.extend(node_map(&node).into_keys());
+                    }
+                }
+                node_ids
+            })
+            .await;
+
+        Some(node_ids)
+    }
+
+    /// Update node statuses and record events from a patch applied to the document
+    ///
+    /// Patches to nodes not within the `scope` of the job are ignored.
+    async fn patch(&self, patch: Patch, scope: Option<&HashSet<NodeId>>) {
+        let Some(node_id) = patch.node_id else {
+            return;
+        };
+
+        if scope.map_or(false, |scope| !scope.contains(&node_id)) {
+            return;
+        }
+
+        for (path, op) in patch.ops {
+            let PatchOp::Set(value) = op else {
+                continue;
+            };
+
+            if path.len() != 1 {
+                continue;
+            }
+
+            match path.front() {
+                Some(PatchSlot::Property(NodeProperty::ExecutionStatus)) => {
+                    let Ok(status) = ExecutionStatus::from_value(value) else {
+                        continue;
+                    };
+
+                    self.state
+                        .write()
+                        .await
+                        .nodes
+                        .insert(node_id.clone(), status.clone());
+
+                    self.event(JobEvent::Node {
+                        node_id: node_id.to_string(),
+                        status: status.to_string(),
+                        time: Utc::now().to_rfc3339(),
+                    })
+                    .await;
+                }
+                Some(PatchSlot::Property(NodeProperty::ExecutionMessages)) => {
+                    let Ok(messages) = Vec::<ExecutionMessage>::from_value(value) else {
+                        continue;
+                    };
+
+                    for message in messages {
+                        self.event(JobEvent::Message {
+                            node_id: node_id.to_string(),
+                            level: message.level.to_string(),
+                            message: message.message,
+                            time: Utc::now().to_rfc3339(),
+                        })
+                        .await;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Get information on the job
+    async fn info(&self) -> JobInfo {
+        let state = self.state.read().await;
+
+        let finished = state
+            .nodes
+            .values()
+            .filter(|status| {
+                !matches!(
+                    status,
+                    ExecutionStatus::Scheduled
+                        | ExecutionStatus::Pending
+                        | ExecutionStatus::Running
+                )
+            })
+            .count();
+
+        let (kind, node_ids) = match &self.command {
+            Command::ExecuteNodes((nodes, ..)) => ("execute-nodes", nodes.node_ids()),
+            _ => ("execute-document", &[][..]),
+        };
+
+        JobInfo {
+            id: self.id,
+            document_id: self.doc.id().to_string(),
+            kind: kind.to_string(),
+            node_ids: node_ids.iter().map(|id| id.to_string()).collect(),
+            status: state.status,
+            error: state.error.clone(),
+            created: self.created.to_rfc3339(),
+            started: state.started.map(|time| time.to_rfc3339()),
+            ended: state.ended.map(|time| time.to_rfc3339()),
+            progress: JobProgress {
+                total: state.nodes.len(),
+                finished,
+            },
+            nodes: state
+                .nodes
+                .iter()
+                .map(|(node_id, status)| JobNode {
+                    node_id: node_id.to_string(),
+                    status: status.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Get a stream of the job's events
+    ///
+    /// Replays past events and then streams new events until the job finishes.
+    async fn stream(&self) -> impl Stream<Item = JobEvent> {
+        // Take a read lock so that no events are recorded between taking
+        // the history and subscribing
+        let state = self.state.read().await;
+        let history = state.events.iter().cloned().collect::<Vec<_>>();
+        let finished = history
+            .iter()
+            .any(|event| matches!(event, JobEvent::Job { status, .. } if status.finished()));
+        let receiver = self.events.subscribe();
+        drop(state);
+
+        let live = stream::unfold(
+            (receiver, finished),
+            |(mut receiver, finished)| async move {
+                if finished {
+                    return None;
+                }
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => {
+                            let finished =
+                                matches!(&event, JobEvent::Job { status, .. } if status.finished());
+                            return Some((event, (receiver, finished)));
+                        }
+                        Err(RecvError::Lagged(..)) => continue,
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            },
+        );
+
+        stream::iter(history).chain(live)
+    }
+}
+
+/// Information on a job
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub(crate) struct JobInfo {
+    /// The id of the job
+    id: u64,
+
+    /// The id of the document being executed
+    document_id: String,
+
+    /// The kind of job: `execute-document` or `execute-nodes`
+    kind: String,
+
+    /// The ids of the nodes being executed (for `execute-nodes` jobs)
+    node_ids: Vec<String>,
+
+    /// The status of the job
+    status: JobStatus,
+
+    /// The error message if the job failed
+    error: Option<String>,
+
+    /// When the job was created
+    created: String,
+
+    /// When the job started running
+    started: Option<String>,
+
+    /// When the job ended
+    ended: Option<String>,
+
+    /// The progress of the job
+    progress: JobProgress,
+
+    /// The execution status of each node executed by the job
+    nodes: Vec<JobNode>,
+}
+
+/// The progress of a job
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub(crate) struct JobProgress {
+    /// The number of nodes that have been scheduled for execution
+    total: usize,
+
+    /// The number of nodes that have finished executing
+    finished: usize,
+}
+
+/// The execution status of a node in a job
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub(crate) struct JobNode {
+    /// The id of the node
+    node_id: String,
+
+    /// The execution status of the node
+    status: String,
+}
+
+/// A request to submit a job
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub(crate) struct JobRequest {
+    /// The id of the document to execute
+    document_id: String,
+
+    /// The ids of the nodes to execute
+    ///
+    /// If empty, the entire document is executed.
+    #[serde(default)]
+    node_ids: Vec<String>,
+
+    /// The scope of execution when `nodeIds` are specified
+    #[schemars(schema_with = "crate::api::object_schema")]
+    scope: Option<CommandScope>,
+
+    /// Options for execution
+    #[serde(default)]
+    #[schemars(schema_with = "crate::api::object_schema")]
+    options: ExecuteOptions,
+}
+
+/// Create a router for job routes
+pub fn router() -> Router<ServerState> {
+    Router::new()
+        .route("/jobs", get(list_jobs).post(submit_job))
+        .route("/jobs/{job_id}", get(get_job))
+        .route("/jobs/{job_id}/events", get(job_events))
+        .route("/jobs/{job_id}/cancel", post(cancel_job))
+}
+
+/// Handle a request to list jobs
+async fn list_jobs(State(ServerState { jobs, .. }): State<ServerState>) -> Json<Vec<JobInfo>> {
+    let mut infos = Vec::new();
+    for job in jobs.list().await {
+        infos.push(job.info().await);
+    }
+    Json(infos)
+}
+
+/// Handle a request to submit a job
+async fn submit_job(
+    State(ServerState { docs, jobs, .. }): State<ServerState>,
+    Json(request): Json<JobRequest>,
+) -> Result<Response, InternalError> {
+    let doc = match doc_by_id(&docs, &request.document_id).await {
+        Ok(doc) => doc,
+        Err(..) => {
+            return Ok(not_found(format!(
+                "No document with id `{}`",
+                request.document_id
+            )))
+        }
+    };
+
+    let command = if request.node_ids.is_empty() {
+        Command::ExecuteDocument(request.options)
+    } else {
+        let mut node_ids = Vec::with_capacity(request.node_ids.len());
+        for node_id in request.node_ids {
+            match NodeId::from_str(&node_id) {
+                Ok(node_id) => node_ids.push(node_id),
+                Err(..) => return Ok(bad_request(format!("Invalid node id `{node_id}`"))),
+            }
+        }
+        Command::ExecuteNodes((
+            CommandNodes::new(node_ids, request.scope.unwrap_or_default()),
+            request.options,
+        ))
+    };
+
+    let job = jobs.submit(doc, command).await;
+
+    Ok((StatusCode::ACCEPTED, Json(job.info().await)).into_response())
+}
+
+/// Get a job by id, or a `404 Not Found` response
+async fn job(jobs: &Jobs, job_id: u64) -> Result<Arc<Job>, Response> {
+    jobs.get(job_id)
+        .await
+        .ok_or_else(|| not_found(format!("No job with id `{job_id}`")))
+}
+
+/// Handle a request to get a job
+async fn get_job(
+    State(ServerState { jobs, .. }): State<ServerState>,
+    Path(job_id): Path<u64>,
+) -> Response {
+    match job(&jobs, job_id).await {
+        Ok(job) => Json(job.info().await).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Handle a request to stream the events of a job as Server-Sent Events
+async fn job_events(
+    State(ServerState { jobs, .. }): State<ServerState>,
+    Path(job_id): Path<u64>,
+) -> Response {
+    let job = match job(&jobs, job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+
+    let events = job.stream().await.map(|event| {
+        let sse = Event::default().event(event.name());
+        Ok::<_, Infallible>(sse.json_data(&event).unwrap_or_else(|error| {
+            tracing::error!("While serializing job event: {error}");
+            Event::default().comment("serialization error")
+        }))
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Handle a request to cancel a job
+async fn cancel_job(
+    State(ServerState { jobs, .. }): State<ServerState>,
+    Path(job_id): Path<u64>,
+) -> Response {
+    let job = match job(&jobs, job_id).await {
+        Ok(job) => job,
+        Err(response) => return response,
+    };
+
+    job.cancel();
+
+    StatusCode::ACCEPTED.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::to_bytes;
+    use codecs::{DecodeOptions, Format};
+    use common::{
+        eyre::{OptionExt, Result},
+        serde_json,
+        tokio::time::{sleep, Duration},
+    };
+
+    use super::*;
+
+    /// Get the JSON body of a response
+    async fn body(response: Response) -> Result<serde_json::Value> {
+        let bytes = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    /// Create a document with Markdown content and return its id
+    async fn create(state: &ServerState, content: &str) -> Result<String> {
+        let doc = Document::init(state.dir.clone(), None, None)?;
+        doc.load(
+            content,
+            Some(DecodeOptions {
+                format: Some(Format::Markdown),
+                ..Default::default()
+            }),
+            None,
+        )
+        .await?;
+        doc.compile(CommandWait::Yes).await?;
+
+        let doc = state.docs.insert(doc).await?;
+        Ok(doc.id().to_string())
+    }
+
+    /// Submit a job for a document
+    async fn submit(state: &ServerState, document_id: &str) -> Result<Response> {
+        Ok(submit_job(
+            State(state.clone()),
+            Json(JobRequest {
+                document_id: document_id.to_string(),
+                node_ids: Vec::new(),
+                scope: None,
+                options: ExecuteOptions::default(),
+            }),
+        )
+        .await?)
+    }
+
+    /// Test that requests for unknown documents and jobs return `404 Not Found`
+    #[tokio::test]
+    async fn not_found() -> Result<()> {
+        let state = ServerState::default();
+
+        let response = submit(&state, "doc_unknown").await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get_job(State(state.clone()), Path(999)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = cancel_job(State(state.clone()), Path(999)).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    /// Test submitting a job and polling until it finishes
+    #[tokio::test]
+    async fn submit_and_poll() -> Result<()> {
+        let state = ServerState::default();
+
+        let id = create(&state, "Hello world").await?;
+
+        let response = submit(&state, &id).await?;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let job_id = body(response).await?["id"]
+            .as_u64()
+            .ok_or_eyre("no job id")?;
+
+        let mut status = serde_json::Value::Null;
+        for _ in 0..50 {
+            let response = get_job(State(state.clone()), Path(job_id)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            status = body(response).await?["status"].clone();
+            if status == "succeeded" {
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(status, "succeeded");
+
+        let Json(jobs) = list_jobs(State(state.clone())).await;
+        assert_eq!(jobs.len(), 1);
+
+        let response = cancel_job(State(state.clone()), Path(job_id)).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        Ok(())
+    }
+
+    /// Test that the latest status of a command is available to jobs whose
+    /// status receiver has lagged
+    #[tokio::test]
+    async fn latest_command_status() -> Result<()> {
+        let state = ServerState::default();
+
+        let id = create(&state, "Hello world").await?;
+        let doc = doc_by_id(&state.docs, &id).await?;
+
+        let (command_id, ..) = doc
+            .command_subscribe(Command::ExecuteDocument(ExecuteOptions::default()))
+            .await?;
+
+        let mut status = Option::None;
+        for _ in 0..50 {
+            status = doc.command_status(command_id);
+            if status.as_ref().map_or(false, |status| status.finished()) {
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        assert!(matches!(status, Option::Some(CommandStatus::Succeeded)));
+        assert!(doc.command_status(command_id + 1).is_none());
+
+        Ok(())
+    }
+
+    /// Test that only patches to nodes within the scope of a job are recorded
+    #[tokio::test]
+    async fn patch_scope() -> Result<()> {
+        let state = ServerState::default();
+
+        let id = create(&state, "Hello world").await?;
+        let doc = doc_by_id(&state.docs, &id).await?;
+        let job = Job::new(1, doc, Command::ExecuteDocument(ExecuteOptions::default()));
+
+        let patch = |node_id: NodeId| -> Result<Patch> {
+            Ok(Patch {
+                node_id: Some(node_id),
+                ops: vec![(
+                    NodeProperty::ExecutionStatus.into(),
+                    PatchOp::Set(ExecutionStatus::Succeeded.to_value()?),
+                )],
+                ..Default::default()
+            })
+        };
+
+        let inside = NodeId::new(b"cdc", b"inside");
+        let outside = NodeId::new(b"cdc", b"outside");
+        let scope = HashSet::from([inside.clone()]);
+
+        job.patch(patch(inside.clone())?, Some(&scope)).await;
+        job.patch(patch(outside.clone())?, Some(&scope)).await;
+
+        let state = job.state.read().await;
+        assert!(state.nodes.contains_key(&inside));
+        assert!(!state.nodes.contains_key(&outside));
+        assert_eq!(state.events.len(), 1);
+
+        Ok(())
+    }
+
+    /// Test that the oldest finished jobs are pruned
+    #[tokio::test]
+    async fn prune_finished() -> Result<()> {
+        let state = ServerState::default();
+
+        let id = create(&state, "Hello world").await?;
+        let doc = doc_by_id(&state.docs, &id).await?;
+
+        let jobs = Jobs::default();
+        for id in 1..=3 {
+            let job = Arc::new(Job::new(
+                id,
+                doc.clone(),
+                Command::ExecuteDocument(ExecuteOptions::default()),
+            ));
+            if id < 3 {
+                job.status(JobStatus::Succeeded, None).await;
+            }
+            jobs.jobs.write().await.insert(id, job);
+        }
+
+        jobs.prune(1).await;
+
+        let ids = jobs
+            .list()
+            .await
+            .iter()
+            .map(|job| job.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![2, 3]);
+
+        Ok(())
+    }
+}
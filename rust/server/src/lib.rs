@@ -1,5 +1,7 @@
+mod api;
 mod documents;
 mod errors;
+mod jobs;
 mod login;
 mod server;
 mod statics;
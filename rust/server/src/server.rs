@@ -30,7 +30,9 @@ use document::SyncDirection;
 pub(crate) use version::STENCILA_VERSION;
 
 use crate::{
+    api,
     documents::{self, Documents},
+    jobs::{Jobs, DEFAULT_MAX_JOBS},
     login, statics,
 };
 
@@ -55,6 +57,9 @@ pub(crate) struct ServerState {
 
     /// The cache of documents
     pub docs: Arc<Documents>,
+
+    /// The queue of execution jobs
+    pub jobs: Arc<Jobs>,
 }
 
 /// Run the HTTP/Websocket server
@@ -109,6 +114,14 @@ pub struct ServeOptions {
     #[arg(long)]
     pub sync: Option<SyncDirection>,
 
+    /// The maximum number of execution jobs to run concurrently
+    ///
+    /// Applies across all documents. Additional jobs are queued until
+    /// a running job finishes.
+    #[arg(long, default_value_t = DEFAULT_MAX_JOBS)]
+    #[default(DEFAULT_MAX_JOBS)]
+    pub max_jobs: usize,
+
     /// The access token to use
     ///
     /// This is not a CLI argument. It is only passed to the `serve()` function
@@ -127,6 +140,7 @@ pub async fn serve(
         raw,
         source,
         sync,
+        max_jobs,
         access_token,
     }: ServeOptions,
 ) -> eyre::Result<()> {
@@ -152,12 +166,17 @@ pub async fn serve(
         raw,
         source,
         sync,
+        jobs: Arc::new(Jobs::new(max_jobs)),
         ..Default::default()
     };
 
     let router = Router::new()
         .nest("/~static", statics::router())
         .route("/~login", get(login::login))
+        .nest(
+            "/~api",
+            api::router().route_layer(middleware_fn(state.clone(), auth_middleware)),
+        )
         .nest(
             "/~documents",
             documents::router().route_layer(middleware_fn(state.clone(), auth_middleware)),
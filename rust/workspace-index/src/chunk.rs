@@ -0,0 +1,97 @@
+//! Splitting of documents into chunks of text for indexing
+
+use codec_text::to_text;
+use schema::{Block, NodeType, Visitor, WalkControl, WalkNode};
+
+/// The maximum number of characters in a section for it to be a single chunk
+///
+/// Sections longer than this are split into chunks for each of their paragraphs
+/// (and any nested sections shorter than this).
+pub const MAX_SECTION_CHARS: usize = 2000;
+
+/// The minimum number of characters in a paragraph for it to be a chunk
+///
+/// Avoids indexing short paragraphs (e.g. "See below:") which are unlikely
+/// to be useful passages by themselves.
+pub const MIN_PARAGRAPH_CHARS: usize = 40;
+
+/// Split a node into chunks of text at `Section` and `Paragraph` granularity
+pub fn chunks<T: WalkNode>(node: &T) -> Vec<(NodeType, String)> {
+    let mut chunker = Chunker::default();
+    chunker.visit(node);
+    chunker.chunks
+}
+
+/// A visitor that collects chunks of text
+#[derive(Default)]
+struct Chunker {
+    chunks: Vec<(NodeType, String)>,
+}
+
+impl Visitor for Chunker {
+    fn visit_block(&mut self, block: &Block) -> WalkControl {
+        match block {
+            Block::Section(section) => {
+                let text = to_text(section);
+                if text.chars().count() > MAX_SECTION_CHARS {
+                    return WalkControl::Continue;
+                }
+
+                if !text.trim().is_empty() {
+                    self.chunks
+                        .push((NodeType::Section, text.trim().to_string()));
+                }
+                WalkControl::Break
+            }
+            Block::Paragraph(paragraph) => {
+                let text = to_text(paragraph);
+                if text.trim().chars().count() >= MIN_PARAGRAPH_CHARS {
+                    self.chunks
+                        .push((NodeType::Paragraph, text.trim().to_string()));
+                }
+                WalkControl::Break
+            }
+            // Do not index suggestions because they have not been accepted into the document
+            Block::SuggestionBlock(..) => WalkControl::Break,
+            _ => WalkControl::Continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schema::shortcuts::{art, h1, p, sec, t};
+
+    use super::*;
+
+    #[test]
+    fn sections_and_paragraphs() {
+        let long = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. ".repeat(40);
+
+        let article = art([
+            p([t(
+                "A paragraph that is long enough to be a chunk by itself.",
+            )]),
+            p([t("Too short.")]),
+            sec([h1([t("Short")]), p([t("Short section.")])]),
+            sec([
+                h1([t("Long")]),
+                p([t(long.as_str())]),
+                p([t(long.as_str())]),
+            ]),
+        ]);
+
+        let chunks = chunks(&article);
+        let types: Vec<NodeType> = chunks.iter().map(|(node_type, ..)| *node_type).collect();
+        assert_eq!(
+            types,
+            vec![
+                NodeType::Paragraph,
+                NodeType::Section,
+                NodeType::Paragraph,
+                NodeType::Paragraph
+            ]
+        );
+        assert!(chunks[1].1.contains("Short section."));
+    }
+}
@@ -0,0 +1,173 @@
+//! Embeddings of text generated by a local model
+//!
+//! Embeddings are generated by an embedding model served by Ollama on the local
+//! machine (`nomic-embed-text` by default, or the model named in the
+//! `STENCILA_EMBEDDINGS_MODEL` environment variable). Unlike lexical vectors, embeddings
+//! capture the meaning of texts so that synonyms and paraphrases are matched. If Ollama,
+//! or the model, is not available then lexical vectors are used instead so that
+//! workspaces can still be indexed, and searched, without the model.
+
+use std::{env, thread};
+
+use common::{
+    eyre::{eyre, Result},
+    serde::{Deserialize, Serialize},
+    tokio::{self, sync::OnceCell},
+    tracing,
+};
+use ollama_rs::{
+    generation::embeddings::request::{EmbeddingsInput, GenerateEmbeddingsRequest},
+    Ollama,
+};
+
+use crate::lexical;
+
+/// The default embedding model
+const DEFAULT_MODEL: &str = "nomic-embed-text";
+
+/// The maximum number of texts in each request for embeddings
+const BATCH_SIZE: usize = 64;
+
+/// How texts are converted into vectors
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase", crate = "common::serde")]
+pub enum Vectorizer {
+    /// Lexical vectors (a hashed bag of words)
+    #[default]
+    Lexical,
+
+    /// Embeddings generated by a model, with the given name, served by Ollama
+    Ollama(String),
+}
+
+impl Vectorizer {
+    /// Get the vectorizer to use for indices
+    ///
+    /// Checks, once per process, whether the embedding model is available and
+    /// falls back to lexical vectors if it is not.
+    pub async fn current() -> Self {
+        static CURRENT: OnceCell<Vectorizer> = OnceCell::const_new();
+
+        CURRENT
+            .get_or_init(|| async {
+                let model = env::var("STENCILA_EMBEDDINGS_MODEL")
+                    .unwrap_or_else(|_| DEFAULT_MODEL.to_string());
+                match embed(&model, vec!["test".to_string()]).await {
+                    Ok(..) => Self::Ollama(model),
+                    Err(error) => {
+                        tracing::debug!(
+                            "Embedding model `{model}` not available, using lexical vectors: {error}"
+                        );
+                        Self::Lexical
+                    }
+                }
+            })
+            .await
+            .clone()
+    }
+
+    /// Vectorize texts into vectors of unit length
+    pub async fn vectorize(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        match self {
+            Self::Lexical => Ok(texts.iter().map(|text| lexical::vectorize(text)).collect()),
+            Self::Ollama(model) => {
+                let mut vectors = Vec::with_capacity(texts.len());
+                for batch in texts.chunks(BATCH_SIZE) {
+                    vectors.append(&mut embed(model, batch.to_vec()).await?);
+                }
+                Ok(vectors)
+            }
+        }
+    }
+
+    /// Vectorize a query, blocking until it is done
+    ///
+    /// Used when searching an index from synchronous code (e.g. prompt templates).
+    /// Embeddings are generated on a separate thread, with its own runtime, so that
+    /// this can be called from within an async context.
+    pub fn vectorize_blocking(&self, query: &str) -> Result<Vec<f32>> {
+        let Self::Ollama(..) = self else {
+            return Ok(lexical::vectorize(query));
+        };
+
+        let query = query.to_string();
+        let vectors = thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()?
+                        .block_on(self.vectorize(vec![query]))
+                })
+                .join()
+                .map_err(|_| eyre!("Thread generating embedding panicked"))?
+        })?;
+
+        vectors
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre!("No embedding generated for query"))
+    }
+}
+
+/// Generate embeddings for texts using an Ollama model
+///
+/// Embeddings are normalized to unit length so that their similarity can be
+/// calculated using [`lexical::similarity`].
+async fn embed(model: &str, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+    let count = texts.len();
+    let request =
+        GenerateEmbeddingsRequest::new(model.to_string(), EmbeddingsInput::Multiple(texts));
+    let response = Ollama::default()
+        .generate_embeddings(request)
+        .await
+        .map_err(|error| eyre!(error))?;
+
+    if response.embeddings.len() != count {
+        return Err(eyre!(
+            "Expected {count} embeddings from model `{model}` but got {}",
+            response.embeddings.len()
+        ));
+    }
+
+    Ok(response.embeddings.into_iter().map(normalize).collect())
+}
+
+/// Normalize a vector to unit length
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in vector.iter_mut() {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized() {
+        let vector = normalize(vec![3.0, 4.0]);
+        assert_eq!(vector, vec![0.6, 0.8]);
+
+        let vector = normalize(vec![0.0, 0.0]);
+        assert_eq!(vector, vec![0.0, 0.0]);
+    }
+
+    #[tokio::test]
+    async fn lexical_vectors() -> Result<()> {
+        let texts = vec!["Bacteria grow slowly.".to_string(), String::new()];
+        let vectors = Vectorizer::Lexical.vectorize(texts.clone()).await?;
+        assert_eq!(vectors.len(), 2);
+        assert_eq!(vectors[0], lexical::vectorize(&texts[0]));
+        assert_eq!(
+            Vectorizer::Lexical.vectorize_blocking(&texts[0])?,
+            vectors[0]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+//! Lexical vectors of text for similarity search
+//!
+//! Uses feature hashing of words, and pairs of adjacent words, into a fixed
+//! number of dimensions (i.e. a hashed bag of words). Unlike embeddings
+//! generated by a language model, these vectors only capture the words that
+//! texts have in common, not their meaning, so synonyms and paraphrases are not
+//! matched. However, they are fast, deterministic, and do not require a model to
+//! be downloaded, making it possible to index a workspace, and vectorize queries
+//! within prompts, synchronously and without network access.
+
+use std::hash::Hasher;
+
+use common::seahash::SeaHasher;
+
+/// The number of dimensions of vectors
+pub const DIMENSIONS: usize = 256;
+
+/// Words which are ignored because they carry little meaning
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has", "have", "in",
+    "is", "it", "its", "of", "on", "or", "that", "the", "this", "to", "was", "were", "which",
+    "with",
+];
+
+/// Vectorize text into a lexical vector of unit length
+///
+/// Returns a zero vector if the text has no words.
+pub fn vectorize(text: &str) -> Vec<f32> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect();
+
+    let mut counts = vec![0f32; DIMENSIONS];
+    let mut add = |feature: &str| {
+        let mut hasher = SeaHasher::new();
+        hasher.write(feature.as_bytes());
+        let hash = hasher.finish();
+
+        // Use the high bits of the hash for the index, and the lowest bit for the
+        // sign (to reduce the bias of collisions), so that they are independent
+        let index = ((hash >> 32) % DIMENSIONS as u64) as usize;
+        let sign = if hash & 1 == 0 { 1.0 } else { -1.0 };
+        counts[index] += sign;
+    };
+
+    for word in &words {
+        add(word);
+    }
+    for pair in words.windows(2) {
+        add(&[pair[0].as_str(), " ", pair[1].as_str()].concat());
+    }
+
+    // Dampen the weight of frequently occurring features
+    let mut vector: Vec<f32> = counts
+        .into_iter()
+        .map(|count| count.signum() * count.abs().ln_1p())
+        .collect();
+
+    let norm = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in vector.iter_mut() {
+            *value /= norm;
+        }
+    }
+
+    vector
+}
+
+/// Calculate the cosine similarity of two vectors
+///
+/// Because vectors are of unit length, this is their dot product.
+pub fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similar_texts_are_closer() {
+        let query = vectorize("growth of bacteria at high temperatures");
+        let related = vectorize("Bacteria grow slowly when temperatures are high.");
+        let unrelated = vectorize("The election results were announced on Tuesday.");
+
+        assert!(similarity(&query, &related) > similarity(&query, &unrelated));
+        assert!((similarity(&related, &related) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn empty_text() {
+        let vector = vectorize("a the, of!");
+        assert_eq!(vector.len(), DIMENSIONS);
+        assert!(vector.iter().all(|value| *value == 0.0));
+    }
+}
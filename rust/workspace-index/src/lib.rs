@@ -0,0 +1,538 @@
+//! An index of passages in the documents of a workspace
+//!
+//! Documents in a workspace are split into chunks of text at `Section` and
+//! `Paragraph` granularity (using their plain text representation) and each
+//! chunk is converted into a vector. When an embedding model is available locally
+//! (via Ollama) chunks are embedded so that passages are matched on their meaning.
+//! Otherwise, lexical vectors (a hashed bag of words) are used and passages are
+//! matched on the words they share with a query. The index is stored on disk, in the
+//! Stencila cache directory, and is refreshed incrementally so that only documents
+//! which have been modified since they were last indexed are decoded again.
+//!
+//! The index is used to provide prompts with passages from other documents in
+//! the workspace which are relevant to the current instruction.
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    hash::Hasher,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use codecs::{DecodeOptions, Format, LossesResponse};
+use common::{
+    eyre::Result,
+    once_cell::sync::Lazy,
+    seahash::SeaHasher,
+    serde::{Deserialize, Serialize},
+    serde_json,
+    tokio::{self, fs, sync::Mutex},
+    tracing,
+};
+use ignore::Walk;
+
+mod chunk;
+mod embed;
+mod lexical;
+
+pub use chunk::chunks;
+pub use embed::Vectorizer;
+pub use lexical::{similarity, vectorize, DIMENSIONS};
+
+/// The version of the index file format
+///
+/// Should be incremented whenever chunking or vectorization changes so that
+/// existing indices are rebuilt.
+const VERSION: u32 = 3;
+
+/// The maximum number of documents indexed in a workspace
+///
+/// Avoids indexing large numbers of documents when the workspace root
+/// could not be determined (e.g. a document in the user's home directory).
+const MAX_FILES: usize = 1000;
+
+/// The maximum size, in bytes, of documents indexed
+const MAX_FILE_SIZE: u64 = 5_000_000;
+
+/// Files which indicate the root of a workspace
+const ROOT_FILES: [&str; 4] = ["stencila.yaml", "stencila.yml", "stencila.toml", ".git"];
+
+/// Get the root directory of the workspace containing a directory
+///
+/// This is the closest ancestor of `dir` which contains a Stencila config
+/// file or a Git repository. Falls back to `dir` if there is no such ancestor.
+pub fn workspace_root(dir: &Path) -> PathBuf {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+
+    dir.ancestors()
+        .find(|ancestor| ROOT_FILES.iter().any(|name| ancestor.join(name).exists()))
+        .unwrap_or(&dir)
+        .to_path_buf()
+}
+
+/// Whether a file is a document that should be indexed
+fn is_indexable(path: &Path) -> bool {
+    let format = Format::from_path(path);
+    format.is_markdown_flavor() || matches!(format, Format::Ipynb | Format::Jats)
+}
+
+/// A chunk of text in a document, and its vector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct Chunk {
+    /// The type of node that the chunk was generated from e.g. `Paragraph`
+    pub node_type: String,
+
+    /// The text of the chunk
+    pub text: String,
+
+    /// The vector of the text
+    pub vector: Vec<f32>,
+}
+
+impl Chunk {
+    /// Create a new chunk, lexically vectorizing its text
+    pub fn new(node_type: &str, text: &str) -> Self {
+        Self {
+            node_type: node_type.to_string(),
+            text: text.to_string(),
+            vector: vectorize(text),
+        }
+    }
+}
+
+/// An indexed document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(crate = "common::serde")]
+struct IndexedFile {
+    /// The last modified time of the file, in seconds since the Unix epoch,
+    /// when it was indexed
+    modified: u64,
+
+    /// The chunks of the document
+    chunks: Vec<Chunk>,
+}
+
+/// A passage returned from a search of an index
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", crate = "common::serde")]
+pub struct Passage {
+    /// The path of the document, relative to the root of the workspace
+    pub path: String,
+
+    /// The type of node that the passage was generated from
+    pub node_type: String,
+
+    /// The text of the passage
+    pub text: String,
+
+    /// The similarity of the passage to the query
+    pub score: f32,
+}
+
+/// An index of the passages in the documents of a workspace
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(crate = "common::serde")]
+pub struct Index {
+    /// The version of the index format
+    version: u32,
+
+    /// The root directory of the workspace
+    root: PathBuf,
+
+    /// How the chunks, and queries, are vectorized
+    vectorizer: Vectorizer,
+
+    /// The indexed documents, keyed by their path relative to the root
+    files: BTreeMap<String, IndexedFile>,
+}
+
+impl Index {
+    /// Create a new, empty, index for a workspace using lexical vectors
+    pub fn new(root: &Path) -> Self {
+        Self::with_vectorizer(root, Vectorizer::Lexical)
+    }
+
+    /// Create a new, empty, index for a workspace using a vectorizer
+    pub fn with_vectorizer(root: &Path, vectorizer: Vectorizer) -> Self {
+        Self {
+            version: VERSION,
+            root: root.to_path_buf(),
+            vectorizer,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Get the path of the file that the index for a workspace is stored in
+    fn file(root: &Path) -> Result<PathBuf> {
+        let mut hasher = SeaHasher::new();
+        hasher.write(root.to_string_lossy().as_bytes());
+        let name = format!("{:016x}.json", hasher.finish());
+
+        Ok(app::get_app_dir(app::DirType::Cache, true)?
+            .join("indexes")
+            .join(name))
+    }
+
+    /// Load the index for a workspace from disk
+    ///
+    /// Returns an empty index if the index has not been stored yet, could not be
+    /// read, is of a previous version, or was vectorized differently to the
+    /// [`Vectorizer::current`] (e.g. the embedding model has become available).
+    pub async fn load(root: &Path) -> Result<Self> {
+        let vectorizer = Vectorizer::current().await;
+
+        let path = Self::file(root)?;
+        if !path.exists() {
+            return Ok(Self::with_vectorizer(root, vectorizer));
+        }
+
+        let json = fs::read_to_string(&path).await?;
+        match serde_json::from_str::<Self>(&json) {
+            Ok(index)
+                if index.version == VERSION
+                    && index.root == root
+                    && index.vectorizer == vectorizer =>
+            {
+                Ok(index)
+            }
+            Ok(..) => Ok(Self::with_vectorizer(root, vectorizer)),
+            Err(error) => {
+                tracing::warn!("While reading index `{}`: {error}", path.display());
+                Ok(Self::with_vectorizer(root, vectorizer))
+            }
+        }
+    }
+
+    /// Store the index on disk
+    pub async fn save(&self) -> Result<()> {
+        let path = Self::file(&self.root)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let json = serde_json::to_string(self)?;
+        fs::write(&path, json).await?;
+
+        Ok(())
+    }
+
+    /// Refresh the index
+    ///
+    /// Documents that have been modified since they were last indexed are decoded
+    /// and chunked again, and those that no longer exist are removed. Returns `true`
+    /// if the index was changed.
+    pub async fn refresh(&mut self) -> Result<bool> {
+        let mut changed = false;
+        let mut present = HashSet::new();
+
+        for entry in Walk::new(&self.root).flatten() {
+            let path = entry.path();
+            if !path.is_file() || !is_indexable(path) {
+                continue;
+            }
+
+            if present.len() >= MAX_FILES {
+                tracing::warn!(
+                    "Only indexing the first {MAX_FILES} documents in workspace `{}`",
+                    self.root.display()
+                );
+                break;
+            }
+
+            let Ok(relative) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().to_string();
+            present.insert(relative.clone());
+
+            let metadata = fs::metadata(path).await?;
+            if metadata.len() > MAX_FILE_SIZE {
+                continue;
+            }
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+
+            if self
+                .files
+                .get(&relative)
+                .map_or(false, |file| file.modified == modified)
+            {
+                continue;
+            }
+
+            let chunks = match Self::chunk_file(path, &self.vectorizer).await {
+                Ok(chunks) => chunks,
+                Err(error) => {
+                    tracing::debug!("While indexing `{}`: {error}", path.display());
+                    Vec::new()
+                }
+            };
+
+            tracing::trace!("Indexed {} chunks in `{relative}`", chunks.len());
+            self.insert(&relative, modified, chunks);
+            changed = true;
+        }
+
+        let before = self.files.len();
+        self.files.retain(|path, _| present.contains(path));
+        changed |= self.files.len() != before;
+
+        Ok(changed)
+    }
+
+    /// Decode a document, split it into chunks, and vectorize them
+    async fn chunk_file(path: &Path, vectorizer: &Vectorizer) -> Result<Vec<Chunk>> {
+        let node = codecs::from_path(
+            path,
+            Some(DecodeOptions {
+                losses: LossesResponse::Ignore,
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+        let (node_types, texts): (Vec<_>, Vec<_>) = chunks(&node).into_iter().unzip();
+        let vectors = vectorizer.vectorize(texts.clone()).await?;
+
+        Ok(node_types
+            .into_iter()
+            .zip(texts)
+            .zip(vectors)
+            .map(|((node_type, text), vector)| Chunk {
+                node_type: node_type.to_string(),
+                text,
+                vector,
+            })
+            .collect())
+    }
+
+    /// Insert, or replace, the chunks of a document into the index
+    pub fn insert(&mut self, path: &str, modified: u64, chunks: Vec<Chunk>) {
+        self.files
+            .insert(path.to_string(), IndexedFile { modified, chunks });
+    }
+
+    /// Get the path of a document relative to the root of the workspace
+    ///
+    /// Returns `None` if the document is not within the workspace.
+    pub fn relative_path(&self, path: &Path) -> Option<String> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        path.strip_prefix(&self.root)
+            .ok()
+            .map(|relative| relative.to_string_lossy().to_string())
+    }
+
+    /// Get the number of passages in the index
+    pub fn len(&self) -> usize {
+        self.files.values().map(|file| file.chunks.len()).sum()
+    }
+
+    /// Whether the index has no passages
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Search the index for the passages most similar to a query
+    ///
+    /// The query is vectorized in the same way as the chunks in the index. Returns
+    /// up to `count` passages, in descending order of similarity to the query,
+    /// excluding any that have no similarity. Passages from the `exclude` document
+    /// (e.g. the document the query is from), a path relative to the root of the
+    /// workspace, are not returned.
+    pub fn search(&self, query: &str, count: usize, exclude: Option<&str>) -> Vec<Passage> {
+        let query = match self.vectorizer.vectorize_blocking(query) {
+            Ok(query) => query,
+            Err(error) => {
+                tracing::warn!("While vectorizing search query: {error}");
+                return Vec::new();
+            }
+        };
+
+        let mut passages: Vec<Passage> = self
+            .files
+            .iter()
+            .filter(|(path, ..)| Some(path.as_str()) != exclude)
+            .flat_map(|(path, file)| {
+                file.chunks.iter().map(|chunk| Passage {
+                    path: path.clone(),
+                    node_type: chunk.node_type.clone(),
+                    text: chunk.text.clone(),
+                    score: similarity(&query, &chunk.vector),
+                })
+            })
+            .filter(|passage| passage.score > 0.0)
+            .collect();
+
+        passages.sort_by(|a, b| b.score.total_cmp(&a.score));
+        passages.truncate(count);
+        passages
+    }
+}
+
+/// The minimum interval between refreshes of an index
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An index which has been loaded, and the state of its refreshes
+struct Loaded {
+    /// The index, as at its last refresh
+    index: Arc<Index>,
+
+    /// When the index was last refreshed
+    refreshed: Option<Instant>,
+
+    /// Whether the index is currently being refreshed
+    refreshing: bool,
+}
+
+/// Indices which have been loaded, keyed by workspace root
+static INDICES: Lazy<Mutex<HashMap<PathBuf, Loaded>>> = Lazy::new(Mutex::default);
+
+/// Get the index for the workspace containing a directory
+///
+/// The index is loaded from disk if necessary. Because refreshing an index walks the
+/// workspace, and decodes modified documents, it is done in the background (at most
+/// once every [`REFRESH_INTERVAL`]) and the index as at its last refresh is returned.
+/// The exception is an index which has never been built, which is built before
+/// returning so that the first use of an index is not empty.
+pub async fn get(dir: &Path) -> Result<Arc<Index>> {
+    let root = workspace_root(dir);
+
+    // Only hold the lock while loading the index and deciding whether to refresh it
+    let (index, refresh) = {
+        let mut indices = INDICES.lock().await;
+
+        let loaded = match indices.entry(root.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(Loaded {
+                index: Arc::new(Index::load(&root).await?),
+                refreshed: None,
+                refreshing: false,
+            }),
+        };
+
+        let refresh = !loaded.refreshing
+            && loaded
+                .refreshed
+                .map_or(true, |refreshed| refreshed.elapsed() >= REFRESH_INTERVAL);
+        if refresh {
+            loaded.refreshing = true;
+        }
+
+        (loaded.index.clone(), refresh)
+    };
+
+    if !refresh {
+        return Ok(index);
+    }
+
+    if index.files.is_empty() {
+        return refresh_loaded(root, index).await;
+    }
+
+    tokio::spawn({
+        let index = index.clone();
+        async move {
+            if let Err(error) = refresh_loaded(root, index).await {
+                tracing::warn!("While refreshing workspace index: {error}");
+            }
+        }
+    });
+
+    Ok(index)
+}
+
+/// Refresh a loaded index and store it, on disk and in [`INDICES`], if it has changed
+async fn refresh_loaded(root: PathBuf, index: Arc<Index>) -> Result<Arc<Index>> {
+    let mut index = Arc::unwrap_or_clone(index);
+    let result = match index.refresh().await {
+        Ok(true) => index.save().await.map(|_| true),
+        result => result,
+    };
+
+    let mut indices = INDICES.lock().await;
+    let Some(loaded) = indices.get_mut(&root) else {
+        return Ok(Arc::new(index));
+    };
+
+    loaded.refreshing = false;
+    loaded.refreshed = Some(Instant::now());
+    if result? {
+        loaded.index = Arc::new(index);
+    }
+
+    Ok(loaded.index.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{tempfile::tempdir, tokio};
+
+    use super::*;
+
+    #[test]
+    fn search() {
+        let mut index = Index::new(Path::new("/workspace"));
+        index.insert(
+            "a.md",
+            0,
+            vec![
+                Chunk::new("Paragraph", "Bacteria grow slowly at high temperatures."),
+                Chunk::new(
+                    "Paragraph",
+                    "The election results were announced on Tuesday.",
+                ),
+            ],
+        );
+        index.insert(
+            "b.md",
+            0,
+            vec![Chunk::new(
+                "Section",
+                "Temperature and bacterial growth rates in soil samples.",
+            )],
+        );
+        assert_eq!(index.len(), 3);
+
+        let passages = index.search("effect of temperature on bacteria", 2, None);
+        assert_eq!(passages.len(), 2);
+        assert!(passages
+            .iter()
+            .all(|passage| !passage.text.contains("election")));
+        assert!(passages[0].score >= passages[1].score);
+
+        let passages = index.search("effect of temperature on bacteria", 2, Some("a.md"));
+        assert_eq!(passages.len(), 1);
+        assert_eq!(passages[0].path, "b.md");
+    }
+
+    #[tokio::test]
+    async fn refresh() -> Result<()> {
+        let dir = tempdir()?;
+        let root = dir.path().canonicalize()?;
+        let doc = root.join("doc.md");
+        fs::write(
+            &doc,
+            "Bacteria grow slowly when temperatures are high in soil.\n",
+        )
+        .await?;
+        fs::write(root.join("data.csv"), "a,b\n1,2\n").await?;
+
+        let mut index = Index::new(&root);
+        assert!(index.refresh().await?);
+        assert_eq!(index.len(), 1);
+        assert!(!index.refresh().await?);
+        assert_eq!(index.relative_path(&doc).as_deref(), Some("doc.md"));
+
+        fs::remove_file(&doc).await?;
+        assert!(index.refresh().await?);
+        assert!(index.is_empty());
+
+        Ok(())
+    }
+}
@@ -49,6 +49,8 @@ export function hydrate(value: types.Node): types.Node {
       return Object.setPrototypeOf(value, types.AudioObject.prototype);
     case "AuthorRole":
       return Object.setPrototypeOf(value, types.AuthorRole.prototype);
+    case "Book":
+      return Object.setPrototypeOf(value, types.Book.prototype);
     case "BooleanValidator":
       return Object.setPrototypeOf(value, types.BooleanValidator.prototype);
     case "Brand":
@@ -59,6 +61,8 @@ export function hydrate(value: types.Node): types.Node {
       return Object.setPrototypeOf(value, types.CallArgument.prototype);
     case "CallBlock":
       return Object.setPrototypeOf(value, types.CallBlock.prototype);
+    case "Chapter":
+      return Object.setPrototypeOf(value, types.Chapter.prototype);
     case "Chat":
       return Object.setPrototypeOf(value, types.Chat.prototype);
     case "ChatMessage":
@@ -299,6 +303,8 @@ export function hydrate(value: types.Node): types.Node {
       return Object.setPrototypeOf(value, types.Text.prototype);
     case "ThematicBreak":
       return Object.setPrototypeOf(value, types.ThematicBreak.prototype);
+    case "Thesis":
+      return Object.setPrototypeOf(value, types.Thesis.prototype);
     case "Thing":
       return Object.setPrototypeOf(value, types.Thing.prototype);
     case "Time":
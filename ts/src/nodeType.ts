@@ -16,11 +16,13 @@ export type NodeType =
   | "Article"
   | "AudioObject"
   | "AuthorRole"
+  | "Book"
   | "BooleanValidator"
   | "Brand"
   | "Button"
   | "CallArgument"
   | "CallBlock"
+  | "Chapter"
   | "Chat"
   | "ChatMessage"
   | "ChatMessageGroup"
@@ -131,6 +133,7 @@ export type NodeType =
   | "TableRow"
   | "Text"
   | "ThematicBreak"
+  | "Thesis"
   | "Thing"
   | "Time"
   | "TimeValidator"
@@ -161,11 +164,13 @@ export const NodeTypeList = [
   "Article",
   "AudioObject",
   "AuthorRole",
+  "Book",
   "BooleanValidator",
   "Brand",
   "Button",
   "CallArgument",
   "CallBlock",
+  "Chapter",
   "Chat",
   "ChatMessage",
   "ChatMessageGroup",
@@ -276,6 +281,7 @@ export const NodeTypeList = [
   "TableRow",
   "Text",
   "ThematicBreak",
+  "Thesis",
   "Thing",
   "Time",
   "TimeValidator",
@@ -0,0 +1,31 @@
+// Generated file; do not edit. See https://github.com/stencila/stencila/tree/main/rust/schema-gen
+
+import { CreativeWork } from "./CreativeWork.js";
+import { type Object } from "./Object.js";
+
+/**
+ * A book.
+ */
+export class Book extends CreativeWork {
+  // @ts-expect-error 'not assignable to the same property in base type'
+  type: "Book";
+
+  /**
+   * Additional metadata for the book.
+   */
+  extra?: Object;
+
+  constructor(options?: Partial<Book>) {
+    super();
+    this.type = "Book";
+    if (options) Object.assign(this, options);
+    
+  }
+}
+
+/**
+* Create a new `Book`
+*/
+export function book(options?: Partial<Book>): Book {
+  return new Book(options);
+}
@@ -0,0 +1,47 @@
+// Generated file; do not edit. See https://github.com/stencila/stencila/tree/main/rust/schema-gen
+
+import { CreativeWork } from "./CreativeWork.js";
+import { IntegerOrString } from "./IntegerOrString.js";
+import { type Object } from "./Object.js";
+
+/**
+ * A chapter, or other section, of a book.
+ */
+export class Chapter extends CreativeWork {
+  // @ts-expect-error 'not assignable to the same property in base type'
+  type: "Chapter";
+
+  /**
+   * The page on which the chapter starts; for example "135" or "xiii".
+   */
+  pageStart?: IntegerOrString;
+
+  /**
+   * The page on which the chapter ends; for example "138" or "xvi".
+   */
+  pageEnd?: IntegerOrString;
+
+  /**
+   * Any description of pages that is not separated into pageStart and pageEnd; for example, "1-6, 9, 55".
+   */
+  pagination?: string;
+
+  /**
+   * Additional metadata for the chapter.
+   */
+  extra?: Object;
+
+  constructor(options?: Partial<Chapter>) {
+    super();
+    this.type = "Chapter";
+    if (options) Object.assign(this, options);
+    
+  }
+}
+
+/**
+* Create a new `Chapter`
+*/
+export function chapter(options?: Partial<Chapter>): Chapter {
+  return new Chapter(options);
+}
@@ -43,6 +43,11 @@ export class CodeChunk extends CodeExecutable {
    */
   isInvisible?: boolean;
 
+  /**
+   * Whether the code of the code chunk should be hidden from the reader.
+   */
+  isCodeHidden?: boolean;
+
   /**
    * Whether the code should be treated as side-effect free when executed.
    */
@@ -1,6 +1,7 @@
 // Generated file; do not edit. See https://github.com/stencila/stencila/tree/main/rust/schema-gen
 
 import { Entity } from "./Entity.js";
+import { ExecutionBounds } from "./ExecutionBounds.js";
 
 /**
  * Stencila document configuration options.
@@ -14,6 +15,26 @@ export class Config extends Entity {
    */
   theme?: string;
 
+  /**
+   * The names of the kernels to use for executing code, in order of preference.
+   */
+  kernels?: string[];
+
+  /**
+   * The ids of the models to use for instructions, in order of preference.
+   */
+  models?: string[];
+
+  /**
+   * The default bounds on the execution of suggestions and other child nodes.
+   */
+  executionBounds?: ExecutionBounds;
+
+  /**
+   * The maximum cost, in US dollars, of model tasks when executing the document.
+   */
+  budget?: number;
+
   constructor(options?: Partial<Config>) {
     super();
     this.type = "Config";
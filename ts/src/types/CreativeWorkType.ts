@@ -4,6 +4,8 @@ import { hydrate } from "../hydrate.js";
 
 import { type Article } from "./Article.js";
 import { type AudioObject } from "./AudioObject.js";
+import { type Book } from "./Book.js";
+import { type Chapter } from "./Chapter.js";
 import { type Chat } from "./Chat.js";
 import { type Claim } from "./Claim.js";
 import { type Collection } from "./Collection.js";
@@ -20,6 +22,7 @@ import { type Review } from "./Review.js";
 import { type SoftwareApplication } from "./SoftwareApplication.js";
 import { type SoftwareSourceCode } from "./SoftwareSourceCode.js";
 import { type Table } from "./Table.js";
+import { type Thesis } from "./Thesis.js";
 import { type VideoObject } from "./VideoObject.js";
 
 /**
@@ -28,6 +31,8 @@ import { type VideoObject } from "./VideoObject.js";
 export type CreativeWorkType =
   Article |
   AudioObject |
+  Book |
+  Chapter |
   Chat |
   Claim |
   Collection |
@@ -44,6 +49,7 @@ export type CreativeWorkType =
   SoftwareApplication |
   SoftwareSourceCode |
   Table |
+  Thesis |
   VideoObject;
 
 /**
@@ -53,6 +59,8 @@ export function creativeWorkType(other: CreativeWorkType): CreativeWorkType {
   switch(other.type) {
     case "Article":
     case "AudioObject":
+    case "Book":
+    case "Chapter":
     case "Chat":
     case "Claim":
     case "Collection":
@@ -69,6 +77,7 @@ export function creativeWorkType(other: CreativeWorkType): CreativeWorkType {
     case "SoftwareApplication":
     case "SoftwareSourceCode":
     case "Table":
+    case "Thesis":
     case "VideoObject":
       return hydrate(other) as CreativeWorkType
     default:
@@ -10,11 +10,13 @@ import { type ArrayValidator } from "./ArrayValidator.js";
 import { type Article } from "./Article.js";
 import { type AudioObject } from "./AudioObject.js";
 import { type AuthorRole } from "./AuthorRole.js";
+import { type Book } from "./Book.js";
 import { type BooleanValidator } from "./BooleanValidator.js";
 import { type Brand } from "./Brand.js";
 import { type Button } from "./Button.js";
 import { type CallArgument } from "./CallArgument.js";
 import { type CallBlock } from "./CallBlock.js";
+import { type Chapter } from "./Chapter.js";
 import { type Chat } from "./Chat.js";
 import { type ChatMessage } from "./ChatMessage.js";
 import { type ChatMessageGroup } from "./ChatMessageGroup.js";
@@ -128,6 +130,7 @@ import { type TableCell } from "./TableCell.js";
 import { type TableRow } from "./TableRow.js";
 import { type Text } from "./Text.js";
 import { type ThematicBreak } from "./ThematicBreak.js";
+import { type Thesis } from "./Thesis.js";
 import { type Thing } from "./Thing.js";
 import { type Time } from "./Time.js";
 import { type TimeValidator } from "./TimeValidator.js";
@@ -161,11 +164,13 @@ export type Node =
   Article |
   AudioObject |
   AuthorRole |
+  Book |
   BooleanValidator |
   Brand |
   Button |
   CallArgument |
   CallBlock |
+  Chapter |
   Chat |
   ChatMessage |
   ChatMessageGroup |
@@ -276,6 +281,7 @@ export type Node =
   TableRow |
   Text |
   ThematicBreak |
+  Thesis |
   Thing |
   Time |
   TimeValidator |
@@ -305,11 +311,13 @@ export function node(other: Node): Node {
     case "Article":
     case "AudioObject":
     case "AuthorRole":
+    case "Book":
     case "BooleanValidator":
     case "Brand":
     case "Button":
     case "CallArgument":
     case "CallBlock":
+    case "Chapter":
     case "Chat":
     case "ChatMessage":
     case "ChatMessageGroup":
@@ -420,6 +428,7 @@ export function node(other: Node): Node {
     case "TableRow":
     case "Text":
     case "ThematicBreak":
+    case "Thesis":
     case "Thing":
     case "Time":
     case "TimeValidator":
@@ -2,6 +2,7 @@
 
 import { Block } from "./Block.js";
 import { Suggestion } from "./Suggestion.js";
+import { UnsignedInteger } from "./UnsignedInteger.js";
 
 /**
  * Abstract base type for nodes that indicate a suggested change to block content.
@@ -15,6 +16,21 @@ export class SuggestionBlock extends Suggestion {
    */
   content: Block[];
 
+  /**
+   * The number of tokens in the prompt used to generate the suggestion.
+   */
+  promptTokens?: UnsignedInteger;
+
+  /**
+   * The number of tokens generated for the suggestion.
+   */
+  completionTokens?: UnsignedInteger;
+
+  /**
+   * The estimated cost, in US dollars, of generating the suggestion.
+   */
+  cost?: number;
+
   constructor(content: Block[], options?: Partial<SuggestionBlock>) {
     super();
     this.type = "SuggestionBlock";
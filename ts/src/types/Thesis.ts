@@ -0,0 +1,31 @@
+// Generated file; do not edit. See https://github.com/stencila/stencila/tree/main/rust/schema-gen
+
+import { CreativeWork } from "./CreativeWork.js";
+import { type Object } from "./Object.js";
+
+/**
+ * A thesis or dissertation submitted in support of an academic degree.
+ */
+export class Thesis extends CreativeWork {
+  // @ts-expect-error 'not assignable to the same property in base type'
+  type: "Thesis";
+
+  /**
+   * Additional metadata for the thesis.
+   */
+  extra?: Object;
+
+  constructor(options?: Partial<Thesis>) {
+    super();
+    this.type = "Thesis";
+    if (options) Object.assign(this, options);
+    
+  }
+}
+
+/**
+* Create a new `Thesis`
+*/
+export function thesis(options?: Partial<Thesis>): Thesis {
+  return new Thesis(options);
+}
@@ -1,7 +1,9 @@
 // Generated file; do not edit. See https://github.com/stencila/stencila/tree/main/rust/schema-gen
 import { type Article } from "./Article.js";
 import { type AudioObject } from "./AudioObject.js";
+import { type Book } from "./Book.js";
 import { type Brand } from "./Brand.js";
+import { type Chapter } from "./Chapter.js";
 import { type Chat } from "./Chat.js";
 import { type Claim } from "./Claim.js";
 import { type Collection } from "./Collection.js";
@@ -30,6 +32,7 @@ import { type Review } from "./Review.js";
 import { type SoftwareApplication } from "./SoftwareApplication.js";
 import { type SoftwareSourceCode } from "./SoftwareSourceCode.js";
 import { type Table } from "./Table.js";
+import { type Thesis } from "./Thesis.js";
 import { type VideoObject } from "./VideoObject.js";
 
 /**
@@ -38,7 +41,9 @@ import { type VideoObject } from "./VideoObject.js";
 export type ThingType =
   Article |
   AudioObject |
+  Book |
   Brand |
+  Chapter |
   Chat |
   Claim |
   Collection |
@@ -67,6 +72,7 @@ export type ThingType =
   SoftwareApplication |
   SoftwareSourceCode |
   Table |
+  Thesis |
   VideoObject;
 
 
@@ -13,11 +13,13 @@ export * from "./AuthorRole.js";
 export * from "./AuthorRoleAuthor.js";
 export * from "./AuthorRoleName.js";
 export * from "./Block.js";
+export * from "./Book.js";
 export * from "./BooleanValidator.js";
 export * from "./Brand.js";
 export * from "./Button.js";
 export * from "./CallArgument.js";
 export * from "./CallBlock.js";
+export * from "./Chapter.js";
 export * from "./Chat.js";
 export * from "./ChatMessage.js";
 export * from "./ChatMessageGroup.js";
@@ -183,6 +185,7 @@ export * from "./TableRow.js";
 export * from "./TableRowType.js";
 export * from "./Text.js";
 export * from "./ThematicBreak.js";
+export * from "./Thesis.js";
 export * from "./Thing.js";
 export * from "./ThingType.js";
 export * from "./Time.js";